@@ -257,11 +257,11 @@ fn generate_grpc_client() {
     let verbose = env::var("VERBOSE_BUILD").unwrap_or_else(|_| "0".to_string()) == "1";
     let out_dir = "src/generated";
 
-    // Generate gRPC client for the main RPC service
+    // Generate gRPC clients for the main RPC service and the replication service
     if fs::metadata("proto/rpc.proto").is_ok() {
         match tonic_build::configure()
             .out_dir(out_dir)
-            .compile(&["proto/rpc.proto"], &["proto/"])
+            .compile(&["proto/rpc.proto", "proto/replication.proto"], &["proto/"])
         {
             Ok(()) => {
                 if verbose {
@@ -5,6 +5,14 @@
 -- NOTE: This script is IDEMPOTENT and safe to run multiple times.
 -- It uses CREATE TABLE IF NOT EXISTS to avoid dropping data.
 -- To drop existing data, use: snaprag reset --force
+--
+-- This is the baseline (version 0) of the versioned migrations. Later schema
+-- changes go in numbered NNN_name.up.sql / NNN_name.down.sql files and are
+-- applied with: snaprag migrate up
+-- migrate:no-transaction (CREATE INDEX CONCURRENTLY below)
+--
+-- Vector columns use an embedding_dimension placeholder in double braces, which
+-- is replaced with the configured embeddings.dimension before the script runs.
 
 -- Enable pgvector extension (requires superuser)
 -- If this fails, run on DB server: sudo -u postgres psql -d snaprag -c 'CREATE EXTENSION IF NOT EXISTS vector;'
@@ -63,9 +71,9 @@ SELECT
     MAX(CASE WHEN field_name = 'primary_address_ethereum' THEN field_value END) as primary_address_ethereum,
     MAX(CASE WHEN field_name = 'primary_address_solana' THEN field_value END) as primary_address_solana,
     NULL::varchar(255) as profile_token,
-    NULL::vector(384) as profile_embedding,
-    NULL::vector(384) as bio_embedding,
-    NULL::vector(384) as interests_embedding,
+    NULL::vector({{embedding_dimension}}) as profile_embedding,
+    NULL::vector({{embedding_dimension}}) as bio_embedding,
+    NULL::vector({{embedding_dimension}}) as interests_embedding,
     MAX(timestamp) as last_updated_timestamp,
     NOW() as last_updated_at,
     NULL::integer as shard_id,
@@ -77,9 +85,9 @@ GROUP BY fid;
 -- Profile Embeddings (separate table for UPDATE support)
 CREATE TABLE IF NOT EXISTS profile_embeddings (
     fid bigint PRIMARY KEY,
-    profile_embedding vector(384),
-    bio_embedding vector(384),
-    interests_embedding vector(384),
+    profile_embedding vector({{embedding_dimension}}),
+    bio_embedding vector({{embedding_dimension}}),
+    interests_embedding vector({{embedding_dimension}}),
     updated_at timestamp with time zone DEFAULT now()
 );
 
@@ -87,9 +95,9 @@ CREATE TABLE IF NOT EXISTS profile_embeddings (
 CREATE OR REPLACE VIEW user_profiles_with_embeddings AS
 SELECT 
     p.*,
-    COALESCE(e.profile_embedding, NULL::vector(384)) as profile_embedding_vec,
-    COALESCE(e.bio_embedding, NULL::vector(384)) as bio_embedding_vec,
-    COALESCE(e.interests_embedding, NULL::vector(384)) as interests_embedding_vec
+    COALESCE(e.profile_embedding, NULL::vector({{embedding_dimension}})) as profile_embedding_vec,
+    COALESCE(e.bio_embedding, NULL::vector({{embedding_dimension}})) as bio_embedding_vec,
+    COALESCE(e.interests_embedding, NULL::vector({{embedding_dimension}})) as interests_embedding_vec
 FROM user_profiles p
 LEFT JOIN profile_embeddings e ON p.fid = e.fid;
 
@@ -137,7 +145,9 @@ CREATE TABLE IF NOT EXISTS casts (
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     shard_id INTEGER,
     block_height BIGINT,
-    transaction_fid BIGINT
+    transaction_fid BIGINT,
+    parent_fid BIGINT,
+    parent_url TEXT
 );
 
 -- Add tracking columns if they don't exist
@@ -145,6 +155,26 @@ ALTER TABLE casts ADD COLUMN IF NOT EXISTS shard_id INTEGER;
 ALTER TABLE casts ADD COLUMN IF NOT EXISTS block_height BIGINT;
 ALTER TABLE casts ADD COLUMN IF NOT EXISTS transaction_fid BIGINT;
 
+-- Parent metadata: parent_fid for replies, parent_url for channel casts
+ALTER TABLE casts ADD COLUMN IF NOT EXISTS parent_fid BIGINT;
+ALTER TABLE casts ADD COLUMN IF NOT EXISTS parent_url TEXT;
+
+-- Cast removes (tombstones): casts stay append-only, readers exclude any cast
+-- with a matching (target_hash, fid) row here
+CREATE TABLE IF NOT EXISTS cast_removes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    fid BIGINT NOT NULL,
+    target_hash BYTEA NOT NULL,
+    timestamp BIGINT NOT NULL,
+    message_hash BYTEA UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    shard_id INTEGER,
+    block_height BIGINT,
+    transaction_fid BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_cast_removes_target ON cast_removes(target_hash, fid);
+
 CREATE TABLE IF NOT EXISTS links (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     fid BIGINT NOT NULL,
@@ -169,6 +199,23 @@ CREATE INDEX IF NOT EXISTS idx_links_latest ON links(fid, target_fid, timestamp
 CREATE INDEX IF NOT EXISTS idx_links_event_type ON links(event_type);
 CREATE INDEX IF NOT EXISTS idx_links_fid_type ON links(fid, link_type);
 
+-- Link compact states (LINK_COMPACT_STATE, type 14): the authoritative link set
+-- of an FID at a point in time. Applied to links as synthetic add/remove events.
+CREATE TABLE IF NOT EXISTS link_compact_states (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    fid BIGINT NOT NULL,
+    link_type VARCHAR(50) NOT NULL DEFAULT 'follow',
+    target_fids BIGINT[] NOT NULL,
+    timestamp BIGINT NOT NULL,
+    message_hash BYTEA UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    shard_id INTEGER,
+    block_height BIGINT,
+    transaction_fid BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_link_compact_states_fid ON link_compact_states(fid, link_type, timestamp DESC);
+
 CREATE TABLE IF NOT EXISTS user_data (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     fid BIGINT NOT NULL,
@@ -257,6 +304,43 @@ CREATE INDEX IF NOT EXISTS idx_username_proofs_fid ON username_proofs(fid);
 CREATE INDEX IF NOT EXISTS idx_username_proofs_username ON username_proofs(username);
 CREATE INDEX IF NOT EXISTS idx_username_proofs_timestamp ON username_proofs(timestamp DESC);
 
+-- Fname transfers (validator messages): ownership history of fnames
+CREATE TABLE IF NOT EXISTS fname_transfers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transfer_id BIGINT UNIQUE NOT NULL,
+    from_fid BIGINT NOT NULL,  -- 0 for a first registration
+    to_fid BIGINT NOT NULL,    -- 0 when the name is released
+    username TEXT NOT NULL,
+    owner BYTEA,
+    signature BYTEA,
+    timestamp BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    shard_id INTEGER,
+    block_height BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_fname_transfers_username ON fname_transfers(username, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_fname_transfers_from_fid ON fname_transfers(from_fid);
+CREATE INDEX IF NOT EXISTS idx_fname_transfers_to_fid ON fname_transfers(to_fid);
+
+-- Storage lends (LEND_STORAGE, type 15): storage units lent from fid to to_fid
+CREATE TABLE IF NOT EXISTS storage_lends (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    fid BIGINT NOT NULL,
+    to_fid BIGINT NOT NULL,
+    num_units BIGINT NOT NULL,
+    unit_type SMALLINT NOT NULL DEFAULT 0,  -- 0=legacy, 1=2024, 2=2025
+    timestamp BIGINT NOT NULL,
+    message_hash BYTEA UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    shard_id INTEGER,
+    block_height BIGINT,
+    transaction_fid BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_lends_fid ON storage_lends(fid, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_storage_lends_to_fid ON storage_lends(to_fid, timestamp DESC);
+
 CREATE TABLE IF NOT EXISTS frame_actions (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     fid BIGINT NOT NULL,
@@ -289,7 +373,7 @@ CREATE TABLE IF NOT EXISTS cast_embeddings (
     message_hash BYTEA UNIQUE NOT NULL,
     fid BIGINT NOT NULL,
     text TEXT NOT NULL,
-    embedding VECTOR(384),
+    embedding VECTOR({{embedding_dimension}}),
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
@@ -318,7 +402,7 @@ CREATE TABLE IF NOT EXISTS cast_embedding_chunks (
     chunk_index INTEGER NOT NULL,
     chunk_text TEXT NOT NULL,
     chunk_strategy VARCHAR(50) NOT NULL, -- 'paragraph', 'sentence', 'importance', 'sliding_window'
-    embedding VECTOR(384) NOT NULL,
+    embedding VECTOR({{embedding_dimension}}) NOT NULL,
     chunk_length INTEGER NOT NULL,
     is_aggregated BOOLEAN DEFAULT FALSE,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
@@ -332,7 +416,7 @@ CREATE TABLE IF NOT EXISTS cast_embedding_aggregated (
     message_hash BYTEA UNIQUE NOT NULL,
     fid BIGINT NOT NULL,
     text TEXT NOT NULL,
-    embedding VECTOR(384) NOT NULL,
+    embedding VECTOR({{embedding_dimension}}) NOT NULL,
     aggregation_strategy VARCHAR(50) NOT NULL, -- 'mean', 'max', 'weighted_mean', 'first_chunk'
     chunk_count INTEGER NOT NULL,
     total_text_length INTEGER NOT NULL,
@@ -368,6 +452,58 @@ CREATE INDEX IF NOT EXISTS idx_onchain_events_type
 CREATE INDEX IF NOT EXISTS idx_onchain_events_block 
     ON onchain_events(block_number DESC);
 
+-- Decoded on-chain state, maintained by the sync pipeline from SIGNER,
+-- ID_REGISTER and STORAGE_RENT events. last_block_number/last_log_index
+-- order updates so replays never roll state back.
+CREATE TABLE IF NOT EXISTS fid_signers (
+    fid BIGINT NOT NULL,
+    key BYTEA NOT NULL,             -- Ed25519 public key
+    key_type INTEGER NOT NULL DEFAULT 1,
+    metadata BYTEA,                 -- ABI-encoded SignedKeyRequest metadata
+    active BOOLEAN NOT NULL,
+    added_block BIGINT,
+    added_at BIGINT,                -- block timestamp (unix seconds)
+    removed_block BIGINT,
+    removed_at BIGINT,
+    removal_type SMALLINT,          -- 2=remove, 3=admin_reset
+    last_block_number BIGINT NOT NULL,
+    last_log_index INTEGER NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (fid, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_fid_signers_active ON fid_signers(fid) WHERE active;
+CREATE INDEX IF NOT EXISTS idx_fid_signers_key ON fid_signers(key);
+
+CREATE TABLE IF NOT EXISTS fid_registrations (
+    fid BIGINT PRIMARY KEY,
+    custody_address BYTEA,
+    recovery_address BYTEA,
+    registered_block BIGINT,
+    registered_at BIGINT,           -- block timestamp (unix seconds)
+    last_block_number BIGINT NOT NULL,
+    last_log_index INTEGER NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fid_registrations_custody ON fid_registrations(custody_address);
+
+CREATE TABLE IF NOT EXISTS storage_rents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    fid BIGINT NOT NULL,
+    payer BYTEA,
+    units BIGINT NOT NULL,
+    expiry BIGINT NOT NULL,         -- unix seconds
+    block_number BIGINT NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_timestamp BIGINT NOT NULL,
+    transaction_hash BYTEA,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(block_number, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_rents_fid ON storage_rents(fid, expiry DESC);
+
 -- ==============================================================================
 -- 6. SYNC TRACKING
 -- ==============================================================================
@@ -416,8 +552,8 @@ CREATE TABLE IF NOT EXISTS user_profile_trends (
     username_changes_count INTEGER DEFAULT 0,
     activity_score FLOAT DEFAULT 0.0,
     engagement_score FLOAT DEFAULT 0.0,
-    profile_embedding VECTOR(384),
-    bio_embedding VECTOR(384),
+    profile_embedding VECTOR({{embedding_dimension}}),
+    bio_embedding VECTOR({{embedding_dimension}}),
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     UNIQUE(fid, trend_period, trend_date)
 );
@@ -431,6 +567,15 @@ CREATE TABLE IF NOT EXISTS user_profile_trends (
 -- casts (essential only)
 CREATE INDEX IF NOT EXISTS idx_casts_fid ON casts(fid);
 
+-- Channel feeds and thread lookups
+CREATE INDEX IF NOT EXISTS idx_casts_parent_url_timestamp
+ON casts(parent_url, timestamp DESC) WHERE parent_url IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_casts_root_hash ON casts(root_hash);
+
+-- Replies whose root is not resolved yet (parent not synced); stays small
+CREATE INDEX IF NOT EXISTS idx_casts_unresolved_root
+ON casts(parent_hash) WHERE root_hash IS NULL AND parent_hash IS NOT NULL;
+
 -- processed_messages (for sync tracking)
 CREATE INDEX IF NOT EXISTS idx_processed_shard_height 
 ON processed_messages(shard_id, block_height DESC);
@@ -454,13 +599,20 @@ CREATE INDEX IF NOT EXISTS idx_cast_embedding_aggregated_message_hash ON cast_em
 CREATE INDEX IF NOT EXISTS idx_cast_embedding_aggregated_fid ON cast_embedding_aggregated(fid);
 
 -- Vector similarity search indexes for multi-vector tables
-CREATE INDEX IF NOT EXISTS idx_cast_embedding_chunks_embedding_cosine 
-ON cast_embedding_chunks USING ivfflat (embedding vector_cosine_ops) 
-WITH (lists = 100);
-
-CREATE INDEX IF NOT EXISTS idx_cast_embedding_aggregated_embedding_cosine 
-ON cast_embedding_aggregated USING ivfflat (embedding vector_cosine_ops) 
-WITH (lists = 100);
+-- (pgvector cannot index vectors with more than 2000 dimensions)
+DO $$
+BEGIN
+    IF {{embedding_dimension}} <= 2000 THEN
+        CREATE INDEX IF NOT EXISTS idx_cast_embedding_chunks_embedding_cosine
+        ON cast_embedding_chunks USING ivfflat (embedding vector_cosine_ops)
+        WITH (lists = 100);
+
+        CREATE INDEX IF NOT EXISTS idx_cast_embedding_aggregated_embedding_cosine
+        ON cast_embedding_aggregated USING ivfflat (embedding vector_cosine_ops)
+        WITH (lists = 100);
+    END IF;
+END
+$$;
 
 -- Optimize queries for cast embeddings backfill
 CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_casts_text_hash 
@@ -539,6 +691,10 @@ ALTER TABLE user_profile_changes SET (
     autovacuum_enabled = false
 );
 
+ALTER TABLE cast_removes SET (
+    autovacuum_enabled = false
+);
+
 ALTER TABLE links SET (
     autovacuum_enabled = false
 );
@@ -0,0 +1,23 @@
+-- Drop side-by-side embedding columns of versions > 1, then the registry.
+-- Casts embedded only by a later version are removed so the original
+-- NOT NULL constraint on cast_embeddings.embedding can be restored.
+
+DO $$
+DECLARE
+    col RECORD;
+BEGIN
+    FOR col IN
+        SELECT table_name, column_name
+        FROM information_schema.columns
+        WHERE table_schema = current_schema()
+          AND table_name IN ('cast_embeddings', 'profile_embeddings')
+          AND column_name ~ '_v[0-9]+$'
+    LOOP
+        EXECUTE format('ALTER TABLE %I DROP COLUMN IF EXISTS %I', col.table_name, col.column_name);
+    END LOOP;
+END $$;
+
+DROP TABLE IF EXISTS embedding_versions;
+
+DELETE FROM cast_embeddings WHERE embedding IS NULL;
+ALTER TABLE cast_embeddings ALTER COLUMN embedding SET NOT NULL;
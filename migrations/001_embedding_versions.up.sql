@@ -0,0 +1,27 @@
+-- Embedding model registry
+--
+-- Every stored vector belongs to an embedding version (model + dimension).
+-- Version 1 lives in the original columns (cast_embeddings.embedding,
+-- profile_embeddings.*_embedding); later versions get side-by-side columns
+-- suffixed with _v<version>, added when the version is registered.
+--
+-- At most one version is 'active' (served by search) and at most one is
+-- 'shadow' (being backfilled) at any time.
+
+CREATE TABLE IF NOT EXISTS embedding_versions (
+    version INTEGER PRIMARY KEY CHECK (version > 0),
+    model TEXT NOT NULL,
+    dimension INTEGER NOT NULL CHECK (dimension > 0),
+    state TEXT NOT NULL CHECK (state IN ('active', 'shadow', 'retired')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    activated_at TIMESTAMPTZ,
+    retired_at TIMESTAMPTZ,
+    pruned_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_embedding_versions_live_state
+    ON embedding_versions(state)
+    WHERE state IN ('active', 'shadow');
+
+-- Rows written after a cutover only carry the active version's vector
+ALTER TABLE cast_embeddings ALTER COLUMN embedding DROP NOT NULL;
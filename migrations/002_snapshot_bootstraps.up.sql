@@ -0,0 +1,18 @@
+-- Snapshot bootstrap progress
+--
+-- `snaprag sync bootstrap` loads a shard's replication snapshot page by page.
+-- The cursor (virtual trie shard + page token) is saved after every page so
+-- an interrupted bootstrap resumes where it stopped. Block sync takes over
+-- from height + 1 once completed_at is set.
+
+CREATE TABLE IF NOT EXISTS snapshot_bootstraps (
+    shard_id INTEGER PRIMARY KEY,
+    height BIGINT NOT NULL,
+    snapshot_timestamp BIGINT NOT NULL,
+    virtual_shard INTEGER NOT NULL DEFAULT 0,
+    page_token TEXT,
+    entries_applied BIGINT NOT NULL DEFAULT 0,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
@@ -0,0 +1,21 @@
+-- Messages that failed hash / signature verification during sync
+--
+-- With `message_verification = "reject"` only the hash, author and reason
+-- are kept; "quarantine" also stores the full message so it can be
+-- inspected or re-ingested later.
+
+CREATE TABLE IF NOT EXISTS rejected_messages (
+    id BIGSERIAL PRIMARY KEY,
+    message_hash BYTEA NOT NULL UNIQUE,
+    fid BIGINT NOT NULL,
+    message_type INTEGER NOT NULL,
+    signer BYTEA NOT NULL,
+    reason VARCHAR(64) NOT NULL,        -- hash_mismatch, invalid_signature, inactive_signer, ...
+    message JSONB,                      -- quarantine mode only
+    shard_id INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    rejected_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rejected_messages_fid ON rejected_messages(fid);
+CREATE INDEX IF NOT EXISTS idx_rejected_messages_reason ON rejected_messages(reason);
@@ -0,0 +1,23 @@
+-- Ledger of processed block heights
+--
+-- Each row is an inclusive range of heights whose chunks were written for a
+-- shard; overlapping and adjacent ranges are merged on insert. Heights
+-- between two ranges were skipped by sync and are reported by
+-- `snaprag sync verify` / refetched by `snaprag sync repair`.
+
+CREATE TABLE IF NOT EXISTS processed_blocks (
+    shard_id INTEGER NOT NULL,
+    start_height BIGINT NOT NULL,
+    end_height BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (shard_id, start_height),
+    CHECK (end_height >= start_height)
+);
+
+-- Blocks synced before the ledger existed cannot be told apart; treat
+-- everything below the saved progress (the height sync resumes at) as processed
+INSERT INTO processed_blocks (shard_id, start_height, end_height)
+SELECT shard_id, 0, last_processed_height - 1
+FROM sync_progress
+WHERE last_processed_height > 0
+ON CONFLICT (shard_id, start_height) DO NOTHING;
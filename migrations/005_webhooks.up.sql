@@ -0,0 +1,58 @@
+-- Change-data-capture webhooks
+--
+-- Sync records every committed message that matches an active subscription
+-- in `webhook_events`; its id is the offset subscribers replay from. Pending
+-- deliveries are queued per subscription and event, retried with backoff and
+-- moved to `webhook_dead_letters` once the attempts are exhausted.
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    -- HMAC-SHA256 key of the delivery signature
+    secret TEXT NOT NULL,
+    -- Filters; an empty list / NULL matches everything
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    fid BIGINT,
+    target_fid BIGINT,
+    parent_hash BYTEA,
+    channel TEXT,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Highest event offset delivered successfully
+    last_delivered_offset BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS webhook_events (
+    id BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    fid BIGINT NOT NULL,
+    target_fid BIGINT,
+    message_hash BYTEA NOT NULL UNIQUE,
+    parent_hash BYTEA,
+    channel TEXT,
+    timestamp BIGINT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    subscription_id BIGINT NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    event_id BIGINT NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subscription_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at);
+
+CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+    subscription_id BIGINT NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    event_id BIGINT NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subscription_id, event_id)
+);
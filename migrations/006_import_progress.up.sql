@@ -0,0 +1,14 @@
+-- Progress of `snaprag import`
+--
+-- One row per table of an archive, keyed by the archive's id. Rows are loaded
+-- in batches and `rows_loaded` advances in the same transaction as each
+-- batch, so an interrupted import resumes after the last committed batch.
+
+CREATE TABLE IF NOT EXISTS import_progress (
+    archive_id UUID NOT NULL,
+    table_name TEXT NOT NULL,
+    rows_loaded BIGINT NOT NULL DEFAULT 0,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (archive_id, table_name)
+);
@@ -0,0 +1,16 @@
+-- Hashes of processed blocks
+--
+-- Sync records the header hash and parent hash of every chunk it applies, in
+-- the same transaction as the chunk's rows. A chunk whose parent hash does
+-- not match the stored block below it (or a stored block replaced by another
+-- hash) marks a divergence: rows from the fork height up are rolled back by
+-- their `shard_id` / `block_height` and the canonical chunks are re-applied.
+
+CREATE TABLE IF NOT EXISTS block_hashes (
+    shard_id INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    block_hash BYTEA NOT NULL,
+    parent_hash BYTEA NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (shard_id, block_height)
+);
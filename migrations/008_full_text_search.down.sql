@@ -0,0 +1,5 @@
+-- migrate:no-transaction
+DROP INDEX CONCURRENTLY IF EXISTS idx_user_profile_changes_field_tsv;
+ALTER TABLE user_profile_changes DROP COLUMN IF EXISTS field_tsv;
+DROP INDEX CONCURRENTLY IF EXISTS idx_casts_text_tsv;
+ALTER TABLE casts DROP COLUMN IF EXISTS text_tsv;
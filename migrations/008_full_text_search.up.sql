@@ -0,0 +1,31 @@
+-- migrate:no-transaction
+-- Full-text search columns for keyword retrieval
+--
+-- Cast text and profile usernames, display names and bios get generated
+-- `tsvector` columns with GIN indexes. Keyword search parses queries with
+-- `websearch_to_tsquery` and orders matches by `ts_rank_cd`. Cast text and
+-- bios use the `english` configuration (stemming, stop words); usernames and
+-- display names use `simple` so handles are matched as written.
+--
+-- Adding a stored generated column rewrites the table, which takes a while on
+-- a fully synced `casts` table.
+
+ALTER TABLE casts ADD COLUMN IF NOT EXISTS text_tsv tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', COALESCE(text, ''))) STORED;
+
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_casts_text_tsv
+    ON casts USING gin(text_tsv);
+
+-- Names rank above bios: weight A for names, B for bios
+ALTER TABLE user_profile_changes ADD COLUMN IF NOT EXISTS field_tsv tsvector
+    GENERATED ALWAYS AS (
+        CASE field_name
+            WHEN 'username' THEN setweight(to_tsvector('simple', COALESCE(field_value, '')), 'A')
+            WHEN 'display_name' THEN setweight(to_tsvector('simple', COALESCE(field_value, '')), 'A')
+            WHEN 'bio' THEN setweight(to_tsvector('english', COALESCE(field_value, '')), 'B')
+        END
+    ) STORED;
+
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_user_profile_changes_field_tsv
+    ON user_profile_changes USING gin(field_tsv)
+    WHERE field_tsv IS NOT NULL;
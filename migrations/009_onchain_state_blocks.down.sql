@@ -0,0 +1,8 @@
+ALTER TABLE storage_rents DROP COLUMN IF EXISTS block_height;
+ALTER TABLE storage_rents DROP COLUMN IF EXISTS shard_id;
+
+ALTER TABLE fid_registrations DROP COLUMN IF EXISTS block_height;
+ALTER TABLE fid_registrations DROP COLUMN IF EXISTS shard_id;
+
+ALTER TABLE fid_signers DROP COLUMN IF EXISTS block_height;
+ALTER TABLE fid_signers DROP COLUMN IF EXISTS shard_id;
@@ -0,0 +1,17 @@
+-- Block tags for decoded on-chain state
+--
+-- `fid_signers` and `fid_registrations` record the shard block of the event
+-- that last changed them, `storage_rents` the block of the event itself. A
+-- rollback deletes storage rents of rolled-back blocks and rebuilds signers
+-- and registrations whose last change was rolled back from the on-chain
+-- events that remain. Rows written before this migration are not tagged and
+-- are left alone by rollbacks.
+
+ALTER TABLE fid_signers ADD COLUMN IF NOT EXISTS shard_id INTEGER;
+ALTER TABLE fid_signers ADD COLUMN IF NOT EXISTS block_height BIGINT;
+
+ALTER TABLE fid_registrations ADD COLUMN IF NOT EXISTS shard_id INTEGER;
+ALTER TABLE fid_registrations ADD COLUMN IF NOT EXISTS block_height BIGINT;
+
+ALTER TABLE storage_rents ADD COLUMN IF NOT EXISTS shard_id INTEGER;
+ALTER TABLE storage_rents ADD COLUMN IF NOT EXISTS block_height BIGINT;
@@ -0,0 +1,134 @@
+//! Server-Sent Events for answers streamed from the LLM
+//!
+//! A streamed answer is sent as:
+//! - one `sources` event with what the answer is based on
+//! - `token` events, `{"delta": "..."}`, as the model generates text
+//! - one `done` event with the response metadata, or an `error` event
+//!   (`{"error": "..."}`) if generation fails part-way
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+
+use axum::response::sse::Event;
+use axum::response::sse::KeepAlive;
+use axum::response::sse::Sse;
+use futures::Stream;
+use futures::StreamExt;
+use serde_json::Value;
+use tracing::error;
+
+use crate::llm::StreamingResponse;
+use crate::Result;
+
+type TokenStream = Pin<Box<dyn Stream<Item = Result<String>> + Send>>;
+
+enum AnswerState<F> {
+    Sources(Value, TokenStream, F),
+    Tokens(TokenStream, String, F),
+    Finished,
+}
+
+fn json_event(name: &str, data: &Value) -> Event {
+    Event::default().event(name).data(data.to_string())
+}
+
+/// Stream an answer as `sources`, `token` and `done` events
+///
+/// `finish` receives the complete answer once the model is done and returns
+/// the payload of the `done` event; it is not called if generation fails.
+pub fn answer_events<F, Fut>(
+    sources: Value,
+    answer: StreamingResponse,
+    finish: F,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>
+where
+    F: FnOnce(String) -> Fut + Send + 'static,
+    Fut: Future<Output = Value> + Send,
+{
+    let state = AnswerState::Sources(sources, answer.into_stream(), finish);
+    let stream = futures::stream::unfold(state, |state| async move {
+        let (event, next) = match state {
+            AnswerState::Sources(sources, tokens, finish) => (
+                json_event("sources", &sources),
+                AnswerState::Tokens(tokens, String::new(), finish),
+            ),
+            AnswerState::Tokens(mut tokens, mut answer, finish) => match tokens.next().await {
+                Some(Ok(delta)) => {
+                    answer.push_str(&delta);
+                    (
+                        json_event("token", &serde_json::json!({ "delta": delta })),
+                        AnswerState::Tokens(tokens, answer, finish),
+                    )
+                }
+                Some(Err(e)) => {
+                    error!("LLM stream failed: {}", e);
+                    (
+                        json_event("error", &serde_json::json!({ "error": e.to_string() })),
+                        AnswerState::Finished,
+                    )
+                }
+                None => (
+                    json_event("done", &finish(answer).await),
+                    AnswerState::Finished,
+                ),
+            },
+            AnswerState::Finished => return None,
+        };
+        Some((Ok(event), next))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::response::IntoResponse;
+
+    use super::*;
+
+    async fn body(
+        sse: Sse<impl Stream<Item = std::result::Result<Event, Infallible>> + Send + 'static>,
+    ) -> String {
+        let bytes = axum::body::to_bytes(sse.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_answer_events_order() {
+        let tokens = futures::stream::iter(vec![Ok("Hel".to_string()), Ok("lo".to_string())]);
+        let sse = answer_events(
+            serde_json::json!([{ "fid": 3 }]),
+            StreamingResponse::new(Box::pin(tokens)),
+            |answer| async move { serde_json::json!({ "answer": answer }) },
+        );
+
+        assert_eq!(
+            body(sse).await,
+            "event: sources\ndata: [{\"fid\":3}]\n\n\
+             event: token\ndata: {\"delta\":\"Hel\"}\n\n\
+             event: token\ndata: {\"delta\":\"lo\"}\n\n\
+             event: done\ndata: {\"answer\":\"Hello\"}\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_answer_events_stop_on_error() {
+        let tokens = futures::stream::iter(vec![
+            Ok("partial".to_string()),
+            Err(crate::SnapRagError::Custom("connection reset".to_string())),
+        ]);
+        let sse = answer_events(
+            serde_json::json!([]),
+            StreamingResponse::new(Box::pin(tokens)),
+            |_| async { unreachable!("finish runs only for complete answers") },
+        );
+
+        let body = body(sse).await;
+        assert!(body
+            .ends_with("event: error\ndata: {\"error\":\"Custom error: connection reset\"}\n\n"));
+        assert!(!body.contains("event: done"));
+    }
+}
@@ -2,11 +2,14 @@
 use axum::extract::Path;
 use axum::extract::State;
 use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::Json;
 use tracing::error;
 use tracing::info;
 
 use super::AppState;
+use crate::api::answer_stream::answer_events;
 use crate::api::types::ApiResponse;
 use crate::api::types::ChatMessageRequest;
 use crate::api::types::ChatMessageResponse;
@@ -196,21 +199,30 @@ pub async fn create_chat_session(
     })))
 }
 
+/// Chat message reply carrying an error
+fn chat_error(message: impl Into<String>) -> Response {
+    Json(ApiResponse::<ChatMessageResponse>::error(message)).into_response()
+}
+
 /// Send a message in a chat session
 ///
+/// With `stream: true` the reply is sent as Server-Sent Events: the casts
+/// used as context, then token deltas, then the usual response fields. The
+/// exchange is added to the session history once the reply is complete.
+///
 /// # Panics
 /// Panics if the system time is before UNIX_EPOCH (1970-01-01), which is impossible on modern systems
 pub async fn send_chat_message(
     State(state): State<AppState>,
     Json(req): Json<ChatMessageRequest>,
-) -> Result<Json<ApiResponse<ChatMessageResponse>>, StatusCode> {
+) -> Result<Response, StatusCode> {
     info!("POST /api/chat/message - session: {}", req.session_id);
 
     // Get session
     let mut session = match state.session_manager.get_session(&req.session_id) {
         Some(s) => s,
         None => {
-            return Ok(Json(ApiResponse::error("Session not found or expired")));
+            return Ok(chat_error("Session not found or expired"));
         }
     };
 
@@ -218,10 +230,7 @@ pub async fn send_chat_message(
     let profile = match state.database.get_user_profile(session.fid).await {
         Ok(Some(p)) => p,
         Ok(None) => {
-            return Ok(Json(ApiResponse::error(format!(
-                "User {} not found",
-                session.fid
-            ))));
+            return Ok(chat_error(format!("User {} not found", session.fid)));
         }
         Err(e) => {
             error!("Database error: {}", e);
@@ -234,7 +243,7 @@ pub async fn send_chat_message(
         Ok(emb) => emb,
         Err(e) => {
             error!("Embedding generation failed: {}", e);
-            return Ok(Json(ApiResponse::error("Failed to process question")));
+            return Ok(chat_error("Failed to process question"));
         }
     };
 
@@ -249,7 +258,7 @@ pub async fn send_chat_message(
         Ok(results) => results,
         Err(e) => {
             error!("Vector search failed: {}", e);
-            return Ok(Json(ApiResponse::error("Failed to search context")));
+            return Ok(chat_error("Failed to search context"));
         }
     };
 
@@ -292,11 +301,21 @@ pub async fn send_chat_message(
         llm
     } else {
         error!("LLM service not configured");
-        return Ok(Json(ApiResponse::error(
-            "LLM service not configured".to_string(),
-        )));
+        return Ok(chat_error("LLM service not configured".to_string()));
     };
 
+    if req.stream {
+        return Ok(stream_chat_reply(
+            &state,
+            llm_service,
+            session,
+            req.message,
+            &user_casts,
+            &context,
+        )
+        .await);
+    }
+
     let response_text = match llm_service
         .generate_with_params(&context, session.temperature, 2000)
         .await
@@ -304,7 +323,7 @@ pub async fn send_chat_message(
         Ok(text) => text,
         Err(e) => {
             error!("LLM generation failed: {}", e);
-            return Ok(Json(ApiResponse::error("Failed to generate response")));
+            return Ok(chat_error("Failed to generate response"));
         }
     };
 
@@ -320,7 +339,62 @@ pub async fn send_chat_message(
         message: response_text,
         relevant_casts_count: user_casts.len(),
         conversation_length: session.conversation_history.len(),
-    })))
+    }))
+    .into_response())
+}
+
+/// Stream a chat reply, recording the exchange once it is complete
+async fn stream_chat_reply(
+    state: &AppState,
+    llm_service: &crate::llm::LlmService,
+    mut session: crate::api::session::ChatSession,
+    message: String,
+    user_casts: &[crate::models::CastSearchResult],
+    context: &str,
+) -> Response {
+    let tokens = match llm_service
+        .generate_stream_with_params(context, session.temperature, 2000)
+        .await
+    {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            error!("LLM generation failed: {}", e);
+            return chat_error("Failed to generate response");
+        }
+    };
+
+    let sources: Vec<_> = user_casts
+        .iter()
+        .map(|cast| {
+            serde_json::json!({
+                "message_hash": hex::encode(&cast.message_hash),
+                "text": cast.text,
+                "timestamp": cast.timestamp,
+                "similarity": cast.similarity,
+            })
+        })
+        .collect();
+    let relevant_casts_count = user_casts.len();
+    let session_manager = state.session_manager.clone();
+
+    answer_events(
+        serde_json::Value::Array(sources),
+        tokens,
+        move |answer| async move {
+            session.add_message("user", message);
+            session.add_message("assistant", answer.clone());
+            session_manager.update_session(session.clone());
+
+            serde_json::to_value(ChatMessageResponse {
+                session_id: session.session_id,
+                message: answer,
+                relevant_casts_count,
+                conversation_length: session.conversation_history.len(),
+            })
+            .unwrap_or_default()
+        },
+    )
+    .into_response()
 }
 
 /// Get session information
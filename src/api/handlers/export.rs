@@ -0,0 +1,123 @@
+/// Bulk dataset export handler
+use axum::body::Body;
+use axum::body::Bytes;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::header;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::Response;
+use serde::Deserialize;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use super::require_api_key;
+use super::AppState;
+use crate::export::export_chunks;
+use crate::export::parse_time;
+use crate::export::ExportFormat;
+use crate::export::ExportQuery;
+use crate::export::ExportTable;
+use crate::export::FidRange;
+
+/// Chunks buffered between the export and a slow client
+const EXPORT_CHANNEL_CAPACITY: usize = 4;
+
+/// Query parameters of `/api/export`
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub table: ExportTable,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// RFC 3339, `YYYY-MM-DD` or Unix seconds
+    pub since: Option<String>,
+    /// RFC 3339, `YYYY-MM-DD` or Unix seconds
+    pub until: Option<String>,
+    /// `min-max`, `min-`, `-max` or a single FID
+    pub fid_range: Option<String>,
+}
+
+impl ExportParams {
+    fn query(&self) -> crate::Result<ExportQuery> {
+        let query = ExportQuery {
+            table: self.table,
+            since: self.since.as_deref().map(parse_time).transpose()?,
+            until: self.until.as_deref().map(parse_time).transpose()?,
+            fids: self
+                .fid_range
+                .as_deref()
+                .map(str::parse::<FidRange>)
+                .transpose()?
+                .unwrap_or_default(),
+        };
+        query.validate()?;
+        Ok(query)
+    }
+}
+
+/// Stream a table export as the response body (GET /api/export)
+///
+/// The body is produced batch by batch from a server-side cursor. An error
+/// after the response started aborts the body, so clients see a truncated
+/// transfer rather than a silently short file.
+pub async fn export_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, StatusCode> {
+    info!("GET /api/export {:?}", params);
+
+    require_api_key(&state.config.export.api_keys, "[export] api_keys", &headers)?;
+    let query = params.query().map_err(|e| {
+        warn!("Invalid export request: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let format = params.format;
+
+    let (sender, mut receiver) =
+        tokio::sync::mpsc::channel::<std::io::Result<Bytes>>(EXPORT_CHANNEL_CAPACITY);
+    let database = state.database.clone();
+    let fetch_size = state.config.export.fetch_size;
+    let export_query = query.clone();
+    tokio::spawn(async move {
+        let result = export_chunks(&database, &export_query, format, fetch_size, |chunk| {
+            let sender = sender.clone();
+            async move {
+                if chunk.is_empty() {
+                    return Ok(());
+                }
+                sender.send(Ok(Bytes::from(chunk))).await.map_err(|_| {
+                    crate::SnapRagError::Custom("Export client disconnected".to_string())
+                })
+            }
+        })
+        .await;
+
+        match result {
+            Ok(rows) => info!(
+                "✅ Exported {} {} rows over /api/export",
+                rows,
+                export_query.table.name()
+            ),
+            Err(e) => {
+                error!("Export of {} failed: {}", export_query.table.name(), e);
+                let _ = sender.send(Err(std::io::Error::other(e.to_string()))).await;
+            }
+        }
+    });
+
+    let body = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                query.table.name(),
+                format.extension()
+            ),
+        )
+        .body(Body::from_stream(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -4,10 +4,12 @@ use std::sync::Arc;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::http::StatusCode;
 use axum::Json;
 use tracing::error;
 use tracing::info;
+use tracing::warn;
 
 use crate::api::cache::CacheService;
 use crate::api::types::ApiResponse;
@@ -29,21 +31,27 @@ use crate::social_graph::SocialGraphAnalyzer;
 
 // Re-export sub-modules
 pub mod chat;
+pub mod export;
 pub mod mbti;
 pub mod metrics;
 pub mod profile;
 pub mod rag;
 pub mod search;
 pub mod stats;
+pub mod stream;
+pub mod subscriptions;
 
 // Re-export handlers
 pub use chat::*;
+pub use export::*;
 pub use mbti::*;
 pub use metrics::*;
 pub use profile::*;
 pub use rag::*;
 pub use search::*;
 pub use stats::*;
+pub use stream::*;
+pub use subscriptions::*;
 
 /// Shared application state
 #[derive(Clone)]
@@ -55,6 +63,24 @@ pub struct AppState {
     pub lazy_loader: Option<Arc<crate::sync::LazyLoader>>,
     pub session_manager: Arc<crate::api::session::SessionManager>,
     pub cache_service: Arc<CacheService>,
+    /// Live feed of synced events, when `[live_feed]` is enabled
+    pub live_feed: Option<Arc<crate::api::live_feed::LiveFeed>>,
+    /// Reranker applied to RAG retrieval, when `[rerank] method` is set
+    pub reranker: Option<Arc<dyn crate::rag::Rerank>>,
+}
+
+/// Check the `X-API-KEY` header against `keys`, read from the `setting` config key
+///
+/// An empty key list disables the endpoint.
+fn require_api_key(keys: &[String], setting: &str, headers: &HeaderMap) -> Result<(), StatusCode> {
+    if keys.is_empty() {
+        warn!("Request rejected: {} is empty", setting);
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    match headers.get("X-API-KEY").and_then(|h| h.to_str().ok()) {
+        Some(key) if keys.iter().any(|expected| expected == key) => Ok(()),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
 }
 
 /// Health check handler
@@ -9,8 +9,11 @@ use tracing::info;
 
 use super::AppState;
 use crate::api::types::ApiResponse;
+use crate::api::types::OnchainStateResponse;
 use crate::api::types::ProfileResponse;
 use crate::api::types::SearchQuery;
+use crate::api::types::SignerResponse;
+use crate::api::types::StorageRentResponse;
 use crate::models::UserProfileQuery;
 
 /// Get profile by FID (with automatic lazy loading and caching)
@@ -157,6 +160,61 @@ pub async fn list_profiles(
     }
 }
 
+/// Get decoded on-chain state (custody, signers, storage) by FID
+pub async fn get_profile_onchain(
+    State(state): State<AppState>,
+    Path(fid): Path<i64>,
+) -> Result<Json<ApiResponse<OnchainStateResponse>>, StatusCode> {
+    info!("GET /api/profiles/{}/onchain", fid);
+
+    let onchain = match state.database.get_onchain_state(fid).await {
+        Ok(onchain) => onchain,
+        Err(e) => {
+            error!("Error fetching on-chain state: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if onchain.registration.is_none()
+        && onchain.active_signers.is_empty()
+        && onchain.storage_rents.is_empty()
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let to_hex = |bytes: &Vec<u8>| format!("0x{}", hex::encode(bytes));
+    let registration = onchain.registration.as_ref();
+
+    Ok(Json(ApiResponse::success(OnchainStateResponse {
+        fid,
+        custody_address: registration.and_then(|r| r.custody_address.as_ref().map(to_hex)),
+        recovery_address: registration.and_then(|r| r.recovery_address.as_ref().map(to_hex)),
+        registered_block: registration.and_then(|r| r.registered_block),
+        registered_at: registration.and_then(|r| r.registered_at),
+        active_signers: onchain
+            .active_signers
+            .iter()
+            .map(|signer| SignerResponse {
+                key: to_hex(&signer.key),
+                key_type: signer.key_type,
+                added_block: signer.added_block,
+                added_at: signer.added_at,
+            })
+            .collect(),
+        storage_units: onchain.storage_units,
+        storage_rents: onchain
+            .storage_rents
+            .iter()
+            .map(|rent| StorageRentResponse {
+                units: rent.units,
+                expiry: rent.expiry,
+                payer: rent.payer.as_ref().map(to_hex),
+                block_number: rent.block_number,
+            })
+            .collect(),
+    })))
+}
+
 /// Get profile by username (with caching)
 pub async fn get_profile_by_username(
     State(state): State<AppState>,
@@ -1,38 +1,61 @@
 /// RAG-related API handlers
 use axum::extract::State;
 use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::Json;
 use tracing::error;
 use tracing::info;
 
 use super::AppState;
+use crate::api::answer_stream::answer_events;
 use crate::api::types::ApiResponse;
+use crate::api::types::CastRagQueryRequest;
 use crate::api::types::RagQueryRequest;
+use crate::rag::CastRagQuery;
+use crate::rag::CastRagResponse;
+use crate::rag::CastRagService;
+use crate::rag::QueryUnderstanding;
 use crate::rag::RagQuery;
 use crate::rag::RagService;
 use crate::rag::RetrievalMethod;
 
 /// RAG query
+///
+/// With `stream: true` the answer is sent as Server-Sent Events: the
+/// retrieved profiles, then token deltas, then the query metadata,
+/// including the rewritten queries and extracted filters when query
+/// understanding is enabled.
 pub async fn rag_query(
     State(state): State<AppState>,
     Json(req): Json<RagQueryRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> Result<Response, StatusCode> {
     info!("POST /api/rag/query: {}", req.question);
 
     let llm_service = if let Some(llm) = &state.llm_service {
         llm.clone()
     } else {
         error!("LLM service not configured");
-        return Ok(Json(ApiResponse::error(
+        return Ok(Json(ApiResponse::<String>::error(
             "LLM service not configured. Please check your configuration.".to_string(),
-        )));
+        ))
+        .into_response());
     };
 
-    let rag_service = RagService::from_services(
+    let mut rag_service = RagService::from_services(
         state.database.clone(),
         state.embedding_service.clone(),
         (*llm_service).clone(),
     );
+    if let Some(reranker) = &state.reranker {
+        rag_service = rag_service.with_reranker(reranker.clone(), state.config.rerank.candidates);
+    }
+    if state.config.query_understanding.enabled {
+        rag_service = rag_service.with_query_understanding(QueryUnderstanding::new(
+            (*llm_service).clone(),
+            state.config.query_understanding.clone(),
+        ));
+    }
 
     let method = match req.method.as_deref() {
         Some("semantic") => RetrievalMethod::Semantic,
@@ -49,11 +72,87 @@ pub async fn rag_query(
         max_tokens: req.max_tokens,
     };
 
+    if req.stream {
+        return match rag_service.query_stream(query).await {
+            Ok(stream) => {
+                let sources = serde_json::to_value(&stream.sources).unwrap_or_default();
+                let sources_count = stream.sources.len();
+                let question = stream.query;
+                let query_analysis = stream.metadata.query_analysis;
+                Ok(
+                    answer_events(sources, stream.answer, move |answer| async move {
+                        serde_json::json!({
+                            "query": question,
+                            "answer": answer,
+                            "sources_count": sources_count,
+                            "query_analysis": query_analysis,
+                        })
+                    })
+                    .into_response(),
+                )
+            }
+            Err(e) => {
+                error!("Error processing RAG query: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        };
+    }
+
     match rag_service.query_with_options(query).await {
-        Ok(response) => Ok(Json(ApiResponse::success(response.answer))),
+        Ok(response) => Ok(Json(ApiResponse::success(response)).into_response()),
         Err(e) => {
             error!("Error processing RAG query: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
+
+/// RAG query over casts
+///
+/// The answer cites the returned sources as `[n]`; `citations` maps each
+/// marker to its cast, and `unknown_markers` / `uncited_sources` flag
+/// citations of missing sources and sources left uncited.
+pub async fn rag_casts(
+    State(state): State<AppState>,
+    Json(req): Json<CastRagQueryRequest>,
+) -> Result<Json<ApiResponse<CastRagResponse>>, StatusCode> {
+    info!("POST /api/rag/casts: {}", req.question);
+
+    let Some(llm_service) = &state.llm_service else {
+        error!("LLM service not configured");
+        return Ok(Json(ApiResponse::error(
+            "LLM service not configured. Please check your configuration.".to_string(),
+        )));
+    };
+
+    let mut rag_service = CastRagService::from_services(
+        state.database.clone(),
+        state.embedding_service.clone(),
+        (**llm_service).clone(),
+    );
+    if let Some(reranker) = &state.reranker {
+        rag_service = rag_service.with_reranker(reranker.clone(), state.config.rerank.candidates);
+    }
+    if state.config.query_understanding.enabled {
+        rag_service = rag_service.with_query_understanding(QueryUnderstanding::new(
+            (**llm_service).clone(),
+            state.config.query_understanding.clone(),
+        ));
+    }
+
+    let query = CastRagQuery {
+        question: req.question,
+        retrieval_limit: req.retrieval_limit,
+        threshold: req.threshold,
+        temperature: req.temperature,
+        max_tokens: req.max_tokens,
+    };
+
+    match rag_service.query_with_options(query).await {
+        Ok(response) => Ok(Json(ApiResponse::success(response))),
+        Err(e) => {
+            error!("Error processing cast RAG query: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
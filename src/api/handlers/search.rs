@@ -63,10 +63,20 @@ pub async fn search_casts(
 
     let retriever = CastRetriever::new(state.database.clone(), state.embedding_service.clone());
 
-    match retriever
-        .semantic_search(&req.query, req.limit, Some(req.threshold))
-        .await
-    {
+    let results = match req.channel.as_deref() {
+        Some(channel) => {
+            retriever
+                .semantic_search_in_channel(&req.query, channel, req.limit, Some(req.threshold))
+                .await
+        }
+        None => {
+            retriever
+                .semantic_search(&req.query, req.limit, Some(req.threshold))
+                .await
+        }
+    };
+
+    match results {
         Ok(results) => {
             let response: Vec<CastResponse> = results
                 .into_iter()
@@ -0,0 +1,113 @@
+/// Live feed streaming handlers (SSE and WebSocket)
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::ws::Message;
+use axum::extract::ws::WebSocket;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::Event;
+use axum::response::sse::KeepAlive;
+use axum::response::sse::Sse;
+use axum::response::Response;
+use futures::Stream;
+use tracing::info;
+use tracing::warn;
+
+use super::AppState;
+use crate::api::live_feed::LiveFeed;
+use crate::api::live_feed::LiveFeedFilter;
+use crate::api::live_feed::LiveFeedMessage;
+use crate::api::live_feed::LiveFeedQuery;
+use crate::api::live_feed::LiveFeedSubscriber;
+
+/// Live feed and client filter for a stream request
+fn open_feed(
+    state: &AppState,
+    query: &LiveFeedQuery,
+) -> Result<(Arc<LiveFeed>, LiveFeedFilter), StatusCode> {
+    let Some(feed) = state.live_feed.clone() else {
+        warn!("Live feed requested but [live_feed] is disabled");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let filter = LiveFeedFilter::from_query(query).map_err(|e| {
+        warn!("Invalid live feed filter: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    Ok((feed, filter))
+}
+
+/// JSON text sent for a feed message; lag notices are `{"lagged": n}`
+fn message_json(message: &LiveFeedMessage) -> String {
+    match message {
+        LiveFeedMessage::Event(event) => {
+            serde_json::to_string(event.as_ref()).expect("live feed events always serialize")
+        }
+        LiveFeedMessage::Lagged(skipped) => serde_json::json!({ "lagged": skipped }).to_string(),
+    }
+}
+
+/// Stream live events as Server-Sent Events (GET /api/stream/events)
+///
+/// Each SSE event is named after its event type (`cast_add`, `link_add`, ...)
+/// or `lagged` when the client fell behind.
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Query(query): Query<LiveFeedQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    info!("GET /api/stream/events {:?}", query);
+
+    let (feed, filter) = open_feed(&state, &query)?;
+    let stream = futures::stream::unfold(
+        (feed.subscribe(), filter),
+        |(mut subscriber, filter)| async move {
+            let message = subscriber.next(&filter).await?;
+            let name = match &message {
+                LiveFeedMessage::Event(event) => event.event_type.as_str(),
+                LiveFeedMessage::Lagged(_) => "lagged",
+            };
+            let event = Event::default().event(name).data(message_json(&message));
+            Some((Ok(event), (subscriber, filter)))
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Stream live events over a WebSocket (GET /api/stream/ws)
+pub async fn stream_events_ws(
+    State(state): State<AppState>,
+    Query(query): Query<LiveFeedQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    info!("GET /api/stream/ws {:?}", query);
+
+    let (feed, filter) = open_feed(&state, &query)?;
+    let subscriber = feed.subscribe();
+    Ok(ws.on_upgrade(move |socket| forward_to_socket(socket, subscriber, filter)))
+}
+
+/// Send feed messages as text frames until either side closes
+async fn forward_to_socket(
+    mut socket: WebSocket,
+    mut subscriber: LiveFeedSubscriber,
+    filter: LiveFeedFilter,
+) {
+    loop {
+        tokio::select! {
+            message = subscriber.next(&filter) => {
+                let Some(message) = message else { break };
+                if socket.send(Message::Text(message_json(&message))).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                // Pings are answered by axum; other client frames are ignored
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,299 @@
+/// Webhook subscription API handlers
+use axum::extract::Path;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::Json;
+use tracing::error;
+use tracing::info;
+
+use super::require_api_key;
+use super::AppState;
+use crate::api::types::ApiResponse;
+use crate::api::types::CreateSubscriptionRequest;
+use crate::api::types::DeadLetterResponse;
+use crate::api::types::ReplaySubscriptionRequest;
+use crate::api::types::ReplaySubscriptionResponse;
+use crate::api::types::SubscriptionResponse;
+use crate::database::channel_url;
+use crate::database::WebhookSubscription;
+use crate::webhooks::resolve_destination;
+use crate::webhooks::SubscriptionFilter;
+
+/// Dead letters returned per request
+const DEAD_LETTER_LIMIT: i64 = 100;
+
+impl From<WebhookSubscription> for SubscriptionResponse {
+    fn from(subscription: WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id,
+            url: subscription.url,
+            secret: None,
+            event_types: subscription.event_types,
+            fid: subscription.fid,
+            target_fid: subscription.target_fid,
+            parent_hash: subscription.parent_hash.as_deref().map(hex::encode),
+            channel: subscription.channel,
+            active: subscription.active,
+            last_delivered_offset: subscription.last_delivered_offset,
+            created_at: subscription.created_at.timestamp(),
+        }
+    }
+}
+
+/// Check the `X-API-KEY` header against `[webhooks] api_keys`
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    require_api_key(
+        &state.config.webhooks.api_keys,
+        "[webhooks] api_keys",
+        headers,
+    )
+}
+
+/// Validate a create request into the endpoint URL and filters
+fn parse_subscription_request(
+    req: &CreateSubscriptionRequest,
+) -> crate::Result<(String, SubscriptionFilter)> {
+    let url = url::Url::parse(&req.url)
+        .map_err(|e| crate::SnapRagError::Custom(format!("Invalid url '{}': {e}", req.url)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(crate::SnapRagError::Custom(
+            "Webhook url must use http or https".to_string(),
+        ));
+    }
+
+    let event_types = req
+        .event_types
+        .iter()
+        .map(|event_type| event_type.parse())
+        .collect::<crate::Result<Vec<_>>>()?;
+    let parent_hash = req
+        .parent_hash
+        .as_deref()
+        .map(|hash| {
+            hex::decode(hash.trim_start_matches("0x"))
+                .map_err(|_| crate::SnapRagError::Custom(format!("Invalid parent_hash '{hash}'")))
+        })
+        .transpose()?;
+
+    Ok((
+        url.to_string(),
+        SubscriptionFilter {
+            event_types,
+            fid: req.fid,
+            target_fid: req.target_fid,
+            parent_hash,
+            channel: req.channel.as_deref().map(channel_url),
+        },
+    ))
+}
+
+/// Create a webhook subscription (POST /api/subscriptions)
+pub async fn create_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateSubscriptionRequest>,
+) -> Result<Json<ApiResponse<SubscriptionResponse>>, StatusCode> {
+    info!("POST /api/subscriptions: {}", req.url);
+    authorize(&state, &headers)?;
+
+    let (url, filter) = match parse_subscription_request(&req) {
+        Ok(parsed) => parsed,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    if let Err(e) = resolve_destination(&url).await {
+        return Ok(Json(ApiResponse::error(e.to_string())));
+    }
+    let secret = req.secret.clone().unwrap_or_else(|| {
+        format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        )
+    });
+
+    match state
+        .database
+        .create_webhook_subscription(&url, &secret, &filter)
+        .await
+    {
+        Ok(subscription) => {
+            info!("🪝 Created webhook subscription {}", subscription.id);
+            Ok(Json(ApiResponse::success(SubscriptionResponse {
+                secret: Some(secret),
+                ..subscription.into()
+            })))
+        }
+        Err(e) => {
+            error!("Error creating webhook subscription: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// List webhook subscriptions (GET /api/subscriptions)
+pub async fn list_subscriptions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<SubscriptionResponse>>>, StatusCode> {
+    info!("GET /api/subscriptions");
+    authorize(&state, &headers)?;
+
+    match state.database.list_webhook_subscriptions().await {
+        Ok(subscriptions) => Ok(Json(ApiResponse::success(
+            subscriptions.into_iter().map(Into::into).collect(),
+        ))),
+        Err(e) => {
+            error!("Error listing webhook subscriptions: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get a webhook subscription (GET /api/subscriptions/:id)
+pub async fn get_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<SubscriptionResponse>>, StatusCode> {
+    info!("GET /api/subscriptions/{}", id);
+    authorize(&state, &headers)?;
+
+    match state.database.get_webhook_subscription(id).await {
+        Ok(Some(subscription)) => Ok(Json(ApiResponse::success(subscription.into()))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error getting webhook subscription {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Delete a webhook subscription and its pending deliveries (DELETE /api/subscriptions/:id)
+pub async fn delete_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    info!("DELETE /api/subscriptions/{}", id);
+    authorize(&state, &headers)?;
+
+    match state.database.delete_webhook_subscription(id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(format!(
+            "Subscription {id} deleted"
+        )))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error deleting webhook subscription {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Deliver recorded events again from an offset (POST /api/subscriptions/:id/replay)
+pub async fn replay_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Json(req): Json<ReplaySubscriptionRequest>,
+) -> Result<Json<ApiResponse<ReplaySubscriptionResponse>>, StatusCode> {
+    info!(
+        "POST /api/subscriptions/{}/replay from offset {}",
+        id, req.from_offset
+    );
+    authorize(&state, &headers)?;
+
+    let subscription = match state.database.get_webhook_subscription(id).await {
+        Ok(Some(subscription)) => subscription,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error getting webhook subscription {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match state
+        .database
+        .replay_webhook_subscription(&subscription, req.from_offset)
+        .await
+    {
+        Ok(queued) => Ok(Json(ApiResponse::success(ReplaySubscriptionResponse {
+            subscription_id: id,
+            from_offset: req.from_offset,
+            queued,
+        }))),
+        Err(e) => {
+            error!("Error replaying webhook subscription {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// List dead-lettered deliveries (GET /api/subscriptions/:id/dead-letters)
+pub async fn list_subscription_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Vec<DeadLetterResponse>>>, StatusCode> {
+    info!("GET /api/subscriptions/{}/dead-letters", id);
+    authorize(&state, &headers)?;
+
+    match state
+        .database
+        .list_webhook_dead_letters(id, DEAD_LETTER_LIMIT)
+        .await
+    {
+        Ok(dead_letters) => Ok(Json(ApiResponse::success(
+            dead_letters
+                .into_iter()
+                .map(|dead_letter| DeadLetterResponse {
+                    event_id: dead_letter.event_id,
+                    attempts: dead_letter.attempts,
+                    last_error: dead_letter.last_error,
+                    failed_at: dead_letter.failed_at.timestamp(),
+                })
+                .collect(),
+        ))),
+        Err(e) => {
+            error!("Error listing dead letters of subscription {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::ChangeEventType;
+
+    fn request(url: &str) -> CreateSubscriptionRequest {
+        CreateSubscriptionRequest {
+            url: url.to_string(),
+            secret: None,
+            event_types: vec!["cast_add".to_string()],
+            fid: None,
+            target_fid: None,
+            parent_hash: Some("0xabcd".to_string()),
+            channel: Some("base".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_subscription_request() {
+        let (url, filter) =
+            parse_subscription_request(&request("https://example.com/hook")).unwrap();
+        assert_eq!(url, "https://example.com/hook");
+        assert_eq!(filter.event_types, vec![ChangeEventType::CastAdd]);
+        assert_eq!(filter.parent_hash, Some(vec![0xab, 0xcd]));
+        assert_eq!(
+            filter.channel.as_deref(),
+            Some("https://warpcast.com/~/channel/base")
+        );
+
+        assert!(parse_subscription_request(&request("ftp://example.com")).is_err());
+        assert!(parse_subscription_request(&request("not a url")).is_err());
+        let mut bad_type = request("https://example.com/hook");
+        bad_type.event_types = vec!["follow".to_string()];
+        assert!(parse_subscription_request(&bad_type).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Fan-out of live feed events to SSE and WebSocket clients
+//!
+//! One `LISTEN` connection per server feeds a bounded broadcast channel.
+//! Every client reads from its own position in that channel: a client that
+//! falls more than `buffer_size` events behind skips the oldest ones and is
+//! told how many it missed, so slow clients never hold up the feed or sync.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use crate::database::channel_url;
+use crate::database::LiveFeedEvent;
+use crate::database::LIVE_FEED_CHANNEL;
+use crate::Result;
+
+/// Query parameters of the stream endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct LiveFeedQuery {
+    /// Comma-separated author FIDs
+    pub fids: Option<String>,
+    /// Channel name (e.g. `base`) or parent URL
+    pub channel: Option<String>,
+    /// Case-insensitive keyword in the cast text
+    pub keyword: Option<String>,
+}
+
+/// Which events a client receives; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveFeedFilter {
+    pub fids: HashSet<i64>,
+    pub channel: Option<String>,
+    /// Lowercased keyword; only cast events have text to match
+    pub keyword: Option<String>,
+}
+
+impl LiveFeedFilter {
+    pub fn from_query(query: &LiveFeedQuery) -> Result<Self> {
+        let fids = query
+            .fids
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|fid| !fid.is_empty())
+            .map(|fid| {
+                fid.parse::<i64>()
+                    .map_err(|_| crate::SnapRagError::Custom(format!("Invalid FID '{fid}'")))
+            })
+            .collect::<Result<HashSet<_>>>()?;
+
+        Ok(Self {
+            fids,
+            channel: query.channel.as_deref().map(channel_url),
+            keyword: query
+                .keyword
+                .as_deref()
+                .map(str::trim)
+                .filter(|keyword| !keyword.is_empty())
+                .map(str::to_lowercase),
+        })
+    }
+
+    #[must_use]
+    pub fn matches(&self, event: &LiveFeedEvent) -> bool {
+        (self.fids.is_empty() || self.fids.contains(&event.fid))
+            && self
+                .channel
+                .as_ref()
+                .is_none_or(|channel| event.channel.as_ref() == Some(channel))
+            && self.keyword.as_ref().is_none_or(|keyword| {
+                event
+                    .text()
+                    .is_some_and(|text| text.to_lowercase().contains(keyword.as_str()))
+            })
+    }
+}
+
+/// What a client is sent next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveFeedMessage {
+    Event(Arc<LiveFeedEvent>),
+    /// The client fell behind and this many events were dropped
+    Lagged(u64),
+}
+
+/// Broadcast hub of the live feed
+#[derive(Debug)]
+pub struct LiveFeed {
+    sender: broadcast::Sender<Arc<LiveFeedEvent>>,
+}
+
+impl LiveFeed {
+    #[must_use]
+    pub fn new(buffer_size: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer_size.max(1));
+        Self { sender }
+    }
+
+    #[must_use]
+    pub fn subscribe(&self) -> LiveFeedSubscriber {
+        LiveFeedSubscriber {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Send an event to every connected client
+    pub fn publish(&self, event: LiveFeedEvent) {
+        // Without clients there is nobody to send to
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    /// Number of connected clients
+    #[must_use]
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Forward notifications published by sync until the listener fails
+    ///
+    /// The listener reconnects on its own after connection loss; events
+    /// published meanwhile are missed.
+    pub async fn listen(self: Arc<Self>, pool: sqlx::PgPool) -> Result<()> {
+        let mut listener = PgListener::connect_with(&pool).await?;
+        listener.listen(LIVE_FEED_CHANNEL).await?;
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<LiveFeedEvent>(notification.payload()) {
+                Ok(event) => self.publish(event),
+                Err(e) => warn!("Ignoring malformed live feed notification: {}", e),
+            }
+        }
+    }
+}
+
+/// One client's position in the live feed
+#[derive(Debug)]
+pub struct LiveFeedSubscriber {
+    receiver: broadcast::Receiver<Arc<LiveFeedEvent>>,
+}
+
+impl LiveFeedSubscriber {
+    /// Next event matching `filter`; `None` once the feed shut down
+    pub async fn next(&mut self, filter: &LiveFeedFilter) -> Option<LiveFeedMessage> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if filter.matches(&event) => return Some(LiveFeedMessage::Event(event)),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => return Some(LiveFeedMessage::Lagged(skipped)),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::ChangeEventType;
+
+    fn cast(fid: i64, text: &str, channel: Option<&str>) -> LiveFeedEvent {
+        LiveFeedEvent {
+            event_type: ChangeEventType::CastAdd,
+            fid,
+            target_fid: None,
+            message_hash: "ab".to_string(),
+            parent_hash: None,
+            channel: channel.map(ToString::to_string),
+            timestamp: 1,
+            data: serde_json::json!({ "text": text }),
+        }
+    }
+
+    #[test]
+    fn test_filter_from_query() {
+        let filter = LiveFeedFilter::from_query(&LiveFeedQuery {
+            fids: Some("3, 99,".to_string()),
+            channel: Some("base".to_string()),
+            keyword: Some(" ETH ".to_string()),
+        })
+        .unwrap();
+        assert_eq!(filter.fids, HashSet::from([3, 99]));
+        assert_eq!(
+            filter.channel.as_deref(),
+            Some("https://warpcast.com/~/channel/base")
+        );
+        assert_eq!(filter.keyword.as_deref(), Some("eth"));
+
+        assert!(LiveFeedFilter::from_query(&LiveFeedQuery {
+            fids: Some("3,x".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let base = Some("https://warpcast.com/~/channel/base");
+        assert!(LiveFeedFilter::default().matches(&cast(1, "gm", None)));
+
+        let filter = LiveFeedFilter {
+            fids: HashSet::from([1]),
+            channel: base.map(ToString::to_string),
+            keyword: Some("eth".to_string()),
+        };
+        assert!(filter.matches(&cast(1, "Ethereum is up", base)));
+        assert!(!filter.matches(&cast(2, "Ethereum is up", base)));
+        assert!(!filter.matches(&cast(1, "Ethereum is up", None)));
+        assert!(!filter.matches(&cast(1, "gm", base)));
+
+        // Events without text never match a keyword
+        let follow = LiveFeedEvent {
+            event_type: ChangeEventType::LinkAdd,
+            data: serde_json::json!({ "link_type": "follow" }),
+            ..cast(1, "", None)
+        };
+        assert!(!LiveFeedFilter {
+            keyword: Some("follow".to_string()),
+            ..Default::default()
+        }
+        .matches(&follow));
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_skips_oldest_events() {
+        let feed = LiveFeed::new(2);
+        let mut subscriber = feed.subscribe();
+        assert_eq!(feed.client_count(), 1);
+
+        for fid in 1..=5 {
+            feed.publish(cast(fid, "gm", None));
+        }
+
+        let filter = LiveFeedFilter::default();
+        assert_eq!(
+            subscriber.next(&filter).await,
+            Some(LiveFeedMessage::Lagged(3))
+        );
+        for fid in [4, 5] {
+            match subscriber.next(&filter).await {
+                Some(LiveFeedMessage::Event(event)) => assert_eq!(event.fid, fid),
+                other => panic!("expected event, got {other:?}"),
+            }
+        }
+
+        drop(feed);
+        assert_eq!(subscriber.next(&filter).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_skips_filtered_events() {
+        let feed = LiveFeed::new(8);
+        let mut subscriber = feed.subscribe();
+        feed.publish(cast(1, "gm", None));
+        feed.publish(cast(2, "gm", None));
+
+        let filter = LiveFeedFilter {
+            fids: HashSet::from([2]),
+            ..Default::default()
+        };
+        match subscriber.next(&filter).await {
+            Some(LiveFeedMessage::Event(event)) => assert_eq!(event.fid, 2),
+            other => panic!("expected event, got {other:?}"),
+        }
+    }
+}
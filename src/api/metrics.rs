@@ -28,6 +28,8 @@ use prometheus::Opts;
 use prometheus::Registry;
 use prometheus::TextEncoder;
 
+use crate::database::HeightRange;
+
 /// Metrics registry and collectors
 pub struct Metrics {
     pub registry: Registry,
@@ -39,6 +41,8 @@ pub struct Metrics {
     pub sync_status: GaugeVec,
     pub sync_errors: Counter,
     pub sync_duration: Histogram,
+    pub sync_gap_ranges: GaugeVec,
+    pub sync_gap_blocks: GaugeVec,
 
     // Database metrics
     pub db_total_profiles: Gauge,
@@ -107,6 +111,22 @@ impl Metrics {
         )
         .buckets(vec![0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]))?;
 
+        let sync_gap_ranges = register_gauge_vec!(
+            Opts::new(
+                "snaprag_sync_gap_ranges",
+                "Number of missing block ranges per shard"
+            ),
+            &["shard_id"]
+        )?;
+
+        let sync_gap_blocks = register_gauge_vec!(
+            Opts::new(
+                "snaprag_sync_gap_blocks",
+                "Number of missing blocks per shard"
+            ),
+            &["shard_id"]
+        )?;
+
         // Database metrics
         let db_total_profiles = register_gauge!(Opts::new(
             "snaprag_db_total_profiles",
@@ -198,6 +218,8 @@ impl Metrics {
         registry.register(Box::new(sync_status.clone()))?;
         registry.register(Box::new(sync_errors.clone()))?;
         registry.register(Box::new(sync_duration.clone()))?;
+        registry.register(Box::new(sync_gap_ranges.clone()))?;
+        registry.register(Box::new(sync_gap_blocks.clone()))?;
         registry.register(Box::new(db_total_profiles.clone()))?;
         registry.register(Box::new(db_total_casts.clone()))?;
         registry.register(Box::new(db_profiles_with_embeddings.clone()))?;
@@ -223,6 +245,8 @@ impl Metrics {
             sync_status,
             sync_errors,
             sync_duration,
+            sync_gap_ranges,
+            sync_gap_blocks,
             db_total_profiles,
             db_total_casts,
             db_profiles_with_embeddings,
@@ -275,6 +299,24 @@ impl Metrics {
                     .set(status_value);
             }
         }
+
+        // Gaps in the processed_blocks ledger
+        if let Ok(shards) = database.processed_block_shards().await {
+            for shard in shards {
+                if let Ok(gaps) = database.find_sync_gaps(shard, None, None).await {
+                    let shard_id = shard.to_string();
+                    let missing: u64 = gaps.iter().map(HeightRange::block_count).sum();
+                    #[allow(clippy::cast_precision_loss)] // Gap counts are far below 2^52
+                    let (ranges, blocks) = (gaps.len() as f64, missing as f64);
+                    self.sync_gap_ranges
+                        .with_label_values(&[&shard_id])
+                        .set(ranges);
+                    self.sync_gap_blocks
+                        .with_label_values(&[&shard_id])
+                        .set(blocks);
+                }
+            }
+        }
     }
 
     /// Update database metrics
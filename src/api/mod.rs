@@ -1,9 +1,11 @@
 //! API server module for serving read-only services via REST and MCP
 
+pub mod answer_stream;
 pub mod backend_api_key;
 pub mod cache;
 pub mod cache_proxy;
 pub mod handlers;
+pub mod live_feed;
 pub mod mcp;
 pub mod metrics;
 #[cfg(feature = "payment")]
@@ -31,13 +31,18 @@ impl Default for PricingConfig {
                 "/resources".to_string(), // MCP resources
                 "/tools".to_string(),     // MCP tools list
             ],
-            basic_endpoints: vec!["/profiles".to_string(), "/profiles/:fid".to_string()],
+            basic_endpoints: vec![
+                "/profiles".to_string(),
+                "/profiles/:fid".to_string(),
+                "/profiles/:fid/onchain".to_string(),
+            ],
             premium_endpoints: vec![
                 "/search/profiles".to_string(),
                 "/search/casts".to_string(),
-                "/tools/call".to_string(), // MCP tool calls
+                "/tools/call".to_string(),    // MCP tool calls
+                "/subscriptions".to_string(), // Webhook subscriptions and their sub-routes
             ],
-            enterprise_endpoints: vec!["/rag/query".to_string()],
+            enterprise_endpoints: vec!["/rag/query".to_string(), "/rag/casts".to_string()],
         }
     }
 }
@@ -133,6 +138,7 @@ impl PricingConfig {
             p if p.contains("/search/profiles") => "Semantic profile search".to_string(),
             p if p.contains("/search/casts") => "Semantic cast search".to_string(),
             p if p.contains("/rag/query") => "RAG query with LLM generation".to_string(),
+            p if p.contains("/rag/casts") => "Cast RAG query with citations".to_string(),
             p if p.contains("/mcp") => "MCP protocol endpoint".to_string(),
             _ => "API endpoint".to_string(),
         }
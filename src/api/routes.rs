@@ -18,6 +18,7 @@ pub fn api_routes(state: AppState) -> Router {
         // Profile endpoints
         .route("/profiles", get(handlers::list_profiles))
         .route("/profiles/:fid", get(handlers::get_profile))
+        .route("/profiles/:fid/onchain", get(handlers::get_profile_onchain))
         .route(
             "/profiles/username/:username",
             get(handlers::get_profile_by_username),
@@ -30,6 +31,7 @@ pub fn api_routes(state: AppState) -> Router {
         .route("/search/casts", post(handlers::search_casts))
         // RAG endpoints
         .route("/rag/query", post(handlers::rag_query))
+        .route("/rag/casts", post(handlers::rag_casts))
         // Chat endpoints (interactive AI role-play)
         .route("/chat/create", post(handlers::create_chat_session))
         .route("/chat/message", post(handlers::send_chat_message))
@@ -38,6 +40,28 @@ pub fn api_routes(state: AppState) -> Router {
             "/chat/session/:session_id",
             delete(handlers::delete_chat_session),
         )
+        // Webhook subscriptions (change-data-capture, authenticated by [webhooks] api_keys)
+        .route(
+            "/subscriptions",
+            get(handlers::list_subscriptions).post(handlers::create_subscription),
+        )
+        .route(
+            "/subscriptions/:id",
+            get(handlers::get_subscription).delete(handlers::delete_subscription),
+        )
+        .route(
+            "/subscriptions/:id/replay",
+            post(handlers::replay_subscription),
+        )
+        .route(
+            "/subscriptions/:id/dead-letters",
+            get(handlers::list_subscription_dead_letters),
+        )
+        // Live feed of newly synced events
+        .route("/stream/events", get(handlers::stream_events))
+        .route("/stream/ws", get(handlers::stream_events_ws))
+        // Bulk dataset export (authenticated by [export] api_keys)
+        .route("/export", get(handlers::export_data))
         // Statistics
         .route("/stats", get(handlers::get_stats))
         // Prometheus metrics
@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use axum::Router;
+use futures::FutureExt;
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::Any;
 use tower_http::cors::CorsLayer;
@@ -15,6 +16,7 @@ use crate::api::cache::CacheService;
 use crate::api::cache_proxy::cache_proxy_middleware;
 use crate::api::cache_proxy::CacheProxyState;
 use crate::api::handlers::AppState;
+use crate::api::live_feed::LiveFeed;
 use crate::api::mcp;
 use crate::api::metrics;
 #[cfg(feature = "payment")]
@@ -110,6 +112,41 @@ pub async fn serve_api(
         info!("⚠️ Cache service disabled in configuration");
     }
 
+    // Deliver change events recorded by sync to webhook subscribers
+    if config.webhooks.enabled {
+        let dispatcher =
+            crate::webhooks::WebhookDispatcher::new(database.clone(), config.webhooks.clone())?;
+        tokio::spawn(dispatcher.run());
+        info!("✅ Webhook dispatcher started");
+    }
+
+    // Push events published by sync to SSE / WebSocket clients
+    let live_feed = if config.live_feed.enabled {
+        let feed = Arc::new(LiveFeed::new(config.live_feed.buffer_size));
+        let pool = database.pool().clone();
+        tokio::spawn(feed.clone().listen(pool).map(|result| {
+            if let Err(e) = result {
+                tracing::error!("❌ Live feed listener stopped: {}", e);
+            }
+        }));
+        info!("✅ Live feed enabled (/api/stream/events, /api/stream/ws)");
+        Some(feed)
+    } else {
+        None
+    };
+
+    let reranker = crate::rag::rerank::reranker_from_config(config, database.clone()).await?;
+    if let Some(reranker) = &reranker {
+        info!("✅ {} reranker initialized", reranker.name());
+    }
+
+    if !config.export.api_keys.is_empty() {
+        info!("✅ Bulk export enabled (/api/export)");
+    }
+    if !config.webhooks.api_keys.is_empty() {
+        info!("✅ Webhook subscriptions enabled (/api/subscriptions)");
+    }
+
     let state = AppState {
         config: Arc::new(config.clone()),
         database,
@@ -118,6 +155,8 @@ pub async fn serve_api(
         lazy_loader,
         session_manager,
         cache_service,
+        live_feed,
+        reranker,
     };
 
     // Build API routes
@@ -257,7 +296,7 @@ pub async fn serve_api(
         info!("  Free:     /api/health, /api/stats");
         info!("  $0.001:   /api/profiles");
         info!("  $0.01:    /api/search/*");
-        info!("  $0.1:     /api/rag/query");
+        info!("  $0.1:     /api/rag/query, /api/rag/casts");
         info!("");
     }
 
@@ -268,6 +307,7 @@ pub async fn serve_api(
     info!("  POST /api/search/profiles - Search profiles");
     info!("  POST /api/search/casts   - Search casts");
     info!("  POST /api/rag/query      - RAG query");
+    info!("  POST /api/rag/casts      - Cast RAG query with citations");
     info!("  GET  /api/stats          - Statistics");
     info!("");
     info!("  GET  /mcp/               - MCP server info");
@@ -40,6 +40,9 @@ pub struct CreateChatResponse {
 pub struct ChatMessageRequest {
     pub session_id: String,
     pub message: String,
+    /// Reply with a `text/event-stream` of sources, token deltas and metadata
+    #[serde(default)]
+    pub stream: bool,
 }
 
 /// Chat message response
@@ -134,6 +137,9 @@ pub struct CastSearchRequest {
     pub limit: usize,
     #[serde(default = "default_threshold")]
     pub threshold: f32,
+    /// Restrict results to a channel (channel URL or bare name)
+    #[serde(default)]
+    pub channel: Option<String>,
 }
 
 const fn default_threshold() -> f32 {
@@ -152,6 +158,24 @@ pub struct RagQueryRequest {
     pub temperature: f32,
     #[serde(default = "default_max_tokens")]
     pub max_tokens: usize,
+    /// Reply with a `text/event-stream` of sources, token deltas and metadata
+    #[serde(default)]
+    pub stream: bool,
+}
+
+/// Cast RAG query request
+#[derive(Debug, Deserialize)]
+pub struct CastRagQueryRequest {
+    pub question: String,
+    #[serde(default = "default_rag_limit")]
+    pub retrieval_limit: usize,
+    /// Minimum semantic similarity of retrieved casts
+    #[serde(default)]
+    pub threshold: Option<f32>,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: usize,
 }
 
 const fn default_rag_limit() -> usize {
@@ -175,6 +199,37 @@ pub struct ProfileResponse {
     pub github_username: Option<String>,
 }
 
+/// Decoded on-chain state response (addresses and keys are 0x-prefixed hex)
+#[derive(Debug, Serialize)]
+pub struct OnchainStateResponse {
+    pub fid: i64,
+    pub custody_address: Option<String>,
+    pub recovery_address: Option<String>,
+    pub registered_block: Option<i64>,
+    pub registered_at: Option<i64>,
+    pub active_signers: Vec<SignerResponse>,
+    pub storage_units: i64,
+    pub storage_rents: Vec<StorageRentResponse>,
+}
+
+/// Active signer key
+#[derive(Debug, Serialize)]
+pub struct SignerResponse {
+    pub key: String,
+    pub key_type: i32,
+    pub added_block: Option<i64>,
+    pub added_at: Option<i64>,
+}
+
+/// Storage rent entry
+#[derive(Debug, Serialize)]
+pub struct StorageRentResponse {
+    pub units: i64,
+    pub expiry: i64,
+    pub payer: Option<String>,
+    pub block_number: i64,
+}
+
 /// Cast response
 #[derive(Debug, Serialize)]
 pub struct CastResponse {
@@ -246,3 +301,66 @@ pub struct FetchResponse {
     pub embeddings_generated: Option<usize>,
     pub source: String, // "database" or "snapchain"
 }
+
+/// Create webhook subscription request
+#[derive(Debug, Deserialize)]
+pub struct CreateSubscriptionRequest {
+    /// Endpoint receiving the signed POST requests (http or https)
+    pub url: String,
+    /// HMAC-SHA256 signing secret, generated when omitted
+    pub secret: Option<String>,
+    /// Event types to receive (e.g. `link_add`), all when empty
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Author FID of the message
+    pub fid: Option<i64>,
+    /// Followed FID or author of the replied/reacted-to cast
+    pub target_fid: Option<i64>,
+    /// Hex hash of the replied/reacted-to cast
+    pub parent_hash: Option<String>,
+    /// Channel name (e.g. `base`) or parent URL
+    pub channel: Option<String>,
+}
+
+/// Webhook subscription response
+#[derive(Debug, Serialize)]
+pub struct SubscriptionResponse {
+    pub id: i64,
+    pub url: String,
+    /// Only returned when the subscription is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub event_types: Vec<String>,
+    pub fid: Option<i64>,
+    pub target_fid: Option<i64>,
+    pub parent_hash: Option<String>,
+    pub channel: Option<String>,
+    pub active: bool,
+    pub last_delivered_offset: i64,
+    pub created_at: i64,
+}
+
+/// Replay webhook subscription request
+#[derive(Debug, Deserialize)]
+pub struct ReplaySubscriptionRequest {
+    /// First event offset to deliver again
+    #[serde(default)]
+    pub from_offset: i64,
+}
+
+/// Replay webhook subscription response
+#[derive(Debug, Serialize)]
+pub struct ReplaySubscriptionResponse {
+    pub subscription_id: i64,
+    pub from_offset: i64,
+    pub queued: u64,
+}
+
+/// Dead-lettered webhook delivery
+#[derive(Debug, Serialize)]
+pub struct DeadLetterResponse {
+    pub event_id: i64,
+    pub attempts: i32,
+    pub last_error: String,
+    pub failed_at: i64,
+}
@@ -85,6 +85,9 @@ pub enum Commands {
         #[arg(short, long)]
         force: bool,
     },
+    /// Versioned schema migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommands),
     /// Manage database indexes and autovacuum for bulk operations
     #[command(subcommand)]
     Index(IndexCommands),
@@ -94,6 +97,51 @@ pub enum Commands {
     /// Synchronization commands
     #[command(subcommand)]
     Sync(SyncCommands),
+    /// Diff FIDs against the hub's message sets, repair them and print a JSON report
+    Reconcile {
+        /// FID to reconcile
+        #[arg(
+            long,
+            conflicts_with = "fids_file",
+            required_unless_present = "fids_file"
+        )]
+        fid: Option<u64>,
+        /// File with one FID per line (blank lines and `#` comments are ignored)
+        #[arg(long)]
+        fids_file: Option<String>,
+        /// Only report differences, do not write anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Write the JSON report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Export profiles, casts, links or embeddings as CSV, NDJSON or Parquet, or a full archive
+    Export {
+        /// Dataset to export
+        #[arg(value_enum)]
+        table: ExportDataType,
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "ndjson")]
+        format: ExportFileFormat,
+        /// Only rows at or after this time (RFC 3339, YYYY-MM-DD or Unix seconds)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only rows before this time (RFC 3339, YYYY-MM-DD or Unix seconds)
+        #[arg(long)]
+        until: Option<String>,
+        /// Filter by FID range (min-max, min-, -max or a single FID)
+        #[arg(long)]
+        fid_range: Option<String>,
+        /// Output file (a directory for `archive`)
+        #[arg(short, long)]
+        out: String,
+    },
+    /// Load an archive written by `export archive` into an empty database
+    Import {
+        /// Archive directory
+        dir: String,
+    },
     /// Show statistics and analytics (fast overview by default, detailed with --detailed)
     Stats {
         /// Show detailed statistics instead of fast overview
@@ -133,6 +181,14 @@ pub enum Commands {
         #[arg(short, long)]
         detailed: bool,
     },
+    /// Show decoded on-chain state of a FID (custody, signers, storage)
+    Onchain {
+        /// Farcaster ID to query
+        fid: i64,
+        /// Also show removed signers and full storage rent history
+        #[arg(short, long)]
+        detailed: bool,
+    },
     /// Cast commands
     #[command(subcommand)]
     Cast(CastCommands),
@@ -262,6 +318,48 @@ pub enum SyncCommands {
         #[arg(long)]
         interval: Option<u64>,
     },
+    /// Load a shard from a replication snapshot, then continue with block sync
+    Bootstrap {
+        /// Shard ID to bootstrap
+        #[arg(long, default_value = "1")]
+        shard: u32,
+        /// Snapshot height (default: the most recent snapshot)
+        #[arg(long)]
+        height: Option<u64>,
+        /// Record the replication responses to this JSON fixture
+        #[arg(long)]
+        record: Option<std::path::PathBuf>,
+        /// Stop after the snapshot instead of continuing with block sync
+        #[arg(long)]
+        no_follow: bool,
+    },
+    /// Find block ranges that sync skipped
+    Verify {
+        /// Shard IDs to check (comma-separated, default: from config)
+        #[arg(long)]
+        shard: Option<String>,
+        /// First height to check (default: first processed height)
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last height to check (default: last processed height)
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Re-fetch the block ranges that sync skipped
+    Repair {
+        /// Shard IDs to repair (comma-separated, default: from config)
+        #[arg(long)]
+        shard: Option<String>,
+        /// First height to check (default: first processed height)
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last height to check (default: last processed height)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Blocks fetched per request (default: from config)
+        #[arg(long)]
+        batch: Option<u32>,
+    },
     /// Test single block synchronization
     Test {
         /// Shard ID to test
@@ -283,6 +381,29 @@ pub enum SyncCommands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum MigrateCommands {
+    /// Show applied and pending migrations
+    Status,
+    /// Apply pending migrations
+    Up {
+        /// Stop after applying this version (default: latest)
+        #[arg(long)]
+        target: Option<i64>,
+    },
+    /// Roll back the most recently applied migrations
+    Down {
+        /// Number of migrations to roll back
+        #[arg(long, default_value = "1")]
+        steps: usize,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Check applied migrations against the embedded scripts
+    Verify,
+}
+
 #[derive(Subcommand)]
 pub enum IndexCommands {
     /// Disable non-essential indexes and autovacuum for bulk sync (faster writes)
@@ -298,7 +419,11 @@ pub enum IndexCommands {
         force: bool,
     },
     /// Show current index and autovacuum status
-    Status,
+    Status {
+        /// Sampled queries per vector index for the recall estimate (0 to skip)
+        #[arg(long, default_value = "10")]
+        recall_samples: usize,
+    },
 }
 
 #[derive(Subcommand)]
@@ -334,6 +459,23 @@ pub enum CastCommands {
         /// Show detailed information
         #[arg(short, long)]
         detailed: bool,
+        /// Only search casts in this channel (e.g. "base" or a channel URL)
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Get recent casts in a channel
+    Channel {
+        /// Channel name (e.g. "base") or channel URL
+        channel: String,
+        /// Maximum number of casts
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Compute missing thread root hashes for stored replies
+    BackfillRoots {
+        /// Maximum number of passes (each pass resolves one more thread level)
+        #[arg(long, default_value = "32")]
+        max_passes: usize,
     },
     /// Get recent casts by FID
     Recent {
@@ -477,6 +619,11 @@ pub enum EmbeddingsCommands {
         #[command(subcommand)]
         action: CastEmbeddingAction,
     },
+    /// Switch embedding models without downtime (versioned vector columns)
+    Model {
+        #[command(subcommand)]
+        action: EmbeddingModelAction,
+    },
     /// Generate embeddings for cast content (alias for 'cast backfill')
     #[command(name = "backfill-casts")]
     BackfillCasts {
@@ -590,6 +737,43 @@ pub enum CastEmbeddingAction {
     Analyze,
 }
 
+#[derive(Subcommand)]
+pub enum EmbeddingModelAction {
+    /// Show embedding versions and shadow backfill coverage
+    Status,
+    /// Register a new model as the shadow version and add its vector columns
+    Add {
+        /// Model name (served by the configured embeddings provider and endpoint)
+        model: String,
+        /// Embedding dimension produced by the model
+        #[arg(long)]
+        dimension: usize,
+    },
+    /// Backfill the shadow version while search keeps using the active one
+    Backfill {
+        /// Process in batches of N casts or profiles
+        #[arg(short, long, default_value = "100")]
+        batch_size: usize,
+        /// Maximum number of casts and of profiles to process
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Atomically switch search to the shadow version
+    Cutover {
+        /// Minimum fraction (0.0-1.0) of active vectors the shadow version must cover
+        #[arg(long, default_value = "0.99")]
+        min_coverage: f64,
+    },
+    /// Remove the vectors of a retired or abandoned shadow version
+    Prune {
+        /// Embedding version to prune
+        version: i32,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum ServeCommands {
     /// Start API server (`RESTful` + MCP)
@@ -626,6 +810,40 @@ pub enum EmbeddingDataType {
     Cast,
 }
 
+#[derive(ValueEnum, Clone, Copy)]
+pub enum ExportDataType {
+    /// Current user profiles
+    Profiles,
+    /// Profile field messages
+    ProfileChanges,
+    /// Casts
+    Casts,
+    /// Links (follows)
+    Links,
+    /// Reactions (likes and recasts)
+    Reactions,
+    /// Cast embeddings
+    CastEmbeddings,
+    /// Profile, bio and interests embeddings
+    ProfileEmbeddings,
+    /// Processed block ranges per shard
+    ProcessedBlocks,
+    /// Sync progress per shard
+    SyncProgress,
+    /// Every table needed to restore the instance with `snaprag import`
+    Archive,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum ExportFileFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+    /// Columnar Parquet
+    Parquet,
+}
+
 #[derive(ValueEnum, Clone)]
 pub enum DataType {
     /// List FIDs (user IDs)
@@ -1,3 +1,7 @@
+use std::io::Write;
+
+use futures::StreamExt;
+
 use super::retrieval::analyze_writing_style;
 use super::retrieval::Spinner;
 /// LLM interaction (prompt building and calling)
@@ -30,6 +34,96 @@ pub async fn generate_ai_response_with_social(
     temperature: f32,
     social_profile: Option<&crate::social_graph::SocialProfile>,
 ) -> Result<String> {
+    let context = build_ai_prompt(profile, casts, question, history, social_profile);
+
+    // Generate response with spinner
+    let spinner = Spinner::new("Thinking");
+    spinner.start();
+
+    let response = llm
+        .generate_with_params(&context, adjusted_temperature(casts, temperature), 2000)
+        .await?;
+
+    spinner.stop();
+
+    Ok(response)
+}
+
+/// Generate AI response, printing tokens as they arrive
+///
+/// Returns the complete response once the model is done.
+pub async fn stream_ai_response(
+    llm: &LlmService,
+    profile: &crate::models::UserProfile,
+    casts: &[crate::models::CastSearchResult],
+    question: &str,
+    history: Option<&Vec<(String, String)>>,
+    temperature: f32,
+) -> Result<String> {
+    let context = build_ai_prompt(profile, casts, question, history, None);
+
+    // The spinner runs until the first token
+    let spinner = Spinner::new("Thinking");
+    spinner.start();
+    let result = print_tokens(
+        llm,
+        &context,
+        adjusted_temperature(casts, temperature),
+        &spinner,
+    )
+    .await;
+    spinner.stop();
+    println!();
+
+    result
+}
+
+async fn print_tokens(
+    llm: &LlmService,
+    context: &str,
+    temperature: f32,
+    spinner: &Spinner,
+) -> Result<String> {
+    let mut tokens = llm
+        .generate_stream_with_params(context, temperature, 2000)
+        .await?
+        .into_stream();
+
+    let mut response = String::new();
+    while let Some(delta) = tokens.next().await {
+        let delta = delta?;
+        if response.is_empty() {
+            spinner.stop();
+        }
+        print!("{delta}");
+        std::io::stdout().flush()?;
+        response.push_str(&delta);
+    }
+    Ok(response)
+}
+
+/// Use lower temperature for very concise styles
+fn adjusted_temperature(casts: &[crate::models::CastSearchResult], temperature: f32) -> f32 {
+    if casts.is_empty() {
+        temperature
+    } else {
+        let avg_len: usize = casts.iter().map(|c| c.text.len()).sum::<usize>() / casts.len().max(1);
+        if avg_len < 80 {
+            temperature.min(0.5) // Lower temp for brief styles
+        } else {
+            temperature
+        }
+    }
+}
+
+/// Build the role-play prompt for a question
+fn build_ai_prompt(
+    profile: &crate::models::UserProfile,
+    casts: &[crate::models::CastSearchResult],
+    question: &str,
+    history: Option<&Vec<(String, String)>>,
+    social_profile: Option<&crate::social_graph::SocialProfile>,
+) -> String {
     let fid = profile.fid;
     let display_name = profile.display_name.as_deref().unwrap_or("Unknown");
     let username = profile.username.as_deref();
@@ -141,29 +235,7 @@ pub async fn generate_ai_response_with_social(
     // Log context in debug mode for troubleshooting
     tracing::debug!("=== LLM PROMPT ===\n{}\n=== END PROMPT ===", context);
 
-    // Generate response with spinner
-    let spinner = Spinner::new("Thinking");
-    spinner.start();
-
-    // Use lower temperature for very concise styles
-    let adjusted_temp = if casts.is_empty() {
-        temperature
-    } else {
-        let avg_len: usize = casts.iter().map(|c| c.text.len()).sum::<usize>() / casts.len().max(1);
-        if avg_len < 80 {
-            temperature.min(0.5) // Lower temp for brief styles
-        } else {
-            temperature
-        }
-    };
-
-    let response = llm
-        .generate_with_params(&context, adjusted_temp, 2000)
-        .await?;
-
-    spinner.stop();
-
-    Ok(response)
+    context
 }
 
 /// Format social profile for LLM context
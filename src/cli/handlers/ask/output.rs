@@ -7,6 +7,13 @@ pub fn display_response(
     total_casts: usize,
     relevant_casts: usize,
 ) {
+    print_response_header(profile);
+    print_wrapped(response, 70);
+    print_response_footer(total_casts, relevant_casts);
+}
+
+/// Box naming the user who answers
+pub fn print_response_header(profile: &crate::models::UserProfile) {
     let username = profile
         .username
         .as_ref()
@@ -17,9 +24,10 @@ pub fn display_response(
     println!("║  {display_name} ({username})                                           ");
     println!("╚═══════════════════════════════════════════════════════════════╝");
     println!();
+}
 
-    print_wrapped(response, 70);
-
+/// Summary of the casts an answer is based on
+pub fn print_response_footer(total_casts: usize, relevant_casts: usize) {
     println!();
     println!("─────────────────────────────────────────────────────────────────");
     println!("💬 Based on {total_casts} casts  |  🎯 Context: {relevant_casts} relevant casts");
@@ -4,6 +4,8 @@ use std::io::Write;
 use crate::cli::output::truncate_str;
 use crate::database::Database;
 use crate::embeddings::EmbeddingService;
+use crate::rag::rerank::rerank;
+use crate::rag::Rerank;
 use crate::Result;
 
 /// Simple spinner for showing progress
@@ -50,13 +52,18 @@ impl Spinner {
     }
 }
 
-/// Find relevant casts using semantic search and heuristics
+/// Find relevant casts using semantic search and a reranker
+///
+/// The `rerank_candidates` most similar casts of the user are reranked and
+/// cut to `context_limit`.
 ///
 /// # Panics
 /// Panics if the system time is before UNIX_EPOCH (1970-01-01), which is impossible on modern systems
 pub async fn find_relevant_casts(
     database: &Database,
     embedding_service: &EmbeddingService,
+    reranker: &dyn Rerank,
+    rerank_candidates: usize,
     fid: u64,
     question: &str,
     context_limit: usize,
@@ -85,37 +92,16 @@ pub async fn find_relevant_casts(
         search_results.len()
     );
 
-    spinner.stop();
-
     // Filter to only include casts from this FID
     let mut user_casts: Vec<_> = search_results
         .into_iter()
         .filter(|result| result.fid == fid as i64)
         .collect();
+    user_casts.truncate(rerank_candidates.max(context_limit));
 
-    // Calculate recency score - newer posts get higher weight
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    // Prioritize: relevance + substance + recency
-    // Score = similarity * log(length) * recency_factor
-    user_casts.sort_by(|a, b| {
-        // Recency factor: 1.0 for recent (< 30 days), decays to 0.5 for old (> 1 year)
-        let age_days_first = ((now - a.timestamp) as f32) / 86400.0;
-        let age_days_second = ((now - b.timestamp) as f32) / 86400.0;
-        let recency_a = (1.0 - (age_days_first / 365.0).min(0.5)).max(0.5);
-        let recency_b = (1.0 - (age_days_second / 365.0).min(0.5)).max(0.5);
-
-        // Combine: similarity (most important) + substance + recency
-        let score_a = a.similarity * (a.text.len() as f32).ln().max(1.0) * recency_a;
-        let score_b = b.similarity * (b.text.len() as f32).ln().max(1.0) * recency_b;
-
-        score_b
-            .partial_cmp(&score_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    let mut user_casts = rerank(reranker, question, user_casts).await;
+
+    spinner.stop();
 
     // Take top results
     user_casts.truncate(context_limit);
@@ -124,7 +110,7 @@ pub async fn find_relevant_casts(
 
     if verbose && !user_relevant_casts.is_empty() {
         println!();
-        println!("   📋 Top relevant casts (sorted by: relevance × substance × recency):");
+        println!("   📋 Top relevant casts (ranked by {}):", reranker.name());
 
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -149,7 +135,7 @@ pub async fn find_relevant_casts(
             };
 
             println!(
-                "      {}. [sim: {:.2}, len: {}, age: {}] {}",
+                "      {}. [score: {:.2}, len: {}, age: {}] {}",
                 idx + 1,
                 result.similarity,
                 result.text.len(),
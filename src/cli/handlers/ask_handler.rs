@@ -14,9 +14,9 @@ use hex;
 
 // Import the new ask module
 use crate::cli::handlers::ask::args::parse_user_identifier;
-use crate::cli::handlers::ask::llm::generate_ai_response;
-use crate::cli::handlers::ask::output::display_response;
-use crate::cli::handlers::ask::output::print_wrapped;
+use crate::cli::handlers::ask::llm::stream_ai_response;
+use crate::cli::handlers::ask::output::print_response_footer;
+use crate::cli::handlers::ask::output::print_response_header;
 use crate::cli::handlers::ask::retrieval::analyze_writing_style;
 use crate::cli::handlers::ask::retrieval::find_relevant_casts;
 use crate::cli::handlers::ask::retrieval::Spinner;
@@ -28,6 +28,9 @@ use crate::cli::output::truncate_str;
 use crate::database::Database;
 use crate::embeddings::EmbeddingService;
 use crate::llm::LlmService;
+use crate::rag::rerank::reranker_from_config;
+use crate::rag::HeuristicReranker;
+use crate::rag::Rerank;
 use crate::social_graph::SocialGraphAnalyzer;
 use crate::sync::client::SnapchainClient;
 use crate::sync::lazy_loader::LazyLoader;
@@ -51,6 +54,16 @@ pub async fn handle_ask(
     let embedding_service = Arc::new(EmbeddingService::new(config)?);
     let llm_service = Arc::new(LlmService::new(config)?);
 
+    // Rank context casts with the configured reranker, or by similarity and recency
+    let reranker: Arc<dyn Rerank> = match reranker_from_config(config, database.clone()).await? {
+        Some(reranker) => reranker,
+        None => Arc::new(HeuristicReranker::new(
+            config.rerank.weights,
+            config.rerank.recency_half_life_days,
+        )),
+    };
+    let rerank_candidates = config.rerank.candidates;
+
     // Parse user identifier (FID or username)
     let fid = parse_user_identifier(&user_identifier, &database).await?;
 
@@ -72,6 +85,8 @@ pub async fn handle_ask(
             &database,
             &embedding_service,
             &llm_service,
+            reranker.as_ref(),
+            rerank_candidates,
             fid,
             &profile,
             &casts,
@@ -90,6 +105,8 @@ pub async fn handle_ask(
             &database,
             &embedding_service,
             &llm_service,
+            reranker.as_ref(),
+            rerank_candidates,
             fid,
             &profile,
             &casts,
@@ -348,6 +365,8 @@ async fn answer_single_question(
     database: &Database,
     embedding_service: &EmbeddingService,
     llm_service: &LlmService,
+    reranker: &dyn Rerank,
+    rerank_candidates: usize,
     fid: u64,
     profile: &crate::models::UserProfile,
     casts: &[crate::models::Cast],
@@ -360,6 +379,8 @@ async fn answer_single_question(
     let relevant_casts = find_relevant_casts(
         database,
         embedding_service,
+        reranker,
+        rerank_candidates,
         fid,
         question,
         context_limit,
@@ -380,8 +401,9 @@ async fn answer_single_question(
     }
     println!();
 
-    // Generate response (spinner shows "Thinking...")
-    let response = generate_ai_response(
+    // Stream the response (spinner shows "Thinking..." until the first token)
+    print_response_header(profile);
+    stream_ai_response(
         llm_service,
         profile,
         &relevant_casts,
@@ -390,9 +412,7 @@ async fn answer_single_question(
         temperature,
     )
     .await?;
-
-    // Display response
-    display_response(profile, &response, casts.len(), relevant_casts.len());
+    print_response_footer(casts.len(), relevant_casts.len());
 
     Ok(())
 }
@@ -402,6 +422,8 @@ async fn run_interactive_chat(
     database: &Database,
     embedding_service: &EmbeddingService,
     llm_service: &LlmService,
+    reranker: &dyn Rerank,
+    rerank_candidates: usize,
     fid: u64,
     profile: &crate::models::UserProfile,
     casts: &[crate::models::Cast],
@@ -502,6 +524,8 @@ async fn run_interactive_chat(
         let relevant_casts = find_relevant_casts(
             database,
             embedding_service,
+            reranker,
+            rerank_candidates,
             fid,
             question,
             context_limit,
@@ -518,8 +542,12 @@ async fn run_interactive_chat(
             println!("   ✅ Found {} relevant casts", relevant_casts.len());
         }
 
-        // Generate response with conversation history (spinner shows "Thinking...")
-        let response = generate_ai_response(
+        // Stream the response with conversation history
+        println!();
+        println!("{display_name}:");
+        println!();
+
+        let response = stream_ai_response(
             llm_service,
             profile,
             &relevant_casts,
@@ -529,14 +557,6 @@ async fn run_interactive_chat(
         )
         .await?;
 
-        // Display response
-        println!();
-        println!("{display_name}:");
-        println!();
-
-        // Word wrap the response
-        print_wrapped(&response, 70);
-
         println!();
         println!("─────────────────────────────────────────────────────────────────");
         println!();
@@ -18,10 +18,18 @@ pub async fn handle_cast_search(
     limit: usize,
     threshold: f32,
     detailed: bool,
+    channel: Option<String>,
 ) -> Result<()> {
     use crate::embeddings::EmbeddingService;
 
-    print_info(&format!("🔍 Searching casts: \"{query}\""));
+    if let Some(channel) = &channel {
+        print_info(&format!(
+            "🔍 Searching casts in /{}: \"{query}\"",
+            channel_name(channel)
+        ));
+    } else {
+        print_info(&format!("🔍 Searching casts: \"{query}\""));
+    }
 
     // Check if we have any cast embeddings
     let embed_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cast_embeddings")
@@ -42,7 +50,12 @@ pub async fn handle_cast_search(
     // Search casts
     let results = snaprag
         .database()
-        .semantic_search_casts(query_embedding, limit as i64, Some(threshold))
+        .semantic_search_casts(
+            query_embedding,
+            limit as i64,
+            Some(threshold),
+            channel.as_deref(),
+        )
         .await?;
 
     if results.is_empty() {
@@ -173,6 +186,79 @@ pub async fn handle_cast_recent(snaprag: &SnapRag, fid: i64, limit: usize) -> Re
     Ok(())
 }
 
+/// Handle cast channel command
+pub async fn handle_cast_channel(snaprag: &SnapRag, channel: String, limit: usize) -> Result<()> {
+    print_info(&format!("📺 Recent casts in /{}", channel_name(&channel)));
+
+    let casts = snaprag
+        .database()
+        .list_casts(crate::models::CastQuery {
+            fid: None,
+            text_search: None,
+            parent_hash: None,
+            root_hash: None,
+            channel: Some(channel.clone()),
+            has_mentions: None,
+            has_embeds: None,
+            start_timestamp: None,
+            end_timestamp: None,
+            limit: Some(limit as i64),
+            offset: Some(0),
+            sort_by: Some(crate::models::CastSortBy::Timestamp),
+            sort_order: Some(crate::models::SortOrder::Desc),
+        })
+        .await?;
+
+    if casts.is_empty() {
+        print_warning(&format!("No casts found in channel '{channel}'"));
+        return Ok(());
+    }
+
+    println!("\n📅 Recent Casts ({} total):", casts.len());
+    println!("{}", "─".repeat(100));
+
+    for (idx, cast) in casts.iter().enumerate() {
+        let timestamp_str = chrono::DateTime::from_timestamp(cast.timestamp, 0).map_or_else(
+            || "Unknown".to_string(),
+            |dt| dt.format("%Y-%m-%d %H:%M").to_string(),
+        );
+
+        println!("{}. FID {} | {}", idx + 1, cast.fid, timestamp_str);
+        if let Some(text) = &cast.text {
+            println!("   {}", truncate_str(text, 200));
+        } else {
+            println!("   (No text content)");
+        }
+        println!();
+    }
+
+    println!("{}", "─".repeat(100));
+
+    Ok(())
+}
+
+/// Handle cast root hash backfill command
+pub async fn handle_cast_backfill_roots(snaprag: &SnapRag, max_passes: usize) -> Result<()> {
+    print_info("🧵 Backfilling cast thread root hashes...");
+
+    let updated = snaprag
+        .database()
+        .backfill_cast_root_hashes(max_passes)
+        .await?;
+
+    print_info(&format!("✅ Resolved root hash for {updated} casts"));
+
+    Ok(())
+}
+
+/// Short channel name for display ("base" for "https://warpcast.com/~/channel/base")
+fn channel_name(channel: &str) -> &str {
+    channel
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or(channel)
+}
+
 /// Handle cast thread command
 ///
 /// # Panics
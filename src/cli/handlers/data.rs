@@ -137,6 +137,7 @@ pub async fn handle_list_command(
                 text_search: search,
                 parent_hash: None,
                 root_hash: None,
+                channel: None,
                 has_mentions: None,
                 has_embeds: None,
                 start_timestamp: None,
@@ -415,3 +416,69 @@ pub async fn handle_activity_command(
 
     Ok(())
 }
+
+/// Handle onchain command
+pub async fn handle_onchain_command(snaprag: &SnapRag, fid: i64, detailed: bool) -> Result<()> {
+    print_info(&format!("⛓️  On-chain state for FID {fid}"));
+
+    let database = snaprag.database();
+    let onchain = database.get_onchain_state(fid).await?;
+    let format_time = |timestamp: i64| {
+        chrono::DateTime::from_timestamp(timestamp, 0).map_or_else(
+            || "Unknown".to_string(),
+            |dt| dt.format("%Y-%m-%d %H:%M").to_string(),
+        )
+    };
+
+    println!("\n🪪 Registration:");
+    if let Some(registration) = &onchain.registration {
+        if let Some(custody) = &registration.custody_address {
+            println!("  Custody:  0x{}", hex::encode(custody));
+        }
+        if let Some(recovery) = &registration.recovery_address {
+            println!("  Recovery: 0x{}", hex::encode(recovery));
+        }
+        if let Some(registered_at) = registration.registered_at {
+            println!(
+                "  Registered: {} (block {})",
+                format_time(registered_at),
+                registration.registered_block.unwrap_or_default()
+            );
+        }
+    } else {
+        print_warning("  No ID_REGISTER events synced for this FID");
+    }
+
+    let signers = if detailed {
+        database.get_fid_signers(fid).await?
+    } else {
+        onchain.active_signers.clone()
+    };
+    println!(
+        "\n🔑 Signers ({} active):",
+        signers.iter().filter(|signer| signer.active).count()
+    );
+    for signer in &signers {
+        let status = if signer.active { "✅" } else { "❌" };
+        let since = signer
+            .added_at
+            .map_or_else(|| "Unknown".to_string(), format_time);
+        println!("  {status} 0x{} (added {since})", hex::encode(&signer.key));
+    }
+
+    println!("\n💾 Storage: {} active units", onchain.storage_units);
+    let now = chrono::Utc::now().timestamp();
+    for rent in &onchain.storage_rents {
+        if !detailed && rent.expiry <= now {
+            continue;
+        }
+        println!(
+            "  {} units, expires {} (block {})",
+            rent.units,
+            format_time(rent.expiry),
+            rent.block_number
+        );
+    }
+
+    Ok(())
+}
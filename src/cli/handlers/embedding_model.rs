@@ -0,0 +1,215 @@
+//! Embedding model migration handlers
+//!
+//! A new model is added as a shadow version, backfilled next to the active
+//! one and then cut over atomically; search keeps working throughout.
+
+use crate::cli::commands::EmbeddingModelAction;
+use crate::cli::output::print_info;
+use crate::cli::output::print_success;
+use crate::cli::output::print_warning;
+use crate::database::Database;
+use crate::database::EmbeddingVersion;
+use crate::embeddings::backfill_shadow_embeddings;
+use crate::embeddings::embedding_service_for_version;
+use crate::embeddings::EmbeddingConfig;
+use crate::AppConfig;
+use crate::Result;
+use crate::SnapRagError;
+
+/// Handle embedding model migration commands
+pub async fn handle_embedding_model_command(
+    config: &AppConfig,
+    action: &EmbeddingModelAction,
+) -> Result<()> {
+    let database = Database::from_config(config).await?;
+    ensure_base_version(config, &database).await?;
+
+    match action {
+        EmbeddingModelAction::Status => handle_model_status(&database).await,
+        EmbeddingModelAction::Add { model, dimension } => {
+            handle_model_add(&database, model, *dimension).await
+        }
+        EmbeddingModelAction::Backfill { batch_size, limit } => {
+            handle_model_backfill(config, &database, *batch_size, *limit).await
+        }
+        EmbeddingModelAction::Cutover { min_coverage } => {
+            handle_model_cutover(config, &database, *min_coverage).await
+        }
+        EmbeddingModelAction::Prune { version, force } => {
+            handle_model_prune(&database, *version, *force).await
+        }
+    }
+}
+
+/// Register the configured model as version 1 on first use
+async fn ensure_base_version(config: &AppConfig, database: &Database) -> Result<()> {
+    if database.active_embedding_version().await?.is_some() {
+        return Ok(());
+    }
+
+    database
+        .verify_embedding_dimension(config.embedding_dimension())
+        .await?;
+    let base = database
+        .register_base_embedding_version(config.embedding_model(), config.embedding_dimension())
+        .await?;
+    print_info(&format!(
+        "Registered {} ({} dims) as embedding version {}",
+        base.model, base.dimension, base.version
+    ));
+    Ok(())
+}
+
+async fn handle_model_status(database: &Database) -> Result<()> {
+    let versions = database.list_embedding_versions().await?;
+
+    println!("\n🧬 Embedding Versions");
+    println!("═══════════════════════════════════════════════════════════════");
+    println!(
+        "{:<8} {:<36} {:>6} {:<10} {:<20}",
+        "Version", "Model", "Dims", "State", "Since"
+    );
+    for version in &versions {
+        let since = version
+            .pruned_at
+            .or(version.retired_at)
+            .or(version.activated_at)
+            .unwrap_or(version.created_at);
+        let state = if version.pruned_at.is_some() {
+            "pruned"
+        } else {
+            version.state.as_str()
+        };
+        println!(
+            "{:<8} {:<36} {:>6} {:<10} {:<20}",
+            version.version,
+            version.model,
+            version.dimension,
+            state,
+            since.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    if let Some(shadow) = database.shadow_embedding_version().await? {
+        print_coverage(database, &shadow).await?;
+    } else {
+        println!();
+        print_info(
+            "No shadow version, add one with: snaprag embeddings model add <model> --dimension <n>",
+        );
+    }
+
+    Ok(())
+}
+
+async fn print_coverage(database: &Database, shadow: &EmbeddingVersion) -> Result<()> {
+    let coverage = database.embedding_coverage(shadow).await?;
+
+    println!("\n📈 Shadow coverage (version {}):", shadow.version);
+    println!(
+        "  Casts:    {}/{}",
+        coverage.casts_covered, coverage.casts_total
+    );
+    println!(
+        "  Profiles: {}/{} vectors",
+        coverage.profiles_covered, coverage.profiles_total
+    );
+    println!("  Overall:  {:.2}%", coverage.ratio() * 100.0);
+    Ok(())
+}
+
+async fn handle_model_add(database: &Database, model: &str, dimension: usize) -> Result<()> {
+    if dimension == 0 {
+        return Err(SnapRagError::Custom(
+            "Embedding dimension must be positive".to_string(),
+        ));
+    }
+
+    let version = database
+        .add_shadow_embedding_version(model, dimension)
+        .await?;
+    print_success(&format!(
+        "Added {} ({} dims) as shadow embedding version {}",
+        version.model, version.dimension, version.version
+    ));
+    print_info("Next: snaprag embeddings model backfill");
+    Ok(())
+}
+
+async fn handle_model_backfill(
+    config: &AppConfig,
+    database: &Database,
+    batch_size: usize,
+    limit: Option<usize>,
+) -> Result<()> {
+    let shadow = database.shadow_embedding_version().await?.ok_or_else(|| {
+        SnapRagError::Custom("No shadow embedding version to backfill".to_string())
+    })?;
+
+    print_info(&format!(
+        "Backfilling version {} ({}) - search keeps using the active model",
+        shadow.version, shadow.model
+    ));
+    let service =
+        embedding_service_for_version(&EmbeddingConfig::from_app_config(config), &shadow)?;
+    let stats = backfill_shadow_embeddings(database, &service, &shadow, batch_size, limit).await?;
+
+    print_success(&format!(
+        "Embedded {} casts and {} profiles",
+        stats.casts_embedded, stats.profiles_embedded
+    ));
+    if stats.casts_failed + stats.profiles_failed > 0 {
+        print_warning(&format!(
+            "{} casts and {} profiles failed, re-run backfill to retry",
+            stats.casts_failed, stats.profiles_failed
+        ));
+    }
+    print_coverage(database, &shadow).await
+}
+
+async fn handle_model_cutover(
+    config: &AppConfig,
+    database: &Database,
+    min_coverage: f64,
+) -> Result<()> {
+    if !(0.0..=1.0).contains(&min_coverage) {
+        return Err(SnapRagError::Custom(
+            "--min-coverage must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    let active = database.cutover_embedding_version(min_coverage).await?;
+    print_success(&format!(
+        "Search now uses embedding version {} ({}, {} dims)",
+        active.version, active.model, active.dimension
+    ));
+
+    if config.embedding_model() != active.model
+        || config.embedding_dimension() != active.dimension()
+    {
+        print_warning(&format!(
+            "Update config.toml so new embeddings use the active model:\n  \
+             [embeddings]\n  model = \"{}\"\n  dimension = {}",
+            active.model, active.dimension
+        ));
+    }
+    print_info("The previous version is retired; remove its vectors with: snaprag embeddings model prune <version>");
+    Ok(())
+}
+
+async fn handle_model_prune(database: &Database, version: i32, force: bool) -> Result<()> {
+    if !force {
+        print_warning(&format!(
+            "This will permanently remove all vectors of embedding version {version}."
+        ));
+        println!("Use --force to confirm and proceed.");
+        return Ok(());
+    }
+
+    let pruned = database.prune_embedding_version(version).await?;
+    print_success(&format!(
+        "Pruned embedding version {} ({})",
+        pruned.version, pruned.model
+    ));
+    Ok(())
+}
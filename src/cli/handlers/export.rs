@@ -0,0 +1,160 @@
+//! Bulk dataset export handler
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use crate::cli::commands::ExportDataType;
+use crate::cli::commands::ExportFileFormat;
+use crate::cli::output::print_info;
+use crate::cli::output::print_success;
+use crate::export::archive::write_archive;
+use crate::export::export_chunks;
+use crate::export::parse_time;
+use crate::export::ExportFormat;
+use crate::export::ExportQuery;
+use crate::export::ExportTable;
+use crate::Result;
+use crate::SnapRag;
+
+/// Table behind a dataset; `None` for a full archive
+const fn export_table(table: ExportDataType) -> Option<ExportTable> {
+    Some(match table {
+        ExportDataType::Profiles => ExportTable::Profiles,
+        ExportDataType::ProfileChanges => ExportTable::ProfileChanges,
+        ExportDataType::Casts => ExportTable::Casts,
+        ExportDataType::Links => ExportTable::Links,
+        ExportDataType::Reactions => ExportTable::Reactions,
+        ExportDataType::CastEmbeddings => ExportTable::CastEmbeddings,
+        ExportDataType::ProfileEmbeddings => ExportTable::ProfileEmbeddings,
+        ExportDataType::ProcessedBlocks => ExportTable::ProcessedBlocks,
+        ExportDataType::SyncProgress => ExportTable::SyncProgress,
+        ExportDataType::Archive => return None,
+    })
+}
+
+impl From<ExportFileFormat> for ExportFormat {
+    fn from(format: ExportFileFormat) -> Self {
+        match format {
+            ExportFileFormat::Csv => Self::Csv,
+            ExportFileFormat::Ndjson => Self::Ndjson,
+            ExportFileFormat::Parquet => Self::Parquet,
+        }
+    }
+}
+
+/// Export a table to a file, or every archived table to a directory
+///
+/// The export is written under a `.tmp` name and only renamed to `out`
+/// once it is complete.
+pub async fn handle_export_command(
+    snaprag: &SnapRag,
+    table: ExportDataType,
+    format: ExportFileFormat,
+    since: Option<String>,
+    until: Option<String>,
+    fid_range: Option<String>,
+    out: String,
+) -> Result<()> {
+    snaprag.database().verify_schema_or_error().await?;
+
+    let Some(table) = export_table(table) else {
+        if since.is_some() || until.is_some() || fid_range.is_some() {
+            return Err(crate::SnapRagError::Custom(
+                "An archive holds every row; --since, --until and --fid-range do not apply"
+                    .to_string(),
+            ));
+        }
+        if !matches!(format, ExportFileFormat::Ndjson) {
+            return Err(crate::SnapRagError::Custom(
+                "Archives are always written as NDJSON".to_string(),
+            ));
+        }
+        return export_archive(snaprag, &out).await;
+    };
+
+    let query = ExportQuery {
+        table,
+        since: since.as_deref().map(parse_time).transpose()?,
+        until: until.as_deref().map(parse_time).transpose()?,
+        fids: fid_range
+            .as_deref()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    query.validate()?;
+    let format = ExportFormat::from(format);
+
+    let path = Path::new(&out);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_file_name(format!(
+        "{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+
+    print_info(&format!(
+        "Exporting {} as {} to {}",
+        query.table.name(),
+        format.extension(),
+        out
+    ));
+    let mut file = BufWriter::new(File::create(&tmp)?);
+    let result = export_chunks(
+        snaprag.database(),
+        &query,
+        format,
+        snaprag.config.export.fetch_size,
+        |chunk| std::future::ready(file.write_all(&chunk).map_err(Into::into)),
+    )
+    .await
+    .and_then(|rows| {
+        file.flush()?;
+        Ok(rows)
+    });
+    drop(file);
+
+    match result {
+        Ok(rows) => {
+            std::fs::rename(&tmp, path)?;
+            print_success(&format!(
+                "Exported {} {} rows to {}",
+                rows,
+                query.table.name(),
+                out
+            ));
+            Ok(())
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+async fn export_archive(snaprag: &SnapRag, out: &str) -> Result<()> {
+    print_info(&format!("Archiving the database to {out}"));
+    let manifest = write_archive(
+        snaprag.database(),
+        &snaprag.config,
+        Path::new(out),
+        snaprag.config.export.fetch_size,
+    )
+    .await?;
+
+    for entry in &manifest.tables {
+        println!("  {:<20} {:>12} rows", entry.table.name(), entry.rows);
+    }
+    print_success(&format!(
+        "Archive {} written to {} (schema version {}, {}-dimensional {} embeddings)",
+        manifest.archive_id,
+        out,
+        manifest.schema_version,
+        manifest.embedding_dimension,
+        manifest.embedding_model
+    ));
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Archive import handler
+
+use std::path::Path;
+
+use crate::cli::output::print_info;
+use crate::cli::output::print_success;
+use crate::export::archive::import_archive;
+use crate::Result;
+use crate::SnapRag;
+
+/// Load an archive written by `snaprag export archive`
+///
+/// Rerunning the command after an interruption resumes the import.
+pub async fn handle_import_command(snaprag: &SnapRag, dir: &str) -> Result<()> {
+    snaprag.database().verify_schema_or_error().await?;
+
+    print_info(&format!("Importing archive {dir}"));
+    let manifest = import_archive(
+        snaprag.database(),
+        Path::new(dir),
+        snaprag.config.export.fetch_size,
+    )
+    .await?;
+
+    for entry in &manifest.tables {
+        println!("  {:<20} {:>12} rows", entry.table.name(), entry.rows);
+    }
+    print_success(&format!(
+        "Imported archive {} taken at {}; the next sync starts from the beginning to fill in the remaining tables",
+        manifest.archive_id,
+        manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    Ok(())
+}
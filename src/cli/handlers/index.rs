@@ -10,15 +10,23 @@ use std::io::{
 };
 
 use crate::cli::commands::IndexCommands;
+use crate::config::VectorIndexType;
+use crate::database::VectorIndexSettings;
+use crate::database::MAX_INDEXED_VECTOR_DIMENSION;
 use crate::errors::Result;
 use crate::SnapRag;
 
+/// Neighbours compared per sampled query when estimating recall
+const RECALL_K: usize = 10;
+
 /// Handle index management commands
 pub async fn handle_index_command(snaprag: &SnapRag, command: &IndexCommands) -> Result<()> {
     match command {
         IndexCommands::Unset { force } => handle_index_unset(snaprag, *force).await,
         IndexCommands::Set { force } => handle_index_set(snaprag, *force).await,
-        IndexCommands::Status => handle_index_status(snaprag).await,
+        IndexCommands::Status { recall_samples } => {
+            handle_index_status(snaprag, *recall_samples).await
+        }
     }
 }
 
@@ -163,7 +171,7 @@ async fn handle_index_set(snaprag: &SnapRag, force: bool) -> Result<()> {
 
     println!("\n✅ This will:");
     println!("  1. Recreate all non-essential indexes (CONCURRENTLY, won't block writes)");
-    println!("  2. Skip vector indexes (already exist, not affected during sync)");
+    println!("  2. Ensure vector indexes exist (not dropped during sync)");
     println!("  3. Re-enable autovacuum on all tables");
     println!("  4. Run VACUUM ANALYZE to optimize query performance");
     println!("\n⏱️  This may take 30-60 minutes for large datasets.\n");
@@ -209,8 +217,8 @@ async fn handle_index_set(snaprag: &SnapRag, force: bool) -> Result<()> {
         // Cast embedding aggregated - basic indexes
         ("idx_cast_embedding_aggregated_fid", "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_cast_embedding_aggregated_fid ON cast_embedding_aggregated(fid)"),
         ("idx_cast_embedding_aggregated_message_hash", "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_cast_embedding_aggregated_message_hash ON cast_embedding_aggregated(message_hash)"),
-        // Note: Vector indexes (idx_cast_embedding_*_embedding_cosine) are NOT recreated here
-        // because they don't need to be dropped during sync (no vector data during sync)
+        // Note: Vector indexes (idx_cast_embedding_*_embedding_cosine) are only ensured below,
+        // they don't need to be dropped during sync (no vector data during sync)
         // User profiles
         ("idx_user_profiles_username", "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_user_profiles_username ON user_profiles(username)"),
         ("idx_user_profiles_display_name", "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_user_profiles_display_name ON user_profiles(display_name)"),
@@ -255,6 +263,8 @@ async fn handle_index_set(snaprag: &SnapRag, force: bool) -> Result<()> {
         }
     }
 
+    ensure_vector_indexes(snaprag).await?;
+
     println!("\n🔄 Re-enabling autovacuum...");
 
     // Re-enable autovacuum on all main tables
@@ -305,7 +315,7 @@ async fn handle_index_set(snaprag: &SnapRag, force: bool) -> Result<()> {
 }
 
 /// Show current status of indexes and autovacuum
-async fn handle_index_status(snaprag: &SnapRag) -> Result<()> {
+async fn handle_index_status(snaprag: &SnapRag, recall_samples: usize) -> Result<()> {
     let db = snaprag.database.pool();
 
     println!("\n📊 Database Index & Autovacuum Status\n");
@@ -449,36 +459,10 @@ async fn handle_index_status(snaprag: &SnapRag) -> Result<()> {
         trigram_indexes.len()
     );
 
-    // Show vector index status (these are NOT managed by index set/unset)
+    // Show vector index status (these are NOT dropped by index unset)
     println!("\n🎯 Vector Indexes (Not affected by sync):");
-    let vector_indexes = vec![
-        "idx_cast_embedding_chunks_embedding_cosine",
-        "idx_cast_embedding_aggregated_embedding_cosine",
-    ];
-
-    let mut vector_count = 0;
-    for index_name in &vector_indexes {
-        let result: Option<(bool,)> =
-            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = $1)")
-                .bind(index_name)
-                .fetch_optional(db)
-                .await?;
-
-        if let Some((exists,)) = result {
-            if exists {
-                println!("  ✅ {index_name}");
-                vector_count += 1;
-            } else {
-                println!("  ❌ {index_name} (missing)");
-            }
-        }
-    }
-
-    println!(
-        "\n  Status: {}/{} vector indexes present",
-        vector_count,
-        vector_indexes.len()
-    );
+    print_embedding_dimensions(snaprag).await?;
+    print_vector_index_status(snaprag, recall_samples).await?;
     println!("  Note: Vector indexes are NOT dropped during sync (no vector data during sync)");
 
     // Check autovacuum status
@@ -566,3 +550,159 @@ async fn handle_index_status(snaprag: &SnapRag) -> Result<()> {
 
     Ok(())
 }
+
+/// Create missing vector indexes if the embedding dimension allows it
+async fn ensure_vector_indexes(snaprag: &SnapRag) -> Result<()> {
+    let dimension = snaprag.config.embedding_dimension();
+
+    println!("\n🎯 Ensuring vector indexes (dimension {dimension})...");
+    if dimension > MAX_INDEXED_VECTOR_DIMENSION {
+        println!(
+            "  ⚠️  Skipped: pgvector cannot index more than {MAX_INDEXED_VECTOR_DIMENSION} dimensions"
+        );
+        return Ok(());
+    }
+    if let Err(e) = snaprag.database.verify_embedding_dimension(dimension).await {
+        println!("  ⚠️  Skipped: {e}");
+        return Ok(());
+    }
+
+    let settings = VectorIndexSettings::from_config(&snaprag.config.performance);
+    let replaced = other_index_type(settings.index_type);
+    for target in snaprag.database.vector_index_targets().await? {
+        let name = target.index_name(settings.index_type);
+        print!("  🔨 Creating {name} ({})... ", settings.index_type);
+        io::stdout().flush()?;
+        match sqlx::query(&target.create_index_sql(&settings))
+            .execute(snaprag.database.pool())
+            .await
+        {
+            Ok(_) => println!("✅"),
+            Err(e) => {
+                println!("⚠️  Failed: {e}");
+                continue;
+            }
+        }
+
+        // Only drop the index of the other type once its replacement exists
+        let old_name = target.index_name(replaced);
+        if index_exists(snaprag, &old_name).await? {
+            let sql = format!("DROP INDEX CONCURRENTLY IF EXISTS {old_name}");
+            match sqlx::query(&sql).execute(snaprag.database.pool()).await {
+                Ok(_) => println!("  🧹 Dropped {old_name} ({replaced})"),
+                Err(e) => println!("  ⚠️  Failed to drop {old_name}: {e}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Show which vector indexes exist and, optionally, their estimated recall
+async fn print_vector_index_status(snaprag: &SnapRag, recall_samples: usize) -> Result<()> {
+    let configured = snaprag.config.vector_index_type();
+    let params = snaprag.database.vector_search_params();
+    println!(
+        "  Configured: {configured} (search: ivfflat.probes = {}, hnsw.ef_search = {})",
+        params.ivfflat_probes, params.hnsw_ef_search
+    );
+
+    let targets = snaprag.database.vector_index_targets().await?;
+    let mut present = 0;
+    for target in &targets {
+        let mut found = None;
+        for index_type in [configured, other_index_type(configured)] {
+            let name = target.index_name(index_type);
+            if index_exists(snaprag, &name).await? {
+                found = Some((name, index_type));
+                break;
+            }
+        }
+
+        let Some((name, index_type)) = found else {
+            println!("  ❌ {} (missing)", target.index_name(configured));
+            continue;
+        };
+        present += 1;
+        let marker = if index_type == configured {
+            "✅"
+        } else {
+            "⚠️ "
+        };
+        print!("  {marker} {name} ({index_type})");
+        if index_type != configured {
+            print!(" - run 'snaprag index set' to switch to {configured}");
+        }
+
+        if recall_samples > 0 {
+            io::stdout().flush()?;
+            match snaprag
+                .database
+                .estimate_vector_index_recall(target, recall_samples, RECALL_K)
+                .await
+            {
+                Ok(Some(estimate)) => print!(
+                    " - recall@{} ≈ {:.1}% ({} sampled queries)",
+                    estimate.k,
+                    estimate.recall * 100.0,
+                    estimate.samples
+                ),
+                Ok(None) => print!(" - recall: no vectors to sample"),
+                Err(e) => print!(" - recall: failed ({e})"),
+            }
+        }
+        println!();
+    }
+
+    println!(
+        "\n  Status: {present}/{} vector indexes present",
+        targets.len()
+    );
+    if recall_samples > 0 {
+        println!("  Recall compares index search with an exact scan on sampled stored vectors");
+    }
+
+    Ok(())
+}
+
+async fn index_exists(snaprag: &SnapRag, name: &str) -> Result<bool> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = $1)")
+            .bind(name)
+            .fetch_one(snaprag.database.pool())
+            .await?;
+    Ok(exists)
+}
+
+const fn other_index_type(index_type: VectorIndexType) -> VectorIndexType {
+    match index_type {
+        VectorIndexType::Ivfflat => VectorIndexType::Hnsw,
+        VectorIndexType::Hnsw => VectorIndexType::Ivfflat,
+    }
+}
+
+/// Print the configured embedding dimension next to the stored column types
+async fn print_embedding_dimensions(snaprag: &SnapRag) -> Result<()> {
+    let dimension = snaprag.config.embedding_dimension();
+    println!("  Configured embedding dimension: {dimension}");
+
+    for column in snaprag.database.embedding_column_dimensions().await? {
+        let marker = if usize::try_from(column.dimension).ok() == Some(dimension) {
+            "✅"
+        } else {
+            "❌"
+        };
+        println!(
+            "  {marker} {}.{} vector({})",
+            column.table, column.column, column.dimension
+        );
+    }
+
+    if dimension > MAX_INDEXED_VECTOR_DIMENSION {
+        println!(
+            "  ⚠️  Vector indexes unavailable above {MAX_INDEXED_VECTOR_DIMENSION} dimensions"
+        );
+    }
+
+    Ok(())
+}
@@ -22,9 +22,17 @@ pub async fn handle_init_command(snaprag: &SnapRag, force: bool, skip_indexes: b
     print_info("🗄️  Initializing SnapRAG database...");
     println!();
 
+    // Refuse to mix embedding models of different dimensions in one database
+    let embedding_dimension = snaprag.config.embedding_dimension();
+    snaprag
+        .database()
+        .verify_embedding_dimension(embedding_dimension)
+        .await?;
+    print_info(&format!("📐 Embedding dimension: {embedding_dimension}"));
+
     // Run complete initialization SQL
     print_info("📋 Running complete initialization script...");
-    match run_complete_init(snaprag) {
+    match run_complete_init(embedding_dimension) {
         Ok(()) => {
             print_success("✅ All tables created");
             print_success("✅ Vector columns configured");
@@ -45,9 +53,15 @@ pub async fn handle_init_command(snaprag: &SnapRag, force: bool, skip_indexes: b
         }
     }
 
-    // Schema migrations are now consolidated in 000_complete_init.sql
-    // No need to run additional migrations (they would overwrite with old schema)
-    print_info("✅ Schema fully initialized from 000_complete_init.sql");
+    // The init script is the migration baseline; record it and apply later versions
+    let database = snaprag.database();
+    database.record_baseline().await?;
+    let applied = database.migrate_up(None, embedding_dimension).await?;
+    if applied.is_empty() {
+        print_info("✅ Schema fully initialized from 000_complete_init.sql");
+    } else {
+        print_success(&format!("✅ Applied {} schema migration(s)", applied.len()));
+    }
 
     if skip_indexes {
         print_info("⏭️  Skipping index creation (--skip-indexes)");
@@ -72,6 +86,49 @@ pub async fn handle_init_command(snaprag: &SnapRag, force: bool, skip_indexes: b
     Ok(())
 }
 
+/// Tables dropped by `snaprag reset`, dependent tables first
+const RESET_TABLES: &[&str] = &[
+    "cast_embedding_chunks",     // Multi-vector chunks table
+    "cast_embedding_aggregated", // Multi-vector aggregated table
+    "cast_embeddings",           // Drop after multi-vector tables due to FK constraint
+    "user_profile_changes",      // Event-sourcing table
+    "profile_embeddings",        // Embeddings table
+    "user_profile_snapshots",
+    "user_profile_trends",
+    "user_data",
+    "user_data_changes",
+    "casts",
+    "cast_removes", // CastRemove tombstones
+    "links",
+    "link_compact_states",
+    "fname_transfers",
+    "storage_lends",
+    "reactions",
+    "verifications",
+    "onchain_events", // System messages
+    "fid_signers",    // Decoded on-chain state
+    "fid_registrations",
+    "storage_rents",
+    "username_proofs",
+    "frame_actions",
+    "user_activities",
+    "processed_messages",
+    "sync_progress",
+    "sync_stats",
+    "schema_migrations",
+    "embedding_versions",
+    "snapshot_bootstraps",
+    "rejected_messages",
+    "processed_blocks",
+    "block_hashes",
+    "webhook_dead_letters", // Drop before subscriptions and events due to FK constraints
+    "webhook_deliveries",
+    "webhook_events",
+    "webhook_subscriptions",
+    "import_progress",
+    // Note: user_activity_timeline removed for performance
+];
+
 /// Handle database reset command
 pub async fn handle_reset_command(snaprag: &SnapRag, force: bool) -> Result<()> {
     // 🛡️ PRODUCTION SAFETY CHECK: Require explicit environment variable to run reset
@@ -134,31 +191,8 @@ pub async fn handle_reset_command(snaprag: &SnapRag, force: bool) -> Result<()>
     }
 
     // Then drop tables
-    let tables = [
-        "cast_embedding_chunks",     // Multi-vector chunks table
-        "cast_embedding_aggregated", // Multi-vector aggregated table
-        "cast_embeddings",           // Drop after multi-vector tables due to FK constraint
-        "user_profile_changes",      // Event-sourcing table
-        "profile_embeddings",        // Embeddings table
-        "user_profile_snapshots",
-        "user_profile_trends",
-        "user_data",
-        "user_data_changes",
-        "casts",
-        "links",
-        "reactions",
-        "verifications",
-        "onchain_events", // System messages
-        "username_proofs",
-        "frame_actions",
-        "user_activities",
-        "processed_messages",
-        "sync_progress",
-        "sync_stats",
-        // Note: user_activity_timeline removed for performance
-    ];
-
-    for table in &tables {
+
+    for table in RESET_TABLES {
         match sqlx::query(&format!("DROP TABLE IF EXISTS {table} CASCADE"))
             .execute(snaprag.database().pool())
             .await
@@ -186,9 +220,9 @@ pub async fn handle_reset_command(snaprag: &SnapRag, force: bool) -> Result<()>
 }
 
 /// Run complete database initialization from migration file
-fn run_complete_init(snaprag: &SnapRag) -> Result<()> {
+fn run_complete_init(embedding_dimension: usize) -> Result<()> {
     // Write SQL file to temp location
-    let init_sql = include_str!("../../../migrations/000_complete_init.sql");
+    let init_sql = crate::database::MIGRATIONS[0].render_up(embedding_dimension);
     let temp_sql_path = "/tmp/snaprag_init.sql";
     std::fs::write(temp_sql_path, init_sql)?;
 
@@ -299,86 +333,6 @@ fn run_complete_init(snaprag: &SnapRag) -> Result<()> {
     Ok(())
 }
 
-/// Run schema migrations
-/// NOTE: All schema is now in `000_complete_init.sql`, no additional migrations needed
-fn run_schema_migrations(_snaprag: &SnapRag) -> Result<()> {
-    // No additional migrations - everything is in 000_complete_init.sql
-    let migrations: Vec<(&str, &str)> = vec![];
-
-    // Get database connection info from environment or config.toml (required)
-    let db_url = std::env::var("DATABASE_URL")
-        .or_else(|_| {
-            std::fs::read_to_string("config.toml")
-                .ok()
-                .and_then(|content| {
-                    content
-                        .lines()
-                        .find(|line| line.contains("url =") && line.contains("postgresql"))
-                        .and_then(|line| line.split('"').nth(1))
-                        .map(String::from)
-                })
-                .ok_or(std::env::VarError::NotPresent)
-        })
-        .map_err(|_| {
-            crate::SnapRagError::Custom(
-                "❌ Database URL not found in environment or config.toml!\n\n\
-             Please configure database URL before running migrations."
-                    .to_string(),
-            )
-        })?;
-
-    let url_without_scheme = db_url
-        .strip_prefix("postgresql://")
-        .or_else(|| db_url.strip_prefix("postgres://"))
-        .ok_or_else(|| crate::SnapRagError::Custom("Invalid database URL".to_string()))?;
-
-    let (user_pass, host_db) = url_without_scheme
-        .split_once('@')
-        .ok_or_else(|| crate::SnapRagError::Custom("Invalid database URL".to_string()))?;
-
-    let (user, password) = user_pass.split_once(':').unwrap_or((user_pass, ""));
-    let (host_port, database) = host_db.split_once('/').unwrap_or((host_db, "snaprag"));
-    let (host, port) = host_port.split_once(':').unwrap_or((host_port, "5432"));
-
-    // Run each migration
-    for (name, sql) in migrations {
-        let temp_path = format!("/tmp/snaprag_{name}");
-        std::fs::write(&temp_path, sql)?;
-
-        tracing::info!("Running migration: {}", name);
-
-        let output = std::process::Command::new("psql")
-            .env("PGPASSWORD", password)
-            .arg("-h")
-            .arg(host)
-            .arg("-p")
-            .arg(port)
-            .arg("-U")
-            .arg(user)
-            .arg("-d")
-            .arg(database)
-            .arg("-f")
-            .arg(&temp_path)
-            .arg("-v")
-            .arg("ON_ERROR_STOP=0")
-            .output()?;
-
-        std::fs::remove_file(&temp_path).ok();
-
-        if output.status.success() {
-            tracing::info!("Migration {} completed", name);
-        } else {
-            tracing::warn!(
-                "Migration {} had warnings: {}",
-                name,
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-    }
-
-    Ok(())
-}
-
 /// Create performance-optimized indexes
 async fn create_optimized_indexes(snaprag: &SnapRag) -> Result<()> {
     let pool = snaprag.database().pool();
@@ -406,6 +360,7 @@ async fn create_optimized_indexes(snaprag: &SnapRag) -> Result<()> {
 
     // 4. Update statistics
     sqlx::query("ANALYZE casts").execute(pool).await.ok();
+    sqlx::query("ANALYZE cast_removes").execute(pool).await.ok();
     sqlx::query("ANALYZE user_profile_changes")
         .execute(pool)
         .await
@@ -0,0 +1,146 @@
+//! Versioned schema migration handlers
+
+use std::io::stdin;
+
+use crate::cli::commands::MigrateCommands;
+use crate::cli::output::print_info;
+use crate::cli::output::print_prompt;
+use crate::cli::output::print_success;
+use crate::cli::output::print_warning;
+use crate::database::MigrationState;
+use crate::Result;
+use crate::SnapRag;
+
+/// Handle migration commands
+pub async fn handle_migrate_command(snaprag: &SnapRag, command: &MigrateCommands) -> Result<()> {
+    match command {
+        MigrateCommands::Status => handle_migrate_status(snaprag).await,
+        MigrateCommands::Up { target } => handle_migrate_up(snaprag, *target).await,
+        MigrateCommands::Down { steps, force } => {
+            handle_migrate_down(snaprag, *steps, *force).await
+        }
+        MigrateCommands::Verify => handle_migrate_verify(snaprag).await,
+    }
+}
+
+async fn handle_migrate_status(snaprag: &SnapRag) -> Result<()> {
+    let statuses = snaprag.database().migration_status().await?;
+
+    println!("\n📋 Schema Migrations");
+    println!("═══════════════════════════════════════════════════════════════");
+    println!(
+        "{:<8} {:<32} {:<10} {:<20} {:>8}",
+        "Version", "Name", "State", "Applied at", "Time"
+    );
+    for status in &statuses {
+        let applied_at = status.applied_at.map_or_else(
+            || "-".to_string(),
+            |at| at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        let duration = status
+            .execution_ms
+            .map_or_else(|| "-".to_string(), |ms| format!("{ms}ms"));
+        let state = if status.bootstrapped {
+            format!("{} (bootstrapped)", status.state)
+        } else {
+            status.state.to_string()
+        };
+        println!(
+            "{:<8} {:<32} {:<10} {:<20} {:>8}",
+            format!("{:03}", status.version),
+            status.name,
+            state,
+            applied_at,
+            duration
+        );
+    }
+
+    let pending = statuses
+        .iter()
+        .filter(|s| s.state == MigrationState::Pending)
+        .count();
+    println!();
+    if pending > 0 {
+        print_info(&format!(
+            "ℹ️  {pending} pending migration(s), run: snaprag migrate up"
+        ));
+    } else {
+        print_success("✅ Schema is up to date");
+    }
+
+    Ok(())
+}
+
+async fn handle_migrate_up(snaprag: &SnapRag, target: Option<i64>) -> Result<()> {
+    print_info("⬆️  Applying pending migrations...");
+    let applied = snaprag
+        .database()
+        .migrate_up(target, snaprag.config.embedding_dimension())
+        .await?;
+
+    if applied.is_empty() {
+        print_success("✅ No pending migrations");
+    } else {
+        for version in &applied {
+            print_success(&format!("Applied migration {version:03}"));
+        }
+        print_success(&format!("✅ Applied {} migration(s)", applied.len()));
+    }
+
+    Ok(())
+}
+
+async fn handle_migrate_down(snaprag: &SnapRag, steps: usize, force: bool) -> Result<()> {
+    if !force {
+        print_warning(&format!(
+            "This will roll back the last {steps} migration(s) and may drop data!"
+        ));
+        print_prompt("Are you sure you want to continue? (y/N)");
+
+        let mut input = String::new();
+        stdin().read_line(&mut input)?;
+
+        if !input.trim().to_lowercase().starts_with('y') {
+            print_info("Operation cancelled.");
+            return Ok(());
+        }
+    }
+
+    let reverted = snaprag
+        .database()
+        .migrate_down(steps, snaprag.config.embedding_dimension())
+        .await?;
+    for version in &reverted {
+        print_success(&format!("Rolled back migration {version:03}"));
+    }
+    print_success(&format!("✅ Rolled back {} migration(s)", reverted.len()));
+
+    Ok(())
+}
+
+async fn handle_migrate_verify(snaprag: &SnapRag) -> Result<()> {
+    let problems = snaprag.database().verify_migrations().await?;
+
+    if problems.is_empty() {
+        print_success("✅ All applied migrations match their scripts");
+        return Ok(());
+    }
+
+    for status in &problems {
+        match status.state {
+            MigrationState::Modified => print_warning(&format!(
+                "Migration {:03}_{} was modified after it was applied",
+                status.version, status.name
+            )),
+            _ => print_warning(&format!(
+                "Migration {:03}_{} is applied but unknown to this binary",
+                status.version, status.name
+            )),
+        }
+    }
+
+    Err(crate::SnapRagError::Custom(format!(
+        "{} migration(s) failed verification",
+        problems.len()
+    )))
+}
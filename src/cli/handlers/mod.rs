@@ -8,12 +8,16 @@
 //!
 //! This module is organized by functional domains:
 //! - init: Database initialization and reset
+//! - migrate: Versioned schema migrations
 //! - data: Data querying (list, search, activity)
 //! - cast: Cast operations (search, recent, thread)
 //! - rag: RAG queries
 //! - embeddings: Embedding generation and backfill
+//! - embedding_model: Embedding model versions, shadow backfill and cutover
 //! - fetch: Lazy loading (on-demand fetching)
 //! - sync: Synchronization commands
+//! - reconcile: Per-FID reconciliation against the hub
+//! - export: Bulk dataset exports
 //! - serve: API server
 //! - info: Information display (stats, dashboard, config)
 //! - ask: AI role-playing as a specific user
@@ -23,14 +27,19 @@ pub mod ask;
 pub mod ask_handler;
 pub mod cast;
 pub mod data;
+pub mod embedding_model;
 pub mod embeddings;
+pub mod export;
 pub mod fastsync;
 pub mod fetch;
+pub mod import;
 pub mod index;
 pub mod info;
 pub mod init;
 pub mod mbti;
+pub mod migrate;
 pub mod rag;
+pub mod reconcile;
 pub mod serve;
 pub mod social;
 pub mod sync;
@@ -40,14 +49,19 @@ pub use ask::*;
 pub use ask_handler::*;
 pub use cast::*;
 pub use data::*;
+pub use embedding_model::*;
 pub use embeddings::*;
+pub use export::*;
 pub use fastsync::*;
 pub use fetch::*;
+pub use import::*;
 pub use index::*;
 pub use info::*;
 pub use init::*;
 pub use mbti::*;
+pub use migrate::*;
 pub use rag::*;
+pub use reconcile::*;
 pub use serve::*;
 pub use social::*;
 pub use sync::*;
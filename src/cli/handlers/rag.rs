@@ -1,7 +1,10 @@
 //! RAG (Retrieval-Augmented Generation) handlers
 
+use std::io::Write;
 use std::sync::Arc;
 
+use futures::StreamExt;
+
 use crate::cli::output::print_info;
 use crate::cli::output::print_warning;
 use crate::cli::output::truncate_str;
@@ -19,12 +22,7 @@ pub async fn handle_rag_query_casts(
     max_tokens: usize,
     verbose: bool,
 ) -> Result<()> {
-    use std::sync::Arc;
-
-    use crate::embeddings::EmbeddingService;
-    use crate::llm::LlmService;
-    use crate::rag::CastContextAssembler;
-    use crate::rag::CastRetriever;
+    use crate::rag::CastRagQuery;
 
     print_info(&format!("🤖 RAG Query on Casts: \"{query}\""));
 
@@ -38,61 +36,63 @@ pub async fn handle_rag_query_casts(
         return Ok(());
     }
 
-    // Step 1: Retrieve relevant casts using CastRetriever
-    println!("\n🔍 Step 1: Retrieving relevant casts...");
-    let config = AppConfig::load()?;
-    let embedding_service = Arc::new(EmbeddingService::new(&config)?);
-    let database = Arc::new(Database::from_config(&config).await?);
-    let cast_retriever = CastRetriever::new(database, embedding_service);
-
-    let results = cast_retriever
-        .semantic_search(&query, limit, Some(threshold))
+    println!("\n⏳ Retrieving casts and generating a cited answer...");
+    let rag_service = snaprag.create_cast_rag_service().await?;
+    let response = rag_service
+        .query_with_options(CastRagQuery {
+            question: query,
+            retrieval_limit: limit,
+            threshold: Some(threshold),
+            temperature,
+            max_tokens,
+        })
         .await?;
 
-    if results.is_empty() {
+    if response.sources.is_empty() {
         print_warning("No relevant casts found");
         return Ok(());
     }
 
-    println!("   ✓ Found {} relevant casts", results.len());
-
-    // Step 2: Assemble context using CastContextAssembler
-    println!("🔧 Step 2: Assembling context...");
-    let context_assembler = CastContextAssembler::default();
-    let context = context_assembler
-        .assemble_with_authors(&results, snaprag.database())
-        .await?;
-
-    if verbose {
-        println!("   Context length: {} chars", context.len());
-    }
-
-    // Step 3: Generate answer with LLM using enhanced prompts
-    println!("💭 Step 3: Generating answer...");
-    let llm_service = LlmService::new(&config)?;
-
-    // Use specialized cast RAG prompt
-    let prompt = crate::rag::build_cast_rag_prompt(&query, &context);
-
-    let answer = llm_service
-        .generate_with_params(&prompt, temperature, max_tokens)
-        .await?;
-
     // Print results
     println!("\n{}", "═".repeat(100));
     println!("📝 Answer:\n");
-    println!("{}", answer.trim());
+    println!("{}", response.answer.trim());
     println!("\n{}", "═".repeat(100));
 
+    let citations = &response.citations;
+    if citations.citations.is_empty() {
+        print_warning("The answer cites none of the retrieved casts");
+    } else {
+        println!("\n🔗 Citations:");
+        for citation in &citations.citations {
+            println!(
+                "  [{}] FID {} | {}",
+                citation.marker, citation.fid, citation.url
+            );
+        }
+    }
+    if !citations.unknown_markers.is_empty() {
+        print_warning(&format!(
+            "Answer cites casts that were not retrieved: {:?}",
+            citations.unknown_markers
+        ));
+    }
+
     if verbose {
-        println!("\n📚 Sources ({} casts):", results.len());
-        for (idx, result) in results.iter().enumerate() {
+        println!("\n📚 Sources ({} casts):", response.sources.len());
+        for source in &response.sources {
+            let cited = if citations.uncited_sources.contains(&source.number) {
+                "  "
+            } else {
+                "✓ "
+            };
             println!(
-                "  {}. FID {} | Similarity: {:.2}% | \"{}...\"",
-                idx + 1,
-                result.fid,
-                result.similarity * 100.0,
-                result.text.chars().take(50).collect::<String>()
+                "  {}{}. @{} | Score: {:.2}% | \"{}\"",
+                cited,
+                source.number,
+                source.author,
+                source.similarity * 100.0,
+                truncate_str(&source.text, 50)
             );
         }
     } else {
@@ -140,11 +140,31 @@ pub async fn handle_rag_query(
         max_tokens,
     };
 
-    let response = rag_service.query_with_options(rag_query).await?;
+    let response = rag_service.query_stream(rag_query).await?;
+
+    if verbose {
+        if let Some(analysis) = &response.metadata.query_analysis {
+            println!("🧠 Search queries:");
+            for text in analysis.queries() {
+                println!("  - {text}");
+            }
+            for answer in &analysis.hypothetical_answers {
+                println!("  - (hypothetical) {}", truncate_str(answer, 100));
+            }
+            if !analysis.filters.is_empty() {
+                println!("🔎 Filters: {:?}", analysis.filters);
+            }
+        }
+    }
 
     println!("\n📝 Answer:");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("{}", response.answer);
+    let mut tokens = response.answer.into_stream();
+    while let Some(delta) = tokens.next().await {
+        print!("{}", delta?);
+        std::io::stdout().flush()?;
+    }
+    println!();
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     println!("📚 Sources ({} profiles):", response.sources.len());
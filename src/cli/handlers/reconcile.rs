@@ -0,0 +1,115 @@
+//! Per-FID reconciliation handler
+
+use serde::Serialize;
+
+use crate::cli::output::print_success;
+use crate::cli::output::print_warning;
+use crate::sync::FidReconcileReport;
+use crate::sync::Reconciler;
+use crate::sync::SnapchainClient;
+use crate::Result;
+use crate::SnapRag;
+
+/// JSON report of a `reconcile` run
+#[derive(Debug, Serialize)]
+struct ReconcileOutput {
+    dry_run: bool,
+    fids: Vec<FidReconcileReport>,
+    failed: Vec<FailedFid>,
+}
+
+#[derive(Debug, Serialize)]
+struct FailedFid {
+    fid: u64,
+    error: String,
+}
+
+/// Parse a FIDs file: one FID per line, blank lines and `#` comments ignored
+pub fn parse_fids_file(contents: &str) -> Result<Vec<u64>> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<u64>()
+                .map_err(|_| crate::SnapRagError::Custom(format!("Invalid FID '{line}'")))
+        })
+        .collect()
+}
+
+/// Reconcile FIDs against the hub and print the JSON diff report
+pub async fn handle_reconcile_command(
+    snaprag: &SnapRag,
+    fid: Option<u64>,
+    fids_file: Option<String>,
+    dry_run: bool,
+    output: Option<String>,
+) -> Result<()> {
+    snaprag.database().verify_schema_or_error().await?;
+
+    let fids = match (fid, fids_file) {
+        (Some(fid), _) => vec![fid],
+        (None, Some(path)) => parse_fids_file(&std::fs::read_to_string(&path)?)?,
+        (None, None) => {
+            return Err(crate::SnapRagError::Custom(
+                "Either --fid or --fids-file is required".to_string(),
+            ))
+        }
+    };
+
+    let client = SnapchainClient::from_config(&snaprag.config).await?;
+    let reconciler = Reconciler::new(&client, snaprag.database()).dry_run(dry_run);
+
+    let mut report = ReconcileOutput {
+        dry_run,
+        fids: Vec::with_capacity(fids.len()),
+        failed: Vec::new(),
+    };
+    for fid in fids {
+        match reconciler.reconcile_fid(fid).await {
+            Ok(fid_report) => report.fids.push(fid_report),
+            Err(e) => {
+                tracing::warn!("Failed to reconcile FID {}: {}", fid, e);
+                report.failed.push(FailedFid {
+                    fid,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&report)?;
+    if let Some(path) = output {
+        std::fs::write(&path, json)?;
+        let inconsistent = report
+            .fids
+            .iter()
+            .filter(|fid_report| !fid_report.is_consistent())
+            .count();
+        print_success(&format!(
+            "Reconciled {} FIDs ({} differed from the hub), report written to {}",
+            report.fids.len(),
+            inconsistent,
+            path
+        ));
+        if !report.failed.is_empty() {
+            print_warning(&format!("{} FIDs failed", report.failed.len()));
+        }
+    } else {
+        println!("{json}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fids_file() {
+        let fids = parse_fids_file("3\n\n# team\n  99  \n2 # dwr\n").unwrap();
+        assert_eq!(fids, vec![3, 99, 2]);
+        assert!(parse_fids_file("3\nabc\n").is_err());
+    }
+}
@@ -7,6 +7,13 @@ use crate::cli::commands::SyncCommands;
 use crate::cli::output::print_error;
 use crate::cli::output::print_info;
 use crate::cli::output::print_success;
+use crate::cli::output::print_warning;
+use crate::database::Database;
+use crate::database::HeightRange;
+use crate::sync::bootstrap::bootstrap_shard;
+use crate::sync::bootstrap::bootstrap_shard_recorded;
+use crate::sync::service::SyncService;
+use crate::sync::SnapchainClient;
 use crate::AppConfig;
 use crate::Result;
 use crate::SnapRag;
@@ -17,6 +24,9 @@ pub async fn handle_sync_command(mut snaprag: SnapRag, sync_command: SyncCommand
         SyncCommands::Stop { .. } | SyncCommands::Status => {
             // These commands don't require full schema
         }
+        _ if !snaprag.database().sync_sinks().writes_postgres() => {
+            // Export-only sync runs without a database
+        }
         _ => {
             // All other sync commands require initialized database
             snaprag.database().verify_schema_or_error().await?;
@@ -39,15 +49,8 @@ pub async fn handle_sync_command(mut snaprag: SnapRag, sync_command: SyncCommand
             let from_block = from.unwrap_or(0);
             let to_block = to.unwrap_or(u64::MAX);
 
-            // Parse shard IDs if provided
-            let shard_ids = if let Some(shard_str) = shard {
-                shard_str
-                    .split(',')
-                    .filter_map(|s| s.trim().parse::<u32>().ok())
-                    .collect::<Vec<_>>()
-            } else {
-                vec![] // Use default from config
-            };
+            // Parse shard IDs if provided (empty: use default from config)
+            let shard_ids = parse_shard_ids(shard.as_deref());
 
             // Apply command-line overrides to config
             if batch.is_some() || interval.is_some() || !shard_ids.is_empty() {
@@ -95,6 +98,71 @@ pub async fn handle_sync_command(mut snaprag: SnapRag, sync_command: SyncCommand
                 .start_sync_with_range_and_workers(from_block, to_block, workers_per_shard)
                 .await?;
         }
+        SyncCommands::Bootstrap {
+            shard,
+            height,
+            record,
+            no_follow,
+        } => {
+            print_info(&format!(
+                "Bootstrapping shard {shard} from {}...",
+                height.map_or_else(
+                    || "the latest snapshot".to_string(),
+                    |h| format!("the snapshot at height {h}")
+                )
+            ));
+
+            let stats = {
+                let client = SnapchainClient::from_config(&snaprag.config).await?;
+                let database = snaprag.database();
+                match &record {
+                    Some(path) => {
+                        bootstrap_shard_recorded(&client, database, shard, height, path).await?
+                    }
+                    None => bootstrap_shard(&client, database, shard, height).await?,
+                }
+            };
+            if let Some(path) = &record {
+                print_info(&format!(
+                    "Recorded replication responses to {}",
+                    path.display()
+                ));
+            }
+
+            print_success(&format!(
+                "Shard {shard} bootstrapped at height {} ({} entries{})",
+                stats.height,
+                stats.entries,
+                if stats.resumed { ", resumed" } else { "" }
+            ));
+
+            if no_follow {
+                print_info(&format!(
+                    "Continue later with: snaprag sync start --shard {shard} --from {}",
+                    stats.height + 1
+                ));
+            } else {
+                snaprag.override_sync_config(vec![shard], None, None)?;
+                snaprag
+                    .start_sync_with_range_and_workers(stats.height + 1, u64::MAX, 1)
+                    .await?;
+            }
+        }
+        SyncCommands::Verify { shard, from, to } => {
+            let shard_ids = shards_or_configured(&snaprag.config, shard.as_deref());
+            let gaps = find_gaps(snaprag.database(), &shard_ids, from, to).await?;
+            print_gaps(&gaps);
+        }
+        SyncCommands::Repair {
+            shard,
+            from,
+            to,
+            batch,
+        } => {
+            let shard_ids = shards_or_configured(&snaprag.config, shard.as_deref());
+            let batch_size = batch.unwrap_or(snaprag.config.sync.batch_size).max(1);
+            repair_gaps(&snaprag, &shard_ids, from, to, batch_size).await?;
+        }
         SyncCommands::Test { shard, block } => {
             print_info(&format!(
                 "Testing single block synchronization for shard {shard} block {block}..."
@@ -139,3 +207,124 @@ pub async fn handle_sync_command(mut snaprag: SnapRag, sync_command: SyncCommand
     }
     Ok(())
 }
+
+/// Parse a comma-separated list of shard IDs
+fn parse_shard_ids(shard: Option<&str>) -> Vec<u32> {
+    shard.map_or_else(Vec::new, |shard_str| {
+        shard_str
+            .split(',')
+            .filter_map(|s| s.trim().parse::<u32>().ok())
+            .collect()
+    })
+}
+
+fn shards_or_configured(config: &AppConfig, shard: Option<&str>) -> Vec<u32> {
+    let shard_ids = parse_shard_ids(shard);
+    if shard_ids.is_empty() {
+        config.sync.shard_ids.clone()
+    } else {
+        shard_ids
+    }
+}
+
+/// Missing height ranges of each shard
+async fn find_gaps(
+    database: &Database,
+    shard_ids: &[u32],
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<Vec<(u32, Vec<HeightRange>)>> {
+    let mut gaps = Vec::with_capacity(shard_ids.len());
+    for &shard_id in shard_ids {
+        gaps.push((shard_id, database.find_sync_gaps(shard_id, from, to).await?));
+    }
+    Ok(gaps)
+}
+
+fn print_gaps(gaps: &[(u32, Vec<HeightRange>)]) {
+    println!("\n🧩 Sync Gaps");
+    println!("═══════════════════════════════════════════════════════════════");
+    for (shard_id, ranges) in gaps {
+        let missing: u64 = ranges.iter().map(HeightRange::block_count).sum();
+        if ranges.is_empty() {
+            println!("  Shard {shard_id}: no gaps");
+            continue;
+        }
+        println!(
+            "  Shard {shard_id}: {} gaps, {missing} missing blocks",
+            ranges.len()
+        );
+        for range in ranges {
+            println!("    {range} ({} blocks)", range.block_count());
+        }
+    }
+
+    if gaps.iter().any(|(_, ranges)| !ranges.is_empty()) {
+        println!();
+        print_info("Re-fetch the missing blocks with: snaprag sync repair");
+    }
+}
+
+/// Re-fetch the missing ranges of each shard through the sync coordinator
+///
+/// Repairing processes old blocks, which moves the saved progress back; it is
+/// restored afterwards so the next sync resumes where it left off.
+async fn repair_gaps(
+    snaprag: &SnapRag,
+    shard_ids: &[u32],
+    from: Option<u64>,
+    to: Option<u64>,
+    batch_size: u32,
+) -> Result<()> {
+    let database = snaprag.database();
+    let gaps = find_gaps(database, shard_ids, from, to).await?;
+    if gaps.iter().all(|(_, ranges)| ranges.is_empty()) {
+        print_success("No gaps to repair");
+        return Ok(());
+    }
+
+    let sync_service = SyncService::new(&snaprag.config, database.clone()).await?;
+    for (shard_id, ranges) in &gaps {
+        if ranges.is_empty() {
+            continue;
+        }
+
+        let progress = database.get_last_processed_height(*shard_id).await?;
+        let mut blocks = 0;
+        let mut messages = 0;
+        for range in ranges {
+            print_info(&format!("Repairing shard {shard_id} blocks {range}..."));
+            let mut start = range.start;
+            while start <= range.end {
+                let size = (range.end - start + 1).min(u64::from(batch_size));
+                let stats = sync_service
+                    .poll_batch(*shard_id, start, u32::try_from(size).unwrap_or(batch_size))
+                    .await?;
+                blocks += stats.blocks_processed();
+                messages += stats.messages_processed();
+                start += size;
+            }
+        }
+        if progress > 0 {
+            database
+                .update_last_processed_height(*shard_id, progress)
+                .await?;
+        }
+
+        let remaining = database.find_sync_gaps(*shard_id, from, to).await?;
+        if remaining.is_empty() {
+            print_success(&format!(
+                "Shard {shard_id} repaired: {blocks} blocks, {messages} messages"
+            ));
+        } else {
+            let missing: u64 = remaining.iter().map(HeightRange::block_count).sum();
+            print_warning(&format!(
+                "Shard {shard_id}: {missing} blocks in {} ranges are still missing (not returned by the node)",
+                remaining.len()
+            ));
+        }
+    }
+    drop(sync_service);
+
+    Ok(())
+}
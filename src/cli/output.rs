@@ -356,7 +356,16 @@ pub fn print_config(config: &AppConfig) {
 
     println!("⚡ Performance:");
     println!("  Vector indexes: {}", config.vector_indexes_enabled());
+    println!("  Vector index type: {}", config.vector_index_type());
     println!("  Vector index lists: {}", config.vector_index_lists());
+    println!(
+        "  HNSW m / ef_construction: {} / {}",
+        config.performance.hnsw_m, config.performance.hnsw_ef_construction
+    );
+    println!(
+        "  Search ivfflat.probes / hnsw.ef_search: {} / {}",
+        config.performance.ivfflat_probes, config.performance.hnsw_ef_search
+    );
     println!();
 
     println!("🔄 Sync:");
@@ -364,6 +373,13 @@ pub fn print_config(config: &AppConfig) {
     println!("  gRPC endpoint: {}", config.snapchain_grpc_endpoint());
     println!("  Real-time sync: {}", config.realtime_sync_enabled());
     println!("  Historical sync: {}", config.historical_sync_enabled());
+    println!("  Real-time mode: {:?}", config.realtime_sync_mode());
+    println!(
+        "  Message verification: {:?} (signers: {})",
+        config.message_verification(),
+        config.sync.verify_message_signers
+    );
+    println!("  Flush strategy: {:?}", config.flush_strategy());
     println!(
         "  Historical sync from event ID: {}",
         config.historical_sync_from_event_id()
@@ -373,6 +389,50 @@ pub fn print_config(config: &AppConfig) {
     println!("  Shard IDs: {:?}", config.shard_ids());
     println!();
 
+    println!("🪝 Webhooks:");
+    println!("  Enabled: {}", config.webhooks.enabled);
+    println!(
+        "  API: {}",
+        if config.webhooks.api_keys.is_empty() {
+            "disabled".to_string()
+        } else {
+            format!("{} key(s)", config.webhooks.api_keys.len())
+        }
+    );
+    println!(
+        "  Max attempts: {} (retry {}s..{}s)",
+        config.webhooks.max_attempts,
+        config.webhooks.retry_base_secs,
+        config.webhooks.retry_max_secs
+    );
+    println!();
+
+    println!("📡 Live feed:");
+    println!("  Enabled: {}", config.live_feed.enabled);
+    println!("  Buffer size: {}", config.live_feed.buffer_size);
+    println!();
+
+    println!("📦 Sync sinks:");
+    println!("  Kinds: {:?}", config.sinks.kinds);
+    println!("  Output dir: {}", config.sinks.output_dir);
+    println!(
+        "  Rotation: {} rows / {} bytes",
+        config.sinks.max_file_rows, config.sinks.max_file_bytes
+    );
+    println!();
+
+    println!("📤 Export:");
+    println!(
+        "  API: {}",
+        if config.export.api_keys.is_empty() {
+            "disabled".to_string()
+        } else {
+            format!("{} key(s)", config.export.api_keys.len())
+        }
+    );
+    println!("  Fetch size: {}", config.export.fetch_size);
+    println!();
+
     println!("🤖 LLM:");
     println!("  Endpoint: {}", config.llm_endpoint());
     println!("  Key: {}", config.llm_key());
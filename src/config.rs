@@ -66,10 +66,71 @@ const fn default_continuous_sync_interval() -> u64 {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceConfig {
     pub enable_vector_indexes: bool,
+    /// ivfflat: number of inverted lists
     pub vector_index_lists: usize,
+    /// Index type for embedding columns
+    #[serde(default)]
+    pub vector_index_type: VectorIndexType,
+    /// HNSW: max connections per node
+    #[serde(default = "default_hnsw_m")]
+    pub hnsw_m: usize,
+    /// HNSW: candidate list size while building
+    #[serde(default = "default_hnsw_ef_construction")]
+    pub hnsw_ef_construction: usize,
+    /// ivfflat: lists probed per query (pgvector default is 1)
+    #[serde(default = "default_ivfflat_probes")]
+    pub ivfflat_probes: usize,
+    /// HNSW: candidate list size per query, also caps the rows an index scan returns
+    #[serde(default = "default_hnsw_ef_search")]
+    pub hnsw_ef_search: usize,
+}
+
+/// pgvector index type for embedding columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorIndexType {
+    /// Inverted file index, fast to build, recall depends on `ivfflat_probes`
+    #[default]
+    Ivfflat,
+    /// Hierarchical navigable small world graph, better recall and latency
+    Hnsw,
+}
+
+impl VectorIndexType {
+    /// pgvector access method name
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ivfflat => "ivfflat",
+            Self::Hnsw => "hnsw",
+        }
+    }
+}
+
+impl std::fmt::Display for VectorIndexType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+const fn default_hnsw_m() -> usize {
+    16 // pgvector default
+}
+
+const fn default_hnsw_ef_construction() -> usize {
+    64 // pgvector default
+}
+
+const fn default_ivfflat_probes() -> usize {
+    10 // ~sqrt(lists) for the default 100 lists
+}
+
+const fn default_hnsw_ef_search() -> usize {
+    40 // pgvector default
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // Independent on/off switches
 pub struct SyncConfig {
     pub snapchain_http_endpoint: String,
     pub snapchain_grpc_endpoint: String,
@@ -85,6 +146,58 @@ pub struct SyncConfig {
     /// Continuous sync interval in seconds (how often to poll for new blocks)
     #[serde(default = "default_continuous_sync_interval")]
     pub continuous_sync_interval_secs: u64,
+    /// How real-time sync receives new data (`poll` or `stream`)
+    #[serde(default)]
+    pub realtime_sync_mode: RealtimeSyncMode,
+    /// Hash and signature verification of ingested messages
+    #[serde(default)]
+    pub message_verification: MessageVerification,
+    /// With verification on, also require the signer to be an active key of the FID
+    #[serde(default = "default_verify_message_signers")]
+    pub verify_message_signers: bool,
+    /// How batches are written to `PostgreSQL` (`insert` or `copy`)
+    #[serde(default)]
+    pub flush_strategy: FlushStrategy,
+}
+
+/// Real-time sync transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RealtimeSyncMode {
+    /// Poll `GetShardChunks` for new blocks on an interval
+    #[default]
+    Poll,
+    /// Consume `HubEvent`s from the `Subscribe` RPC, resuming by event id
+    Stream,
+}
+
+/// What sync does with messages that fail verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageVerification {
+    /// Trust the node, ingest every message as received
+    #[default]
+    Off,
+    /// Skip invalid messages, recording hash and reason in `rejected_messages`
+    Reject,
+    /// Like `reject`, but also keep the full message in `rejected_messages`
+    Quarantine,
+}
+
+/// How sync writes batched rows to `PostgreSQL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FlushStrategy {
+    /// Multi-row `INSERT ... VALUES` statements, split at the bind parameter limit
+    #[default]
+    Insert,
+    /// Binary `COPY FROM STDIN` into temporary staging tables, merged with
+    /// `INSERT ... SELECT ... ON CONFLICT DO NOTHING`; fastest for historical sync
+    Copy,
+}
+
+const fn default_verify_message_signers() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -272,6 +385,337 @@ const fn default_use_llm() -> bool {
     false
 }
 
+/// Change-data-capture webhook configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhooksConfig {
+    /// Record change events for active subscriptions during sync and deliver
+    /// them from the API server
+    #[serde(default)]
+    pub enabled: bool,
+    /// Keys accepted in the `X-API-KEY` header of `/api/subscriptions`; none disables it
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Delivery attempts before an event is dead-lettered
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on every further attempt
+    #[serde(default = "default_webhook_retry_base_secs")]
+    pub retry_base_secs: u64,
+    /// Upper bound of the retry delay
+    #[serde(default = "default_webhook_retry_max_secs")]
+    pub retry_max_secs: u64,
+    /// Timeout of one delivery request
+    #[serde(default = "default_webhook_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// How often the dispatcher polls for due deliveries
+    #[serde(default = "default_webhook_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Deliveries claimed and sent concurrently per poll
+    #[serde(default = "default_webhook_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_keys: Vec::new(),
+            max_attempts: default_webhook_max_attempts(),
+            retry_base_secs: default_webhook_retry_base_secs(),
+            retry_max_secs: default_webhook_retry_max_secs(),
+            request_timeout_secs: default_webhook_request_timeout_secs(),
+            poll_interval_ms: default_webhook_poll_interval_ms(),
+            batch_size: default_webhook_batch_size(),
+        }
+    }
+}
+
+const fn default_webhook_max_attempts() -> u32 {
+    8
+}
+
+const fn default_webhook_retry_base_secs() -> u64 {
+    5
+}
+
+const fn default_webhook_retry_max_secs() -> u64 {
+    3600 // 1 hour
+}
+
+const fn default_webhook_request_timeout_secs() -> u64 {
+    10
+}
+
+const fn default_webhook_poll_interval_ms() -> u64 {
+    1000
+}
+
+const fn default_webhook_batch_size() -> usize {
+    50
+}
+
+/// Live SSE / WebSocket feed configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveFeedConfig {
+    /// Publish committed sync events and serve them on `/api/stream/*`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Events buffered per client; a client further behind skips the oldest
+    #[serde(default = "default_live_feed_buffer_size")]
+    pub buffer_size: usize,
+}
+
+impl Default for LiveFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            buffer_size: default_live_feed_buffer_size(),
+        }
+    }
+}
+
+const fn default_live_feed_buffer_size() -> usize {
+    1024
+}
+
+/// Destinations of synced batches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinksConfig {
+    /// Active sinks; leaving out `postgres` gives an export-only sync that
+    /// keeps its progress next to the exported files
+    #[serde(default = "default_sink_kinds")]
+    pub kinds: Vec<SinkKind>,
+    /// Root directory of the NDJSON and Parquet sinks
+    #[serde(default = "default_sink_output_dir")]
+    pub output_dir: String,
+    /// Rows per file before the next part is started
+    #[serde(default = "default_sink_max_file_rows")]
+    pub max_file_rows: usize,
+    /// Bytes per file before the next part is started
+    #[serde(default = "default_sink_max_file_bytes")]
+    pub max_file_bytes: u64,
+}
+
+impl SinksConfig {
+    /// Whether sync writes to `PostgreSQL`
+    #[must_use]
+    pub fn writes_postgres(&self) -> bool {
+        self.kinds.contains(&SinkKind::Postgres)
+    }
+}
+
+impl Default for SinksConfig {
+    fn default() -> Self {
+        Self {
+            kinds: default_sink_kinds(),
+            output_dir: default_sink_output_dir(),
+            max_file_rows: default_sink_max_file_rows(),
+            max_file_bytes: default_sink_max_file_bytes(),
+        }
+    }
+}
+
+/// A destination of synced batches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// The `PostgreSQL` tables everything else reads from
+    Postgres,
+    /// One JSON object per line, one file set per table
+    Ndjson,
+    /// Columnar Parquet files, one file set per table
+    Parquet,
+}
+
+fn default_sink_kinds() -> Vec<SinkKind> {
+    vec![SinkKind::Postgres]
+}
+
+fn default_sink_output_dir() -> String {
+    "exports".to_string()
+}
+
+const fn default_sink_max_file_rows() -> usize {
+    1_000_000
+}
+
+const fn default_sink_max_file_bytes() -> u64 {
+    256 * 1024 * 1024 // 256 MiB
+}
+
+/// Bulk dataset exports and imports (`snaprag export`, `snaprag import`, `/api/export`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Keys accepted in the `X-API-KEY` header of `/api/export`; none disables the endpoint
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Rows fetched from the server-side cursor per batch, and rows loaded per import transaction
+    #[serde(default = "default_export_fetch_size")]
+    pub fetch_size: usize,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            fetch_size: default_export_fetch_size(),
+        }
+    }
+}
+
+const fn default_export_fetch_size() -> usize {
+    5000
+}
+
+/// Reranking of retrieval results before they reach the LLM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankConfig {
+    /// Reranker applied after retrieval
+    #[serde(default)]
+    pub method: RerankMethod,
+    /// Candidates retrieved before the reranker cuts them to the requested limit
+    #[serde(default = "default_rerank_candidates")]
+    pub candidates: usize,
+    /// `HuggingFace` model of the `cross_encoder` method
+    #[serde(default = "default_cross_encoder_model")]
+    pub cross_encoder_model: String,
+    /// Run the cross-encoder on the CPU even when a GPU is available
+    #[serde(default)]
+    pub cross_encoder_cpu: bool,
+    /// Signal weights of the `heuristic` method
+    #[serde(default)]
+    pub weights: HeuristicWeights,
+    /// Age in days at which the recency signal of a cast halves
+    #[serde(default = "default_recency_half_life_days")]
+    pub recency_half_life_days: f32,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            method: RerankMethod::default(),
+            candidates: default_rerank_candidates(),
+            cross_encoder_model: default_cross_encoder_model(),
+            cross_encoder_cpu: false,
+            weights: HeuristicWeights::default(),
+            recency_half_life_days: default_recency_half_life_days(),
+        }
+    }
+}
+
+/// A reranker applied after retrieval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RerankMethod {
+    /// Keep the retrieval order
+    #[default]
+    None,
+    /// Mix similarity with engagement, recency and author reputation
+    Heuristic,
+    /// Let the configured LLM order the candidates
+    Llm,
+    /// Score query/document pairs with a local cross-encoder (`local-gpu` feature)
+    CrossEncoder,
+}
+
+/// Weights of the signals mixed by the heuristic reranker
+///
+/// Each signal is scaled to `0..1` within the candidates before weighting.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HeuristicWeights {
+    /// Retrieval similarity
+    #[serde(default = "default_similarity_weight")]
+    pub similarity: f32,
+    /// Reactions and replies
+    #[serde(default = "default_engagement_weight")]
+    pub engagement: f32,
+    /// Age of the cast
+    #[serde(default = "default_recency_weight")]
+    pub recency: f32,
+    /// Follower count of the author
+    #[serde(default = "default_reputation_weight")]
+    pub reputation: f32,
+}
+
+impl Default for HeuristicWeights {
+    fn default() -> Self {
+        Self {
+            similarity: default_similarity_weight(),
+            engagement: default_engagement_weight(),
+            recency: default_recency_weight(),
+            reputation: default_reputation_weight(),
+        }
+    }
+}
+
+const fn default_rerank_candidates() -> usize {
+    50
+}
+
+fn default_cross_encoder_model() -> String {
+    "cross-encoder/ms-marco-MiniLM-L-6-v2".to_string()
+}
+
+const fn default_recency_half_life_days() -> f32 {
+    30.0
+}
+
+const fn default_similarity_weight() -> f32 {
+    1.0
+}
+
+const fn default_engagement_weight() -> f32 {
+    0.3
+}
+
+const fn default_recency_weight() -> f32 {
+    0.2
+}
+
+const fn default_reputation_weight() -> f32 {
+    0.2
+}
+
+/// LLM query understanding before RAG retrieval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryUnderstandingConfig {
+    /// Rewrite and expand questions with the LLM before retrieval
+    #[serde(default)]
+    pub enabled: bool,
+    /// Paraphrases of the question retrieved alongside it
+    #[serde(default = "default_paraphrases")]
+    pub paraphrases: usize,
+    /// Hypothetical answers (HyDE) retrieved by semantic search
+    #[serde(default = "default_hypothetical_answers")]
+    pub hypothetical_answers: usize,
+    /// Extract usernames, FIDs, date ranges and channels from the question
+    #[serde(default = "default_extract_filters")]
+    pub extract_filters: bool,
+}
+
+impl Default for QueryUnderstandingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            paraphrases: default_paraphrases(),
+            hypothetical_answers: default_hypothetical_answers(),
+            extract_filters: default_extract_filters(),
+        }
+    }
+}
+
+const fn default_paraphrases() -> usize {
+    3
+}
+
+const fn default_hypothetical_answers() -> usize {
+    1
+}
+
+const fn default_extract_filters() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
@@ -290,6 +734,18 @@ pub struct AppConfig {
     pub cache_server: CacheServerConfig,
     #[serde(default)]
     pub mbti: MbtiConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub live_feed: LiveFeedConfig,
+    #[serde(default)]
+    pub sinks: SinksConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub rerank: RerankConfig,
+    #[serde(default)]
+    pub query_understanding: QueryUnderstandingConfig,
 }
 
 impl AppConfig {
@@ -434,6 +890,12 @@ impl AppConfig {
         self.performance.vector_index_lists
     }
 
+    /// Get vector index type
+    #[must_use]
+    pub const fn vector_index_type(&self) -> VectorIndexType {
+        self.performance.vector_index_type
+    }
+
     /// Get snapchain HTTP endpoint
     #[must_use]
     pub fn snapchain_http_endpoint(&self) -> &str {
@@ -464,6 +926,24 @@ impl AppConfig {
         self.sync.historical_sync_from_event_id
     }
 
+    /// Get real-time sync mode
+    #[must_use]
+    pub const fn realtime_sync_mode(&self) -> RealtimeSyncMode {
+        self.sync.realtime_sync_mode
+    }
+
+    /// Get message verification mode
+    #[must_use]
+    pub const fn message_verification(&self) -> MessageVerification {
+        self.sync.message_verification
+    }
+
+    /// Get batch flush strategy
+    #[must_use]
+    pub const fn flush_strategy(&self) -> FlushStrategy {
+        self.sync.flush_strategy
+    }
+
     /// Get sync batch size
     #[must_use]
     pub const fn sync_batch_size(&self) -> u32 {
@@ -540,6 +1020,11 @@ impl Default for AppConfig {
             performance: PerformanceConfig {
                 enable_vector_indexes: true,
                 vector_index_lists: 100,
+                vector_index_type: VectorIndexType::Ivfflat,
+                hnsw_m: default_hnsw_m(),
+                hnsw_ef_construction: default_hnsw_ef_construction(),
+                ivfflat_probes: default_ivfflat_probes(),
+                hnsw_ef_search: default_hnsw_ef_search(),
             },
             sync: SyncConfig {
                 snapchain_http_endpoint: "http://localhost:3383".to_string(),
@@ -552,6 +1037,10 @@ impl Default for AppConfig {
                 shard_ids: vec![0, 1, 2],
                 enable_continuous_sync: true,
                 continuous_sync_interval_secs: 5,
+                realtime_sync_mode: RealtimeSyncMode::Poll,
+                message_verification: MessageVerification::Off,
+                verify_message_signers: true,
+                flush_strategy: FlushStrategy::Insert,
             },
             llm: LlmConfig {
                 llm_endpoint: "http://localhost:11434".to_string(),
@@ -569,6 +1058,12 @@ impl Default for AppConfig {
             redis: None,
             cache_server: CacheServerConfig::default(),
             mbti: MbtiConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            live_feed: LiveFeedConfig::default(),
+            sinks: SinksConfig::default(),
+            export: ExportConfig::default(),
+            rerank: RerankConfig::default(),
+            query_understanding: QueryUnderstandingConfig::default(),
         }
     }
 }
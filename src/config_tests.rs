@@ -85,6 +85,10 @@ mod tests {
             shard_ids: vec![0, 1, 2],
             enable_continuous_sync: true,
             continuous_sync_interval_secs: 5,
+            realtime_sync_mode: RealtimeSyncMode::Poll,
+            message_verification: MessageVerification::Off,
+            verify_message_signers: true,
+            flush_strategy: FlushStrategy::Insert,
         };
 
         assert_eq!(config.shard_ids.len(), 3);
@@ -150,10 +154,28 @@ mod tests {
         let config = PerformanceConfig {
             enable_vector_indexes: true,
             vector_index_lists: 100,
+            vector_index_type: VectorIndexType::Hnsw,
+            hnsw_m: 16,
+            hnsw_ef_construction: 64,
+            ivfflat_probes: 10,
+            hnsw_ef_search: 40,
         };
 
         assert!(config.enable_vector_indexes);
         assert_eq!(config.vector_index_lists, 100);
+        assert_eq!(config.vector_index_type.as_str(), "hnsw");
+    }
+
+    #[test]
+    fn test_performance_config_index_defaults() {
+        let config: PerformanceConfig =
+            toml::from_str("enable_vector_indexes = true\nvector_index_lists = 100").unwrap();
+
+        assert_eq!(config.vector_index_type, VectorIndexType::Ivfflat);
+        assert_eq!(config.hnsw_m, 16);
+        assert_eq!(config.hnsw_ef_construction, 64);
+        assert_eq!(config.ivfflat_probes, 10);
+        assert_eq!(config.hnsw_ef_search, 40);
     }
 
     // ====== Cache Config Tests ======
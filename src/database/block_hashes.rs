@@ -0,0 +1,291 @@
+//! Header hashes of processed blocks and rollback of diverged blocks
+//!
+//! Every applied chunk records its hash and parent hash in `block_hashes`.
+//! When sync finds that the stored chain no longer matches the chunks served
+//! by the node, everything synced from the fork height up is rolled back by
+//! `shard_id` / `block_height` before the canonical chunks are re-applied.
+//! Signers and FID registrations are folded state rather than rows per
+//! message, so the ones last changed by a rolled-back block are rebuilt from
+//! the on-chain events below the fork.
+
+use sqlx::PgConnection;
+
+use super::Database;
+use crate::Result;
+
+/// Tables whose rows carry the `shard_id` they were synced from, with the
+/// column holding their block height
+const BLOCK_TAGGED_TABLES: &[(&str, &str)] = &[
+    ("user_profile_changes", "block_height"),
+    ("casts", "block_height"),
+    ("cast_removes", "block_height"),
+    ("links", "block_height"),
+    ("link_compact_states", "block_height"),
+    ("user_data", "block_height"),
+    ("reactions", "block_height"),
+    ("verifications", "block_height"),
+    ("username_proofs", "block_height"),
+    ("fname_transfers", "block_height"),
+    ("storage_lends", "block_height"),
+    ("frame_actions", "block_height"),
+    // `block_number` is the Ethereum block of the event
+    ("onchain_events", "shard_block_height"),
+    ("storage_rents", "block_height"),
+    ("processed_messages", "block_height"),
+    ("rejected_messages", "block_height"),
+];
+
+/// Decoded on-chain state tagged with the block of its last change; rows of
+/// rolled-back blocks are rebuilt from the on-chain events that remain
+const ONCHAIN_STATE_TABLES: &[&str] = &["fid_signers", "fid_registrations"];
+
+/// Tables of vectors derived from casts
+const CAST_EMBEDDING_TABLES: &[&str] = &[
+    "cast_embeddings",
+    "cast_embedding_chunks",
+    "cast_embedding_aggregated",
+];
+
+/// Stored hashes of one block
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct BlockHash {
+    pub block_height: i64,
+    pub block_hash: Vec<u8>,
+    pub parent_hash: Vec<u8>,
+}
+
+/// What a rollback removed
+#[derive(Debug, Clone, Default)]
+pub struct BlockRollback {
+    /// Rows deleted across all block-tagged tables
+    pub rows_deleted: u64,
+    /// Hashes of the deleted casts, whose embeddings may now be orphaned
+    pub cast_hashes: Vec<Vec<u8>>,
+}
+
+/// Record block hashes, replacing those of re-applied heights
+///
+/// `blocks` holds `(shard_id, block_height, block_hash, parent_hash)`; a
+/// height listed twice keeps its last entry.
+pub async fn record_block_hashes(
+    conn: &mut PgConnection,
+    blocks: &[(u32, u64, Vec<u8>, Vec<u8>)],
+) -> Result<()> {
+    let mut latest = std::collections::BTreeMap::new();
+    for (shard_id, height, hash, parent_hash) in blocks {
+        latest.insert((*shard_id, *height), (hash, parent_hash));
+    }
+
+    let mut shard_ids = Vec::with_capacity(latest.len());
+    let mut heights = Vec::with_capacity(latest.len());
+    let mut hashes = Vec::with_capacity(latest.len());
+    let mut parent_hashes = Vec::with_capacity(latest.len());
+    for ((shard_id, height), (hash, parent_hash)) in latest {
+        shard_ids.push(i32::try_from(shard_id).unwrap_or(i32::MAX));
+        heights.push(i64::try_from(height).unwrap_or(i64::MAX));
+        hashes.push(hash.clone());
+        parent_hashes.push(parent_hash.clone());
+    }
+
+    sqlx::query(
+        r"
+        INSERT INTO block_hashes (shard_id, block_height, block_hash, parent_hash)
+        SELECT * FROM UNNEST($1::int[], $2::bigint[], $3::bytea[], $4::bytea[])
+        ON CONFLICT (shard_id, block_height)
+        DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            parent_hash = EXCLUDED.parent_hash,
+            recorded_at = NOW()
+        ",
+    )
+    .bind(&shard_ids)
+    .bind(&heights)
+    .bind(&hashes)
+    .bind(&parent_hashes)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+impl Database {
+    /// Stored block hashes of a shard in `from..=to`, ordered by height
+    pub async fn block_hashes(&self, shard_id: u32, from: u64, to: u64) -> Result<Vec<BlockHash>> {
+        Ok(sqlx::query_as::<_, BlockHash>(
+            "SELECT block_height, block_hash, parent_hash FROM block_hashes \
+             WHERE shard_id = $1 AND block_height BETWEEN $2 AND $3 ORDER BY block_height",
+        )
+        .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+        .bind(i64::try_from(from).unwrap_or(i64::MAX))
+        .bind(i64::try_from(to).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Stored hashes of one block
+    pub async fn block_hash(&self, shard_id: u32, height: u64) -> Result<Option<BlockHash>> {
+        Ok(self.block_hashes(shard_id, height, height).await?.pop())
+    }
+
+    /// Highest block of a shard with a stored hash
+    pub async fn highest_hashed_block(&self, shard_id: u32) -> Result<Option<u64>> {
+        let height: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(block_height) FROM block_hashes WHERE shard_id = $1")
+                .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(height.and_then(|height| u64::try_from(height).ok()))
+    }
+}
+
+/// Remove everything synced from `shard_id` at `from_height` and above
+///
+/// Runs on the caller's transaction, so the canonical chunks can be
+/// re-applied before anything is committed. Sync progress is moved back
+/// below the fork as well.
+pub async fn rollback_blocks(
+    conn: &mut PgConnection,
+    shard_id: u32,
+    from_height: u64,
+) -> Result<BlockRollback> {
+    let shard = i32::try_from(shard_id).unwrap_or(i32::MAX);
+    let from = i64::try_from(from_height).unwrap_or(i64::MAX);
+    let mut rollback = BlockRollback::default();
+
+    for (table, height_column) in BLOCK_TAGGED_TABLES {
+        if *table == "casts" {
+            rollback.cast_hashes = sqlx::query_scalar(
+                "DELETE FROM casts WHERE shard_id = $1 AND block_height >= $2 \
+                 RETURNING message_hash",
+            )
+            .bind(shard)
+            .bind(from)
+            .fetch_all(&mut *conn)
+            .await?;
+            rollback.rows_deleted += rollback.cast_hashes.len() as u64;
+            continue;
+        }
+
+        rollback.rows_deleted += sqlx::query(&format!(
+            "DELETE FROM {table} WHERE shard_id = $1 AND {height_column} >= $2"
+        ))
+        .bind(shard)
+        .bind(from)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    let mut onchain_fids: Vec<i64> = Vec::new();
+    for table in ONCHAIN_STATE_TABLES {
+        let fids: Vec<i64> = sqlx::query_scalar(&format!(
+            "DELETE FROM {table} WHERE shard_id = $1 AND block_height >= $2 RETURNING fid"
+        ))
+        .bind(shard)
+        .bind(from)
+        .fetch_all(&mut *conn)
+        .await?;
+        rollback.rows_deleted += fids.len() as u64;
+        onchain_fids.extend(fids);
+    }
+    onchain_fids.sort_unstable();
+    onchain_fids.dedup();
+    crate::sync::shard_processor::rebuild_onchain_state(conn, &onchain_fids).await?;
+
+    sqlx::query("DELETE FROM block_hashes WHERE shard_id = $1 AND block_height >= $2")
+        .bind(shard)
+        .bind(from)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM processed_blocks WHERE shard_id = $1 AND start_height >= $2")
+        .bind(shard)
+        .bind(from)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "UPDATE processed_blocks SET end_height = $2 - 1, updated_at = NOW() \
+         WHERE shard_id = $1 AND end_height >= $2",
+    )
+    .bind(shard)
+    .bind(from)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "UPDATE sync_progress SET last_processed_height = GREATEST($2 - 1, 0), updated_at = NOW() \
+         WHERE shard_id = $1 AND last_processed_height >= $2",
+    )
+    .bind(shard)
+    .bind(from)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(rollback)
+}
+
+/// Delete embeddings of `cast_hashes` whose cast no longer exists
+///
+/// Casts that the canonical chunks re-applied keep their vectors.
+pub async fn prune_orphaned_cast_embeddings(
+    conn: &mut PgConnection,
+    cast_hashes: &[Vec<u8>],
+) -> Result<u64> {
+    if cast_hashes.is_empty() {
+        return Ok(0);
+    }
+
+    let mut pruned = 0;
+    for table in CAST_EMBEDDING_TABLES {
+        pruned += sqlx::query(&format!(
+            "DELETE FROM {table} e WHERE e.message_hash = ANY($1) \
+             AND NOT EXISTS (SELECT 1 FROM casts c WHERE c.message_hash = e.message_hash)"
+        ))
+        .bind(cast_hashes)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MIGRATIONS;
+
+    /// Whether the migrations give `table` a `column`
+    fn defines_column(sql: &str, table: &str, column: &str) -> bool {
+        let created = sql
+            .split(&format!("CREATE TABLE IF NOT EXISTS {table} ("))
+            .skip(1)
+            .filter_map(|rest| rest.split("\n);").next())
+            .any(|body| {
+                body.lines()
+                    .any(|line| line.split_whitespace().next() == Some(column))
+            });
+        created
+            || sql.contains(&format!(
+                "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {column} "
+            ))
+    }
+
+    #[test]
+    fn test_rolled_back_tables_have_block_columns() {
+        let sql: String = MIGRATIONS.iter().map(|migration| migration.up).collect();
+
+        for (table, height_column) in BLOCK_TAGGED_TABLES {
+            assert!(defines_column(&sql, table, "shard_id"), "{table}.shard_id");
+            assert!(
+                defines_column(&sql, table, height_column),
+                "{table}.{height_column}"
+            );
+        }
+        for table in ONCHAIN_STATE_TABLES {
+            assert!(defines_column(&sql, table, "shard_id"), "{table}.shard_id");
+            assert!(
+                defines_column(&sql, table, "block_height"),
+                "{table}.block_height"
+            );
+        }
+        assert!(!defines_column(&sql, "onchain_events", "block_height"));
+    }
+}
@@ -1,14 +1,63 @@
 #![allow(clippy::cast_possible_truncation)] // Vec lengths and batch sizes won't exceed u32::MAX
 
+use super::embedding_versions::active_column;
+use super::embedding_versions::check_active_dimension;
+use super::embedding_versions::multi_vector_searchable;
+use super::embedding_versions::CAST_EMBEDDING_COLUMN;
 use super::Database;
 use crate::models::Cast;
 use crate::models::CastQuery;
+use crate::models::CastSearchFilters;
 use crate::models::CastSearchResult;
 use crate::models::CastSortBy;
 use crate::models::CastStats;
 use crate::models::SortOrder;
 use crate::Result;
 
+/// SQL predicate excluding casts that have a `CastRemove` tombstone (expects casts aliased as `c`)
+pub const NOT_REMOVED: &str =
+    "NOT EXISTS (SELECT 1 FROM cast_removes cr WHERE cr.target_hash = c.message_hash AND cr.fid = c.fid)";
+
+/// [`NOT_REMOVED`] for reply counts (expects replies aliased as `r`)
+pub const REPLY_NOT_REMOVED: &str =
+    "NOT EXISTS (SELECT 1 FROM cast_removes cr WHERE cr.target_hash = r.message_hash AND cr.fid = r.fid)";
+
+/// SQL conditions applying [`CastSearchFilters`] to casts aliased as `c`
+///
+/// The filters must be bound as `$4` to `$7` with [`bind_cast_filters`].
+pub const CAST_FILTERS: &str = "(cardinality($4::bigint[]) = 0 OR c.fid = ANY($4)) \
+    AND ($5::bigint IS NULL OR c.timestamp >= $5) \
+    AND ($6::bigint IS NULL OR c.timestamp < $6) \
+    AND (cardinality($7::text[]) = 0 OR c.parent_url = ANY($7))";
+
+/// Bind `filters` as the parameters of [`CAST_FILTERS`]
+pub fn bind_cast_filters<'q, O>(
+    query: sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments>,
+    filters: &'q CastSearchFilters,
+) -> sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments> {
+    query
+        .bind(&filters.fids)
+        .bind(filters.since)
+        .bind(filters.until)
+        .bind(&filters.channels)
+}
+
+/// Resolve a channel filter to the `parent_url` stored on channel casts.
+///
+/// Full URLs (anything with a scheme, e.g. `https://` or `chain://`) pass through
+/// unchanged; bare names such as `base` or `/base` map to the Warpcast channel URL.
+#[must_use]
+pub fn channel_url(channel: &str) -> String {
+    if channel.contains("://") {
+        channel.to_string()
+    } else {
+        format!(
+            "https://warpcast.com/~/channel/{}",
+            channel.trim_start_matches('/')
+        )
+    }
+}
+
 /// Cast thread structure
 #[derive(Debug, Clone)]
 pub struct CastThread {
@@ -30,15 +79,16 @@ impl Database {
             || query.text_search.is_some()
             || query.parent_hash.is_some()
             || query.root_hash.is_some()
+            || query.channel.is_some()
             || query.start_timestamp.is_some()
             || query.end_timestamp.is_some()
         {
             // Complex query - use the specific filters we support
-            let mut conditions = vec!["1=1".to_string()];
+            let mut conditions = vec![NOT_REMOVED.to_string()];
             let mut param_idx = 1;
 
             if let Some(_fid) = query.fid {
-                let condition = format!("fid = ${param_idx}");
+                let condition = format!("c.fid = ${param_idx}");
                 conditions.push(condition);
                 param_idx += 1;
             }
@@ -55,6 +105,18 @@ impl Database {
                 param_idx += 1;
             }
 
+            if let Some(_root_hash) = &query.root_hash {
+                let condition = format!("root_hash = ${param_idx}");
+                conditions.push(condition);
+                param_idx += 1;
+            }
+
+            if let Some(_channel) = &query.channel {
+                let condition = format!("parent_url = ${param_idx}");
+                conditions.push(condition);
+                param_idx += 1;
+            }
+
             if let Some(_start_timestamp) = query.start_timestamp {
                 let condition = format!("timestamp >= ${param_idx}");
                 conditions.push(condition);
@@ -79,7 +141,7 @@ impl Database {
             };
 
             let sql = format!(
-                "SELECT * FROM casts WHERE {where_clause} ORDER BY {order_by} {order_dir} LIMIT {limit} OFFSET {offset}"
+                "SELECT * FROM casts c WHERE {where_clause} ORDER BY {order_by} {order_dir} LIMIT {limit} OFFSET {offset}"
             );
 
             let mut q = sqlx::query_as::<_, Cast>(&sql);
@@ -93,6 +155,12 @@ impl Database {
             if let Some(parent_hash) = &query.parent_hash {
                 q = q.bind(parent_hash);
             }
+            if let Some(root_hash) = &query.root_hash {
+                q = q.bind(root_hash);
+            }
+            if let Some(channel) = &query.channel {
+                q = q.bind(channel_url(channel));
+            }
             if let Some(start_timestamp) = query.start_timestamp {
                 q = q.bind(start_timestamp);
             }
@@ -104,7 +172,7 @@ impl Database {
         } else {
             // Simple query - just sort and paginate
             sqlx::query_as::<_, Cast>(
-                "SELECT * FROM casts ORDER BY timestamp DESC LIMIT $1 OFFSET $2",
+                &format!("SELECT * FROM casts c WHERE {NOT_REMOVED} ORDER BY timestamp DESC LIMIT $1 OFFSET $2"),
             )
             .bind(limit)
             .bind(offset)
@@ -128,9 +196,14 @@ impl Database {
         // This is more efficient than dynamic SQL construction
         let limit = limit.unwrap_or(1_000_000);
 
-        let casts = sqlx::query_as::<_, Cast>(
-            "SELECT * FROM casts WHERE fid = $1 ORDER BY timestamp DESC LIMIT $2 OFFSET $3",
-        )
+        let casts = sqlx::query_as::<_, Cast>(&format!(
+            r"
+            SELECT c.* FROM casts c
+            WHERE c.fid = $1
+            AND {NOT_REMOVED}
+            ORDER BY c.timestamp DESC LIMIT $2 OFFSET $3
+            "
+        ))
         .bind(fid)
         .bind(limit)
         .bind(offset)
@@ -150,10 +223,13 @@ impl Database {
             .fetch_one(&self.pool)
             .await?;
 
-        let existing_embeddings =
-            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM cast_embeddings")
-                .fetch_one(&self.pool)
-                .await?;
+        let active = self.active_embedding_version().await?;
+        let existing_embeddings = sqlx::query_scalar::<_, i64>(&format!(
+            "SELECT COUNT(*) FROM cast_embeddings WHERE {} IS NOT NULL",
+            active_column(CAST_EMBEDDING_COLUMN, active.as_ref())
+        ))
+        .fetch_one(&self.pool)
+        .await?;
 
         let missing = total_casts - existing_embeddings;
 
@@ -173,23 +249,27 @@ impl Database {
         limit: usize,
         offset: usize,
     ) -> Result<Vec<Cast>> {
-        let casts = sqlx::query_as::<_, Cast>(
+        let active = self.active_embedding_version().await?;
+        let sql = format!(
             r"
             SELECT c.* 
             FROM casts c
             WHERE c.message_hash NOT IN (
-                SELECT message_hash FROM cast_embeddings
+                SELECT message_hash FROM cast_embeddings WHERE {} IS NOT NULL
             )
             AND c.text IS NOT NULL 
             AND length(c.text) > 0
+            AND {NOT_REMOVED}
             ORDER BY c.timestamp DESC
             LIMIT $1 OFFSET $2
             ",
-        )
-        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
-        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
-        .fetch_all(&self.pool)
-        .await?;
+            active_column(CAST_EMBEDDING_COLUMN, active.as_ref())
+        );
+        let casts = sqlx::query_as::<_, Cast>(&sql)
+            .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+            .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await?;
 
         Ok(casts)
     }
@@ -204,10 +284,12 @@ impl Database {
             return Ok(std::collections::HashSet::new());
         }
 
-        // Get all hashes that already have embeddings
-        let existing = sqlx::query_scalar::<_, Vec<u8>>(
-            "SELECT message_hash FROM cast_embeddings WHERE message_hash = ANY($1)",
-        )
+        // Get all hashes that already have embeddings from the active model
+        let active = self.active_embedding_version().await?;
+        let existing = sqlx::query_scalar::<_, Vec<u8>>(&format!(
+            "SELECT message_hash FROM cast_embeddings WHERE message_hash = ANY($1) AND {} IS NOT NULL",
+            active_column(CAST_EMBEDDING_COLUMN, active.as_ref())
+        ))
         .bind(message_hashes)
         .fetch_all(&self.pool)
         .await?;
@@ -225,6 +307,8 @@ impl Database {
     }
 
     /// Store cast embedding (single vector - backward compatibility)
+    ///
+    /// Written to the column of the active embedding version.
     pub async fn store_cast_embedding(
         &self,
         message_hash: &[u8],
@@ -232,16 +316,20 @@ impl Database {
         text: &str,
         embedding: &[f32],
     ) -> Result<()> {
-        sqlx::query(
+        let active = self.active_embedding_version().await?;
+        check_active_dimension(active.as_ref(), embedding)?;
+        let column = active_column(CAST_EMBEDDING_COLUMN, active.as_ref());
+
+        sqlx::query(&format!(
             r"
-            INSERT INTO cast_embeddings (message_hash, fid, text, embedding)
+            INSERT INTO cast_embeddings (message_hash, fid, text, {column})
             VALUES ($1, $2, $3, $4)
             ON CONFLICT (message_hash) 
             DO UPDATE SET 
-                embedding = EXCLUDED.embedding,
+                {column} = EXCLUDED.{column},
                 updated_at = NOW()
-            ",
-        )
+            "
+        ))
         .bind(message_hash)
         .bind(fid)
         .bind(text)
@@ -332,6 +420,9 @@ impl Database {
     /// Semantic search for casts (lightweight version without engagement metrics)
     /// Now searches both single-vector and multi-vector tables for comprehensive results
     ///
+    /// Multi-vector tables hold base-model vectors, so they are only searched
+    /// while the base embedding version is active.
+    ///
     /// # Panics
     /// Never panics - unwrap is only called when HashMap key is known to exist
     pub async fn semantic_search_casts_simple(
@@ -357,8 +448,12 @@ impl Database {
             chunk_strategy: Option<String>,
         }
 
+        let active = self.active_embedding_version().await?;
+        check_active_dimension(active.as_ref(), &query_embedding)?;
+        let column = active_column(CAST_EMBEDDING_COLUMN, active.as_ref());
+
         // Search both single-vector and multi-vector tables
-        let raw_results = sqlx::query_as::<_, RawResult>(
+        let single_vector = format!(
             r"
             (
                 -- Search single-vector embeddings (original table)
@@ -370,14 +465,19 @@ impl Database {
                     c.parent_hash,
                     c.embeds,
                     c.mentions,
-                    1 - (ce.embedding <=> $1::vector) as similarity,
+                    1 - (ce.{column} <=> $1::vector) as similarity,
                     NULL::integer as chunk_index,
                     NULL::text as chunk_text,
                     'single'::text as chunk_strategy
                 FROM cast_embeddings ce
                 INNER JOIN casts c ON ce.message_hash = c.message_hash
-                WHERE 1 - (ce.embedding <=> $1::vector) > $2
+                WHERE 1 - (ce.{column} <=> $1::vector) > $2
+                AND {NOT_REMOVED}
             )
+            "
+        );
+        let multi_vector = &format!(
+            r"
             UNION ALL
             (
                 -- Search multi-vector chunks
@@ -396,6 +496,7 @@ impl Database {
                 FROM cast_embedding_chunks cec
                 INNER JOIN casts c ON cec.message_hash = c.message_hash
                 WHERE 1 - (cec.embedding <=> $1::vector) > $2
+                AND {NOT_REMOVED}
             )
             UNION ALL
             (
@@ -415,16 +516,27 @@ impl Database {
                 FROM cast_embedding_aggregated cea
                 INNER JOIN casts c ON cea.message_hash = c.message_hash
                 WHERE 1 - (cea.embedding <=> $1::vector) > $2
+                AND {NOT_REMOVED}
             )
-            ORDER BY similarity DESC
-            LIMIT $3
-            ",
-        )
-        .bind(&query_embedding)
-        .bind(threshold_val)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+            "
+        );
+        let sql = format!(
+            "{single_vector}{}\n            ORDER BY similarity DESC\n            LIMIT $3",
+            if multi_vector_searchable(active.as_ref()) {
+                multi_vector
+            } else {
+                ""
+            }
+        );
+
+        let mut tx = self.begin_vector_search().await?;
+        let raw_results = sqlx::query_as::<_, RawResult>(&sql)
+            .bind(&query_embedding)
+            .bind(threshold_val)
+            .bind(limit)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
 
         // Deduplicate results by message_hash, keeping the highest similarity score
         let mut deduplicated: std::collections::HashMap<Vec<u8>, CastSearchResult> =
@@ -471,12 +583,32 @@ impl Database {
         Ok(results)
     }
 
-    /// Semantic search for casts with engagement metrics
+    /// Semantic search for casts with engagement metrics, optionally limited to a channel
     pub async fn semantic_search_casts(
         &self,
         query_embedding: Vec<f32>,
         limit: i64,
         threshold: Option<f32>,
+        channel: Option<&str>,
+    ) -> Result<Vec<CastSearchResult>> {
+        let filters = CastSearchFilters {
+            channels: channel.map(channel_url).into_iter().collect(),
+            ..CastSearchFilters::default()
+        };
+        self.filtered_semantic_search_casts(query_embedding, limit, threshold, &filters)
+            .await
+    }
+
+    /// [`Self::semantic_search_casts`] restricted to casts matching `filters`
+    ///
+    /// # Errors
+    /// Returns an error if the query fails
+    pub async fn filtered_semantic_search_casts(
+        &self,
+        query_embedding: Vec<f32>,
+        limit: i64,
+        threshold: Option<f32>,
+        filters: &CastSearchFilters,
     ) -> Result<Vec<CastSearchResult>> {
         let threshold_val = threshold.unwrap_or(0.0);
 
@@ -494,7 +626,12 @@ impl Database {
             reaction_count: Option<i64>,
         }
 
-        let raw_results = sqlx::query_as::<_, RawResult>(
+        let active = self.active_embedding_version().await?;
+        check_active_dimension(active.as_ref(), &query_embedding)?;
+        let column = active_column(CAST_EMBEDDING_COLUMN, active.as_ref());
+
+        let mut tx = self.begin_vector_search().await?;
+        let sql = format!(
             r"
             SELECT 
                 ce.message_hash,
@@ -504,8 +641,8 @@ impl Database {
                 c.parent_hash,
                 c.embeds,
                 c.mentions,
-                1 - (ce.embedding <=> $1::vector) as similarity,
-                (SELECT COUNT(*) FROM casts WHERE parent_hash = ce.message_hash) as reply_count,
+                1 - (ce.{column} <=> $1::vector) as similarity,
+                (SELECT COUNT(*) FROM casts r WHERE r.parent_hash = ce.message_hash AND {REPLY_NOT_REMOVED}) as reply_count,
                 (SELECT COUNT(*) FROM (
                     SELECT *, ROW_NUMBER() OVER (
                         PARTITION BY fid, target_cast_hash 
@@ -516,16 +653,23 @@ impl Database {
                 ) r WHERE r.rn = 1 AND r.event_type = 'add') as reaction_count
             FROM cast_embeddings ce
             INNER JOIN casts c ON ce.message_hash = c.message_hash
-            WHERE 1 - (ce.embedding <=> $1::vector) > $2
-            ORDER BY ce.embedding <=> $1::vector
+            WHERE 1 - (ce.{column} <=> $1::vector) > $2
+            AND {CAST_FILTERS}
+            AND {NOT_REMOVED}
+            ORDER BY ce.{column} <=> $1::vector
             LIMIT $3
-            ",
+            "
+        );
+        let raw_results = bind_cast_filters(
+            sqlx::query_as::<_, RawResult>(&sql)
+                .bind(&query_embedding)
+                .bind(threshold_val)
+                .bind(limit),
+            filters,
         )
-        .bind(&query_embedding)
-        .bind(threshold_val)
-        .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         let results = raw_results
             .into_iter()
@@ -552,10 +696,10 @@ impl Database {
     /// Get cast statistics (replies, reactions, etc.)
     pub async fn get_cast_stats(&self, message_hash: &[u8]) -> Result<CastStats> {
         let stats = sqlx::query_as::<_, CastStats>(
-            r"
+            &format!(r"
             SELECT 
                 $1 as message_hash,
-                (SELECT COUNT(*) FROM casts WHERE parent_hash = $1) as reply_count,
+                (SELECT COUNT(*) FROM casts r WHERE r.parent_hash = $1 AND {REPLY_NOT_REMOVED}) as reply_count,
                 (SELECT COUNT(*) FROM (
                     SELECT *, ROW_NUMBER() OVER (
                         PARTITION BY fid, target_cast_hash 
@@ -572,7 +716,7 @@ impl Database {
                     FROM reactions
                     WHERE target_cast_hash = $1
                 ) r WHERE r.rn = 1 AND r.event_type = 'add') as unique_reactors
-            ",
+            "),
         )
         .bind(message_hash)
         .fetch_one(&self.pool)
@@ -599,6 +743,15 @@ impl Database {
         threshold: Option<f32>,
         search_strategy: Option<&str>, // "chunks", "aggregated", "both"
     ) -> Result<Vec<CastSearchResult>> {
+        let active = self.active_embedding_version().await?;
+        if let Some(version) = active.filter(|v| !multi_vector_searchable(Some(v))) {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Multi-vector embeddings come from the base embedding model and cannot be \
+                 searched while version {} ({}) is active",
+                version.version, version.model
+            )));
+        }
+
         let threshold_val = threshold.unwrap_or(0.0);
         let strategy = search_strategy.unwrap_or("both");
 
@@ -618,7 +771,7 @@ impl Database {
         }
 
         let query = match strategy {
-            "chunks" => {
+            "chunks" => &format!(
                 r"
                 SELECT 
                     cec.message_hash,
@@ -635,11 +788,12 @@ impl Database {
                 FROM cast_embedding_chunks cec
                 INNER JOIN casts c ON cec.message_hash = c.message_hash
                 WHERE 1 - (cec.embedding <=> $1::vector) > $2
+                AND {NOT_REMOVED}
                 ORDER BY cec.embedding <=> $1::vector
                 LIMIT $3
             "
-            }
-            "aggregated" => {
+            ),
+            "aggregated" => &format!(
                 r"
                 SELECT 
                     cea.message_hash,
@@ -656,11 +810,12 @@ impl Database {
                 FROM cast_embedding_aggregated cea
                 INNER JOIN casts c ON cea.message_hash = c.message_hash
                 WHERE 1 - (cea.embedding <=> $1::vector) > $2
+                AND {NOT_REMOVED}
                 ORDER BY cea.embedding <=> $1::vector
                 LIMIT $3
             "
-            }
-            "both" => {
+            ),
+            "both" => &format!(
                 r"
                 (
                     SELECT 
@@ -678,6 +833,7 @@ impl Database {
                     FROM cast_embedding_chunks cec
                     INNER JOIN casts c ON cec.message_hash = c.message_hash
                     WHERE 1 - (cec.embedding <=> $1::vector) > $2
+                    AND {NOT_REMOVED}
                 )
                 UNION ALL
                 (
@@ -696,11 +852,12 @@ impl Database {
                     FROM cast_embedding_aggregated cea
                     INNER JOIN casts c ON cea.message_hash = c.message_hash
                     WHERE 1 - (cea.embedding <=> $1::vector) > $2
+                    AND {NOT_REMOVED}
                 )
                 ORDER BY similarity DESC
                 LIMIT $3
             "
-            }
+            ),
             _ => {
                 return Err(crate::SnapRagError::Custom(
                     "Invalid search strategy".to_string(),
@@ -708,12 +865,14 @@ impl Database {
             }
         };
 
+        let mut tx = self.begin_vector_search().await?;
         let raw_results = sqlx::query_as::<_, RawResult>(query)
             .bind(&query_embedding)
             .bind(threshold_val)
             .bind(limit)
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *tx)
             .await?;
+        tx.commit().await?;
 
         let results = raw_results
             .into_iter()
@@ -737,15 +896,77 @@ impl Database {
         Ok(results)
     }
 
+    /// Get all casts in a thread by its root hash, oldest first
+    pub async fn get_casts_by_root(
+        &self,
+        root_hash: &[u8],
+        limit: Option<i64>,
+    ) -> Result<Vec<Cast>> {
+        let casts = sqlx::query_as::<_, Cast>(&format!(
+            r"
+            SELECT c.* FROM casts c
+            WHERE c.root_hash = $1
+            AND {NOT_REMOVED}
+            ORDER BY c.timestamp ASC LIMIT $2
+            "
+        ))
+        .bind(root_hash)
+        .bind(limit.unwrap_or(500))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(casts)
+    }
+
+    /// Fill in `root_hash` for casts stored before their thread root was known.
+    ///
+    /// Top-level casts become their own root; replies then inherit their parent's
+    /// root one level per pass until nothing changes. Returns the rows updated.
+    pub async fn backfill_cast_root_hashes(&self, max_passes: usize) -> Result<u64> {
+        let mut total = sqlx::query(
+            "UPDATE casts SET root_hash = message_hash WHERE root_hash IS NULL AND parent_hash IS NULL",
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        for pass in 0..max_passes {
+            let updated = sqlx::query(
+                r"
+                UPDATE casts c SET root_hash = p.root_hash
+                FROM casts p
+                WHERE c.root_hash IS NULL AND c.parent_hash IS NOT NULL
+                  AND p.message_hash = c.parent_hash AND p.root_hash IS NOT NULL
+                ",
+            )
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+            tracing::debug!("Root hash backfill pass {}: {} rows", pass + 1, updated);
+            total += updated;
+            if updated == 0 {
+                break;
+            }
+        }
+
+        Ok(total)
+    }
+
     /// Get cast replies (children)
     pub async fn get_cast_replies(
         &self,
         parent_hash: Vec<u8>,
         limit: Option<i64>,
     ) -> Result<Vec<Cast>> {
-        let casts = sqlx::query_as::<_, Cast>(
-            "SELECT * FROM casts WHERE parent_hash = $1 ORDER BY timestamp ASC LIMIT $2",
-        )
+        let casts = sqlx::query_as::<_, Cast>(&format!(
+            r"
+            SELECT c.* FROM casts c
+            WHERE c.parent_hash = $1
+            AND {NOT_REMOVED}
+            ORDER BY c.timestamp ASC LIMIT $2
+            "
+        ))
         .bind(parent_hash)
         .bind(limit.unwrap_or(100))
         .fetch_all(&self.pool)
@@ -778,21 +999,27 @@ impl Database {
         let current_cast = cast.unwrap();
         thread.root = Some(current_cast.clone());
 
-        // Traverse up to find parents
-        let mut current_parent = current_cast.parent_hash.clone();
-        let mut depth = 0;
-        while let Some(parent_hash) = current_parent {
-            if depth >= max_depth {
-                break;
-            }
-
-            if let Some(parent) = self.get_cast_by_hash(parent_hash.clone()).await? {
-                thread.parents.push(parent.clone());
-                current_parent = parent.parent_hash.clone();
-                depth += 1;
-            } else {
-                break;
-            }
+        // Fetch the ancestor chain in one round trip
+        if current_cast.parent_hash.is_some() && max_depth > 0 {
+            thread.parents = sqlx::query_as::<_, Cast>(
+                r"
+                WITH RECURSIVE chain AS (
+                    SELECT p.*, 1 AS depth FROM casts p WHERE p.message_hash = $1
+                    UNION ALL
+                    SELECT p.*, chain.depth + 1 FROM casts p
+                    JOIN chain ON p.message_hash = chain.parent_hash
+                    WHERE chain.depth < $2
+                )
+                SELECT id, fid, text, timestamp, message_hash, parent_hash, root_hash, embeds,
+                       mentions, created_at, shard_id, block_height, transaction_fid,
+                       parent_fid, parent_url
+                FROM chain ORDER BY depth ASC
+                ",
+            )
+            .bind(current_cast.parent_hash.clone())
+            .bind(i32::try_from(max_depth).unwrap_or(i32::MAX))
+            .fetch_all(&self.pool)
+            .await?;
         }
 
         // Reverse parents so root is first
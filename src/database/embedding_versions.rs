@@ -0,0 +1,625 @@
+//! Embedding model versions and their side-by-side vector columns
+//!
+//! Every stored vector belongs to a registered embedding version (model +
+//! dimension). Version 1 lives in the original vector columns; each later
+//! version gets `<column>_v<version>` columns next to them. Search reads the
+//! `active` version while a `shadow` version is backfilled, and cutover swaps
+//! the two in a single transaction.
+
+use super::Database;
+use crate::models::UserProfile;
+use crate::Result;
+use crate::SnapRagError;
+
+/// Version stored in the original (unsuffixed) vector columns
+pub const BASE_EMBEDDING_VERSION: i32 = 1;
+
+/// Versioned vector column of `cast_embeddings`
+pub const CAST_EMBEDDING_COLUMN: &str = "embedding";
+
+/// Versioned vector columns of `profile_embeddings`
+pub const PROFILE_EMBEDDING_COLUMNS: [&str; 3] =
+    ["profile_embedding", "bio_embedding", "interests_embedding"];
+
+/// `undefined_table`: the registry migration has not been applied yet
+const UNDEFINED_TABLE: &str = "42P01";
+
+/// A registered embedding model version
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EmbeddingVersion {
+    pub version: i32,
+    pub model: String,
+    pub dimension: i32,
+    /// `active`, `shadow` or `retired`
+    pub state: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub activated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub retired_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the version's vectors were removed
+    pub pruned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl EmbeddingVersion {
+    /// Column holding this version's vectors for a base column name
+    #[must_use]
+    pub fn column(&self, base: &str) -> String {
+        versioned_column(base, self.version)
+    }
+
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.state == "active"
+    }
+
+    #[must_use]
+    pub fn is_shadow(&self) -> bool {
+        self.state == "shadow"
+    }
+
+    /// Dimension as a vector length
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        usize::try_from(self.dimension).unwrap_or(0)
+    }
+}
+
+/// How much of the active version's vectors a target version has caught up with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmbeddingCoverage {
+    /// `cast_embeddings` rows with an active vector
+    pub casts_total: i64,
+    /// ... of which also have a target vector
+    pub casts_covered: i64,
+    /// Non-null active `profile_embeddings` vectors (all three columns)
+    pub profiles_total: i64,
+    /// ... of which also have a target vector
+    pub profiles_covered: i64,
+}
+
+impl EmbeddingCoverage {
+    /// Fraction of active vectors that have a counterpart in the target version
+    ///
+    /// Nothing to cover counts as fully covered.
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        let total = self.casts_total + self.profiles_total;
+        if total == 0 {
+            1.0
+        } else {
+            #[allow(clippy::cast_precision_loss)] // Ratio only, precision loss is fine
+            let ratio = (self.casts_covered + self.profiles_covered) as f64 / total as f64;
+            ratio
+        }
+    }
+}
+
+/// Column name of `version` for a base column
+fn versioned_column(base: &str, version: i32) -> String {
+    if version == BASE_EMBEDDING_VERSION {
+        base.to_string()
+    } else {
+        format!("{base}_v{version}")
+    }
+}
+
+/// Column to read and write for `base`, given the active version (if any)
+pub(super) fn active_column(base: &str, active: Option<&EmbeddingVersion>) -> String {
+    active.map_or_else(|| base.to_string(), |version| version.column(base))
+}
+
+/// Reject vectors that do not match the dimension of the active version
+pub(super) fn check_active_dimension(
+    active: Option<&EmbeddingVersion>,
+    embedding: &[f32],
+) -> Result<()> {
+    match active {
+        Some(version) if embedding.len() != version.dimension() => {
+            Err(SnapRagError::Custom(format!(
+                "Embedding has {} dimensions, but the active embedding model {} (version {}) uses {}",
+                embedding.len(),
+                version.model,
+                version.version,
+                version.dimension
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether the multi-vector tables (chunks / aggregated) match the active version
+///
+/// They are only ever written by the base model.
+pub(super) fn multi_vector_searchable(active: Option<&EmbeddingVersion>) -> bool {
+    active.is_none_or(|version| version.version == BASE_EMBEDDING_VERSION)
+}
+
+impl Database {
+    /// List all registered embedding versions
+    pub async fn list_embedding_versions(&self) -> Result<Vec<EmbeddingVersion>> {
+        let versions = sqlx::query_as::<_, EmbeddingVersion>(
+            "SELECT * FROM embedding_versions ORDER BY version",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(versions)
+    }
+
+    /// Version currently served by search
+    ///
+    /// `None` when no version is registered (or the registry migration is not
+    /// applied), in which case the original columns are used.
+    pub async fn active_embedding_version(&self) -> Result<Option<EmbeddingVersion>> {
+        self.embedding_version_in_state("active").await
+    }
+
+    /// Version currently being backfilled, if any
+    pub async fn shadow_embedding_version(&self) -> Result<Option<EmbeddingVersion>> {
+        self.embedding_version_in_state("shadow").await
+    }
+
+    async fn embedding_version_in_state(&self, state: &str) -> Result<Option<EmbeddingVersion>> {
+        let result = sqlx::query_as::<_, EmbeddingVersion>(
+            "SELECT * FROM embedding_versions WHERE state = $1",
+        )
+        .bind(state)
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(version) => Ok(version),
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNDEFINED_TABLE) => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Register the model that produced the vectors in the original columns
+    ///
+    /// Does nothing if the registry already has versions; returns the active one.
+    pub async fn register_base_embedding_version(
+        &self,
+        model: &str,
+        dimension: usize,
+    ) -> Result<EmbeddingVersion> {
+        sqlx::query(
+            r"
+            INSERT INTO embedding_versions (version, model, dimension, state, activated_at)
+            SELECT $1, $2, $3, 'active', NOW()
+            WHERE NOT EXISTS (SELECT 1 FROM embedding_versions)
+            ON CONFLICT (version) DO NOTHING
+            ",
+        )
+        .bind(BASE_EMBEDDING_VERSION)
+        .bind(model)
+        .bind(i32::try_from(dimension).unwrap_or(i32::MAX))
+        .execute(&self.pool)
+        .await?;
+
+        self.active_embedding_version()
+            .await?
+            .ok_or_else(|| SnapRagError::Custom("No active embedding version".to_string()))
+    }
+
+    /// Register a new model as the shadow version and add its vector columns
+    pub async fn add_shadow_embedding_version(
+        &self,
+        model: &str,
+        dimension: usize,
+    ) -> Result<EmbeddingVersion> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("LOCK TABLE embedding_versions IN EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        let live: Vec<EmbeddingVersion> =
+            sqlx::query_as("SELECT * FROM embedding_versions WHERE state IN ('active', 'shadow')")
+                .fetch_all(&mut *tx)
+                .await?;
+        if let Some(shadow) = live.iter().find(|v| v.is_shadow()) {
+            return Err(SnapRagError::Custom(format!(
+                "Embedding version {} ({}) is already being backfilled; cut over or prune it first",
+                shadow.version, shadow.model
+            )));
+        }
+        if !live.iter().any(EmbeddingVersion::is_active) {
+            return Err(SnapRagError::Custom(
+                "No active embedding version registered".to_string(),
+            ));
+        }
+
+        let version: EmbeddingVersion = sqlx::query_as(
+            r"
+            INSERT INTO embedding_versions (version, model, dimension, state)
+            SELECT COALESCE(MAX(version), 0) + 1, $1, $2, 'shadow'
+            FROM embedding_versions
+            RETURNING *
+            ",
+        )
+        .bind(model)
+        .bind(i32::try_from(dimension).unwrap_or(i32::MAX))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut columns = vec![("cast_embeddings", CAST_EMBEDDING_COLUMN)];
+        columns.extend(
+            PROFILE_EMBEDDING_COLUMNS
+                .iter()
+                .map(|column| ("profile_embeddings", *column)),
+        );
+        for (table, base) in columns {
+            let sql = format!(
+                "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {} vector({})",
+                version.column(base),
+                version.dimension
+            );
+            sqlx::query(&sql).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        tracing::info!(
+            "🧬 Registered shadow embedding version {} ({}, {} dims)",
+            version.version,
+            version.model,
+            version.dimension
+        );
+
+        Ok(version)
+    }
+
+    /// Coverage of `target` relative to the active version
+    pub async fn embedding_coverage(&self, target: &EmbeddingVersion) -> Result<EmbeddingCoverage> {
+        let active = self.active_embedding_version().await?;
+        let mut conn = self.pool.acquire().await?;
+        fetch_coverage(&mut conn, active.as_ref(), target).await
+    }
+
+    /// Cast embeddings that still need a vector for `version`, by message hash
+    ///
+    /// Returns `(message_hash, text)` pairs after `after` (keyset pagination).
+    pub async fn get_casts_for_embedding_backfill(
+        &self,
+        version: &EmbeddingVersion,
+        after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<(Vec<u8>, String)>> {
+        let sql = format!(
+            r"
+            SELECT message_hash, text
+            FROM cast_embeddings
+            WHERE {} IS NULL
+              AND ($1::bytea IS NULL OR message_hash > $1)
+            ORDER BY message_hash
+            LIMIT $2
+            ",
+            version.column(CAST_EMBEDDING_COLUMN)
+        );
+
+        let rows = sqlx::query_as::<_, (Vec<u8>, String)>(&sql)
+            .bind(after)
+            .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// Profiles with embeddings that still need vectors for `version`, by FID
+    pub async fn get_profiles_for_embedding_backfill(
+        &self,
+        version: &EmbeddingVersion,
+        after_fid: i64,
+        limit: usize,
+    ) -> Result<Vec<UserProfile>> {
+        let missing = PROFILE_EMBEDDING_COLUMNS
+            .iter()
+            .map(|base| format!("e.{} IS NULL", version.column(base)))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let sql = format!(
+            r"
+            SELECT p.*
+            FROM user_profiles p
+            JOIN profile_embeddings e ON p.fid = e.fid
+            WHERE e.fid > $1 AND ({missing})
+            ORDER BY e.fid
+            LIMIT $2
+            "
+        );
+
+        let profiles = sqlx::query_as::<_, UserProfile>(&sql)
+            .bind(after_fid)
+            .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(profiles)
+    }
+
+    /// Store a cast vector for a specific (usually shadow) version
+    pub async fn store_versioned_cast_embedding(
+        &self,
+        version: &EmbeddingVersion,
+        message_hash: &[u8],
+        embedding: &[f32],
+    ) -> Result<()> {
+        check_version_dimension(version, embedding)?;
+        let sql = format!(
+            "UPDATE cast_embeddings SET {} = $2 WHERE message_hash = $1",
+            version.column(CAST_EMBEDDING_COLUMN)
+        );
+        sqlx::query(&sql)
+            .bind(message_hash)
+            .bind(embedding)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Store profile vectors for a specific (usually shadow) version
+    pub async fn store_versioned_profile_embeddings(
+        &self,
+        version: &EmbeddingVersion,
+        fid: i64,
+        profile_embedding: &[f32],
+        bio_embedding: &[f32],
+        interests_embedding: &[f32],
+    ) -> Result<()> {
+        let embeddings = [profile_embedding, bio_embedding, interests_embedding];
+        for embedding in embeddings {
+            check_version_dimension(version, embedding)?;
+        }
+
+        let assignments = PROFILE_EMBEDDING_COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, base)| format!("{} = ${}", version.column(base), i + 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!("UPDATE profile_embeddings SET {assignments} WHERE fid = $1");
+
+        let mut query = sqlx::query(&sql).bind(fid);
+        for embedding in embeddings {
+            query = query.bind(embedding);
+        }
+        query.execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    /// Atomically make the shadow version active and retire the active one
+    ///
+    /// Fails without changing anything unless the shadow version covers at
+    /// least `min_coverage` (0.0-1.0) of the active version's vectors.
+    pub async fn cutover_embedding_version(&self, min_coverage: f64) -> Result<EmbeddingVersion> {
+        let mut tx = self.pool.begin().await?;
+
+        let live: Vec<EmbeddingVersion> = sqlx::query_as(
+            "SELECT * FROM embedding_versions WHERE state IN ('active', 'shadow') FOR UPDATE",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let active = live.iter().find(|v| v.is_active());
+        let shadow = live.iter().find(|v| v.is_shadow()).ok_or_else(|| {
+            SnapRagError::Custom("No shadow embedding version to cut over to".to_string())
+        })?;
+
+        let coverage = fetch_coverage(&mut tx, active, shadow).await?;
+        if coverage.ratio() < min_coverage {
+            return Err(SnapRagError::Custom(format!(
+                "Shadow embedding version {} covers {:.2}% of vectors, below the required {:.2}%",
+                shadow.version,
+                coverage.ratio() * 100.0,
+                min_coverage * 100.0
+            )));
+        }
+
+        sqlx::query(
+            "UPDATE embedding_versions SET state = 'retired', retired_at = NOW() WHERE state = 'active'",
+        )
+        .execute(&mut *tx)
+        .await?;
+        let activated: EmbeddingVersion = sqlx::query_as(
+            r"
+            UPDATE embedding_versions
+            SET state = 'active', activated_at = NOW()
+            WHERE version = $1
+            RETURNING *
+            ",
+        )
+        .bind(shadow.version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        tracing::info!(
+            "🔀 Cut over to embedding version {} ({}, {} dims)",
+            activated.version,
+            activated.model,
+            activated.dimension
+        );
+
+        Ok(activated)
+    }
+
+    /// Remove the vectors of a retired or shadow version
+    ///
+    /// Versioned columns are dropped. The original columns are referenced by
+    /// views, so for version 1 they are cleared instead.
+    pub async fn prune_embedding_version(&self, version: i32) -> Result<EmbeddingVersion> {
+        let mut tx = self.pool.begin().await?;
+
+        let target: EmbeddingVersion =
+            sqlx::query_as("SELECT * FROM embedding_versions WHERE version = $1 FOR UPDATE")
+                .bind(version)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| {
+                    SnapRagError::Custom(format!("Unknown embedding version {version}"))
+                })?;
+        if target.is_active() {
+            return Err(SnapRagError::Custom(format!(
+                "Embedding version {version} is active and cannot be pruned"
+            )));
+        }
+        if target.pruned_at.is_some() {
+            return Ok(target);
+        }
+
+        let mut columns = vec![("cast_embeddings", CAST_EMBEDDING_COLUMN)];
+        columns.extend(
+            PROFILE_EMBEDDING_COLUMNS
+                .iter()
+                .map(|column| ("profile_embeddings", *column)),
+        );
+        for (table, base) in columns {
+            let column = target.column(base);
+            let sql = if target.version == BASE_EMBEDDING_VERSION {
+                format!("UPDATE {table} SET {column} = NULL WHERE {column} IS NOT NULL")
+            } else {
+                format!("ALTER TABLE {table} DROP COLUMN IF EXISTS {column}")
+            };
+            sqlx::query(&sql).execute(&mut *tx).await?;
+        }
+
+        let pruned: EmbeddingVersion = sqlx::query_as(
+            r"
+            UPDATE embedding_versions
+            SET state = 'retired',
+                retired_at = COALESCE(retired_at, NOW()),
+                pruned_at = NOW()
+            WHERE version = $1
+            RETURNING *
+            ",
+        )
+        .bind(version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        tracing::info!(
+            "🧹 Pruned embedding version {} ({})",
+            pruned.version,
+            pruned.model
+        );
+
+        Ok(pruned)
+    }
+}
+
+fn check_version_dimension(version: &EmbeddingVersion, embedding: &[f32]) -> Result<()> {
+    if embedding.len() == version.dimension() {
+        Ok(())
+    } else {
+        Err(SnapRagError::Custom(format!(
+            "Embedding has {} dimensions, but embedding version {} ({}) uses {}",
+            embedding.len(),
+            version.version,
+            version.model,
+            version.dimension
+        )))
+    }
+}
+
+async fn fetch_coverage(
+    conn: &mut sqlx::PgConnection,
+    active: Option<&EmbeddingVersion>,
+    target: &EmbeddingVersion,
+) -> Result<EmbeddingCoverage> {
+    let cast_active = active_column(CAST_EMBEDDING_COLUMN, active);
+    let cast_target = target.column(CAST_EMBEDDING_COLUMN);
+    let (casts_total, casts_covered): (i64, i64) = sqlx::query_as(&format!(
+        r"
+        SELECT
+            COUNT(*) FILTER (WHERE {cast_active} IS NOT NULL),
+            COUNT(*) FILTER (WHERE {cast_active} IS NOT NULL AND {cast_target} IS NOT NULL)
+        FROM cast_embeddings
+        "
+    ))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let (totals, covered): (Vec<String>, Vec<String>) = PROFILE_EMBEDDING_COLUMNS
+        .iter()
+        .map(|base| {
+            let from = active_column(base, active);
+            let to = target.column(base);
+            (
+                format!("COUNT(*) FILTER (WHERE {from} IS NOT NULL)"),
+                format!("COUNT(*) FILTER (WHERE {from} IS NOT NULL AND {to} IS NOT NULL)"),
+            )
+        })
+        .unzip();
+    let (profiles_total, profiles_covered): (i64, i64) = sqlx::query_as(&format!(
+        "SELECT {}, {} FROM profile_embeddings",
+        totals.join(" + "),
+        covered.join(" + ")
+    ))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(EmbeddingCoverage {
+        casts_total,
+        casts_covered,
+        profiles_total,
+        profiles_covered,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: i32, state: &str) -> EmbeddingVersion {
+        EmbeddingVersion {
+            version,
+            model: "test-model".to_string(),
+            dimension: 4,
+            state: state.to_string(),
+            created_at: chrono::Utc::now(),
+            activated_at: None,
+            retired_at: None,
+            pruned_at: None,
+        }
+    }
+
+    #[test]
+    fn test_versioned_columns() {
+        assert_eq!(version(1, "active").column("embedding"), "embedding");
+        assert_eq!(
+            version(3, "shadow").column("bio_embedding"),
+            "bio_embedding_v3"
+        );
+        assert_eq!(active_column("embedding", None), "embedding");
+        assert_eq!(
+            active_column("embedding", Some(&version(2, "active"))),
+            "embedding_v2"
+        );
+        assert!(multi_vector_searchable(None));
+        assert!(multi_vector_searchable(Some(&version(1, "active"))));
+        assert!(!multi_vector_searchable(Some(&version(2, "active"))));
+    }
+
+    #[test]
+    fn test_dimension_checks() {
+        let active = version(2, "active");
+        assert!(check_active_dimension(None, &[0.0; 7]).is_ok());
+        assert!(check_active_dimension(Some(&active), &[0.0; 4]).is_ok());
+        assert!(check_active_dimension(Some(&active), &[0.0; 3]).is_err());
+        assert!(check_version_dimension(&active, &[0.0; 5]).is_err());
+    }
+
+    #[test]
+    fn test_coverage_ratio() {
+        assert!((EmbeddingCoverage::default().ratio() - 1.0).abs() < f64::EPSILON);
+        let coverage = EmbeddingCoverage {
+            casts_total: 6,
+            casts_covered: 3,
+            profiles_total: 4,
+            profiles_covered: 4,
+        };
+        assert!((coverage.ratio() - 0.7).abs() < 1e-9);
+    }
+}
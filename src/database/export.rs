@@ -0,0 +1,332 @@
+//! Server-side cursors over exported tables
+//!
+//! An export runs in a read-only `REPEATABLE READ` transaction, so every
+//! batch comes from the same snapshot, and fetches rows from a `NO SCROLL`
+//! cursor so `PostgreSQL` never materializes the whole result for the client.
+
+use sqlx::postgres::PgRow;
+use sqlx::PgConnection;
+use sqlx::Postgres;
+use sqlx::Row;
+use sqlx::Transaction;
+
+use super::embedding_versions::active_column;
+use super::embedding_versions::EmbeddingVersion;
+use super::embedding_versions::CAST_EMBEDDING_COLUMN;
+use super::embedding_versions::PROFILE_EMBEDDING_COLUMNS;
+use super::Database;
+use crate::export::columns::Cell;
+use crate::export::columns::Column;
+use crate::export::columns::ColumnType;
+use crate::export::ExportQuery;
+use crate::export::ExportTable;
+use crate::Result;
+
+const CURSOR_NAME: &str = "snaprag_export";
+
+/// Column a table's `--since`/`--until` bounds apply to
+#[derive(Clone, Copy)]
+enum TimeColumn {
+    /// Seconds since the Farcaster epoch
+    Farcaster(&'static str),
+    /// A `timestamptz`
+    Timestamp(&'static str),
+}
+
+/// Where an export reads from and which filters it supports
+struct ExportSource {
+    select: String,
+    order_by: &'static str,
+    fid_column: Option<&'static str>,
+    time_column: Option<TimeColumn>,
+}
+
+impl ExportSource {
+    fn by_fid(select: impl Into<String>, time_column: TimeColumn) -> Self {
+        Self {
+            select: select.into(),
+            order_by: "fid",
+            fid_column: Some("fid"),
+            time_column: Some(time_column),
+        }
+    }
+}
+
+fn export_source(table: ExportTable, active: Option<&EmbeddingVersion>) -> ExportSource {
+    match table {
+        ExportTable::Profiles => ExportSource::by_fid(
+            "SELECT fid, username, display_name, bio, pfp_url, banner_url, location, \
+             website_url, twitter_username, github_username, primary_address_ethereum, \
+             primary_address_solana, last_updated_timestamp::bigint AS last_updated_timestamp \
+             FROM user_profiles",
+            TimeColumn::Farcaster("last_updated_timestamp"),
+        ),
+        ExportTable::ProfileChanges => ExportSource::by_fid(
+            "SELECT fid, field_name::text AS field_name, field_value, timestamp, message_hash, \
+             shard_id::bigint AS shard_id, block_height \
+             FROM user_profile_changes",
+            TimeColumn::Farcaster("timestamp"),
+        ),
+        ExportTable::Casts => ExportSource::by_fid(
+            "SELECT fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, \
+             mentions, parent_fid, parent_url, shard_id::bigint AS shard_id, block_height \
+             FROM casts",
+            TimeColumn::Farcaster("timestamp"),
+        ),
+        ExportTable::Links => ExportSource::by_fid(
+            "SELECT fid, target_fid, link_type::text AS link_type, \
+             event_type::text AS event_type, timestamp, message_hash, \
+             shard_id::bigint AS shard_id, block_height \
+             FROM links",
+            TimeColumn::Farcaster("timestamp"),
+        ),
+        ExportTable::Reactions => ExportSource::by_fid(
+            "SELECT fid, target_cast_hash, target_fid, reaction_type::bigint AS reaction_type, \
+             event_type::text AS event_type, timestamp, message_hash, \
+             shard_id::bigint AS shard_id, block_height \
+             FROM reactions",
+            TimeColumn::Farcaster("timestamp"),
+        ),
+        ExportTable::CastEmbeddings => ExportSource {
+            select: format!(
+                "SELECT ce.message_hash, ce.fid, ce.text, c.timestamp, \
+                 ce.{}::real[] AS embedding \
+                 FROM cast_embeddings ce LEFT JOIN casts c ON c.message_hash = ce.message_hash",
+                active_column(CAST_EMBEDDING_COLUMN, active)
+            ),
+            order_by: "ce.fid",
+            fid_column: Some("ce.fid"),
+            time_column: Some(TimeColumn::Farcaster("c.timestamp")),
+        },
+        ExportTable::ProfileEmbeddings => {
+            let vectors: Vec<_> = PROFILE_EMBEDDING_COLUMNS
+                .iter()
+                .map(|base| format!("{}::real[] AS {base}", active_column(base, active)))
+                .collect();
+            ExportSource::by_fid(
+                format!(
+                    "SELECT fid, {}, EXTRACT(EPOCH FROM updated_at)::bigint AS updated_at \
+                     FROM profile_embeddings",
+                    vectors.join(", ")
+                ),
+                TimeColumn::Timestamp("updated_at"),
+            )
+        }
+        ExportTable::ProcessedBlocks => ExportSource {
+            select: "SELECT shard_id::bigint AS shard_id, start_height, end_height \
+                     FROM processed_blocks"
+                .to_string(),
+            order_by: "shard_id, start_height",
+            fid_column: None,
+            time_column: None,
+        },
+        ExportTable::SyncProgress => ExportSource {
+            select: "SELECT shard_id::bigint AS shard_id, last_processed_height \
+                     FROM sync_progress"
+                .to_string(),
+            order_by: "shard_id",
+            fid_column: None,
+            time_column: None,
+        },
+    }
+}
+
+/// SELECT of an export with its filters inlined
+///
+/// Every filter value is an integer, so the statement can be declared as a
+/// cursor without bind parameters.
+fn export_sql(query: &ExportQuery, active: Option<&EmbeddingVersion>) -> Result<String> {
+    query.validate()?;
+    let source = export_source(query.table, active);
+
+    let mut conditions = Vec::new();
+    if let Some(fid_column) = source.fid_column {
+        if let Some(min) = query.fids.min {
+            conditions.push(format!("{fid_column} >= {min}"));
+        }
+        if let Some(max) = query.fids.max {
+            conditions.push(format!("{fid_column} <= {max}"));
+        }
+    }
+    if let Some(time_column) = source.time_column {
+        for (bound, operator) in [(query.since, ">="), (query.until, "<")] {
+            let Some(bound) = bound else { continue };
+            conditions.push(match time_column {
+                TimeColumn::Farcaster(column) => format!(
+                    "{column} {operator} {}",
+                    bound.timestamp() - farcaster_epoch_seconds()
+                ),
+                TimeColumn::Timestamp(column) => {
+                    format!("{column} {operator} to_timestamp({})", bound.timestamp())
+                }
+            });
+        }
+    }
+
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    Ok(format!(
+        "{}{filter} ORDER BY {}",
+        source.select, source.order_by
+    ))
+}
+
+fn farcaster_epoch_seconds() -> i64 {
+    i64::try_from(crate::FARCASTER_EPOCH / 1000).unwrap_or_default()
+}
+
+/// Decode a row into one cell per exported column
+fn row_cells(row: &PgRow, columns: &[Column]) -> Result<Vec<Cell>> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            Ok(match column.column_type {
+                ColumnType::Int64 => row.try_get::<Option<i64>, _>(index)?.into(),
+                ColumnType::Text => row.try_get::<Option<String>, _>(index)?.into(),
+                ColumnType::Bytes => row.try_get::<Option<Vec<u8>>, _>(index)?.into(),
+                ColumnType::Json => row.try_get::<Option<serde_json::Value>, _>(index)?.into(),
+                ColumnType::Floats => row.try_get::<Option<Vec<f32>>, _>(index)?.into(),
+            })
+        })
+        .collect()
+}
+
+/// A read-only snapshot that exports are read from
+///
+/// Cursors opened one after another see the same data, so an archive of
+/// several tables is consistent with the sync progress it contains.
+pub struct ExportSnapshot {
+    tx: Transaction<'static, Postgres>,
+    active: Option<EmbeddingVersion>,
+}
+
+impl ExportSnapshot {
+    /// Open a server-side cursor over an export, fetching `fetch_size` rows at a time
+    pub async fn cursor(
+        &mut self,
+        query: &ExportQuery,
+        fetch_size: usize,
+    ) -> Result<ExportCursor<'_>> {
+        let sql = export_sql(query, self.active.as_ref())?;
+        tracing::debug!("Export cursor: {}", sql);
+
+        sqlx::query(&format!("DECLARE {CURSOR_NAME} NO SCROLL CURSOR FOR {sql}"))
+            .persistent(false)
+            .execute(&mut *self.tx)
+            .await?;
+
+        Ok(ExportCursor {
+            conn: &mut self.tx,
+            table: query.table,
+            fetch_size: fetch_size.max(1),
+            done: false,
+        })
+    }
+
+    /// End the snapshot
+    pub async fn finish(self) -> Result<()> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// An open export; fetch batches until `None`, then [`close`](Self::close)
+pub struct ExportCursor<'a> {
+    conn: &'a mut PgConnection,
+    table: ExportTable,
+    fetch_size: usize,
+    done: bool,
+}
+
+impl ExportCursor<'_> {
+    /// Next batch of at most `fetch_size` rows, `None` once the cursor is drained
+    pub async fn next_batch(&mut self) -> Result<Option<Vec<Vec<Cell>>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        // Not cached: the same FETCH returns different columns per export
+        let rows = sqlx::query(&format!(
+            "FETCH FORWARD {} FROM {CURSOR_NAME}",
+            self.fetch_size
+        ))
+        .persistent(false)
+        .fetch_all(&mut *self.conn)
+        .await?;
+        if rows.len() < self.fetch_size {
+            self.done = true;
+        }
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let columns = self.table.columns();
+        rows.iter()
+            .map(|row| row_cells(row, columns))
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    /// Close the cursor so the snapshot can open the next one
+    pub async fn close(self) -> Result<()> {
+        sqlx::query(&format!("CLOSE {CURSOR_NAME}"))
+            .execute(&mut *self.conn)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Begin a read-only `REPEATABLE READ` snapshot for exports
+    pub async fn begin_export_snapshot(&self) -> Result<ExportSnapshot> {
+        let active = self.active_embedding_version().await?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        Ok(ExportSnapshot { tx, active })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::FidRange;
+
+    #[test]
+    fn test_export_sql_filters() {
+        let mut query = ExportQuery::new(ExportTable::Casts);
+        assert_eq!(
+            export_sql(&query, None).unwrap(),
+            "SELECT fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, \
+             mentions, parent_fid, parent_url, shard_id::bigint AS shard_id, block_height \
+             FROM casts ORDER BY fid"
+        );
+
+        query.fids = FidRange {
+            min: Some(10),
+            max: Some(20),
+        };
+        query.since = Some(crate::export::parse_time("2021-01-02").unwrap());
+        assert!(export_sql(&query, None)
+            .unwrap()
+            .ends_with("WHERE fid >= 10 AND fid <= 20 AND timestamp >= 86400 ORDER BY fid"));
+
+        let mut query = ExportQuery::new(ExportTable::ProfileEmbeddings);
+        query.until = Some(crate::export::parse_time("1700000000").unwrap());
+        let sql = export_sql(&query, None).unwrap();
+        assert!(sql.contains("bio_embedding::real[] AS bio_embedding"));
+        assert!(sql.ends_with("WHERE updated_at < to_timestamp(1700000000) ORDER BY fid"));
+
+        let mut query = ExportQuery::new(ExportTable::SyncProgress);
+        assert!(export_sql(&query, None)
+            .unwrap()
+            .ends_with("ORDER BY shard_id"));
+        query.fids.min = Some(1);
+        assert!(export_sql(&query, None).is_err());
+    }
+}
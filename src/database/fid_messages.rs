@@ -0,0 +1,170 @@
+//! Current message state of a single FID
+//!
+//! Mirrors what a hub keeps per FID: the winning message of every CRDT key,
+//! whether an add or a remove. Used to reconcile local data against the hub.
+
+use super::Database;
+use super::NOT_REMOVED;
+use crate::models::Cast;
+use crate::models::Link;
+use crate::models::Reaction;
+use crate::models::Verification;
+use crate::Result;
+
+/// Latest local message of each key of one FID
+#[derive(Debug, Clone, Default)]
+pub struct FidMessageState {
+    /// Casts without a `CastRemove` tombstone
+    pub casts: Vec<Cast>,
+    /// Hashes of `CastRemove` messages
+    pub cast_removes: Vec<Vec<u8>>,
+    /// Latest link event per target FID
+    pub links: Vec<Link>,
+    /// Hashes of the latest link compact state per link type
+    pub link_compact_states: Vec<Vec<u8>>,
+    /// Latest reaction event per target cast
+    pub reactions: Vec<Reaction>,
+    /// Latest verification event per address
+    pub verifications: Vec<Verification>,
+    /// Hashes of the latest `UserDataAdd` per profile field
+    pub user_data: Vec<Vec<u8>>,
+}
+
+impl Database {
+    /// Current message state of `fid`
+    pub async fn fid_message_state(&self, fid: i64) -> Result<FidMessageState> {
+        let casts = sqlx::query_as::<_, Cast>(&format!(
+            r"
+            SELECT * FROM casts c
+            WHERE c.fid = $1
+              AND {NOT_REMOVED}
+            "
+        ))
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let cast_removes = sqlx::query_scalar::<_, Vec<u8>>(
+            "SELECT message_hash FROM cast_removes WHERE fid = $1",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let links = self.latest_fid_links(fid).await?;
+
+        let link_compact_states = sqlx::query_scalar::<_, Vec<u8>>(
+            r"
+            SELECT DISTINCT ON (link_type) message_hash
+            FROM link_compact_states
+            WHERE fid = $1
+            ORDER BY link_type, timestamp DESC
+            ",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let reactions = self.latest_fid_reactions(fid).await?;
+        let verifications = self.latest_fid_verifications(fid).await?;
+
+        // `fid_created` rows are synthetic, not hub messages
+        let user_data = sqlx::query_scalar::<_, Vec<u8>>(
+            r"
+            SELECT DISTINCT ON (field_name) message_hash
+            FROM user_profile_changes
+            WHERE fid = $1 AND field_name <> 'fid_created'
+            ORDER BY field_name, timestamp DESC
+            ",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(FidMessageState {
+            casts,
+            cast_removes,
+            links,
+            link_compact_states,
+            reactions,
+            verifications,
+            user_data,
+        })
+    }
+
+    /// Latest link event of `fid` per target FID
+    async fn latest_fid_links(&self, fid: i64) -> Result<Vec<Link>> {
+        let rows = sqlx::query_as::<_, Link>(
+            r"
+            WITH latest_events AS (
+                SELECT *, ROW_NUMBER() OVER (
+                    PARTITION BY fid, target_fid
+                    ORDER BY timestamp DESC
+                ) as rn
+                FROM links
+                WHERE fid = $1
+            )
+            SELECT id, fid, target_fid, link_type, event_type, timestamp, message_hash,
+                   created_at, shard_id, block_height, transaction_fid
+            FROM latest_events
+            WHERE rn = 1
+            ",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Latest reaction event of `fid` per target cast
+    async fn latest_fid_reactions(&self, fid: i64) -> Result<Vec<Reaction>> {
+        let rows = sqlx::query_as::<_, Reaction>(
+            r"
+            WITH latest_events AS (
+                SELECT *, ROW_NUMBER() OVER (
+                    PARTITION BY fid, target_cast_hash
+                    ORDER BY timestamp DESC
+                ) as rn
+                FROM reactions
+                WHERE fid = $1
+            )
+            SELECT id, fid, target_cast_hash, target_fid, reaction_type, event_type, timestamp,
+                   message_hash, created_at, shard_id, block_height, transaction_fid
+            FROM latest_events
+            WHERE rn = 1
+            ",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Latest verification event of `fid` per address
+    async fn latest_fid_verifications(&self, fid: i64) -> Result<Vec<Verification>> {
+        let rows = sqlx::query_as::<_, Verification>(
+            r"
+            WITH latest_events AS (
+                SELECT *, ROW_NUMBER() OVER (
+                    PARTITION BY fid, address
+                    ORDER BY timestamp DESC
+                ) as rn
+                FROM verifications
+                WHERE fid = $1
+            )
+            SELECT id, fid, address, claim_signature, block_hash, verification_type, chain_id,
+                   event_type, timestamp, message_hash, created_at, shard_id, block_height,
+                   transaction_fid
+            FROM latest_events
+            WHERE rn = 1
+            ",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
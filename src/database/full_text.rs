@@ -0,0 +1,192 @@
+//! Full-text keyword search over casts and profiles
+//!
+//! Cast text and profile names and bios carry generated `tsvector` columns
+//! (`casts.text_tsv`, `user_profile_changes.field_tsv`) with GIN indexes.
+//! Queries are parsed with `websearch_to_tsquery`, so `"quoted phrases"`,
+//! `-excluded` words and `OR` work as in web search engines, and matches are
+//! ordered by their `ts_rank_cd` cover density.
+
+use super::bind_cast_filters;
+use super::Database;
+use super::CAST_FILTERS;
+use super::NOT_REMOVED;
+use super::REPLY_NOT_REMOVED;
+use crate::models::CastSearchFilters;
+use crate::models::CastSearchResult;
+use crate::models::UserProfile;
+use crate::Result;
+
+/// `ts_rank_cd` normalization dividing by one plus the log of the document
+/// length, so long texts do not win by repetition
+const LENGTH_NORMALIZATION: i32 = 1;
+
+/// `ts_rank_cd` normalization mapping a rank into `0..1` as `rank / (rank + 1)`
+const BOUNDED_NORMALIZATION: i32 = 32;
+
+/// A profile matched by full-text search
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RankedProfile {
+    #[sqlx(flatten)]
+    pub profile: UserProfile,
+    /// Combined rank of the matching name and bio, in `0..1`
+    pub rank: f32,
+}
+
+impl Database {
+    /// Full-text search for casts, best matches first
+    ///
+    /// `similarity` of each result is its `ts_rank_cd` score in `0..1`.
+    pub async fn full_text_search_casts(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<CastSearchResult>> {
+        self.filtered_full_text_search_casts(query, limit, &CastSearchFilters::default())
+            .await
+    }
+
+    /// [`Self::full_text_search_casts`] restricted to casts matching `filters`
+    ///
+    /// # Errors
+    /// Returns an error if the query fails
+    pub async fn filtered_full_text_search_casts(
+        &self,
+        query: &str,
+        limit: i64,
+        filters: &CastSearchFilters,
+    ) -> Result<Vec<CastSearchResult>> {
+        #[derive(sqlx::FromRow)]
+        struct RawResult {
+            message_hash: Vec<u8>,
+            fid: i64,
+            text: String,
+            timestamp: i64,
+            parent_hash: Option<Vec<u8>>,
+            embeds: Option<serde_json::Value>,
+            mentions: Option<serde_json::Value>,
+            similarity: f32,
+            reply_count: Option<i64>,
+            reaction_count: Option<i64>,
+        }
+
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = format!(
+            r"
+            WITH query AS (
+                SELECT websearch_to_tsquery('english', $1) AS q
+            ),
+            matches AS (
+                SELECT
+                    c.message_hash,
+                    c.fid,
+                    COALESCE(c.text, '') as text,
+                    c.timestamp,
+                    c.parent_hash,
+                    c.embeds,
+                    c.mentions,
+                    ts_rank_cd(c.text_tsv, query.q, $3)::real as similarity
+                FROM casts c, query
+                WHERE c.text_tsv @@ query.q
+                AND {CAST_FILTERS}
+                AND {NOT_REMOVED}
+                ORDER BY similarity DESC, c.timestamp DESC
+                LIMIT $2
+            )
+            SELECT
+                m.*,
+                (SELECT COUNT(*) FROM casts r WHERE r.parent_hash = m.message_hash AND {REPLY_NOT_REMOVED}) as reply_count,
+                (SELECT COUNT(*) FROM (
+                    SELECT *, ROW_NUMBER() OVER (
+                        PARTITION BY fid, target_cast_hash
+                        ORDER BY timestamp DESC
+                    ) as rn
+                    FROM reactions
+                    WHERE target_cast_hash = m.message_hash
+                ) r WHERE r.rn = 1 AND r.event_type = 'add') as reaction_count
+            FROM matches m
+            ORDER BY m.similarity DESC, m.timestamp DESC
+            "
+        );
+        let raw_results = bind_cast_filters(
+            sqlx::query_as::<_, RawResult>(&sql)
+                .bind(query)
+                .bind(limit)
+                .bind(LENGTH_NORMALIZATION | BOUNDED_NORMALIZATION),
+            filters,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(raw_results
+            .into_iter()
+            .map(|r| CastSearchResult {
+                message_hash: r.message_hash,
+                fid: r.fid,
+                text: r.text,
+                timestamp: r.timestamp,
+                parent_hash: r.parent_hash,
+                embeds: r.embeds,
+                mentions: r.mentions,
+                similarity: r.similarity,
+                reply_count: Some(r.reply_count.unwrap_or(0)),
+                reaction_count: Some(r.reaction_count.unwrap_or(0)),
+                chunk_index: None,
+                chunk_text: None,
+                chunk_strategy: None,
+            })
+            .collect())
+    }
+
+    /// Full-text search for profiles by username, display name and bio
+    ///
+    /// Only the current value of each field is searched, and each field must
+    /// match the whole query on its own. Names outweigh bios, and names are
+    /// also matched unstemmed so handles are found as written.
+    pub async fn full_text_search_profiles(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<RankedProfile>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(sqlx::query_as::<_, RankedProfile>(
+            r"
+            WITH query AS (
+                SELECT websearch_to_tsquery('simple', $1) || websearch_to_tsquery('english', $1) AS q
+            ),
+            matches AS (
+                SELECT c.fid, ts_rank_cd(c.field_tsv, query.q, $3) AS rank
+                FROM user_profile_changes c, query
+                WHERE c.field_tsv @@ query.q
+                AND NOT EXISTS (
+                    SELECT 1 FROM user_profile_changes newer
+                    WHERE newer.fid = c.fid
+                    AND newer.field_name = c.field_name
+                    AND newer.timestamp > c.timestamp
+                )
+            ),
+            ranked AS (
+                SELECT fid, (SUM(rank) / (SUM(rank) + 1))::real AS rank
+                FROM matches
+                GROUP BY fid
+                ORDER BY rank DESC, fid
+                LIMIT $2
+            )
+            SELECT p.*, ranked.rank
+            FROM ranked
+            JOIN user_profiles p ON p.fid = ranked.fid
+            ORDER BY ranked.rank DESC, p.fid
+            ",
+        )
+        .bind(query)
+        .bind(limit)
+        .bind(LENGTH_NORMALIZATION)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,227 @@
+//! Loading exported archives back into the database
+//!
+//! Rows are inserted with `ON CONFLICT`, so loading a batch twice is
+//! harmless, and every batch advances `import_progress` in the same
+//! transaction, so an interrupted import resumes after its last batch.
+
+use sqlx::query_builder::Separated;
+use sqlx::Postgres;
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+use super::embedding_versions::active_column;
+use super::Database;
+use crate::export::columns::Cell;
+use crate::export::columns::Column;
+use crate::export::columns::ColumnType;
+use crate::export::ExportTable;
+use crate::Result;
+
+/// Bind parameters `PostgreSQL` accepts in one statement
+const MAX_BIND_PARAMETERS: usize = 65_535;
+
+/// Load progress of one archive table
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ImportProgress {
+    pub table_name: String,
+    pub rows_loaded: i64,
+    pub completed: bool,
+}
+
+/// Where the rows of an archive table are stored
+struct ImportTarget {
+    table: &'static str,
+    /// Exported columns that are derived from other tables and not stored
+    skipped: &'static [&'static str],
+    on_conflict: &'static str,
+}
+
+const DEDUPLICATE_MESSAGES: &str = " ON CONFLICT (message_hash) DO NOTHING";
+
+fn import_target(table: ExportTable) -> Option<ImportTarget> {
+    let message_table = |name| ImportTarget {
+        table: name,
+        skipped: &[],
+        on_conflict: DEDUPLICATE_MESSAGES,
+    };
+
+    Some(match table {
+        ExportTable::ProfileChanges => message_table("user_profile_changes"),
+        ExportTable::Casts => message_table("casts"),
+        ExportTable::Links => message_table("links"),
+        ExportTable::Reactions => message_table("reactions"),
+        ExportTable::CastEmbeddings => ImportTarget {
+            table: "cast_embeddings",
+            skipped: &["timestamp"],
+            on_conflict: DEDUPLICATE_MESSAGES,
+        },
+        ExportTable::ProfileEmbeddings => ImportTarget {
+            table: "profile_embeddings",
+            skipped: &["updated_at"],
+            on_conflict: " ON CONFLICT (fid) DO NOTHING",
+        },
+        // Profiles are a view over user_profile_changes. Archives only hold
+        // part of what sync writes, so sync starts over after an import
+        // instead of resuming from restored progress.
+        ExportTable::Profiles | ExportTable::ProcessedBlocks | ExportTable::SyncProgress => {
+            return None
+        }
+    })
+}
+
+fn unsupported(table: ExportTable) -> crate::SnapRagError {
+    crate::SnapRagError::Custom(format!("{} cannot be imported", table.name()))
+}
+
+/// Bind a cell, typing NULL after its column
+fn push_cell(
+    builder: &mut Separated<'_, '_, Postgres, &'static str>,
+    column: &Column,
+    cell: &Cell,
+) {
+    match cell {
+        Cell::Null => match column.column_type {
+            ColumnType::Int64 => builder.push_bind(None::<i64>),
+            ColumnType::Text => builder.push_bind(None::<String>),
+            ColumnType::Bytes => builder.push_bind(None::<Vec<u8>>),
+            ColumnType::Json => builder.push_bind(None::<serde_json::Value>),
+            ColumnType::Floats => builder.push_bind(None::<Vec<f32>>),
+        },
+        Cell::Int(value) => builder.push_bind(*value),
+        Cell::Text(value) => builder.push_bind(value.clone()),
+        Cell::Bytes(value) => builder.push_bind(value.clone()),
+        Cell::Json(value) => builder.push_bind(value.clone()),
+        Cell::Floats(values) => builder.push_bind(values.clone()),
+    };
+}
+
+impl Database {
+    /// Load progress of every table of an archive
+    pub async fn import_progress(&self, archive_id: Uuid) -> Result<Vec<ImportProgress>> {
+        Ok(sqlx::query_as::<_, ImportProgress>(
+            "SELECT table_name, rows_loaded, completed FROM import_progress \
+             WHERE archive_id = $1 ORDER BY table_name",
+        )
+        .bind(archive_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Tables behind `tables` that already hold rows
+    pub async fn non_empty_import_tables(
+        &self,
+        tables: &[ExportTable],
+    ) -> Result<Vec<&'static str>> {
+        let mut non_empty = Vec::new();
+        for table in tables {
+            let target = import_target(*table).ok_or_else(|| unsupported(*table))?;
+            let has_rows: bool =
+                sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {})", target.table))
+                    .fetch_one(&self.pool)
+                    .await?;
+            if has_rows {
+                non_empty.push(target.table);
+            }
+        }
+        Ok(non_empty)
+    }
+
+    /// Insert a batch of archive rows and record `rows_loaded` in one transaction
+    ///
+    /// Vectors go into the columns of the active embedding version.
+    pub async fn import_batch(
+        &self,
+        archive_id: Uuid,
+        table: ExportTable,
+        rows: &[Vec<Cell>],
+        rows_loaded: u64,
+    ) -> Result<()> {
+        let target = import_target(table).ok_or_else(|| unsupported(table))?;
+        let active = self.active_embedding_version().await?;
+
+        let columns: Vec<(usize, &Column)> = table
+            .columns()
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| !target.skipped.contains(&column.name))
+            .collect();
+        let names: Vec<String> = columns
+            .iter()
+            .map(|(_, column)| match column.column_type {
+                ColumnType::Floats => active_column(column.name, active.as_ref()),
+                _ => column.name.to_string(),
+            })
+            .collect();
+
+        let mut tx = self.pool.begin().await?;
+        for chunk in rows.chunks((MAX_BIND_PARAMETERS / columns.len()).max(1)) {
+            let mut builder = QueryBuilder::<Postgres>::new(format!(
+                "INSERT INTO {} ({}) ",
+                target.table,
+                names.join(", ")
+            ));
+            builder.push_values(chunk, |mut values, row| {
+                for (index, column) in &columns {
+                    push_cell(&mut values, column, row.get(*index).unwrap_or(&Cell::Null));
+                }
+            });
+            builder.push(target.on_conflict);
+            builder.build().execute(&mut *tx).await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO import_progress (archive_id, table_name, rows_loaded) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (archive_id, table_name) DO UPDATE SET \
+             rows_loaded = EXCLUDED.rows_loaded, updated_at = NOW()",
+        )
+        .bind(archive_id)
+        .bind(table.name())
+        .bind(i64::try_from(rows_loaded).unwrap_or(i64::MAX))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Mark an archive table as fully loaded
+    pub async fn complete_import_table(
+        &self,
+        archive_id: Uuid,
+        table: ExportTable,
+        rows_loaded: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO import_progress (archive_id, table_name, rows_loaded, completed) \
+             VALUES ($1, $2, $3, TRUE) \
+             ON CONFLICT (archive_id, table_name) DO UPDATE SET \
+             rows_loaded = EXCLUDED.rows_loaded, completed = TRUE, updated_at = NOW()",
+        )
+        .bind(archive_id)
+        .bind(table.name())
+        .bind(i64::try_from(rows_loaded).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_targets_store_every_column() {
+        assert!(import_target(ExportTable::Profiles).is_none());
+        assert!(import_target(ExportTable::SyncProgress).is_none());
+
+        for table in crate::export::archive::ARCHIVE_TABLES {
+            let target = import_target(table).unwrap();
+            for skipped in target.skipped {
+                assert!(table.columns().iter().any(|column| column.name == *skipped));
+            }
+        }
+    }
+}
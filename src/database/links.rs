@@ -234,4 +234,34 @@ impl Database {
 
         Ok(links)
     }
+
+    /// Active follower counts of `fids`; FIDs without followers are left out
+    ///
+    /// # Errors
+    /// - Database connection errors
+    /// - SQL query execution errors
+    pub async fn follower_counts(
+        &self,
+        fids: &[i64],
+    ) -> Result<std::collections::HashMap<i64, i64>> {
+        let counts: Vec<(i64, i64)> = sqlx::query_as(
+            r"
+            WITH latest_events AS (
+                SELECT DISTINCT ON (fid, target_fid) target_fid, event_type
+                FROM links
+                WHERE target_fid = ANY($1) AND link_type = 'follow'
+                ORDER BY fid, target_fid, timestamp DESC
+            )
+            SELECT target_fid, COUNT(*)
+            FROM latest_events
+            WHERE event_type = 'add'
+            GROUP BY target_fid
+            ",
+        )
+        .bind(fids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(counts.into_iter().collect())
+    }
 }
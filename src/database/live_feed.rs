@@ -0,0 +1,104 @@
+//! Live feed of committed sync events over `LISTEN` / `NOTIFY`
+//!
+//! Sync and the API server run as separate processes; after a batch commits,
+//! sync publishes its change events on [`LIVE_FEED_CHANNEL`] and every API
+//! server listening on it pushes them to its SSE and WebSocket clients.
+//! Notifications are fire-and-forget: events published while no server
+//! listens are not kept.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::Database;
+use crate::webhooks::ChangeEvent;
+use crate::webhooks::ChangeEventType;
+use crate::Result;
+
+/// `NOTIFY` channel of the live feed
+pub const LIVE_FEED_CHANNEL: &str = "snaprag_live_feed";
+
+/// `NOTIFY` payloads must be shorter than 8000 bytes
+const MAX_NOTIFY_PAYLOAD_BYTES: usize = 7999;
+
+/// A committed event as published on the live feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveFeedEvent {
+    pub event_type: ChangeEventType,
+    pub fid: i64,
+    pub target_fid: Option<i64>,
+    pub message_hash: String,
+    pub parent_hash: Option<String>,
+    pub channel: Option<String>,
+    /// Farcaster timestamp of the message
+    pub timestamp: i64,
+    pub data: serde_json::Value,
+}
+
+impl LiveFeedEvent {
+    /// Text of a cast event
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        self.data.get("text").and_then(serde_json::Value::as_str)
+    }
+}
+
+impl From<&ChangeEvent> for LiveFeedEvent {
+    fn from(event: &ChangeEvent) -> Self {
+        Self {
+            event_type: event.event_type,
+            fid: event.fid,
+            target_fid: event.target_fid,
+            message_hash: hex::encode(&event.message_hash),
+            parent_hash: event.parent_hash.as_deref().map(hex::encode),
+            channel: event.channel.clone(),
+            timestamp: event.timestamp,
+            data: event.payload.clone(),
+        }
+    }
+}
+
+impl Database {
+    /// Publish committed sync events on the live feed
+    #[must_use]
+    pub const fn with_live_feed(mut self, enabled: bool) -> Self {
+        self.live_feed = enabled;
+        self
+    }
+
+    /// Whether sync publishes committed events on the live feed
+    #[must_use]
+    pub const fn live_feed(&self) -> bool {
+        self.live_feed
+    }
+
+    /// `NOTIFY` the live feed of `events`
+    ///
+    /// Events too large for a notification are skipped. Returns the number
+    /// of events published.
+    pub async fn notify_live_feed(&self, events: &[ChangeEvent]) -> Result<usize> {
+        let mut payloads = Vec::with_capacity(events.len());
+        for event in events {
+            let payload = serde_json::to_string(&LiveFeedEvent::from(event))?;
+            if payload.len() > MAX_NOTIFY_PAYLOAD_BYTES {
+                tracing::debug!(
+                    "Skipping live feed event {} ({} bytes)",
+                    hex::encode(&event.message_hash),
+                    payload.len()
+                );
+                continue;
+            }
+            payloads.push(payload);
+        }
+        if payloads.is_empty() {
+            return Ok(0);
+        }
+
+        sqlx::query("SELECT pg_notify($1, payload) FROM UNNEST($2::text[]) AS payload")
+            .bind(LIVE_FEED_CHANNEL)
+            .bind(&payloads)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(payloads.len())
+    }
+}
@@ -0,0 +1,733 @@
+//! Versioned schema migrations
+//!
+//! Migrations are plain SQL files in `/migrations`, embedded into the binary and
+//! applied in version order. `000_complete_init.sql` is the baseline (version 0);
+//! every later change is a `NNN_name.up.sql` file with an optional
+//! `NNN_name.down.sql` rollback script.
+//!
+//! Applied migrations are recorded in `schema_migrations` together with a SHA-256
+//! checksum of the up script, so edits to an already-applied file are detected by
+//! `snaprag migrate verify`. Databases created from the monolithic init file before
+//! versioning existed are bootstrapped by re-applying the (idempotent) baseline and
+//! recording it as version 0.
+//!
+//! Each migration runs in its own transaction unless its script contains a
+//! `-- migrate:no-transaction` line (needed for `CREATE INDEX CONCURRENTLY`), in
+//! which case its statements are executed one by one.
+//!
+//! Scripts may use an `{{embedding_dimension}}` placeholder for vector column sizes;
+//! it is replaced with the configured embedding dimension before execution, while
+//! checksums are computed over the unrendered script.
+
+use sha2::Digest;
+use sha2::Sha256;
+use sqlx::pool::PoolConnection;
+use sqlx::Postgres;
+
+use super::Database;
+use crate::Result;
+use crate::SnapRagError;
+
+/// Advisory lock key that serializes concurrent migration runners ("snaprag")
+const MIGRATION_LOCK_ID: i64 = 0x0073_6e61_7072_6167;
+
+/// Marker line that opts a migration out of the wrapping transaction
+const NO_TRANSACTION_MARKER: &str = "-- migrate:no-transaction";
+
+/// Placeholder for the configured embedding dimension in migration scripts
+pub const EMBEDDING_DIMENSION_PLACEHOLDER: &str = "{{embedding_dimension}}";
+
+/// A schema migration embedded in the binary
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: Option<&'static str>,
+}
+
+impl Migration {
+    /// SHA-256 of the up script, hex encoded
+    #[must_use]
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.up.as_bytes()))
+    }
+
+    /// Whether the up script must run outside a transaction
+    #[must_use]
+    pub fn is_transactional(&self) -> bool {
+        !has_no_transaction_marker(self.up)
+    }
+
+    /// The up script with placeholders replaced
+    #[must_use]
+    pub fn render_up(&self, embedding_dimension: usize) -> String {
+        render_sql(self.up, embedding_dimension)
+    }
+}
+
+/// Replace placeholders in a migration script
+#[must_use]
+pub fn render_sql(sql: &str, embedding_dimension: usize) -> String {
+    sql.replace(
+        EMBEDDING_DIMENSION_PLACEHOLDER,
+        &embedding_dimension.to_string(),
+    )
+}
+
+/// All known migrations, in ascending version order
+///
+/// New migrations are appended here with the next free version number.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 0,
+        name: "complete_init",
+        up: include_str!("../../migrations/000_complete_init.sql"),
+        down: None,
+    },
+    Migration {
+        version: 1,
+        name: "embedding_versions",
+        up: include_str!("../../migrations/001_embedding_versions.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/001_embedding_versions.down.sql"
+        )),
+    },
+    Migration {
+        version: 2,
+        name: "snapshot_bootstraps",
+        up: include_str!("../../migrations/002_snapshot_bootstraps.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/002_snapshot_bootstraps.down.sql"
+        )),
+    },
+    Migration {
+        version: 3,
+        name: "rejected_messages",
+        up: include_str!("../../migrations/003_rejected_messages.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/003_rejected_messages.down.sql"
+        )),
+    },
+    Migration {
+        version: 4,
+        name: "processed_blocks",
+        up: include_str!("../../migrations/004_processed_blocks.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/004_processed_blocks.down.sql"
+        )),
+    },
+    Migration {
+        version: 5,
+        name: "webhooks",
+        up: include_str!("../../migrations/005_webhooks.up.sql"),
+        down: Some(include_str!("../../migrations/005_webhooks.down.sql")),
+    },
+    Migration {
+        version: 6,
+        name: "import_progress",
+        up: include_str!("../../migrations/006_import_progress.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/006_import_progress.down.sql"
+        )),
+    },
+    Migration {
+        version: 7,
+        name: "block_hashes",
+        up: include_str!("../../migrations/007_block_hashes.up.sql"),
+        down: Some(include_str!("../../migrations/007_block_hashes.down.sql")),
+    },
+    Migration {
+        version: 8,
+        name: "full_text_search",
+        up: include_str!("../../migrations/008_full_text_search.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/008_full_text_search.down.sql"
+        )),
+    },
+    Migration {
+        version: 9,
+        name: "onchain_state_blocks",
+        up: include_str!("../../migrations/009_onchain_state_blocks.up.sql"),
+        down: Some(include_str!(
+            "../../migrations/009_onchain_state_blocks.down.sql"
+        )),
+    },
+];
+
+/// State of a migration relative to the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    /// Known to this binary but not applied yet
+    Pending,
+    /// Applied and the checksum matches the embedded script
+    Applied,
+    /// Applied, but the embedded script has changed since
+    Modified,
+    /// Recorded in the database but unknown to this binary (applied by a newer version)
+    Unknown,
+}
+
+impl std::fmt::Display for MigrationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            Self::Pending => "pending",
+            Self::Applied => "applied",
+            Self::Modified => "modified",
+            Self::Unknown => "unknown",
+        };
+        f.write_str(state)
+    }
+}
+
+/// Status of a single migration, as reported by `migrate status`
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub execution_ms: Option<i64>,
+    /// Baseline adopted from a schema created outside the migration runner
+    pub bootstrapped: bool,
+}
+
+/// A row of `schema_migrations`
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    name: String,
+    checksum: String,
+    applied_at: chrono::DateTime<chrono::Utc>,
+    execution_ms: i64,
+    bootstrapped: bool,
+}
+
+impl Database {
+    /// Status of every known and recorded migration, in version order
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_migrations_table(&mut conn).await?;
+        let applied = fetch_applied(&mut conn).await?;
+        Ok(merge_status(MIGRATIONS, &applied))
+    }
+
+    /// Migrations whose checksum no longer matches or that this binary does not know
+    pub async fn verify_migrations(&self) -> Result<Vec<MigrationStatus>> {
+        Ok(self
+            .migration_status()
+            .await?
+            .into_iter()
+            .filter(|status| {
+                matches!(
+                    status.state,
+                    MigrationState::Modified | MigrationState::Unknown
+                )
+            })
+            .collect())
+    }
+
+    /// Number of known migrations not yet applied
+    pub async fn pending_migration_count(&self) -> Result<usize> {
+        Ok(self
+            .migration_status()
+            .await?
+            .iter()
+            .filter(|status| status.state == MigrationState::Pending)
+            .count())
+    }
+
+    /// Highest applied migration version, `None` before the baseline
+    pub async fn schema_version(&self) -> Result<Option<i64>> {
+        Ok(self
+            .migration_status()
+            .await?
+            .iter()
+            .filter(|status| status.state != MigrationState::Pending)
+            .map(|status| status.version)
+            .max())
+    }
+
+    /// Record the baseline as applied without executing it
+    ///
+    /// Used after `snaprag init` has already created the schema from
+    /// `000_complete_init.sql`. Returns false if the baseline was already recorded.
+    pub async fn record_baseline(&self) -> Result<bool> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_migrations_table(&mut conn).await?;
+        let baseline = &MIGRATIONS[0];
+
+        let result = sqlx::query(
+            r"
+            INSERT INTO schema_migrations (version, name, checksum, execution_ms, bootstrapped)
+            VALUES ($1, $2, $3, 0, TRUE)
+            ON CONFLICT (version) DO NOTHING
+            ",
+        )
+        .bind(baseline.version)
+        .bind(baseline.name)
+        .bind(baseline.checksum())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Apply pending migrations up to `target` (all when `None`)
+    ///
+    /// Refuses to run while an applied migration has been modified or the stored
+    /// embedding columns disagree with `embedding_dimension`. Returns the versions
+    /// that were applied.
+    pub async fn migrate_up(
+        &self,
+        target: Option<i64>,
+        embedding_dimension: usize,
+    ) -> Result<Vec<i64>> {
+        self.verify_embedding_dimension(embedding_dimension).await?;
+
+        let mut conn = self.pool.acquire().await?;
+        ensure_migrations_table(&mut conn).await?;
+        lock(&mut conn).await?;
+        let result = self
+            .migrate_up_locked(&mut conn, target, embedding_dimension)
+            .await;
+        unlock(&mut conn).await;
+        result
+    }
+
+    async fn migrate_up_locked(
+        &self,
+        conn: &mut PoolConnection<Postgres>,
+        target: Option<i64>,
+        embedding_dimension: usize,
+    ) -> Result<Vec<i64>> {
+        let applied = fetch_applied(conn).await?;
+        let drifted: Vec<_> = merge_status(MIGRATIONS, &applied)
+            .into_iter()
+            .filter(|status| status.state == MigrationState::Modified)
+            .map(|status| status.version)
+            .collect();
+        if !drifted.is_empty() {
+            return Err(SnapRagError::Custom(format!(
+                "Applied migrations {drifted:?} have been modified since they were applied; \
+                 run `snaprag migrate verify` for details"
+            )));
+        }
+
+        // A schema created from the monolithic init file has tables but no history
+        let bootstrap = applied.is_empty() && self.is_schema_initialized().await?;
+        if bootstrap {
+            tracing::info!("📦 Existing schema without migration history, bootstrapping baseline");
+        }
+
+        let mut newly_applied = Vec::new();
+        for migration in pending_migrations(MIGRATIONS, &applied, target) {
+            tracing::info!(
+                "⬆️  Applying migration {:03}_{}",
+                migration.version,
+                migration.name
+            );
+            let started = std::time::Instant::now();
+            let up = migration.render_up(embedding_dimension);
+
+            if migration.is_transactional() {
+                let mut tx = sqlx::Connection::begin(&mut **conn).await?;
+                sqlx::raw_sql(&up).execute(&mut *tx).await?;
+                record_applied(&mut tx, migration, started, false).await?;
+                tx.commit().await?;
+            } else {
+                for statement in split_statements(&up) {
+                    sqlx::raw_sql(statement)
+                        .execute(&mut **conn)
+                        .await
+                        .map_err(|e| {
+                            SnapRagError::Custom(format!(
+                                "Migration {:03}_{} failed: {e}\nStatement: {statement}",
+                                migration.version, migration.name
+                            ))
+                        })?;
+                }
+                let is_baseline = migration.version == MIGRATIONS[0].version;
+                record_applied(conn, migration, started, bootstrap && is_baseline).await?;
+            }
+
+            tracing::info!(
+                "✅ Migration {:03}_{} applied in {}ms",
+                migration.version,
+                migration.name,
+                started.elapsed().as_millis()
+            );
+            newly_applied.push(migration.version);
+        }
+
+        Ok(newly_applied)
+    }
+
+    /// Roll back the last `steps` applied migrations
+    ///
+    /// Fails before touching the schema if any of them has no down script.
+    /// Returns the versions that were rolled back.
+    pub async fn migrate_down(&self, steps: usize, embedding_dimension: usize) -> Result<Vec<i64>> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_migrations_table(&mut conn).await?;
+        lock(&mut conn).await?;
+        let result = migrate_down_locked(&mut conn, steps, embedding_dimension).await;
+        unlock(&mut conn).await;
+        result
+    }
+}
+
+async fn migrate_down_locked(
+    conn: &mut PoolConnection<Postgres>,
+    steps: usize,
+    embedding_dimension: usize,
+) -> Result<Vec<i64>> {
+    let applied = fetch_applied(conn).await?;
+
+    let mut to_revert = Vec::new();
+    for record in applied.iter().rev().take(steps) {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.version == record.version)
+            .ok_or_else(|| {
+                SnapRagError::Custom(format!(
+                    "Migration {:03}_{} is unknown to this binary and cannot be rolled back",
+                    record.version, record.name
+                ))
+            })?;
+        let down = migration.down.ok_or_else(|| {
+            SnapRagError::Custom(format!(
+                "Migration {:03}_{} has no down script",
+                migration.version, migration.name
+            ))
+        })?;
+        to_revert.push((migration, render_sql(down, embedding_dimension)));
+    }
+
+    let mut reverted = Vec::new();
+    for (migration, down) in to_revert {
+        tracing::info!(
+            "⬇️  Rolling back migration {:03}_{}",
+            migration.version,
+            migration.name
+        );
+
+        if has_no_transaction_marker(&down) {
+            for statement in split_statements(&down) {
+                sqlx::raw_sql(statement).execute(&mut **conn).await?;
+            }
+            sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+                .bind(migration.version)
+                .execute(&mut **conn)
+                .await?;
+        } else {
+            let mut tx = sqlx::Connection::begin(&mut **conn).await?;
+            sqlx::raw_sql(&down).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+                .bind(migration.version)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        reverted.push(migration.version);
+    }
+
+    Ok(reverted)
+}
+
+async fn ensure_migrations_table(conn: &mut PoolConnection<Postgres>) -> Result<()> {
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+            execution_ms BIGINT NOT NULL DEFAULT 0,
+            bootstrapped BOOLEAN NOT NULL DEFAULT FALSE
+        )
+        ",
+    )
+    .execute(&mut **conn)
+    .await?;
+    Ok(())
+}
+
+async fn fetch_applied(conn: &mut PoolConnection<Postgres>) -> Result<Vec<AppliedMigration>> {
+    Ok(sqlx::query_as::<_, AppliedMigration>(
+        "SELECT version, name, checksum, applied_at, execution_ms, bootstrapped
+         FROM schema_migrations ORDER BY version",
+    )
+    .fetch_all(&mut **conn)
+    .await?)
+}
+
+async fn record_applied(
+    conn: &mut sqlx::PgConnection,
+    migration: &Migration,
+    started: std::time::Instant,
+    bootstrapped: bool,
+) -> Result<()> {
+    sqlx::query(
+        r"
+        INSERT INTO schema_migrations (version, name, checksum, execution_ms, bootstrapped)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
+    )
+    .bind(migration.version)
+    .bind(migration.name)
+    .bind(migration.checksum())
+    .bind(i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX))
+    .bind(bootstrapped)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn lock(conn: &mut PoolConnection<Postgres>) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_ID)
+        .execute(&mut **conn)
+        .await?;
+    Ok(())
+}
+
+async fn unlock(conn: &mut PoolConnection<Postgres>) {
+    if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_ID)
+        .execute(&mut **conn)
+        .await
+    {
+        tracing::warn!("Failed to release migration lock: {}", e);
+    }
+}
+
+fn has_no_transaction_marker(sql: &str) -> bool {
+    sql.lines()
+        .any(|line| line.trim_start().starts_with(NO_TRANSACTION_MARKER))
+}
+
+/// Known migrations that are not applied yet, up to and including `target`
+fn pending_migrations<'a>(
+    migrations: &'a [Migration],
+    applied: &[AppliedMigration],
+    target: Option<i64>,
+) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .filter(|m| target.is_none_or(|target| m.version <= target))
+        .collect()
+}
+
+fn merge_status(migrations: &[Migration], applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let mut statuses: Vec<MigrationStatus> = migrations
+        .iter()
+        .map(|migration| {
+            let record = applied.iter().find(|a| a.version == migration.version);
+            let state = match record {
+                None => MigrationState::Pending,
+                Some(record) if record.checksum == migration.checksum() => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+            };
+            MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                state,
+                applied_at: record.map(|r| r.applied_at),
+                execution_ms: record.map(|r| r.execution_ms),
+                bootstrapped: record.is_some_and(|r| r.bootstrapped),
+            }
+        })
+        .collect();
+
+    statuses.extend(
+        applied
+            .iter()
+            .filter(|a| !migrations.iter().any(|m| m.version == a.version))
+            .map(|record| MigrationStatus {
+                version: record.version,
+                name: record.name.clone(),
+                state: MigrationState::Unknown,
+                applied_at: Some(record.applied_at),
+                execution_ms: Some(record.execution_ms),
+                bootstrapped: record.bootstrapped,
+            }),
+    );
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+/// Split a SQL script into individual statements
+///
+/// Understands quoted strings, quoted identifiers, dollar-quoted bodies and
+/// comments, so semicolons inside function bodies do not split statements.
+/// Comment-only fragments are dropped.
+fn split_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end + 1);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+            }
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // Doubled quote is an escaped quote
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'$' => {
+                let tag_len = sql[i + 1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .filter(|&len| bytes.get(i + 1 + len) == Some(&b'$'));
+                if let Some(len) = tag_len {
+                    let tag = &sql[i..i + len + 2];
+                    let body_start = i + tag.len();
+                    i = sql[body_start..]
+                        .find(tag)
+                        .map_or(bytes.len(), |end| body_start + end + tag.len());
+                } else {
+                    i += 1;
+                }
+            }
+            b';' => {
+                statements.push(&sql[start..=i]);
+                i += 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    statements.push(&sql[start..]);
+
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|statement| !is_comment_only(statement))
+        .collect()
+}
+
+fn is_comment_only(statement: &str) -> bool {
+    statement.lines().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with("--") || line == ";"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered_and_baseline_first() {
+        assert_eq!(MIGRATIONS[0].version, 0);
+        assert!(MIGRATIONS[0].down.is_none());
+        assert!(!MIGRATIONS[0].is_transactional());
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "versions must increase");
+        }
+        assert_eq!(MIGRATIONS[0].checksum().len(), 64);
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "-- header; not a statement\n\
+                   CREATE TABLE a (x TEXT DEFAULT 'a;b''c');\n\
+                   CREATE FUNCTION f() RETURNS TRIGGER AS $$ BEGIN x := 1; RETURN NEW; END; $$ LANGUAGE plpgsql;\n\
+                   /* block; comment */ SELECT $tag$;$tag$;\n\
+                   -- trailing comment\n";
+        let statements = split_statements(sql);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].ends_with("'a;b''c');"));
+        assert!(statements[1].contains("RETURN NEW; END; $$ LANGUAGE plpgsql;"));
+        assert!(statements[2].ends_with("SELECT $tag$;$tag$;"));
+    }
+
+    #[test]
+    fn test_split_baseline_keeps_function_body_whole() {
+        let rendered = MIGRATIONS[0].render_up(768);
+        assert!(!rendered.contains(EMBEDDING_DIMENSION_PLACEHOLDER));
+        assert!(rendered.contains("embedding VECTOR(768) NOT NULL"));
+        let statements = split_statements(&rendered);
+        let function = statements
+            .iter()
+            .find(|s| s.contains("update_cast_embeddings_updated_at()") && s.contains("$$"))
+            .unwrap();
+        assert!(function.trim_end().ends_with("plpgsql;"));
+        assert!(statements.iter().any(|s| s.contains("CONCURRENTLY")));
+    }
+
+    #[test]
+    fn test_merge_status() {
+        let migrations = [
+            Migration {
+                version: 0,
+                name: "base",
+                up: "SELECT 1;",
+                down: None,
+            },
+            Migration {
+                version: 1,
+                name: "changed",
+                up: "SELECT 2;",
+                down: Some("SELECT 3;"),
+            },
+            Migration {
+                version: 2,
+                name: "new",
+                up: "SELECT 4;",
+                down: None,
+            },
+        ];
+        let record = |version: i64, checksum: String| AppliedMigration {
+            version,
+            name: format!("m{version}"),
+            checksum,
+            applied_at: chrono::Utc::now(),
+            execution_ms: 1,
+            bootstrapped: false,
+        };
+        let applied = vec![
+            record(0, migrations[0].checksum()),
+            record(1, "stale".to_string()),
+            record(7, "x".to_string()),
+        ];
+
+        let states: Vec<_> = merge_status(&migrations, &applied)
+            .into_iter()
+            .map(|s| (s.version, s.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (0, MigrationState::Applied),
+                (1, MigrationState::Modified),
+                (2, MigrationState::Pending),
+                (7, MigrationState::Unknown),
+            ]
+        );
+
+        let pending = pending_migrations(&migrations, &applied[..1], Some(1));
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].version, 1);
+    }
+}
@@ -34,7 +34,7 @@
 //! let database = Database::from_config(&config).await?;
 //!
 //! // Initialize schema
-//! database.init_schema().await?;
+//! database.init_schema(config.embedding_dimension()).await?;
 //!
 //! // Query user profile
 //! if let Some(profile) = database.get_user_profile(3).await? {
@@ -46,24 +46,48 @@
 //!
 //! # Modules
 //!
+//! - `block_hashes`: Hashes of processed blocks and rollback of diverged blocks
 //! - `casts`: Cast storage and retrieval operations
+//! - `embedding_versions`: Embedding model versions, shadow columns and cutover
+//! - `export`: Server-side cursors over exported tables in a read-only snapshot
+//! - `fid_messages`: Current per-FID message state for hub reconciliation
+//! - `import`: Resumable loading of exported archives
 //! - `links`: Social link management (follows, etc.)
+//! - `live_feed`: Live feed of committed sync events over `LISTEN` / `NOTIFY`
+//! - `migrations`: Versioned schema migrations and `schema_migrations` tracking
+//! - `processed_blocks`: Ledger of processed block heights and sync gap detection
+//! - `rejected_messages`: Message verification policy and rejected messages
 //! - `schema`: Database schema initialization and validation
 //! - `sync`: Sync state tracking
 //! - `user_activity`: Activity timeline queries
 //! - `user_data`: User data change tracking
 //! - `user_profiles`: Profile queries and updates
 //! - `user_snapshots`: Historical profile snapshots
+//! - `vector_indexes`: Vector index definitions, per-query search parameters and recall
+//! - `webhooks`: Webhook subscriptions, change events and the delivery queue
 
 use sqlx::PgPool;
 
+use crate::config::FlushStrategy;
 use crate::models::*;
+use crate::sync::SyncSinks;
 use crate::Result;
 use crate::SnapRagError;
 
 // Re-export submodules
+mod block_hashes;
 mod casts;
+mod embedding_versions;
+mod export;
+mod fid_messages;
+mod full_text;
+mod import;
 mod links;
+mod live_feed;
+mod migrations;
+mod onchain;
+mod processed_blocks;
+mod rejected_messages;
 mod schema;
 mod sync;
 mod user_activity;
@@ -72,10 +96,58 @@ mod user_data_changes;
 mod user_profiles;
 mod user_snapshots;
 mod username_proofs;
+mod vector_indexes;
+mod webhooks;
 
 // Re-export public types
+pub(crate) use block_hashes::prune_orphaned_cast_embeddings;
+pub(crate) use block_hashes::record_block_hashes;
+pub(crate) use block_hashes::rollback_blocks;
+pub use block_hashes::BlockHash;
+pub use block_hashes::BlockRollback;
+pub(crate) use casts::bind_cast_filters;
+pub use casts::channel_url;
 pub use casts::CastThread;
+pub(crate) use casts::CAST_FILTERS;
+pub(crate) use casts::NOT_REMOVED;
+pub(crate) use casts::REPLY_NOT_REMOVED;
+pub use embedding_versions::EmbeddingCoverage;
+pub use embedding_versions::EmbeddingVersion;
+pub use embedding_versions::BASE_EMBEDDING_VERSION;
+pub use embedding_versions::CAST_EMBEDDING_COLUMN;
+pub use embedding_versions::PROFILE_EMBEDDING_COLUMNS;
+pub use export::ExportCursor;
+pub use export::ExportSnapshot;
+pub use fid_messages::FidMessageState;
+pub use full_text::RankedProfile;
+pub use import::ImportProgress;
+pub use live_feed::LiveFeedEvent;
+pub use live_feed::LIVE_FEED_CHANNEL;
+pub use migrations::render_sql;
+pub use migrations::Migration;
+pub use migrations::MigrationState;
+pub use migrations::MigrationStatus;
+pub use migrations::MIGRATIONS;
+pub use processed_blocks::find_gaps;
+pub use processed_blocks::height_ranges;
+pub(crate) use processed_blocks::record_processed_range;
+pub use processed_blocks::HeightRange;
+pub(crate) use rejected_messages::active_signer_keys;
+pub use rejected_messages::RejectedMessage;
+pub use rejected_messages::VerificationPolicy;
+pub use schema::VectorColumn;
+pub use sync::SnapshotBootstrap;
 pub use sync::SyncStats;
+pub use vector_indexes::RecallEstimate;
+pub use vector_indexes::VectorIndexSettings;
+pub use vector_indexes::VectorIndexTarget;
+pub use vector_indexes::VectorSearchParams;
+pub use vector_indexes::MAX_INDEXED_VECTOR_DIMENSION;
+pub(crate) use webhooks::record_change_events;
+pub use webhooks::PendingDelivery;
+pub use webhooks::WebhookDeadLetter;
+pub use webhooks::WebhookEvent;
+pub use webhooks::WebhookSubscription;
 
 /// Database connection pool wrapper
 ///
@@ -88,6 +160,12 @@ pub use sync::SyncStats;
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: PgPool,
+    vector_search: VectorSearchParams,
+    message_verification: VerificationPolicy,
+    flush_strategy: FlushStrategy,
+    change_capture: bool,
+    live_feed: bool,
+    sync_sinks: SyncSinks,
 }
 
 impl Database {
@@ -107,7 +185,15 @@ impl Database {
     /// ```
     #[must_use]
     pub const fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            vector_search: VectorSearchParams::DEFAULT,
+            message_verification: VerificationPolicy::DISABLED,
+            flush_strategy: FlushStrategy::Insert,
+            change_capture: false,
+            live_feed: false,
+            sync_sinks: SyncSinks::postgres(),
+        }
     }
 
     /// Create a new database instance from configuration
@@ -138,7 +224,13 @@ impl Database {
             .min_connections(config.min_connections())
             .acquire_timeout(std::time::Duration::from_secs(config.connection_timeout()));
 
-        let pool = pool_options.connect(config.database_url()).await?;
+        // An export-only sync must start without a reachable database
+        let sync_sinks = SyncSinks::from_config(config)?;
+        let pool = if sync_sinks.writes_postgres() {
+            pool_options.connect(config.database_url()).await?
+        } else {
+            pool_options.connect_lazy(config.database_url())?
+        };
 
         tracing::debug!(
             "Database pool configured: max_connections={}, min_connections={}",
@@ -146,14 +238,34 @@ impl Database {
             config.min_connections()
         );
 
-        Ok(Self::new(pool))
+        Ok(Self::new(pool)
+            .with_vector_search(VectorSearchParams::from_config(&config.performance))
+            .with_message_verification(VerificationPolicy::from_config(&config.sync))
+            .with_flush_strategy(config.flush_strategy())
+            .with_change_capture(config.webhooks.enabled)
+            .with_live_feed(config.live_feed.enabled)
+            .with_sync_sinks(sync_sinks))
+    }
+
+    /// Write sync batches with `strategy`
+    #[must_use]
+    pub const fn with_flush_strategy(mut self, strategy: FlushStrategy) -> Self {
+        self.flush_strategy = strategy;
+        self
+    }
+
+    /// How sync batches are written
+    #[must_use]
+    pub const fn flush_strategy(&self) -> FlushStrategy {
+        self.flush_strategy
     }
 
     /// Run database migrations
     ///
-    /// Note: Migrations are currently managed manually via SQL files in `/migrations`.
-    /// Future enhancement: Could integrate with sqlx migrations or refinery.
-    pub const fn migrate(&self) -> Result<()> {
+    /// Applies all pending versioned migrations from `/migrations`, bootstrapping
+    /// the baseline on databases created by the monolithic init script.
+    pub async fn migrate(&self, embedding_dimension: usize) -> Result<()> {
+        self.migrate_up(None, embedding_dimension).await?;
         Ok(())
     }
 
@@ -0,0 +1,93 @@
+use super::Database;
+use crate::models::FidRegistration;
+use crate::models::FidSigner;
+use crate::models::OnchainState;
+use crate::models::StorageRent;
+use crate::Result;
+
+impl Database {
+    /// Get the active signer keys of a FID, most recently added first
+    pub async fn get_active_signers(&self, fid: i64) -> Result<Vec<FidSigner>> {
+        let signers = sqlx::query_as::<_, FidSigner>(
+            "SELECT * FROM fid_signers WHERE fid = $1 AND active ORDER BY added_block DESC NULLS LAST",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(signers)
+    }
+
+    /// Get all signer keys of a FID, including removed ones
+    pub async fn get_fid_signers(&self, fid: i64) -> Result<Vec<FidSigner>> {
+        let signers = sqlx::query_as::<_, FidSigner>(
+            "SELECT * FROM fid_signers WHERE fid = $1 ORDER BY last_block_number DESC, last_log_index DESC",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(signers)
+    }
+
+    /// Get custody / recovery addresses and registration time of a FID
+    pub async fn get_fid_registration(&self, fid: i64) -> Result<Option<FidRegistration>> {
+        let registration =
+            sqlx::query_as::<_, FidRegistration>("SELECT * FROM fid_registrations WHERE fid = $1")
+                .bind(fid)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(registration)
+    }
+
+    /// Find the FID whose current custody address is `custody_address`
+    pub async fn get_fid_by_custody_address(&self, custody_address: &[u8]) -> Result<Option<i64>> {
+        let fid = sqlx::query_scalar::<_, i64>(
+            "SELECT fid FROM fid_registrations WHERE custody_address = $1 ORDER BY last_block_number DESC LIMIT 1",
+        )
+        .bind(custody_address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(fid)
+    }
+
+    /// Get storage rent history of a FID, newest first
+    pub async fn get_storage_rents(&self, fid: i64) -> Result<Vec<StorageRent>> {
+        let rents = sqlx::query_as::<_, StorageRent>(
+            "SELECT * FROM storage_rents WHERE fid = $1 ORDER BY block_number DESC, log_index DESC",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rents)
+    }
+
+    /// Count storage units of a FID that have not expired at `at` (unix seconds)
+    pub async fn get_storage_units(&self, fid: i64, at: i64) -> Result<i64> {
+        let units = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(SUM(units), 0)::BIGINT FROM storage_rents WHERE fid = $1 AND expiry > $2",
+        )
+        .bind(fid)
+        .bind(at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(units)
+    }
+
+    /// Get the decoded on-chain state of a FID (registration, signers, storage)
+    pub async fn get_onchain_state(&self, fid: i64) -> Result<OnchainState> {
+        let now = chrono::Utc::now().timestamp();
+
+        Ok(OnchainState {
+            fid,
+            registration: self.get_fid_registration(fid).await?,
+            active_signers: self.get_active_signers(fid).await?,
+            storage_units: self.get_storage_units(fid, now).await?,
+            storage_rents: self.get_storage_rents(fid).await?,
+        })
+    }
+}
@@ -0,0 +1,232 @@
+//! Ledger of processed block heights per shard
+//!
+//! Every flushed batch records the heights of its chunks in `processed_blocks`
+//! as merged, inclusive ranges. Holes between the ranges are blocks that sync
+//! skipped, e.g. a failed batch of a parallel worker.
+
+use sqlx::PgConnection;
+
+use super::Database;
+use crate::Result;
+
+/// Inclusive range of block heights
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct HeightRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl HeightRange {
+    #[must_use]
+    pub const fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// Number of blocks in the range
+    #[must_use]
+    pub const fn block_count(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+impl std::fmt::Display for HeightRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// Collapse block heights (in any order, duplicates allowed) into ranges
+#[must_use]
+pub fn height_ranges(heights: &[u64]) -> Vec<HeightRange> {
+    let mut sorted = heights.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges: Vec<HeightRange> = Vec::new();
+    for height in sorted {
+        match ranges.last_mut() {
+            Some(last) if last.end + 1 == height => last.end = height,
+            _ => ranges.push(HeightRange::new(height, height)),
+        }
+    }
+    ranges
+}
+
+/// Heights in `from..=to` that no range in `processed` covers
+///
+/// `processed` must be sorted by start height; ranges may overlap or touch.
+#[must_use]
+pub fn find_gaps(processed: &[HeightRange], from: u64, to: u64) -> Vec<HeightRange> {
+    let mut gaps = Vec::new();
+    let mut next = from;
+
+    for range in processed {
+        if next > to {
+            break;
+        }
+        if range.end < next {
+            continue;
+        }
+        if range.start > next {
+            gaps.push(HeightRange::new(next, (range.start - 1).min(to)));
+        }
+        next = next.max(range.end.saturating_add(1));
+    }
+    if next <= to {
+        gaps.push(HeightRange::new(next, to));
+    }
+
+    gaps
+}
+
+/// Add `range` to the ledger of `shard_id`, merging it with ranges it overlaps or touches
+pub async fn record_processed_range(
+    conn: &mut PgConnection,
+    shard_id: u32,
+    range: HeightRange,
+) -> Result<()> {
+    sqlx::query(
+        r"
+        WITH absorbed AS (
+            DELETE FROM processed_blocks
+            WHERE shard_id = $1 AND start_height <= $3 + 1 AND end_height >= $2 - 1
+            RETURNING start_height, end_height
+        )
+        INSERT INTO processed_blocks (shard_id, start_height, end_height)
+        SELECT $1, LEAST($2, MIN(start_height)), GREATEST($3, MAX(end_height)) FROM absorbed
+        ON CONFLICT (shard_id, start_height)
+        DO UPDATE SET
+            end_height = GREATEST(processed_blocks.end_height, EXCLUDED.end_height),
+            updated_at = NOW()
+        ",
+    )
+    .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+    .bind(i64::try_from(range.start).unwrap_or(i64::MAX))
+    .bind(i64::try_from(range.end).unwrap_or(i64::MAX))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+impl Database {
+    /// Processed height ranges of a shard, ordered by start height
+    pub async fn processed_block_ranges(&self, shard_id: u32) -> Result<Vec<HeightRange>> {
+        let rows = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT start_height, end_height FROM processed_blocks WHERE shard_id = $1 ORDER BY start_height",
+        )
+        .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(start, end)| {
+                HeightRange::new(
+                    u64::try_from(start).unwrap_or(0),
+                    u64::try_from(end).unwrap_or(0),
+                )
+            })
+            .collect())
+    }
+
+    /// Shards that have processed blocks
+    pub async fn processed_block_shards(&self) -> Result<Vec<u32>> {
+        let shards = sqlx::query_scalar::<_, i32>(
+            "SELECT DISTINCT shard_id FROM processed_blocks ORDER BY shard_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(shards
+            .into_iter()
+            .filter_map(|shard| u32::try_from(shard).ok())
+            .collect())
+    }
+
+    /// Record `range` of `shard_id` as processed
+    pub async fn record_processed_blocks(&self, shard_id: u32, range: HeightRange) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        record_processed_range(&mut conn, shard_id, range).await
+    }
+
+    /// Missing height ranges of a shard
+    ///
+    /// `from` defaults to the first processed height and `to` to the last one,
+    /// so only holes inside what was synced are reported.
+    pub async fn find_sync_gaps(
+        &self,
+        shard_id: u32,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Result<Vec<HeightRange>> {
+        let processed = self.processed_block_ranges(shard_id).await?;
+        let Some(first) = processed.first() else {
+            // Nothing synced yet, an explicit range is entirely missing
+            return Ok(match (from, to) {
+                (Some(from), Some(to)) if from <= to => vec![HeightRange::new(from, to)],
+                _ => Vec::new(),
+            });
+        };
+
+        let from = from.unwrap_or(first.start);
+        let to = to.unwrap_or_else(|| processed.iter().map(|r| r.end).max().unwrap_or(first.end));
+        Ok(find_gaps(&processed, from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_ranges() {
+        assert!(height_ranges(&[]).is_empty());
+        assert_eq!(
+            height_ranges(&[7, 3, 4, 5, 5, 10, 8]),
+            vec![
+                HeightRange::new(3, 5),
+                HeightRange::new(7, 8),
+                HeightRange::new(10, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        let processed = [
+            HeightRange::new(0, 99),
+            HeightRange::new(150, 199),
+            HeightRange::new(180, 250),
+            HeightRange::new(252, 300),
+        ];
+
+        assert_eq!(
+            find_gaps(&processed, 0, 300),
+            vec![HeightRange::new(100, 149), HeightRange::new(251, 251)]
+        );
+        // Bounds clip the gaps
+        assert_eq!(
+            find_gaps(&processed, 120, 260),
+            vec![HeightRange::new(120, 149), HeightRange::new(251, 251)]
+        );
+        // Beyond the last range everything is missing
+        assert_eq!(
+            find_gaps(&processed, 290, 320),
+            vec![HeightRange::new(301, 320)]
+        );
+        assert_eq!(find_gaps(&[], 5, 9), vec![HeightRange::new(5, 9)]);
+        assert!(find_gaps(&processed, 0, 99).is_empty());
+    }
+
+    #[test]
+    fn test_height_range_display() {
+        assert_eq!(HeightRange::new(4, 4).to_string(), "4");
+        assert_eq!(HeightRange::new(4, 9).to_string(), "4-9");
+        assert_eq!(HeightRange::new(4, 9).block_count(), 6);
+    }
+}
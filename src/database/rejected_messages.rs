@@ -0,0 +1,140 @@
+//! Messages rejected by sync-time hash and signature verification
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use sqlx::PgConnection;
+
+use super::Database;
+use crate::config::MessageVerification;
+use crate::config::SyncConfig;
+use crate::Result;
+
+/// How sync verifies ingested messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationPolicy {
+    pub mode: MessageVerification,
+    /// Also require the signer to be an active key of the message's FID
+    pub verify_signers: bool,
+}
+
+impl VerificationPolicy {
+    /// Verification off, messages are ingested as received
+    pub const DISABLED: Self = Self {
+        mode: MessageVerification::Off,
+        verify_signers: false,
+    };
+
+    #[must_use]
+    pub const fn from_config(config: &SyncConfig) -> Self {
+        Self {
+            mode: config.message_verification,
+            verify_signers: config.verify_message_signers,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        !matches!(self.mode, MessageVerification::Off)
+    }
+}
+
+impl Default for VerificationPolicy {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+/// A message that failed verification
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RejectedMessage {
+    pub id: i64,
+    pub message_hash: Vec<u8>,
+    pub fid: i64,
+    pub message_type: i32,
+    pub signer: Vec<u8>,
+    pub reason: String,
+    /// Full message, only kept in quarantine mode
+    pub message: Option<serde_json::Value>,
+    pub shard_id: i32,
+    pub block_height: i64,
+    pub rejected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Active signer keys of each of `fids`, read on `conn`
+///
+/// Lets a rollback verify the re-applied chunks against the signer state it
+/// rebuilt before committing. FIDs without an active key are absent from the
+/// map.
+pub async fn active_signer_keys(
+    conn: &mut PgConnection,
+    fids: &[i64],
+) -> Result<HashMap<i64, HashSet<Vec<u8>>>> {
+    let rows = sqlx::query_as::<_, (i64, Vec<u8>)>(
+        "SELECT fid, key FROM fid_signers WHERE fid = ANY($1) AND active",
+    )
+    .bind(fids)
+    .fetch_all(conn)
+    .await?;
+
+    let mut keys: HashMap<i64, HashSet<Vec<u8>>> = HashMap::new();
+    for (fid, key) in rows {
+        keys.entry(fid).or_default().insert(key);
+    }
+    Ok(keys)
+}
+
+impl Database {
+    /// Verify ingested messages according to `policy`
+    #[must_use]
+    pub const fn with_message_verification(mut self, policy: VerificationPolicy) -> Self {
+        self.message_verification = policy;
+        self
+    }
+
+    /// Message verification policy used by sync
+    #[must_use]
+    pub const fn message_verification(&self) -> VerificationPolicy {
+        self.message_verification
+    }
+
+    /// Active signer keys of each of `fids`
+    ///
+    /// FIDs without an active key are absent from the map.
+    pub async fn active_signer_keys(&self, fids: &[i64]) -> Result<HashMap<i64, HashSet<Vec<u8>>>> {
+        active_signer_keys(&mut *self.pool.acquire().await?, fids).await
+    }
+
+    /// Most recently rejected messages, optionally of one FID
+    pub async fn list_rejected_messages(
+        &self,
+        fid: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<RejectedMessage>> {
+        let rejected = sqlx::query_as::<_, RejectedMessage>(
+            r"
+            SELECT * FROM rejected_messages
+            WHERE $1::BIGINT IS NULL OR fid = $1
+            ORDER BY rejected_at DESC, id DESC
+            LIMIT $2
+            ",
+        )
+        .bind(fid)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rejected)
+    }
+
+    /// Number of rejected messages per reason
+    pub async fn count_rejected_messages(&self) -> Result<Vec<(String, i64)>> {
+        let counts = sqlx::query_as::<_, (String, i64)>(
+            "SELECT reason, COUNT(*) FROM rejected_messages GROUP BY reason ORDER BY reason",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+}
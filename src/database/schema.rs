@@ -2,6 +2,24 @@ use super::Database;
 use crate::Result;
 use crate::SnapRagError;
 
+/// Vector columns written by the embedding pipeline, as (table, column)
+const EMBEDDING_COLUMNS: &[(&str, &str)] = &[
+    ("cast_embeddings", "embedding"),
+    ("cast_embedding_chunks", "embedding"),
+    ("cast_embedding_aggregated", "embedding"),
+    ("profile_embeddings", "profile_embedding"),
+    ("profile_embeddings", "bio_embedding"),
+    ("profile_embeddings", "interests_embedding"),
+];
+
+/// A `vector(n)` column and its declared dimension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorColumn {
+    pub table: String,
+    pub column: String,
+    pub dimension: i32,
+}
+
 impl Database {
     /// Check if database schema is initialized
     /// Returns true if all required tables exist
@@ -93,16 +111,107 @@ impl Database {
                     .to_string(),
             ));
         }
+
+        match self.pending_migration_count().await {
+            Ok(0) => {}
+            Ok(pending) => {
+                return Err(SnapRagError::Custom(format!(
+                    "❌ {pending} pending schema migration(s)!\n\n\
+                     Please run the following command to update the database:\n\n\
+                     \x1b[1;32msnaprag migrate up\x1b[0m\n\n\
+                     Then start sync again."
+                )));
+            }
+            Err(e) => tracing::debug!("Could not check schema migrations: {}", e),
+        }
         Ok(())
     }
 
+    /// Dimensions of the existing embedding columns
+    ///
+    /// Columns whose table does not exist yet are omitted.
+    pub async fn embedding_column_dimensions(&self) -> Result<Vec<VectorColumn>> {
+        let tables: Vec<&str> = EMBEDDING_COLUMNS.iter().map(|(table, _)| *table).collect();
+
+        let rows: Vec<(String, String, i32)> = sqlx::query_as(
+            r"
+            SELECT c.relname::text, a.attname::text, a.atttypmod
+            FROM pg_attribute a
+            JOIN pg_class c ON c.oid = a.attrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            JOIN pg_type t ON t.oid = a.atttypid
+            WHERE n.nspname = 'public'
+              AND c.relname = ANY($1)
+              AND t.typname = 'vector'
+              AND a.attnum > 0
+              AND NOT a.attisdropped
+            ",
+        )
+        .bind(&tables)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut columns: Vec<VectorColumn> = rows
+            .into_iter()
+            .filter(|(table, column, _)| {
+                EMBEDDING_COLUMNS
+                    .iter()
+                    .any(|(t, c)| t == table && c == column)
+            })
+            .map(|(table, column, dimension)| VectorColumn {
+                table,
+                column,
+                dimension,
+            })
+            .collect();
+        columns.sort_by(|a, b| (&a.table, &a.column).cmp(&(&b.table, &b.column)));
+        Ok(columns)
+    }
+
+    /// Fail if any existing embedding column disagrees with `expected` dimensions
+    pub async fn verify_embedding_dimension(&self, expected: usize) -> Result<()> {
+        // After a cutover the original columns belong to a retired model
+        if let Some(active) = self.active_embedding_version().await? {
+            if active.version != super::BASE_EMBEDDING_VERSION {
+                if active.dimension() == expected {
+                    return Ok(());
+                }
+                return Err(SnapRagError::Custom(format!(
+                    "❌ Embedding dimension mismatch!\n\n\
+                     The configured embeddings.dimension is {expected}, but the active embedding\n\
+                     model is {} (version {}, {} dimensions).\n\n\
+                     Set embeddings.model and embeddings.dimension in config.toml to the active model.",
+                    active.model, active.version, active.dimension
+                )));
+            }
+        }
+
+        let columns = self.embedding_column_dimensions().await?;
+        let mismatched = dimension_mismatches(&columns, expected);
+        if mismatched.is_empty() {
+            return Ok(());
+        }
+
+        let details: Vec<String> = mismatched
+            .iter()
+            .map(|c| format!("  {}.{} is vector({})", c.table, c.column, c.dimension))
+            .collect();
+        Err(SnapRagError::Custom(format!(
+            "❌ Embedding dimension mismatch!\n\n\
+             The configured embeddings.dimension is {expected}, but the database has:\n{}\n\n\
+             Set embeddings.dimension in config.toml to match the stored columns, or\n\
+             reset the embedding tables before switching to a model with a different dimension.",
+            details.join("\n")
+        )))
+    }
+
     /// Initialize database schema
-    pub async fn init_schema(&self) -> Result<()> {
+    pub async fn init_schema(&self, embedding_dimension: usize) -> Result<()> {
         // Note: user_profiles is created as a VIEW in migrations/000_complete_init.sql
         // We don't create it here to avoid conflict
 
         // Create user_profile_snapshots table
-        sqlx::query(
+        sqlx::query(&super::render_sql(
             r"
             CREATE TABLE IF NOT EXISTS user_profile_snapshots (
                 id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
@@ -121,14 +230,15 @@ impl Database {
                 primary_address_ethereum VARCHAR(42),
                 primary_address_solana VARCHAR(44),
                 profile_token VARCHAR(255),
-                profile_embedding VECTOR(1536),
-                bio_embedding VECTOR(1536),
-                interests_embedding VECTOR(1536),
+                profile_embedding VECTOR({{embedding_dimension}}),
+                bio_embedding VECTOR({{embedding_dimension}}),
+                interests_embedding VECTOR({{embedding_dimension}}),
                 created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
                 UNIQUE(fid, snapshot_timestamp)
             )
             ",
-        )
+            embedding_dimension,
+        ))
         .execute(&self.pool)
         .await?;
 
@@ -177,7 +287,7 @@ impl Database {
         // All activity data is in specialized tables (casts, links, reactions, etc.)
 
         // Create user_profile_trends table
-        sqlx::query(
+        sqlx::query(&super::render_sql(
             r"
             CREATE TABLE IF NOT EXISTS user_profile_trends (
                 id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
@@ -189,13 +299,14 @@ impl Database {
                 username_changes_count INTEGER DEFAULT 0,
                 activity_score FLOAT DEFAULT 0.0,
                 engagement_score FLOAT DEFAULT 0.0,
-                profile_embedding VECTOR(1536),
-                bio_embedding VECTOR(1536),
+                profile_embedding VECTOR({{embedding_dimension}}),
+                bio_embedding VECTOR({{embedding_dimension}}),
                 created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
                 UNIQUE(fid, trend_period, trend_date)
             )
             ",
-        )
+            embedding_dimension,
+        ))
         .execute(&self.pool)
         .await?;
 
@@ -221,3 +332,37 @@ impl Database {
         Ok(())
     }
 }
+
+/// Columns whose declared dimension differs from `expected`
+///
+/// Unconstrained `vector` columns (no declared dimension) are ignored.
+fn dimension_mismatches(columns: &[VectorColumn], expected: usize) -> Vec<&VectorColumn> {
+    columns
+        .iter()
+        .filter(|c| c.dimension > 0 && usize::try_from(c.dimension).ok() != Some(expected))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimension_mismatches() {
+        let column = |table: &str, dimension: i32| VectorColumn {
+            table: table.to_string(),
+            column: "embedding".to_string(),
+            dimension,
+        };
+        let columns = vec![
+            column("cast_embeddings", 384),
+            column("cast_embedding_chunks", 768),
+            column("cast_embedding_aggregated", -1),
+        ];
+
+        let mismatched = dimension_mismatches(&columns, 384);
+        assert_eq!(mismatched, vec![&columns[1]]);
+        assert_eq!(dimension_mismatches(&columns, 768), vec![&columns[0]]);
+        assert!(dimension_mismatches(&columns[2..], 1536).is_empty());
+    }
+}
@@ -1,4 +1,7 @@
+use super::processed_blocks::record_processed_range;
+use super::processed_blocks::HeightRange;
 use super::Database;
+use crate::sync::SyncSinks;
 use crate::Result;
 
 /// Sync-related data structures
@@ -13,9 +16,51 @@ pub struct SyncStats {
     pub last_sync_timestamp: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Progress of a snapshot bootstrap for one shard
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SnapshotBootstrap {
+    pub shard_id: i32,
+    pub height: i64,
+    pub snapshot_timestamp: i64,
+    /// Virtual trie shard the next page is read from
+    pub virtual_shard: i32,
+    /// Page token within `virtual_shard`, `None` for its first page
+    pub page_token: Option<String>,
+    pub entries_applied: i64,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SnapshotBootstrap {
+    #[must_use]
+    pub const fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
+
 impl Database {
+    /// Write sync batches to `sinks`
+    #[must_use]
+    pub fn with_sync_sinks(mut self, sinks: SyncSinks) -> Self {
+        self.sync_sinks = sinks;
+        self
+    }
+
+    /// The sinks sync batches are written to
+    #[must_use]
+    pub const fn sync_sinks(&self) -> &SyncSinks {
+        &self.sync_sinks
+    }
+
     /// Get the last processed height for a shard
+    ///
+    /// An export-only sync reads it from its progress file.
     pub async fn get_last_processed_height(&self, shard_id: u32) -> Result<u64> {
+        if let Some(progress) = self.sync_sinks.export_progress() {
+            return progress.last_processed_height(shard_id);
+        }
+
         let row = sqlx::query_scalar::<_, Option<i64>>(
             "SELECT last_processed_height FROM sync_progress WHERE shard_id = $1",
         )
@@ -28,6 +73,10 @@ impl Database {
 
     /// Update the last processed height for a shard
     pub async fn update_last_processed_height(&self, shard_id: u32, height: u64) -> Result<()> {
+        if let Some(progress) = self.sync_sinks.export_progress() {
+            return progress.update(shard_id, height);
+        }
+
         sqlx::query(
             r"
             INSERT INTO sync_progress (shard_id, last_processed_height, status, updated_at)
@@ -47,6 +96,130 @@ impl Database {
         Ok(())
     }
 
+    /// Snapshot bootstrap progress of a shard, if one was started
+    pub async fn get_snapshot_bootstrap(&self, shard_id: u32) -> Result<Option<SnapshotBootstrap>> {
+        let bootstrap = sqlx::query_as::<_, SnapshotBootstrap>(
+            "SELECT * FROM snapshot_bootstraps WHERE shard_id = $1",
+        )
+        .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(bootstrap)
+    }
+
+    /// Start (or resume) bootstrapping a shard from the snapshot at `height`
+    ///
+    /// An unfinished bootstrap of the same height keeps its cursor; any other
+    /// previous bootstrap of the shard is restarted from the first page.
+    pub async fn start_snapshot_bootstrap(
+        &self,
+        shard_id: u32,
+        height: u64,
+        snapshot_timestamp: u64,
+    ) -> Result<SnapshotBootstrap> {
+        let bootstrap = sqlx::query_as::<_, SnapshotBootstrap>(
+            r"
+            INSERT INTO snapshot_bootstraps (shard_id, height, snapshot_timestamp)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (shard_id) DO UPDATE SET
+                height = EXCLUDED.height,
+                snapshot_timestamp = EXCLUDED.snapshot_timestamp,
+                virtual_shard = 0,
+                page_token = NULL,
+                entries_applied = 0,
+                started_at = NOW(),
+                updated_at = NOW(),
+                completed_at = NULL
+            WHERE snapshot_bootstraps.height <> EXCLUDED.height
+               OR snapshot_bootstraps.completed_at IS NOT NULL
+            RETURNING *
+            ",
+        )
+        .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+        .bind(i64::try_from(height).unwrap_or(i64::MAX))
+        .bind(i64::try_from(snapshot_timestamp).unwrap_or(i64::MAX))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match bootstrap {
+            Some(bootstrap) => Ok(bootstrap),
+            // Unfinished bootstrap of the same snapshot: resume it as is
+            None => self.get_snapshot_bootstrap(shard_id).await?.ok_or_else(|| {
+                crate::SnapRagError::Custom(format!(
+                    "Snapshot bootstrap of shard {shard_id} disappeared"
+                ))
+            }),
+        }
+    }
+
+    /// Save the cursor of the next snapshot page to read
+    pub async fn save_snapshot_bootstrap_cursor(
+        &self,
+        shard_id: u32,
+        virtual_shard: u32,
+        page_token: Option<&str>,
+        entries_applied: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            UPDATE snapshot_bootstraps
+            SET virtual_shard = $2, page_token = $3, entries_applied = $4, updated_at = NOW()
+            WHERE shard_id = $1
+            ",
+        )
+        .bind(i32::try_from(shard_id).unwrap_or(i32::MAX))
+        .bind(i32::try_from(virtual_shard).unwrap_or(i32::MAX))
+        .bind(page_token)
+        .bind(i64::try_from(entries_applied).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark the bootstrap complete and hand the shard over to block sync
+    ///
+    /// Block sync resumes at `height + 1`, the first block not covered by the
+    /// snapshot.
+    pub async fn complete_snapshot_bootstrap(&self, shard_id: u32, height: u64) -> Result<()> {
+        let shard = i32::try_from(shard_id).unwrap_or(i32::MAX);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r"
+            UPDATE snapshot_bootstraps
+            SET completed_at = NOW(), updated_at = NOW()
+            WHERE shard_id = $1
+            ",
+        )
+        .bind(shard)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO sync_progress (shard_id, last_processed_height, status, updated_at)
+            VALUES ($1, $2, 'syncing', NOW())
+            ON CONFLICT (shard_id)
+            DO UPDATE SET
+                last_processed_height = EXCLUDED.last_processed_height,
+                status = 'syncing',
+                updated_at = NOW()
+            ",
+        )
+        .bind(shard)
+        .bind(i64::try_from(height.saturating_add(1)).unwrap_or(i64::MAX))
+        .execute(&mut *tx)
+        .await?;
+
+        // The snapshot covers every block up to its height
+        record_processed_range(&mut tx, shard_id, HeightRange::new(0, height)).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     /// Update sync status for a shard
     pub async fn update_sync_status(
         &self,
@@ -210,11 +383,13 @@ impl Database {
         root_hash: Option<Vec<u8>>,
         embeds: Option<serde_json::Value>,
         mentions: Option<serde_json::Value>,
+        parent_fid: Option<i64>,
+        parent_url: Option<String>,
     ) -> Result<()> {
         sqlx::query(
             r"
-            INSERT INTO casts (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO casts (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions, parent_fid, parent_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             ON CONFLICT (message_hash) DO NOTHING
             "
         )
@@ -226,6 +401,8 @@ impl Database {
         .bind(root_hash)
         .bind(embeds)
         .bind(mentions)
+        .bind(parent_fid)
+        .bind(parent_url)
         .execute(&self.pool)
         .await?;
 
@@ -1,3 +1,5 @@
+use super::embedding_versions::active_column;
+use super::embedding_versions::check_active_dimension;
 use super::Database;
 use crate::models::UserData;
 use crate::models::UserDataQuery;
@@ -105,6 +107,8 @@ impl Database {
     }
 
     /// Update profile embeddings
+    ///
+    /// Written to the columns of the active embedding version.
     pub async fn update_profile_embeddings(
         &self,
         fid: i64,
@@ -112,17 +116,20 @@ impl Database {
         bio_embedding: Option<Vec<f32>>,
         interests_embedding: Option<Vec<f32>>,
     ) -> Result<()> {
+        let active = self.active_embedding_version().await?;
+
         // Build dynamic query based on which embeddings are provided
         let mut field_names = Vec::new();
 
-        if profile_embedding.is_some() {
-            field_names.push("profile_embedding");
-        }
-        if bio_embedding.is_some() {
-            field_names.push("bio_embedding");
-        }
-        if interests_embedding.is_some() {
-            field_names.push("interests_embedding");
+        for (base, embedding) in [
+            ("profile_embedding", &profile_embedding),
+            ("bio_embedding", &bio_embedding),
+            ("interests_embedding", &interests_embedding),
+        ] {
+            if let Some(embedding) = embedding {
+                check_active_dimension(active.as_ref(), embedding)?;
+                field_names.push(active_column(base, active.as_ref()));
+            }
         }
 
         if field_names.is_empty() {
@@ -170,23 +177,28 @@ impl Database {
         similarity_threshold: Option<f32>,
     ) -> Result<Vec<UserProfile>> {
         let threshold = similarity_threshold.unwrap_or(0.8);
+        let active = self.active_embedding_version().await?;
+        check_active_dimension(active.as_ref(), &query_embedding)?;
+        let column = active_column("profile_embedding", active.as_ref());
 
-        let profiles = sqlx::query_as(
+        let mut tx = self.begin_vector_search().await?;
+        let profiles = sqlx::query_as(&format!(
             r"
             SELECT p.*
             FROM user_profiles p
             JOIN profile_embeddings e ON p.fid = e.fid
-            WHERE e.profile_embedding IS NOT NULL
-                AND (e.profile_embedding <=> $1::vector) < $2
-            ORDER BY e.profile_embedding <=> $1::vector
+            WHERE e.{column} IS NOT NULL
+                AND (e.{column} <=> $1::vector) < $2
+            ORDER BY e.{column} <=> $1::vector
             LIMIT $3
-            ",
-        )
+            "
+        ))
         .bind(query_embedding)
         .bind(threshold)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         Ok(profiles)
     }
@@ -199,23 +211,28 @@ impl Database {
         similarity_threshold: Option<f32>,
     ) -> Result<Vec<UserProfile>> {
         let threshold = similarity_threshold.unwrap_or(0.8);
+        let active = self.active_embedding_version().await?;
+        check_active_dimension(active.as_ref(), &query_embedding)?;
+        let column = active_column("bio_embedding", active.as_ref());
 
-        let profiles = sqlx::query_as(
+        let mut tx = self.begin_vector_search().await?;
+        let profiles = sqlx::query_as(&format!(
             r"
             SELECT p.*
             FROM user_profiles p
             JOIN profile_embeddings e ON p.fid = e.fid
-            WHERE e.bio_embedding IS NOT NULL
-                AND (e.bio_embedding <=> $1::vector) < $2
-            ORDER BY e.bio_embedding <=> $1::vector
+            WHERE e.{column} IS NOT NULL
+                AND (e.{column} <=> $1::vector) < $2
+            ORDER BY e.{column} <=> $1::vector
             LIMIT $3
-            ",
-        )
+            "
+        ))
         .bind(query_embedding)
         .bind(threshold)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         Ok(profiles)
     }
@@ -273,24 +290,13 @@ impl Database {
                 self.semantic_search_profiles(embedding, limit, None).await
             }
             (None, Some(text)) => {
-                // Text search only
-                let query = UserProfileQuery {
-                    fid: None,
-                    username: None,
-                    display_name: None,
-                    bio: None,
-                    location: None,
-                    twitter_username: None,
-                    github_username: None,
-                    limit: Some(limit),
-                    offset: None,
-                    start_timestamp: None,
-                    end_timestamp: None,
-                    sort_by: None,
-                    sort_order: None,
-                    search_term: Some(text),
-                };
-                self.list_user_profiles(query).await
+                // Full-text search only
+                Ok(self
+                    .full_text_search_profiles(&text, limit)
+                    .await?
+                    .into_iter()
+                    .map(|ranked| ranked.profile)
+                    .collect())
             }
             (None, None) => {
                 // No search criteria
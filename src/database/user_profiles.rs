@@ -1,4 +1,5 @@
 use super::Database;
+use super::NOT_REMOVED;
 use crate::models::CreateUserProfileRequest;
 use crate::models::UpdateUserProfileRequest;
 use crate::models::UserDataType;
@@ -391,10 +392,16 @@ impl Database {
 
     /// Count casts for a specific FID
     pub async fn count_casts_by_fid(&self, fid: i64) -> Result<i64> {
-        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM casts WHERE fid = $1")
-            .bind(fid)
-            .fetch_one(&self.pool)
-            .await?;
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            r"
+            SELECT COUNT(*) FROM casts c
+            WHERE c.fid = $1
+            AND {NOT_REMOVED}
+            "
+        ))
+        .bind(fid)
+        .fetch_one(&self.pool)
+        .await?;
         Ok(count)
     }
 
@@ -1,4 +1,5 @@
 use super::Database;
+use crate::models::FnameTransfer;
 use crate::models::UsernameProof;
 use crate::models::UsernameType;
 use crate::Result;
@@ -69,4 +70,28 @@ impl Database {
 
         Ok(proofs)
     }
+
+    /// Get the transfer history of an fname, oldest first
+    pub async fn get_fname_transfers(&self, username: &str) -> Result<Vec<FnameTransfer>> {
+        let transfers = sqlx::query_as::<_, FnameTransfer>(
+            "SELECT * FROM fname_transfers WHERE username = $1 ORDER BY timestamp ASC, transfer_id ASC",
+        )
+        .bind(username)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transfers)
+    }
+
+    /// Get all fname transfers to or from a FID, newest first
+    pub async fn get_fid_fname_transfers(&self, fid: i64) -> Result<Vec<FnameTransfer>> {
+        let transfers = sqlx::query_as::<_, FnameTransfer>(
+            "SELECT * FROM fname_transfers WHERE from_fid = $1 OR to_fid = $1 ORDER BY timestamp DESC, transfer_id DESC",
+        )
+        .bind(fid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transfers)
+    }
 }
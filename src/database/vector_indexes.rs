@@ -0,0 +1,322 @@
+//! Vector similarity indexes and per-query search parameters
+//!
+//! Embedding columns are indexed with ivfflat or HNSW, as selected in
+//! `PerformanceConfig`. Vector searches run in a short transaction that sets
+//! `ivfflat.probes` and `hnsw.ef_search` for that query only.
+
+use sqlx::Postgres;
+use sqlx::Transaction;
+
+use super::embedding_versions::active_column;
+use super::embedding_versions::CAST_EMBEDDING_COLUMN;
+use super::Database;
+use crate::config::PerformanceConfig;
+use crate::config::VectorIndexType;
+use crate::Result;
+
+/// pgvector cannot build ivfflat/hnsw indexes on vectors with more dimensions
+pub const MAX_INDEXED_VECTOR_DIMENSION: usize = 2000;
+
+/// Per-query search parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorSearchParams {
+    pub ivfflat_probes: usize,
+    pub hnsw_ef_search: usize,
+}
+
+impl VectorSearchParams {
+    /// Defaults matching `PerformanceConfig`'s serde defaults
+    pub const DEFAULT: Self = Self {
+        ivfflat_probes: 10,
+        hnsw_ef_search: 40,
+    };
+
+    #[must_use]
+    pub const fn from_config(config: &PerformanceConfig) -> Self {
+        Self {
+            ivfflat_probes: config.ivfflat_probes,
+            hnsw_ef_search: config.hnsw_ef_search,
+        }
+    }
+}
+
+impl Default for VectorSearchParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Build parameters for vector indexes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorIndexSettings {
+    pub index_type: VectorIndexType,
+    pub lists: usize,
+    pub m: usize,
+    pub ef_construction: usize,
+}
+
+impl VectorIndexSettings {
+    #[must_use]
+    pub const fn from_config(config: &PerformanceConfig) -> Self {
+        Self {
+            index_type: config.vector_index_type,
+            lists: config.vector_index_lists,
+            m: config.hnsw_m,
+            ef_construction: config.hnsw_ef_construction,
+        }
+    }
+}
+
+/// A vector column that gets a similarity index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorIndexTarget {
+    pub table: &'static str,
+    pub column: String,
+}
+
+impl VectorIndexTarget {
+    /// Index name for `index_type` (ivfflat keeps the historical `_cosine` names)
+    #[must_use]
+    pub fn index_name(&self, index_type: VectorIndexType) -> String {
+        let suffix = match index_type {
+            VectorIndexType::Ivfflat => "cosine",
+            VectorIndexType::Hnsw => "hnsw",
+        };
+        format!("idx_{}_{}_{suffix}", self.table, self.column)
+    }
+
+    /// `CREATE INDEX CONCURRENTLY` statement for the configured index type
+    #[must_use]
+    pub fn create_index_sql(&self, settings: &VectorIndexSettings) -> String {
+        let options = match settings.index_type {
+            VectorIndexType::Ivfflat => format!("lists = {}", settings.lists),
+            VectorIndexType::Hnsw => format!(
+                "m = {}, ef_construction = {}",
+                settings.m, settings.ef_construction
+            ),
+        };
+        format!(
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS {} ON {} USING {} ({} vector_cosine_ops) WITH ({options})",
+            self.index_name(settings.index_type),
+            self.table,
+            settings.index_type,
+            self.column
+        )
+    }
+}
+
+/// Recall of approximate (index) search against exact kNN on sampled queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecallEstimate {
+    pub samples: usize,
+    pub k: usize,
+    /// Fraction of exact neighbours returned by the index search
+    pub recall: f64,
+}
+
+impl Database {
+    /// Use `params` for the `ivfflat.probes` / `hnsw.ef_search` of every vector search
+    #[must_use]
+    pub const fn with_vector_search(mut self, params: VectorSearchParams) -> Self {
+        self.vector_search = params;
+        self
+    }
+
+    /// Per-query vector search parameters
+    #[must_use]
+    pub const fn vector_search_params(&self) -> VectorSearchParams {
+        self.vector_search
+    }
+
+    /// Begin a transaction with the vector search parameters set for its queries
+    pub async fn begin_vector_search(&self) -> Result<Transaction<'static, Postgres>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "SELECT set_config('ivfflat.probes', $1, true), set_config('hnsw.ef_search', $2, true)",
+        )
+        .bind(self.vector_search.ivfflat_probes.to_string())
+        .bind(self.vector_search.hnsw_ef_search.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(tx)
+    }
+
+    /// Vector columns that search reads, in the active embedding version
+    pub async fn vector_index_targets(&self) -> Result<Vec<VectorIndexTarget>> {
+        let active = self.active_embedding_version().await?;
+
+        Ok(vec![
+            VectorIndexTarget {
+                table: "cast_embeddings",
+                column: active_column(CAST_EMBEDDING_COLUMN, active.as_ref()),
+            },
+            VectorIndexTarget {
+                table: "cast_embedding_chunks",
+                column: "embedding".to_string(),
+            },
+            VectorIndexTarget {
+                table: "cast_embedding_aggregated",
+                column: "embedding".to_string(),
+            },
+            VectorIndexTarget {
+                table: "profile_embeddings",
+                column: active_column("profile_embedding", active.as_ref()),
+            },
+            VectorIndexTarget {
+                table: "profile_embeddings",
+                column: active_column("bio_embedding", active.as_ref()),
+            },
+        ])
+    }
+
+    /// Estimate index recall@k by comparing index search with exact kNN
+    ///
+    /// Query vectors are sampled from the column itself. Returns `None` when
+    /// the column has no vectors to sample.
+    pub async fn estimate_vector_index_recall(
+        &self,
+        target: &VectorIndexTarget,
+        samples: usize,
+        k: usize,
+    ) -> Result<Option<RecallEstimate>> {
+        let rows: f32 = sqlx::query_scalar(
+            "SELECT GREATEST(reltuples, 1)::real FROM pg_class WHERE relname = $1",
+        )
+        .bind(target.table)
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(1.0);
+        let percent = sample_percent(samples, f64::from(rows));
+
+        let queries: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT {column}::text FROM {table} TABLESAMPLE BERNOULLI ({percent}) \
+             WHERE {column} IS NOT NULL LIMIT $1",
+            column = target.column,
+            table = target.table,
+        ))
+        .bind(i64::try_from(samples).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        if queries.is_empty() {
+            return Ok(None);
+        }
+
+        let mut expected = 0;
+        let mut found = 0;
+        for query in &queries {
+            let approximate = self.nearest_rows(target, query, k, true).await?;
+            let exact = self.nearest_rows(target, query, k, false).await?;
+            expected += exact.len();
+            found += exact.iter().filter(|row| approximate.contains(row)).count();
+        }
+
+        Ok(Some(RecallEstimate {
+            samples: queries.len(),
+            k,
+            recall: ratio(found, expected),
+        }))
+    }
+
+    /// Row ids of the `k` nearest neighbours, via the index or by exact scan
+    async fn nearest_rows(
+        &self,
+        target: &VectorIndexTarget,
+        query: &str,
+        k: usize,
+        use_index: bool,
+    ) -> Result<Vec<String>> {
+        let mut tx = self.begin_vector_search().await?;
+        if !use_index {
+            sqlx::query("SET LOCAL enable_indexscan = off")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("SET LOCAL enable_bitmapscan = off")
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let rows = sqlx::query_scalar::<_, String>(&format!(
+            "SELECT ctid::text FROM {table} WHERE {column} IS NOT NULL \
+             ORDER BY {column} <=> $1::vector LIMIT $2",
+            table = target.table,
+            column = target.column,
+        ))
+        .bind(query)
+        .bind(i64::try_from(k).unwrap_or(i64::MAX))
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(rows)
+    }
+}
+
+/// TABLESAMPLE percentage that should yield about `samples` rows (with headroom)
+fn sample_percent(samples: usize, rows: f64) -> f64 {
+    #[allow(clippy::cast_precision_loss)] // Sample counts are small
+    let wanted = samples as f64 * 20.0;
+    (wanted / rows.max(1.0) * 100.0).clamp(0.0001, 100.0)
+}
+
+#[allow(clippy::cast_precision_loss)] // Result counts are small
+fn ratio(found: usize, expected: usize) -> f64 {
+    if expected == 0 {
+        1.0
+    } else {
+        found as f64 / expected as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> VectorIndexTarget {
+        VectorIndexTarget {
+            table: "cast_embedding_chunks",
+            column: "embedding".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_index_names_keep_ivfflat_names() {
+        assert_eq!(
+            target().index_name(VectorIndexType::Ivfflat),
+            "idx_cast_embedding_chunks_embedding_cosine"
+        );
+        assert_eq!(
+            target().index_name(VectorIndexType::Hnsw),
+            "idx_cast_embedding_chunks_embedding_hnsw"
+        );
+    }
+
+    #[test]
+    fn test_create_index_sql() {
+        let mut settings = VectorIndexSettings {
+            index_type: VectorIndexType::Ivfflat,
+            lists: 200,
+            m: 24,
+            ef_construction: 100,
+        };
+        assert_eq!(
+            target().create_index_sql(&settings),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_cast_embedding_chunks_embedding_cosine \
+             ON cast_embedding_chunks USING ivfflat (embedding vector_cosine_ops) WITH (lists = 200)"
+        );
+
+        settings.index_type = VectorIndexType::Hnsw;
+        assert!(target().create_index_sql(&settings).ends_with(
+            "USING hnsw (embedding vector_cosine_ops) WITH (m = 24, ef_construction = 100)"
+        ));
+    }
+
+    #[test]
+    fn test_sampling_and_ratio() {
+        assert!((sample_percent(10, 100.0) - 100.0).abs() < f64::EPSILON);
+        assert!((sample_percent(10, 2_000_000.0) - 0.01).abs() < 1e-9);
+        assert!(sample_percent(10, 1e12) >= 0.0001);
+        assert!((ratio(0, 0) - 1.0).abs() < f64::EPSILON);
+        assert!((ratio(9, 10) - 0.9).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,483 @@
+//! Webhook subscriptions, recorded change events and the delivery queue
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use sqlx::PgConnection;
+
+use super::Database;
+use crate::webhooks::ChangeEvent;
+use crate::webhooks::ChangeEventType;
+use crate::webhooks::SubscriptionFilter;
+use crate::Result;
+
+/// Events scanned per query when replaying a subscription
+const REPLAY_PAGE_SIZE: i64 = 1000;
+
+/// A registered webhook endpoint
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    /// HMAC key of the delivery signatures
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub fid: Option<i64>,
+    pub target_fid: Option<i64>,
+    pub parent_hash: Option<Vec<u8>>,
+    pub channel: Option<String>,
+    pub active: bool,
+    /// Highest event offset delivered successfully
+    pub last_delivered_offset: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WebhookSubscription {
+    /// Filters of the subscription; event types were validated on creation
+    #[must_use]
+    pub fn filter(&self) -> SubscriptionFilter {
+        SubscriptionFilter {
+            event_types: self
+                .event_types
+                .iter()
+                .filter_map(|event_type| event_type.parse().ok())
+                .collect(),
+            fid: self.fid,
+            target_fid: self.target_fid,
+            parent_hash: self.parent_hash.clone(),
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+/// A change event with its offset
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WebhookEvent {
+    /// Offset of the event, increasing in recording order
+    pub id: i64,
+    #[sqlx(flatten)]
+    pub event: ChangeEvent,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A delivery claimed for sending
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingDelivery {
+    pub subscription_id: i64,
+    /// Previous failed attempts
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+    #[sqlx(flatten)]
+    pub event: WebhookEvent,
+}
+
+/// A delivery that exhausted its attempts
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WebhookDeadLetter {
+    pub subscription_id: i64,
+    pub event_id: i64,
+    pub attempts: i32,
+    pub last_error: String,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Database {
+    /// Record change events of committed sync batches for webhook delivery
+    #[must_use]
+    pub const fn with_change_capture(mut self, enabled: bool) -> Self {
+        self.change_capture = enabled;
+        self
+    }
+
+    /// Whether sync records change events for webhook subscriptions
+    #[must_use]
+    pub const fn change_capture(&self) -> bool {
+        self.change_capture
+    }
+
+    /// Register a webhook endpoint
+    pub async fn create_webhook_subscription(
+        &self,
+        url: &str,
+        secret: &str,
+        filter: &SubscriptionFilter,
+    ) -> Result<WebhookSubscription> {
+        let event_types: Vec<&str> = filter
+            .event_types
+            .iter()
+            .map(ChangeEventType::as_str)
+            .collect();
+
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(
+            r"
+            INSERT INTO webhook_subscriptions (url, secret, event_types, fid, target_fid, parent_hash, channel)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            ",
+        )
+        .bind(url)
+        .bind(secret)
+        .bind(&event_types)
+        .bind(filter.fid)
+        .bind(filter.target_fid)
+        .bind(&filter.parent_hash)
+        .bind(&filter.channel)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// All webhook subscriptions, oldest first
+    pub async fn list_webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>> {
+        let subscriptions = sqlx::query_as::<_, WebhookSubscription>(
+            "SELECT * FROM webhook_subscriptions ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    pub async fn get_webhook_subscription(&self, id: i64) -> Result<Option<WebhookSubscription>> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(
+            "SELECT * FROM webhook_subscriptions WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// Delete a subscription with its queued deliveries and dead letters
+    ///
+    /// Returns `false` if it did not exist.
+    pub async fn delete_webhook_subscription(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Claim up to `limit` due deliveries of active subscriptions
+    ///
+    /// Claimed deliveries are not due again until `lease` has passed, so
+    /// several dispatchers can share the queue.
+    pub async fn claim_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<PendingDelivery>> {
+        let deliveries = sqlx::query_as::<_, PendingDelivery>(
+            r"
+            WITH due AS (
+                SELECT d.subscription_id, d.event_id
+                FROM webhook_deliveries d
+                JOIN webhook_subscriptions s ON s.id = d.subscription_id
+                WHERE d.next_attempt_at <= NOW() AND s.active
+                ORDER BY d.event_id
+                LIMIT $1
+                FOR UPDATE OF d SKIP LOCKED
+            ),
+            claimed AS (
+                UPDATE webhook_deliveries d
+                SET next_attempt_at = NOW() + make_interval(secs => $2)
+                FROM due
+                WHERE d.subscription_id = due.subscription_id AND d.event_id = due.event_id
+                RETURNING d.subscription_id, d.event_id, d.attempts
+            )
+            SELECT c.subscription_id, c.attempts, s.url, s.secret, e.*
+            FROM claimed c
+            JOIN webhook_subscriptions s ON s.id = c.subscription_id
+            JOIN webhook_events e ON e.id = c.event_id
+            ORDER BY e.id
+            ",
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Remove a sent delivery from the queue and advance the subscription offset
+    pub async fn complete_webhook_delivery(
+        &self,
+        subscription_id: i64,
+        event_id: i64,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM webhook_deliveries WHERE subscription_id = $1 AND event_id = $2")
+            .bind(subscription_id)
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r"
+            UPDATE webhook_subscriptions
+            SET last_delivered_offset = GREATEST(last_delivered_offset, $2)
+            WHERE id = $1
+            ",
+        )
+        .bind(subscription_id)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record a failed attempt and schedule the next one after `delay`
+    pub async fn retry_webhook_delivery(
+        &self,
+        subscription_id: i64,
+        event_id: i64,
+        error: &str,
+        delay: Duration,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1,
+                last_error = $3,
+                next_attempt_at = NOW() + make_interval(secs => $4)
+            WHERE subscription_id = $1 AND event_id = $2
+            ",
+        )
+        .bind(subscription_id)
+        .bind(event_id)
+        .bind(error)
+        .bind(delay.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move a delivery that exhausted its attempts to `webhook_dead_letters`
+    pub async fn dead_letter_webhook_delivery(
+        &self,
+        subscription_id: i64,
+        event_id: i64,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            WITH failed AS (
+                DELETE FROM webhook_deliveries
+                WHERE subscription_id = $1 AND event_id = $2
+                RETURNING subscription_id, event_id, attempts + 1 AS attempts
+            )
+            INSERT INTO webhook_dead_letters (subscription_id, event_id, attempts, last_error)
+            SELECT subscription_id, event_id, attempts, $3 FROM failed
+            ON CONFLICT (subscription_id, event_id) DO UPDATE
+            SET attempts = EXCLUDED.attempts,
+                last_error = EXCLUDED.last_error,
+                failed_at = NOW()
+            ",
+        )
+        .bind(subscription_id)
+        .bind(event_id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Dead letters of a subscription, most recent first
+    pub async fn list_webhook_dead_letters(
+        &self,
+        subscription_id: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDeadLetter>> {
+        let dead_letters = sqlx::query_as::<_, WebhookDeadLetter>(
+            r"
+            SELECT * FROM webhook_dead_letters
+            WHERE subscription_id = $1
+            ORDER BY failed_at DESC, event_id DESC
+            LIMIT $2
+            ",
+        )
+        .bind(subscription_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(dead_letters)
+    }
+
+    /// Queue every recorded event from offset `from_offset` on that matches the
+    /// subscription, including delivered and dead-lettered ones
+    ///
+    /// Returns the number of queued deliveries.
+    pub async fn replay_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+        from_offset: i64,
+    ) -> Result<u64> {
+        let filter = subscription.filter();
+        let mut cursor = from_offset;
+        let mut queued = 0;
+
+        loop {
+            let events = sqlx::query_as::<_, WebhookEvent>(
+                "SELECT * FROM webhook_events WHERE id >= $1 ORDER BY id LIMIT $2",
+            )
+            .bind(cursor)
+            .bind(REPLAY_PAGE_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = events.last() else {
+                break;
+            };
+            cursor = last.id + 1;
+
+            let event_ids: Vec<i64> = events
+                .iter()
+                .filter(|event| filter.matches(&event.event))
+                .map(|event| event.id)
+                .collect();
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(
+                "DELETE FROM webhook_dead_letters WHERE subscription_id = $1 AND event_id = ANY($2)",
+            )
+            .bind(subscription.id)
+            .bind(&event_ids)
+            .execute(&mut *tx)
+            .await?;
+            let result = sqlx::query(
+                r"
+                INSERT INTO webhook_deliveries (subscription_id, event_id)
+                SELECT $1, event_id FROM UNNEST($2::bigint[]) AS event_id
+                ON CONFLICT (subscription_id, event_id) DO UPDATE
+                SET attempts = 0, last_error = NULL, next_attempt_at = NOW()
+                ",
+            )
+            .bind(subscription.id)
+            .bind(&event_ids)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            queued += result.rows_affected();
+        }
+
+        Ok(queued)
+    }
+}
+
+/// Change events written by [`record_change_events`]
+#[derive(Debug, Default)]
+pub struct RecordedChangeEvents {
+    /// Number of queued webhook deliveries
+    pub queued: usize,
+    /// Message hashes of the events that were not recorded before
+    pub inserted: HashSet<Vec<u8>>,
+}
+
+/// Store change events and queue the deliveries of those matching an active
+/// subscription
+///
+/// Runs in the transaction of the synced batch, so a committed message always
+/// has its deliveries queued. Only matching events are stored, unless
+/// `record_all` is set (the live feed needs to know which events are new).
+/// Events already recorded (same message hash) are skipped, so re-synced
+/// blocks are not delivered or published twice. Without `queue_deliveries`
+/// events are stored but not queued.
+pub async fn record_change_events(
+    conn: &mut PgConnection,
+    events: &[ChangeEvent],
+    queue_deliveries: bool,
+    record_all: bool,
+) -> Result<RecordedChangeEvents> {
+    let subscriptions: Vec<(i64, SubscriptionFilter)> = if queue_deliveries {
+        sqlx::query_as::<_, WebhookSubscription>("SELECT * FROM webhook_subscriptions WHERE active")
+            .fetch_all(&mut *conn)
+            .await?
+            .iter()
+            .map(|subscription| (subscription.id, subscription.filter()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut matched: HashMap<&[u8], Vec<i64>> = HashMap::new();
+    let mut recorded_events = Vec::new();
+    for event in events {
+        let subscribers: Vec<i64> = subscriptions
+            .iter()
+            .filter(|(_, filter)| filter.matches(event))
+            .map(|(id, _)| *id)
+            .collect();
+        if !subscribers.is_empty() {
+            matched.insert(&event.message_hash, subscribers);
+            recorded_events.push(event);
+        } else if record_all {
+            recorded_events.push(event);
+        }
+    }
+    if recorded_events.is_empty() {
+        return Ok(RecordedChangeEvents::default());
+    }
+
+    let recorded = sqlx::query_as::<_, (i64, Vec<u8>)>(
+        r"
+        INSERT INTO webhook_events
+            (event_type, fid, target_fid, message_hash, parent_hash, channel, timestamp, payload)
+        SELECT event_type, fid, target_fid, message_hash, parent_hash, channel, timestamp, payload::jsonb
+        FROM UNNEST($1::text[], $2::bigint[], $3::bigint[], $4::bytea[], $5::bytea[], $6::text[], $7::bigint[], $8::text[])
+            AS e(event_type, fid, target_fid, message_hash, parent_hash, channel, timestamp, payload)
+        ON CONFLICT (message_hash) DO NOTHING
+        RETURNING id, message_hash
+        ",
+    )
+    .bind(recorded_events.iter().map(|e| e.event_type.as_str()).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.fid).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.target_fid).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.message_hash.as_slice()).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.parent_hash.as_deref()).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.channel.as_deref()).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.timestamp).collect::<Vec<_>>())
+    .bind(recorded_events.iter().map(|e| e.payload.to_string()).collect::<Vec<_>>())
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let (subscription_ids, event_ids): (Vec<i64>, Vec<i64>) = recorded
+        .iter()
+        .flat_map(|(event_id, hash)| {
+            matched
+                .get(hash.as_slice())
+                .into_iter()
+                .flatten()
+                .map(move |subscription_id| (*subscription_id, *event_id))
+        })
+        .unzip();
+
+    sqlx::query(
+        r"
+        INSERT INTO webhook_deliveries (subscription_id, event_id)
+        SELECT * FROM UNNEST($1::bigint[], $2::bigint[])
+        ON CONFLICT DO NOTHING
+        ",
+    )
+    .bind(&subscription_ids)
+    .bind(&event_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(RecordedChangeEvents {
+        queued: subscription_ids.len(),
+        inserted: recorded.into_iter().map(|(_, hash)| hash).collect(),
+    })
+}
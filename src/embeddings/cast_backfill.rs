@@ -144,6 +144,7 @@ pub async fn backfill_cast_embeddings_multiprocess(
         gpu_devices,
         worker_startup_timeout_secs: 30,
         max_retries: 3,
+        embedding_dimension: config.map_or(384, crate::AppConfig::embedding_dimension),
     };
 
     info!(
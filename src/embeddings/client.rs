@@ -78,12 +78,15 @@ impl EmbeddingClient {
     }
 
     /// Create a new embedding client with async initialization for LocalGPU
+    ///
+    /// The local model must produce `dimension`-sized embeddings.
     #[cfg(feature = "local-gpu")]
     pub async fn new_async(
         provider: EmbeddingProvider,
         model: String,
         endpoint: String,
         api_key: Option<String>,
+        dimension: usize,
         gpu_device_id: Option<usize>,
     ) -> Result<Self> {
         let client = Client::builder()
@@ -98,7 +101,7 @@ impl EmbeddingClient {
             Some(
                 crate::embeddings::local_gpu::LocalGPUClient::new_with_dimension(
                     &model,
-                    384,
+                    dimension,
                     gpu_device_id,
                 )
                 .await?,
@@ -10,6 +10,7 @@ use super::client::EmbeddingProvider;
 use super::EmbeddingConfig;
 use super::MAX_BATCH_SIZE;
 use crate::errors::Result;
+use crate::errors::SnapragError;
 
 /// Service for generating embeddings with caching and optimization
 pub struct EmbeddingService {
@@ -70,6 +71,7 @@ impl EmbeddingService {
                 config.model.clone(),
                 config.endpoint.clone(),
                 config.api_key.clone(),
+                config.dimension,
                 gpu_device_id,
             )
             .await?
@@ -98,7 +100,9 @@ impl EmbeddingService {
         // Preprocess text to handle newlines and invalid characters
         let processed_text = crate::embeddings::preprocess_text_for_embedding(text)?;
 
-        self.client.generate(&processed_text).await
+        let embedding = self.client.generate(&processed_text).await?;
+        self.check_dimension(&embedding)?;
+        Ok(embedding)
     }
 
     /// Generate embeddings for multiple texts in batch
@@ -152,6 +156,10 @@ impl EmbeddingService {
             all_embeddings
         };
 
+        for embedding in &embeddings {
+            self.check_dimension(embedding)?;
+        }
+
         // Insert zero vectors for empty texts at correct positions
         let zero_vector = vec![0.0; self.config.dimension];
         for pos in empty_positions.iter().rev() {
@@ -247,6 +255,26 @@ impl EmbeddingService {
         self.config.dimension
     }
 
+    /// Reject embeddings that would not fit the configured vector columns
+    fn check_dimension(&self, embedding: &[f32]) -> Result<()> {
+        if embedding.len() == self.config.dimension {
+            Ok(())
+        } else {
+            Err(SnapragError::EmbeddingError(format!(
+                "Model {} returned {} dimensions, but embeddings.dimension is {}",
+                self.config.model,
+                embedding.len(),
+                self.config.dimension
+            )))
+        }
+    }
+
+    /// Get the embedding configuration
+    #[must_use]
+    pub const fn config(&self) -> &EmbeddingConfig {
+        &self.config
+    }
+
     /// Get the model name
     #[must_use]
     pub fn model(&self) -> &str {
@@ -63,7 +63,7 @@ impl LocalGPUClient {
     }
 
     /// Create a new local GPU client with specified embedding dimension and GPU device
-    /// BGE small model supports 384 dimensions
+    /// The dimension must match the model's hidden size (384 for BGE small)
     pub async fn new_with_dimension(
         model_name: &str,
         embedding_dim: usize,
@@ -74,14 +74,6 @@ impl LocalGPUClient {
             model_name, embedding_dim
         );
 
-        // Validate embedding dimension for BGE small model
-        if embedding_dim != 384 {
-            return Err(SnapragError::EmbeddingError(format!(
-                "BGE small model only supports 384 dimensions, got: {}",
-                embedding_dim
-            )));
-        }
-
         // Determine device (CUDA > Metal > CPU) with optional GPU selection
         let device = Self::get_device(gpu_device_id)?;
         info!("Using device: {:?}", device);
@@ -93,7 +85,7 @@ impl LocalGPUClient {
         let tokenizer = Self::load_tokenizer(&model_path)?;
 
         // Load BERT model
-        let model = Self::load_bert_model(&model_path, &device)?;
+        let model = Self::load_bert_model(&model_path, &device, embedding_dim)?;
 
         Ok(Self {
             tokenizer,
@@ -105,7 +97,7 @@ impl LocalGPUClient {
     }
 
     /// Get device with optional GPU selection
-    fn get_device(gpu_device_id: Option<usize>) -> Result<Device> {
+    pub(crate) fn get_device(gpu_device_id: Option<usize>) -> Result<Device> {
         match gpu_device_id {
             Some(device_id) => {
                 // User specified a GPU device ID
@@ -170,7 +162,7 @@ impl LocalGPUClient {
     }
 
     /// Download model from HuggingFace if not already cached
-    async fn download_model(model_name: &str) -> Result<PathBuf> {
+    pub(crate) async fn download_model(model_name: &str) -> Result<PathBuf> {
         info!("Initializing HuggingFace API...");
 
         let api = Api::new().map_err(|e| {
@@ -334,7 +326,7 @@ impl LocalGPUClient {
     }
 
     /// Load tokenizer from model path
-    fn load_tokenizer(model_path: &PathBuf) -> Result<Tokenizer> {
+    pub(crate) fn load_tokenizer(model_path: &PathBuf) -> Result<Tokenizer> {
         let tokenizer_files = ["tokenizer.json", "tokenizer_config.json", "vocab.txt"];
 
         for tokenizer_file in &tokenizer_files {
@@ -357,7 +349,11 @@ impl LocalGPUClient {
     }
 
     /// Load BERT model from downloaded files
-    fn load_bert_model(model_path: &PathBuf, device: &Device) -> Result<BertModel> {
+    fn load_bert_model(
+        model_path: &PathBuf,
+        device: &Device,
+        embedding_dim: usize,
+    ) -> Result<BertModel> {
         info!("Loading BERT model from: {:?}", model_path);
 
         // Load config
@@ -368,6 +364,14 @@ impl LocalGPUClient {
         let config: BertConfig = serde_json::from_str(&config_content)
             .map_err(|e| SnapragError::EmbeddingError(format!("Failed to parse config: {}", e)))?;
 
+        // The model's hidden size is the embedding dimension it produces
+        if config.hidden_size != embedding_dim {
+            return Err(SnapragError::EmbeddingError(format!(
+                "Model produces {} dimension embeddings, but {} were configured",
+                config.hidden_size, embedding_dim
+            )));
+        }
+
         // Load model weights
         let model_file = model_path.join("model.safetensors");
         let vb =
@@ -1,17 +1,32 @@
-//! Migration utilities for converting existing embeddings to multi-vector format
+//! Migration utilities for embeddings
 //!
-//! This module provides tools to migrate existing single-vector embeddings
-//! to the new multi-vector format while maintaining backward compatibility.
+//! This module provides tools to:
+//! - migrate existing single-vector embeddings to the multi-vector format
+//!   while maintaining backward compatibility
+//! - move to a new embedding model without downtime: the new model is
+//!   registered as a shadow version (see `Database::add_shadow_embedding_version`),
+//!   backfilled into side-by-side columns with `backfill_shadow_embeddings`
+//!   while search keeps using the active model, then switched over atomically
+//!   with `Database::cutover_embedding_version`
+//!
+//! Queries must be embedded by the model that produced the searched vectors;
+//! `ActiveEmbeddingService` follows the active version across a cutover.
 
 #![allow(clippy::cast_possible_truncation)] // Batch sizes are reasonable and won't exceed limits
 
+use std::sync::Arc;
+use std::sync::Mutex;
+
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
 use crate::database::Database;
+use crate::database::EmbeddingVersion;
 use crate::embeddings::AggregationStrategy;
 use crate::embeddings::ChunkStrategy;
+use crate::embeddings::EmbeddingConfig;
+use crate::embeddings::EmbeddingService;
 use crate::embeddings::MultiVectorEmbeddingService;
 use crate::errors::Result;
 use crate::errors::SnapRagError;
@@ -278,6 +293,249 @@ pub struct MigrationAnalysis {
     pub estimated_migration_time_minutes: f64,
 }
 
+/// Embeds queries with the model of the active embedding version
+///
+/// Uses the configured service while it matches the active version (or no
+/// version is registered), and builds a service for the active model with the
+/// same provider and endpoint once a cutover switches to another model.
+pub struct ActiveEmbeddingService {
+    database: Arc<Database>,
+    configured: Arc<EmbeddingService>,
+    switched: Mutex<Option<(i32, Arc<EmbeddingService>)>>,
+}
+
+impl ActiveEmbeddingService {
+    #[must_use]
+    pub const fn new(database: Arc<Database>, configured: Arc<EmbeddingService>) -> Self {
+        Self {
+            database,
+            configured,
+            switched: Mutex::new(None),
+        }
+    }
+
+    /// Service for the currently active model
+    pub async fn service(&self) -> Result<Arc<EmbeddingService>> {
+        let Some(active) = self.database.active_embedding_version().await? else {
+            return Ok(self.configured.clone());
+        };
+        if serves_version(&self.configured, &active) {
+            return Ok(self.configured.clone());
+        }
+
+        if let Some((version, service)) = self
+            .switched
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .as_ref()
+        {
+            if *version == active.version {
+                return Ok(service.clone());
+            }
+        }
+
+        info!(
+            "🔀 Embedding queries with active model {} (version {})",
+            active.model, active.version
+        );
+        let service = Arc::new(embedding_service_for_version(
+            self.configured.config(),
+            &active,
+        )?);
+        *self
+            .switched
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) =
+            Some((active.version, service.clone()));
+        Ok(service)
+    }
+
+    /// Generate a query embedding with the active model
+    pub async fn generate(&self, text: &str) -> Result<Vec<f32>> {
+        self.service().await?.generate(text).await
+    }
+}
+
+/// Whether `service` produces vectors of `version`
+fn serves_version(service: &EmbeddingService, version: &EmbeddingVersion) -> bool {
+    service.model() == version.model && service.dimension() == version.dimension()
+}
+
+/// Build a service for `version`, reusing the provider and endpoint of `base`
+pub fn embedding_service_for_version(
+    base: &EmbeddingConfig,
+    version: &EmbeddingVersion,
+) -> Result<EmbeddingService> {
+    EmbeddingService::from_config(EmbeddingConfig {
+        model: version.model.clone(),
+        dimension: version.dimension(),
+        ..base.clone()
+    })
+}
+
+/// Shadow backfill statistics
+#[derive(Debug, Clone, Default)]
+pub struct ShadowBackfillStats {
+    pub casts_embedded: usize,
+    pub casts_failed: usize,
+    pub profiles_embedded: usize,
+    pub profiles_failed: usize,
+}
+
+/// Embed existing casts and profiles with the shadow version's model
+///
+/// Only rows still missing a shadow vector are processed, so an interrupted
+/// backfill can simply be run again. Search keeps using the active version
+/// throughout. `limit` caps the number of casts and profiles processed.
+pub async fn backfill_shadow_embeddings(
+    database: &Database,
+    embedding_service: &EmbeddingService,
+    version: &EmbeddingVersion,
+    batch_size: usize,
+    limit: Option<usize>,
+) -> Result<ShadowBackfillStats> {
+    if !serves_version(embedding_service, version) {
+        return Err(SnapRagError::Custom(format!(
+            "Embedding service uses {} ({} dims), but version {} is {} ({} dims)",
+            embedding_service.model(),
+            embedding_service.dimension(),
+            version.version,
+            version.model,
+            version.dimension
+        )));
+    }
+
+    info!(
+        "Backfilling embedding version {} ({}) into shadow columns",
+        version.version, version.model
+    );
+    let mut stats = ShadowBackfillStats::default();
+    let limit = limit.unwrap_or(usize::MAX);
+    let batch_size = batch_size.max(1);
+
+    // Casts: re-embed the text stored alongside the active vector
+    let mut after: Option<Vec<u8>> = None;
+    while stats.casts_embedded + stats.casts_failed < limit {
+        let remaining = limit - stats.casts_embedded - stats.casts_failed;
+        let rows = database
+            .get_casts_for_embedding_backfill(version, after.as_deref(), batch_size.min(remaining))
+            .await?;
+        let Some((last_hash, _)) = rows.last() else {
+            break;
+        };
+        after = Some(last_hash.clone());
+
+        let texts: Vec<&str> = rows.iter().map(|(_, text)| text.as_str()).collect();
+        let embeddings = match embedding_service.generate_batch(texts).await {
+            Ok(embeddings) => embeddings.into_iter().map(Ok).collect(),
+            Err(e) => {
+                warn!("Batch embedding failed, retrying casts one by one: {}", e);
+                let mut embeddings = Vec::with_capacity(rows.len());
+                for (_, text) in &rows {
+                    embeddings.push(embedding_service.generate(text).await);
+                }
+                embeddings
+            }
+        };
+
+        for ((message_hash, _), embedding) in rows.iter().zip(embeddings) {
+            let stored = match embedding {
+                Ok(embedding) => {
+                    database
+                        .store_versioned_cast_embedding(version, message_hash, &embedding)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match stored {
+                Ok(()) => stats.casts_embedded += 1,
+                Err(e) => {
+                    stats.casts_failed += 1;
+                    error!(
+                        "Failed to embed cast {} with version {}: {}",
+                        hex::encode(message_hash),
+                        version.version,
+                        e
+                    );
+                }
+            }
+        }
+        info!(
+            "Shadow backfill: {} casts embedded, {} failed",
+            stats.casts_embedded, stats.casts_failed
+        );
+    }
+
+    // Profiles: regenerate all three vectors from the current profile
+    let mut after_fid = 0;
+    while stats.profiles_embedded + stats.profiles_failed < limit {
+        let remaining = limit - stats.profiles_embedded - stats.profiles_failed;
+        let profiles = database
+            .get_profiles_for_embedding_backfill(version, after_fid, batch_size.min(remaining))
+            .await?;
+        let Some(last) = profiles.last() else {
+            break;
+        };
+        after_fid = last.fid;
+
+        for profile in &profiles {
+            match backfill_shadow_profile(database, embedding_service, version, profile).await {
+                Ok(()) => stats.profiles_embedded += 1,
+                Err(e) => {
+                    stats.profiles_failed += 1;
+                    error!(
+                        "Failed to embed profile {} with version {}: {}",
+                        profile.fid, version.version, e
+                    );
+                }
+            }
+        }
+        info!(
+            "Shadow backfill: {} profiles embedded, {} failed",
+            stats.profiles_embedded, stats.profiles_failed
+        );
+    }
+
+    info!("Shadow backfill completed: {:?}", stats);
+    Ok(stats)
+}
+
+async fn backfill_shadow_profile(
+    database: &Database,
+    embedding_service: &EmbeddingService,
+    version: &EmbeddingVersion,
+    profile: &crate::models::UserProfile,
+) -> Result<()> {
+    let profile_embedding = embedding_service
+        .generate_profile_embedding(
+            profile.username.as_deref(),
+            profile.display_name.as_deref(),
+            profile.bio.as_deref(),
+            profile.location.as_deref(),
+        )
+        .await?;
+    let bio_embedding = embedding_service
+        .generate_bio_embedding(profile.bio.as_deref())
+        .await?;
+    let interests_embedding = embedding_service
+        .generate_interests_embedding(
+            profile.bio.as_deref(),
+            profile.twitter_username.as_deref(),
+            profile.github_username.as_deref(),
+        )
+        .await?;
+
+    database
+        .store_versioned_profile_embeddings(
+            version,
+            profile.fid,
+            &profile_embedding,
+            &bio_embedding,
+            &interests_embedding,
+        )
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
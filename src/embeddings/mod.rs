@@ -137,10 +137,14 @@ pub use client::EmbeddingClient;
 pub use client::EmbeddingProvider;
 pub use generator::EmbeddingService;
 pub use migration::analyze_existing_embeddings;
+pub use migration::backfill_shadow_embeddings;
+pub use migration::embedding_service_for_version;
 pub use migration::migrate_existing_embeddings;
+pub use migration::ActiveEmbeddingService;
 pub use migration::MigrationAnalysis;
 pub use migration::MigrationOptions;
 pub use migration::MigrationStats;
+pub use migration::ShadowBackfillStats;
 pub use multi_vector::AggregationStrategy;
 pub use multi_vector::ChunkMetadata;
 pub use multi_vector::ChunkStrategy;
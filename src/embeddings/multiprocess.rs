@@ -41,6 +41,14 @@ pub struct MultiProcessConfig {
     pub worker_startup_timeout_secs: u64,
     /// Maximum retries for failed embeddings
     pub max_retries: usize,
+    /// Embedding dimension the workers must produce
+    #[serde(default = "default_embedding_dimension")]
+    pub embedding_dimension: usize,
+}
+
+/// BGE small, the default local model, produces 384-dimension embeddings
+const fn default_embedding_dimension() -> usize {
+    384
 }
 
 impl Default for MultiProcessConfig {
@@ -51,6 +59,7 @@ impl Default for MultiProcessConfig {
             gpu_devices: vec![0], // Default to single GPU
             worker_startup_timeout_secs: 30,
             max_retries: 3,
+            embedding_dimension: default_embedding_dimension(),
         }
     }
 }
@@ -156,6 +165,10 @@ impl MultiProcessEmbeddingGenerator {
                     "GPU_DEVICE_ID",
                     gpu_device.map(|d| d.to_string()).unwrap_or_default(),
                 )
+                .env(
+                    "EMBEDDING_DIMENSION",
+                    self.config.embedding_dimension.to_string(),
+                )
                 .env("RUST_LOG", "info")
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
@@ -280,6 +293,7 @@ impl MultiProcessEmbeddingGenerator {
             let worker_id = batch_id % self.config.worker_processes;
             let worker_tx = self.worker_channels[worker_id].clone();
             let db_clone = Arc::clone(&db);
+            let embedding_dimension = self.config.embedding_dimension;
             let gpu_device = self
                 .config
                 .gpu_devices
@@ -320,7 +334,7 @@ impl MultiProcessEmbeddingGenerator {
                     if let Some(text) = &cast.text {
                         if !text.trim().is_empty() {
                             // Simulate embedding generation (in real implementation, this would come from worker)
-                            let embedding = vec![0.0; 384]; // Placeholder
+                            let embedding = vec![0.0; embedding_dimension]; // Placeholder
 
                             match db_clone
                                 .store_cast_embedding(
@@ -424,6 +438,11 @@ pub async fn worker_main() -> Result<()> {
         .ok()
         .and_then(|s| s.parse::<usize>().ok());
 
+    let embedding_dimension = std::env::var("EMBEDDING_DIMENSION")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or_else(default_embedding_dimension);
+
     info!(
         "Starting embedding worker {} with GPU device {:?}",
         worker_id, gpu_device_id
@@ -432,7 +451,7 @@ pub async fn worker_main() -> Result<()> {
     // Initialize local GPU client
     let client = crate::embeddings::local_gpu::LocalGPUClient::new_with_dimension(
         "BAAI/bge-small-en-v1.5",
-        384,
+        embedding_dimension,
         gpu_device_id,
     )
     .await?;
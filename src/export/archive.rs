@@ -0,0 +1,401 @@
+//! Archives of a whole instance, restored with `snaprag import`
+//!
+//! An archive is a directory with one NDJSON file per table and a
+//! `manifest.json`. Every table is read from the same snapshot. The manifest
+//! is written last: a directory without one is an incomplete archive.
+//!
+//! An archive holds the messages and embeddings that queries read, not every
+//! table sync writes (on-chain events, signers, verifications, ...). It
+//! carries no `sync_progress` or `processed_blocks`, so sync run after an
+//! import starts over and fills in the rest, skipping the messages that are
+//! already stored.
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::format::parse_json_row;
+use super::ExportFormat;
+use super::ExportQuery;
+use super::ExportTable;
+use super::ExportWriter;
+use crate::database::Database;
+use crate::database::ExportSnapshot;
+use crate::database::CAST_EMBEDDING_COLUMN;
+use crate::AppConfig;
+use crate::Result;
+
+/// Layout version of the archive directory
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Manifest file name inside an archive
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Archived tables in import order
+pub const ARCHIVE_TABLES: [ExportTable; 6] = [
+    ExportTable::ProfileChanges,
+    ExportTable::Casts,
+    ExportTable::Links,
+    ExportTable::Reactions,
+    ExportTable::CastEmbeddings,
+    ExportTable::ProfileEmbeddings,
+];
+
+/// One table file of an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveTable {
+    pub table: ExportTable,
+    /// File name relative to the archive directory
+    pub file: String,
+    pub rows: u64,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// Identifies the archive in `import_progress`
+    pub archive_id: Uuid,
+    /// Highest migration applied to the exported database
+    pub schema_version: i64,
+    /// Dimension of the archived vectors
+    pub embedding_dimension: usize,
+    /// Model that produced the archived vectors
+    pub embedding_model: String,
+    pub created_at: DateTime<Utc>,
+    pub tables: Vec<ArchiveTable>,
+}
+
+impl ArchiveManifest {
+    /// Read and validate the manifest of the archive in `dir`
+    ///
+    /// # Errors
+    /// Returns an error if the manifest is missing or unreadable, was written
+    /// by another archive format or does not list every archived table
+    pub fn read(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let manifest: Self = serde_json::from_slice(&std::fs::read(&path).map_err(|e| {
+            crate::SnapRagError::Custom(format!(
+                "Cannot read {}: {e} (an archive without a manifest is incomplete)",
+                path.display()
+            ))
+        })?)?;
+
+        if manifest.format_version != ARCHIVE_FORMAT_VERSION {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Archive format {} is not supported (expected {ARCHIVE_FORMAT_VERSION})",
+                manifest.format_version
+            )));
+        }
+        if let Some(missing) = ARCHIVE_TABLES
+            .iter()
+            .find(|table| manifest.table(**table).is_none())
+        {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Archive manifest does not list {}",
+                missing.name()
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Write the manifest, replacing it atomically
+    fn write(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        let tmp = dir.join(format!("{MANIFEST_FILE}.tmp"));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// File entry of an archived table
+    #[must_use]
+    pub fn table(&self, table: ExportTable) -> Option<&ArchiveTable> {
+        self.tables.iter().find(|entry| entry.table == table)
+    }
+}
+
+/// Model and dimension of the stored vectors
+///
+/// The active embedding version when one is registered, otherwise the
+/// original vector columns and the configured model.
+async fn stored_embedding_model(
+    database: &Database,
+    config: &AppConfig,
+) -> Result<(String, usize)> {
+    if let Some(active) = database.active_embedding_version().await? {
+        let dimension = active.dimension();
+        return Ok((active.model, dimension));
+    }
+
+    let dimension = database
+        .embedding_column_dimensions()
+        .await?
+        .iter()
+        .find(|column| column.table == "cast_embeddings" && column.column == CAST_EMBEDDING_COLUMN)
+        .and_then(|column| usize::try_from(column.dimension).ok())
+        .unwrap_or_else(|| config.embedding_dimension());
+    Ok((config.embedding_model().to_string(), dimension))
+}
+
+/// Export every archived table from one snapshot into `dir`
+///
+/// # Errors
+/// Returns an error if `dir` already holds an archive, the database has no
+/// applied migrations, or a table cannot be exported
+pub async fn write_archive(
+    database: &Database,
+    config: &AppConfig,
+    dir: &Path,
+    fetch_size: usize,
+) -> Result<ArchiveManifest> {
+    if dir.join(MANIFEST_FILE).exists() {
+        return Err(crate::SnapRagError::Custom(format!(
+            "{} already contains an archive",
+            dir.display()
+        )));
+    }
+    let schema_version = database.schema_version().await?.ok_or_else(|| {
+        crate::SnapRagError::Custom("The database has no applied migrations".to_string())
+    })?;
+    let (embedding_model, embedding_dimension) = stored_embedding_model(database, config).await?;
+    std::fs::create_dir_all(dir)?;
+
+    let mut snapshot = database.begin_export_snapshot().await?;
+    let mut tables = Vec::with_capacity(ARCHIVE_TABLES.len());
+    for table in ARCHIVE_TABLES {
+        let file = format!("{}.{}", table.name(), ExportFormat::Ndjson.extension());
+        let rows = write_table(&mut snapshot, table, &dir.join(&file), fetch_size).await?;
+        info!("📦 Archived {} {} rows", rows, table.name());
+        tables.push(ArchiveTable { table, file, rows });
+    }
+    snapshot.finish().await?;
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        archive_id: Uuid::new_v4(),
+        schema_version,
+        embedding_dimension,
+        embedding_model,
+        created_at: Utc::now(),
+        tables,
+    };
+    manifest.write(dir)?;
+    Ok(manifest)
+}
+
+async fn write_table(
+    snapshot: &mut ExportSnapshot,
+    table: ExportTable,
+    path: &Path,
+    fetch_size: usize,
+) -> Result<u64> {
+    let tmp = path.with_extension("ndjson.tmp");
+    let mut writer = ExportWriter::new(
+        ExportFormat::Ndjson,
+        table.columns(),
+        BufWriter::new(File::create(&tmp)?),
+    )?;
+
+    let mut cursor = snapshot
+        .cursor(&ExportQuery::new(table), fetch_size)
+        .await?;
+    while let Some(rows) = cursor.next_batch().await? {
+        writer.write_rows(&rows)?;
+    }
+    cursor.close().await?;
+
+    let rows = writer.rows();
+    writer.finish()?.flush()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(rows)
+}
+
+/// Check that an archive fits the database it is imported into
+async fn check_compatible(database: &Database, manifest: &ArchiveManifest) -> Result<()> {
+    let schema_version = database.schema_version().await?;
+    if schema_version != Some(manifest.schema_version) {
+        return Err(crate::SnapRagError::Custom(format!(
+            "Archive schema version {} does not match the database ({}); \
+             run `snaprag migrate up` with a matching release first",
+            manifest.schema_version,
+            schema_version.map_or_else(|| "no migrations".to_string(), |v| v.to_string())
+        )));
+    }
+
+    database
+        .verify_embedding_dimension(manifest.embedding_dimension)
+        .await?;
+    if let Some(active) = database.active_embedding_version().await? {
+        if active.model != manifest.embedding_model {
+            warn!(
+                "Archive vectors come from {}, the active embedding model is {}",
+                manifest.embedding_model, active.model
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Load the archive in `dir`, `batch_size` rows per transaction
+///
+/// A fresh import needs empty tables. An import of the same archive that
+/// was interrupted resumes after its last committed batch.
+///
+/// # Errors
+/// Returns an error if the archive does not fit the database, the tables
+/// are not empty, or a file is malformed or disagrees with the manifest
+pub async fn import_archive(
+    database: &Database,
+    dir: &Path,
+    batch_size: usize,
+) -> Result<ArchiveManifest> {
+    let manifest = ArchiveManifest::read(dir)?;
+    check_compatible(database, &manifest).await?;
+
+    let progress = database.import_progress(manifest.archive_id).await?;
+    if progress.is_empty() {
+        let non_empty = database.non_empty_import_tables(&ARCHIVE_TABLES).await?;
+        if !non_empty.is_empty() {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Import needs an empty database, but {} already hold rows",
+                non_empty.join(", ")
+            )));
+        }
+    } else {
+        info!("🔁 Resuming import of archive {}", manifest.archive_id);
+    }
+
+    for table in ARCHIVE_TABLES {
+        let entry = manifest.table(table).ok_or_else(|| {
+            crate::SnapRagError::Custom(format!("Archive manifest does not list {}", table.name()))
+        })?;
+        let loaded = progress.iter().find(|p| p.table_name == table.name());
+        if loaded.is_some_and(|p| p.completed) {
+            info!("⏭️  {} already imported", table.name());
+            continue;
+        }
+        let skip = loaded
+            .and_then(|p| u64::try_from(p.rows_loaded).ok())
+            .unwrap_or(0);
+
+        import_table(database, &manifest, entry, dir, skip, batch_size.max(1)).await?;
+    }
+    Ok(manifest)
+}
+
+async fn import_table(
+    database: &Database,
+    manifest: &ArchiveManifest,
+    entry: &ArchiveTable,
+    dir: &Path,
+    skip: u64,
+    batch_size: usize,
+) -> Result<()> {
+    let table = entry.table;
+    let reader = BufReader::new(File::open(dir.join(&entry.file))?);
+    let mut line_number = 0u64;
+    let mut loaded = skip;
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for line in reader.lines() {
+        let line = line?;
+        line_number += 1;
+        if line_number <= skip {
+            continue;
+        }
+        batch.push(parse_json_row(table.columns(), &line).map_err(|e| {
+            crate::SnapRagError::Custom(format!("{}:{line_number}: {e}", entry.file))
+        })?);
+
+        if batch.len() >= batch_size {
+            loaded += batch.len() as u64;
+            database
+                .import_batch(manifest.archive_id, table, &batch, loaded)
+                .await?;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        loaded += batch.len() as u64;
+        database
+            .import_batch(manifest.archive_id, table, &batch, loaded)
+            .await?;
+    }
+
+    if line_number != entry.rows {
+        return Err(crate::SnapRagError::Custom(format!(
+            "{} has {line_number} rows, but the manifest lists {}",
+            entry.file, entry.rows
+        )));
+    }
+    database
+        .complete_import_table(manifest.archive_id, table, loaded)
+        .await?;
+    info!("📥 Imported {} {} rows", loaded, table.name());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> ArchiveManifest {
+        ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            archive_id: Uuid::new_v4(),
+            schema_version: 6,
+            embedding_dimension: 768,
+            embedding_model: "nomic-embed-text".to_string(),
+            created_at: Utc::now(),
+            tables: ARCHIVE_TABLES
+                .iter()
+                .map(|table| ArchiveTable {
+                    table: *table,
+                    file: format!("{}.ndjson", table.name()),
+                    rows: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = manifest();
+        manifest.write(dir.path()).unwrap();
+        assert_eq!(ArchiveManifest::read(dir.path()).unwrap(), manifest);
+        assert_eq!(
+            manifest.table(ExportTable::Casts).unwrap().file,
+            "casts.ndjson"
+        );
+        assert!(manifest.table(ExportTable::SyncProgress).is_none());
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ArchiveManifest::read(dir.path()).is_err());
+
+        let mut manifest = manifest();
+        manifest.format_version = ARCHIVE_FORMAT_VERSION + 1;
+        manifest.write(dir.path()).unwrap();
+        assert!(ArchiveManifest::read(dir.path()).is_err());
+
+        manifest.format_version = ARCHIVE_FORMAT_VERSION;
+        manifest.tables.pop();
+        manifest.write(dir.path()).unwrap();
+        assert!(ArchiveManifest::read(dir.path()).is_err());
+    }
+}
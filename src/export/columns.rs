@@ -0,0 +1,125 @@
+//! Typed columns and cell values shared by every exporter
+
+/// Type of an exported column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int64,
+    Text,
+    /// Raw bytes; hex strings in CSV and NDJSON
+    Bytes,
+    /// Nested JSON; JSON text in CSV and Parquet
+    Json,
+    /// An embedding vector; a list of `float32` in Parquet
+    Floats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+}
+
+#[must_use]
+pub const fn int(name: &'static str) -> Column {
+    Column {
+        name,
+        column_type: ColumnType::Int64,
+    }
+}
+
+#[must_use]
+pub const fn text(name: &'static str) -> Column {
+    Column {
+        name,
+        column_type: ColumnType::Text,
+    }
+}
+
+#[must_use]
+pub const fn bytes(name: &'static str) -> Column {
+    Column {
+        name,
+        column_type: ColumnType::Bytes,
+    }
+}
+
+#[must_use]
+pub const fn json(name: &'static str) -> Column {
+    Column {
+        name,
+        column_type: ColumnType::Json,
+    }
+}
+
+#[must_use]
+pub const fn floats(name: &'static str) -> Column {
+    Column {
+        name,
+        column_type: ColumnType::Floats,
+    }
+}
+
+/// Value of one column of a row
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Null,
+    Int(i64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    Floats(Vec<f32>),
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Self::Int(i64::from(value))
+    }
+}
+
+impl From<i16> for Cell {
+    fn from(value: i16) -> Self {
+        Self::Int(i64::from(value))
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for Cell {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl From<serde_json::Value> for Cell {
+    fn from(value: serde_json::Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<Vec<f32>> for Cell {
+    fn from(value: Vec<f32>) -> Self {
+        Self::Floats(value)
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
@@ -0,0 +1,451 @@
+//! Row encoders for CSV, NDJSON and Parquet
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::types::Float32Type;
+use arrow_array::ArrayRef;
+use arrow_array::BinaryArray;
+use arrow_array::Int64Array;
+use arrow_array::ListArray;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use arrow_schema::SchemaRef;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::columns::Cell;
+use super::columns::Column;
+use super::columns::ColumnType;
+use crate::Result;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    #[default]
+    Ndjson,
+    /// Columnar Parquet with Snappy compression
+    Parquet,
+}
+
+impl ExportFormat {
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    /// MIME type of an exported file
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+pub(crate) fn parquet_error(e: impl std::fmt::Display) -> crate::SnapRagError {
+    crate::SnapRagError::Custom(format!("Parquet write failed: {e}"))
+}
+
+/// A cell as a JSON value; bytes become hex strings
+fn json_value(cell: &Cell) -> serde_json::Value {
+    match cell {
+        Cell::Null => serde_json::Value::Null,
+        Cell::Int(value) => serde_json::Value::from(*value),
+        Cell::Text(value) => serde_json::Value::from(value.as_str()),
+        Cell::Bytes(value) => serde_json::Value::from(hex::encode(value)),
+        Cell::Json(value) => value.clone(),
+        Cell::Floats(values) => values
+            .iter()
+            .map(|value| serde_json::Value::from(f64::from(*value)))
+            .collect(),
+    }
+}
+
+/// A row as a JSON object keyed by column name
+#[must_use]
+pub fn json_row(columns: &[Column], cells: &[Cell]) -> serde_json::Value {
+    let row = columns
+        .iter()
+        .zip(cells)
+        .map(|(column, cell)| (column.name.to_string(), json_value(cell)))
+        .collect();
+    serde_json::Value::Object(row)
+}
+
+/// Parse a row written by [`json_row`] back into cells
+///
+/// # Errors
+/// Returns an error if the line is not a JSON object or a value does not fit
+/// its column
+pub fn parse_json_row(columns: &[Column], line: &str) -> Result<Vec<Cell>> {
+    let mut row: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line)?;
+    columns
+        .iter()
+        .map(|column| {
+            let value = row.remove(column.name).unwrap_or_default();
+            parse_json_value(column, value)
+        })
+        .collect()
+}
+
+fn parse_json_value(column: &Column, value: serde_json::Value) -> Result<Cell> {
+    let invalid = || {
+        crate::SnapRagError::Custom(format!(
+            "Invalid value for column {}: expected {:?}",
+            column.name, column.column_type
+        ))
+    };
+
+    if value.is_null() {
+        return Ok(Cell::Null);
+    }
+    Ok(match column.column_type {
+        ColumnType::Int64 => Cell::Int(value.as_i64().ok_or_else(invalid)?),
+        ColumnType::Text => Cell::Text(value.as_str().ok_or_else(invalid)?.to_string()),
+        ColumnType::Bytes => {
+            Cell::Bytes(hex::decode(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?)
+        }
+        ColumnType::Json => Cell::Json(value),
+        #[allow(clippy::cast_possible_truncation)] // Written from f32
+        ColumnType::Floats => Cell::Floats(
+            value
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|value| value.as_f64().map(|value| value as f32))
+                .collect::<Option<_>>()
+                .ok_or_else(invalid)?,
+        ),
+    })
+}
+
+/// Write one CSV field, quoting it when needed; NULL is an empty field
+fn write_csv_field(out: &mut impl Write, cell: &Cell) -> std::io::Result<()> {
+    let field = match cell {
+        Cell::Null => return Ok(()),
+        Cell::Int(value) => return write!(out, "{value}"),
+        Cell::Text(value) => value.clone(),
+        Cell::Bytes(value) => return out.write_all(hex::encode(value).as_bytes()),
+        cell => json_value(cell).to_string(),
+    };
+    write_csv_text(out, &field)
+}
+
+fn write_csv_text(out: &mut impl Write, field: &str) -> std::io::Result<()> {
+    if field.contains([',', '"', '\n', '\r']) {
+        write!(out, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        out.write_all(field.as_bytes())
+    }
+}
+
+/// Arrow schema of a column list; every column is nullable
+#[must_use]
+pub fn arrow_schema(columns: &[Column]) -> SchemaRef {
+    let fields: Vec<_> = columns
+        .iter()
+        .map(|column| {
+            let data_type = match column.column_type {
+                ColumnType::Int64 => DataType::Int64,
+                ColumnType::Text | ColumnType::Json => DataType::Utf8,
+                ColumnType::Bytes => DataType::Binary,
+                ColumnType::Floats => {
+                    DataType::List(Arc::new(Field::new_list_field(DataType::Float32, true)))
+                }
+            };
+            Field::new(column.name, data_type, true)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// Rows as an Arrow record batch of `schema`
+///
+/// # Errors
+/// Returns an error if the arrays do not match the schema
+pub fn record_batch(
+    schema: &SchemaRef,
+    columns: &[Column],
+    rows: &[&[Cell]],
+) -> Result<RecordBatch> {
+    let arrays: Vec<ArrayRef> = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let cells = rows.iter().map(|row| &row[index]);
+            let array: ArrayRef = match column.column_type {
+                ColumnType::Int64 => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Int(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect::<Int64Array>(),
+                ),
+                ColumnType::Text => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Text(value) => Some(value.as_str()),
+                            _ => None,
+                        })
+                        .collect::<StringArray>(),
+                ),
+                ColumnType::Json => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Json(value) => Some(value.to_string()),
+                            _ => None,
+                        })
+                        .collect::<StringArray>(),
+                ),
+                ColumnType::Bytes => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Bytes(value) => Some(value.as_slice()),
+                            _ => None,
+                        })
+                        .collect::<BinaryArray>(),
+                ),
+                ColumnType::Floats => {
+                    Arc::new(ListArray::from_iter_primitive::<Float32Type, _, _>(
+                        cells.map(|cell| match cell {
+                            Cell::Floats(values) => Some(values.iter().copied().map(Some)),
+                            _ => None,
+                        }),
+                    ))
+                }
+            };
+            array
+        })
+        .collect();
+
+    RecordBatch::try_new(schema.clone(), arrays).map_err(parquet_error)
+}
+
+enum Encoder<W: Write + Send> {
+    Csv(W),
+    Ndjson(W),
+    Parquet {
+        writer: Box<ArrowWriter<W>>,
+        schema: SchemaRef,
+    },
+}
+
+/// Streams rows of one table into a writer in an [`ExportFormat`]
+///
+/// Parquet rows are written as one row group per [`write_rows`](Self::write_rows)
+/// call, so memory stays bounded by the batch size.
+pub struct ExportWriter<W: Write + Send> {
+    columns: &'static [Column],
+    encoder: Encoder<W>,
+    rows: u64,
+}
+
+impl<W: Write + Send> ExportWriter<W> {
+    /// Start an export; CSV output begins with the header row
+    ///
+    /// # Errors
+    /// Returns an error if the header cannot be written
+    pub fn new(format: ExportFormat, columns: &'static [Column], mut out: W) -> Result<Self> {
+        let encoder = match format {
+            ExportFormat::Csv => {
+                for (index, column) in columns.iter().enumerate() {
+                    if index > 0 {
+                        out.write_all(b",")?;
+                    }
+                    write_csv_text(&mut out, column.name)?;
+                }
+                out.write_all(b"\n")?;
+                Encoder::Csv(out)
+            }
+            ExportFormat::Ndjson => Encoder::Ndjson(out),
+            ExportFormat::Parquet => {
+                let schema = arrow_schema(columns);
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                let writer = ArrowWriter::try_new(out, schema.clone(), Some(properties))
+                    .map_err(parquet_error)?;
+                Encoder::Parquet {
+                    writer: Box::new(writer),
+                    schema,
+                }
+            }
+        };
+        Ok(Self {
+            columns,
+            encoder,
+            rows: 0,
+        })
+    }
+
+    /// Encode a batch of rows, one cell per column
+    ///
+    /// # Errors
+    /// Returns an error if the output cannot be written
+    pub fn write_rows(&mut self, rows: &[Vec<Cell>]) -> Result<()> {
+        match &mut self.encoder {
+            Encoder::Csv(out) => {
+                for row in rows {
+                    for (index, cell) in row.iter().enumerate() {
+                        if index > 0 {
+                            out.write_all(b",")?;
+                        }
+                        write_csv_field(out, cell)?;
+                    }
+                    out.write_all(b"\n")?;
+                }
+            }
+            Encoder::Ndjson(out) => {
+                for row in rows {
+                    serde_json::to_writer(&mut *out, &json_row(self.columns, row))?;
+                    out.write_all(b"\n")?;
+                }
+            }
+            Encoder::Parquet { writer, schema } => {
+                let rows: Vec<&[Cell]> = rows.iter().map(Vec::as_slice).collect();
+                writer
+                    .write(&record_batch(schema, self.columns, &rows)?)
+                    .map_err(parquet_error)?;
+                writer.flush().map_err(parquet_error)?;
+            }
+        }
+        self.rows += rows.len() as u64;
+        Ok(())
+    }
+
+    /// Rows written so far
+    #[must_use]
+    pub const fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Finish the output (the Parquet footer) and return the writer
+    ///
+    /// # Errors
+    /// Returns an error if the output cannot be written
+    pub fn finish(self) -> Result<W> {
+        Ok(match self.encoder {
+            Encoder::Csv(mut out) | Encoder::Ndjson(mut out) => {
+                out.flush()?;
+                out
+            }
+            Encoder::Parquet { writer, .. } => writer.into_inner().map_err(parquet_error)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array;
+    use axum::body::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::export::columns::bytes;
+    use crate::export::columns::floats;
+    use crate::export::columns::int;
+    use crate::export::columns::text;
+
+    static COLUMNS: &[Column] = &[
+        int("fid"),
+        text("text"),
+        bytes("message_hash"),
+        floats("embedding"),
+    ];
+
+    fn rows() -> Vec<Vec<Cell>> {
+        vec![
+            vec![
+                Cell::Int(1),
+                Cell::Text("gm, \"frens\"".to_string()),
+                Cell::Bytes(vec![0xab, 0xcd]),
+                Cell::Floats(vec![0.5, -1.0]),
+            ],
+            vec![Cell::Int(2), Cell::Null, Cell::Null, Cell::Null],
+        ]
+    }
+
+    fn export(format: ExportFormat) -> Vec<u8> {
+        let mut writer = ExportWriter::new(format, COLUMNS, Vec::new()).unwrap();
+        writer.write_rows(&rows()).unwrap();
+        assert_eq!(writer.rows(), 2);
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_csv_quotes_fields_and_arrays() {
+        let csv = String::from_utf8(export(ExportFormat::Csv)).unwrap();
+        assert_eq!(
+            csv,
+            "fid,text,message_hash,embedding\n\
+             1,\"gm, \"\"frens\"\"\",abcd,\"[0.5,-1.0]\"\n\
+             2,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_ndjson_rows() {
+        let ndjson = String::from_utf8(export(ExportFormat::Ndjson)).unwrap();
+        let first: serde_json::Value =
+            serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+        assert_eq!(first["message_hash"], "abcd");
+        assert_eq!(first["embedding"], serde_json::json!([0.5, -1.0]));
+        assert_eq!(ndjson.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_ndjson_rows_parse_back() {
+        let ndjson = String::from_utf8(export(ExportFormat::Ndjson)).unwrap();
+        let parsed: Vec<Vec<Cell>> = ndjson
+            .lines()
+            .map(|line| parse_json_row(COLUMNS, line).unwrap())
+            .collect();
+        assert_eq!(parsed, rows());
+
+        assert!(parse_json_row(COLUMNS, r#"{"fid": "one"}"#).is_err());
+        assert!(parse_json_row(COLUMNS, r#"{"fid": 1, "message_hash": "zz"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parquet_embeddings_are_float_lists() {
+        let mut writer = ExportWriter::new(ExportFormat::Parquet, COLUMNS, Vec::new()).unwrap();
+        writer.write_rows(&rows()).unwrap();
+        writer.write_rows(&rows()).unwrap();
+        let out = writer.finish().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(out)).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let batch = reader.build().unwrap().next().unwrap().unwrap();
+        let embedding = batch
+            .column_by_name("embedding")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        let first = embedding.value(0);
+        let values = first
+            .as_any()
+            .downcast_ref::<arrow_array::Float32Array>()
+            .unwrap();
+        assert_eq!(values.values().to_vec(), vec![0.5, -1.0]);
+        assert!(embedding.is_null(1));
+    }
+}
@@ -0,0 +1,395 @@
+//! Bulk dataset exports of profiles, casts, links and embeddings
+//!
+//! An export reads one table through a server-side cursor inside a read-only
+//! snapshot and encodes it batch by batch as CSV, NDJSON or Parquet, so
+//! tables of any size stream in bounded memory. `snaprag export` writes the
+//! result to a file and `GET /api/export` streams it as the response body.
+//! Embeddings are exported as float arrays of the active embedding version.
+//!
+//! An [`archive`] holds every table needed to rebuild an instance, read from
+//! a single snapshot, and is loaded back with `snaprag import`.
+
+pub mod archive;
+pub mod columns;
+pub mod format;
+
+use std::future::Future;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+use self::columns::bytes;
+use self::columns::floats;
+use self::columns::int;
+use self::columns::text;
+use self::columns::Column;
+pub use self::format::ExportFormat;
+pub use self::format::ExportWriter;
+use crate::database::Database;
+use crate::sync::sink::records;
+use crate::Result;
+
+/// A dataset that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportTable {
+    /// Current profile fields, one row per FID
+    Profiles,
+    /// Profile field messages that the current profiles are derived from
+    ProfileChanges,
+    /// Casts with their synced shard and block height
+    Casts,
+    /// Link messages (follows) with their synced shard and block height
+    Links,
+    /// Reaction messages with their synced shard and block height
+    Reactions,
+    /// Cast embeddings with the cast's timestamp
+    CastEmbeddings,
+    /// Profile, bio and interests embeddings per FID
+    ProfileEmbeddings,
+    /// Block ranges sync has applied, per shard
+    ProcessedBlocks,
+    /// Last processed block height per shard
+    SyncProgress,
+}
+
+static PROFILE_COLUMNS: &[Column] = &[
+    int("fid"),
+    text("username"),
+    text("display_name"),
+    text("bio"),
+    text("pfp_url"),
+    text("banner_url"),
+    text("location"),
+    text("website_url"),
+    text("twitter_username"),
+    text("github_username"),
+    text("primary_address_ethereum"),
+    text("primary_address_solana"),
+    int("last_updated_timestamp"),
+];
+
+static PROFILE_CHANGE_COLUMNS: &[Column] = &[
+    int("fid"),
+    text("field_name"),
+    text("field_value"),
+    int("timestamp"),
+    bytes("message_hash"),
+    int("shard_id"),
+    int("block_height"),
+];
+
+static CAST_EMBEDDING_COLUMNS: &[Column] = &[
+    bytes("message_hash"),
+    int("fid"),
+    text("text"),
+    int("timestamp"),
+    floats("embedding"),
+];
+
+static PROFILE_EMBEDDING_COLUMNS: &[Column] = &[
+    int("fid"),
+    floats("profile_embedding"),
+    floats("bio_embedding"),
+    floats("interests_embedding"),
+    int("updated_at"),
+];
+
+static PROCESSED_BLOCK_COLUMNS: &[Column] =
+    &[int("shard_id"), int("start_height"), int("end_height")];
+
+static SYNC_PROGRESS_COLUMNS: &[Column] = &[int("shard_id"), int("last_processed_height")];
+
+impl ExportTable {
+    /// Name used on the command line, in the API and in file names
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Profiles => "profiles",
+            Self::ProfileChanges => "profile_changes",
+            Self::Casts => "casts",
+            Self::Links => "links",
+            Self::Reactions => "reactions",
+            Self::CastEmbeddings => "cast_embeddings",
+            Self::ProfileEmbeddings => "profile_embeddings",
+            Self::ProcessedBlocks => "processed_blocks",
+            Self::SyncProgress => "sync_progress",
+        }
+    }
+
+    /// Exported columns, in order
+    ///
+    /// Casts, links and reactions share their columns with the sync file sinks.
+    #[must_use]
+    pub fn columns(self) -> &'static [Column] {
+        match self {
+            Self::Profiles => PROFILE_COLUMNS,
+            Self::ProfileChanges => PROFILE_CHANGE_COLUMNS,
+            Self::Casts => records::CASTS.columns,
+            Self::Links => records::LINKS.columns,
+            Self::Reactions => records::REACTIONS.columns,
+            Self::CastEmbeddings => CAST_EMBEDDING_COLUMNS,
+            Self::ProfileEmbeddings => PROFILE_EMBEDDING_COLUMNS,
+            Self::ProcessedBlocks => PROCESSED_BLOCK_COLUMNS,
+            Self::SyncProgress => SYNC_PROGRESS_COLUMNS,
+        }
+    }
+
+    /// Whether rows can be filtered by FID and time
+    ///
+    /// Sync state is kept per shard and has neither.
+    #[must_use]
+    pub const fn is_filterable(self) -> bool {
+        !matches!(self, Self::ProcessedBlocks | Self::SyncProgress)
+    }
+}
+
+impl FromStr for ExportTable {
+    type Err = crate::SnapRagError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::from(s))
+            .map_err(|_| crate::SnapRagError::Custom(format!("Unknown export table '{s}'")))
+    }
+}
+
+/// Inclusive FID bounds; either side may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FidRange {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl FromStr for FidRange {
+    type Err = crate::SnapRagError;
+
+    /// Parse `min-max`, `min-`, `-max` or a single FID
+    fn from_str(s: &str) -> Result<Self> {
+        let bound = |value: &str| -> Result<Option<i64>> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value.parse().map(Some).map_err(|_| {
+                crate::SnapRagError::Custom(format!("Invalid FID range '{s}', expected min-max"))
+            })
+        };
+
+        let range = if let Some((min, max)) = s.split_once('-') {
+            Self {
+                min: bound(min)?,
+                max: bound(max)?,
+            }
+        } else {
+            let fid = bound(s)?;
+            Self { min: fid, max: fid }
+        };
+        if let (Some(min), Some(max)) = (range.min, range.max) {
+            if min > max {
+                return Err(crate::SnapRagError::Custom(format!(
+                    "Invalid FID range '{s}': {min} > {max}"
+                )));
+            }
+        }
+        Ok(range)
+    }
+}
+
+/// Parse an export time bound: RFC 3339, `YYYY-MM-DD` (UTC midnight) or Unix seconds
+///
+/// # Errors
+/// Returns an error if the value matches none of these forms
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(day.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| {
+            crate::SnapRagError::Custom(format!(
+                "Invalid time '{value}', expected RFC 3339, YYYY-MM-DD or Unix seconds"
+            ))
+        })
+}
+
+/// What to export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportQuery {
+    pub table: ExportTable,
+    /// Inclusive lower time bound
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper time bound
+    pub until: Option<DateTime<Utc>>,
+    pub fids: FidRange,
+}
+
+impl ExportQuery {
+    #[must_use]
+    pub fn new(table: ExportTable) -> Self {
+        Self {
+            table,
+            since: None,
+            until: None,
+            fids: FidRange::default(),
+        }
+    }
+
+    /// Reject filters on a table that cannot be filtered
+    ///
+    /// # Errors
+    /// Returns an error if a FID or time bound is set on such a table
+    pub fn validate(&self) -> Result<()> {
+        let filtered =
+            self.since.is_some() || self.until.is_some() || self.fids != FidRange::default();
+        if filtered && !self.table.is_filterable() {
+            return Err(crate::SnapRagError::Custom(format!(
+                "{} cannot be filtered by FID or time",
+                self.table.name()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// In-memory output of an [`ExportWriter`], drained after every batch
+#[derive(Debug, Clone, Default)]
+struct ChunkBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl ChunkBuffer {
+    fn take(&self) -> Vec<u8> {
+        self.0
+            .lock()
+            .map(|mut buffer| std::mem::take(&mut *buffer))
+            .unwrap_or_default()
+    }
+}
+
+impl Write for ChunkBuffer {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("Export buffer lock poisoned"))?
+            .extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run an export, handing each encoded chunk to `emit` as soon as it is ready
+///
+/// Every fetched batch yields one chunk, and the last chunk completes the
+/// file. An error from `emit` (such as a closed connection) stops the export.
+/// Returns the number of exported rows.
+///
+/// # Errors
+/// Returns an error if the query fails, a row cannot be encoded or `emit` fails
+pub async fn export_chunks<F, Fut>(
+    database: &Database,
+    query: &ExportQuery,
+    format: ExportFormat,
+    fetch_size: usize,
+    mut emit: F,
+) -> Result<u64>
+where
+    F: FnMut(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let buffer = ChunkBuffer::default();
+    let mut writer = ExportWriter::new(format, query.table.columns(), buffer.clone())?;
+    let mut snapshot = database.begin_export_snapshot().await?;
+    let mut cursor = snapshot.cursor(query, fetch_size).await?;
+
+    while let Some(rows) = cursor.next_batch().await? {
+        writer.write_rows(&rows)?;
+        emit(buffer.take()).await?;
+    }
+    cursor.close().await?;
+    snapshot.finish().await?;
+
+    let rows = writer.rows();
+    writer.finish()?;
+    emit(buffer.take()).await?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fid_range() {
+        assert_eq!(
+            "100-200".parse::<FidRange>().unwrap(),
+            FidRange {
+                min: Some(100),
+                max: Some(200)
+            }
+        );
+        assert_eq!(
+            "100-".parse::<FidRange>().unwrap(),
+            FidRange {
+                min: Some(100),
+                max: None
+            }
+        );
+        assert_eq!(
+            "-5".parse::<FidRange>().unwrap(),
+            FidRange {
+                min: None,
+                max: Some(5)
+            }
+        );
+        assert_eq!(
+            "42".parse::<FidRange>().unwrap(),
+            FidRange {
+                min: Some(42),
+                max: Some(42)
+            }
+        );
+        assert!("200-100".parse::<FidRange>().is_err());
+        assert!("abc".parse::<FidRange>().is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let day = parse_time("2024-03-09").unwrap();
+        assert_eq!(day.to_rfc3339(), "2024-03-09T00:00:00+00:00");
+        assert_eq!(parse_time("2024-03-09T00:00:00Z").unwrap(), day);
+        assert_eq!(parse_time("1709942400").unwrap(), day);
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_table_names_round_trip() {
+        for table in [
+            ExportTable::Profiles,
+            ExportTable::ProfileChanges,
+            ExportTable::Casts,
+            ExportTable::Links,
+            ExportTable::Reactions,
+            ExportTable::CastEmbeddings,
+            ExportTable::ProfileEmbeddings,
+            ExportTable::ProcessedBlocks,
+            ExportTable::SyncProgress,
+        ] {
+            assert_eq!(table.name().parse::<ExportTable>().unwrap(), table);
+        }
+        assert!("users".parse::<ExportTable>().is_err());
+    }
+}
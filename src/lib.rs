@@ -133,6 +133,7 @@
 //! - [`sync`]: Data synchronization from Snapchain
 //! - [`social_graph`]: Social network analysis
 //! - [`personality`]: MBTI personality inference
+//! - [`webhooks`]: Change-data-capture webhooks for synced events
 //!
 //! # Error Handling
 //!
@@ -144,6 +145,7 @@ pub mod config;
 pub mod database;
 pub mod embeddings;
 pub mod errors;
+pub mod export;
 pub mod generated;
 pub mod grpc_client;
 pub mod llm;
@@ -153,6 +155,7 @@ pub mod personality;
 pub mod rag;
 pub mod social_graph;
 pub mod sync;
+pub mod webhooks;
 
 // Unit test modules integrated into main lib tests
 // See src/tests/unit_tests.rs for comprehensive unit tests
@@ -220,11 +223,26 @@ pub use models::*;
 // Re-export RAG functionality
 pub use rag::{
     CastContextAssembler,
+    CastRagQuery,
+    CastRagResponse,
+    CastRagService,
     CastRetriever,
+    CastSource,
+    Citation,
     ContextAssembler,
+    CrossEncoderReranker,
+    HeuristicReranker,
+    LlmReranker,
+    QueryAnalysis,
+    QueryFilters,
+    QueryUnderstanding,
+    RagMetadata,
     RagQuery,
     RagResponse,
     RagService,
+    RagStream,
+    Rerank,
+    RerankDocument,
     RetrievalMethod,
     Retriever,
     SearchResult,
@@ -281,7 +299,9 @@ impl SnapRag {
     /// # Errors
     /// Returns error if database schema initialization fails
     pub async fn init_database(&self) -> Result<()> {
-        self.database.init_schema().await?;
+        self.database
+            .init_schema(self.config.embedding_dimension())
+            .await?;
         info!("Database schema initialized");
         Ok(())
     }
@@ -599,6 +619,7 @@ impl SnapRag {
             text_search: None,
             parent_hash: None,
             root_hash: None,
+            channel: None,
             has_mentions: None,
             has_embeds: None,
             start_timestamp: None,
@@ -665,6 +686,16 @@ impl SnapRag {
         RagService::new(&self.config).await
     }
 
+    /// Create a RAG service answering questions from casts with citations
+    ///
+    /// # Errors
+    /// - Embedding service configuration errors (invalid API keys, endpoints)
+    /// - LLM service configuration errors (missing or invalid LLM config)
+    /// - Network connectivity issues (cannot reach embedding/LLM APIs)
+    pub async fn create_cast_rag_service(&self) -> Result<CastRagService> {
+        CastRagService::new(&self.config).await
+    }
+
     /// Create an embedding service for vector generation
     ///
     /// # Errors
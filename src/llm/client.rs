@@ -308,11 +308,53 @@ impl LlmClient {
         temperature: f32,
         max_tokens: usize,
     ) -> Result<StreamingResponse> {
-        // Use unified fallback-to-stream pattern
+        #[derive(Serialize)]
+        struct OpenAIRequest<'a> {
+            model: &'a str,
+            messages: Vec<ChatMessage>,
+            temperature: f32,
+            max_tokens: usize,
+            stream: bool,
+        }
+
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| SnapragError::ConfigError("OpenAI API key not provided".to_string()))?;
+
+        let url = format!("{}/chat/completions", self.endpoint);
+        debug!("Calling OpenAI API (streaming): {}", url);
+
+        let request = OpenAIRequest {
+            model: &self.model,
+            messages: vec![ChatMessage::user(prompt)],
+            temperature,
+            max_tokens,
+            stream: true,
+        };
+
         let response = self
-            .generate_openai(prompt, temperature, max_tokens)
-            .await?;
-        Ok(Self::wrap_in_stream(response))
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| SnapragError::HttpError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(SnapragError::LlmError(format!(
+                "OpenAI API error ({status}): {error_text}"
+            )));
+        }
+
+        Ok(Self::line_stream(response, parse_openai_line))
     }
 
     /// Ollama completion
@@ -445,13 +487,52 @@ impl LlmClient {
         &self,
         prompt: &str,
         temperature: f32,
-        max_tokens: usize,
+        _max_tokens: usize,
     ) -> Result<StreamingResponse> {
-        // Use unified fallback-to-stream pattern
+        #[derive(Serialize)]
+        struct OllamaRequest<'a> {
+            model: &'a str,
+            prompt: &'a str,
+            stream: bool,
+            options: OllamaOptions,
+        }
+
+        #[derive(Serialize)]
+        struct OllamaOptions {
+            temperature: f32,
+        }
+
+        let url = format!("{}/api/generate", self.endpoint);
+        debug!("Calling Ollama API (streaming): {}", url);
+
+        let request = OllamaRequest {
+            model: &self.model,
+            prompt,
+            stream: true,
+            options: OllamaOptions { temperature },
+        };
+
         let response = self
-            .generate_ollama(prompt, temperature, max_tokens)
-            .await?;
-        Ok(Self::wrap_in_stream(response))
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| SnapragError::HttpError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(SnapragError::LlmError(format!(
+                "Ollama API error ({status}): {error_text}"
+            )));
+        }
+
+        Ok(Self::line_stream(response, parse_ollama_line))
     }
 
     /// Custom provider completion
@@ -466,11 +547,164 @@ impl LlmClient {
         ))
     }
 
-    /// Unified helper: Convert non-streaming response to streaming
-    /// This provides a consistent interface while allowing future SSE implementation
-    fn wrap_in_stream(response: String) -> StreamingResponse {
-        use futures::stream;
-        let stream = stream::once(async move { Ok(response) });
+    /// Turn a line-delimited streaming body into text deltas
+    ///
+    /// Lines are split on `\n` before decoding, so multi-byte characters
+    /// cut across network chunks arrive intact.
+    fn line_stream(
+        response: reqwest::Response,
+        parse: fn(&str) -> Result<StreamLine>,
+    ) -> StreamingResponse {
+        struct LineState {
+            response: reqwest::Response,
+            buffer: Vec<u8>,
+            done: bool,
+        }
+
+        let state = LineState {
+            response,
+            buffer: Vec::new(),
+            done: false,
+        };
+        let stream = futures::stream::try_unfold(state, move |mut state| async move {
+            loop {
+                if state.done {
+                    return Ok(None);
+                }
+                if let Some(end) = state.buffer.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = state.buffer.drain(..=end).collect();
+                    let line = parse(String::from_utf8_lossy(&line).trim())?;
+                    state.done = line.done;
+                    match line.text {
+                        Some(text) if !text.is_empty() => return Ok(Some((text, state))),
+                        _ => continue,
+                    }
+                }
+
+                match state
+                    .response
+                    .chunk()
+                    .await
+                    .map_err(|e| SnapragError::HttpError(e.to_string()))?
+                {
+                    Some(bytes) => state.buffer.extend_from_slice(&bytes),
+                    None if state.buffer.is_empty() => return Ok(None),
+                    // Terminate a last line sent without a newline
+                    None => state.buffer.push(b'\n'),
+                }
+            }
+        });
         StreamingResponse::new(Box::pin(stream))
     }
 }
+
+/// Text carried by one line of a streamed completion
+#[derive(Debug, PartialEq, Eq)]
+struct StreamLine {
+    text: Option<String>,
+    /// The provider signalled the end of the completion
+    done: bool,
+}
+
+/// Parse a Server-Sent Events line of an `OpenAI` chat completion stream
+fn parse_openai_line(line: &str) -> Result<StreamLine> {
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        // Blank separators, comments and `event:`/`id:` fields
+        return Ok(StreamLine {
+            text: None,
+            done: false,
+        });
+    };
+    if data == "[DONE]" {
+        return Ok(StreamLine {
+            text: None,
+            done: true,
+        });
+    }
+
+    let chunk: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| SnapragError::LlmError(format!("Failed to parse stream chunk: {e}")))?;
+    if let Some(error) = chunk.get("error") {
+        return Err(SnapragError::LlmError(format!(
+            "OpenAI stream error: {error}"
+        )));
+    }
+    Ok(StreamLine {
+        text: chunk["choices"][0]["delta"]["content"]
+            .as_str()
+            .map(str::to_string),
+        done: false,
+    })
+}
+
+/// Parse an NDJSON line of an Ollama generate or chat stream
+fn parse_ollama_line(line: &str) -> Result<StreamLine> {
+    if line.is_empty() {
+        return Ok(StreamLine {
+            text: None,
+            done: false,
+        });
+    }
+
+    let chunk: serde_json::Value = serde_json::from_str(line)
+        .map_err(|e| SnapragError::LlmError(format!("Failed to parse stream chunk: {e}")))?;
+    if let Some(error) = chunk.get("error").and_then(serde_json::Value::as_str) {
+        return Err(SnapragError::LlmError(format!(
+            "Ollama stream error: {error}"
+        )));
+    }
+    Ok(StreamLine {
+        text: chunk["response"]
+            .as_str()
+            .or_else(|| chunk["message"]["content"].as_str())
+            .map(str::to_string),
+        done: chunk["done"].as_bool().unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: Result<StreamLine>) -> Option<String> {
+        line.unwrap().text
+    }
+
+    #[test]
+    fn test_parse_openai_stream_lines() {
+        assert_eq!(
+            text(parse_openai_line(
+                r#"data: {"choices":[{"delta":{"content":"Hel"},"index":0}]}"#
+            )),
+            Some("Hel".to_string())
+        );
+        // Role-only first chunk and SSE separators carry no text
+        assert_eq!(
+            text(parse_openai_line(
+                r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#
+            )),
+            None
+        );
+        assert_eq!(text(parse_openai_line("")), None);
+        assert_eq!(text(parse_openai_line(": keep-alive")), None);
+        assert!(parse_openai_line("data: [DONE]").unwrap().done);
+        assert!(parse_openai_line(r#"data: {"error":{"message":"rate limited"}}"#).is_err());
+        assert!(parse_openai_line("data: {not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_ollama_stream_lines() {
+        let line = parse_ollama_line(r#"{"model":"m","response":"lo","done":false}"#).unwrap();
+        assert_eq!(line.text.as_deref(), Some("lo"));
+        assert!(!line.done);
+
+        let line =
+            parse_ollama_line(r#"{"message":{"role":"assistant","content":"!"},"done":true}"#)
+                .unwrap();
+        assert_eq!(line.text.as_deref(), Some("!"));
+        assert!(line.done);
+
+        assert!(parse_ollama_line(r#"{"error":"model not found"}"#).is_err());
+        assert_eq!(text(parse_ollama_line("")), None);
+    }
+}
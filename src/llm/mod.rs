@@ -145,6 +145,18 @@ impl LlmService {
             .await
     }
 
+    /// Generate a streaming response with custom parameters
+    pub async fn generate_stream_with_params(
+        &self,
+        prompt: &str,
+        temperature: f32,
+        max_tokens: usize,
+    ) -> Result<StreamingResponse> {
+        self.client
+            .generate_stream(prompt, temperature, max_tokens)
+            .await
+    }
+
     /// Chat completion with message history
     pub async fn chat(&self, messages: Vec<crate::llm::client::ChatMessage>) -> Result<String> {
         self.client
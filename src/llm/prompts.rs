@@ -125,20 +125,31 @@ Please provide:
         )
     }
 
-    /// Semantic search query enhancement
+    /// Query rewriting, expansion and filter extraction
+    ///
+    /// Asks for a JSON object with `rewritten`, `paraphrases`,
+    /// `hypothetical_answers` and `filters`.
     #[must_use]
     pub fn query_enhancement() -> PromptTemplate {
         PromptTemplate::new(
-            r"Enhance the following search query to improve semantic matching:
+            r#"You prepare questions about Farcaster users and casts for a search engine.
 
-Original query: {{query}}
+Today is {{today}}.
 
-Please provide:
-1. Expanded query with related terms
-2. Key concepts to search for
-3. Alternative phrasings
+Question: {{query}}
+
+Return a JSON object with these fields:
+- "rewritten": the question as a concise, self-contained search query
+- "paraphrases": {{paraphrases}} alternative phrasings of the search query, using related terms
+- "hypothetical_answers": {{hypothetical_answers}} short passages (one or two sentences) that a relevant profile or cast might contain
+- "filters": constraints stated in the question, with empty values when absent:
+  - "usernames": Farcaster usernames mentioned, without "@"
+  - "fids": Farcaster IDs (FIDs) mentioned, as numbers
+  - "since": first day of a date range as YYYY-MM-DD, or null
+  - "until": last day of a date range as YYYY-MM-DD, or null
+  - "channels": channel names mentioned, without "/"
 
-Return only the enhanced query text without explanations.",
+Return only the JSON object without explanations."#,
         )
     }
 }
@@ -153,6 +164,14 @@ mod tests {
         assert_eq!(template.variables(), &["name", "age"]);
     }
 
+    #[test]
+    fn test_query_enhancement_variables() {
+        assert_eq!(
+            RagPrompts::query_enhancement().variables(),
+            &["today", "query", "paraphrases", "hypothetical_answers"]
+        );
+    }
+
     #[test]
     fn test_template_render() {
         let template = PromptTemplate::new("Hello {{name}}!");
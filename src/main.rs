@@ -97,6 +97,9 @@ async fn main() -> Result<()> {
         Commands::Reset { force } => {
             snaprag::cli::handle_reset_command(&snaprag, force).await?;
         }
+        Commands::Migrate(migrate_command) => {
+            snaprag::cli::handle_migrate_command(&snaprag, &migrate_command).await?;
+        }
         Commands::Index(index_command) => {
             snaprag::cli::handle_index_command(&snaprag, &index_command).await?;
         }
@@ -106,6 +109,31 @@ async fn main() -> Result<()> {
         Commands::Sync(sync_command) => {
             snaprag::cli::handle_sync_command(snaprag, sync_command).await?;
         }
+        Commands::Reconcile {
+            fid,
+            fids_file,
+            dry_run,
+            output,
+        } => {
+            snaprag::cli::handle_reconcile_command(&snaprag, fid, fids_file, dry_run, output)
+                .await?;
+        }
+        Commands::Export {
+            table,
+            format,
+            since,
+            until,
+            fid_range,
+            out,
+        } => {
+            snaprag::cli::handle_export_command(
+                &snaprag, table, format, since, until, fid_range, out,
+            )
+            .await?;
+        }
+        Commands::Import { dir } => {
+            snaprag::cli::handle_import_command(&snaprag, &dir).await?;
+        }
         Commands::Stats { detailed, export } => {
             snaprag::cli::handle_stats_command(&snaprag, detailed, export).await?;
         }
@@ -119,6 +147,9 @@ async fn main() -> Result<()> {
         Commands::Config => {
             snaprag::cli::handle_config_command(&config)?;
         }
+        Commands::Onchain { fid, detailed } => {
+            snaprag::cli::handle_onchain_command(&snaprag, fid, detailed).await?;
+        }
         Commands::Activity {
             fid,
             limit,
@@ -142,9 +173,18 @@ async fn main() -> Result<()> {
                 limit,
                 threshold,
                 detailed,
+                channel,
             } => {
-                snaprag::cli::handle_cast_search(&snaprag, query, limit, threshold, detailed)
-                    .await?;
+                snaprag::cli::handle_cast_search(
+                    &snaprag, query, limit, threshold, detailed, channel,
+                )
+                .await?;
+            }
+            CastCommands::Channel { channel, limit } => {
+                snaprag::cli::handle_cast_channel(&snaprag, channel, limit).await?;
+            }
+            CastCommands::BackfillRoots { max_passes } => {
+                snaprag::cli::handle_cast_backfill_roots(&snaprag, max_passes).await?;
             }
             CastCommands::Recent { fid, limit } => {
                 snaprag::cli::handle_cast_recent(&snaprag, fid, limit).await?;
@@ -334,6 +374,9 @@ async fn main() -> Result<()> {
                     snaprag::cli::handle_cast_embeddings_analyze(&config).await?;
                 }
             },
+            EmbeddingsCommands::Model { action } => {
+                snaprag::cli::handle_embedding_model_command(&config, &action).await?;
+            }
             EmbeddingsCommands::BackfillCasts {
                 force,
                 batch_size,
@@ -153,6 +153,85 @@ pub struct UsernameProof {
     pub transaction_fid: Option<i64>,
 }
 
+/// Fname transfer record (ownership history of an fname)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FnameTransfer {
+    pub id: Uuid,
+    pub transfer_id: i64,
+    /// Previous owner FID (0 for a first registration)
+    pub from_fid: i64,
+    /// New owner FID (0 when the name is released)
+    pub to_fid: i64,
+    pub username: String,
+    pub owner: Option<Vec<u8>>,
+    pub signature: Option<Vec<u8>>,
+    pub timestamp: i64,
+    pub created_at: DateTime<Utc>,
+    pub shard_id: Option<i32>,
+    pub block_height: Option<i64>,
+}
+
+/// Signer key of a FID, materialized from SIGNER on-chain events
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FidSigner {
+    pub fid: i64,
+    pub key: Vec<u8>,
+    pub key_type: i32,
+    pub metadata: Option<Vec<u8>>,
+    pub active: bool,
+    pub added_block: Option<i64>,
+    /// Block timestamp of the add (unix seconds)
+    pub added_at: Option<i64>,
+    pub removed_block: Option<i64>,
+    pub removed_at: Option<i64>,
+    /// 2 = remove, 3 = admin reset
+    pub removal_type: Option<i16>,
+    pub last_block_number: i64,
+    pub last_log_index: i32,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Custody / recovery state of a FID, materialized from `ID_REGISTER` on-chain events
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FidRegistration {
+    pub fid: i64,
+    pub custody_address: Option<Vec<u8>>,
+    pub recovery_address: Option<Vec<u8>>,
+    pub registered_block: Option<i64>,
+    /// Block timestamp of the registration (unix seconds)
+    pub registered_at: Option<i64>,
+    pub last_block_number: i64,
+    pub last_log_index: i32,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Storage rent record from a `STORAGE_RENT` on-chain event
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StorageRent {
+    pub id: Uuid,
+    pub fid: i64,
+    pub payer: Option<Vec<u8>>,
+    pub units: i64,
+    /// Expiry (unix seconds)
+    pub expiry: i64,
+    pub block_number: i64,
+    pub log_index: i32,
+    pub block_timestamp: i64,
+    pub transaction_hash: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Decoded on-chain state of a FID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnchainState {
+    pub fid: i64,
+    pub registration: Option<FidRegistration>,
+    pub active_signers: Vec<FidSigner>,
+    /// Storage units with an expiry in the future
+    pub storage_units: i64,
+    pub storage_rents: Vec<StorageRent>,
+}
+
 /// User activity timeline record
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserActivityTimeline {
@@ -192,6 +271,19 @@ pub struct CastSearchResult {
     pub chunk_strategy: Option<String>,
 }
 
+/// Restrictions on cast search; empty fields match every cast
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CastSearchFilters {
+    /// Authors
+    pub fids: Vec<i64>,
+    /// Inclusive lower bound of the Farcaster timestamp
+    pub since: Option<i64>,
+    /// Exclusive upper bound of the Farcaster timestamp
+    pub until: Option<i64>,
+    /// Channel (parent URL) filter
+    pub channels: Vec<String>,
+}
+
 /// Cast statistics
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CastStats {
@@ -236,6 +328,10 @@ pub struct Cast {
     pub shard_id: Option<i32>,
     pub block_height: Option<i64>,
     pub transaction_fid: Option<i64>,
+    /// FID of the parent cast author (replies only)
+    pub parent_fid: Option<i64>,
+    /// Parent URL; channel casts use the channel URL here
+    pub parent_url: Option<String>,
 }
 
 /// Link relationship record
@@ -551,6 +647,8 @@ pub struct CastQuery {
     pub text_search: Option<String>,
     pub parent_hash: Option<Vec<u8>>,
     pub root_hash: Option<Vec<u8>>,
+    /// Channel (parent URL) filter
+    pub channel: Option<String>,
     pub has_mentions: Option<bool>,
     pub has_embeds: Option<bool>,
     pub start_timestamp: Option<i64>,
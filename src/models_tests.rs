@@ -89,6 +89,7 @@ mod tests {
             text_search: Some("test".to_string()),
             parent_hash: None,
             root_hash: None,
+            channel: None,
             has_mentions: Some(true),
             has_embeds: Some(false),
             start_timestamp: None,
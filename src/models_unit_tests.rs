@@ -37,6 +37,7 @@ mod tests {
             text_search: Some("blockchain".to_string()),
             parent_hash: None,
             root_hash: None,
+            channel: None,
             has_mentions: Some(true),
             has_embeds: None,
             start_timestamp: Some(1000000),
@@ -173,6 +174,7 @@ mod tests {
             text_search: None,
             parent_hash: None,
             root_hash: None,
+            channel: None,
             has_mentions: None,
             has_embeds: None,
             start_timestamp: None,
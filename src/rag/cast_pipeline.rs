@@ -0,0 +1,444 @@
+//! Cast RAG pipeline: Retrieve -> Number -> Generate -> Cite
+//!
+//! Casts found by hybrid search are placed in the context as numbered
+//! sources, the LLM is asked to cite them inline as `[n]`, and the markers
+//! in the answer are resolved back to the casts they refer to. With query
+//! understanding, the search is limited to the authors, dates and channels
+//! the question names.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Serialize;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::database::channel_url;
+use crate::database::Database;
+use crate::embeddings::EmbeddingService;
+use crate::errors::Result;
+use crate::llm::LlmService;
+use crate::models::CastSearchFilters;
+use crate::rag::build_cited_cast_rag_prompt;
+use crate::rag::context::CastSource;
+use crate::rag::rerank::reranker_from_config;
+use crate::rag::rerank::Rerank;
+use crate::rag::CastContextAssembler;
+use crate::rag::CastRetriever;
+use crate::rag::QueryAnalysis;
+use crate::rag::QueryFilters;
+use crate::rag::QueryUnderstanding;
+
+/// Answer given when no cast matches the question
+const NO_CASTS_ANSWER: &str = "No relevant casts found to answer this question.";
+
+/// Farcaster epoch in Unix seconds
+#[allow(clippy::cast_possible_wrap)] // 1_609_459_200 fits in i64
+const FARCASTER_EPOCH_SECS: i64 = (crate::FARCASTER_EPOCH / 1000) as i64;
+
+/// RAG service answering questions from casts, with citations
+pub struct CastRagService {
+    database: Arc<Database>,
+    retriever: CastRetriever,
+    context_assembler: CastContextAssembler,
+    llm_service: LlmService,
+    query_understanding: Option<QueryUnderstanding>,
+}
+
+impl CastRagService {
+    /// Create a new cast RAG service
+    ///
+    /// # Errors
+    /// - Database connection errors
+    /// - Embedding service configuration errors (invalid API keys, endpoints)
+    /// - LLM service configuration errors (missing or invalid LLM config)
+    /// - Reranker initialization errors (see `[rerank]`)
+    pub async fn new(config: &AppConfig) -> Result<Self> {
+        let database = Arc::new(Database::from_config(config).await?);
+        let embedding_service = Arc::new(EmbeddingService::new(config)?);
+        let reranker = reranker_from_config(config, database.clone()).await?;
+        let llm_service = LlmService::new(config)?;
+
+        let mut service = Self::from_services(database, embedding_service, llm_service.clone());
+        if let Some(reranker) = reranker {
+            service = service.with_reranker(reranker, config.rerank.candidates);
+        }
+        if config.query_understanding.enabled {
+            service = service.with_query_understanding(QueryUnderstanding::new(
+                llm_service,
+                config.query_understanding.clone(),
+            ));
+        }
+        Ok(service)
+    }
+
+    /// Create from existing services
+    #[must_use]
+    pub fn from_services(
+        database: Arc<Database>,
+        embedding_service: Arc<EmbeddingService>,
+        llm_service: LlmService,
+    ) -> Self {
+        Self {
+            retriever: CastRetriever::new(database.clone(), embedding_service),
+            database,
+            context_assembler: CastContextAssembler::default(),
+            llm_service,
+            query_understanding: None,
+        }
+    }
+
+    /// Rerank retrieved casts before they are numbered as sources
+    ///
+    /// See [`CastRetriever::with_reranker`].
+    #[must_use]
+    pub fn with_reranker(mut self, reranker: Arc<dyn Rerank>, candidates: usize) -> Self {
+        self.retriever = self.retriever.with_reranker(reranker, candidates);
+        self
+    }
+
+    /// Analyze questions with the LLM before retrieval
+    ///
+    /// Casts are searched with the rewritten query and only among the
+    /// authors, dates and channels the question names. The analysis is
+    /// returned in `query_analysis`.
+    #[must_use]
+    pub fn with_query_understanding(mut self, query_understanding: QueryUnderstanding) -> Self {
+        self.query_understanding = Some(query_understanding);
+        self
+    }
+
+    /// Answer a question from casts with default options
+    ///
+    /// # Errors
+    /// - Cast retrieval errors (embedding generation, database queries)
+    /// - LLM generation errors (API failures, rate limits, invalid responses)
+    pub async fn query(&self, question: &str) -> Result<CastRagResponse> {
+        self.query_with_options(CastRagQuery {
+            question: question.to_string(),
+            retrieval_limit: 10,
+            threshold: None,
+            temperature: 0.7,
+            max_tokens: 2000,
+        })
+        .await
+    }
+
+    /// Answer a question from casts
+    ///
+    /// # Errors
+    /// - Cast retrieval errors (embedding generation, database queries)
+    /// - Author lookup errors while assembling the context
+    /// - LLM generation errors (API failures, rate limits, invalid responses)
+    pub async fn query_with_options(&self, query: CastRagQuery) -> Result<CastRagResponse> {
+        info!("Processing cast RAG query: {}", query.question);
+
+        let query_analysis = match &self.query_understanding {
+            Some(understanding) => {
+                debug!("Step 0: Analyzing query");
+                Some(understanding.analyze(&query.question).await)
+            }
+            None => None,
+        };
+        let (search_text, filters) = match &query_analysis {
+            Some(analysis) => (
+                analysis.rewritten.as_deref().unwrap_or(&query.question),
+                self.search_filters(&analysis.filters).await?,
+            ),
+            None => (query.question.as_str(), CastSearchFilters::default()),
+        };
+
+        debug!("Step 1: Retrieving casts");
+        let results = self
+            .retriever
+            .filtered_hybrid_search(
+                search_text,
+                query.retrieval_limit,
+                query.threshold,
+                &filters,
+            )
+            .await?;
+        debug!("Retrieved {} casts", results.len());
+
+        if results.is_empty() {
+            return Ok(CastRagResponse {
+                answer: NO_CASTS_ANSWER.to_string(),
+                sources: Vec::new(),
+                citations: Citations::default(),
+                query: query.question,
+                query_analysis,
+            });
+        }
+
+        debug!("Step 2: Assembling numbered sources");
+        let (context, sources) = self
+            .context_assembler
+            .assemble_sources(&results, &self.database)
+            .await?;
+        let prompt = build_cited_cast_rag_prompt(&query.question, &context);
+
+        debug!("Step 3: Generating answer");
+        let answer = self
+            .llm_service
+            .generate_with_params(&prompt, query.temperature, query.max_tokens)
+            .await?;
+
+        let citations = Citations::resolve(&answer, &sources);
+        if !citations.unknown_markers.is_empty() {
+            warn!(
+                "Answer cites casts that were not provided: {:?}",
+                citations.unknown_markers
+            );
+        }
+        info!(
+            "Cast RAG query completed with {} citations",
+            citations.citations.len()
+        );
+
+        Ok(CastRagResponse {
+            answer,
+            sources,
+            citations,
+            query: query.question,
+            query_analysis,
+        })
+    }
+
+    /// Cast search filters for the constraints a question states
+    ///
+    /// Usernames are resolved to FIDs; unknown ones are skipped.
+    async fn search_filters(&self, filters: &QueryFilters) -> Result<CastSearchFilters> {
+        let mut fids = filters.fids.clone();
+        for username in &filters.usernames {
+            if let Some(profile) = self.database.get_user_profile_by_username(username).await? {
+                fids.push(profile.fid);
+            } else {
+                debug!("Unknown username '{}' in question", username);
+            }
+        }
+        fids.sort_unstable();
+        fids.dedup();
+        Ok(cast_search_filters(filters, fids))
+    }
+
+    /// Get retriever reference
+    #[must_use]
+    pub const fn retriever(&self) -> &CastRetriever {
+        &self.retriever
+    }
+}
+
+/// Cast RAG query configuration
+#[derive(Debug, Clone)]
+pub struct CastRagQuery {
+    pub question: String,
+    pub retrieval_limit: usize,
+    /// Minimum semantic similarity of retrieved casts
+    pub threshold: Option<f32>,
+    pub temperature: f32,
+    pub max_tokens: usize,
+}
+
+/// Cast RAG response
+#[derive(Debug, Clone, Serialize)]
+pub struct CastRagResponse {
+    pub answer: String,
+    /// Casts given to the LLM, numbered as cited in the answer
+    pub sources: Vec<CastSource>,
+    #[serde(flatten)]
+    pub citations: Citations,
+    pub query: String,
+    /// Rewritten query and extracted filters, when query understanding is
+    /// enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_analysis: Option<QueryAnalysis>,
+}
+
+/// Farcaster timestamp of a point in time
+const fn farcaster_timestamp(time: DateTime<Utc>) -> i64 {
+    time.timestamp() - FARCASTER_EPOCH_SECS
+}
+
+/// [`QueryFilters`] as cast search filters, with usernames already resolved
+/// to `fids`
+fn cast_search_filters(filters: &QueryFilters, fids: Vec<i64>) -> CastSearchFilters {
+    CastSearchFilters {
+        fids,
+        since: filters.since.map(farcaster_timestamp),
+        until: filters.until.map(farcaster_timestamp),
+        channels: filters
+            .channels
+            .iter()
+            .map(String::as_str)
+            .map(channel_url)
+            .collect(),
+    }
+}
+
+/// A source cited in an answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Citation {
+    pub marker: usize,
+    /// Hex-encoded message hash
+    pub message_hash: String,
+    pub fid: i64,
+    pub url: String,
+}
+
+/// Citation markers of an answer resolved against its sources
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Citations {
+    /// Cited sources, in order of first citation
+    pub citations: Vec<Citation>,
+    /// Markers that name no source, i.e. hallucinated citations
+    pub unknown_markers: Vec<usize>,
+    /// Numbers of sources the answer never cites
+    pub uncited_sources: Vec<usize>,
+}
+
+impl Citations {
+    /// Resolve the `[n]` markers of `answer` against `sources`
+    ///
+    /// Grouped markers such as `[1, 3]` and `[Cast 2]` are accepted too.
+    #[must_use]
+    pub fn resolve(answer: &str, sources: &[CastSource]) -> Self {
+        let mut resolved = Self::default();
+        let mut seen = BTreeSet::new();
+
+        for marker in citation_markers(answer) {
+            if !seen.insert(marker) {
+                continue;
+            }
+            match sources.iter().find(|source| source.number == marker) {
+                Some(source) => resolved.citations.push(Citation {
+                    marker,
+                    message_hash: source.message_hash.clone(),
+                    fid: source.fid,
+                    url: source.url.clone(),
+                }),
+                None => resolved.unknown_markers.push(marker),
+            }
+        }
+
+        resolved.uncited_sources = sources
+            .iter()
+            .map(|source| source.number)
+            .filter(|number| !seen.contains(number))
+            .collect();
+        resolved
+    }
+}
+
+/// Numbers cited in `text`, in order of appearance
+fn citation_markers(text: &str) -> Vec<usize> {
+    let mut markers = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(']') else {
+            break;
+        };
+        let inner = rest[..close].trim();
+        let inner = inner.strip_prefix("Cast").unwrap_or(inner);
+        let numbers: Option<Vec<usize>> = inner
+            .split(',')
+            .map(|part| part.trim().parse().ok())
+            .collect();
+        if let Some(numbers) = numbers {
+            markers.extend(numbers);
+            rest = &rest[close + 1..];
+        }
+    }
+
+    markers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(number: usize) -> CastSource {
+        CastSource {
+            number,
+            message_hash: format!("{number:02x}"),
+            fid: i64::try_from(number).unwrap() * 100,
+            author: format!("user{number}"),
+            url: format!("https://warpcast.com/user{number}/0x{number:02x}"),
+            text: String::new(),
+            timestamp: 0,
+            similarity: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_cast_search_filters() {
+        let filters = QueryFilters {
+            usernames: vec!["dwr".to_string()],
+            fids: vec![3],
+            since: "2024-06-01T00:00:00Z".parse().ok(),
+            until: None,
+            channels: vec!["rust".to_string()],
+        };
+        let search = cast_search_filters(&filters, vec![3, 5]);
+        assert_eq!(search.fids, [3, 5]);
+        assert_eq!(search.since, Some(1_717_200_000 - FARCASTER_EPOCH_SECS));
+        assert_eq!(search.until, None);
+        assert_eq!(search.channels, ["https://warpcast.com/~/channel/rust"]);
+
+        assert_eq!(
+            cast_search_filters(&QueryFilters::default(), Vec::new()),
+            CastSearchFilters::default()
+        );
+    }
+
+    #[test]
+    fn test_citation_markers() {
+        assert_eq!(
+            citation_markers("Rust is popular [2]. Builders agree [1, 3][Cast 4]."),
+            [2, 1, 3, 4]
+        );
+        assert_eq!(
+            citation_markers("Arrays like [a] or [1, x] and an open [5"),
+            Vec::<usize>::new()
+        );
+        assert_eq!(citation_markers("[[2]]"), [2]);
+    }
+
+    #[test]
+    fn test_resolve_citations() {
+        let sources = [source(1), source(2), source(3)];
+        let citations = Citations::resolve("Yes [2]. Also [7] and again [2][1].", &sources);
+
+        assert_eq!(
+            citations.citations,
+            [
+                Citation {
+                    marker: 2,
+                    message_hash: "02".to_string(),
+                    fid: 200,
+                    url: "https://warpcast.com/user2/0x02".to_string(),
+                },
+                Citation {
+                    marker: 1,
+                    message_hash: "01".to_string(),
+                    fid: 100,
+                    url: "https://warpcast.com/user1/0x01".to_string(),
+                },
+            ]
+        );
+        assert_eq!(citations.unknown_markers, [7]);
+        assert_eq!(citations.uncited_sources, [3]);
+    }
+
+    #[test]
+    fn test_uncited_answer() {
+        let citations = Citations::resolve("No citations here.", &[source(1), source(2)]);
+        assert!(citations.citations.is_empty());
+        assert_eq!(citations.uncited_sources, [1, 2]);
+    }
+}
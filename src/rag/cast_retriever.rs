@@ -5,26 +5,51 @@ use std::sync::Arc;
 use tracing::debug;
 
 use crate::database::Database;
+use crate::database::CAST_EMBEDDING_COLUMN;
+use crate::database::NOT_REMOVED;
+use crate::database::REPLY_NOT_REMOVED;
+use crate::embeddings::ActiveEmbeddingService;
 use crate::embeddings::EmbeddingService;
 use crate::errors::Result;
+use crate::models::CastSearchFilters;
 use crate::models::CastSearchResult;
+use crate::rag::rerank;
+use crate::rag::rerank::Rerank;
 
 /// Retriever for cast content
 pub struct CastRetriever {
     database: Arc<Database>,
-    embedding_service: Arc<EmbeddingService>,
+    embedding_service: ActiveEmbeddingService,
+    reranker: Option<Arc<dyn Rerank>>,
+    rerank_candidates: usize,
 }
 
 impl CastRetriever {
     /// Create a new cast retriever
+    ///
+    /// Queries are embedded with the model of the active embedding version,
+    /// which is `embedding_service` unless a model cutover has happened.
     #[must_use]
-    pub const fn new(database: Arc<Database>, embedding_service: Arc<EmbeddingService>) -> Self {
+    pub fn new(database: Arc<Database>, embedding_service: Arc<EmbeddingService>) -> Self {
         Self {
+            embedding_service: ActiveEmbeddingService::new(database.clone(), embedding_service),
             database,
-            embedding_service,
+            reranker: None,
+            rerank_candidates: 0,
         }
     }
 
+    /// Rerank the merged results of `hybrid_search`
+    ///
+    /// `candidates` casts are retrieved by each search (or the requested
+    /// limit, if larger) and cut to the limit after reranking.
+    #[must_use]
+    pub fn with_reranker(mut self, reranker: Arc<dyn Rerank>, candidates: usize) -> Self {
+        self.reranker = Some(reranker);
+        self.rerank_candidates = candidates;
+        self
+    }
+
     /// Semantic search for casts
     ///
     /// # Errors
@@ -46,13 +71,44 @@ impl CastRetriever {
         #[allow(clippy::cast_possible_wrap)] // limit is user-specified, typically small values
         let results = self
             .database
-            .semantic_search_casts(query_embedding, limit as i64, threshold)
+            .semantic_search_casts(query_embedding, limit as i64, threshold, None)
             .await?;
 
         debug!("Found {} matching casts", results.len());
         Ok(results)
     }
 
+    /// Semantic search for casts within a channel
+    ///
+    /// `channel` is a channel URL or a bare channel name (see `database::channel_url`).
+    ///
+    /// # Errors
+    /// - Embedding generation errors (API failures, preprocessing errors)
+    /// - Database query errors (connection failures, vector search errors)
+    pub async fn semantic_search_in_channel(
+        &self,
+        query: &str,
+        channel: &str,
+        limit: usize,
+        threshold: Option<f32>,
+    ) -> Result<Vec<CastSearchResult>> {
+        debug!(
+            "Performing cast semantic search in channel {}: {}",
+            channel, query
+        );
+
+        let query_embedding = self.embedding_service.generate(query).await?;
+
+        #[allow(clippy::cast_possible_wrap)] // limit is user-specified, typically small values
+        let results = self
+            .database
+            .semantic_search_casts(query_embedding, limit as i64, threshold, Some(channel))
+            .await?;
+
+        debug!("Found {} matching casts in channel", results.len());
+        Ok(results)
+    }
+
     /// Search casts by FID
     ///
     /// # Errors
@@ -105,10 +161,16 @@ impl CastRetriever {
         );
 
         // Query recent casts with text content
-        #[allow(clippy::cast_possible_wrap)] // limit/offset are user-specified, typically small values
-        let casts = sqlx::query_as::<_, crate::models::Cast>(
-            "SELECT * FROM casts WHERE text IS NOT NULL AND text != '' ORDER BY timestamp DESC LIMIT $1 OFFSET $2",
-        )
+        // limit/offset are user-specified, typically small values
+        #[allow(clippy::cast_possible_wrap)]
+        let casts = sqlx::query_as::<_, crate::models::Cast>(&format!(
+            r"
+            SELECT c.* FROM casts c
+            WHERE c.text IS NOT NULL AND c.text != ''
+            AND {NOT_REMOVED}
+            ORDER BY c.timestamp DESC LIMIT $1 OFFSET $2
+            "
+        ))
         .bind(limit as i64)
         .bind(offset as i64)
         .fetch_all(self.database.pool())
@@ -117,6 +179,40 @@ impl CastRetriever {
         Ok(casts)
     }
 
+    /// Recent casts posted to a channel
+    ///
+    /// # Errors
+    /// - Database query errors (connection failures, SQL execution errors)
+    pub async fn search_recent_in_channel(
+        &self,
+        channel: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<crate::models::Cast>> {
+        debug!("Searching recent casts in channel {}", channel);
+
+        let casts = self
+            .database
+            .list_casts(crate::models::CastQuery {
+                fid: None,
+                text_search: None,
+                parent_hash: None,
+                root_hash: None,
+                channel: Some(channel.to_string()),
+                has_mentions: None,
+                has_embeds: None,
+                start_timestamp: None,
+                end_timestamp: None,
+                limit: Some(i64::try_from(limit).unwrap_or(i64::MAX)),
+                offset: Some(i64::try_from(offset).unwrap_or(i64::MAX)),
+                sort_by: Some(crate::models::CastSortBy::Timestamp),
+                sort_order: Some(crate::models::SortOrder::Desc),
+            })
+            .await?;
+
+        Ok(casts)
+    }
+
     /// Search casts with time range filter
     pub async fn search_by_time_range(
         &self,
@@ -127,26 +223,41 @@ impl CastRetriever {
         debug!("Searching casts by time range");
 
         let casts = if let (Some(start), Some(end)) = (start_timestamp, end_timestamp) {
-            sqlx::query_as::<_, crate::models::Cast>(
-                "SELECT * FROM casts WHERE text IS NOT NULL AND timestamp >= $1 AND timestamp <= $2 ORDER BY timestamp DESC LIMIT $3",
-            )
+            sqlx::query_as::<_, crate::models::Cast>(&format!(
+                r"
+                SELECT c.* FROM casts c
+                WHERE c.text IS NOT NULL AND c.timestamp >= $1 AND c.timestamp <= $2
+                AND {NOT_REMOVED}
+                ORDER BY c.timestamp DESC LIMIT $3
+                "
+            ))
             .bind(start)
             .bind(end)
             .bind(i64::try_from(limit).unwrap_or(i64::MAX))
             .fetch_all(self.database.pool())
             .await?
         } else if let Some(start) = start_timestamp {
-            sqlx::query_as::<_, crate::models::Cast>(
-                "SELECT * FROM casts WHERE text IS NOT NULL AND timestamp >= $1 ORDER BY timestamp DESC LIMIT $2",
-            )
+            sqlx::query_as::<_, crate::models::Cast>(&format!(
+                r"
+                SELECT c.* FROM casts c
+                WHERE c.text IS NOT NULL AND c.timestamp >= $1
+                AND {NOT_REMOVED}
+                ORDER BY c.timestamp DESC LIMIT $2
+                "
+            ))
             .bind(start)
             .bind(i64::try_from(limit).unwrap_or(i64::MAX))
             .fetch_all(self.database.pool())
             .await?
         } else if let Some(end) = end_timestamp {
-            sqlx::query_as::<_, crate::models::Cast>(
-                "SELECT * FROM casts WHERE text IS NOT NULL AND timestamp <= $1 ORDER BY timestamp DESC LIMIT $2",
-            )
+            sqlx::query_as::<_, crate::models::Cast>(&format!(
+                r"
+                SELECT c.* FROM casts c
+                WHERE c.text IS NOT NULL AND c.timestamp <= $1
+                AND {NOT_REMOVED}
+                ORDER BY c.timestamp DESC LIMIT $2
+                "
+            ))
             .bind(end)
             .bind(i64::try_from(limit).unwrap_or(i64::MAX))
             .fetch_all(self.database.pool())
@@ -186,8 +297,14 @@ impl CastRetriever {
             reaction_count: Option<i64>,
         }
 
+        let column = self.database.active_embedding_version().await?.map_or_else(
+            || CAST_EMBEDDING_COLUMN.to_string(),
+            |version| version.column(CAST_EMBEDDING_COLUMN),
+        );
+
         // Search with FID filter and engagement metrics
-        let raw_results = sqlx::query_as::<_, RawResult>(
+        let mut tx = self.database.begin_vector_search().await?;
+        let raw_results = sqlx::query_as::<_, RawResult>(&format!(
             r"
             SELECT 
                 ce.message_hash,
@@ -197,8 +314,8 @@ impl CastRetriever {
                 c.parent_hash,
                 c.embeds,
                 c.mentions,
-                1 - (ce.embedding <=> $1) as similarity,
-                (SELECT COUNT(*) FROM casts WHERE parent_hash = ce.message_hash) as reply_count,
+                1 - (ce.{column} <=> $1) as similarity,
+                (SELECT COUNT(*) FROM casts r WHERE r.parent_hash = ce.message_hash AND {REPLY_NOT_REMOVED}) as reply_count,
                 (SELECT COUNT(*) FROM (
                     SELECT *, ROW_NUMBER() OVER (
                         PARTITION BY fid, target_cast_hash 
@@ -209,17 +326,19 @@ impl CastRetriever {
                 ) r WHERE r.rn = 1 AND r.event_type = 'add') as reaction_count
             FROM cast_embeddings ce
             INNER JOIN casts c ON ce.message_hash = c.message_hash
-            WHERE ce.fid = $2 AND 1 - (ce.embedding <=> $1) > $3
-            ORDER BY ce.embedding <=> $1
+            WHERE ce.fid = $2 AND 1 - (ce.{column} <=> $1) > $3
+            AND {NOT_REMOVED}
+            ORDER BY ce.{column} <=> $1
             LIMIT $4
-            ",
-        )
+            "
+        ))
         .bind(&query_embedding)
         .bind(fid)
         .bind(threshold_val)
         .bind(i64::try_from(limit).unwrap_or(i64::MAX))
-        .fetch_all(self.database.pool())
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         let results = raw_results
             .into_iter()
@@ -243,94 +362,81 @@ impl CastRetriever {
         Ok(results)
     }
 
-    /// Keyword search for casts with engagement metrics
+    /// Keyword search for casts using full-text search
+    ///
+    /// Supports `"phrases"`, `-exclusions` and `OR`; results are ordered by
+    /// `ts_rank_cd`, which is also their similarity.
+    ///
+    /// # Errors
+    /// - Database query errors (connection failures, SQL execution errors)
     pub async fn keyword_search(&self, query: &str, limit: usize) -> Result<Vec<CastSearchResult>> {
         debug!("Performing cast keyword search: {}", query);
 
-        #[derive(sqlx::FromRow)]
-        struct RawResult {
-            message_hash: Vec<u8>,
-            fid: i64,
-            text: String,
-            timestamp: i64,
-            parent_hash: Option<Vec<u8>>,
-            embeds: Option<serde_json::Value>,
-            mentions: Option<serde_json::Value>,
-            similarity: f32,
-            reply_count: Option<i64>,
-            reaction_count: Option<i64>,
-        }
-
-        let raw_results = sqlx::query_as::<_, RawResult>(
-            r"
-            SELECT 
-                c.message_hash,
-                c.fid,
-                COALESCE(c.text, '') as text,
-                c.timestamp,
-                c.parent_hash,
-                c.embeds,
-                c.mentions,
-                0.8 as similarity,
-                (SELECT COUNT(*) FROM casts WHERE parent_hash = c.message_hash) as reply_count,
-                (SELECT COUNT(*) FROM (
-                    SELECT *, ROW_NUMBER() OVER (
-                        PARTITION BY fid, target_cast_hash 
-                        ORDER BY timestamp DESC
-                    ) as rn
-                    FROM reactions
-                    WHERE target_cast_hash = c.message_hash
-                ) r WHERE r.rn = 1 AND r.event_type = 'add') as reaction_count
-            FROM casts c
-            WHERE c.text ILIKE $1
-            ORDER BY c.timestamp DESC
-            LIMIT $2
-            ",
-        )
-        .bind(format!("%{query}%"))
-        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
-        .fetch_all(self.database.pool())
-        .await?;
-
-        let results = raw_results
-            .into_iter()
-            .map(|r| CastSearchResult {
-                message_hash: r.message_hash,
-                fid: r.fid,
-                text: r.text,
-                timestamp: r.timestamp,
-                parent_hash: r.parent_hash,
-                embeds: r.embeds,
-                mentions: r.mentions,
-                similarity: r.similarity,
-                reply_count: Some(r.reply_count.unwrap_or(0)),
-                reaction_count: Some(r.reaction_count.unwrap_or(0)),
-                chunk_index: None,
-                chunk_text: None,
-                chunk_strategy: None,
-            })
-            .collect();
-
-        Ok(results)
+        self.database
+            .full_text_search_casts(query, i64::try_from(limit).unwrap_or(i64::MAX))
+            .await
     }
 
     /// Hybrid search combining semantic and keyword
+    ///
+    /// Results are fused by reciprocal rank, then reranked if a reranker is
+    /// set.
     pub async fn hybrid_search(
         &self,
         query: &str,
         limit: usize,
         threshold: Option<f32>,
+    ) -> Result<Vec<CastSearchResult>> {
+        self.filtered_hybrid_search(query, limit, threshold, &CastSearchFilters::default())
+            .await
+    }
+
+    /// [`Self::hybrid_search`] over the casts matching `filters`
+    ///
+    /// # Errors
+    /// - Embedding generation errors (API failures, preprocessing errors)
+    /// - Database query errors (connection failures, vector search errors)
+    pub async fn filtered_hybrid_search(
+        &self,
+        query: &str,
+        limit: usize,
+        threshold: Option<f32>,
+        filters: &CastSearchFilters,
     ) -> Result<Vec<CastSearchResult>> {
         debug!("Performing cast hybrid search: {}", query);
 
+        let candidates = match self.reranker {
+            Some(_) => limit.max(self.rerank_candidates),
+            None => limit,
+        };
+        let candidate_limit = i64::try_from(candidates).unwrap_or(i64::MAX);
+
         // Run both searches in parallel
-        let semantic_results = self.semantic_search(query, limit, threshold);
-        let keyword_results = self.keyword_search(query, limit);
+        let semantic_results = async {
+            let query_embedding = self.embedding_service.generate(query).await?;
+            self.database
+                .filtered_semantic_search_casts(
+                    query_embedding,
+                    candidate_limit,
+                    threshold,
+                    filters,
+                )
+                .await
+        };
+        let keyword_results =
+            self.database
+                .filtered_full_text_search_casts(query, candidate_limit, filters);
 
         let (semantic, keyword) = tokio::try_join!(semantic_results, keyword_results)?;
 
         // Merge and deduplicate results using RRF
-        let merged = Self::merge_results(semantic, keyword);
+        let mut merged = Self::merge_results(semantic, keyword);
+        merged.truncate(candidates);
+
+        if let Some(reranker) = &self.reranker {
+            debug!("Reranking {} casts with {}", merged.len(), reranker.name());
+            merged = rerank::rerank(reranker.as_ref(), query, merged).await;
+        }
 
         Ok(merged.into_iter().take(limit).collect())
     }
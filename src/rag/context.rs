@@ -145,6 +145,38 @@ impl Default for ContextAssembler {
     }
 }
 
+/// A cast placed in the context under its source number
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CastSource {
+    pub number: usize,
+    /// Hex-encoded message hash
+    pub message_hash: String,
+    pub fid: i64,
+    /// Username, display name or `FID n`
+    pub author: String,
+    pub url: String,
+    pub text: String,
+    pub timestamp: i64,
+    pub similarity: f32,
+}
+
+/// Warpcast URL of a cast
+///
+/// Without the author's username the cast is linked by its full hash.
+#[must_use]
+pub fn cast_url(username: Option<&str>, message_hash: &[u8]) -> String {
+    let hash = hex::encode(message_hash);
+    username.map_or_else(
+        || format!("https://warpcast.com/~/conversations/0x{hash}"),
+        |username| {
+            format!(
+                "https://warpcast.com/{username}/0x{}",
+                &hash[..hash.len().min(8)]
+            )
+        },
+    )
+}
+
 /// Assembler for creating context from casts
 pub struct CastContextAssembler {
     max_context_length: usize,
@@ -193,20 +225,32 @@ impl CastContextAssembler {
         results: &[crate::models::CastSearchResult],
         database: &crate::database::Database,
     ) -> crate::errors::Result<String> {
+        Ok(self.assemble_sources(results, database).await?.0)
+    }
+
+    /// Assemble context with author information, returning the numbered casts
+    /// that fit into it
+    ///
+    /// Source `n` is the cast labelled `[Cast n]` in the context.
+    ///
+    /// # Errors
+    /// - Database query errors when fetching author profiles
+    pub async fn assemble_sources(
+        &self,
+        results: &[crate::models::CastSearchResult],
+        database: &crate::database::Database,
+    ) -> crate::errors::Result<(String, Vec<CastSource>)> {
         let mut context = String::new();
+        let mut sources = Vec::new();
         let mut total_length = 0;
 
         for (idx, result) in results.iter().enumerate() {
             // Get author information
             let author = database.get_user_profile(result.fid).await?;
-            let author_display = if let Some(profile) = author {
-                profile
-                    .username
-                    .or(profile.display_name)
-                    .unwrap_or_else(|| format!("FID {}", result.fid))
-            } else {
-                format!("FID {}", result.fid)
-            };
+            let username = author.as_ref().and_then(|profile| profile.username.clone());
+            let author_display = author
+                .and_then(|profile| profile.username.or(profile.display_name))
+                .unwrap_or_else(|| format!("FID {}", result.fid));
 
             let entry = format!(
                 "\n[Cast {}]\nAuthor: {}\nSimilarity: {:.2}%\nContent: {}\n",
@@ -222,9 +266,19 @@ impl CastContextAssembler {
 
             context.push_str(&entry);
             total_length += entry.len();
+            sources.push(CastSource {
+                number: idx + 1,
+                message_hash: hex::encode(&result.message_hash),
+                fid: result.fid,
+                author: author_display,
+                url: cast_url(username.as_deref(), &result.message_hash),
+                text: result.text.clone(),
+                timestamp: result.timestamp,
+                similarity: result.similarity,
+            });
         }
 
-        Ok(context)
+        Ok((context, sources))
     }
 
     /// Create a summary of the retrieved casts
@@ -257,3 +311,21 @@ impl Default for CastContextAssembler {
         Self::new(8000) // Larger default for cast content
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_url() {
+        let hash = [0xab, 0xcd, 0xef, 0x01, 0x23, 0x45];
+        assert_eq!(
+            cast_url(Some("dwr"), &hash),
+            "https://warpcast.com/dwr/0xabcdef01"
+        );
+        assert_eq!(
+            cast_url(None, &hash),
+            "https://warpcast.com/~/conversations/0xabcdef012345"
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This module provides end-to-end RAG functionality for querying Farcaster data:
 //! - Semantic retrieval using vector embeddings
+//! - Optional LLM query rewriting, expansion and filter extraction
 //! - Result ranking and reranking
 //! - Context assembly from retrieved documents
 //! - LLM-based answer generation
@@ -25,20 +26,39 @@
 //! }
 //! ```
 
+pub mod cast_pipeline;
 pub mod cast_retriever;
 pub mod context;
 pub mod pipeline;
 pub mod prompts;
+pub mod query_understanding;
+pub mod rerank;
 pub mod retriever;
 
+pub use cast_pipeline::CastRagQuery;
+pub use cast_pipeline::CastRagResponse;
+pub use cast_pipeline::CastRagService;
+pub use cast_pipeline::Citation;
+pub use cast_pipeline::Citations;
 pub use cast_retriever::CastRetriever;
 pub use context::CastContextAssembler;
+pub use context::CastSource;
 pub use context::ContextAssembler;
+pub use pipeline::RagMetadata;
 pub use pipeline::RagQuery;
 pub use pipeline::RagResponse;
 pub use pipeline::RagService;
+pub use pipeline::RagStream;
 pub use pipeline::RetrievalMethod;
 pub use prompts::*;
+pub use query_understanding::QueryAnalysis;
+pub use query_understanding::QueryFilters;
+pub use query_understanding::QueryUnderstanding;
+pub use rerank::CrossEncoderReranker;
+pub use rerank::HeuristicReranker;
+pub use rerank::LlmReranker;
+pub use rerank::Rerank;
+pub use rerank::RerankDocument;
 pub use retriever::Retriever;
 
 use crate::errors::Result;
@@ -61,4 +81,6 @@ pub enum MatchType {
     Keyword,
     /// Combined semantic and keyword match
     Hybrid,
+    /// Profile named in the question by username or FID
+    Named,
 }
@@ -1,4 +1,4 @@
-//! Complete RAG pipeline: Retrieve -> Rank -> Generate
+//! Complete RAG pipeline: Understand -> Retrieve -> Rerank -> Generate
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -12,15 +12,28 @@ use crate::embeddings::EmbeddingService;
 use crate::errors::Result;
 use crate::llm::ChatMessage;
 use crate::llm::LlmService;
+use crate::llm::StreamingResponse;
+use crate::rag::query_understanding::QueryAnalysis;
+use crate::rag::query_understanding::QueryUnderstanding;
+use crate::rag::rerank;
+use crate::rag::rerank::reranker_from_config;
+use crate::rag::rerank::Rerank;
+use crate::rag::retriever::Reranker;
 use crate::rag::ContextAssembler;
 use crate::rag::Retriever;
 use crate::rag::SearchResult;
 
+/// RRF constant for fusing the result sets of query variants
+const RRF_K: f32 = 60.0;
+
 /// Complete RAG service
 pub struct RagService {
     retriever: Retriever,
     context_assembler: ContextAssembler,
     llm_service: LlmService,
+    reranker: Option<Arc<dyn Rerank>>,
+    rerank_candidates: usize,
+    query_understanding: Option<QueryUnderstanding>,
 }
 
 impl RagService {
@@ -30,18 +43,24 @@ impl RagService {
     /// - Database connection errors
     /// - Embedding service configuration errors (invalid API keys, endpoints)
     /// - LLM service configuration errors (missing or invalid LLM config)
+    /// - Reranker initialization errors (see `[rerank]`)
     pub async fn new(config: &AppConfig) -> Result<Self> {
         let database = Arc::new(Database::from_config(config).await?);
         let embedding_service = Arc::new(EmbeddingService::new(config)?);
-        let retriever = Retriever::new(database, embedding_service);
-        let context_assembler = ContextAssembler::default();
+        let reranker = reranker_from_config(config, database.clone()).await?;
         let llm_service = LlmService::new(config)?;
 
-        Ok(Self {
-            retriever,
-            context_assembler,
-            llm_service,
-        })
+        let mut service = Self::from_services(database, embedding_service, llm_service.clone());
+        if let Some(reranker) = reranker {
+            service = service.with_reranker(reranker, config.rerank.candidates);
+        }
+        if config.query_understanding.enabled {
+            service = service.with_query_understanding(QueryUnderstanding::new(
+                llm_service,
+                config.query_understanding.clone(),
+            ));
+        }
+        Ok(service)
     }
 
     /// Create from existing services
@@ -58,9 +77,35 @@ impl RagService {
             retriever,
             context_assembler,
             llm_service,
+            reranker: None,
+            rerank_candidates: 0,
+            query_understanding: None,
         }
     }
 
+    /// Rerank retrieved profiles before they reach the LLM
+    ///
+    /// `candidates` profiles are retrieved (or the query limit, if larger)
+    /// and cut to the query limit after reranking.
+    #[must_use]
+    pub fn with_reranker(mut self, reranker: Arc<dyn Rerank>, candidates: usize) -> Self {
+        self.reranker = Some(reranker);
+        self.rerank_candidates = candidates;
+        self
+    }
+
+    /// Rewrite and expand questions with the LLM before retrieval
+    ///
+    /// The question, its rewrite and paraphrases are retrieved with the query
+    /// method and its hypothetical answers by semantic search, in parallel,
+    /// and the result sets are fused with RRF. Profiles named by username or
+    /// FID come first. The analysis is returned in `metadata.query_analysis`.
+    #[must_use]
+    pub fn with_query_understanding(mut self, query_understanding: QueryUnderstanding) -> Self {
+        self.query_understanding = Some(query_understanding);
+        self
+    }
+
     /// Perform a complete RAG query
     ///
     /// # Errors
@@ -89,57 +134,165 @@ impl RagService {
     /// - Invalid query parameters (negative limits, invalid temperature range)
     pub async fn query_with_options(&self, query: RagQuery) -> Result<RagResponse> {
         info!("Processing RAG query: {}", query.question);
+        let retrieved = self.retrieve(&query).await?;
+
+        // Step 3: Generate answer using LLM
+        debug!("Step 3: Generating answer");
+        let answer = self
+            .llm_service
+            .generate_with_params(&retrieved.prompt, query.temperature, query.max_tokens)
+            .await?;
+
+        info!("RAG query completed successfully");
+
+        Ok(RagResponse {
+            answer,
+            sources: retrieved.sources,
+            context: retrieved.context,
+            metadata: retrieved.metadata,
+            query: query.question,
+        })
+    }
+
+    /// Perform a RAG query, streaming the answer as the LLM generates it
+    ///
+    /// Retrieval finishes before this returns, so the sources are available
+    /// before the first token.
+    ///
+    /// # Errors
+    /// - Document retrieval errors (embedding generation, database queries)
+    /// - LLM errors opening the stream (API failures, unsupported provider)
+    pub async fn query_stream(&self, query: RagQuery) -> Result<RagStream> {
+        info!("Processing streaming RAG query: {}", query.question);
+        let retrieved = self.retrieve(&query).await?;
+
+        debug!("Step 3: Streaming answer");
+        let answer = self
+            .llm_service
+            .generate_stream_with_params(&retrieved.prompt, query.temperature, query.max_tokens)
+            .await?;
+
+        Ok(RagStream {
+            answer,
+            sources: retrieved.sources,
+            context: retrieved.context,
+            metadata: retrieved.metadata,
+            query: query.question,
+        })
+    }
+
+    /// Retrieve documents for a query and build the LLM prompt
+    async fn retrieve(&self, query: &RagQuery) -> Result<Retrieved> {
+        // Step 0: Understand the question
+        let query_analysis = match &self.query_understanding {
+            Some(understanding) => {
+                debug!("Step 0: Analyzing query");
+                Some(understanding.analyze(&query.question).await)
+            }
+            None => None,
+        };
 
         // Step 1: Retrieve relevant documents
         debug!("Step 1: Retrieving documents");
-        let results = match query.retrieval_method {
-            RetrievalMethod::Semantic => {
-                self.retriever
-                    .semantic_search(&query.question, query.retrieval_limit, None)
-                    .await?
-            }
-            RetrievalMethod::Keyword => {
-                self.retriever
-                    .keyword_search(&query.question, query.retrieval_limit)
-                    .await?
-            }
-            RetrievalMethod::Hybrid => {
-                self.retriever
-                    .hybrid_search(&query.question, query.retrieval_limit)
+        let limit = match self.reranker {
+            Some(_) => query.retrieval_limit.max(self.rerank_candidates),
+            None => query.retrieval_limit,
+        };
+        let mut results = match &query_analysis {
+            Some(analysis) => {
+                self.retrieve_variants(analysis, query.retrieval_method, limit)
                     .await?
             }
-            RetrievalMethod::Auto => {
-                self.retriever
-                    .auto_search(&query.question, query.retrieval_limit)
+            None => {
+                self.retrieve_query(&query.question, query.retrieval_method, limit)
                     .await?
             }
         };
 
         debug!("Retrieved {} results", results.len());
 
+        if let Some(reranker) = &self.reranker {
+            debug!("Reranking with {}", reranker.name());
+            results = rerank::rerank(reranker.as_ref(), &query.question, results).await;
+        }
+        results.truncate(query.retrieval_limit);
+
         // Step 2: Assemble context
         debug!("Step 2: Assembling context");
-        let (context, metadata) = self.context_assembler.assemble_with_metadata(&results);
-
-        // Step 3: Generate answer using LLM
-        debug!("Step 3: Generating answer");
+        let (context, sources) = self.context_assembler.assemble_with_metadata(&results);
         let prompt = self.build_prompt(&query.question, &context);
-        let answer = self
-            .llm_service
-            .generate_with_params(&prompt, query.temperature, query.max_tokens)
-            .await?;
-
-        info!("RAG query completed successfully");
 
-        Ok(RagResponse {
-            answer,
+        Ok(Retrieved {
             sources: results,
             context,
-            metadata,
-            query: query.question,
+            metadata: RagMetadata {
+                sources,
+                query_analysis,
+            },
+            prompt,
         })
     }
 
+    /// Retrieve documents for one query text
+    async fn retrieve_query(
+        &self,
+        text: &str,
+        method: RetrievalMethod,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        match method {
+            RetrievalMethod::Semantic => self.retriever.semantic_search(text, limit, None).await,
+            RetrievalMethod::Keyword => self.retriever.keyword_search(text, limit).await,
+            RetrievalMethod::Hybrid => self.retriever.hybrid_search(text, limit).await,
+            RetrievalMethod::Auto => self.retriever.auto_search(text, limit).await,
+        }
+    }
+
+    /// Retrieve every variant of an analyzed question and fuse them with RRF
+    async fn retrieve_variants(
+        &self,
+        analysis: &QueryAnalysis,
+        method: RetrievalMethod,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let variants: Vec<(&str, RetrievalMethod)> = analysis
+            .queries()
+            .into_iter()
+            .map(|text| (text, method))
+            .chain(
+                analysis
+                    .hypothetical_answers
+                    .iter()
+                    .map(|answer| (answer.as_str(), RetrievalMethod::Semantic)),
+            )
+            .collect();
+        debug!("Retrieving {} query variants", variants.len());
+
+        let result_sets = futures::future::try_join_all(
+            variants
+                .into_iter()
+                .map(|(text, method)| self.retrieve_query(text, method, limit)),
+        )
+        .await?;
+        let mut results = Reranker::reciprocal_rank_fusion(result_sets, RRF_K);
+
+        let filters = &analysis.filters;
+        if !filters.usernames.is_empty() || !filters.fids.is_empty() {
+            let named = self
+                .retriever
+                .named_profiles(&filters.usernames, &filters.fids)
+                .await?;
+            results.retain(|result| {
+                !named
+                    .iter()
+                    .any(|profile| profile.profile.fid == result.profile.fid)
+            });
+            results.splice(0..0, named);
+        }
+
+        Ok(results)
+    }
+
     /// Search for profiles without LLM generation
     pub async fn search_profiles(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
         self.retriever.auto_search(query, limit).await
@@ -202,13 +355,40 @@ pub enum RetrievalMethod {
     Auto,
 }
 
+/// Metadata of a RAG response
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RagMetadata {
+    /// Fid, username, score and match type of each source in the context
+    pub sources: Vec<HashMap<String, String>>,
+    /// Rewritten queries and extracted filters, when query understanding is
+    /// enabled
+    pub query_analysis: Option<QueryAnalysis>,
+}
+
+/// Retrieved documents and the prompt built from them
+struct Retrieved {
+    sources: Vec<SearchResult>,
+    context: String,
+    metadata: RagMetadata,
+    prompt: String,
+}
+
+/// RAG response whose answer is still being generated
+pub struct RagStream {
+    pub answer: StreamingResponse,
+    pub sources: Vec<SearchResult>,
+    pub context: String,
+    pub metadata: RagMetadata,
+    pub query: String,
+}
+
 /// RAG response
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct RagResponse {
     pub answer: String,
     pub sources: Vec<SearchResult>,
     pub context: String,
-    pub metadata: Vec<HashMap<String, String>>,
+    pub metadata: RagMetadata,
     pub query: String,
 }
 
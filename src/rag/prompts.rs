@@ -48,6 +48,30 @@ Answer:"#
     )
 }
 
+/// Build cast RAG prompt that requires numbered citations
+#[must_use]
+pub fn build_cited_cast_rag_prompt(question: &str, context: &str) -> String {
+    format!(
+        r"You are an expert Farcaster analyst helping users understand discussions, trends, and community sentiment.
+
+Context: The following are relevant Farcaster casts (posts), each labelled [Cast N]:
+
+{context}
+
+Question: {question}
+
+Instructions:
+1. Answer using only the casts above
+2. Cite the cast behind every claim inline with its number in square brackets, e.g. [3] for Cast 3, or [1][4] for several casts
+3. Only cite numbers of casts listed above; never invent sources
+4. Highlight any consensus or disagreements in the discussion
+5. If the casts don't contain enough information, say so instead of guessing
+6. Keep your answer concise but substantive
+
+Answer:"
+    )
+}
+
 /// Build trend analysis prompt
 #[must_use]
 pub fn build_trend_analysis_prompt(casts: &str, time_period: &str) -> String {
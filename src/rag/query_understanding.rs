@@ -0,0 +1,360 @@
+//! Query understanding: Rewrite -> Expand -> Extract filters
+//!
+//! One LLM call turns a question into a concise search query, paraphrases of
+//! it, hypothetical answers (HyDE) and the filters it states. `RagService`
+//! retrieves every variant in parallel and fuses the result sets with RRF.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use chrono::DateTime;
+use chrono::Days;
+use chrono::NaiveDate;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::debug;
+use tracing::warn;
+
+use crate::config::QueryUnderstandingConfig;
+use crate::errors::Result;
+use crate::llm::prompts::RagPrompts;
+use crate::llm::LlmService;
+
+/// Longest LLM answer requested, in tokens
+const MAX_ANALYSIS_TOKENS: usize = 800;
+
+/// Constraints stated in a question
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueryFilters {
+    /// Lowercase usernames without `@`
+    pub usernames: Vec<String>,
+    pub fids: Vec<i64>,
+    /// Inclusive start of the date range
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive end of the date range
+    pub until: Option<DateTime<Utc>>,
+    /// Lowercase channel names without `/`
+    pub channels: Vec<String>,
+}
+
+impl QueryFilters {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.usernames.is_empty()
+            && self.fids.is_empty()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.channels.is_empty()
+    }
+}
+
+/// A question as understood by the LLM
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueryAnalysis {
+    pub original: String,
+    /// Concise search query, if it differs from the question
+    pub rewritten: Option<String>,
+    pub paraphrases: Vec<String>,
+    /// Passages a relevant document might contain, for semantic search
+    pub hypothetical_answers: Vec<String>,
+    pub filters: QueryFilters,
+}
+
+impl QueryAnalysis {
+    /// The question alone, used when the LLM cannot be reached
+    #[must_use]
+    pub fn passthrough(question: &str) -> Self {
+        Self {
+            original: question.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// The question, its rewrite and its paraphrases, without duplicates
+    #[must_use]
+    pub fn queries(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        std::iter::once(self.original.as_str())
+            .chain(self.rewritten.as_deref())
+            .chain(self.paraphrases.iter().map(String::as_str))
+            .filter(|query| seen.insert(query.to_lowercase()))
+            .collect()
+    }
+}
+
+/// LLM stage rewriting and expanding questions before retrieval
+pub struct QueryUnderstanding {
+    llm_service: LlmService,
+    config: QueryUnderstandingConfig,
+}
+
+impl QueryUnderstanding {
+    #[must_use]
+    pub const fn new(llm_service: LlmService, config: QueryUnderstandingConfig) -> Self {
+        Self {
+            llm_service,
+            config,
+        }
+    }
+
+    /// Analyze a question
+    ///
+    /// A failed LLM call or an unreadable answer is logged and the question
+    /// used as is, so query understanding never fails a query.
+    pub async fn analyze(&self, question: &str) -> QueryAnalysis {
+        match self.try_analyze(question).await {
+            Ok(analysis) => {
+                debug!("Query analysis: {:?}", analysis);
+                analysis
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Query understanding failed, using the question as is: {}",
+                    e
+                );
+                QueryAnalysis::passthrough(question)
+            }
+        }
+    }
+
+    async fn try_analyze(&self, question: &str) -> Result<QueryAnalysis> {
+        let values = HashMap::from([
+            (
+                "today".to_string(),
+                Utc::now().format("%Y-%m-%d").to_string(),
+            ),
+            ("query".to_string(), question.to_string()),
+            (
+                "paraphrases".to_string(),
+                self.config.paraphrases.to_string(),
+            ),
+            (
+                "hypothetical_answers".to_string(),
+                self.config.hypothetical_answers.to_string(),
+            ),
+        ]);
+        let prompt = RagPrompts::query_enhancement().render(&values);
+
+        let answer = self
+            .llm_service
+            .generate_with_params(&prompt, 0.3, MAX_ANALYSIS_TOKENS)
+            .await?;
+        parse_analysis(question, &answer, &self.config)
+    }
+}
+
+/// Analysis as returned by the LLM
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawAnalysis {
+    rewritten: Option<String>,
+    paraphrases: Vec<String>,
+    hypothetical_answers: Vec<String>,
+    filters: RawFilters,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawFilters {
+    usernames: Vec<String>,
+    fids: Vec<i64>,
+    since: Option<String>,
+    until: Option<String>,
+    channels: Vec<String>,
+}
+
+/// Parse the JSON object in an LLM answer, tolerating surrounding text and
+/// code fences
+fn parse_analysis(
+    question: &str,
+    answer: &str,
+    config: &QueryUnderstandingConfig,
+) -> Result<QueryAnalysis> {
+    let json = match (answer.find('{'), answer.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &answer[start..=end],
+        _ => {
+            return Err(crate::SnapRagError::Custom(
+                "No JSON object in query analysis".to_string(),
+            ))
+        }
+    };
+    let raw: RawAnalysis = serde_json::from_str(json)?;
+
+    let rewritten = raw
+        .rewritten
+        .map(|query| query.trim().to_string())
+        .filter(|query| !query.is_empty() && !query.eq_ignore_ascii_case(question.trim()));
+    let filters = if config.extract_filters {
+        clean_filters(raw.filters)
+    } else {
+        QueryFilters::default()
+    };
+
+    Ok(QueryAnalysis {
+        original: question.to_string(),
+        rewritten,
+        paraphrases: clean_texts(raw.paraphrases, config.paraphrases),
+        hypothetical_answers: clean_texts(raw.hypothetical_answers, config.hypothetical_answers),
+        filters,
+    })
+}
+
+/// Trimmed, non-empty, distinct texts, at most `limit`
+fn clean_texts(texts: Vec<String>, limit: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    texts
+        .into_iter()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty() && seen.insert(text.to_lowercase()))
+        .take(limit)
+        .collect()
+}
+
+/// Trimmed, lowercase, distinct names without `prefix`
+fn clean_names(names: Vec<String>, prefix: char) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| name.trim().trim_start_matches(prefix).to_lowercase())
+        .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+        .collect()
+}
+
+fn clean_filters(raw: RawFilters) -> QueryFilters {
+    let mut fids: Vec<i64> = raw.fids.into_iter().filter(|&fid| fid > 0).collect();
+    fids.sort_unstable();
+    fids.dedup();
+
+    let day = |value: Option<String>| {
+        value.and_then(|value| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok())
+    };
+    let since = day(raw.since).map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc());
+    let until = day(raw.until)
+        .and_then(|day| day.checked_add_days(Days::new(1)))
+        .map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc());
+    let (since, until) = match (since, until) {
+        (Some(since), Some(until)) if since >= until => (None, None),
+        range => range,
+    };
+
+    QueryFilters {
+        usernames: clean_names(raw.usernames, '@'),
+        fids,
+        since,
+        until,
+        channels: clean_names(raw.channels, '/'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> QueryUnderstandingConfig {
+        QueryUnderstandingConfig {
+            enabled: true,
+            paraphrases: 2,
+            hypothetical_answers: 1,
+            extract_filters: true,
+        }
+    }
+
+    #[test]
+    fn test_parse_analysis() {
+        let answer = r#"Here you go:
+```json
+{
+  "rewritten": "rust developers building on Farcaster",
+  "paraphrases": ["Rust engineers on Farcaster", " ", "rust engineers on farcaster", "Farcaster Rust devs"],
+  "hypothetical_answers": ["I build Farcaster clients in Rust.", "Rustacean."],
+  "filters": {
+    "usernames": ["@Dwr", "dwr", ""],
+    "fids": [3, -1, 3],
+    "since": "2024-01-01",
+    "until": "2024-01-31",
+    "channels": ["/rust"]
+  }
+}
+```"#;
+        let analysis =
+            parse_analysis("Which rust devs does @dwr follow?", answer, &config()).unwrap();
+
+        assert_eq!(
+            analysis.rewritten.as_deref(),
+            Some("rust developers building on Farcaster")
+        );
+        assert_eq!(
+            analysis.paraphrases,
+            ["Rust engineers on Farcaster", "Farcaster Rust devs"]
+        );
+        assert_eq!(
+            analysis.hypothetical_answers,
+            ["I build Farcaster clients in Rust."]
+        );
+        assert_eq!(analysis.filters.usernames, ["dwr"]);
+        assert_eq!(analysis.filters.fids, [3]);
+        assert_eq!(analysis.filters.channels, ["rust"]);
+        assert_eq!(
+            analysis.filters.since.unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            analysis.filters.until.unwrap().to_rfc3339(),
+            "2024-02-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_analysis_defaults_and_disabled_filters() {
+        let config = QueryUnderstandingConfig {
+            extract_filters: false,
+            ..config()
+        };
+        let analysis = parse_analysis(
+            "AI builders",
+            r#"{"rewritten": "ai builders", "filters": {"usernames": ["vitalik"]}}"#,
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(analysis.rewritten, None);
+        assert!(analysis.paraphrases.is_empty());
+        assert!(analysis.filters.is_empty());
+        assert_eq!(analysis.queries(), ["AI builders"]);
+    }
+
+    #[test]
+    fn test_parse_analysis_rejects_non_json() {
+        assert!(parse_analysis("q", "I cannot help with that.", &config()).is_err());
+        assert!(parse_analysis("q", "{ not json }", &config()).is_err());
+    }
+
+    #[test]
+    fn test_inverted_date_range_is_dropped() {
+        let filters = clean_filters(RawFilters {
+            since: Some("2024-05-01".to_string()),
+            until: Some("2024-04-01".to_string()),
+            ..RawFilters::default()
+        });
+        assert_eq!(filters.since, None);
+        assert_eq!(filters.until, None);
+    }
+
+    #[test]
+    fn test_queries_are_distinct() {
+        let analysis = QueryAnalysis {
+            original: "Who builds frames?".to_string(),
+            rewritten: Some("frame developers".to_string()),
+            paraphrases: vec![
+                "Frame Developers".to_string(),
+                "frames builders".to_string(),
+            ],
+            ..QueryAnalysis::default()
+        };
+        assert_eq!(
+            analysis.queries(),
+            ["Who builds frames?", "frame developers", "frames builders"]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Local cross-encoder reranker
+//!
+//! Scores each query/document pair jointly with a BERT cross-encoder such as
+//! `cross-encoder/ms-marco-MiniLM-L-6-v2`. Runs on CUDA or Metal when
+//! available, or on the CPU. Inference runs on the blocking thread pool so it
+//! does not stall the async runtime.
+//!
+//! This reranker is only available when the `local-gpu` feature is enabled.
+
+#[cfg(feature = "local-gpu")]
+use std::sync::Arc;
+
+use async_trait::async_trait;
+#[cfg(feature = "local-gpu")]
+use candle_core::DType;
+#[cfg(feature = "local-gpu")]
+use candle_core::Device;
+#[cfg(feature = "local-gpu")]
+use candle_core::IndexOp;
+#[cfg(feature = "local-gpu")]
+use candle_core::Module;
+#[cfg(feature = "local-gpu")]
+use candle_core::Tensor;
+#[cfg(feature = "local-gpu")]
+use candle_nn::Linear;
+#[cfg(feature = "local-gpu")]
+use candle_nn::VarBuilder;
+#[cfg(feature = "local-gpu")]
+use candle_transformers::models::bert::BertModel;
+#[cfg(feature = "local-gpu")]
+use candle_transformers::models::bert::Config as BertConfig;
+#[cfg(feature = "local-gpu")]
+use tokenizers::Tokenizer;
+#[cfg(feature = "local-gpu")]
+use tokenizers::TruncationParams;
+#[cfg(feature = "local-gpu")]
+use tracing::info;
+
+use super::Rerank;
+use super::RerankDocument;
+use crate::errors::Result;
+use crate::errors::SnapragError;
+
+/// Longest query/document pair scored, in tokens
+#[cfg(feature = "local-gpu")]
+const MAX_PAIR_TOKENS: usize = 512;
+
+/// Reranker scoring query/document pairs with a local cross-encoder
+#[cfg(feature = "local-gpu")]
+pub struct CrossEncoderReranker {
+    model: Arc<CrossEncoder>,
+}
+
+/// Loaded cross-encoder, shared with the blocking tasks that run it
+#[cfg(feature = "local-gpu")]
+struct CrossEncoder {
+    tokenizer: Tokenizer,
+    model: BertModel,
+    pooler: Linear,
+    classifier: Linear,
+    device: Device,
+}
+
+#[cfg(feature = "local-gpu")]
+impl CrossEncoderReranker {
+    /// Download (if needed) and load a cross-encoder from `HuggingFace`
+    ///
+    /// `cpu` keeps the model on the CPU even when a GPU is available.
+    ///
+    /// # Errors
+    /// - Model download errors
+    /// - Tokenizer or weight loading errors
+    pub async fn new(model_name: &str, cpu: bool) -> Result<Self> {
+        info!("Initializing cross-encoder reranker: {}", model_name);
+
+        let device = if cpu {
+            Device::Cpu
+        } else {
+            crate::embeddings::local_gpu::LocalGPUClient::get_device(None)?
+        };
+        info!("Using device: {:?}", device);
+
+        let model_path =
+            crate::embeddings::local_gpu::LocalGPUClient::download_model(model_name).await?;
+        let mut tokenizer =
+            crate::embeddings::local_gpu::LocalGPUClient::load_tokenizer(&model_path)?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_PAIR_TOKENS,
+                ..TruncationParams::default()
+            }))
+            .map_err(|e| {
+                SnapragError::EmbeddingError(format!("Failed to configure truncation: {}", e))
+            })?;
+
+        let config_content = std::fs::read_to_string(model_path.join("config.json"))
+            .map_err(|e| SnapragError::EmbeddingError(format!("Failed to read config: {}", e)))?;
+        let config: BertConfig = serde_json::from_str(&config_content)
+            .map_err(|e| SnapragError::EmbeddingError(format!("Failed to parse config: {}", e)))?;
+
+        let model_file = model_path.join("model.safetensors");
+        let vb =
+            unsafe { VarBuilder::from_mmaped_safetensors(&[&model_file], DType::F32, &device)? };
+        let pooler = candle_nn::linear(
+            config.hidden_size,
+            config.hidden_size,
+            vb.pp("bert.pooler.dense"),
+        )?;
+        let classifier = candle_nn::linear(config.hidden_size, 1, vb.pp("classifier"))?;
+        let model = BertModel::load(vb, &config).map_err(|e| {
+            SnapragError::EmbeddingError(format!("Failed to load cross-encoder: {}", e))
+        })?;
+
+        info!("Cross-encoder loaded successfully");
+        Ok(Self {
+            model: Arc::new(CrossEncoder {
+                tokenizer,
+                model,
+                pooler,
+                classifier,
+                device,
+            }),
+        })
+    }
+}
+
+#[cfg(feature = "local-gpu")]
+impl CrossEncoder {
+    /// Relevance logit of one query/document pair
+    fn score_pair(&self, query: &str, document: &str) -> Result<f32> {
+        let encoding = self
+            .tokenizer
+            .encode((query, document), true)
+            .map_err(|e| SnapragError::EmbeddingError(format!("Tokenization failed: {}", e)))?;
+
+        let input_ids = Tensor::new(encoding.get_ids(), &self.device)?.unsqueeze(0)?;
+        let token_type_ids = Tensor::new(encoding.get_type_ids(), &self.device)?.unsqueeze(0)?;
+        let attention_mask =
+            Tensor::new(encoding.get_attention_mask(), &self.device)?.unsqueeze(0)?;
+
+        let hidden = self
+            .model
+            .forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+        // Classify the [CLS] token through the BERT pooler
+        let cls = hidden.i((.., 0))?;
+        let pooled = self.pooler.forward(&cls)?.tanh()?;
+        let logit = self.classifier.forward(&pooled)?;
+
+        Ok(logit.flatten_all()?.to_vec1::<f32>()?[0])
+    }
+}
+
+#[cfg(feature = "local-gpu")]
+#[async_trait]
+impl Rerank for CrossEncoderReranker {
+    fn name(&self) -> &'static str {
+        "cross-encoder"
+    }
+
+    async fn score(&self, query: &str, documents: &[RerankDocument]) -> Result<Vec<f32>> {
+        let model = Arc::clone(&self.model);
+        let query = query.to_string();
+        let texts: Vec<String> = documents.iter().map(|d| d.text.clone()).collect();
+
+        tokio::task::spawn_blocking(move || {
+            texts
+                .iter()
+                .map(|text| model.score_pair(&query, text))
+                .collect()
+        })
+        .await
+        .map_err(|e| SnapragError::EmbeddingError(format!("Cross-encoder task failed: {}", e)))?
+    }
+}
+
+// Stub implementation when local-gpu feature is disabled
+#[cfg(not(feature = "local-gpu"))]
+pub struct CrossEncoderReranker;
+
+#[cfg(not(feature = "local-gpu"))]
+impl CrossEncoderReranker {
+    #[allow(clippy::unused_async)]
+    pub async fn new(_model_name: &str, _cpu: bool) -> Result<Self> {
+        Err(SnapragError::ConfigError(
+            "Local GPU support not compiled. Enable 'local-gpu' feature to use cross-encoder reranking.".to_string()
+        ))
+    }
+}
+
+#[cfg(not(feature = "local-gpu"))]
+#[async_trait]
+impl Rerank for CrossEncoderReranker {
+    fn name(&self) -> &'static str {
+        "cross-encoder"
+    }
+
+    async fn score(&self, _query: &str, _documents: &[RerankDocument]) -> Result<Vec<f32>> {
+        Err(SnapragError::ConfigError(
+            "Local GPU support not compiled. Enable 'local-gpu' feature to use cross-encoder reranking.".to_string()
+        ))
+    }
+}
@@ -0,0 +1,248 @@
+//! Heuristic reranker mixing similarity with cast and author signals
+//!
+//! Each signal is scaled into `0..1` across the candidates and the final score
+//! is their weighted sum:
+//! - similarity: retrieval score, relative to the best candidate
+//! - engagement: `ln(1 + reactions + 2 * replies)`, relative to the best
+//!   candidate
+//! - recency: halves every `recency_half_life_days`
+//! - reputation: `ln(1 + followers)` of the author, relative to the best
+//!   candidate
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Rerank;
+use super::RerankDocument;
+use crate::config::HeuristicWeights;
+use crate::database::Database;
+use crate::errors::Result;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Reranker combining retrieval similarity, engagement, recency and reputation
+pub struct HeuristicReranker {
+    weights: HeuristicWeights,
+    recency_half_life_days: f32,
+    database: Option<Arc<Database>>,
+}
+
+impl HeuristicReranker {
+    /// Create a reranker without author reputation
+    #[must_use]
+    pub const fn new(weights: HeuristicWeights, recency_half_life_days: f32) -> Self {
+        Self {
+            weights,
+            recency_half_life_days,
+            database: None,
+        }
+    }
+
+    /// Look up author follower counts for the reputation signal
+    #[must_use]
+    pub fn with_database(mut self, database: Arc<Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Weighted scores of `documents` given author follower counts
+    #[allow(clippy::cast_precision_loss)] // Counts and ages are far below 2^52
+    #[allow(clippy::cast_possible_truncation)] // Scores are weighted sums of 0..1 signals
+    fn combine(
+        &self,
+        documents: &[RerankDocument],
+        followers: &HashMap<i64, i64>,
+        now: i64,
+    ) -> Vec<f32> {
+        let similarity = relative_to_max(
+            documents
+                .iter()
+                .map(|d| f64::from(d.score).max(0.0))
+                .collect(),
+        );
+        let engagement = relative_to_max(
+            documents
+                .iter()
+                .map(|d| {
+                    let interactions =
+                        d.reaction_count.unwrap_or(0) + 2 * d.reply_count.unwrap_or(0);
+                    (interactions.max(0) as f64).ln_1p()
+                })
+                .collect(),
+        );
+        let reputation = relative_to_max(
+            documents
+                .iter()
+                .map(|d| (followers.get(&d.fid).copied().unwrap_or(0).max(0) as f64).ln_1p())
+                .collect(),
+        );
+
+        documents
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                let recency = d.timestamp.map_or(0.0, |timestamp| {
+                    let age_days = (now - timestamp).max(0) as f64 / SECONDS_PER_DAY;
+                    0.5_f64.powf(age_days / f64::from(self.recency_half_life_days))
+                });
+                let weights = &self.weights;
+                let score: f64 = [
+                    (weights.similarity, similarity[i]),
+                    (weights.engagement, engagement[i]),
+                    (weights.recency, recency),
+                    (weights.reputation, reputation[i]),
+                ]
+                .into_iter()
+                .map(|(weight, signal)| f64::from(weight) * signal)
+                .sum();
+                score as f32
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Rerank for HeuristicReranker {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    async fn score(&self, _query: &str, documents: &[RerankDocument]) -> Result<Vec<f32>> {
+        let followers = match &self.database {
+            Some(database) if self.weights.reputation != 0.0 => {
+                let mut fids: Vec<i64> = documents.iter().map(|d| d.fid).collect();
+                fids.sort_unstable();
+                fids.dedup();
+                database.follower_counts(&fids).await?
+            }
+            _ => HashMap::new(),
+        };
+
+        Ok(self.combine(documents, &followers, chrono::Utc::now().timestamp()))
+    }
+}
+
+/// Divide non-negative values by the largest one
+fn relative_to_max(values: Vec<f64>) -> Vec<f64> {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    if max <= f64::EPSILON {
+        return vec![0.0; values.len()];
+    }
+    values.into_iter().map(|v| v / max).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CastSearchResult;
+    use crate::rag::rerank::Rerankable;
+
+    /// Unix time the scores are computed at
+    const NOW: i64 = 1_700_000_000;
+
+    /// A cast posted `days_old` days before `NOW`, stored with its Farcaster
+    /// timestamp like casts coming out of the database
+    fn document(fid: i64, score: f32, days_old: i64, reactions: i64) -> RerankDocument {
+        let posted = crate::unix_to_farcaster_timestamp(NOW as u64) as i64 - days_old * 86_400;
+        CastSearchResult {
+            message_hash: Vec::new(),
+            fid,
+            text: String::new(),
+            timestamp: posted,
+            parent_hash: None,
+            embeds: None,
+            mentions: None,
+            similarity: score,
+            reply_count: Some(0),
+            reaction_count: Some(reactions),
+            chunk_index: None,
+            chunk_text: None,
+            chunk_strategy: None,
+        }
+        .rerank_document()
+    }
+
+    fn only(weights: HeuristicWeights) -> HeuristicReranker {
+        HeuristicReranker::new(weights, 30.0)
+    }
+
+    const NONE: HeuristicWeights = HeuristicWeights {
+        similarity: 0.0,
+        engagement: 0.0,
+        recency: 0.0,
+        reputation: 0.0,
+    };
+
+    fn assert_scores(scores: &[f32], expected: &[f32]) {
+        assert_eq!(scores.len(), expected.len());
+        for (score, expected) in scores.iter().zip(expected) {
+            assert!(
+                (score - expected).abs() < 1e-5,
+                "{scores:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_similarity_is_relative_to_best() {
+        let reranker = only(HeuristicWeights {
+            similarity: 1.0,
+            ..NONE
+        });
+        let scores = reranker.combine(
+            &[
+                document(1, 0.2, 0, 0),
+                document(2, 0.6, 0, 0),
+                document(3, 0.4, 0, 0),
+            ],
+            &HashMap::new(),
+            NOW,
+        );
+        assert_scores(&scores, &[1.0 / 3.0, 1.0, 2.0 / 3.0]);
+    }
+
+    #[test]
+    fn test_recency_halves_every_half_life() {
+        let reranker = only(HeuristicWeights {
+            recency: 1.0,
+            ..NONE
+        });
+        let mut undated = document(3, 0.5, 0, 0);
+        undated.timestamp = None;
+        let scores = reranker.combine(
+            &[document(1, 0.5, 0, 0), document(2, 0.5, 30, 0), undated],
+            &HashMap::new(),
+            NOW,
+        );
+        assert_scores(&scores, &[1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_engagement_and_reputation_lift_results() {
+        let reranker = HeuristicReranker::new(HeuristicWeights::default(), 30.0);
+        let followers = HashMap::from([(1, 10), (2, 5000)]);
+        let scores = reranker.combine(
+            &[document(1, 0.80, 0, 0), document(2, 0.78, 0, 200)],
+            &followers,
+            NOW,
+        );
+        assert!(scores[1] > scores[0]);
+    }
+
+    #[test]
+    fn test_no_signal_scores_zero() {
+        let scores = only(HeuristicWeights {
+            engagement: 1.0,
+            reputation: 1.0,
+            ..NONE
+        })
+        .combine(
+            &[document(1, 0.5, 0, 0), document(2, 0.5, 0, 0)],
+            &HashMap::new(),
+            NOW,
+        );
+        assert_scores(&scores, &[0.0, 0.0]);
+    }
+}
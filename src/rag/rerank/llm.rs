@@ -0,0 +1,133 @@
+//! Listwise reranking with the configured LLM
+//!
+//! The candidates are shown to the LLM as a numbered list and it answers with
+//! their numbers, most relevant first. Candidates it leaves out keep their
+//! retrieval order after the ones it ranked.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Rerank;
+use super::RerankDocument;
+use crate::errors::Result;
+use crate::llm::LlmService;
+
+/// Characters of each candidate shown to the LLM
+const MAX_DOCUMENT_CHARS: usize = 300;
+
+/// Reranker asking the LLM to order the candidates
+pub struct LlmReranker {
+    llm_service: Arc<LlmService>,
+}
+
+impl LlmReranker {
+    #[must_use]
+    pub const fn new(llm_service: Arc<LlmService>) -> Self {
+        Self { llm_service }
+    }
+}
+
+#[async_trait]
+impl Rerank for LlmReranker {
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Candidate counts are small
+    async fn score(&self, query: &str, documents: &[RerankDocument]) -> Result<Vec<f32>> {
+        let prompt = build_rerank_prompt(query, documents);
+        let answer = self
+            .llm_service
+            .generate_with_params(&prompt, 0.0, 200)
+            .await?;
+
+        let order = parse_ranking(&answer, documents.len());
+        let mut scores = vec![0.0; documents.len()];
+        for (position, index) in order.into_iter().enumerate() {
+            scores[index] = 1.0 - position as f32 / documents.len() as f32;
+        }
+        Ok(scores)
+    }
+}
+
+/// Prompt listing the candidates as `[1] ...`, `[2] ...`
+fn build_rerank_prompt(query: &str, documents: &[RerankDocument]) -> String {
+    let mut candidates = String::new();
+    for (i, document) in documents.iter().enumerate() {
+        let text: String = document
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_DOCUMENT_CHARS)
+            .collect();
+        let _ = writeln!(candidates, "[{}] {}", i + 1, text);
+    }
+
+    format!(
+        r"Rank the following passages by how relevant they are to the query.
+
+Query: {query}
+
+Passages:
+{candidates}
+Answer with the passage numbers only, most relevant first, separated by commas (e.g. 3, 1, 2). Leave out passages that are not relevant."
+    )
+}
+
+/// Candidate indices in the order named by the LLM, followed by the ones it
+/// left out
+///
+/// Numbers are 1-based; out-of-range and repeated numbers are ignored.
+fn parse_ranking(answer: &str, count: usize) -> Vec<usize> {
+    let mut order = Vec::with_capacity(count);
+    let mut ranked = vec![false; count];
+
+    for number in answer
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse::<usize>().ok())
+    {
+        if (1..=count).contains(&number) && !ranked[number - 1] {
+            ranked[number - 1] = true;
+            order.push(number - 1);
+        }
+    }
+
+    order.extend((0..count).filter(|&i| !ranked[i]));
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranking() {
+        assert_eq!(parse_ranking("3, 1, 2", 3), [2, 0, 1]);
+        assert_eq!(parse_ranking("[2] > [4]", 4), [1, 3, 0, 2]);
+        assert_eq!(parse_ranking("2, 2, 9, 0, 1", 3), [1, 0, 2]);
+        assert_eq!(parse_ranking("none are relevant", 2), [0, 1]);
+    }
+
+    #[test]
+    fn test_prompt_numbers_and_truncates_candidates() {
+        let documents = [
+            RerankDocument {
+                text: "rust\nis   fast".to_string(),
+                ..RerankDocument::default()
+            },
+            RerankDocument {
+                text: "x".repeat(1000),
+                ..RerankDocument::default()
+            },
+        ];
+        let prompt = build_rerank_prompt("why rust", &documents);
+
+        assert!(prompt.contains("Query: why rust"));
+        assert!(prompt.contains("[1] rust is fast\n"));
+        assert!(prompt.contains(&format!("[2] {}\n", "x".repeat(MAX_DOCUMENT_CHARS))));
+    }
+}
@@ -0,0 +1,253 @@
+//! Reranking of retrieved documents
+//!
+//! Retrieval orders candidates by vector or keyword similarity alone. A
+//! [`Rerank`] implementation rescores the candidates against the query before
+//! they are cut to the requested limit:
+//! - [`HeuristicReranker`] mixes similarity with engagement, recency and
+//!   author reputation
+//! - [`LlmReranker`] lets the configured LLM order the candidates
+//! - [`CrossEncoderReranker`] scores query/document pairs with a local
+//!   cross-encoder (`local-gpu` feature)
+//!
+//! `[rerank] method` selects the reranker used by `RagService`,
+//! `CastRetriever::hybrid_search` and `ask`.
+
+pub mod cross_encoder;
+pub mod heuristic;
+pub mod llm;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+pub use cross_encoder::CrossEncoderReranker;
+pub use heuristic::HeuristicReranker;
+pub use llm::LlmReranker;
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::config::RerankMethod;
+use crate::database::Database;
+use crate::errors::Result;
+use crate::llm::LlmService;
+use crate::models::CastSearchResult;
+use crate::rag::SearchResult;
+
+/// What a reranker knows about a retrieved document
+#[derive(Debug, Clone, Default)]
+pub struct RerankDocument {
+    pub text: String,
+    /// Score given by retrieval
+    pub score: f32,
+    /// Author of a cast, or the profile itself
+    pub fid: i64,
+    /// Unix timestamp of a cast, converted from its Farcaster timestamp
+    pub timestamp: Option<i64>,
+    pub reaction_count: Option<i64>,
+    pub reply_count: Option<i64>,
+}
+
+/// A reranking stage applied after retrieval
+#[async_trait]
+pub trait Rerank: Send + Sync {
+    /// Short name shown in logs
+    fn name(&self) -> &'static str;
+
+    /// Scores of `documents` for `query`, one per document, higher is better
+    async fn score(&self, query: &str, documents: &[RerankDocument]) -> Result<Vec<f32>>;
+}
+
+/// A retrieval result that can be reranked
+pub trait Rerankable {
+    /// The document as seen by a reranker
+    fn rerank_document(&self) -> RerankDocument;
+
+    /// Replace the retrieval score with the reranked score
+    fn set_rerank_score(&mut self, score: f32);
+}
+
+impl Rerankable for SearchResult {
+    fn rerank_document(&self) -> RerankDocument {
+        let profile = &self.profile;
+        let text = [
+            profile.username.as_deref(),
+            profile.display_name.as_deref(),
+            profile.bio.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        RerankDocument {
+            text,
+            score: self.score,
+            fid: profile.fid,
+            ..RerankDocument::default()
+        }
+    }
+
+    fn set_rerank_score(&mut self, score: f32) {
+        self.score = score;
+    }
+}
+
+impl Rerankable for CastSearchResult {
+    fn rerank_document(&self) -> RerankDocument {
+        RerankDocument {
+            text: self.text.clone(),
+            score: self.similarity,
+            fid: self.fid,
+            // Casts are stored with Farcaster timestamps
+            timestamp: u64::try_from(self.timestamp)
+                .ok()
+                .map(crate::farcaster_to_unix_timestamp)
+                .and_then(|timestamp| i64::try_from(timestamp).ok()),
+            reaction_count: self.reaction_count,
+            reply_count: self.reply_count,
+        }
+    }
+
+    fn set_rerank_score(&mut self, score: f32) {
+        self.similarity = score;
+    }
+}
+
+/// Reorder `items` by their reranked score, best first
+///
+/// A failing reranker is logged and the retrieval order kept, so reranking
+/// never fails a query.
+pub async fn rerank<T: Rerankable>(reranker: &dyn Rerank, query: &str, items: Vec<T>) -> Vec<T> {
+    if items.len() < 2 {
+        return items;
+    }
+
+    let documents: Vec<RerankDocument> = items.iter().map(Rerankable::rerank_document).collect();
+    let scores = match reranker.score(query, &documents).await {
+        Ok(scores) if scores.len() == items.len() => scores,
+        Ok(scores) => {
+            warn!(
+                "⚠️  {} reranker returned {} scores for {} documents, keeping retrieval order",
+                reranker.name(),
+                scores.len(),
+                items.len()
+            );
+            return items;
+        }
+        Err(e) => {
+            warn!(
+                "⚠️  {} reranking failed, keeping retrieval order: {}",
+                reranker.name(),
+                e
+            );
+            return items;
+        }
+    };
+
+    let mut ranked: Vec<(f32, T)> = scores.into_iter().zip(items).collect();
+    ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+        .into_iter()
+        .map(|(score, mut item)| {
+            item.set_rerank_score(score);
+            item
+        })
+        .collect()
+}
+
+/// Build the reranker selected by `[rerank] method`, if any
+///
+/// # Errors
+/// - LLM service configuration errors (`llm` method)
+/// - Model download or loading errors, or a build without the `local-gpu`
+///   feature (`cross_encoder` method)
+pub async fn reranker_from_config(
+    config: &AppConfig,
+    database: Arc<Database>,
+) -> Result<Option<Arc<dyn Rerank>>> {
+    let rerank = &config.rerank;
+    Ok(match rerank.method {
+        RerankMethod::None => None,
+        RerankMethod::Heuristic => Some(Arc::new(
+            HeuristicReranker::new(rerank.weights, rerank.recency_half_life_days)
+                .with_database(database),
+        )),
+        RerankMethod::Llm => Some(Arc::new(LlmReranker::new(Arc::new(LlmService::new(
+            config,
+        )?)))),
+        RerankMethod::CrossEncoder => Some(Arc::new(
+            CrossEncoderReranker::new(&rerank.cross_encoder_model, rerank.cross_encoder_cpu)
+                .await?,
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scores documents by text length
+    struct ByLength;
+
+    #[async_trait]
+    impl Rerank for ByLength {
+        fn name(&self) -> &'static str {
+            "length"
+        }
+
+        async fn score(&self, _query: &str, documents: &[RerankDocument]) -> Result<Vec<f32>> {
+            Ok(documents.iter().map(|d| d.text.len() as f32).collect())
+        }
+    }
+
+    /// Always fails
+    struct Broken;
+
+    #[async_trait]
+    impl Rerank for Broken {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+
+        async fn score(&self, _query: &str, _documents: &[RerankDocument]) -> Result<Vec<f32>> {
+            Err(crate::SnapRagError::Custom("model unavailable".to_string()))
+        }
+    }
+
+    fn cast(text: &str, similarity: f32) -> CastSearchResult {
+        CastSearchResult {
+            message_hash: text.as_bytes().to_vec(),
+            fid: 1,
+            text: text.to_string(),
+            timestamp: 0,
+            parent_hash: None,
+            embeds: None,
+            mentions: None,
+            similarity,
+            reply_count: None,
+            reaction_count: None,
+            chunk_index: None,
+            chunk_text: None,
+            chunk_strategy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rerank_reorders_and_rescores() {
+        let casts = vec![cast("a", 0.9), cast("abc", 0.5), cast("ab", 0.7)];
+        let reranked = rerank(&ByLength, "q", casts).await;
+
+        let texts: Vec<_> = reranked.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["abc", "ab", "a"]);
+        assert!((reranked[0].similarity - 3.0).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_failed_rerank_keeps_retrieval_order() {
+        let casts = vec![cast("a", 0.9), cast("abc", 0.5)];
+        let kept = rerank(&Broken, "q", casts).await;
+
+        let texts: Vec<_> = kept.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["a", "abc"]);
+        assert!((kept[0].similarity - 0.9).abs() < f32::EPSILON);
+    }
+}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use tracing::debug;
 
 use crate::database::Database;
+use crate::embeddings::ActiveEmbeddingService;
 use crate::embeddings::EmbeddingService;
 use crate::errors::Result;
 use crate::models::UserProfile;
@@ -15,16 +16,19 @@ use crate::rag::SearchResult;
 /// Retriever for semantic and hybrid search
 pub struct Retriever {
     database: Arc<Database>,
-    embedding_service: Arc<EmbeddingService>,
+    embedding_service: ActiveEmbeddingService,
 }
 
 impl Retriever {
     /// Create a new retriever
+    ///
+    /// Queries are embedded with the model of the active embedding version,
+    /// which is `embedding_service` unless a model cutover has happened.
     #[must_use]
-    pub const fn new(database: Arc<Database>, embedding_service: Arc<EmbeddingService>) -> Self {
+    pub fn new(database: Arc<Database>, embedding_service: Arc<EmbeddingService>) -> Self {
         Self {
+            embedding_service: ActiveEmbeddingService::new(database.clone(), embedding_service),
             database,
-            embedding_service,
         }
     }
 
@@ -66,41 +70,26 @@ impl Retriever {
         Ok(results)
     }
 
-    /// Keyword search using text matching
+    /// Keyword search using full-text matching on names and bios
+    ///
+    /// Supports `"phrases"`, `-exclusions` and `OR`; the score of each
+    /// result is its `ts_rank_cd` rank.
     ///
     /// # Errors
     /// - Database query errors (connection failures, SQL execution errors)
-    /// - Invalid search patterns (malformed queries)
     pub async fn keyword_search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
         debug!("Performing keyword search: {}", query);
 
         let profiles = self
             .database
-            .list_user_profiles(crate::models::UserProfileQuery {
-                fid: None,
-                username: None,
-                display_name: None,
-                bio: None,
-                location: None,
-                twitter_username: None,
-                github_username: None,
-                #[allow(clippy::cast_possible_wrap)] // Limit is guaranteed to be positive and reasonable
-                limit: Some(limit as i64),
-                offset: None,
-                start_timestamp: None,
-                end_timestamp: None,
-                sort_by: None,
-                sort_order: None,
-                search_term: Some(query.to_string()),
-            })
+            .full_text_search_profiles(query, i64::try_from(limit).unwrap_or(i64::MAX))
             .await?;
 
         let results = profiles
             .into_iter()
-            .enumerate()
-            .map(|(idx, profile)| SearchResult {
-                profile,
-                score: 1.0 - (idx as f32 / limit as f32),
+            .map(|ranked| SearchResult {
+                profile: ranked.profile,
+                score: ranked.rank,
                 match_type: MatchType::Keyword,
             })
             .collect();
@@ -135,6 +124,44 @@ impl Retriever {
         Ok(results)
     }
 
+    /// Profiles named by username or FID, in that order
+    ///
+    /// Unknown names are skipped. Each profile scores 1.0.
+    ///
+    /// # Errors
+    /// - Database query errors (connection failures, SQL execution errors)
+    pub async fn named_profiles(
+        &self,
+        usernames: &[String],
+        fids: &[i64],
+    ) -> Result<Vec<SearchResult>> {
+        let mut results: Vec<SearchResult> = Vec::new();
+
+        for username in usernames {
+            if let Some(profile) = self.database.get_user_profile_by_username(username).await? {
+                results.push(SearchResult {
+                    profile,
+                    score: 1.0,
+                    match_type: MatchType::Named,
+                });
+            }
+        }
+        for &fid in fids {
+            if results.iter().any(|result| result.profile.fid == fid) {
+                continue;
+            }
+            if let Some(profile) = self.database.get_user_profile(fid).await? {
+                results.push(SearchResult {
+                    profile,
+                    score: 1.0,
+                    match_type: MatchType::Named,
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
     /// Search with automatic method selection based on query characteristics
     pub async fn auto_search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
         // Analyze query to select optimal search method
@@ -14,6 +14,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::database::Database;
+use crate::database::NOT_REMOVED;
 use crate::Result;
 
 lazy_static! {
@@ -673,15 +674,16 @@ impl SocialGraphAnalyzer {
     /// Analyze users mentioned in casts
     async fn analyze_mentions(&self, fid: i64) -> Result<Vec<UserMention>> {
         // Get casts with mentions
-        let casts = sqlx::query_as::<_, (Option<serde_json::Value>,)>(
+        let casts = sqlx::query_as::<_, (Option<serde_json::Value>,)>(&format!(
             r"
-            SELECT mentions
-            FROM casts
-            WHERE fid = $1 AND mentions IS NOT NULL
-            ORDER BY timestamp DESC
+            SELECT c.mentions
+            FROM casts c
+            WHERE c.fid = $1 AND c.mentions IS NOT NULL
+            AND {NOT_REMOVED}
+            ORDER BY c.timestamp DESC
             LIMIT 100
-            ",
-        )
+            "
+        ))
         .bind(fid)
         .fetch_all(self.database.pool())
         .await?;
@@ -896,16 +898,17 @@ impl SocialGraphAnalyzer {
     /// Analyze user's interaction style
     async fn analyze_interaction_style(&self, fid: i64) -> Result<InteractionStyle> {
         // Optimized: Single query with FILTER instead of 3 separate COUNT queries
-        let counts: (i64, i64, i64) = sqlx::query_as(
+        let counts: (i64, i64, i64) = sqlx::query_as(&format!(
             r"
             SELECT 
                 COUNT(*) as total,
                 COUNT(*) FILTER (WHERE parent_hash IS NOT NULL) as replies,
                 COUNT(*) FILTER (WHERE mentions IS NOT NULL) as mentions
-            FROM casts 
-            WHERE fid = $1
-            ",
-        )
+            FROM casts c
+            WHERE c.fid = $1
+            AND {NOT_REMOVED}
+            "
+        ))
         .bind(fid)
         .fetch_one(self.database.pool())
         .await?;
@@ -0,0 +1,404 @@
+//! Snapshot-based bootstrap sync
+//!
+//! Replaying a shard block by block from height 0 takes days. Instead, a fresh
+//! shard can be loaded from a replication snapshot: `GetShardSnapshotMetadata`
+//! lists the heights a node holds snapshots for, and `GetShardTransactions`
+//! pages through the shard trie at that height, one virtual trie shard at a
+//! time. Each page is written through `flush_batched_data` and the cursor is
+//! saved after every page, so an interrupted bootstrap resumes where it
+//! stopped. Once every page is applied, block sync takes over at `height + 1`.
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+
+use crate::database::Database;
+use crate::sync::client::proto;
+use crate::sync::client::SnapchainClient;
+use crate::sync::shard_processor::ShardProcessor;
+use crate::Result;
+use crate::SnapRagError;
+
+/// Number of virtual trie shards (the first byte of a trie key)
+pub const TRIE_VIRTUAL_SHARDS: u32 = 256;
+
+/// Where snapshot metadata and pages come from
+pub trait SnapshotSource {
+    /// Snapshots available for `shard_id`
+    fn snapshot_metadata(
+        &self,
+        shard_id: u32,
+    ) -> impl Future<Output = Result<Vec<proto::ShardSnapshotMetadata>>> + Send;
+
+    /// One page of trie entries of a snapshot
+    fn shard_transactions(
+        &self,
+        request: proto::ShardTransactionsRequest,
+    ) -> impl Future<Output = Result<proto::ShardTransactionsResponse>> + Send;
+}
+
+impl SnapshotSource for SnapchainClient {
+    async fn snapshot_metadata(&self, shard_id: u32) -> Result<Vec<proto::ShardSnapshotMetadata>> {
+        self.get_shard_snapshot_metadata(shard_id).await
+    }
+
+    async fn shard_transactions(
+        &self,
+        request: proto::ShardTransactionsRequest,
+    ) -> Result<proto::ShardTransactionsResponse> {
+        self.get_shard_transactions(request).await
+    }
+}
+
+/// A page request together with the response the node returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedPage {
+    pub request: proto::ShardTransactionsRequest,
+    pub response: proto::ShardTransactionsResponse,
+}
+
+/// Recorded replication responses, replayable as a `SnapshotSource`
+///
+/// Only non-empty pages are recorded; virtual shards without a recorded first
+/// page replay as empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotFixture {
+    pub snapshots: Vec<proto::ShardSnapshotMetadata>,
+    pub pages: Vec<RecordedPage>,
+}
+
+impl SnapshotFixture {
+    /// Load a fixture written by `snaprag sync bootstrap --record`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the fixture as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Total number of trie entries across all recorded pages
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.pages
+            .iter()
+            .map(|page| page.response.entries.len())
+            .sum()
+    }
+
+    fn page(
+        &self,
+        request: &proto::ShardTransactionsRequest,
+    ) -> Result<proto::ShardTransactionsResponse> {
+        if let Some(page) = self.pages.iter().find(|page| &page.request == request) {
+            return Ok(page.response.clone());
+        }
+
+        if request.page_token.is_some() {
+            return Err(SnapRagError::Custom(format!(
+                "No recorded page for shard {} virtual shard {} token {:?}",
+                request.shard_id, request.trie_virtual_shard, request.page_token
+            )));
+        }
+        Ok(proto::ShardTransactionsResponse {
+            entries: vec![],
+            next_page_token: None,
+        })
+    }
+}
+
+impl SnapshotSource for SnapshotFixture {
+    async fn snapshot_metadata(&self, shard_id: u32) -> Result<Vec<proto::ShardSnapshotMetadata>> {
+        Ok(self
+            .snapshots
+            .iter()
+            .filter(|snapshot| snapshot.shard_id == shard_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn shard_transactions(
+        &self,
+        request: proto::ShardTransactionsRequest,
+    ) -> Result<proto::ShardTransactionsResponse> {
+        self.page(&request)
+    }
+}
+
+/// Passes requests through to `inner` and records the responses as a fixture
+pub struct RecordingSource<'a, S> {
+    inner: &'a S,
+    fixture: Mutex<SnapshotFixture>,
+}
+
+impl<'a, S: SnapshotSource + Sync> RecordingSource<'a, S> {
+    pub fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            fixture: Mutex::new(SnapshotFixture::default()),
+        }
+    }
+
+    /// The responses recorded so far
+    pub fn into_fixture(self) -> SnapshotFixture {
+        self.fixture
+            .into_inner()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn record(&self, update: impl FnOnce(&mut SnapshotFixture)) {
+        if let Ok(mut fixture) = self.fixture.lock() {
+            update(&mut fixture);
+        }
+    }
+}
+
+impl<S: SnapshotSource + Sync> SnapshotSource for RecordingSource<'_, S> {
+    async fn snapshot_metadata(&self, shard_id: u32) -> Result<Vec<proto::ShardSnapshotMetadata>> {
+        let snapshots = self.inner.snapshot_metadata(shard_id).await?;
+        self.record(|fixture| fixture.snapshots.extend(snapshots.iter().cloned()));
+        Ok(snapshots)
+    }
+
+    async fn shard_transactions(
+        &self,
+        request: proto::ShardTransactionsRequest,
+    ) -> Result<proto::ShardTransactionsResponse> {
+        let response = self.inner.shard_transactions(request.clone()).await?;
+        if !response.entries.is_empty() || response.next_page_token.is_some() {
+            self.record(|fixture| {
+                fixture.pages.push(RecordedPage {
+                    request,
+                    response: response.clone(),
+                });
+            });
+        }
+        Ok(response)
+    }
+}
+
+/// Bootstrap through `source` and save its responses to `path` as a fixture
+///
+/// The fixture is written even when the bootstrap fails part way.
+pub async fn bootstrap_shard_recorded<S: SnapshotSource + Sync>(
+    source: &S,
+    database: &Database,
+    shard_id: u32,
+    height: Option<u64>,
+    path: &Path,
+) -> Result<BootstrapStats> {
+    let recorder = RecordingSource::new(source);
+    let result = bootstrap_shard(&recorder, database, shard_id, height).await;
+    recorder.into_fixture().save(path)?;
+    result
+}
+
+/// Outcome of a shard bootstrap
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BootstrapStats {
+    /// Snapshot height; block sync continues at `height + 1`
+    pub height: u64,
+    /// Pages fetched in this run
+    pub pages: u64,
+    /// Trie entries applied, including those of an earlier interrupted run
+    pub entries: u64,
+    /// Whether an interrupted bootstrap of the same snapshot was resumed
+    pub resumed: bool,
+}
+
+/// Pick the snapshot at `height`, or the most recent one
+pub fn select_snapshot(
+    snapshots: &[proto::ShardSnapshotMetadata],
+    height: Option<u64>,
+) -> Result<proto::ShardSnapshotMetadata> {
+    let selected = height.map_or_else(
+        || snapshots.iter().max_by_key(|snapshot| snapshot.height),
+        |height| snapshots.iter().find(|snapshot| snapshot.height == height),
+    );
+
+    selected.cloned().ok_or_else(|| {
+        let available: Vec<u64> = snapshots.iter().map(|snapshot| snapshot.height).collect();
+        height.map_or_else(
+            || SnapRagError::Custom("Node has no snapshots for this shard".to_string()),
+            |height| {
+                SnapRagError::Custom(format!(
+                    "No snapshot at height {height} (available: {available:?})"
+                ))
+            },
+        )
+    })
+}
+
+/// Wrap a page of trie entries as a chunk at the snapshot height
+///
+/// Consecutive entries of the same FID become one transaction; on-chain events
+/// and fname transfers travel as system messages, as they do in blocks.
+#[must_use]
+pub fn snapshot_chunk(
+    snapshot: &proto::ShardSnapshotMetadata,
+    entries: &[proto::ShardTrieEntry],
+) -> proto::ShardChunk {
+    let mut transactions: Vec<proto::Transaction> = Vec::new();
+
+    for entry in entries {
+        let Some(fid) = entry_fid(entry) else {
+            continue;
+        };
+        if transactions.last().is_none_or(|tx| tx.fid != fid) {
+            transactions.push(proto::Transaction {
+                fid,
+                user_messages: vec![],
+                system_messages: vec![],
+            });
+        }
+        let Some(transaction) = transactions.last_mut() else {
+            continue;
+        };
+
+        if let Some(message) = &entry.user_message {
+            transaction.user_messages.push(message.clone());
+        }
+        if entry.on_chain_event.is_some() || entry.fname_transfer.is_some() {
+            transaction.system_messages.push(proto::ValidatorMessage {
+                on_chain_event: entry.on_chain_event.clone(),
+                fname_transfer: entry.fname_transfer.clone(),
+            });
+        }
+    }
+
+    proto::ShardChunk {
+        header: Some(proto::ShardHeader {
+            height: Some(proto::Height {
+                shard_index: snapshot.shard_id,
+                block_number: snapshot.height,
+            }),
+            timestamp: snapshot.timestamp,
+            parent_hash: vec![],
+            shard_root: vec![],
+        }),
+        hash: vec![],
+        transactions,
+    }
+}
+
+/// FID an entry belongs to, `None` for entries without a message
+fn entry_fid(entry: &proto::ShardTrieEntry) -> Option<u64> {
+    if let Some(message) = &entry.user_message {
+        return message.data.as_ref().map(|data| data.fid);
+    }
+    if let Some(event) = &entry.on_chain_event {
+        return Some(event.fid);
+    }
+    entry.fname_transfer.as_ref().map(|transfer| {
+        if transfer.to_fid > 0 {
+            transfer.to_fid
+        } else {
+            transfer.from_fid
+        }
+    })
+}
+
+/// Load a shard from a replication snapshot and hand it over to block sync
+///
+/// Only shards that block sync has not moved past the snapshot height can be
+/// bootstrapped.
+pub async fn bootstrap_shard<S: SnapshotSource + Sync>(
+    source: &S,
+    database: &Database,
+    shard_id: u32,
+    height: Option<u64>,
+) -> Result<BootstrapStats> {
+    let snapshots = source.snapshot_metadata(shard_id).await?;
+    let snapshot = select_snapshot(&snapshots, height)?;
+
+    let synced_to = database.get_last_processed_height(shard_id).await?;
+    if synced_to > snapshot.height {
+        return Err(SnapRagError::Custom(format!(
+            "Shard {shard_id} is already synced to block {synced_to}, past snapshot height {}",
+            snapshot.height
+        )));
+    }
+
+    let progress = database
+        .start_snapshot_bootstrap(shard_id, snapshot.height, snapshot.timestamp)
+        .await?;
+    let mut stats = BootstrapStats {
+        height: snapshot.height,
+        pages: 0,
+        entries: u64::try_from(progress.entries_applied).unwrap_or(0),
+        resumed: progress.virtual_shard > 0 || progress.page_token.is_some(),
+    };
+    if stats.resumed {
+        info!(
+            "📍 Resuming shard {} snapshot {} at virtual shard {} ({} entries applied)",
+            shard_id, snapshot.height, progress.virtual_shard, stats.entries
+        );
+    } else {
+        info!(
+            "📦 Bootstrapping shard {} from snapshot at height {}",
+            shard_id, snapshot.height
+        );
+    }
+
+    let processor = ShardProcessor::new(database.clone());
+    let mut virtual_shard = u32::try_from(progress.virtual_shard).unwrap_or(0);
+    let mut page_token = progress.page_token;
+
+    while virtual_shard < TRIE_VIRTUAL_SHARDS {
+        let response = source
+            .shard_transactions(proto::ShardTransactionsRequest {
+                shard_id,
+                height: snapshot.height,
+                trie_virtual_shard: virtual_shard,
+                page_token: page_token.clone(),
+            })
+            .await?;
+
+        if !response.entries.is_empty() {
+            let chunk = snapshot_chunk(&snapshot, &response.entries);
+            processor.apply_snapshot_chunk(&chunk, shard_id).await?;
+            processor.clear_cache();
+        }
+        stats.pages += 1;
+        stats.entries += response.entries.len() as u64;
+
+        page_token = response.next_page_token;
+        if page_token.is_none() {
+            virtual_shard += 1;
+            if virtual_shard % 16 == 0 {
+                info!(
+                    "Shard {}: {}/{} virtual shards, {} entries",
+                    shard_id, virtual_shard, TRIE_VIRTUAL_SHARDS, stats.entries
+                );
+            }
+        }
+        database
+            .save_snapshot_bootstrap_cursor(
+                shard_id,
+                virtual_shard,
+                page_token.as_deref(),
+                stats.entries,
+            )
+            .await?;
+    }
+
+    database
+        .complete_snapshot_bootstrap(shard_id, snapshot.height)
+        .await?;
+    info!(
+        "✅ Shard {} bootstrapped at height {} ({} entries), block sync continues at {}",
+        shard_id,
+        snapshot.height,
+        stats.entries,
+        snapshot.height + 1
+    );
+
+    Ok(stats)
+}
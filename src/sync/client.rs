@@ -2,6 +2,8 @@
 
 use std::collections::HashMap;
 
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
 use serde::Serialize;
@@ -13,6 +15,7 @@ use tonic::transport::Channel;
 use crate::generated::blocks::ShardChunk;
 use crate::generated::grpc_client::hub_service_client::HubServiceClient;
 use crate::generated::grpc_client::message_data;
+use crate::generated::grpc_client::replication_service_client::ReplicationServiceClient;
 use crate::generated::grpc_client::{
     self as grpc_proto,
 };
@@ -60,8 +63,17 @@ pub mod proto {
     pub struct Message {
         pub data: Option<MessageData>,
         pub hash: Vec<u8>,
+        /// `HashScheme` enum (1 = BLAKE3)
+        #[serde(default)]
+        pub hash_scheme: i32,
         pub signature: Vec<u8>,
+        /// `SignatureScheme` enum (1 = Ed25519)
+        #[serde(default)]
+        pub signature_scheme: i32,
         pub signer: Vec<u8>,
+        /// Serialized `MessageData`, the bytes that were hashed and signed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub data_bytes: Option<Vec<u8>>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +101,9 @@ pub mod proto {
         pub transaction_hash: Vec<u8>,
         pub log_index: u32,
         pub fid: u64,
+        /// Typed event body (signer, id register, storage rent, ...), hex-encoded bytes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub body: Option<serde_json::Value>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +111,10 @@ pub mod proto {
         pub id: u64,
         pub from_fid: u64,
         pub to_fid: u64,
+        pub username: String,
+        pub owner: Vec<u8>,
+        pub signature: Vec<u8>,
+        pub timestamp: u64,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,13 +136,12 @@ pub mod proto {
     pub struct HubEvent {
         pub id: u64,
         pub r#type: i32, // HubEventType enum
+        pub shard_index: u32,
         pub block_number: u64,
-        pub block_hash: Vec<u8>,
-        pub block_timestamp: u64,
-        pub transaction_hash: Vec<u8>,
-        pub log_index: u32,
-        pub fid: u64,
-        pub message: Option<Message>,
+        pub timestamp: u64,
+        pub block_hash: Vec<u8>, // Only set on BLOCK_CONFIRMED
+        pub messages: Vec<Message>,
+        pub on_chain_event: Option<OnChainEvent>,
     }
 
     // Request/Response types
@@ -139,6 +157,41 @@ pub mod proto {
         pub shard_chunks: Vec<ShardChunk>,
     }
 
+    /// A replication snapshot a node can serve for a shard
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ShardSnapshotMetadata {
+        pub shard_id: u32,
+        pub height: u64,
+        pub timestamp: u64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ShardTransactionsRequest {
+        pub shard_id: u32,
+        pub height: u64,
+        pub trie_virtual_shard: u32,
+        pub page_token: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ShardTransactionsResponse {
+        pub entries: Vec<ShardTrieEntry>,
+        /// `None` once every entry under the virtual shard has been returned
+        pub next_page_token: Option<String>,
+    }
+
+    /// A leaf of the shard trie with the message stored under it
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ShardTrieEntry {
+        pub trie_key: Vec<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub user_message: Option<Message>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub on_chain_event: Option<OnChainEvent>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub fname_transfer: Option<FnameTransfer>,
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct GetInfoRequest {}
 
@@ -174,12 +227,50 @@ pub mod proto {
     }
 }
 
+/// Message sets the hub serves per FID through `GetAll*MessagesByFid`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HubMessageSet {
+    Casts,
+    Reactions,
+    Links,
+    Verifications,
+    UserData,
+}
+
+impl HubMessageSet {
+    pub const ALL: [Self; 5] = [
+        Self::Casts,
+        Self::Reactions,
+        Self::Links,
+        Self::Verifications,
+        Self::UserData,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Casts => "casts",
+            Self::Reactions => "reactions",
+            Self::Links => "links",
+            Self::Verifications => "verifications",
+            Self::UserData => "user_data",
+        }
+    }
+}
+
+impl std::fmt::Display for HubMessageSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Snapchain gRPC client
 #[derive(Clone)]
 pub struct SnapchainClient {
     client: Client,
     base_url: String, // HTTP endpoint for REST API
     grpc_client: HubServiceClient<Channel>,
+    replication_client: ReplicationServiceClient<Channel>,
 }
 
 /// Snapchain protobuf client for serialization/deserialization
@@ -206,18 +297,22 @@ impl SnapchainClient {
         tracing::debug!("  HTTP endpoint: {}", base_url);
         tracing::debug!("  gRPC endpoint: {}", grpc_url);
 
-        // Use the generated gRPC client
-        let mut grpc_client =
-            crate::generated::grpc_client::hub_service_client::HubServiceClient::connect(grpc_url)
-                .await
-                .map_err(|e| {
-                    crate::SnapRagError::Custom(format!("Failed to connect to gRPC endpoint: {e}"))
-                })?;
+        // Use the generated gRPC clients, sharing one connection
+        let channel = tonic::transport::Endpoint::new(grpc_url)
+            .map_err(|e| crate::SnapRagError::Custom(format!("Invalid gRPC endpoint: {e}")))?
+            .connect()
+            .await
+            .map_err(|e| {
+                crate::SnapRagError::Custom(format!("Failed to connect to gRPC endpoint: {e}"))
+            })?;
 
         // Set large message size limits for batch processing after client creation
         // Default is 4MB, we increase to 256MB to support batch_size up to 50
         const MAX_MESSAGE_SIZE: usize = 256 * 1024 * 1024; // 256MB
-        grpc_client = grpc_client
+        let grpc_client = HubServiceClient::new(channel.clone())
+            .max_decoding_message_size(MAX_MESSAGE_SIZE)
+            .max_encoding_message_size(MAX_MESSAGE_SIZE);
+        let replication_client = ReplicationServiceClient::new(channel)
             .max_decoding_message_size(MAX_MESSAGE_SIZE)
             .max_encoding_message_size(MAX_MESSAGE_SIZE);
 
@@ -225,6 +320,7 @@ impl SnapchainClient {
             client,
             base_url,
             grpc_client,
+            replication_client,
         })
     }
 
@@ -342,27 +438,7 @@ impl SnapchainClient {
 
                 // Convert user messages
                 for grpc_msg in grpc_tx.user_messages {
-                    let mut proto_msg = proto::Message {
-                        data: None,
-                        hash: grpc_msg.hash.clone(),
-                        signature: grpc_msg.signature.clone(),
-                        signer: grpc_msg.signer.clone(),
-                    };
-
-                    // Convert message data if present
-                    if let Some(grpc_data) = grpc_msg.data {
-                        let body_json = grpc_data.body.as_ref().map(convert_grpc_message_body);
-
-                        proto_msg.data = Some(proto::MessageData {
-                            r#type: grpc_data.r#type,
-                            fid: grpc_data.fid,
-                            timestamp: grpc_data.timestamp,
-                            network: grpc_data.network,
-                            body: body_json,
-                        });
-                    }
-
-                    proto_tx.user_messages.push(proto_msg);
+                    proto_tx.user_messages.push(convert_grpc_message(grpc_msg));
                 }
 
                 // Convert system messages (on-chain events, fname transfers, etc.)
@@ -374,25 +450,14 @@ impl SnapchainClient {
 
                     // Convert on-chain event if present
                     if let Some(grpc_event) = grpc_sys_msg.on_chain_event {
-                        proto_sys_msg.on_chain_event = Some(proto::OnChainEvent {
-                            r#type: grpc_event.r#type,
-                            chain_id: grpc_event.chain_id,
-                            block_number: u64::from(grpc_event.block_number),
-                            block_hash: grpc_event.block_hash.clone(),
-                            block_timestamp: grpc_event.block_timestamp, // Unix timestamp from chain
-                            transaction_hash: grpc_event.transaction_hash.clone(),
-                            log_index: grpc_event.log_index,
-                            fid: grpc_event.fid,
-                        });
+                        proto_sys_msg.on_chain_event =
+                            Some(convert_grpc_on_chain_event(grpc_event));
                     }
 
                     // Convert fname transfer if present
                     if let Some(grpc_fname) = grpc_sys_msg.fname_transfer {
-                        proto_sys_msg.fname_transfer = Some(proto::FnameTransfer {
-                            id: grpc_fname.id,
-                            from_fid: grpc_fname.from_fid,
-                            to_fid: 0, // Note: protobuf may not have to_fid field
-                        });
+                        proto_sys_msg.fname_transfer =
+                            Some(convert_grpc_fname_transfer(grpc_fname));
                     }
 
                     proto_tx.system_messages.push(proto_sys_msg);
@@ -407,6 +472,99 @@ impl SnapchainClient {
         Ok(proto_response)
     }
 
+    /// List the replication snapshots the node holds for a shard
+    pub async fn get_shard_snapshot_metadata(
+        &self,
+        shard_id: u32,
+    ) -> Result<Vec<proto::ShardSnapshotMetadata>> {
+        let response = self
+            .replication_client
+            .clone()
+            .get_shard_snapshot_metadata(grpc_proto::GetShardSnapshotMetadataRequest { shard_id })
+            .await
+            .map_err(|e| {
+                crate::SnapRagError::Custom(format!("gRPC get_shard_snapshot_metadata failed: {e}"))
+            })?;
+
+        Ok(response
+            .into_inner()
+            .snapshots
+            .into_iter()
+            .map(|snapshot| proto::ShardSnapshotMetadata {
+                shard_id: snapshot.shard_id,
+                height: snapshot.height,
+                timestamp: snapshot.timestamp,
+            })
+            .collect())
+    }
+
+    /// Get one page of the trie entries of a snapshot, under one virtual shard
+    pub async fn get_shard_transactions(
+        &self,
+        request: proto::ShardTransactionsRequest,
+    ) -> Result<proto::ShardTransactionsResponse> {
+        let grpc_request = grpc_proto::GetShardTransactionsRequest {
+            shard_id: request.shard_id,
+            height: request.height,
+            trie_virtual_shard: request.trie_virtual_shard,
+            page_token: request.page_token,
+        };
+
+        let response = self
+            .replication_client
+            .clone()
+            .get_shard_transactions(grpc_request)
+            .await
+            .map_err(|e| {
+                crate::SnapRagError::Custom(format!("gRPC get_shard_transactions failed: {e}"))
+            })?
+            .into_inner();
+
+        Ok(proto::ShardTransactionsResponse {
+            entries: response
+                .trie_messages
+                .into_iter()
+                .map(convert_grpc_trie_entry)
+                .collect(),
+            next_page_token: response.next_page_token,
+        })
+    }
+
+    /// Subscribe to the hub event stream of a shard, starting at `from_id`
+    /// (or the live tail when `None`). Only event types that map onto shard
+    /// chunk data are requested.
+    pub async fn subscribe(
+        &self,
+        shard_index: u32,
+        from_id: Option<u64>,
+    ) -> Result<BoxStream<'static, Result<proto::HubEvent>>> {
+        let request = grpc_proto::SubscribeRequest {
+            event_types: vec![
+                grpc_proto::HubEventType::MergeMessage as i32,
+                grpc_proto::HubEventType::MergeUsernameProof as i32,
+                grpc_proto::HubEventType::MergeOnChainEvent as i32,
+                grpc_proto::HubEventType::BlockConfirmed as i32,
+            ],
+            from_id,
+            shard_index: Some(shard_index),
+        };
+
+        let mut grpc_client = self.grpc_client.clone();
+        let response = grpc_client
+            .subscribe(tonic::Request::new(request))
+            .await
+            .map_err(|e| crate::SnapRagError::Custom(format!("gRPC subscribe failed: {e}")))?;
+
+        Ok(response
+            .into_inner()
+            .map(|event| {
+                event.map(convert_hub_event).map_err(|e| {
+                    crate::SnapRagError::Custom(format!("Hub event stream error: {e}"))
+                })
+            })
+            .boxed())
+    }
+
     /// Get links by FID (who this user follows) using gRPC
     pub async fn get_links_by_fid(
         &self,
@@ -496,6 +654,68 @@ impl SnapchainClient {
         })
     }
 
+    /// All messages of `set` the hub holds for `fid`, following page tokens
+    ///
+    /// This is the hub's current CRDT state: adds and removes that won, plus
+    /// link compact states for the links set.
+    pub async fn get_all_messages_by_fid(
+        &self,
+        set: HubMessageSet,
+        fid: u64,
+    ) -> Result<Vec<proto::Message>> {
+        const PAGE_SIZE: u32 = 1000;
+
+        let mut grpc_client = self.grpc_client.clone();
+        let mut messages = Vec::new();
+        let mut page_token: Option<Vec<u8>> = None;
+
+        loop {
+            let request = grpc_proto::FidTimestampRequest {
+                fid,
+                page_size: Some(PAGE_SIZE),
+                page_token: page_token.take(),
+                reverse: None,
+                start_timestamp: None,
+                stop_timestamp: None,
+            };
+            let response = match set {
+                HubMessageSet::Casts => grpc_client.get_all_cast_messages_by_fid(request).await,
+                HubMessageSet::Reactions => {
+                    grpc_client.get_all_reaction_messages_by_fid(request).await
+                }
+                HubMessageSet::Links => grpc_client.get_all_link_messages_by_fid(request).await,
+                HubMessageSet::Verifications => {
+                    grpc_client
+                        .get_all_verification_messages_by_fid(request)
+                        .await
+                }
+                HubMessageSet::UserData => {
+                    grpc_client.get_all_user_data_messages_by_fid(request).await
+                }
+            }
+            .map_err(|e| {
+                crate::SnapRagError::Custom(format!(
+                    "gRPC get all {set} messages for FID {fid} failed: {e}"
+                ))
+            })?
+            .into_inner();
+
+            messages.extend(response.messages.into_iter().map(convert_grpc_message));
+            match response.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+
+        tracing::debug!(
+            "Hub holds {} {} messages for FID {}",
+            messages.len(),
+            set,
+            fid
+        );
+        Ok(messages)
+    }
+
     /// Get links by target FID (who follows this user)
     pub async fn get_links_by_target_fid(
         &self,
@@ -662,6 +882,175 @@ impl SnapchainClient {
     }
 }
 
+fn convert_grpc_message(grpc_msg: grpc_proto::Message) -> proto::Message {
+    // Nodes only send data_bytes for non ts-proto encodings; otherwise the
+    // canonical protobuf encoding of data is what was hashed
+    let data_bytes = grpc_msg
+        .data_bytes
+        .or_else(|| grpc_msg.data.as_ref().map(prost::Message::encode_to_vec));
+
+    proto::Message {
+        data: grpc_msg.data.as_ref().map(convert_grpc_message_data),
+        hash: grpc_msg.hash,
+        hash_scheme: grpc_msg.hash_scheme,
+        signature: grpc_msg.signature,
+        signature_scheme: grpc_msg.signature_scheme,
+        signer: grpc_msg.signer,
+        data_bytes,
+    }
+}
+
+/// Convert protobuf `MessageData` into the JSON-bodied form that gets ingested
+pub(crate) fn convert_grpc_message_data(grpc_data: &grpc_proto::MessageData) -> proto::MessageData {
+    proto::MessageData {
+        r#type: grpc_data.r#type,
+        fid: grpc_data.fid,
+        timestamp: grpc_data.timestamp,
+        network: grpc_data.network,
+        body: grpc_data.body.as_ref().map(convert_grpc_message_body),
+    }
+}
+
+fn convert_grpc_on_chain_event(grpc_event: grpc_proto::OnChainEvent) -> proto::OnChainEvent {
+    proto::OnChainEvent {
+        r#type: grpc_event.r#type,
+        chain_id: grpc_event.chain_id,
+        block_number: u64::from(grpc_event.block_number),
+        block_hash: grpc_event.block_hash,
+        block_timestamp: grpc_event.block_timestamp, // Unix timestamp from chain
+        transaction_hash: grpc_event.transaction_hash,
+        log_index: grpc_event.log_index,
+        fid: grpc_event.fid,
+        body: grpc_event
+            .body
+            .as_ref()
+            .map(convert_grpc_on_chain_event_body),
+    }
+}
+
+fn convert_grpc_on_chain_event_body(body: &grpc_proto::on_chain_event::Body) -> Value {
+    use crate::generated::grpc_client::on_chain_event::Body;
+
+    match body {
+        Body::SignerEventBody(signer) => json!({
+            "signer_event_body": {
+                "key": hex::encode(&signer.key),
+                "key_type": signer.key_type,
+                "event_type": signer.event_type,
+                "metadata": hex::encode(&signer.metadata),
+                "metadata_type": signer.metadata_type,
+            }
+        }),
+        Body::SignerMigratedEventBody(migrated) => json!({
+            "signer_migrated_event_body": {
+                "migrated_at": migrated.migrated_at,
+            }
+        }),
+        Body::IdRegisterEventBody(id_register) => json!({
+            "id_register_event_body": {
+                "to": hex::encode(&id_register.to),
+                "event_type": id_register.event_type,
+                "from": hex::encode(&id_register.from),
+                "recovery_address": hex::encode(&id_register.recovery_address),
+            }
+        }),
+        Body::StorageRentEventBody(storage_rent) => json!({
+            "storage_rent_event_body": {
+                "payer": hex::encode(&storage_rent.payer),
+                "units": storage_rent.units,
+                "expiry": storage_rent.expiry,
+            }
+        }),
+        Body::TierPurchaseEventBody(tier_purchase) => json!({
+            "tier_purchase_event_body": {
+                "tier_type": tier_purchase.tier_type,
+                "for_days": tier_purchase.for_days,
+                "payer": hex::encode(&tier_purchase.payer),
+            }
+        }),
+    }
+}
+
+fn convert_grpc_trie_entry(entry: grpc_proto::ShardTrieEntryWithMessage) -> proto::ShardTrieEntry {
+    use crate::generated::grpc_client::shard_trie_entry_with_message::TrieMessage;
+
+    let mut proto_entry = proto::ShardTrieEntry {
+        trie_key: entry.trie_key,
+        user_message: None,
+        on_chain_event: None,
+        fname_transfer: None,
+    };
+
+    match entry.trie_message {
+        Some(TrieMessage::UserMessage(message)) => {
+            proto_entry.user_message = Some(convert_grpc_message(message));
+        }
+        Some(TrieMessage::OnChainEvent(event)) => {
+            proto_entry.on_chain_event = Some(convert_grpc_on_chain_event(event));
+        }
+        Some(TrieMessage::FnameTransfer(transfer)) => {
+            proto_entry.fname_transfer = Some(convert_grpc_fname_transfer(transfer));
+        }
+        None => {}
+    }
+
+    proto_entry
+}
+
+/// The receiving FID and the name itself travel in the attached username proof
+fn convert_grpc_fname_transfer(grpc_fname: grpc_proto::FnameTransfer) -> proto::FnameTransfer {
+    let proof = grpc_fname.proof.unwrap_or_default();
+
+    proto::FnameTransfer {
+        id: grpc_fname.id,
+        from_fid: grpc_fname.from_fid,
+        to_fid: proof.fid,
+        username: String::from_utf8_lossy(&proof.name).into_owned(),
+        owner: proof.owner,
+        signature: proof.signature,
+        timestamp: proof.timestamp,
+    }
+}
+
+/// Convert a gRPC `HubEvent` into the internal representation used by the
+/// event-stream sync. Prune/revoke/failure bodies carry no data to store.
+pub(crate) fn convert_hub_event(event: grpc_proto::HubEvent) -> proto::HubEvent {
+    use crate::generated::grpc_client::hub_event::Body;
+
+    let mut proto_event = proto::HubEvent {
+        id: event.id,
+        r#type: event.r#type,
+        shard_index: event.shard_index,
+        block_number: event.block_number,
+        timestamp: event.timestamp,
+        block_hash: vec![],
+        messages: vec![],
+        on_chain_event: None,
+    };
+
+    match event.body {
+        Some(Body::MergeMessageBody(body)) => {
+            proto_event
+                .messages
+                .extend(body.message.map(convert_grpc_message));
+        }
+        Some(Body::MergeUsernameProofBody(body)) => {
+            proto_event
+                .messages
+                .extend(body.username_proof_message.map(convert_grpc_message));
+        }
+        Some(Body::MergeOnChainEventBody(body)) => {
+            proto_event.on_chain_event = body.on_chain_event.map(convert_grpc_on_chain_event);
+        }
+        Some(Body::BlockConfirmedBody(body)) => {
+            proto_event.block_hash = body.block_hash;
+        }
+        _ => {}
+    }
+
+    proto_event
+}
+
 fn convert_grpc_message_body(body: &message_data::Body) -> Value {
     match body {
         message_data::Body::CastAddBody(cast_add) => {
@@ -703,6 +1092,29 @@ fn convert_grpc_message_body(body: &message_data::Body) -> Value {
             );
             Value::Object(root)
         }
+        message_data::Body::LinkCompactStateBody(compact_state) => {
+            let mut root = Map::new();
+            root.insert(
+                "link_compact_state_body".to_string(),
+                json!({
+                    "type": compact_state.r#type,
+                    "target_fids": compact_state.target_fids,
+                }),
+            );
+            Value::Object(root)
+        }
+        message_data::Body::LendStorageBody(lend_storage) => {
+            let mut root = Map::new();
+            root.insert(
+                "lend_storage_body".to_string(),
+                json!({
+                    "to_fid": lend_storage.to_fid,
+                    "num_units": lend_storage.num_units,
+                    "unit_type": lend_storage.unit_type,
+                }),
+            );
+            Value::Object(root)
+        }
         _ => Value::Null,
     }
 }
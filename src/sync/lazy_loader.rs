@@ -320,6 +320,8 @@ impl LazyLoader {
                             cast.root_hash.clone(),
                             cast.embeds.clone(),
                             cast.mentions.clone(),
+                            cast.parent_fid,
+                            cast.parent_url.clone(),
                         )
                         .await
                     {
@@ -369,7 +371,20 @@ impl LazyLoader {
             .and_then(|v| v.as_str())
             .and_then(|s| hex::decode(s).ok());
 
-        let root_hash = parent_hash.clone(); // Simplified - could parse full parent chain
+        let parent_fid = body
+            .get("castAddBody")
+            .and_then(|cast_body| cast_body.get("parentCastId"))
+            .and_then(|parent| parent.get("fid"))
+            .and_then(serde_json::Value::as_i64);
+
+        let parent_url = body
+            .get("castAddBody")
+            .and_then(|cast_body| cast_body.get("parentUrl"))
+            .and_then(|v| v.as_str())
+            .map(std::string::ToString::to_string);
+
+        // Thread root is resolved later from the parent chain (top-level casts are their own root)
+        let root_hash = parent_hash.is_none().then(|| message_hash.clone());
 
         let embeds = body
             .get("castAddBody")
@@ -394,6 +409,8 @@ impl LazyLoader {
             shard_id: None,
             block_height: None,
             transaction_fid: None,
+            parent_fid,
+            parent_url,
             created_at: chrono::Utc::now(),
         })
     }
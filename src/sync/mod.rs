@@ -1,25 +1,37 @@
 //! Snapchain synchronization module
 //!
 //! This module provides functionality to sync data from snapchain nodes,
-//! including block-by-block synchronization and real-time event streaming.
+//! including block-by-block synchronization, snapshot bootstrap, per-FID
+//! reconciliation against the hub, real-time event streaming and the sinks
+//! synced batches are written to.
 
+pub mod bootstrap;
 pub mod client;
 pub mod lazy_loader;
 pub mod lock_file;
 pub mod process_monitor;
+pub mod reconcile;
 pub mod service;
 pub mod shard_processor;
+pub mod sink;
 pub mod state_manager;
 pub mod types;
 
+pub use bootstrap::bootstrap_shard;
+pub use bootstrap::BootstrapStats;
+pub use bootstrap::SnapshotFixture;
 pub use client::SnapchainClient;
 pub use lazy_loader::LazyLoader;
 pub use lock_file::SyncLockFile;
 pub use lock_file::SyncLockManager;
 pub use lock_file::SyncProgress;
 pub use lock_file::SyncRange;
+pub use reconcile::FidReconcileReport;
+pub use reconcile::Reconciler;
 pub use service::SyncService;
 pub use shard_processor::ShardProcessor;
+pub use sink::SyncSink;
+pub use sink::SyncSinks;
 pub use state_manager::SyncStateManager;
 pub use state_manager::SyncStats;
 pub use types::*;
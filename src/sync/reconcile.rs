@@ -0,0 +1,395 @@
+//! Per-FID reconciliation against the hub's authoritative message sets
+//!
+//! For each FID the hub's `GetAll*MessagesByFid` sets are compared by message
+//! hash with the latest local message of every CRDT key. Hub messages missing
+//! locally are applied through the normal batch path. Local adds the hub no
+//! longer holds (e.g. signed by a since-revoked key, or a missed remove) get a
+//! synthetic remove so they stop counting as live.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::database::Database;
+use crate::database::FidMessageState;
+use crate::models::Cast;
+use crate::models::Link;
+use crate::models::Reaction;
+use crate::models::ShardBlockInfo;
+use crate::models::Verification;
+use crate::sync::client::proto;
+use crate::sync::client::HubMessageSet;
+use crate::sync::shard_processor::message_verification::MESSAGE_HASH_LENGTH;
+use crate::sync::shard_processor::BatchedData;
+use crate::sync::ShardProcessor;
+use crate::sync::SnapchainClient;
+use crate::Result;
+
+/// Domain prefix of the digest that derives a tombstone hash from the removed message
+const TOMBSTONE_DOMAIN: &[u8] = b"snaprag:reconcile:tombstone:";
+
+/// Differences of one message set of a FID
+#[derive(Debug, Clone, Serialize)]
+pub struct SetDiff {
+    pub set: &'static str,
+    pub hub_messages: usize,
+    pub local_messages: usize,
+    /// Hex hashes of hub messages missing locally
+    pub missing_locally: Vec<String>,
+    /// Hex hashes of local messages the hub does not hold
+    pub extra_locally: Vec<String>,
+    /// Missing messages handed to the batch path (verification may still reject them)
+    pub applied: usize,
+    /// Extra local adds removed with a synthetic remove
+    pub tombstoned: usize,
+}
+
+/// Reconciliation result of one FID
+#[derive(Debug, Clone, Serialize)]
+pub struct FidReconcileReport {
+    pub fid: u64,
+    pub dry_run: bool,
+    pub sets: Vec<SetDiff>,
+}
+
+impl FidReconcileReport {
+    /// Whether local state matched the hub before any change was applied
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.sets
+            .iter()
+            .all(|set| set.missing_locally.is_empty() && set.extra_locally.is_empty())
+    }
+}
+
+/// Positions of hub hashes absent locally and of local hashes absent on the hub
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HashDiff {
+    pub missing: Vec<usize>,
+    pub extra: Vec<usize>,
+}
+
+/// Compare two message sets by hash, keeping input order
+#[must_use]
+pub fn diff_hashes(hub: &[&[u8]], local: &[&[u8]]) -> HashDiff {
+    let hub_set: HashSet<&[u8]> = hub.iter().copied().collect();
+    let local_set: HashSet<&[u8]> = local.iter().copied().collect();
+
+    HashDiff {
+        missing: (0..hub.len())
+            .filter(|&i| !local_set.contains(hub[i]))
+            .collect(),
+        extra: (0..local.len())
+            .filter(|&i| !hub_set.contains(local[i]))
+            .collect(),
+    }
+}
+
+/// Hash of the synthetic remove of `message_hash`
+///
+/// Deterministic, so reconciling twice does not stack tombstones.
+#[must_use]
+pub fn tombstone_hash(message_hash: &[u8]) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(TOMBSTONE_DOMAIN);
+    hasher.update(message_hash);
+    hasher.finalize().as_bytes()[..MESSAGE_HASH_LENGTH].to_vec()
+}
+
+/// A live local add that a synthetic remove can retract
+enum Tombstone<'a> {
+    Cast(&'a Cast),
+    Link(&'a Link),
+    Reaction(&'a Reaction),
+    Verification(&'a Verification),
+}
+
+struct LocalMessage<'a> {
+    hash: &'a [u8],
+    tombstone: Option<Tombstone<'a>>,
+}
+
+impl<'a> LocalMessage<'a> {
+    const fn plain(hash: &'a [u8]) -> Self {
+        Self {
+            hash,
+            tombstone: None,
+        }
+    }
+}
+
+/// Local counterpart of the hub's `set`
+///
+/// Removes, link compact states and user data cannot be retracted, so they
+/// only show up in the report.
+fn local_messages(state: &FidMessageState, set: HubMessageSet) -> Vec<LocalMessage<'_>> {
+    let is_add = |event_type: &str| event_type == "add";
+
+    match set {
+        HubMessageSet::Casts => state
+            .casts
+            .iter()
+            .map(|cast| LocalMessage {
+                hash: &cast.message_hash,
+                tombstone: Some(Tombstone::Cast(cast)),
+            })
+            .chain(state.cast_removes.iter().map(|h| LocalMessage::plain(h)))
+            .collect(),
+        HubMessageSet::Links => state
+            .links
+            .iter()
+            .map(|link| LocalMessage {
+                hash: &link.message_hash,
+                tombstone: is_add(&link.event_type).then_some(Tombstone::Link(link)),
+            })
+            .chain(
+                state
+                    .link_compact_states
+                    .iter()
+                    .map(|h| LocalMessage::plain(h)),
+            )
+            .collect(),
+        HubMessageSet::Reactions => state
+            .reactions
+            .iter()
+            .map(|reaction| LocalMessage {
+                hash: &reaction.message_hash,
+                tombstone: is_add(&reaction.event_type).then_some(Tombstone::Reaction(reaction)),
+            })
+            .collect(),
+        HubMessageSet::Verifications => state
+            .verifications
+            .iter()
+            .map(|verification| LocalMessage {
+                hash: &verification.message_hash,
+                tombstone: is_add(&verification.event_type)
+                    .then_some(Tombstone::Verification(verification)),
+            })
+            .collect(),
+        HubMessageSet::UserData => state
+            .user_data
+            .iter()
+            .map(|h| LocalMessage::plain(h))
+            .collect(),
+    }
+}
+
+/// Queue a remove that wins over `tombstone` (one tick newer)
+fn push_tombstone(batched: &mut BatchedData, tombstone: &Tombstone<'_>) {
+    let info = |fid: i64| ShardBlockInfo::new(0, 0, u64::try_from(fid).unwrap_or(0), 0);
+
+    match tombstone {
+        Tombstone::Cast(cast) => batched.cast_removes.push((
+            cast.fid,
+            cast.message_hash.clone(),
+            cast.timestamp + 1,
+            tombstone_hash(&cast.message_hash),
+            info(cast.fid),
+        )),
+        Tombstone::Link(link) => batched.links.push((
+            link.fid,
+            link.target_fid,
+            link.link_type.clone(),
+            "remove".to_string(),
+            link.timestamp + 1,
+            tombstone_hash(&link.message_hash),
+            info(link.fid),
+        )),
+        Tombstone::Reaction(reaction) => batched.reactions.push((
+            reaction.fid,
+            reaction.target_cast_hash.clone(),
+            reaction.target_fid,
+            reaction.reaction_type,
+            "remove".to_string(),
+            reaction.timestamp + 1,
+            tombstone_hash(&reaction.message_hash),
+            info(reaction.fid),
+        )),
+        Tombstone::Verification(verification) => batched.verifications.push((
+            verification.fid,
+            verification.address.clone(),
+            None,
+            None,
+            verification.verification_type,
+            verification.chain_id,
+            "remove".to_string(),
+            verification.timestamp + 1,
+            tombstone_hash(&verification.message_hash),
+            info(verification.fid),
+        )),
+    }
+}
+
+/// Single-transaction chunk carrying `messages` of `fid` into the batch path
+fn reconcile_chunk(fid: u64, messages: Vec<proto::Message>) -> proto::ShardChunk {
+    proto::ShardChunk {
+        header: Some(proto::ShardHeader {
+            height: Some(proto::Height {
+                shard_index: 0,
+                block_number: 0,
+            }),
+            timestamp: 0,
+            parent_hash: vec![],
+            shard_root: vec![],
+        }),
+        hash: vec![],
+        transactions: vec![proto::Transaction {
+            fid,
+            user_messages: messages,
+            system_messages: vec![],
+        }],
+    }
+}
+
+/// Reconciles FIDs against the hub
+pub struct Reconciler<'a> {
+    client: &'a SnapchainClient,
+    database: &'a Database,
+    processor: ShardProcessor,
+    dry_run: bool,
+}
+
+impl<'a> Reconciler<'a> {
+    #[must_use]
+    pub fn new(client: &'a SnapchainClient, database: &'a Database) -> Self {
+        Self {
+            client,
+            database,
+            processor: ShardProcessor::new(database.clone()),
+            dry_run: false,
+        }
+    }
+
+    /// Only report differences, write nothing
+    #[must_use]
+    pub const fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Diff `fid` against the hub and, unless dry-run, repair local state
+    ///
+    /// Missing messages are applied first; tombstones are then computed from
+    /// the updated state, so a local add retracted by a just-applied hub
+    /// remove is not tombstoned twice.
+    pub async fn reconcile_fid(&self, fid: u64) -> Result<FidReconcileReport> {
+        let local_fid = i64::try_from(fid).unwrap_or(i64::MAX);
+        let state = self.database.fid_message_state(local_fid).await?;
+
+        let mut sets = Vec::with_capacity(HubMessageSet::ALL.len());
+        let mut hub_hashes = Vec::with_capacity(HubMessageSet::ALL.len());
+        let mut missing_messages = Vec::new();
+
+        for set in HubMessageSet::ALL {
+            let hub = self.client.get_all_messages_by_fid(set, fid).await?;
+            let local = local_messages(&state, set);
+
+            let hub_refs: Vec<&[u8]> = hub.iter().map(|m| m.hash.as_slice()).collect();
+            let local_refs: Vec<&[u8]> = local.iter().map(|m| m.hash).collect();
+            let diff = diff_hashes(&hub_refs, &local_refs);
+
+            sets.push(SetDiff {
+                set: set.as_str(),
+                hub_messages: hub.len(),
+                local_messages: local.len(),
+                missing_locally: diff
+                    .missing
+                    .iter()
+                    .map(|&i| hex::encode(hub_refs[i]))
+                    .collect(),
+                extra_locally: diff
+                    .extra
+                    .iter()
+                    .map(|&i| hex::encode(local_refs[i]))
+                    .collect(),
+                applied: 0,
+                tombstoned: 0,
+            });
+            hub_hashes.push(hub_refs.iter().map(|h| h.to_vec()).collect::<HashSet<_>>());
+            missing_messages.extend(diff.missing.iter().map(|&i| hub[i].clone()));
+        }
+
+        let mut report = FidReconcileReport {
+            fid,
+            dry_run: self.dry_run,
+            sets,
+        };
+        if self.dry_run || report.is_consistent() {
+            return Ok(report);
+        }
+
+        if !missing_messages.is_empty() {
+            self.processor
+                .apply_chunk_with(
+                    &reconcile_chunk(fid, missing_messages),
+                    0,
+                    BatchedData::new(),
+                )
+                .await?;
+            for set in &mut report.sets {
+                set.applied = set.missing_locally.len();
+            }
+        }
+
+        if report.sets.iter().any(|set| !set.extra_locally.is_empty()) {
+            let state = self.database.fid_message_state(local_fid).await?;
+            let mut batched = BatchedData::new();
+
+            for ((set, diff), hub) in HubMessageSet::ALL
+                .into_iter()
+                .zip(&mut report.sets)
+                .zip(&hub_hashes)
+            {
+                for message in local_messages(&state, set) {
+                    if let Some(tombstone) = message.tombstone.as_ref() {
+                        if !hub.contains(message.hash) {
+                            push_tombstone(&mut batched, tombstone);
+                            diff.tombstoned += 1;
+                        }
+                    }
+                }
+            }
+
+            crate::sync::shard_processor::flush_batched_data(self.database, batched).await?;
+        }
+
+        info!(
+            "🔁 Reconciled FID {}: {} applied, {} tombstoned",
+            fid,
+            report.sets.iter().map(|set| set.applied).sum::<usize>(),
+            report.sets.iter().map(|set| set.tombstoned).sum::<usize>()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_hashes_keeps_input_order() {
+        let hub: Vec<&[u8]> = vec![b"a", b"b", b"c", b"d"];
+        let local: Vec<&[u8]> = vec![b"e", b"c", b"a", b"f"];
+
+        assert_eq!(
+            diff_hashes(&hub, &local),
+            HashDiff {
+                missing: vec![1, 3],
+                extra: vec![0, 3],
+            }
+        );
+        assert_eq!(diff_hashes(&hub, &hub), HashDiff::default());
+        assert_eq!(diff_hashes(&[], &hub).extra, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_tombstone_hash_is_deterministic() {
+        let hash = [7u8; MESSAGE_HASH_LENGTH];
+        assert_eq!(tombstone_hash(&hash), tombstone_hash(&hash));
+        assert_eq!(tombstone_hash(&hash).len(), MESSAGE_HASH_LENGTH);
+        assert_ne!(tombstone_hash(&hash), hash.to_vec());
+        assert_ne!(tombstone_hash(&hash), tombstone_hash(&[8u8; 20]));
+    }
+}
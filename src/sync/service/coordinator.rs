@@ -41,8 +41,13 @@ impl SyncCoordinator {
                     chunk_count, shard_id, block_number
                 );
 
-                let mut stats =
-                    process_shard_chunks(&self.database, shard_id, response.shard_chunks).await?;
+                let mut stats = process_shard_chunks(
+                    &self.client,
+                    &self.database,
+                    shard_id,
+                    response.shard_chunks,
+                )
+                .await?;
 
                 if stats.blocks_processed() == 0 {
                     stats.blocks_processed = 1;
@@ -95,8 +100,13 @@ impl SyncCoordinator {
                 let chunk_count = response.shard_chunks.len();
                 info!("   ↳ Fetched {} chunks from server", chunk_count);
 
-                let stats =
-                    process_shard_chunks(&self.database, shard_id, response.shard_chunks).await?;
+                let stats = process_shard_chunks(
+                    &self.client,
+                    &self.database,
+                    shard_id,
+                    response.shard_chunks,
+                )
+                .await?;
 
                 info!(
                     "   ✓ Completed blocks {} to {} → {} messages, {} blocks processed",
@@ -123,12 +133,14 @@ impl SyncCoordinator {
 
 /// Process shard chunks and return statistics
 async fn process_shard_chunks(
+    client: &SnapchainClient,
     database: &Database,
     shard_id: u32,
     chunks: Vec<proto::ShardChunk>,
 ) -> Result<ChunkProcessStats> {
     let mut stats = ChunkProcessStats::default();
-    let processor = ShardProcessor::new(database.clone());
+    let processor =
+        ShardProcessor::new(database.clone()).with_chunk_source(Arc::new(client.clone()));
 
     for chunk in chunks {
         let block_number = extract_block_number(&chunk);
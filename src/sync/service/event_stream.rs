@@ -0,0 +1,157 @@
+//! Assembly of hub events into shard chunks for the event-stream sync
+
+use super::state::extract_block_number;
+use crate::database::HeightRange;
+use crate::sync::client::proto;
+
+/// Hub events grouped into per-block shard chunks, ready to be handed to
+/// `ShardProcessor::process_chunks_batch`
+#[derive(Debug, Default)]
+pub struct HubEventBatch {
+    chunks: Vec<proto::ShardChunk>,
+    last_event_id: Option<u64>,
+    event_count: usize,
+    /// Highest block of the previous flushes
+    flushed_height: Option<u64>,
+}
+
+impl HubEventBatch {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Batch for a stream resumed after blocks up to `height` were flushed
+    #[must_use]
+    pub fn resuming_after(height: u64) -> Self {
+        Self {
+            flushed_height: Some(height),
+            ..Self::default()
+        }
+    }
+
+    /// Add an event to the chunk of the block it belongs to
+    pub fn push(&mut self, event: proto::HubEvent) {
+        self.last_event_id = Some(self.last_event_id.map_or(event.id, |id| id.max(event.id)));
+        self.event_count += 1;
+
+        let chunk = self.chunk_for_block(event.shard_index, event.block_number, event.timestamp);
+        if !event.block_hash.is_empty() {
+            chunk.hash = event.block_hash;
+        }
+
+        for message in event.messages {
+            let fid = message.data.as_ref().map_or(0, |data| data.fid);
+            transaction_for_fid(chunk, fid).user_messages.push(message);
+        }
+
+        // On-chain events travel in system transactions (fid 0), as in shard chunks
+        if let Some(on_chain_event) = event.on_chain_event {
+            transaction_for_fid(chunk, 0)
+                .system_messages
+                .push(proto::ValidatorMessage {
+                    on_chain_event: Some(on_chain_event),
+                    fname_transfer: None,
+                });
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.event_count == 0
+    }
+
+    #[must_use]
+    pub const fn event_count(&self) -> usize {
+        self.event_count
+    }
+
+    #[must_use]
+    pub const fn block_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Highest event id added since the last `take`
+    #[must_use]
+    pub const fn last_event_id(&self) -> Option<u64> {
+        self.last_event_id
+    }
+
+    /// Blocks from the previous flush up to the highest block in the batch
+    ///
+    /// Blocks without events get no chunk, but the stream has passed them, so
+    /// they belong in the processed range too.
+    #[must_use]
+    pub fn covered_range(&self) -> Option<HeightRange> {
+        let heights = self.chunks.iter().filter_map(extract_block_number);
+        let end = heights.clone().max()?;
+        let start = self
+            .flushed_height
+            .map_or_else(|| heights.min(), |height| Some(height + 1))?;
+        (start <= end).then(|| HeightRange::new(start, end))
+    }
+
+    /// Drain the batch, returning its chunks and the highest event id
+    pub fn take(&mut self) -> (Vec<proto::ShardChunk>, Option<u64>) {
+        let flushed_height = self
+            .covered_range()
+            .map(|range| range.end)
+            .max(self.flushed_height);
+        let batch = std::mem::replace(
+            self,
+            Self {
+                flushed_height,
+                ..Self::default()
+            },
+        );
+        (batch.chunks, batch.last_event_id)
+    }
+
+    fn chunk_for_block(
+        &mut self,
+        shard_index: u32,
+        block_number: u64,
+        timestamp: u64,
+    ) -> &mut proto::ShardChunk {
+        let position = self
+            .chunks
+            .iter()
+            .rposition(|chunk| extract_block_number(chunk) == Some(block_number));
+
+        let index = position.unwrap_or_else(|| {
+            self.chunks.push(proto::ShardChunk {
+                header: Some(proto::ShardHeader {
+                    height: Some(proto::Height {
+                        shard_index,
+                        block_number,
+                    }),
+                    timestamp,
+                    parent_hash: vec![],
+                    shard_root: vec![],
+                }),
+                hash: vec![],
+                transactions: vec![],
+            });
+            self.chunks.len() - 1
+        });
+
+        &mut self.chunks[index]
+    }
+}
+
+fn transaction_for_fid(chunk: &mut proto::ShardChunk, fid: u64) -> &mut proto::Transaction {
+    let index = chunk
+        .transactions
+        .iter()
+        .position(|tx| tx.fid == fid)
+        .unwrap_or_else(|| {
+            chunk.transactions.push(proto::Transaction {
+                fid,
+                user_messages: vec![],
+                system_messages: vec![],
+            });
+            chunk.transactions.len() - 1
+        });
+
+    &mut chunk.transactions[index]
+}
@@ -5,6 +5,7 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use super::monitoring::RealtimeMonitor;
 use super::state::ChunkProcessStats;
 use crate::config::AppConfig;
 use crate::database::Database;
@@ -14,6 +15,7 @@ use crate::sync::lock_file::SyncLockManager;
 use crate::sync::lock_file::SyncRange;
 use crate::sync::shard_processor::ShardProcessor;
 use crate::sync::state_manager::SyncStateManager;
+use crate::sync::types::RealtimeSyncMode;
 use crate::sync::types::SyncConfig;
 use crate::sync::types::SyncState;
 use crate::Result;
@@ -254,7 +256,8 @@ impl LifecycleManager {
                                 break;
                             }
 
-                            let processor = ShardProcessor::new(database.as_ref().clone());
+                            let processor = ShardProcessor::new(database.as_ref().clone())
+                                .with_chunk_source(Arc::new(client.clone()));
                             processor.process_chunks_batch(&chunks, shard_id).await?;
 
                             // Update stats
@@ -350,7 +353,21 @@ impl LifecycleManager {
     }
 
     fn start_full_realtime_sync(&self) {
-        if self.config.enable_continuous_sync {
+        if self.config.realtime_sync_mode == RealtimeSyncMode::Stream {
+            info!(
+                "Starting event-stream real-time sync for shards {:?}...",
+                self.config.shard_ids
+            );
+
+            let config = self.config.clone();
+            let client = self.client.clone();
+            let database = self.database.clone();
+            let state_manager = self.state_manager.clone();
+
+            tokio::spawn(async move {
+                Self::run_event_stream_sync(config, client, database, state_manager).await;
+            });
+        } else if self.config.enable_continuous_sync {
             info!(
                 "Starting continuous sync monitoring (polling every {} seconds)...",
                 self.config.continuous_sync_interval_secs
@@ -556,6 +573,7 @@ impl LifecycleManager {
                                         // 📊 Measure processing time
                                         let process_start = std::time::Instant::now();
                                         match ShardProcessor::new(database.as_ref().clone())
+                                            .with_chunk_source(Arc::new(client.clone()))
                                             .process_chunks_batch(&chunks, shard_id)
                                             .await
                                         {
@@ -895,8 +913,20 @@ impl LifecycleManager {
 
         info!("🎉 All shards completed successfully!");
 
-        // If we're syncing to latest (u64::MAX), start continuous sync
-        if to_block == u64::MAX && self.config.enable_continuous_sync {
+        // If we're syncing to latest (u64::MAX), follow the event stream when configured
+        if to_block == u64::MAX
+            && self.config.enable_realtime_sync
+            && self.config.realtime_sync_mode == RealtimeSyncMode::Stream
+        {
+            info!("🔄 Switching to event-stream real-time sync...");
+
+            let config = self.config.clone();
+            let client = self.client.clone();
+            let database = self.database.clone();
+            let state_manager = self.state_manager.clone();
+
+            Self::run_event_stream_sync(config, client, database, state_manager).await;
+        } else if to_block == u64::MAX && self.config.enable_continuous_sync {
             info!(
                 "🔄 Starting continuous sync monitoring (polling every {} seconds)...",
                 self.config.continuous_sync_interval_secs
@@ -918,6 +948,30 @@ impl LifecycleManager {
         Ok(())
     }
 
+    /// Follow every configured shard through the hub event stream (runs forever)
+    async fn run_event_stream_sync(
+        config: SyncConfig,
+        client: SnapchainClient,
+        database: Arc<Database>,
+        state_manager: Arc<tokio::sync::RwLock<SyncStateManager>>,
+    ) {
+        let monitors = config.shard_ids.iter().map(|&shard_id| {
+            let monitor = RealtimeMonitor::new(
+                client.clone(),
+                database.clone(),
+                state_manager.clone(),
+                config.clone(),
+            );
+            async move {
+                if let Err(e) = monitor.monitor_shard_realtime(shard_id).await {
+                    error!("❌ Shard {} event stream stopped: {}", shard_id, e);
+                }
+            }
+        });
+
+        futures::future::join_all(monitors).await;
+    }
+
     /// Run continuous sync monitoring for new blocks
     async fn run_continuous_sync(
         config: crate::sync::types::SyncConfig,
@@ -1021,7 +1075,8 @@ impl LifecycleManager {
                     // Process the chunks
                     let processor = crate::sync::shard_processor::ShardProcessor::new(
                         database.as_ref().clone(),
-                    );
+                    )
+                    .with_chunk_source(Arc::new(client.clone()));
 
                     match processor
                         .process_chunks_batch(&response.shard_chunks, shard_id)
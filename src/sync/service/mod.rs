@@ -17,12 +17,14 @@ use crate::Result;
 
 // Re-export sub-modules
 pub mod coordinator;
+pub mod event_stream;
 pub mod lifecycle;
 pub mod monitoring;
 pub mod state;
 
 // Re-export key types
 pub use coordinator::SyncCoordinator;
+pub use event_stream::HubEventBatch;
 pub use lifecycle::LifecycleManager;
 pub use monitoring::RealtimeMonitor;
 pub use state::ChunkProcessStats;
@@ -57,6 +59,8 @@ impl SyncService {
             sync_interval_ms: app_config.sync.sync_interval_ms,
             enable_continuous_sync: app_config.sync.enable_continuous_sync,
             continuous_sync_interval_secs: app_config.sync.continuous_sync_interval_secs,
+            realtime_sync_mode: app_config.sync.realtime_sync_mode,
+            from_event_id: app_config.sync.historical_sync_from_event_id,
         };
 
         // Initialize snapchain client
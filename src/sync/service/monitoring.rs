@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
+use futures::StreamExt;
 use tokio::sync::RwLock;
+use tracing::debug;
 use tracing::error;
 use tracing::info;
 
+use super::event_stream::HubEventBatch;
 use super::state::count_chunk_messages;
 use super::state::extract_block_number;
 use super::state::ChunkProcessStats;
@@ -12,6 +15,7 @@ use crate::sync::client::proto;
 use crate::sync::client::SnapchainClient;
 use crate::sync::shard_processor::ShardProcessor;
 use crate::sync::state_manager::SyncStateManager;
+use crate::sync::types::RealtimeSyncMode;
 use crate::sync::types::SyncConfig;
 use crate::Result;
 
@@ -38,8 +42,181 @@ impl RealtimeMonitor {
         }
     }
 
-    /// Monitor a shard for real-time updates
+    /// Monitor a shard for real-time updates using the configured transport
     pub async fn monitor_shard_realtime(&self, shard_id: u32) -> Result<()> {
+        match self.config.realtime_sync_mode {
+            RealtimeSyncMode::Stream => self.stream_shard_realtime(shard_id).await,
+            RealtimeSyncMode::Poll => self.poll_shard_realtime(shard_id).await,
+        }
+    }
+
+    /// Follow a shard through the `Subscribe` event stream, reconnecting and
+    /// resuming from the last persisted event id whenever the stream ends
+    async fn stream_shard_realtime(&self, shard_id: u32) -> Result<()> {
+        info!("Starting event-stream monitoring for shard {}", shard_id);
+        let retry_delay = tokio::time::Duration::from_millis(self.config.sync_interval_ms.max(100));
+
+        loop {
+            match self.consume_event_stream(shard_id).await {
+                Ok(stats) => {
+                    info!(
+                        "Shard {}: event stream closed after {} messages, reconnecting",
+                        shard_id, stats.messages_processed
+                    );
+                }
+                Err(err) => {
+                    error!("Event stream for shard {} failed: {}", shard_id, err);
+                    let mut sm = self.state_manager.write().await;
+                    sm.add_error(format!("Shard {shard_id} event stream error: {err}"))?;
+                }
+            }
+
+            tokio::time::sleep(retry_delay).await;
+        }
+    }
+
+    /// Consume a single `Subscribe` session until the server closes it.
+    ///
+    /// Events are grouped into per-block chunks and flushed through
+    /// `ShardProcessor::process_chunks_batch` once `batch_size` blocks are
+    /// pending or the stream has been idle for `sync_interval_ms`. Each flush
+    /// records the blocks since the previous one as processed, including those
+    /// without events. The last event id is persisted only after its batch has
+    /// been written.
+    pub async fn consume_event_stream(&self, shard_id: u32) -> Result<ChunkProcessStats> {
+        let from_id = self.resume_event_id(shard_id).await;
+        // Blocks up to the height the previous session flushed are covered
+        let resumed_height = {
+            let sm = self.state_manager.read().await;
+            sm.get_last_event_id(shard_id)
+                .map(|_| sm.get_last_processed_height(shard_id))
+        };
+        info!(
+            "Shard {}: subscribing to hub events from {}",
+            shard_id,
+            from_id.map_or_else(|| "live tail".to_string(), |id| format!("event {id}"))
+        );
+
+        let mut events = self.client.subscribe(shard_id, from_id).await?;
+        let processor = ShardProcessor::new(self.database.as_ref().clone())
+            .with_chunk_source(Arc::new(self.client.clone()));
+        let flush_after = tokio::time::Duration::from_millis(self.config.sync_interval_ms.max(10));
+        let max_blocks = (self.config.batch_size as usize).max(1);
+
+        let mut batch =
+            resumed_height.map_or_else(HubEventBatch::new, HubEventBatch::resuming_after);
+        let mut totals = ChunkProcessStats::default();
+
+        loop {
+            let next = if batch.is_empty() {
+                events.next().await
+            } else if let Ok(next) = tokio::time::timeout(flush_after, events.next()).await {
+                next
+            } else {
+                // Stream went idle: flush what we have to keep latency low
+                self.flush_event_batch(&processor, shard_id, &mut batch, &mut totals)
+                    .await?;
+                continue;
+            };
+
+            match next {
+                Some(Ok(event)) => {
+                    batch.push(event);
+                    if batch.block_count() >= max_blocks {
+                        self.flush_event_batch(&processor, shard_id, &mut batch, &mut totals)
+                            .await?;
+                    }
+                }
+                Some(Err(err)) => {
+                    self.flush_event_batch(&processor, shard_id, &mut batch, &mut totals)
+                        .await?;
+                    return Err(err);
+                }
+                None => {
+                    self.flush_event_batch(&processor, shard_id, &mut batch, &mut totals)
+                        .await?;
+                    return Ok(totals);
+                }
+            }
+        }
+    }
+
+    /// Event id to resume from: one past the last persisted id, else the
+    /// configured starting event id (0 = live tail)
+    async fn resume_event_id(&self, shard_id: u32) -> Option<u64> {
+        let persisted = {
+            let sm = self.state_manager.read().await;
+            sm.get_last_event_id(shard_id)
+        };
+
+        persisted
+            .map(|id| id.saturating_add(1))
+            .or_else(|| (self.config.from_event_id > 0).then_some(self.config.from_event_id))
+    }
+
+    /// Write pending chunks and persist progress
+    async fn flush_event_batch(
+        &self,
+        processor: &ShardProcessor,
+        shard_id: u32,
+        batch: &mut HubEventBatch,
+        totals: &mut ChunkProcessStats,
+    ) -> Result<()> {
+        let event_count = batch.event_count();
+        let covered = batch.covered_range();
+        let (chunks, last_event_id) = batch.take();
+        let Some(last_event_id) = last_event_id else {
+            return Ok(());
+        };
+
+        processor.process_chunks_batch(&chunks, shard_id).await?;
+        // Chunks only exist for blocks with events; the blocks in between
+        // were passed too and must not show up as gaps
+        if let Some(range) = covered {
+            self.database
+                .record_processed_blocks(shard_id, range)
+                .await?;
+        }
+
+        // A block can be split across flushes; only count it once
+        let previous_block = totals.last_block_number;
+        let mut stats = ChunkProcessStats::default();
+        for chunk in &chunks {
+            let block_number = extract_block_number(chunk);
+            let message_count = count_chunk_messages(chunk);
+            if block_number.is_some() && block_number <= previous_block {
+                stats.messages_processed += message_count;
+            } else {
+                stats.record_chunk(block_number, message_count);
+            }
+        }
+
+        {
+            let mut sm = self.state_manager.write().await;
+            sm.increment_blocks_processed(shard_id, stats.blocks_processed)?;
+            sm.increment_messages_processed(shard_id, stats.messages_processed)?;
+            if let Some(block) = stats.last_block_number {
+                sm.update_last_processed_height(shard_id, block)?;
+            }
+            sm.update_last_event_id(shard_id, last_event_id)?;
+        }
+
+        debug!(
+            "Shard {}: applied {} events ({} messages), last event id {}",
+            shard_id, event_count, stats.messages_processed, last_event_id
+        );
+
+        totals.blocks_processed += stats.blocks_processed;
+        totals.messages_processed += stats.messages_processed;
+        if stats.last_block_number > totals.last_block_number {
+            totals.last_block_number = stats.last_block_number;
+        }
+
+        Ok(())
+    }
+
+    /// Poll `GetShardChunks` for new blocks on an interval
+    async fn poll_shard_realtime(&self, shard_id: u32) -> Result<()> {
         info!("Starting real-time monitoring for shard {}", shard_id);
         let retry_delay = tokio::time::Duration::from_millis(self.config.sync_interval_ms.max(100));
 
@@ -66,7 +243,9 @@ impl RealtimeMonitor {
                             shard_id, chunk_count, last_processed_height
                         );
 
-                        let stats = process_shard_chunks(&self.database, shard_id, chunks).await?;
+                        let stats =
+                            process_shard_chunks(&self.client, &self.database, shard_id, chunks)
+                                .await?;
 
                         if stats.blocks_processed > 0 {
                             let next_height = stats.last_block_number.map_or_else(
@@ -113,12 +292,14 @@ impl RealtimeMonitor {
 
 /// Process shard chunks and return statistics
 async fn process_shard_chunks(
+    client: &SnapchainClient,
     database: &Database,
     shard_id: u32,
     chunks: Vec<proto::ShardChunk>,
 ) -> Result<ChunkProcessStats> {
     let mut stats = ChunkProcessStats::default();
-    let processor = ShardProcessor::new(database.clone());
+    let processor =
+        ShardProcessor::new(database.clone()).with_chunk_source(Arc::new(client.clone()));
 
     for chunk in chunks {
         let block_number = extract_block_number(&chunk);
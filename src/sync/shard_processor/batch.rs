@@ -1,9 +1,18 @@
 use std::collections::HashMap;
 use std::fmt::Write as _;
 
+use sqlx::PgConnection;
 use tracing::warn;
 
+use super::copy;
+use super::copy::copy_merge;
+use super::copy::CopyRows;
 use super::types::BatchedData;
+use crate::config::FlushStrategy;
+use crate::database::height_ranges;
+use crate::database::record_block_hashes;
+use crate::database::record_change_events;
+use crate::database::record_processed_range;
 use crate::database::Database;
 use crate::Result;
 
@@ -11,35 +20,84 @@ use crate::Result;
 const MAX_PARAMS: usize = 65000;
 
 // Batch sizes for different entity types
-const PROFILE_PARAMS_PER_ROW: usize = 5; // fid, field_name, field_value, timestamp, message_hash
-const ONCHAIN_PARAMS_PER_ROW: usize = 9; // fid, event_type, chain_id, block_number, block_hash, block_timestamp, tx_hash, log_index, event_data
+const PROFILE_PARAMS_PER_ROW: usize = 8; // fid, field_name, field_value, timestamp, message_hash, shard_id, block_height, transaction_fid
+const ONCHAIN_PARAMS_PER_ROW: usize = 11; // fid, event_type, chain_id, block_number, block_hash, block_timestamp, tx_hash, log_index, event_data, shard_id, shard_block_height
 const USERNAME_PARAMS_PER_ROW: usize = 10; // fid, username, username_type, owner, signature, timestamp, message_hash, shard_id, block_height, transaction_fid
 const FRAME_PARAMS_PER_ROW: usize = 13; // fid, url, button_index, cast_hash, cast_fid, input_text, state, transaction_id, timestamp, message_hash, shard_id, block_height, transaction_fid
+const CAST_REMOVE_PARAMS_PER_ROW: usize = 7; // fid, target_hash, timestamp, message_hash, shard_id, block_height, transaction_fid
+const SIGNER_PARAMS_PER_ROW: usize = 14; // fid, key, key_type, metadata, active, added_block, added_at, removed_block, removed_at, removal_type, last_block_number, last_log_index, shard_id, block_height
+const REGISTRATION_PARAMS_PER_ROW: usize = 9; // fid, custody_address, recovery_address, registered_block, registered_at, last_block_number, last_log_index, shard_id, block_height
+const STORAGE_RENT_PARAMS_PER_ROW: usize = 10; // fid, payer, units, expiry, block_number, log_index, block_timestamp, transaction_hash, shard_id, block_height
+const FNAME_TRANSFER_PARAMS_PER_ROW: usize = 9; // transfer_id, from_fid, to_fid, username, owner, signature, timestamp, shard_id, block_height
+const STORAGE_LEND_PARAMS_PER_ROW: usize = 9; // fid, to_fid, num_units, unit_type, timestamp, message_hash, shard_id, block_height, transaction_fid
+const REJECTED_PARAMS_PER_ROW: usize = 8; // message_hash, fid, message_type, signer, reason, message, shard_id, block_height
 
 const PROFILE_CHUNK_SIZE: usize = MAX_PARAMS / PROFILE_PARAMS_PER_ROW;
 const ONCHAIN_CHUNK_SIZE: usize = MAX_PARAMS / ONCHAIN_PARAMS_PER_ROW;
 const USERNAME_CHUNK_SIZE: usize = MAX_PARAMS / USERNAME_PARAMS_PER_ROW;
 const FRAME_CHUNK_SIZE: usize = MAX_PARAMS / FRAME_PARAMS_PER_ROW;
+const CAST_REMOVE_CHUNK_SIZE: usize = MAX_PARAMS / CAST_REMOVE_PARAMS_PER_ROW;
+const SIGNER_CHUNK_SIZE: usize = MAX_PARAMS / SIGNER_PARAMS_PER_ROW;
+const REGISTRATION_CHUNK_SIZE: usize = MAX_PARAMS / REGISTRATION_PARAMS_PER_ROW;
+const STORAGE_RENT_CHUNK_SIZE: usize = MAX_PARAMS / STORAGE_RENT_PARAMS_PER_ROW;
+const FNAME_TRANSFER_CHUNK_SIZE: usize = MAX_PARAMS / FNAME_TRANSFER_PARAMS_PER_ROW;
+const STORAGE_LEND_CHUNK_SIZE: usize = MAX_PARAMS / STORAGE_LEND_PARAMS_PER_ROW;
+const REJECTED_CHUNK_SIZE: usize = MAX_PARAMS / REJECTED_PARAMS_PER_ROW;
 
 /// Flush batched data to database
 /// Public for testing, but re-exported through mod.rs
 pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Result<()> {
     let start = std::time::Instant::now();
+
+    // Start a transaction for the entire batch
+    let mut tx = database.pool().begin().await?;
+    let live_events = write_batched_data(&mut tx, database, batched).await?;
+    tx.commit().await?;
+    publish_live_feed(database, &live_events).await;
+
+    let elapsed = start.elapsed();
+    if elapsed.as_millis() > 1000 {
+        warn!("Batch flush took {}ms (slow!)", elapsed.as_millis());
+    } else {
+        tracing::trace!("Batch flush completed in {}ms", elapsed.as_millis());
+    }
+
+    Ok(())
+}
+
+/// Write batched data on `conn`, which the caller commits
+///
+/// Returns the live feed events of messages stored for the first time, to
+/// publish with [`publish_live_feed`] once the transaction has committed.
+pub async fn write_batched_data(
+    conn: &mut PgConnection,
+    database: &Database,
+    batched: BatchedData,
+) -> Result<Vec<crate::webhooks::ChangeEvent>> {
     tracing::trace!(
-        "Flushing batch: {} FIDs, {} casts, {} links, {} reactions, {} verifications, {} profile updates, {} onchain events, {} username proofs, {} frame actions",
+        "Flushing batch: {} FIDs, {} casts, {} cast removes, {} links, {} link compact states, {} reactions, {} verifications, {} profile updates, {} onchain events, {} username proofs, {} fname transfers, {} storage lends, {} frame actions",
         batched.fids_to_ensure.len(),
         batched.casts.len(),
+        batched.cast_removes.len(),
         batched.links.len(),
+        batched.link_compact_states.len(),
         batched.reactions.len(),
         batched.verifications.len(),
         batched.profile_updates.len(),
         batched.onchain_events.len(),
         batched.username_proofs.len(),
+        batched.fname_transfers.len(),
+        batched.storage_lends.len(),
         batched.frame_actions.len()
     );
 
-    // Start a transaction for the entire batch
-    let mut tx = database.pool().begin().await?;
+    // High-volume message tables go through binary COPY when configured
+    let use_copy = database.flush_strategy() == FlushStrategy::Copy;
+
+    // Change events for webhooks and the live feed, taken before the rows are
+    // moved into queries
+    let change_events = (database.change_capture() || database.live_feed())
+        .then(|| crate::webhooks::change_events(&batched));
 
     // Batch insert FIDs to user_profile_changes (event-sourcing table)
     // 🚀 EVENT-SOURCING MODE: Each FID creates a synthetic "fid_created" event
@@ -87,7 +145,7 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                     .bind(synthetic_hash);
             }
 
-            let result = q.execute(&mut *tx).await?;
+            let result = q.execute(&mut *conn).await?;
             if result.rows_affected() > 0 {
                 tracing::debug!("Created {} FID events", result.rows_affected());
             }
@@ -120,45 +178,46 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
             );
         }
 
-        const PARAMS_PER_ROW: usize = 10; // Added shard_id and block_height
-        const MAX_PARAMS: usize = 65000; // Keep below u16::MAX (65535)
-        const CHUNK_SIZE: usize = MAX_PARAMS / PARAMS_PER_ROW; // ~6500 rows per chunk
-
-        // Split casts into chunks
-        for chunk in deduped_casts.chunks(CHUNK_SIZE) {
-            // Build dynamic query
-            // 🚀 Pre-allocate capacity
-            let estimated_size = 150 + chunk.len() * 60;
-            let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO casts (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions, shard_id, block_height) VALUES ");
-
-            // 🚀 Direct string building
-            for i in 0..chunk.len() {
-                if i > 0 {
-                    query.push_str(", ");
+        // Thread roots: top-level casts are their own root, replies inherit the
+        // root of their parent from this batch or from rows already stored
+        let parents: HashMap<Vec<u8>, Option<Vec<u8>>> = deduped_casts
+            .iter()
+            .map(|cast| (cast.3.clone(), cast.4.clone()))
+            .collect();
+        let external_parents: Vec<Vec<u8>> = parents
+            .values()
+            .flatten()
+            .filter(|parent| !parents.contains_key(*parent))
+            .cloned()
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        let mut stored_roots = HashMap::new();
+        if !external_parents.is_empty() {
+            let rows = sqlx::query_as::<_, (Vec<u8>, Option<Vec<u8>>)>(
+                r"
+                SELECT message_hash,
+                       COALESCE(root_hash, CASE WHEN parent_hash IS NULL THEN message_hash END)
+                FROM casts WHERE message_hash = ANY($1)
+                ",
+            )
+            .bind(&external_parents)
+            .fetch_all(&mut *conn)
+            .await?;
+            for (hash, root) in rows {
+                if let Some(root) = root {
+                    stored_roots.insert(hash, root);
                 }
-                let base = i * PARAMS_PER_ROW;
-                query.push_str(&format!(
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                    base + 4,
-                    base + 5,
-                    base + 6,
-                    base + 7,
-                    base + 8,
-                    base + 9,
-                    base + 10
-                ));
             }
+        }
+        let roots = resolve_root_hashes(&parents, &stored_roots);
 
-            // 🚀 CRITICAL FIX: Use DO NOTHING for re-sync performance
-            // Casts are immutable - if message_hash exists, no need to update
-            // This prevents 166M+ unnecessary updates on re-sync
-            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+        const PARAMS_PER_ROW: usize = 13; // Added shard_id, block_height, transaction_fid, parent_fid and parent_url
+        const MAX_PARAMS: usize = 65000; // Keep below u16::MAX (65535)
+        const CHUNK_SIZE: usize = MAX_PARAMS / PARAMS_PER_ROW; // ~6500 rows per chunk
 
-            let mut q = sqlx::query(&query);
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::CASTS);
             for (
                 fid,
                 text,
@@ -168,23 +227,228 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 root_hash,
                 embeds,
                 mentions,
+                parent_fid,
+                parent_url,
                 shard_block_info,
-            ) in chunk
+            ) in &deduped_casts
             {
-                q = q
-                    .bind(fid)
-                    .bind(text)
-                    .bind(timestamp)
-                    .bind(message_hash)
-                    .bind(parent_hash)
-                    .bind(root_hash)
-                    .bind(embeds)
-                    .bind(mentions)
-                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+                let root_hash = root_hash
+                    .as_ref()
+                    .or_else(|| roots.get(message_hash))
+                    .cloned();
+                rows.push(&[
+                    fid,
+                    text,
+                    timestamp,
+                    message_hash,
+                    parent_hash,
+                    &root_hash,
+                    embeds,
+                    mentions,
+                    parent_fid,
+                    parent_url,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                    &i64::try_from(shard_block_info.transaction_fid).unwrap_or(0),
+                ]);
             }
+            copy_merge(conn, rows).await?;
+        } else {
+            // Split casts into chunks
+            for chunk in deduped_casts.chunks(CHUNK_SIZE) {
+                // Build dynamic query
+                // 🚀 Pre-allocate capacity
+                let estimated_size = 150 + chunk.len() * 60;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO casts (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions, parent_fid, parent_url, shard_id, block_height, transaction_fid) VALUES ");
+
+                // 🚀 Direct string building
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * PARAMS_PER_ROW;
+                    query.push_str(&format!(
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7,
+                        base + 8,
+                        base + 9,
+                        base + 10,
+                        base + 11,
+                        base + 12,
+                        base + 13
+                    ));
+                }
+
+                // 🚀 CRITICAL FIX: Use DO NOTHING for re-sync performance
+                // Casts are immutable - if message_hash exists, no need to update
+                // This prevents 166M+ unnecessary updates on re-sync
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (
+                    fid,
+                    text,
+                    timestamp,
+                    message_hash,
+                    parent_hash,
+                    root_hash,
+                    embeds,
+                    mentions,
+                    parent_fid,
+                    parent_url,
+                    shard_block_info,
+                ) in chunk
+                {
+                    let root_hash = root_hash.as_ref().or_else(|| roots.get(message_hash));
+                    q = q
+                        .bind(fid)
+                        .bind(text)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(parent_hash)
+                        .bind(root_hash)
+                        .bind(embeds)
+                        .bind(mentions)
+                        .bind(parent_fid)
+                        .bind(parent_url)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                }
 
-            q.execute(&mut *tx).await?;
+                q.execute(&mut *conn).await?;
+            }
+        }
+
+        // Replies stored before their parent arrived can now inherit its root,
+        // and so can their own stored replies, however deep
+        let inserted: Vec<Vec<u8>> = deduped_casts.iter().map(|cast| cast.3.clone()).collect();
+        sqlx::query(
+            r"
+            WITH RECURSIVE orphans AS (
+                SELECT c.message_hash, p.root_hash
+                FROM casts c
+                JOIN casts p ON p.message_hash = c.parent_hash
+                WHERE c.parent_hash = ANY($1) AND c.root_hash IS NULL
+                  AND p.root_hash IS NOT NULL
+                UNION ALL
+                SELECT c.message_hash, o.root_hash
+                FROM casts c
+                JOIN orphans o ON c.parent_hash = o.message_hash
+                WHERE c.root_hash IS NULL
+            )
+            UPDATE casts c SET root_hash = o.root_hash
+            FROM orphans o
+            WHERE c.message_hash = o.message_hash
+            ",
+        )
+        .bind(&inserted)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    // Batch insert cast removes (tombstones)
+    // Casts stay append-only; readers filter on cast_removes. Derived embeddings
+    // are dropped here so removed casts no longer surface in vector search.
+    if !batched.cast_removes.is_empty() {
+        tracing::debug!(
+            "🗑️  Batch inserting {} cast removes",
+            batched.cast_removes.len()
+        );
+
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::CAST_REMOVES);
+            for (fid, target_hash, timestamp, message_hash, shard_block_info) in
+                &batched.cast_removes
+            {
+                rows.push(&[
+                    fid,
+                    target_hash,
+                    timestamp,
+                    message_hash,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                    &i64::try_from(shard_block_info.transaction_fid).unwrap_or(0),
+                ]);
+            }
+            copy_merge(conn, rows).await?;
+        } else {
+            for chunk in batched.cast_removes.chunks(CAST_REMOVE_CHUNK_SIZE) {
+                let estimated_size = 200 + chunk.len() * 50;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO cast_removes (fid, target_hash, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
+
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * CAST_REMOVE_PARAMS_PER_ROW;
+                    write!(
+                        &mut query,
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7
+                    )
+                    .expect("write! to String should not fail");
+                }
+
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (fid, target_hash, timestamp, message_hash, shard_block_info) in chunk {
+                    q = q
+                        .bind(fid)
+                        .bind(target_hash)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                }
+
+                q.execute(&mut *conn).await?;
+            }
+        }
+
+        let (fids, target_hashes): (Vec<i64>, Vec<Vec<u8>>) = batched
+            .cast_removes
+            .iter()
+            .map(|(fid, target_hash, ..)| (*fid, target_hash.clone()))
+            .unzip();
+
+        for table in [
+            "cast_embeddings",
+            "cast_embedding_chunks",
+            "cast_embedding_aggregated",
+        ] {
+            let result = sqlx::query(&format!(
+                "DELETE FROM {table} t USING unnest($1::bigint[], $2::bytea[]) AS r(fid, target_hash) \
+                 WHERE t.message_hash = r.target_hash AND t.fid = r.fid"
+            ))
+            .bind(&fids)
+            .bind(&target_hashes)
+            .execute(&mut *conn)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                tracing::debug!(
+                    "Removed {} rows from {} for deleted casts",
+                    result.rows_affected(),
+                    table
+                );
+            }
         }
     }
 
@@ -196,34 +460,8 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
         const MAX_PARAMS: usize = 65000;
         const CHUNK_SIZE: usize = MAX_PARAMS / PARAMS_PER_ROW;
 
-        for chunk in batched.links.chunks(CHUNK_SIZE) {
-            // 🚀 Pre-allocate
-            let estimated_size = 150 + chunk.len() * 60;
-            let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO links (fid, target_fid, link_type, event_type, timestamp, message_hash, shard_id, block_height) VALUES ");
-
-            // 🚀 Direct building
-            for i in 0..chunk.len() {
-                if i > 0 {
-                    query.push_str(", ");
-                }
-                let base = i * PARAMS_PER_ROW;
-                query.push_str(&format!(
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                    base + 4,
-                    base + 5,
-                    base + 6,
-                    base + 7,
-                    base + 8
-                ));
-            }
-
-            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
-
-            let mut q = sqlx::query(&query);
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::LINKS);
             for (
                 fid,
                 target_fid,
@@ -232,20 +470,180 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 timestamp,
                 message_hash,
                 shard_block_info,
-            ) in chunk
+            ) in &batched.links
             {
-                q = q
-                    .bind(fid)
-                    .bind(target_fid)
-                    .bind(link_type)
-                    .bind(event_type)
-                    .bind(timestamp)
-                    .bind(message_hash)
-                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+                rows.push(&[
+                    fid,
+                    target_fid,
+                    link_type,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                ]);
+            }
+            copy_merge(conn, rows).await?;
+        } else {
+            for chunk in batched.links.chunks(CHUNK_SIZE) {
+                // 🚀 Pre-allocate
+                let estimated_size = 150 + chunk.len() * 60;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO links (fid, target_fid, link_type, event_type, timestamp, message_hash, shard_id, block_height) VALUES ");
+
+                // 🚀 Direct building
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * PARAMS_PER_ROW;
+                    query.push_str(&format!(
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7,
+                        base + 8
+                    ));
+                }
+
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (
+                    fid,
+                    target_fid,
+                    link_type,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    shard_block_info,
+                ) in chunk
+                {
+                    q = q
+                        .bind(fid)
+                        .bind(target_fid)
+                        .bind(link_type)
+                        .bind(event_type)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+                }
+
+                q.execute(&mut *conn).await?;
+            }
+        }
+    }
+
+    // Apply link compact states after the regular link events of this batch
+    // The compact state is authoritative as of its timestamp: links active before
+    // it but missing from the set get a synthetic 'remove', targets in the set that
+    // are not active get a synthetic 'add'. Events newer than the state still win.
+    // Synthetic events use message_hash = compact message_hash || target_fid, so
+    // re-applying the same state is a no-op.
+    if !batched.link_compact_states.is_empty() {
+        tracing::debug!(
+            "📎 Applying {} link compact states",
+            batched.link_compact_states.len()
+        );
+
+        let mut compact_states: Vec<_> = batched.link_compact_states.iter().collect();
+        compact_states.sort_by_key(|state| state.3);
+
+        for (fid, link_type, target_fids, timestamp, message_hash, shard_block_info) in
+            compact_states
+        {
+            let shard_id = i32::try_from(shard_block_info.shard_id).unwrap_or(0);
+            let block_height = i64::try_from(shard_block_info.block_height).unwrap_or(0);
+            let transaction_fid = i64::try_from(shard_block_info.transaction_fid).unwrap_or(0);
+
+            let inserted = sqlx::query(
+                r"
+                INSERT INTO link_compact_states (fid, link_type, target_fids, timestamp, message_hash, shard_id, block_height, transaction_fid)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (message_hash) DO NOTHING
+                ",
+            )
+            .bind(fid)
+            .bind(link_type)
+            .bind(target_fids)
+            .bind(timestamp)
+            .bind(message_hash)
+            .bind(shard_id)
+            .bind(block_height)
+            .bind(transaction_fid)
+            .execute(&mut *conn)
+            .await?;
+
+            if inserted.rows_affected() == 0 {
+                continue; // Already applied
             }
 
-            q.execute(&mut *tx).await?;
+            let removed = sqlx::query(
+                r"
+                INSERT INTO links (fid, target_fid, link_type, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid)
+                SELECT l.fid, l.target_fid, l.link_type, 'remove', $3, $4 || int8send(l.target_fid), $6, $7, $8
+                FROM (
+                    SELECT DISTINCT ON (target_fid) fid, target_fid, link_type, event_type, timestamp
+                    FROM links
+                    WHERE fid = $1 AND link_type = $2
+                    ORDER BY target_fid, timestamp DESC
+                ) l
+                WHERE l.event_type = 'add' AND l.timestamp < $3 AND NOT (l.target_fid = ANY($5))
+                ON CONFLICT (message_hash) DO NOTHING
+                ",
+            )
+            .bind(fid)
+            .bind(link_type)
+            .bind(timestamp)
+            .bind(message_hash)
+            .bind(target_fids)
+            .bind(shard_id)
+            .bind(block_height)
+            .bind(transaction_fid)
+            .execute(&mut *conn)
+            .await?;
+
+            let added = sqlx::query(
+                r"
+                INSERT INTO links (fid, target_fid, link_type, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid)
+                SELECT $1, t.target_fid, $2, 'add', $3, $4 || int8send(t.target_fid), $6, $7, $8
+                FROM unnest($5::bigint[]) AS t(target_fid)
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM (
+                        SELECT event_type, timestamp FROM links
+                        WHERE fid = $1 AND target_fid = t.target_fid AND link_type = $2
+                        ORDER BY timestamp DESC
+                        LIMIT 1
+                    ) latest
+                    WHERE latest.event_type = 'add' OR latest.timestamp >= $3
+                )
+                ON CONFLICT (message_hash) DO NOTHING
+                ",
+            )
+            .bind(fid)
+            .bind(link_type)
+            .bind(timestamp)
+            .bind(message_hash)
+            .bind(target_fids)
+            .bind(shard_id)
+            .bind(block_height)
+            .bind(transaction_fid)
+            .execute(&mut *conn)
+            .await?;
+
+            tracing::debug!(
+                "Link compact state for FID {} ({}): {} targets, {} added, {} removed",
+                fid,
+                link_type,
+                target_fids.len(),
+                added.rows_affected(),
+                removed.rows_affected()
+            );
         }
     }
 
@@ -257,37 +655,8 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
         const MAX_PARAMS: usize = 65000;
         const CHUNK_SIZE: usize = MAX_PARAMS / PARAMS_PER_ROW;
 
-        for chunk in batched.reactions.chunks(CHUNK_SIZE) {
-            // 🚀 Pre-allocate
-            let estimated_size = 200 + chunk.len() * 75;
-            let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO reactions (fid, target_cast_hash, target_fid, reaction_type, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
-
-            // 🚀 Direct building
-            for i in 0..chunk.len() {
-                if i > 0 {
-                    query.push_str(", ");
-                }
-                let base = i * PARAMS_PER_ROW;
-                query.push_str(&format!(
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                    base + 4,
-                    base + 5,
-                    base + 6,
-                    base + 7,
-                    base + 8,
-                    base + 9,
-                    base + 10
-                ));
-            }
-
-            // Only check message_hash (composite constraint will be removed in migration 007)
-            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
-
-            let mut q = sqlx::query(&query);
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::REACTIONS);
             for (
                 fid,
                 target_cast_hash,
@@ -297,22 +666,80 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 timestamp,
                 message_hash,
                 shard_block_info,
-            ) in chunk
+            ) in &batched.reactions
             {
-                q = q
-                    .bind(fid)
-                    .bind(target_cast_hash)
-                    .bind(target_fid)
-                    .bind(reaction_type)
-                    .bind(event_type)
-                    .bind(timestamp)
-                    .bind(message_hash)
-                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                rows.push(&[
+                    fid,
+                    target_cast_hash,
+                    target_fid,
+                    reaction_type,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                    &i64::try_from(shard_block_info.transaction_fid).unwrap_or(0),
+                ]);
             }
+            copy_merge(conn, rows).await?;
+        } else {
+            for chunk in batched.reactions.chunks(CHUNK_SIZE) {
+                // 🚀 Pre-allocate
+                let estimated_size = 200 + chunk.len() * 75;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO reactions (fid, target_cast_hash, target_fid, reaction_type, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
+
+                // 🚀 Direct building
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * PARAMS_PER_ROW;
+                    query.push_str(&format!(
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7,
+                        base + 8,
+                        base + 9,
+                        base + 10
+                    ));
+                }
+
+                // Only check message_hash (composite constraint will be removed in migration 007)
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (
+                    fid,
+                    target_cast_hash,
+                    target_fid,
+                    reaction_type,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    shard_block_info,
+                ) in chunk
+                {
+                    q = q
+                        .bind(fid)
+                        .bind(target_cast_hash)
+                        .bind(target_fid)
+                        .bind(reaction_type)
+                        .bind(event_type)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                }
 
-            q.execute(&mut *tx).await?;
+                q.execute(&mut *conn).await?;
+            }
         }
     }
 
@@ -327,39 +754,8 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
         const MAX_PARAMS: usize = 65000;
         const CHUNK_SIZE: usize = MAX_PARAMS / PARAMS_PER_ROW;
 
-        for chunk in batched.verifications.chunks(CHUNK_SIZE) {
-            // 🚀 Pre-allocate
-            let estimated_size = 250 + chunk.len() * 85;
-            let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO verifications (fid, address, claim_signature, block_hash, verification_type, chain_id, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
-
-            // 🚀 Direct building
-            for i in 0..chunk.len() {
-                if i > 0 {
-                    query.push_str(", ");
-                }
-                let base = i * PARAMS_PER_ROW;
-                query.push_str(&format!(
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                    base + 4,
-                    base + 5,
-                    base + 6,
-                    base + 7,
-                    base + 8,
-                    base + 9,
-                    base + 10,
-                    base + 11,
-                    base + 12
-                ));
-            }
-
-            // Only check message_hash (composite constraint will be removed in migration 007)
-            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
-
-            let mut q = sqlx::query(&query);
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::VERIFICATIONS);
             for (
                 fid,
                 address,
@@ -371,24 +767,88 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 timestamp,
                 message_hash,
                 shard_block_info,
-            ) in chunk
+            ) in &batched.verifications
             {
-                q = q
-                    .bind(fid)
-                    .bind(address)
-                    .bind(claim_signature)
-                    .bind(block_hash)
-                    .bind(verification_type)
-                    .bind(chain_id)
-                    .bind(event_type)
-                    .bind(timestamp)
-                    .bind(message_hash)
-                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
-                    .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                rows.push(&[
+                    fid,
+                    address,
+                    claim_signature,
+                    block_hash,
+                    verification_type,
+                    chain_id,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                    &i64::try_from(shard_block_info.transaction_fid).unwrap_or(0),
+                ]);
             }
+            copy_merge(conn, rows).await?;
+        } else {
+            for chunk in batched.verifications.chunks(CHUNK_SIZE) {
+                // 🚀 Pre-allocate
+                let estimated_size = 250 + chunk.len() * 85;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO verifications (fid, address, claim_signature, block_hash, verification_type, chain_id, event_type, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
+
+                // 🚀 Direct building
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * PARAMS_PER_ROW;
+                    query.push_str(&format!(
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7,
+                        base + 8,
+                        base + 9,
+                        base + 10,
+                        base + 11,
+                        base + 12
+                    ));
+                }
+
+                // Only check message_hash (composite constraint will be removed in migration 007)
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (
+                    fid,
+                    address,
+                    claim_signature,
+                    block_hash,
+                    verification_type,
+                    chain_id,
+                    event_type,
+                    timestamp,
+                    message_hash,
+                    shard_block_info,
+                ) in chunk
+                {
+                    q = q
+                        .bind(fid)
+                        .bind(address)
+                        .bind(claim_signature)
+                        .bind(block_hash)
+                        .bind(verification_type)
+                        .bind(chain_id)
+                        .bind(event_type)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                }
 
-            q.execute(&mut *tx).await?;
+                q.execute(&mut *conn).await?;
+            }
         }
     }
 
@@ -424,43 +884,67 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
             );
         }
 
-        for chunk in updates_list.chunks(PROFILE_CHUNK_SIZE) {
-            let estimated_size = 200 + chunk.len() * 50;
-            let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO user_profile_changes (fid, field_name, field_value, timestamp, message_hash) VALUES ");
-
-            for i in 0..chunk.len() {
-                if i > 0 {
-                    query.push_str(", ");
-                }
-                let base = i * PROFILE_PARAMS_PER_ROW;
-                write!(
-                    &mut query,
-                    "(${}, ${}, ${}, ${}, ${})",
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                    base + 4,
-                    base + 5
-                )
-                .expect("write! to String should not fail");
+        if use_copy {
+            let mut rows = CopyRows::new(&copy::PROFILE_CHANGES);
+            for (fid, field_name, value, timestamp, message_hash, shard_block_info) in &updates_list
+            {
+                rows.push(&[
+                    fid,
+                    field_name,
+                    value,
+                    timestamp,
+                    message_hash,
+                    &i32::try_from(shard_block_info.shard_id).unwrap_or(0),
+                    &i64::try_from(shard_block_info.block_height).unwrap_or(0),
+                    &i64::try_from(shard_block_info.transaction_fid).unwrap_or(0),
+                ]);
             }
+            copy_merge(conn, rows).await?;
+        } else {
+            for chunk in updates_list.chunks(PROFILE_CHUNK_SIZE) {
+                let estimated_size = 200 + chunk.len() * 50;
+                let mut query = String::with_capacity(estimated_size);
+                query.push_str("INSERT INTO user_profile_changes (fid, field_name, field_value, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
+
+                for i in 0..chunk.len() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * PROFILE_PARAMS_PER_ROW;
+                    write!(
+                        &mut query,
+                        "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                        base + 1,
+                        base + 2,
+                        base + 3,
+                        base + 4,
+                        base + 5,
+                        base + 6,
+                        base + 7,
+                        base + 8
+                    )
+                    .expect("write! to String should not fail");
+                }
 
-            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+                query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+                let mut q = sqlx::query(&query);
+                for (fid, field_name, value, timestamp, message_hash, shard_block_info) in chunk {
+                    // Use the actual message_hash from Farcaster (passed from message_handlers)
+                    // This ensures deduplication works correctly across re-syncs
+                    q = q
+                        .bind(fid)
+                        .bind(field_name)
+                        .bind(value)
+                        .bind(timestamp)
+                        .bind(message_hash)
+                        .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                        .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+                }
 
-            let mut q = sqlx::query(&query);
-            for (fid, field_name, value, timestamp, message_hash) in chunk {
-                // Use the actual message_hash from Farcaster (passed from message_handlers)
-                // This ensures deduplication works correctly across re-syncs
-                q = q
-                    .bind(fid)
-                    .bind(field_name)
-                    .bind(value)
-                    .bind(timestamp)
-                    .bind(message_hash);
+                q.execute(&mut *conn).await?;
             }
-
-            q.execute(&mut *tx).await?;
         }
     }
 
@@ -493,7 +977,7 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
         for chunk in deduped_events.chunks(ONCHAIN_CHUNK_SIZE) {
             let estimated_size = 250 + chunk.len() * 70;
             let mut query = String::with_capacity(estimated_size);
-            query.push_str("INSERT INTO onchain_events (fid, event_type, chain_id, block_number, block_hash, block_timestamp, transaction_hash, log_index, event_data) VALUES ");
+            query.push_str("INSERT INTO onchain_events (fid, event_type, chain_id, block_number, block_hash, block_timestamp, transaction_hash, log_index, event_data, shard_id, shard_block_height) VALUES ");
 
             for i in 0..chunk.len() {
                 if i > 0 {
@@ -502,7 +986,7 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 let base = i * ONCHAIN_PARAMS_PER_ROW;
                 write!(
                     &mut query,
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
                     base + 1,
                     base + 2,
                     base + 3,
@@ -511,7 +995,9 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                     base + 6,
                     base + 7,
                     base + 8,
-                    base + 9
+                    base + 9,
+                    base + 10,
+                    base + 11
                 )
                 .expect("write! to String should not fail");
             }
@@ -529,6 +1015,7 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                 tx_hash,
                 log_index,
                 event_data,
+                shard_block_info,
             ) in chunk
             {
                 q = q
@@ -540,10 +1027,99 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                     .bind(block_timestamp)
                     .bind(tx_hash)
                     .bind(log_index)
-                    .bind(event_data);
+                    .bind(event_data)
+                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+            }
+
+            q.execute(&mut *conn).await?;
+        }
+    }
+
+    // Maintain decoded on-chain state (signers, custody/recovery, storage rent)
+    // Events are folded per key first so each row is written once per batch; the
+    // upsert only applies state newer than what is stored (by block, log index).
+    if !batched.signer_events.is_empty() {
+        let signers = fold_signer_events(&batched.signer_events);
+        tracing::debug!(
+            "🔑 Upserting {} signers from {} signer events",
+            signers.len(),
+            batched.signer_events.len()
+        );
+        upsert_signers(conn, &signers).await?;
+    }
+
+    if !batched.id_register_events.is_empty() {
+        let registrations = fold_id_register_events(&batched.id_register_events);
+        tracing::debug!(
+            "🪪 Upserting {} FID registrations from {} id register events",
+            registrations.len(),
+            batched.id_register_events.len()
+        );
+        upsert_registrations(conn, &registrations).await?;
+    }
+
+    if !batched.storage_rents.is_empty() {
+        tracing::debug!(
+            "💾 Batch inserting {} storage rents",
+            batched.storage_rents.len()
+        );
+
+        for chunk in batched.storage_rents.chunks(STORAGE_RENT_CHUNK_SIZE) {
+            let mut query = String::with_capacity(200 + chunk.len() * 50);
+            query.push_str("INSERT INTO storage_rents (fid, payer, units, expiry, block_number, log_index, block_timestamp, transaction_hash, shard_id, block_height) VALUES ");
+
+            for i in 0..chunk.len() {
+                if i > 0 {
+                    query.push_str(", ");
+                }
+                let base = i * STORAGE_RENT_PARAMS_PER_ROW;
+                write!(
+                    &mut query,
+                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    base + 1,
+                    base + 2,
+                    base + 3,
+                    base + 4,
+                    base + 5,
+                    base + 6,
+                    base + 7,
+                    base + 8,
+                    base + 9,
+                    base + 10
+                )
+                .expect("write! to String should not fail");
+            }
+
+            query.push_str(" ON CONFLICT (block_number, log_index) DO NOTHING");
+
+            let mut q = sqlx::query(&query);
+            for (
+                fid,
+                payer,
+                units,
+                expiry,
+                block_number,
+                log_index,
+                block_timestamp,
+                transaction_hash,
+                shard_block_info,
+            ) in chunk
+            {
+                q = q
+                    .bind(fid)
+                    .bind(payer)
+                    .bind(units)
+                    .bind(expiry)
+                    .bind(block_number)
+                    .bind(log_index)
+                    .bind(block_timestamp)
+                    .bind(transaction_hash)
+                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
             }
 
-            q.execute(&mut *tx).await?;
+            q.execute(&mut *conn).await?;
         }
     }
 
@@ -612,7 +1188,125 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                     .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
             }
 
-            q.execute(&mut *tx).await?;
+            q.execute(&mut *conn).await?;
+        }
+    }
+
+    // Batch insert fname transfers (ownership history)
+    if !batched.fname_transfers.is_empty() {
+        tracing::info!(
+            "🏷️  Batch inserting {} fname transfers",
+            batched.fname_transfers.len()
+        );
+
+        for chunk in batched.fname_transfers.chunks(FNAME_TRANSFER_CHUNK_SIZE) {
+            let estimated_size = 200 + chunk.len() * 60;
+            let mut query = String::with_capacity(estimated_size);
+            query.push_str("INSERT INTO fname_transfers (transfer_id, from_fid, to_fid, username, owner, signature, timestamp, shard_id, block_height) VALUES ");
+
+            for i in 0..chunk.len() {
+                if i > 0 {
+                    query.push_str(", ");
+                }
+                let base = i * FNAME_TRANSFER_PARAMS_PER_ROW;
+                write!(
+                    &mut query,
+                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    base + 1,
+                    base + 2,
+                    base + 3,
+                    base + 4,
+                    base + 5,
+                    base + 6,
+                    base + 7,
+                    base + 8,
+                    base + 9
+                )
+                .expect("write! to String should not fail");
+            }
+
+            query.push_str(" ON CONFLICT (transfer_id) DO NOTHING");
+
+            let mut q = sqlx::query(&query);
+            for (
+                transfer_id,
+                from_fid,
+                to_fid,
+                username,
+                owner,
+                signature,
+                timestamp,
+                shard_block_info,
+            ) in chunk
+            {
+                q = q
+                    .bind(transfer_id)
+                    .bind(from_fid)
+                    .bind(to_fid)
+                    .bind(username)
+                    .bind(owner)
+                    .bind(signature)
+                    .bind(timestamp)
+                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+            }
+
+            q.execute(&mut *conn).await?;
+        }
+    }
+
+    // Batch insert storage lends
+    if !batched.storage_lends.is_empty() {
+        tracing::info!(
+            "💾 Batch inserting {} storage lends",
+            batched.storage_lends.len()
+        );
+
+        for chunk in batched.storage_lends.chunks(STORAGE_LEND_CHUNK_SIZE) {
+            let estimated_size = 200 + chunk.len() * 60;
+            let mut query = String::with_capacity(estimated_size);
+            query.push_str("INSERT INTO storage_lends (fid, to_fid, num_units, unit_type, timestamp, message_hash, shard_id, block_height, transaction_fid) VALUES ");
+
+            for i in 0..chunk.len() {
+                if i > 0 {
+                    query.push_str(", ");
+                }
+                let base = i * STORAGE_LEND_PARAMS_PER_ROW;
+                write!(
+                    &mut query,
+                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    base + 1,
+                    base + 2,
+                    base + 3,
+                    base + 4,
+                    base + 5,
+                    base + 6,
+                    base + 7,
+                    base + 8,
+                    base + 9
+                )
+                .expect("write! to String should not fail");
+            }
+
+            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+            let mut q = sqlx::query(&query);
+            for (fid, to_fid, num_units, unit_type, timestamp, message_hash, shard_block_info) in
+                chunk
+            {
+                q = q
+                    .bind(fid)
+                    .bind(to_fid)
+                    .bind(num_units)
+                    .bind(unit_type)
+                    .bind(timestamp)
+                    .bind(message_hash)
+                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
+            }
+
+            q.execute(&mut *conn).await?;
         }
     }
 
@@ -686,19 +1380,643 @@ pub async fn flush_batched_data(database: &Database, batched: BatchedData) -> Re
                     .bind(i64::try_from(shard_block_info.transaction_fid).unwrap_or(0));
             }
 
-            q.execute(&mut *tx).await?;
+            q.execute(&mut *conn).await?;
         }
     }
 
-    // Commit the transaction
-    tx.commit().await?;
+    // Batch insert messages that failed verification
+    if !batched.rejected_messages.is_empty() {
+        tracing::info!(
+            "🚫 Batch inserting {} rejected messages",
+            batched.rejected_messages.len()
+        );
 
-    let elapsed = start.elapsed();
-    if elapsed.as_millis() > 1000 {
-        warn!("Batch flush took {}ms (slow!)", elapsed.as_millis());
-    } else {
-        tracing::trace!("Batch flush completed in {}ms", elapsed.as_millis());
+        for chunk in batched.rejected_messages.chunks(REJECTED_CHUNK_SIZE) {
+            let estimated_size = 200 + chunk.len() * 60;
+            let mut query = String::with_capacity(estimated_size);
+            query.push_str("INSERT INTO rejected_messages (message_hash, fid, message_type, signer, reason, message, shard_id, block_height) VALUES ");
+
+            for i in 0..chunk.len() {
+                if i > 0 {
+                    query.push_str(", ");
+                }
+                let base = i * REJECTED_PARAMS_PER_ROW;
+                write!(
+                    &mut query,
+                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    base + 1,
+                    base + 2,
+                    base + 3,
+                    base + 4,
+                    base + 5,
+                    base + 6,
+                    base + 7,
+                    base + 8
+                )
+                .expect("write! to String should not fail");
+            }
+
+            query.push_str(" ON CONFLICT (message_hash) DO NOTHING");
+
+            let mut q = sqlx::query(&query);
+            for (message_hash, fid, message_type, signer, reason, message, shard_block_info) in
+                chunk
+            {
+                q = q
+                    .bind(message_hash)
+                    .bind(fid)
+                    .bind(message_type)
+                    .bind(signer)
+                    .bind(*reason)
+                    .bind(message)
+                    .bind(i32::try_from(shard_block_info.shard_id).unwrap_or(0))
+                    .bind(i64::try_from(shard_block_info.block_height).unwrap_or(0));
+            }
+
+            q.execute(&mut *conn).await?;
+        }
+    }
+
+    // Record processed heights in the same transaction as their data
+    if !batched.processed_blocks.is_empty() {
+        let mut heights_by_shard: HashMap<u32, Vec<u64>> = HashMap::new();
+        for (shard_id, height) in &batched.processed_blocks {
+            heights_by_shard.entry(*shard_id).or_default().push(*height);
+        }
+        for (shard_id, heights) in heights_by_shard {
+            for range in height_ranges(&heights) {
+                record_processed_range(conn, shard_id, range).await?;
+            }
+        }
+    }
+    if !batched.block_hashes.is_empty() {
+        record_block_hashes(conn, &batched.block_hashes).await?;
+    }
+
+    // Queue webhook deliveries with the messages they describe, so a committed
+    // batch is never missing its deliveries. The live feed gets only the events
+    // recorded for the first time, not messages replayed by a re-sync or reorg.
+    let mut live_events = Vec::new();
+    if let Some(events) = change_events.filter(|events| !events.is_empty()) {
+        let mut recorded = record_change_events(
+            conn,
+            &events,
+            database.change_capture(),
+            database.live_feed(),
+        )
+        .await?;
+        if recorded.queued > 0 {
+            tracing::debug!("🪝 Queued {} webhook deliveries", recorded.queued);
+        }
+        if database.live_feed() {
+            live_events = events
+                .into_iter()
+                .filter(|event| recorded.inserted.remove(&event.message_hash))
+                .collect();
+        }
+    }
+
+    Ok(live_events)
+}
+
+/// Push committed messages to live feed clients
+///
+/// The live feed is at-most-once: the batch is already durable and clients
+/// have no replay, so a failed NOTIFY only costs connected clients events.
+pub async fn publish_live_feed(database: &Database, events: &[crate::webhooks::ChangeEvent]) {
+    if events.is_empty() {
+        return;
+    }
+    if let Err(e) = database.notify_live_feed(events).await {
+        warn!("Failed to publish {} live feed events: {}", events.len(), e);
+    }
+}
+
+/// Upsert folded signer state, leaving rows that already hold newer state
+async fn upsert_signers(conn: &mut PgConnection, signers: &[SignerState]) -> Result<()> {
+    for chunk in signers.chunks(SIGNER_CHUNK_SIZE) {
+        let mut query = String::with_capacity(600 + chunk.len() * 80);
+        query.push_str("INSERT INTO fid_signers (fid, key, key_type, metadata, active, added_block, added_at, removed_block, removed_at, removal_type, last_block_number, last_log_index, shard_id, block_height) VALUES ");
+
+        for i in 0..chunk.len() {
+            if i > 0 {
+                query.push_str(", ");
+            }
+            let base = i * SIGNER_PARAMS_PER_ROW;
+            write!(
+                &mut query,
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                base + 1,
+                base + 2,
+                base + 3,
+                base + 4,
+                base + 5,
+                base + 6,
+                base + 7,
+                base + 8,
+                base + 9,
+                base + 10,
+                base + 11,
+                base + 12,
+                base + 13,
+                base + 14
+            )
+            .expect("write! to String should not fail");
+        }
+
+        query.push_str(
+            " ON CONFLICT (fid, key) DO UPDATE SET \
+             key_type = EXCLUDED.key_type, \
+             metadata = COALESCE(EXCLUDED.metadata, fid_signers.metadata), \
+             active = EXCLUDED.active, \
+             added_block = COALESCE(EXCLUDED.added_block, fid_signers.added_block), \
+             added_at = COALESCE(EXCLUDED.added_at, fid_signers.added_at), \
+             removed_block = EXCLUDED.removed_block, \
+             removed_at = EXCLUDED.removed_at, \
+             removal_type = EXCLUDED.removal_type, \
+             last_block_number = EXCLUDED.last_block_number, \
+             last_log_index = EXCLUDED.last_log_index, \
+             shard_id = EXCLUDED.shard_id, \
+             block_height = EXCLUDED.block_height, \
+             updated_at = NOW() \
+             WHERE (EXCLUDED.last_block_number, EXCLUDED.last_log_index) > \
+                   (fid_signers.last_block_number, fid_signers.last_log_index)",
+        );
+
+        let mut q = sqlx::query(&query);
+        for signer in chunk {
+            q = q
+                .bind(signer.fid)
+                .bind(&signer.key)
+                .bind(signer.key_type)
+                .bind(&signer.metadata)
+                .bind(signer.active)
+                .bind(signer.added_block)
+                .bind(signer.added_at)
+                .bind(signer.removed_block)
+                .bind(signer.removed_at)
+                .bind(signer.removal_type)
+                .bind(signer.last_block_number)
+                .bind(signer.last_log_index)
+                .bind(signer.shard_id)
+                .bind(signer.block_height);
+        }
+
+        q.execute(&mut *conn).await?;
     }
+    Ok(())
+}
 
+/// Upsert folded registration state, leaving rows that already hold newer state
+async fn upsert_registrations(
+    conn: &mut PgConnection,
+    registrations: &[RegistrationState],
+) -> Result<()> {
+    for chunk in registrations.chunks(REGISTRATION_CHUNK_SIZE) {
+        let mut query = String::with_capacity(600 + chunk.len() * 50);
+        query.push_str("INSERT INTO fid_registrations (fid, custody_address, recovery_address, registered_block, registered_at, last_block_number, last_log_index, shard_id, block_height) VALUES ");
+
+        for i in 0..chunk.len() {
+            if i > 0 {
+                query.push_str(", ");
+            }
+            let base = i * REGISTRATION_PARAMS_PER_ROW;
+            write!(
+                &mut query,
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                base + 1,
+                base + 2,
+                base + 3,
+                base + 4,
+                base + 5,
+                base + 6,
+                base + 7,
+                base + 8,
+                base + 9
+            )
+            .expect("write! to String should not fail");
+        }
+
+        query.push_str(
+            " ON CONFLICT (fid) DO UPDATE SET \
+             custody_address = COALESCE(EXCLUDED.custody_address, fid_registrations.custody_address), \
+             recovery_address = COALESCE(EXCLUDED.recovery_address, fid_registrations.recovery_address), \
+             registered_block = COALESCE(EXCLUDED.registered_block, fid_registrations.registered_block), \
+             registered_at = COALESCE(EXCLUDED.registered_at, fid_registrations.registered_at), \
+             last_block_number = EXCLUDED.last_block_number, \
+             last_log_index = EXCLUDED.last_log_index, \
+             shard_id = EXCLUDED.shard_id, \
+             block_height = EXCLUDED.block_height, \
+             updated_at = NOW() \
+             WHERE (EXCLUDED.last_block_number, EXCLUDED.last_log_index) > \
+                   (fid_registrations.last_block_number, fid_registrations.last_log_index)",
+        );
+
+        let mut q = sqlx::query(&query);
+        for registration in chunk {
+            q = q
+                .bind(registration.fid)
+                .bind(&registration.custody_address)
+                .bind(&registration.recovery_address)
+                .bind(registration.registered_block)
+                .bind(registration.registered_at)
+                .bind(registration.last_block_number)
+                .bind(registration.last_log_index)
+                .bind(registration.shard_id)
+                .bind(registration.block_height);
+        }
+
+        q.execute(&mut *conn).await?;
+    }
     Ok(())
 }
+
+/// Rebuild signer and registration state of `fids` from their stored on-chain events
+///
+/// A rollback deletes the rows whose last change came from a rolled-back
+/// block; folding the events that remain restores the state below the fork.
+/// Rows that were not deleted already hold their latest state and are left as
+/// they are by the upserts.
+pub async fn rebuild_onchain_state(conn: &mut PgConnection, fids: &[i64]) -> Result<()> {
+    if fids.is_empty() {
+        return Ok(());
+    }
+
+    let rows: Vec<(Option<serde_json::Value>, Option<i32>, Option<i64>)> = sqlx::query_as(
+        "SELECT event_data, shard_id, shard_block_height FROM onchain_events \
+         WHERE fid = ANY($1) AND event_type IN (1, 3)",
+    )
+    .bind(fids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut batched = BatchedData::new();
+    for (event_data, shard_id, block_height) in rows {
+        let Some(event) = event_data.and_then(|data| {
+            serde_json::from_value::<crate::sync::client::proto::OnChainEvent>(data).ok()
+        }) else {
+            continue;
+        };
+        let shard_block_info = crate::models::ShardBlockInfo::new(
+            shard_id.and_then(|id| u32::try_from(id).ok()).unwrap_or(0),
+            block_height
+                .and_then(|height| u64::try_from(height).ok())
+                .unwrap_or(0),
+            0,
+            0,
+        );
+        super::handlers::collect_onchain_state(&event, &shard_block_info, &mut batched);
+    }
+
+    upsert_signers(conn, &fold_signer_events(&batched.signer_events)).await?;
+    upsert_registrations(conn, &fold_id_register_events(&batched.id_register_events)).await?;
+    Ok(())
+}
+
+/// Latest state of one signer key within a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SignerState {
+    fid: i64,
+    key: Vec<u8>,
+    key_type: i32,
+    metadata: Option<Vec<u8>>,
+    active: bool,
+    added_block: Option<i64>,
+    added_at: Option<i64>,
+    removed_block: Option<i64>,
+    removed_at: Option<i64>,
+    removal_type: Option<i16>,
+    last_block_number: i64,
+    last_log_index: i32,
+    /// Shard block of the last applied event
+    shard_id: i32,
+    block_height: i64,
+}
+
+/// Latest custody / recovery state of one FID within a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegistrationState {
+    fid: i64,
+    custody_address: Option<Vec<u8>>,
+    recovery_address: Option<Vec<u8>>,
+    registered_block: Option<i64>,
+    registered_at: Option<i64>,
+    last_block_number: i64,
+    last_log_index: i32,
+    /// Shard block of the last applied event
+    shard_id: i32,
+    block_height: i64,
+}
+
+/// Fold signer events into one state per (fid, key), applied in chain order
+///
+/// ADD (1) activates a key; REMOVE (2) and ADMIN_RESET (3) deactivate it.
+fn fold_signer_events(events: &[SignerEventRow]) -> Vec<SignerState> {
+    let mut ordered: Vec<&SignerEventRow> = events
+        .iter()
+        .filter(|event| matches!(event.3, 1..=3))
+        .collect();
+    ordered.sort_by_key(|event| (event.5, event.6));
+
+    let mut states: HashMap<(i64, Vec<u8>), SignerState> = HashMap::new();
+    for (
+        fid,
+        key,
+        key_type,
+        event_type,
+        metadata,
+        block_number,
+        log_index,
+        block_timestamp,
+        shard_block_info,
+    ) in ordered
+    {
+        let state = states
+            .entry((*fid, key.clone()))
+            .or_insert_with(|| SignerState {
+                fid: *fid,
+                key: key.clone(),
+                key_type: *key_type,
+                metadata: None,
+                active: false,
+                added_block: None,
+                added_at: None,
+                removed_block: None,
+                removed_at: None,
+                removal_type: None,
+                last_block_number: *block_number,
+                last_log_index: *log_index,
+                shard_id: 0,
+                block_height: 0,
+            });
+
+        if *event_type == 1 {
+            state.active = true;
+            state.key_type = *key_type;
+            state.metadata.clone_from(metadata);
+            state.added_block = Some(*block_number);
+            state.added_at = Some(*block_timestamp);
+            state.removed_block = None;
+            state.removed_at = None;
+            state.removal_type = None;
+        } else {
+            state.active = false;
+            state.removed_block = Some(*block_number);
+            state.removed_at = Some(*block_timestamp);
+            state.removal_type = Some(*event_type);
+        }
+        state.last_block_number = *block_number;
+        state.last_log_index = *log_index;
+        state.shard_id = i32::try_from(shard_block_info.shard_id).unwrap_or(0);
+        state.block_height = i64::try_from(shard_block_info.block_height).unwrap_or(0);
+    }
+
+    states.into_values().collect()
+}
+
+/// Fold id register events into one state per FID, applied in chain order
+///
+/// REGISTER (1) sets custody, recovery and registration time; TRANSFER (2) moves
+/// custody; CHANGE_RECOVERY (3) replaces the recovery address.
+fn fold_id_register_events(events: &[IdRegisterEventRow]) -> Vec<RegistrationState> {
+    let mut ordered: Vec<&IdRegisterEventRow> = events
+        .iter()
+        .filter(|event| matches!(event.1, 1..=3))
+        .collect();
+    ordered.sort_by_key(|event| (event.5, event.6));
+
+    let mut states: HashMap<i64, RegistrationState> = HashMap::new();
+    for (
+        fid,
+        event_type,
+        to,
+        _from,
+        recovery_address,
+        block_number,
+        log_index,
+        block_timestamp,
+        shard_block_info,
+    ) in ordered
+    {
+        let state = states.entry(*fid).or_insert_with(|| RegistrationState {
+            fid: *fid,
+            custody_address: None,
+            recovery_address: None,
+            registered_block: None,
+            registered_at: None,
+            last_block_number: *block_number,
+            last_log_index: *log_index,
+            shard_id: 0,
+            block_height: 0,
+        });
+
+        match event_type {
+            1 => {
+                state.custody_address.clone_from(to);
+                state.recovery_address.clone_from(recovery_address);
+                state.registered_block = Some(*block_number);
+                state.registered_at = Some(*block_timestamp);
+            }
+            2 => state.custody_address.clone_from(to),
+            _ => state.recovery_address.clone_from(recovery_address),
+        }
+        state.last_block_number = *block_number;
+        state.last_log_index = *log_index;
+        state.shard_id = i32::try_from(shard_block_info.shard_id).unwrap_or(0);
+        state.block_height = i64::try_from(shard_block_info.block_height).unwrap_or(0);
+    }
+
+    states.into_values().collect()
+}
+
+type SignerEventRow = (
+    i64,
+    Vec<u8>,
+    i32,
+    i16,
+    Option<Vec<u8>>,
+    i64,
+    i32,
+    i64,
+    crate::models::ShardBlockInfo,
+);
+type IdRegisterEventRow = (
+    i64,
+    i16,
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+    i64,
+    i32,
+    i64,
+    crate::models::ShardBlockInfo,
+);
+
+/// Resolve the thread root of every cast in a batch
+///
+/// `parents` maps each batched cast to its parent hash; `stored` holds the
+/// known roots of parents that are already in the database. Casts whose chain
+/// leaves the batch through an unknown parent are left out and picked up later
+/// by the fix-up update or `backfill_cast_root_hashes`.
+fn resolve_root_hashes(
+    parents: &HashMap<Vec<u8>, Option<Vec<u8>>>,
+    stored: &HashMap<Vec<u8>, Vec<u8>>,
+) -> HashMap<Vec<u8>, Vec<u8>> {
+    // Guards against malformed parent cycles
+    const MAX_DEPTH: usize = 1024;
+
+    let mut roots = HashMap::with_capacity(parents.len());
+    for hash in parents.keys() {
+        let mut current = hash;
+        for _ in 0..MAX_DEPTH {
+            match parents.get(current) {
+                Some(None) => {
+                    roots.insert(hash.clone(), current.clone());
+                    break;
+                }
+                Some(Some(parent)) => current = parent,
+                None => {
+                    if let Some(root) = stored.get(current) {
+                        roots.insert(hash.clone(), root.clone());
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_root_hashes() {
+        let parents: HashMap<Vec<u8>, Option<Vec<u8>>> = [
+            (vec![1], None),          // top-level
+            (vec![2], Some(vec![1])), // reply in batch
+            (vec![3], Some(vec![2])), // nested reply in batch
+            (vec![4], Some(vec![9])), // reply to stored cast
+            (vec![5], Some(vec![8])), // reply to unknown cast
+            (vec![6], Some(vec![7])), // cycle
+            (vec![7], Some(vec![6])),
+        ]
+        .into_iter()
+        .collect();
+        let stored: HashMap<Vec<u8>, Vec<u8>> = [(vec![9], vec![10])].into_iter().collect();
+
+        let roots = resolve_root_hashes(&parents, &stored);
+
+        assert_eq!(roots.get(&vec![1]), Some(&vec![1]));
+        assert_eq!(roots.get(&vec![2]), Some(&vec![1]));
+        assert_eq!(roots.get(&vec![3]), Some(&vec![1]));
+        assert_eq!(roots.get(&vec![4]), Some(&vec![10]));
+        assert!(!roots.contains_key(&vec![5]));
+        assert!(!roots.contains_key(&vec![6]));
+    }
+
+    fn block(height: u64) -> crate::models::ShardBlockInfo {
+        crate::models::ShardBlockInfo::new(1, height, 0, 0)
+    }
+
+    #[test]
+    fn test_fold_signer_events() {
+        let key = vec![0x11; 32];
+        let other = vec![0x22; 32];
+        let events = vec![
+            // Out of order on purpose: remove (block 20) comes after add (block 10)
+            (7, key.clone(), 1, 2, None, 20, 0, 2_000, block(502)),
+            (
+                7,
+                key.clone(),
+                1,
+                1,
+                Some(vec![0xAA]),
+                10,
+                3,
+                1_000,
+                block(500),
+            ),
+            (7, other.clone(), 1, 1, None, 15, 1, 1_500, block(501)),
+        ];
+
+        let mut signers = fold_signer_events(&events);
+        signers.sort_by(|a, b| a.key.cmp(&b.key));
+
+        assert_eq!(signers.len(), 2);
+        assert!(!signers[0].active);
+        assert_eq!(signers[0].added_block, Some(10));
+        assert_eq!(signers[0].removed_block, Some(20));
+        assert_eq!(signers[0].removal_type, Some(2));
+        assert_eq!(signers[0].metadata, Some(vec![0xAA]));
+        assert_eq!(
+            (signers[0].last_block_number, signers[0].last_log_index),
+            (20, 0)
+        );
+        // Tagged with the shard block of the remove, for rollbacks
+        assert_eq!((signers[0].shard_id, signers[0].block_height), (1, 502));
+        assert!(signers[1].active);
+        assert_eq!(signers[1].removed_block, None);
+    }
+
+    #[test]
+    fn test_fold_id_register_events() {
+        let custody = vec![0x01; 20];
+        let new_custody = vec![0x02; 20];
+        let recovery = vec![0x03; 20];
+        let new_recovery = vec![0x04; 20];
+        let events = vec![
+            (
+                9,
+                3,
+                None,
+                None,
+                Some(new_recovery.clone()),
+                30,
+                0,
+                3_000,
+                block(703),
+            ),
+            (
+                9,
+                1,
+                Some(custody),
+                None,
+                Some(recovery),
+                10,
+                0,
+                1_000,
+                block(701),
+            ),
+            (
+                9,
+                2,
+                Some(new_custody.clone()),
+                None,
+                None,
+                20,
+                0,
+                2_000,
+                block(702),
+            ),
+        ];
+
+        let registrations = fold_id_register_events(&events);
+
+        assert_eq!(
+            registrations,
+            vec![RegistrationState {
+                fid: 9,
+                custody_address: Some(new_custody),
+                recovery_address: Some(new_recovery),
+                registered_block: Some(10),
+                registered_at: Some(1_000),
+                last_block_number: 30,
+                last_log_index: 0,
+                shard_id: 1,
+                block_height: 703,
+            }]
+        );
+    }
+}
@@ -16,8 +16,11 @@ pub(super) async fn collect_cast_add(
     let timestamp = i64::from(data.timestamp);
 
     // Parse cast data from the body
+    // root_hash is resolved from the parent chain at flush time
     let mut text = None;
     let mut parent_hash = None;
+    let mut parent_fid = None;
+    let mut parent_url = None;
     let root_hash = None;
     let mut embeds = None;
     let mut mentions = None;
@@ -29,7 +32,7 @@ pub(super) async fn collect_cast_add(
                 text = text_value.as_str().map(std::string::ToString::to_string);
             }
 
-            // Extract parent cast info
+            // Extract parent: either a cast (reply) or a URL (channel)
             if let Some(parent) = cast_add_body.get("parent") {
                 if let Some(parent_cast_id) = parent.get("parent_cast_id") {
                     if let Some(parent_hash_value) = parent_cast_id.get("hash") {
@@ -37,6 +40,13 @@ pub(super) async fn collect_cast_add(
                             parent_hash = hex::decode(hash_str).ok();
                         }
                     }
+                    parent_fid = parent_cast_id
+                        .get("fid")
+                        .and_then(serde_json::Value::as_u64)
+                        .and_then(|fid| i64::try_from(fid).ok());
+                }
+                if let Some(url) = parent.get("parent_url").and_then(|v| v.as_str()) {
+                    parent_url = Some(url.to_string());
                 }
             }
 
@@ -62,6 +72,8 @@ pub(super) async fn collect_cast_add(
         root_hash,
         embeds,
         mentions,
+        parent_fid,
+        parent_url,
         shard_block_info.clone(),
     ));
 
@@ -69,3 +81,42 @@ pub(super) async fn collect_cast_add(
 
     Ok(())
 }
+
+/// Collect cast remove (type 2) as a tombstone for batch processing
+///
+/// The original cast row is kept (append-only); readers exclude casts that have
+/// a matching `cast_removes` row for the same FID.
+pub(super) fn collect_cast_remove(
+    body: &serde_json::Value,
+    fid: i64,
+    timestamp: i64,
+    message_hash: &[u8],
+    shard_block_info: &ShardBlockInfo,
+    batched: &mut BatchedData,
+) {
+    let Some(target_hash_str) = body
+        .get("cast_remove_body")
+        .and_then(|b| b.get("target_hash"))
+        .and_then(|v| v.as_str())
+    else {
+        tracing::warn!("CastRemove for FID {} has no target_hash", fid);
+        return;
+    };
+
+    match hex::decode(target_hash_str) {
+        Ok(target_hash) if !target_hash.is_empty() => {
+            batched.cast_removes.push((
+                fid,
+                target_hash,
+                timestamp,
+                message_hash.to_vec(),
+                shard_block_info.clone(),
+            ));
+
+            tracing::debug!("Collected cast remove: FID {} [REMOVE]", fid);
+        }
+        _ => {
+            tracing::warn!("Failed to decode cast remove target hash for FID {}", fid);
+        }
+    }
+}
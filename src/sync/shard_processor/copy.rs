@@ -0,0 +1,348 @@
+//! Binary `COPY` flush path
+//!
+//! Rows are encoded in `PostgreSQL`'s binary COPY format, streamed into a
+//! session-local temporary staging table (temporary tables are never
+//! WAL-logged) and merged into the target table with
+//! `INSERT ... SELECT ... ON CONFLICT DO NOTHING`, so duplicates are skipped
+//! exactly like on the INSERT path. Staging tables are created once per pooled
+//! connection and emptied after every merge.
+
+use sqlx::PgConnection;
+
+use crate::Result;
+
+/// Binary COPY file signature, followed by flags and header extension length
+const COPY_SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+
+/// Size of the `CopyData` messages the encoded rows are sent in
+const COPY_SEND_BYTES: usize = 1 << 20;
+
+/// A table written through `COPY`
+#[derive(Debug)]
+pub struct CopyTarget {
+    pub table: &'static str,
+    pub columns: &'static [&'static str],
+    /// Conflict target of the merge, e.g. `(message_hash)`
+    pub conflict: &'static str,
+}
+
+impl CopyTarget {
+    fn staging_table(&self) -> String {
+        format!("copy_staging_{}", self.table)
+    }
+}
+
+pub const CASTS: CopyTarget = CopyTarget {
+    table: "casts",
+    columns: &[
+        "fid",
+        "text",
+        "timestamp",
+        "message_hash",
+        "parent_hash",
+        "root_hash",
+        "embeds",
+        "mentions",
+        "parent_fid",
+        "parent_url",
+        "shard_id",
+        "block_height",
+        "transaction_fid",
+    ],
+    conflict: "(message_hash)",
+};
+
+pub const CAST_REMOVES: CopyTarget = CopyTarget {
+    table: "cast_removes",
+    columns: &[
+        "fid",
+        "target_hash",
+        "timestamp",
+        "message_hash",
+        "shard_id",
+        "block_height",
+        "transaction_fid",
+    ],
+    conflict: "(message_hash)",
+};
+
+pub const LINKS: CopyTarget = CopyTarget {
+    table: "links",
+    columns: &[
+        "fid",
+        "target_fid",
+        "link_type",
+        "event_type",
+        "timestamp",
+        "message_hash",
+        "shard_id",
+        "block_height",
+    ],
+    conflict: "(message_hash)",
+};
+
+pub const REACTIONS: CopyTarget = CopyTarget {
+    table: "reactions",
+    columns: &[
+        "fid",
+        "target_cast_hash",
+        "target_fid",
+        "reaction_type",
+        "event_type",
+        "timestamp",
+        "message_hash",
+        "shard_id",
+        "block_height",
+        "transaction_fid",
+    ],
+    conflict: "(message_hash)",
+};
+
+pub const VERIFICATIONS: CopyTarget = CopyTarget {
+    table: "verifications",
+    columns: &[
+        "fid",
+        "address",
+        "claim_signature",
+        "block_hash",
+        "verification_type",
+        "chain_id",
+        "event_type",
+        "timestamp",
+        "message_hash",
+        "shard_id",
+        "block_height",
+        "transaction_fid",
+    ],
+    conflict: "(message_hash)",
+};
+
+pub const PROFILE_CHANGES: CopyTarget = CopyTarget {
+    table: "user_profile_changes",
+    columns: &[
+        "fid",
+        "field_name",
+        "field_value",
+        "timestamp",
+        "message_hash",
+        "shard_id",
+        "block_height",
+        "transaction_fid",
+    ],
+    conflict: "(message_hash)",
+};
+
+/// A value that can be written as one field of a binary COPY row
+///
+/// Each implementation must match the binary wire format of its column type.
+pub trait CopyField {
+    fn write_field(&self, buf: &mut Vec<u8>);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    let len = i32::try_from(bytes.len()).expect("COPY field larger than 2 GiB");
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+impl CopyField for i16 {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        write_bytes(buf, &self.to_be_bytes());
+    }
+}
+
+impl CopyField for i32 {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        write_bytes(buf, &self.to_be_bytes());
+    }
+}
+
+impl CopyField for i64 {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        write_bytes(buf, &self.to_be_bytes());
+    }
+}
+
+/// `BYTEA`
+impl CopyField for Vec<u8> {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        write_bytes(buf, self);
+    }
+}
+
+/// `TEXT` / `VARCHAR`
+impl CopyField for String {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        write_bytes(buf, self.as_bytes());
+    }
+}
+
+/// `JSONB`: a version byte followed by the JSON text
+impl CopyField for serde_json::Value {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        let mut bytes = vec![1];
+        serde_json::to_writer(&mut bytes, self).expect("JSON value should serialize");
+        write_bytes(buf, &bytes);
+    }
+}
+
+/// `NULL` when `None`
+impl<T: CopyField> CopyField for Option<T> {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        match self {
+            Some(value) => value.write_field(buf),
+            None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+}
+
+/// Rows of one table encoded in binary COPY format
+pub struct CopyRows {
+    target: &'static CopyTarget,
+    buf: Vec<u8>,
+    rows: usize,
+}
+
+impl CopyRows {
+    #[must_use]
+    pub fn new(target: &'static CopyTarget) -> Self {
+        let mut buf = Vec::with_capacity(64 * 1024);
+        buf.extend_from_slice(COPY_SIGNATURE);
+        buf.extend_from_slice(&0i32.to_be_bytes()); // flags
+        buf.extend_from_slice(&0i32.to_be_bytes()); // header extension length
+        Self {
+            target,
+            buf,
+            rows: 0,
+        }
+    }
+
+    /// Append a row; `fields` follow the order of the target's columns
+    pub fn push(&mut self, fields: &[&dyn CopyField]) {
+        assert_eq!(
+            fields.len(),
+            self.target.columns.len(),
+            "COPY row for {} has the wrong number of fields",
+            self.target.table
+        );
+        let count = i16::try_from(fields.len()).expect("COPY rows have at most 1600 columns");
+        self.buf.extend_from_slice(&count.to_be_bytes());
+        for field in fields {
+            field.write_field(&mut self.buf);
+        }
+        self.rows += 1;
+    }
+
+    #[must_use]
+    pub const fn row_count(&self) -> usize {
+        self.rows
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.buf.extend_from_slice(&(-1i16).to_be_bytes()); // file trailer
+        self.buf
+    }
+}
+
+/// Stream `rows` into the staging table of their target and merge them
+///
+/// Returns the number of rows inserted into the target table.
+pub async fn copy_merge(conn: &mut PgConnection, rows: CopyRows) -> Result<u64> {
+    if rows.row_count() == 0 {
+        return Ok(0);
+    }
+
+    let target = rows.target;
+    let staging = target.staging_table();
+    let columns = target.columns.join(", ");
+
+    // Temporary tables live per connection; the pool reuses connections
+    sqlx::query(&format!(
+        "CREATE TEMP TABLE IF NOT EXISTS {staging} AS \
+         SELECT {columns} FROM {table} WITH NO DATA",
+        table = target.table
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    let data = rows.finish();
+    let mut copy = conn
+        .copy_in_raw(&format!(
+            "COPY {staging} ({columns}) FROM STDIN WITH (FORMAT binary)"
+        ))
+        .await?;
+    for part in data.chunks(COPY_SEND_BYTES) {
+        copy.send(part).await?;
+    }
+    copy.finish().await?;
+
+    let merged = sqlx::query(&format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM {staging} \
+         ON CONFLICT {conflict} DO NOTHING",
+        table = target.table,
+        conflict = target.conflict
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(&format!("TRUNCATE {staging}"))
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(merged.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_rows_binary_encoding() {
+        let mut rows = CopyRows::new(&PROFILE_CHANGES);
+        rows.push(&[
+            &3i64,
+            &"username".to_string(),
+            &None::<String>,
+            &7i64,
+            &vec![0xab, 0xcd],
+            &2i32,
+            &40i64,
+            &3i64,
+        ]);
+        assert_eq!(rows.row_count(), 1);
+
+        let data = rows.finish();
+        let mut expected = b"PGCOPY\n\xff\r\n\0".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0, 8]); // field count
+        expected.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 3]);
+        expected.extend_from_slice(&[0, 0, 0, 8]);
+        expected.extend_from_slice(b"username");
+        expected.extend_from_slice(&[0xff, 0xff, 0xff, 0xff]); // NULL
+        expected.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 7]);
+        expected.extend_from_slice(&[0, 0, 0, 2, 0xab, 0xcd]);
+        expected.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 2]);
+        expected.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 40]);
+        expected.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 3]);
+        expected.extend_from_slice(&[0xff, 0xff]); // trailer
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_copy_field_jsonb_and_small_ints() {
+        let mut buf = Vec::new();
+        serde_json::json!([1]).write_field(&mut buf);
+        assert_eq!(buf, [0, 0, 0, 4, 1, b'[', b'1', b']']);
+
+        buf.clear();
+        2i16.write_field(&mut buf);
+        Some(900i32).write_field(&mut buf);
+        assert_eq!(buf, [0, 0, 0, 2, 0, 2, 0, 0, 0, 4, 0, 0, 3, 132]);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong number of fields")]
+    fn test_copy_rows_rejects_short_rows() {
+        CopyRows::new(&LINKS).push(&[&1i64]);
+    }
+}
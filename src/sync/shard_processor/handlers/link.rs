@@ -97,3 +97,50 @@ pub(super) fn handle_link_remove(
         }
     }
 }
+
+/// Handle `LinkCompactState` message (type 14)
+///
+/// Carries the complete set of targets `fid` links to as of `timestamp`; it is
+/// applied against the `links` event log when the batch is flushed.
+pub(super) fn handle_link_compact_state(
+    body: &serde_json::Value,
+    fid: i64,
+    timestamp: i64,
+    message_hash: &[u8],
+    shard_block_info: &ShardBlockInfo,
+    batched: &mut BatchedData,
+) {
+    if let Some(compact_body) = body.get("link_compact_state_body") {
+        let link_type = compact_body
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("follow");
+
+        let target_fids: Vec<i64> = compact_body
+            .get("target_fids")
+            .and_then(|v| v.as_array())
+            .map(|fids| {
+                fids.iter()
+                    .filter_map(serde_json::Value::as_i64)
+                    .filter(|target_fid| *target_fid > 0)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        tracing::debug!(
+            "Collected link compact state: FID {} has {} {} links",
+            fid,
+            target_fids.len(),
+            link_type
+        );
+
+        batched.link_compact_states.push((
+            fid,
+            link_type.to_string(),
+            target_fids,
+            timestamp,
+            message_hash.to_vec(),
+            shard_block_info.clone(),
+        ));
+    }
+}
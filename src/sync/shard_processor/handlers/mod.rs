@@ -2,13 +2,15 @@
 ///
 /// This module is organized by message type for better maintainability:
 /// - reaction.rs: ReactionAdd/Remove handlers
-/// - link.rs: LinkAdd/Remove handlers
+/// - link.rs: LinkAdd/Remove/CompactState handlers
 /// - verification.rs: VerificationAdd/Remove handlers (ETH + Solana)
 /// - `user_data.rs`: `UserDataAdd` handler (13 field types)
 /// - username.rs: `UsernameProof` handler
 /// - frame.rs: `FrameAction` handler
-/// - system.rs: System message (`OnChainEvent`) handler
+/// - storage.rs: `LendStorage` handler
+/// - system.rs: System message (`OnChainEvent`, `FnameTransfer`) handler
 use super::cast_handlers::collect_cast_add;
+use super::cast_handlers::collect_cast_remove;
 use super::types::BatchedData;
 use crate::models::ShardBlockInfo;
 use crate::sync::client::proto::Message as FarcasterMessage;
@@ -18,11 +20,13 @@ use crate::Result;
 mod frame;
 mod link;
 mod reaction;
+mod storage;
 mod system;
 mod user_data;
 mod username;
 mod verification;
 
+pub(super) use system::collect_onchain_state;
 pub(super) use system::process_system_message;
 
 /// Collect transaction data and route to appropriate handlers
@@ -82,7 +86,17 @@ pub(super) async fn collect_message_data(
             collect_cast_add(data, &message_hash, shard_block_info, batched).await?;
         }
         2 => {
-            // CastRemove - no action needed (soft delete handled in casts table)
+            // CastRemove - recorded as a tombstone in cast_removes
+            if let Some(body) = &data.body {
+                collect_cast_remove(
+                    body,
+                    fid,
+                    timestamp,
+                    &message_hash,
+                    shard_block_info,
+                    batched,
+                );
+            }
         }
         3 => {
             // ReactionAdd
@@ -165,7 +179,14 @@ pub(super) async fn collect_message_data(
         11 => {
             // UserDataAdd
             if let Some(body) = &data.body {
-                user_data::handle_user_data_add(body, fid, timestamp, &message_hash, batched);
+                user_data::handle_user_data_add(
+                    body,
+                    fid,
+                    timestamp,
+                    &message_hash,
+                    shard_block_info,
+                    batched,
+                );
             }
         }
         12 => {
@@ -194,13 +215,31 @@ pub(super) async fn collect_message_data(
                 );
             }
         }
-        14 | 15 => {
-            // LINK_COMPACT_STATE (14) and LEND_STORAGE (15) - low priority, log only
-            tracing::debug!(
-                "Received message type {} for FID {} - not stored",
-                message_type,
-                fid
-            );
+        14 => {
+            // LinkCompactState - authoritative link set, applied at flush
+            if let Some(body) = &data.body {
+                link::handle_link_compact_state(
+                    body,
+                    fid,
+                    timestamp,
+                    &message_hash,
+                    shard_block_info,
+                    batched,
+                );
+            }
+        }
+        15 => {
+            // LendStorage
+            if let Some(body) = &data.body {
+                storage::handle_lend_storage(
+                    body,
+                    fid,
+                    timestamp,
+                    &message_hash,
+                    shard_block_info,
+                    batched,
+                );
+            }
         }
         _ => {
             tracing::debug!("Unknown message type {} for FID {}", message_type, fid);
@@ -0,0 +1,50 @@
+use super::super::types::BatchedData;
+/// `LendStorage` message handler
+use crate::models::ShardBlockInfo;
+
+/// Handle `LendStorage` message (type 15)
+pub(super) fn handle_lend_storage(
+    body: &serde_json::Value,
+    fid: i64,
+    timestamp: i64,
+    message_hash: &[u8],
+    shard_block_info: &ShardBlockInfo,
+    batched: &mut BatchedData,
+) {
+    if let Some(lend_body) = body.get("lend_storage_body") {
+        let to_fid = lend_body
+            .get("to_fid")
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or(0);
+
+        let num_units = lend_body
+            .get("num_units")
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or(0);
+
+        let unit_type = lend_body
+            .get("unit_type")
+            .and_then(serde_json::Value::as_i64)
+            .map_or(0, |v| i16::try_from(v).unwrap_or(0));
+
+        if to_fid > 0 {
+            batched.storage_lends.push((
+                fid,
+                to_fid,
+                num_units,
+                unit_type,
+                timestamp,
+                message_hash.to_vec(),
+                shard_block_info.clone(),
+            ));
+
+            tracing::debug!(
+                "Collected storage lend: FID {} -> {} ({} units, type {})",
+                fid,
+                to_fid,
+                num_units,
+                unit_type
+            );
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use super::super::types::BatchedData;
-/// System message handler (`OnChain` Events)
+/// System message handler (`OnChain` Events and fname transfers)
 use crate::models::ShardBlockInfo;
+use crate::sync::client::proto::OnChainEvent;
 use crate::Result;
 
-/// Process system messages (`OnChainEvents`)
+/// Process system messages (`OnChainEvents` and `FnameTransfers`)
 pub(in crate::sync::shard_processor) async fn process_system_message(
     system_msg: &crate::sync::client::proto::ValidatorMessage,
     shard_block_info: &ShardBlockInfo,
@@ -35,6 +36,9 @@ pub(in crate::sync::shard_processor) async fn process_system_message(
         // Serialize event body to JSON for storage
         let event_data = serde_json::to_value(onchain_event).unwrap_or(serde_json::Value::Null);
 
+        // Decoded state (signers, custody, storage) is maintained from the typed body
+        collect_onchain_state(onchain_event, shard_block_info, batched);
+
         batched.onchain_events.push((
             fid,
             event_type,
@@ -45,6 +49,7 @@ pub(in crate::sync::shard_processor) async fn process_system_message(
             transaction_hash,
             log_index,
             event_data,
+            shard_block_info.clone(),
         ));
 
         tracing::debug!(
@@ -53,12 +58,110 @@ pub(in crate::sync::shard_processor) async fn process_system_message(
             event_type,
             block_number
         );
-    } else {
+    }
+
+    // Fname transfers move an fname between FIDs; kept as ownership history
+    if let Some(transfer) = &system_msg.fname_transfer {
+        let timestamp = if transfer.timestamp > 0 {
+            transfer.timestamp
+        } else {
+            shard_block_info.timestamp
+        };
+
+        batched.fname_transfers.push((
+            i64::try_from(transfer.id).unwrap_or(0),
+            i64::try_from(transfer.from_fid).unwrap_or(0),
+            i64::try_from(transfer.to_fid).unwrap_or(0),
+            transfer.username.clone(),
+            transfer.owner.clone(),
+            transfer.signature.clone(),
+            i64::try_from(timestamp).unwrap_or(0),
+            shard_block_info.clone(),
+        ));
+
+        tracing::debug!(
+            "Collected fname transfer {}: @{} FID {} -> {}",
+            transfer.id,
+            transfer.username,
+            transfer.from_fid,
+            transfer.to_fid
+        );
+    }
+
+    if system_msg.on_chain_event.is_none() && system_msg.fname_transfer.is_none() {
         tracing::warn!(
-            "System message in shard {} has no onchain_event",
+            "System message in shard {} has no onchain_event or fname_transfer",
             shard_block_info.shard_id
         );
     }
 
     Ok(())
 }
+
+/// Collect decoded signer, id register and storage rent state from an on-chain event body
+pub(in crate::sync::shard_processor) fn collect_onchain_state(
+    onchain_event: &OnChainEvent,
+    shard_block_info: &ShardBlockInfo,
+    batched: &mut BatchedData,
+) {
+    let Some(body) = &onchain_event.body else {
+        return;
+    };
+    let fid = i64::try_from(onchain_event.fid).unwrap_or(0);
+    let block_number = i64::try_from(onchain_event.block_number).unwrap_or(0);
+    let log_index = i32::try_from(onchain_event.log_index).unwrap_or(0);
+    let block_timestamp = i64::try_from(onchain_event.block_timestamp).unwrap_or(0);
+
+    let hex_field = |obj: &serde_json::Value, key: &str| {
+        obj.get(key)
+            .and_then(|v| v.as_str())
+            .and_then(|s| hex::decode(s).ok())
+            .filter(|bytes| !bytes.is_empty())
+    };
+    let int_field = |obj: &serde_json::Value, key: &str| {
+        obj.get(key)
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or(0)
+    };
+
+    if let Some(signer) = body.get("signer_event_body") {
+        if let Some(key) = hex_field(signer, "key") {
+            batched.signer_events.push((
+                fid,
+                key,
+                i32::try_from(int_field(signer, "key_type")).unwrap_or(0),
+                i16::try_from(int_field(signer, "event_type")).unwrap_or(0),
+                hex_field(signer, "metadata"),
+                block_number,
+                log_index,
+                block_timestamp,
+                shard_block_info.clone(),
+            ));
+        }
+    } else if let Some(id_register) = body.get("id_register_event_body") {
+        batched.id_register_events.push((
+            fid,
+            i16::try_from(int_field(id_register, "event_type")).unwrap_or(0),
+            hex_field(id_register, "to"),
+            hex_field(id_register, "from"),
+            hex_field(id_register, "recovery_address"),
+            block_number,
+            log_index,
+            block_timestamp,
+            shard_block_info.clone(),
+        ));
+    } else if let Some(storage_rent) = body.get("storage_rent_event_body") {
+        batched.storage_rents.push((
+            fid,
+            hex_field(storage_rent, "payer"),
+            int_field(storage_rent, "units"),
+            int_field(storage_rent, "expiry"),
+            block_number,
+            log_index,
+            block_timestamp,
+            (!onchain_event.transaction_hash.is_empty())
+                .then(|| onchain_event.transaction_hash.clone()),
+            shard_block_info.clone(),
+        ));
+    }
+}
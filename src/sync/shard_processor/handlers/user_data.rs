@@ -1,5 +1,6 @@
 use super::super::types::BatchedData;
 /// `UserDataAdd` message handler
+use crate::models::ShardBlockInfo;
 use crate::Result;
 
 /// Handle `UserDataAdd` message (type 11) - all 13 profile field types
@@ -8,6 +9,7 @@ pub(super) fn handle_user_data_add(
     fid: i64,
     timestamp: i64,
     message_hash: &[u8],
+    shard_block_info: &ShardBlockInfo,
     batched: &mut BatchedData,
 ) {
     if let Some(user_data_body) = body.get("user_data_body") {
@@ -48,6 +50,7 @@ pub(super) fn handle_user_data_add(
                 Some(value.to_string()),
                 timestamp,
                 message_hash.to_vec(),
+                shard_block_info.clone(),
             ));
         }
     }
@@ -0,0 +1,256 @@
+//! Hash and signature verification of ingested user messages
+//!
+//! A Farcaster message is valid when `hash` is the BLAKE3 digest of its
+//! serialized `MessageData` truncated to 20 bytes, `signature` is an Ed25519
+//! signature of that hash by `signer`, and `signer` is an active key of the
+//! message's FID according to SIGNER on-chain events.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use ed25519_dalek::Signature;
+use ed25519_dalek::VerifyingKey;
+use prost::Message as _;
+
+use crate::generated::grpc_client::MessageData as GrpcMessageData;
+use crate::models::ShardBlockInfo;
+use crate::sync::client::convert_grpc_message_data;
+use crate::sync::client::proto::Message;
+use crate::sync::client::proto::OnChainEvent;
+use crate::sync::client::proto::ShardChunk;
+
+/// Farcaster message hashes are BLAKE3 digests truncated to 20 bytes
+pub const MESSAGE_HASH_LENGTH: usize = 20;
+
+const HASH_SCHEME_BLAKE3: i32 = 1;
+const SIGNATURE_SCHEME_ED25519: i32 = 1;
+
+const SIGNER_EVENT_ADD: i64 = 1;
+const SIGNER_EVENT_REMOVE: i64 = 2;
+const SIGNER_EVENT_ADMIN_RESET: i64 = 3;
+
+/// Why a message failed verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectionReason {
+    /// No data / data bytes to verify
+    MissingData,
+    /// `data_bytes` do not decode to the message's `data` and body
+    DataMismatch,
+    UnsupportedHashScheme,
+    HashMismatch,
+    UnsupportedSignatureScheme,
+    InvalidSignature,
+    /// Validly signed, but by a key that is not an active signer of the FID
+    InactiveSigner,
+}
+
+impl RejectionReason {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingData => "missing_data",
+            Self::DataMismatch => "data_mismatch",
+            Self::UnsupportedHashScheme => "unsupported_hash_scheme",
+            Self::HashMismatch => "hash_mismatch",
+            Self::UnsupportedSignatureScheme => "unsupported_signature_scheme",
+            Self::InvalidSignature => "invalid_signature",
+            Self::InactiveSigner => "inactive_signer",
+        }
+    }
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Check the hash and signature of `message`
+///
+/// # Errors
+///
+/// Returns the reason the message is invalid.
+pub fn verify_message(message: &Message) -> std::result::Result<(), RejectionReason> {
+    let (Some(data), Some(data_bytes)) = (&message.data, &message.data_bytes) else {
+        return Err(RejectionReason::MissingData);
+    };
+
+    // The signed bytes must describe the message that gets ingested, body
+    // included
+    let signed = GrpcMessageData::decode(data_bytes.as_slice())
+        .map(|signed| convert_grpc_message_data(&signed))
+        .map_err(|_| RejectionReason::DataMismatch)?;
+    if signed.fid != data.fid
+        || signed.r#type != data.r#type
+        || signed.timestamp != data.timestamp
+        || signed.network != data.network
+        || signed.body != data.body
+    {
+        return Err(RejectionReason::DataMismatch);
+    }
+
+    if message.hash_scheme != HASH_SCHEME_BLAKE3 {
+        return Err(RejectionReason::UnsupportedHashScheme);
+    }
+    let digest = blake3::hash(data_bytes);
+    if message.hash != digest.as_bytes()[..MESSAGE_HASH_LENGTH] {
+        return Err(RejectionReason::HashMismatch);
+    }
+
+    if message.signature_scheme != SIGNATURE_SCHEME_ED25519 {
+        return Err(RejectionReason::UnsupportedSignatureScheme);
+    }
+    let key = <[u8; 32]>::try_from(message.signer.as_slice())
+        .ok()
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or(RejectionReason::InvalidSignature)?;
+    let signature =
+        Signature::from_slice(&message.signature).map_err(|_| RejectionReason::InvalidSignature)?;
+    key.verify_strict(&message.hash, &signature)
+        .map_err(|_| RejectionReason::InvalidSignature)
+}
+
+/// Active signer keys per FID, kept current with SIGNER on-chain events
+#[derive(Debug, Clone, Default)]
+pub struct SignerSet {
+    keys: HashMap<i64, HashSet<Vec<u8>>>,
+}
+
+impl SignerSet {
+    #[must_use]
+    pub const fn new(keys: HashMap<i64, HashSet<Vec<u8>>>) -> Self {
+        Self { keys }
+    }
+
+    /// Add or remove the key of a SIGNER event; other events are ignored
+    pub fn apply_event(&mut self, event: &OnChainEvent) {
+        let Some(signer) = event
+            .body
+            .as_ref()
+            .and_then(|body| body.get("signer_event_body"))
+        else {
+            return;
+        };
+        let Some(key) = signer
+            .get("key")
+            .and_then(|v| v.as_str())
+            .and_then(|s| hex::decode(s).ok())
+        else {
+            return;
+        };
+        let fid = i64::try_from(event.fid).unwrap_or(0);
+
+        match signer.get("event_type").and_then(serde_json::Value::as_i64) {
+            Some(SIGNER_EVENT_ADD) => {
+                self.keys.entry(fid).or_default().insert(key);
+            }
+            Some(SIGNER_EVENT_REMOVE | SIGNER_EVENT_ADMIN_RESET) => {
+                if let Some(keys) = self.keys.get_mut(&fid) {
+                    keys.remove(&key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[must_use]
+    pub fn is_active(&self, fid: i64, key: &[u8]) -> bool {
+        self.keys.get(&fid).is_some_and(|keys| keys.contains(key))
+    }
+}
+
+/// A user message that failed verification, located by its position in the batch
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub chunk_index: usize,
+    pub transaction_index: usize,
+    pub message_index: usize,
+    pub reason: RejectionReason,
+    pub shard_block_info: ShardBlockInfo,
+}
+
+/// FIDs that authored user messages in `chunks`
+#[must_use]
+pub fn message_fids(chunks: &[ShardChunk]) -> Vec<i64> {
+    let fids: HashSet<i64> = chunks
+        .iter()
+        .flat_map(|chunk| &chunk.transactions)
+        .filter(|transaction| transaction.fid > 0)
+        .flat_map(|transaction| &transaction.user_messages)
+        .filter_map(|message| message.data.as_ref())
+        .map(|data| i64::try_from(data.fid).unwrap_or(0))
+        .collect();
+    fids.into_iter().collect()
+}
+
+/// Verify the user messages of `chunks` in order
+///
+/// With `signers`, the SIGNER events of each transaction are applied before
+/// its user messages are checked, so a key added in the same block signs
+/// valid messages.
+#[must_use]
+pub fn verify_chunks(
+    chunks: &[ShardChunk],
+    shard_id: u32,
+    mut signers: Option<&mut SignerSet>,
+) -> Vec<Rejection> {
+    let mut rejections = Vec::new();
+
+    for (chunk_index, chunk) in chunks.iter().enumerate() {
+        let header = chunk.header.as_ref();
+        let block_number = header
+            .and_then(|h| h.height.as_ref())
+            .map_or(0, |h| h.block_number);
+        let timestamp = header.map_or(0, |h| h.timestamp);
+
+        for (transaction_index, transaction) in chunk.transactions.iter().enumerate() {
+            if let Some(signers) = signers.as_deref_mut() {
+                for event in transaction
+                    .system_messages
+                    .iter()
+                    .filter_map(|message| message.on_chain_event.as_ref())
+                {
+                    signers.apply_event(event);
+                }
+            }
+
+            // User messages of system transactions are not ingested
+            if transaction.fid == 0 {
+                continue;
+            }
+
+            for (message_index, message) in transaction.user_messages.iter().enumerate() {
+                let result = verify_message(message).and_then(|()| {
+                    signers.as_deref().map_or(Ok(()), |signers| {
+                        let fid = message
+                            .data
+                            .as_ref()
+                            .map_or(0, |data| i64::try_from(data.fid).unwrap_or(0));
+                        if signers.is_active(fid, &message.signer) {
+                            Ok(())
+                        } else {
+                            Err(RejectionReason::InactiveSigner)
+                        }
+                    })
+                });
+
+                if let Err(reason) = result {
+                    rejections.push(Rejection {
+                        chunk_index,
+                        transaction_index,
+                        message_index,
+                        reason,
+                        shard_block_info: ShardBlockInfo::new(
+                            shard_id,
+                            block_number,
+                            transaction.fid,
+                            timestamp,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    rejections
+}
@@ -1,40 +1,72 @@
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::sync::Arc;
 
+use sqlx::PgConnection;
 use tracing::debug;
+use tracing::warn;
 
+use crate::config::MessageVerification;
+use crate::database::active_signer_keys;
 use crate::database::Database;
 use crate::sync::client::proto::ShardChunk;
+use crate::sync::sink::PostgresSink;
+use crate::sync::sink::SyncSink;
 use crate::Result;
 
 // Re-export submodules
 mod batch;
 mod cast_handlers;
+mod copy;
 mod handlers;
+pub mod message_verification;
+mod reorg;
 mod types;
 mod utils;
 
 // Re-export types
 // Re-export batch function for testing (both unit and integration tests)
 pub use batch::flush_batched_data;
+pub(crate) use batch::rebuild_onchain_state;
+pub(crate) use batch::write_batched_data;
+pub use message_verification::RejectionReason;
+pub use message_verification::SignerSet;
+pub use reorg::chunk_link;
+pub use reorg::find_divergence;
+pub use reorg::ChunkLink;
+pub use reorg::ChunkSource;
+pub use reorg::Divergence;
+pub use reorg::MAX_REORG_DEPTH;
 pub use types::BatchedData;
 
 /// Processor for handling shard chunks and extracting user data
 pub struct ShardProcessor {
     database: Database,
+    // Sinks every batch is written to; `PostgreSQL`, if active, comes last
+    sinks: Vec<Arc<dyn SyncSink>>,
     // Cache for FIDs that have been ensured to exist in this batch
     fid_cache: std::sync::Mutex<HashSet<i64>>,
     // Cache for FIDs that have been registered (via id_register event)
     registered_fids: std::sync::Mutex<HashSet<i64>>,
+    // Node canonical chunks are fetched from when a rollback needs them
+    chunk_source: Option<Arc<dyn ChunkSource>>,
 }
 
 impl ShardProcessor {
     /// Create a new shard processor
     #[must_use]
     pub fn new(database: Database) -> Self {
+        let mut sinks = database.sync_sinks().file_sinks().to_vec();
+        if database.sync_sinks().writes_postgres() {
+            sinks.push(Arc::new(PostgresSink::new(database.clone())));
+        }
+
         Self {
             database,
+            sinks,
             fid_cache: std::sync::Mutex::new(HashSet::new()),
             registered_fids: std::sync::Mutex::new(HashSet::new()),
+            chunk_source: None,
         }
     }
 
@@ -46,12 +78,77 @@ impl ShardProcessor {
         // Note: We DON'T clear registered_fids as it should persist across batches
     }
 
+    /// Attach the node canonical chunks are fetched from when a rollback
+    /// needs blocks outside the batch being processed
+    #[must_use]
+    pub fn with_chunk_source(mut self, source: Arc<dyn ChunkSource>) -> Self {
+        self.chunk_source = Some(source);
+        self
+    }
+
     /// Process multiple chunks in a single batch for maximum performance
+    ///
+    /// Stored blocks that the chunks show to be stale are rolled back, and the
+    /// canonical chunks around the batch are re-applied with it in the same
+    /// transaction.
     pub async fn process_chunks_batch(&self, chunks: &[ShardChunk], shard_id: u32) -> Result<()> {
+        let mut last_height = chunks
+            .iter()
+            .filter_map(|chunk| chunk.header.as_ref()?.height.as_ref())
+            .map(|height| height.block_number)
+            .next_back();
+
+        if let Some(reorg) = self.reconcile_chain(chunks, shard_id).await? {
+            let top = self.apply_reorg(chunks, shard_id, reorg).await?;
+            last_height = last_height.max(top);
+        } else {
+            self.apply_chunks(chunks, shard_id).await?;
+        }
+
+        // Update sync progress for the last chunk
+        if let Some(height) = last_height {
+            self.database
+                .update_last_processed_height(shard_id, height)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Process a shard chunk and extract all user data
+    pub async fn process_chunk(&self, chunk: &ShardChunk, shard_id: u32) -> Result<()> {
+        self.process_chunks_batch(std::slice::from_ref(chunk), shard_id)
+            .await
+    }
+
+    /// Collect and write chunks in one batch, recording their block hashes
+    async fn apply_chunks(&self, chunks: &[ShardChunk], shard_id: u32) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        let batched = self.collect_chunks(chunks, shard_id, None).await?;
+        // Single batch write for all chunks
+        self.write_batch(shard_id, batched).await
+    }
+
+    /// Collect the rows of all chunks into one batch
+    ///
+    /// Signer keys are read on `conn` when given, so chunks re-applied after a
+    /// rollback are verified against the rebuilt signer state.
+    async fn collect_chunks(
+        &self,
+        chunks: &[ShardChunk],
+        shard_id: u32,
+        conn: Option<&mut PgConnection>,
+    ) -> Result<BatchedData> {
         // Collect all data from all chunks
         let mut batched = BatchedData::new();
+        let chunks = self
+            .verified_chunks(chunks, shard_id, &mut batched, conn)
+            .await?;
 
-        for chunk in chunks {
+        for chunk in chunks.iter() {
             let header = chunk
                 .header
                 .as_ref()
@@ -71,6 +168,12 @@ impl ShardProcessor {
                 block_number,
                 chunk.transactions.len()
             );
+            batched.processed_blocks.push((shard_id, block_number));
+            if let Some(link) = chunk_link(chunk) {
+                batched
+                    .block_hashes
+                    .push((shard_id, block_number, link.hash, link.parent_hash));
+            }
 
             // Process each transaction and collect data
             for (tx_idx, transaction) in chunk.transactions.iter().enumerate() {
@@ -86,69 +189,143 @@ impl ShardProcessor {
             }
         }
 
-        // Single batch insert for all chunks
-        batch::flush_batched_data(&self.database, batched).await?;
-
-        // Update sync progress for the last chunk
-        if let Some(last_chunk) = chunks.last() {
-            if let Some(header) = &last_chunk.header {
-                if let Some(height) = &header.height {
-                    self.database
-                        .update_last_processed_height(shard_id, height.block_number)
-                        .await?;
-                }
-            }
-        }
+        Ok(batched)
+    }
 
-        Ok(())
+    /// Write a snapshot page (wrapped as a chunk) without moving sync progress
+    ///
+    /// Bootstrap hands the shard to block sync only once the whole snapshot
+    /// has been applied.
+    pub async fn apply_snapshot_chunk(&self, chunk: &ShardChunk, shard_id: u32) -> Result<()> {
+        self.apply_chunk_with(chunk, shard_id, BatchedData::new())
+            .await
     }
 
-    /// Process a shard chunk and extract all user data
-    pub async fn process_chunk(&self, chunk: &ShardChunk, shard_id: u32) -> Result<()> {
+    /// Write a synthetic chunk together with rows already collected in `batched`
+    ///
+    /// Everything lands in one flush; sync progress does not move.
+    pub async fn apply_chunk_with(
+        &self,
+        chunk: &ShardChunk,
+        shard_id: u32,
+        mut batched: BatchedData,
+    ) -> Result<()> {
         let header = chunk
             .header
             .as_ref()
             .ok_or_else(|| crate::SnapRagError::Custom("Missing chunk header".to_string()))?;
+        let block_number = header.height.as_ref().map_or(0, |h| h.block_number);
 
-        let height = header
-            .height
-            .as_ref()
-            .ok_or_else(|| crate::SnapRagError::Custom("Missing header height".to_string()))?;
-
-        let block_number = height.block_number;
-        let timestamp = header.timestamp;
-
-        debug!(
-            "Processing shard {} block {} with {} transactions",
-            shard_id,
-            block_number,
-            chunk.transactions.len()
-        );
-
-        // Collect all data for batch insert
-        let mut batched = BatchedData::new();
-
-        // Process each transaction and collect data
-        for (tx_idx, transaction) in chunk.transactions.iter().enumerate() {
+        let verified = self
+            .verified_chunks(std::slice::from_ref(chunk), shard_id, &mut batched, None)
+            .await?;
+        for (tx_idx, transaction) in verified[0].transactions.iter().enumerate() {
             handlers::collect_transaction_data(
                 transaction,
                 shard_id,
                 block_number,
-                timestamp,
+                header.timestamp,
                 tx_idx,
                 &mut batched,
             )
             .await?;
         }
 
-        // Batch insert all collected data
-        batch::flush_batched_data(&self.database, batched).await?;
+        self.write_batch(shard_id, batched).await
+    }
 
-        // Update sync progress
-        self.database
-            .update_last_processed_height(shard_id, block_number)
-            .await?;
+    /// Hand a batch to every sink; the last one takes it without a copy
+    async fn write_batch(&self, shard_id: u32, batched: BatchedData) -> Result<()> {
+        let Some((last, rest)) = self.sinks.split_last() else {
+            return Ok(());
+        };
+        for sink in rest {
+            sink.write(shard_id, Cow::Borrowed(&batched)).await?;
+            debug!("Wrote shard {} batch to the {} sink", shard_id, sink.name());
+        }
+        last.write(shard_id, Cow::Owned(batched)).await
+    }
 
-        Ok(())
+    /// Drop user messages that fail verification, recording them in `batched`
+    ///
+    /// Returns `chunks` unchanged when verification is off or every message passes.
+    async fn verified_chunks<'a>(
+        &self,
+        chunks: &'a [ShardChunk],
+        shard_id: u32,
+        batched: &mut BatchedData,
+        conn: Option<&mut PgConnection>,
+    ) -> Result<Cow<'a, [ShardChunk]>> {
+        let policy = self.database.message_verification();
+        if !policy.is_enabled() {
+            return Ok(Cow::Borrowed(chunks));
+        }
+
+        let mut signers = if policy.verify_signers {
+            let fids = message_verification::message_fids(chunks);
+            let keys = match conn {
+                Some(conn) => active_signer_keys(conn, &fids).await?,
+                None => self.database.active_signer_keys(&fids).await?,
+            };
+            Some(SignerSet::new(keys))
+        } else {
+            None
+        };
+        let rejections = message_verification::verify_chunks(chunks, shard_id, signers.as_mut());
+        if rejections.is_empty() {
+            return Ok(Cow::Borrowed(chunks));
+        }
+
+        warn!(
+            "🚫 Rejected {} messages in shard {} that failed verification",
+            rejections.len(),
+            shard_id
+        );
+
+        let mut rejected = HashSet::new();
+        for rejection in rejections {
+            let message = &chunks[rejection.chunk_index].transactions[rejection.transaction_index]
+                .user_messages[rejection.message_index];
+            let (fid, message_type) = message.data.as_ref().map_or((0, 0), |data| {
+                (i64::try_from(data.fid).unwrap_or(0), data.r#type)
+            });
+            debug!(
+                "Rejected message {} of FID {}: {}",
+                hex::encode(&message.hash),
+                fid,
+                rejection.reason
+            );
+
+            batched.rejected_messages.push((
+                message.hash.clone(),
+                fid,
+                message_type,
+                message.signer.clone(),
+                rejection.reason.as_str(),
+                (policy.mode == MessageVerification::Quarantine)
+                    .then(|| serde_json::to_value(message).ok())
+                    .flatten(),
+                rejection.shard_block_info,
+            ));
+            rejected.insert((
+                rejection.chunk_index,
+                rejection.transaction_index,
+                rejection.message_index,
+            ));
+        }
+
+        let mut verified = chunks.to_vec();
+        for (chunk_index, chunk) in verified.iter_mut().enumerate() {
+            for (transaction_index, transaction) in chunk.transactions.iter_mut().enumerate() {
+                let mut message_index = 0;
+                transaction.user_messages.retain(|_| {
+                    let keep = !rejected.contains(&(chunk_index, transaction_index, message_index));
+                    message_index += 1;
+                    keep
+                });
+            }
+        }
+
+        Ok(Cow::Owned(verified))
     }
 }
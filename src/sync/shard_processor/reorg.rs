@@ -0,0 +1,429 @@
+//! Detection and repair of chunks that diverge from the stored chain
+//!
+//! Every applied chunk leaves its hash and parent hash in `block_hashes`.
+//! Before a batch is written, its chunks are compared with the stored blocks
+//! around them. When the node serves a chunk that replaces a stored block, or
+//! whose parent is not the stored block below it, the fork height is found
+//! by walking back along the canonical parents, every row synced from the
+//! fork up is rolled back, and the canonical chunks are applied in its place
+//! within the same transaction.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use tracing::info;
+use tracing::warn;
+
+use super::batch::publish_live_feed;
+use super::write_batched_data;
+use super::ShardProcessor;
+use crate::database::prune_orphaned_cast_embeddings;
+use crate::database::rollback_blocks;
+use crate::database::BlockHash;
+use crate::sync::client::proto;
+use crate::sync::client::proto::ShardChunk;
+use crate::sync::client::SnapchainClient;
+use crate::Result;
+
+/// Blocks a rollback may span before sync stops instead of rolling back
+pub const MAX_REORG_DEPTH: u64 = 128;
+
+/// Where canonical chunks are fetched from during a rollback
+#[async_trait]
+pub trait ChunkSource: Send + Sync {
+    /// Chunks of `shard_id` at heights `from..=to`, in height order
+    async fn shard_chunks(&self, shard_id: u32, from: u64, to: u64) -> Result<Vec<ShardChunk>>;
+}
+
+#[async_trait]
+impl ChunkSource for SnapchainClient {
+    async fn shard_chunks(&self, shard_id: u32, from: u64, to: u64) -> Result<Vec<ShardChunk>> {
+        let request = proto::ShardChunksRequest {
+            shard_id,
+            start_block_number: from,
+            stop_block_number: Some(to),
+        };
+        Ok(self.get_shard_chunks(request).await?.shard_chunks)
+    }
+}
+
+/// Height and hashes of a chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkLink {
+    pub height: u64,
+    pub hash: Vec<u8>,
+    pub parent_hash: Vec<u8>,
+}
+
+/// Hashes of a chunk
+///
+/// `None` unless the chunk carries both its hash and its parent hash. Chunks
+/// assembled from hub events take their hash from `BLOCK_CONFIRMED` but have
+/// no parent hash, so they are neither recorded nor checked for divergence.
+#[must_use]
+pub fn chunk_link(chunk: &ShardChunk) -> Option<ChunkLink> {
+    let header = chunk.header.as_ref()?;
+    let height = header.height.as_ref()?.block_number;
+    (!chunk.hash.is_empty() && !header.parent_hash.is_empty()).then(|| ChunkLink {
+        height,
+        hash: chunk.hash.clone(),
+        parent_hash: header.parent_hash.clone(),
+    })
+}
+
+/// How the chunks being applied disagree with the stored chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// The stored block at `height`, below the first chunk, is not its parent
+    Parent { height: u64, expected_hash: Vec<u8> },
+    /// The stored block at `height` has another hash than the chunk served for it
+    Replaced { height: u64 },
+    /// The stored block at `height`, above the last chunk, does not build on it
+    Child { height: u64 },
+}
+
+/// Compare chunks, sorted by height, with the stored blocks around them
+///
+/// # Errors
+/// Returns an error if two consecutive chunks do not link, which means the
+/// node served an inconsistent range and no rollback can fix it
+pub fn find_divergence(links: &[ChunkLink], stored: &[BlockHash]) -> Result<Option<Divergence>> {
+    let stored: BTreeMap<u64, &BlockHash> = stored
+        .iter()
+        .filter_map(|block| Some((u64::try_from(block.block_height).ok()?, block)))
+        .collect();
+    let (Some(first), Some(last)) = (links.first(), links.last()) else {
+        return Ok(None);
+    };
+
+    for pair in links.windows(2) {
+        if pair[1].height == pair[0].height + 1 && pair[1].parent_hash != pair[0].hash {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Chunk {} is not the parent of chunk {}; the node served an inconsistent range",
+                pair[0].height, pair[1].height
+            )));
+        }
+    }
+
+    if let Some(height) = first.height.checked_sub(1) {
+        if stored
+            .get(&height)
+            .is_some_and(|below| below.block_hash != first.parent_hash)
+        {
+            return Ok(Some(Divergence::Parent {
+                height,
+                expected_hash: first.parent_hash.clone(),
+            }));
+        }
+    }
+    if let Some(link) = links.iter().find(|link| {
+        stored
+            .get(&link.height)
+            .is_some_and(|block| block.block_hash != link.hash)
+    }) {
+        return Ok(Some(Divergence::Replaced {
+            height: link.height,
+        }));
+    }
+    let height = last.height + 1;
+    if stored
+        .get(&height)
+        .is_some_and(|above| above.parent_hash != last.hash)
+    {
+        return Ok(Some(Divergence::Child { height }));
+    }
+    Ok(None)
+}
+
+/// A divergence to roll back and the canonical chunks that replace it
+pub(super) struct Reorg {
+    pub fork_height: u64,
+    /// Canonical chunks between the fork and the first chunk of the batch
+    pub replay_below: Vec<ShardChunk>,
+    /// Canonical chunks above the batch up to the highest stored block
+    pub replay_above: Vec<ShardChunk>,
+}
+
+impl ShardProcessor {
+    /// Find stored blocks that `chunks` show to be stale
+    ///
+    /// Canonical chunks outside the batch are fetched here, before anything
+    /// is deleted, so a failed fetch leaves the database untouched.
+    pub(super) async fn reconcile_chain(
+        &self,
+        chunks: &[ShardChunk],
+        shard_id: u32,
+    ) -> Result<Option<Reorg>> {
+        // Block hashes live in PostgreSQL
+        if !self.database.sync_sinks().writes_postgres() {
+            return Ok(None);
+        }
+
+        let mut links: Vec<ChunkLink> = chunks.iter().filter_map(chunk_link).collect();
+        links.sort_by_key(|link| link.height);
+        let (Some(first), Some(last)) = (links.first(), links.last()) else {
+            return Ok(None);
+        };
+        let stored = self
+            .database
+            .block_hashes(shard_id, first.height.saturating_sub(1), last.height + 1)
+            .await?;
+        let Some(divergence) = find_divergence(&links, &stored)? else {
+            return Ok(None);
+        };
+        warn!(
+            "🔀 Shard {} diverged from the stored chain: {:?}",
+            shard_id, divergence
+        );
+
+        let (fork_height, replay_below) = match divergence {
+            Divergence::Parent {
+                height,
+                expected_hash,
+            } => {
+                self.walk_back(shard_id, height, expected_hash, first.height)
+                    .await?
+            }
+            Divergence::Replaced { height } | Divergence::Child { height } => (height, Vec::new()),
+        };
+
+        let top = self
+            .database
+            .highest_hashed_block(shard_id)
+            .await?
+            .map_or(last.height, |top| top.max(last.height));
+        if top - fork_height + 1 > MAX_REORG_DEPTH {
+            return Err(crate::SnapRagError::Custom(format!(
+                "Shard {shard_id} diverged at block {fork_height}, {} blocks below the stored \
+                 tip {top}; more than {MAX_REORG_DEPTH} blocks are not rolled back automatically",
+                top - fork_height + 1
+            )));
+        }
+        let replay_above = if top > last.height {
+            self.canonical_chunks(shard_id, last.height + 1, top)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(Reorg {
+            fork_height,
+            replay_below,
+            replay_above,
+        }))
+    }
+
+    /// Roll back from the fork and apply the canonical chunks in one transaction
+    ///
+    /// The batch is written together with the canonical chunks below and
+    /// above it, so readers never see the shard rolled back but not yet
+    /// re-applied, and a failed re-apply leaves the stored chain untouched for
+    /// the next sync to retry. Returns the highest re-applied height above the
+    /// batch.
+    pub(super) async fn apply_reorg(
+        &self,
+        chunks: &[ShardChunk],
+        shard_id: u32,
+        reorg: Reorg,
+    ) -> Result<Option<u64>> {
+        let top = reorg
+            .replay_above
+            .iter()
+            .filter_map(chunk_link)
+            .map(|link| link.height)
+            .max();
+        let canonical: Vec<ShardChunk> = reorg
+            .replay_below
+            .into_iter()
+            .chain(chunks.iter().cloned())
+            .chain(reorg.replay_above)
+            .collect();
+
+        let mut tx = self.database.pool().begin().await?;
+        let rollback = rollback_blocks(&mut tx, shard_id, reorg.fork_height).await?;
+        let batched = self
+            .collect_chunks(&canonical, shard_id, Some(&mut tx))
+            .await?;
+        for sink in self.database.sync_sinks().file_sinks() {
+            sink.write(shard_id, Cow::Borrowed(&batched)).await?;
+        }
+        let live_events = write_batched_data(&mut tx, &self.database, batched).await?;
+        let pruned = prune_orphaned_cast_embeddings(&mut tx, &rollback.cast_hashes).await?;
+        tx.commit().await?;
+        publish_live_feed(&self.database, &live_events).await;
+
+        info!(
+            "🔀 Rolled back {} rows of shard {} from block {} and re-applied {} canonical \
+             blocks; pruned {} orphaned cast embeddings",
+            rollback.rows_deleted,
+            shard_id,
+            reorg.fork_height,
+            canonical.len(),
+            pruned
+        );
+        Ok(top)
+    }
+
+    /// Follow canonical parents down from `height` until a stored block matches
+    ///
+    /// Returns the fork height and the canonical chunks from there up to
+    /// `first`, exclusive.
+    async fn walk_back(
+        &self,
+        shard_id: u32,
+        mut height: u64,
+        mut expected_hash: Vec<u8>,
+        first: u64,
+    ) -> Result<(u64, Vec<ShardChunk>)> {
+        let mut replay = Vec::new();
+        loop {
+            match self.database.block_hash(shard_id, height).await? {
+                Some(stored) if stored.block_hash != expected_hash => {}
+                // A match, or a block synced before hashes were recorded
+                _ => break,
+            }
+            if first - height > MAX_REORG_DEPTH {
+                return Err(crate::SnapRagError::Custom(format!(
+                    "Shard {shard_id} diverged more than {MAX_REORG_DEPTH} blocks below block \
+                     {first}; not rolling back automatically"
+                )));
+            }
+
+            let (chunk, link) = self
+                .canonical_chunks(shard_id, height, height)
+                .await?
+                .into_iter()
+                .find_map(|chunk| {
+                    let link = chunk_link(&chunk).filter(|link| link.height == height)?;
+                    Some((chunk, link))
+                })
+                .ok_or_else(|| {
+                    crate::SnapRagError::Custom(format!(
+                        "The node did not serve block {height} of shard {shard_id}"
+                    ))
+                })?;
+            if link.hash != expected_hash {
+                return Err(crate::SnapRagError::Custom(format!(
+                    "Block {height} of shard {shard_id} is not the parent of block {}",
+                    height + 1
+                )));
+            }
+            expected_hash = link.parent_hash;
+            replay.push(chunk);
+
+            let Some(below) = height.checked_sub(1) else {
+                replay.reverse();
+                return Ok((0, replay));
+            };
+            height = below;
+        }
+
+        replay.reverse();
+        Ok((height + 1, replay))
+    }
+
+    async fn canonical_chunks(&self, shard_id: u32, from: u64, to: u64) -> Result<Vec<ShardChunk>> {
+        let source = self.chunk_source.as_ref().ok_or_else(|| {
+            crate::SnapRagError::Custom(format!(
+                "Shard {shard_id} diverged from the stored chain, but this sync has no node \
+                 to fetch canonical blocks {from}-{to} from"
+            ))
+        })?;
+        source.shard_chunks(shard_id, from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(height: u64, hash: u8, parent_hash: u8) -> ChunkLink {
+        ChunkLink {
+            height,
+            hash: vec![hash],
+            parent_hash: vec![parent_hash],
+        }
+    }
+
+    fn stored(height: i64, hash: u8, parent_hash: u8) -> BlockHash {
+        BlockHash {
+            block_height: height,
+            block_hash: vec![hash],
+            parent_hash: vec![parent_hash],
+        }
+    }
+
+    #[test]
+    fn test_matching_chain_has_no_divergence() {
+        let links = [link(11, 0xb, 0xa), link(12, 0xc, 0xb)];
+        let chain = [
+            stored(10, 0xa, 0x9),
+            stored(11, 0xb, 0xa),
+            stored(13, 0xd, 0xc),
+        ];
+        assert_eq!(find_divergence(&links, &chain).unwrap(), None);
+        assert_eq!(find_divergence(&links, &[]).unwrap(), None);
+        assert_eq!(find_divergence(&[], &chain).unwrap(), None);
+    }
+
+    #[test]
+    fn test_divergences() {
+        let links = [link(11, 0xb, 0xa), link(12, 0xc, 0xb)];
+
+        assert_eq!(
+            find_divergence(&links, &[stored(10, 0xf, 0x9)]).unwrap(),
+            Some(Divergence::Parent {
+                height: 10,
+                expected_hash: vec![0xa]
+            })
+        );
+        assert_eq!(
+            find_divergence(&links, &[stored(10, 0xa, 0x9), stored(12, 0xe, 0xb)]).unwrap(),
+            Some(Divergence::Replaced { height: 12 })
+        );
+        assert_eq!(
+            find_divergence(&links, &[stored(13, 0xd, 0xe)]).unwrap(),
+            Some(Divergence::Child { height: 13 })
+        );
+    }
+
+    #[test]
+    fn test_unlinked_chunks_are_an_error() {
+        let links = [link(11, 0xb, 0xa), link(12, 0xc, 0xf)];
+        assert!(find_divergence(&links, &[]).is_err());
+        // Non-consecutive heights are not compared
+        let links = [link(11, 0xb, 0xa), link(13, 0xd, 0xc)];
+        assert!(find_divergence(&links, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_event_chunks_have_no_link() {
+        let chunk = ShardChunk {
+            header: Some(proto::ShardHeader {
+                height: Some(proto::Height {
+                    shard_index: 1,
+                    block_number: 7,
+                }),
+                timestamp: 0,
+                parent_hash: vec![],
+                shard_root: vec![],
+            }),
+            hash: vec![],
+            transactions: vec![],
+        };
+        assert_eq!(chunk_link(&chunk), None);
+
+        // Hub events carry the block hash but not the parent hash
+        let mut chunk = ShardChunk {
+            hash: vec![1],
+            ..chunk
+        };
+        assert_eq!(chunk_link(&chunk), None);
+
+        chunk.header.as_mut().unwrap().parent_hash = vec![2];
+        let link = chunk_link(&chunk).unwrap();
+        assert_eq!(link.height, 7);
+        assert_eq!(link.parent_hash, [2]);
+    }
+}
@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 
 /// Batched data for bulk insert
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct BatchedData {
-    // Casts: (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions, shard_block_info)
+    // Casts: (fid, text, timestamp, message_hash, parent_hash, root_hash, embeds, mentions, parent_fid, parent_url, shard_block_info)
     pub casts: Vec<(
         i64,
         Option<String>,
@@ -13,8 +13,18 @@ pub struct BatchedData {
         Option<Vec<u8>>,
         Option<serde_json::Value>,
         Option<serde_json::Value>,
+        Option<i64>,    // parent_fid
+        Option<String>, // parent_url (channel)
         crate::models::ShardBlockInfo,
     )>,
+    // Cast removes (tombstones): (fid, target_hash, timestamp, message_hash, shard_block_info)
+    pub cast_removes: Vec<(
+        i64,                           // fid
+        Vec<u8>,                       // target_hash (message_hash of the removed cast)
+        i64,                           // timestamp
+        Vec<u8>,                       // message_hash
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
     // Links: (fid, target_fid, link_type, event_type, timestamp, message_hash, shard_block_info)
     pub links: Vec<(
         i64,                           // fid
@@ -25,6 +35,16 @@ pub struct BatchedData {
         Vec<u8>,                       // message_hash
         crate::models::ShardBlockInfo, // shard_block_info
     )>,
+    // Link compact states: (fid, link_type, target_fids, timestamp, message_hash, shard_block_info)
+    // Each one is the authoritative link set of `fid` as of `timestamp`
+    pub link_compact_states: Vec<(
+        i64,                           // fid
+        String,                        // link_type
+        Vec<i64>,                      // target_fids
+        i64,                           // timestamp
+        Vec<u8>,                       // message_hash
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
     // Reactions: (fid, target_cast_hash, target_fid, reaction_type, event_type, timestamp, message_hash, shard_block_info)
     pub reactions: Vec<(
         i64,                           // fid
@@ -51,9 +71,16 @@ pub struct BatchedData {
     )>,
     // ❌ Removed: activities field (user_activity_timeline table dropped for performance)
     pub fids_to_ensure: HashSet<i64>,
-    // Profile field updates: (fid, field_name, value, timestamp, message_hash)
-    pub profile_updates: Vec<(i64, String, Option<String>, i64, Vec<u8>)>,
-    // Onchain events: (fid, event_type, chain_id, block_number, block_hash, block_timestamp, tx_hash, log_index, event_data)
+    // Profile field updates: (fid, field_name, value, timestamp, message_hash, shard_block_info)
+    pub profile_updates: Vec<(
+        i64,
+        String,
+        Option<String>,
+        i64,
+        Vec<u8>,
+        crate::models::ShardBlockInfo,
+    )>,
+    // Onchain events: (fid, event_type, chain_id, block_number, block_hash, block_timestamp, tx_hash, log_index, event_data, shard_block_info)
     pub onchain_events: Vec<(
         i64,
         i32,
@@ -64,6 +91,43 @@ pub struct BatchedData {
         Option<Vec<u8>>,
         Option<i32>,
         serde_json::Value,
+        crate::models::ShardBlockInfo,
+    )>,
+    // Signer events: (fid, key, key_type, event_type, metadata, block_number, log_index, block_timestamp, shard_block_info)
+    pub signer_events: Vec<(
+        i64,                           // fid
+        Vec<u8>,                       // key (Ed25519 public key)
+        i32,                           // key_type
+        i16,                           // event_type (1=add, 2=remove, 3=admin_reset)
+        Option<Vec<u8>>,               // metadata
+        i64,                           // block_number
+        i32,                           // log_index
+        i64,                           // block_timestamp
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
+    // Id register events: (fid, event_type, to, from, recovery_address, block_number, log_index, block_timestamp, shard_block_info)
+    pub id_register_events: Vec<(
+        i64,                           // fid
+        i16,                           // event_type (1=register, 2=transfer, 3=change_recovery)
+        Option<Vec<u8>>,               // to (custody address)
+        Option<Vec<u8>>,               // from
+        Option<Vec<u8>>,               // recovery_address
+        i64,                           // block_number
+        i32,                           // log_index
+        i64,                           // block_timestamp
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
+    // Storage rents: (fid, payer, units, expiry, block_number, log_index, block_timestamp, transaction_hash, shard_block_info)
+    pub storage_rents: Vec<(
+        i64,                           // fid
+        Option<Vec<u8>>,               // payer
+        i64,                           // units
+        i64,                           // expiry (unix seconds)
+        i64,                           // block_number
+        i32,                           // log_index
+        i64,                           // block_timestamp
+        Option<Vec<u8>>,               // transaction_hash
+        crate::models::ShardBlockInfo, // shard_block_info
     )>,
     // ❌ Removed: Separate remove vectors no longer needed (using event_type in main vectors)
     // Username proofs: (fid, username, owner, signature, username_type, timestamp, message_hash, shard_block_info)
@@ -77,6 +141,27 @@ pub struct BatchedData {
         Vec<u8>,
         crate::models::ShardBlockInfo,
     )>,
+    // Fname transfers: (transfer_id, from_fid, to_fid, username, owner, signature, timestamp, shard_block_info)
+    pub fname_transfers: Vec<(
+        i64,                           // transfer_id
+        i64,                           // from_fid
+        i64,                           // to_fid
+        String,                        // username
+        Vec<u8>,                       // owner
+        Vec<u8>,                       // signature
+        i64,                           // timestamp
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
+    // Storage lends: (fid, to_fid, num_units, unit_type, timestamp, message_hash, shard_block_info)
+    pub storage_lends: Vec<(
+        i64,                           // fid (lender)
+        i64,                           // to_fid (borrower)
+        i64,                           // num_units
+        i16,                           // unit_type
+        i64,                           // timestamp
+        Vec<u8>,                       // message_hash
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
     // Frame actions: (fid, url, button_index, cast_hash, cast_fid, input_text, state, transaction_id, timestamp, message_hash, shard_block_info)
     pub frame_actions: Vec<(
         i64,
@@ -91,6 +176,21 @@ pub struct BatchedData {
         Vec<u8>,
         crate::models::ShardBlockInfo,
     )>,
+    // Rejected messages: (message_hash, fid, message_type, signer, reason, message, shard_block_info)
+    // `message` is only kept in quarantine mode
+    pub rejected_messages: Vec<(
+        Vec<u8>,                       // message_hash
+        i64,                           // fid
+        i32,                           // message_type
+        Vec<u8>,                       // signer
+        &'static str,                  // reason
+        Option<serde_json::Value>,     // message
+        crate::models::ShardBlockInfo, // shard_block_info
+    )>,
+    // Heights of the chunks in this batch, recorded in the processed_blocks ledger: (shard_id, block_height)
+    pub processed_blocks: Vec<(u32, u64)>,
+    // Header hashes of the chunks in this batch: (shard_id, block_height, block_hash, parent_hash)
+    pub block_hashes: Vec<(u32, u64, Vec<u8>, Vec<u8>)>,
 }
 
 impl BatchedData {
@@ -0,0 +1,91 @@
+//! Partition layout shared by the file sinks
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use super::records::Record;
+
+/// Directory of one partition: `<root>/<table>/shard=<id>/date=<YYYY-MM-DD>`
+pub fn partition_dir(root: &Path, table: &str, shard_id: u32, day: NaiveDate) -> PathBuf {
+    root.join(table)
+        .join(format!("shard={shard_id}"))
+        .join(format!("date={day}"))
+}
+
+/// Path of part `part` in a partition directory
+pub fn part_path(dir: &Path, part: u32, extension: &str) -> PathBuf {
+    dir.join(format!("part-{part:05}.{extension}"))
+}
+
+/// First part number after every `part-NNNNN.<extension>` in `dir`
+///
+/// Restarted sinks never append to files written by an earlier run.
+pub fn next_part(dir: &Path, extension: &str) -> io::Result<u32> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let suffix = format!(".{extension}");
+    let mut next = 0;
+    for entry in entries {
+        let name = entry?.file_name();
+        let part = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("part-"))
+            .and_then(|name| name.strip_suffix(suffix.as_str()))
+            .and_then(|part| part.parse::<u32>().ok());
+        if let Some(part) = part {
+            next = next.max(part + 1);
+        }
+    }
+    Ok(next)
+}
+
+/// Records grouped by shard and day, keeping their order
+pub fn partitions(records: &[Record]) -> BTreeMap<(u32, NaiveDate), Vec<&Record>> {
+    let mut partitions: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for record in records {
+        partitions
+            .entry((record.shard_id, record.day))
+            .or_default()
+            .push(record);
+    }
+    partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_part_skips_existing_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(next_part(&dir.path().join("missing"), "ndjson").unwrap(), 0);
+
+        for name in [
+            "part-00000.ndjson",
+            "part-00003.ndjson",
+            "part-00007.parquet",
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        assert_eq!(next_part(dir.path(), "ndjson").unwrap(), 4);
+        assert_eq!(next_part(dir.path(), "parquet").unwrap(), 8);
+    }
+
+    #[test]
+    fn test_partition_dir() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(
+            partition_dir(Path::new("exports/ndjson"), "casts", 1, day),
+            PathBuf::from("exports/ndjson/casts/shard=1/date=2024-03-09")
+        );
+    }
+}
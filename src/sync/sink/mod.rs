@@ -0,0 +1,262 @@
+//! Destinations of synced batches
+//!
+//! [`ShardProcessor`](super::shard_processor::ShardProcessor) hands every batch
+//! to each active [`SyncSink`]: the `PostgreSQL` writer and the NDJSON and
+//! Parquet file sinks, which export the raw stream per table as
+//! `<output_dir>/<format>/<table>/shard=<id>/date=<YYYY-MM-DD>/part-NNNNN.<ext>`.
+//!
+//! File sinks are written before `PostgreSQL` and a failed batch is retried
+//! whole, so exported rows are delivered at least once; consumers should
+//! deduplicate on `message_hash`. Without the `postgres` sink, sync progress
+//! is kept in `<output_dir>/sync_progress.json` instead of `sync_progress`.
+
+mod files;
+mod ndjson;
+mod parquet;
+mod postgres;
+mod progress;
+pub mod records;
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+pub use ndjson::NdjsonSink;
+pub use postgres::PostgresSink;
+pub use progress::ExportProgress;
+
+pub use self::parquet::ParquetSink;
+use crate::config::SinkKind;
+use crate::database::VerificationPolicy;
+use crate::sync::shard_processor::BatchedData;
+use crate::AppConfig;
+use crate::Result;
+
+/// Progress file of an export-only sync, relative to `output_dir`
+pub const EXPORT_PROGRESS_FILE: &str = "sync_progress.json";
+
+/// A destination of synced batches
+#[async_trait]
+pub trait SyncSink: fmt::Debug + Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Write a batch synced from `shard_id`
+    ///
+    /// Sinks that only read the batch leave it borrowed, so the sink after
+    /// them can take it without a copy.
+    async fn write(&self, shard_id: u32, batched: Cow<'_, BatchedData>) -> Result<()>;
+}
+
+/// When a file sink starts the next part of a partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileRotation {
+    pub max_rows: usize,
+    pub max_bytes: u64,
+}
+
+/// The sinks sync writes to
+#[derive(Debug, Clone)]
+pub struct SyncSinks {
+    postgres: bool,
+    files: Vec<Arc<dyn SyncSink>>,
+    progress: Option<Arc<ExportProgress>>,
+}
+
+impl SyncSinks {
+    /// Only `PostgreSQL`
+    #[must_use]
+    pub const fn postgres() -> Self {
+        Self {
+            postgres: true,
+            files: Vec::new(),
+            progress: None,
+        }
+    }
+
+    /// Sinks selected by `[sinks]`
+    ///
+    /// # Errors
+    /// Returns an error if no sink is selected, if an export-only sync would
+    /// need the database for signer verification, or if the progress file of
+    /// an export-only sync cannot be read
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let sinks = &config.sinks;
+        if sinks.kinds.is_empty() {
+            return Err(crate::SnapRagError::Custom(
+                "[sinks] kinds must name at least one sink".to_string(),
+            ));
+        }
+
+        let root = Path::new(&sinks.output_dir);
+        let rotation = FileRotation {
+            max_rows: sinks.max_file_rows.max(1),
+            max_bytes: sinks.max_file_bytes.max(1),
+        };
+        let mut seen = HashSet::new();
+        let mut files: Vec<Arc<dyn SyncSink>> = Vec::new();
+        for kind in &sinks.kinds {
+            if !seen.insert(*kind) {
+                continue;
+            }
+            match kind {
+                SinkKind::Postgres => {}
+                SinkKind::Ndjson => {
+                    files.push(Arc::new(NdjsonSink::new(root.join("ndjson"), rotation)));
+                }
+                SinkKind::Parquet => {
+                    files.push(Arc::new(ParquetSink::new(root.join("parquet"), rotation)));
+                }
+            }
+        }
+
+        if sinks.writes_postgres() {
+            return Ok(Self {
+                postgres: true,
+                files,
+                progress: None,
+            });
+        }
+
+        let policy = VerificationPolicy::from_config(&config.sync);
+        if policy.is_enabled() && policy.verify_signers {
+            return Err(crate::SnapRagError::Custom(
+                "Signer verification reads signers from PostgreSQL; set \
+                 sync.verify_message_signers = false for an export-only sync"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Self {
+            postgres: false,
+            files,
+            progress: Some(Arc::new(ExportProgress::open(
+                root.join(EXPORT_PROGRESS_FILE),
+            )?)),
+        })
+    }
+
+    /// Whether batches are written to `PostgreSQL`
+    #[must_use]
+    pub const fn writes_postgres(&self) -> bool {
+        self.postgres
+    }
+
+    /// Active file sinks
+    #[must_use]
+    pub fn file_sinks(&self) -> &[Arc<dyn SyncSink>] {
+        &self.files
+    }
+
+    /// Sync progress of an export-only sync
+    #[must_use]
+    pub fn export_progress(&self) -> Option<&ExportProgress> {
+        self.progress.as_deref()
+    }
+}
+
+impl Default for SyncSinks {
+    fn default() -> Self {
+        Self::postgres()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MessageVerification;
+    use crate::database::Database;
+    use crate::models::ShardBlockInfo;
+    use crate::sync::client::proto;
+    use crate::sync::ShardProcessor;
+
+    fn export_config(output_dir: &Path) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.sinks.kinds = vec![SinkKind::Ndjson, SinkKind::Parquet, SinkKind::Ndjson];
+        config.sinks.output_dir = output_dir.display().to_string();
+        config
+    }
+
+    #[test]
+    fn test_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = export_config(dir.path());
+        let sinks = SyncSinks::from_config(&config).unwrap();
+        assert!(!sinks.writes_postgres());
+        assert_eq!(sinks.file_sinks().len(), 2);
+        assert!(sinks.export_progress().is_some());
+
+        config.sync.message_verification = MessageVerification::Reject;
+        assert!(SyncSinks::from_config(&config).is_err());
+        config.sync.verify_message_signers = false;
+        assert!(SyncSinks::from_config(&config).is_ok());
+
+        config.sinks.kinds = vec![];
+        assert!(SyncSinks::from_config(&config).is_err());
+
+        let sinks = SyncSinks::from_config(&AppConfig::default()).unwrap();
+        assert!(sinks.writes_postgres());
+        assert!(sinks.file_sinks().is_empty());
+        assert!(sinks.export_progress().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_only_sync_needs_no_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let sinks = SyncSinks::from_config(&export_config(dir.path())).unwrap();
+        // Nothing listens here, so any query would fail
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost:1/snaprag")
+            .unwrap();
+        let database = Database::new(pool).with_sync_sinks(sinks);
+
+        let chunk = proto::ShardChunk {
+            header: Some(proto::ShardHeader {
+                height: Some(proto::Height {
+                    shard_index: 1,
+                    block_number: 5,
+                }),
+                timestamp: 0,
+                parent_hash: vec![],
+                shard_root: vec![],
+            }),
+            hash: vec![],
+            transactions: vec![],
+        };
+        let mut batched = BatchedData::new();
+        batched.links.push((
+            7,
+            3,
+            "follow".to_string(),
+            "add".to_string(),
+            0,
+            vec![1; 20],
+            ShardBlockInfo::new(1, 5, 7, 0),
+        ));
+        ShardProcessor::new(database.clone())
+            .apply_chunk_with(&chunk, 1, batched)
+            .await
+            .unwrap();
+
+        let partition = Path::new("links/shard=1/date=2021-01-01");
+        assert!(dir
+            .path()
+            .join("ndjson")
+            .join(partition)
+            .join("part-00000.ndjson")
+            .exists());
+        assert!(dir
+            .path()
+            .join("parquet")
+            .join(partition)
+            .join("part-00000.parquet")
+            .exists());
+
+        database.update_last_processed_height(1, 5).await.unwrap();
+        assert_eq!(database.get_last_processed_height(1).await.unwrap(), 5);
+        assert!(dir.path().join(EXPORT_PROGRESS_FILE).exists());
+    }
+}
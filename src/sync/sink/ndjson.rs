@@ -0,0 +1,189 @@
+//! The NDJSON file sink
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use super::files::next_part;
+use super::files::part_path;
+use super::files::partition_dir;
+use super::files::partitions;
+use super::records::table_records;
+use super::records::Record;
+use super::records::Table;
+use super::FileRotation;
+use super::SyncSink;
+use crate::export::format::json_row;
+use crate::sync::shard_processor::BatchedData;
+use crate::Result;
+
+const EXTENSION: &str = "ndjson";
+
+/// Part a partition currently appends to
+#[derive(Debug, Clone, Copy)]
+struct CurrentPart {
+    part: u32,
+    rows: usize,
+    bytes: u64,
+}
+
+/// Appends one JSON object per row to the current part of each partition
+///
+/// Parts are flushed after every batch and stay readable while they grow;
+/// a restarted sink starts new parts instead of appending to old ones.
+#[derive(Debug)]
+pub struct NdjsonSink {
+    root: PathBuf,
+    rotation: FileRotation,
+    parts: Mutex<HashMap<PathBuf, CurrentPart>>,
+}
+
+impl NdjsonSink {
+    #[must_use]
+    pub fn new(root: PathBuf, rotation: FileRotation) -> Self {
+        Self {
+            root,
+            rotation,
+            parts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn append(
+        &self,
+        parts: &mut HashMap<PathBuf, CurrentPart>,
+        dir: &Path,
+        table: &Table,
+        records: &[&Record],
+    ) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut current = match parts.get(dir) {
+            Some(current) => *current,
+            None => CurrentPart {
+                part: next_part(dir, EXTENSION)?,
+                rows: 0,
+                bytes: 0,
+            },
+        };
+
+        let mut file = open_part(dir, current.part)?;
+        for record in records {
+            let mut line = serde_json::to_vec(&json_row(table.columns, &record.cells))?;
+            line.push(b'\n');
+            let line_bytes = line.len() as u64;
+
+            if current.rows > 0
+                && (current.rows >= self.rotation.max_rows
+                    || current.bytes + line_bytes > self.rotation.max_bytes)
+            {
+                file.flush()?;
+                current = CurrentPart {
+                    part: current.part + 1,
+                    rows: 0,
+                    bytes: 0,
+                };
+                file = open_part(dir, current.part)?;
+            }
+
+            file.write_all(&line)?;
+            current.rows += 1;
+            current.bytes += line_bytes;
+        }
+        file.flush()?;
+
+        parts.insert(dir.to_path_buf(), current);
+        Ok(())
+    }
+}
+
+fn open_part(dir: &Path, part: u32) -> Result<BufWriter<File>> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(part_path(dir, part, EXTENSION))?;
+    Ok(BufWriter::new(file))
+}
+
+#[async_trait]
+impl SyncSink for NdjsonSink {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+
+    async fn write(&self, shard_id: u32, batched: Cow<'_, BatchedData>) -> Result<()> {
+        let tables = table_records(shard_id, &batched);
+        let mut parts = self
+            .parts
+            .lock()
+            .map_err(|_| crate::SnapRagError::Custom("NDJSON sink lock poisoned".to_string()))?;
+
+        for table in &tables {
+            for ((shard_id, day), records) in partitions(&table.records) {
+                let dir = partition_dir(&self.root, table.table.name, shard_id, day);
+                self.append(&mut parts, &dir, table.table, &records)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ShardBlockInfo;
+
+    fn link_batch(count: i64) -> BatchedData {
+        let mut batched = BatchedData::new();
+        for fid in 0..count {
+            batched.links.push((
+                fid,
+                3,
+                "follow".to_string(),
+                "add".to_string(),
+                86_400, // 2021-01-02
+                vec![0xab; 20],
+                ShardBlockInfo::new(1, 10, 0, 0),
+            ));
+        }
+        batched
+    }
+
+    #[tokio::test]
+    async fn test_rotates_by_rows_and_restarts_in_new_part() {
+        let dir = tempfile::tempdir().unwrap();
+        let rotation = FileRotation {
+            max_rows: 2,
+            max_bytes: u64::MAX,
+        };
+        let sink = NdjsonSink::new(dir.path().to_path_buf(), rotation);
+        sink.write(1, Cow::Owned(link_batch(3))).await.unwrap();
+
+        let partition = dir.path().join("links/shard=1/date=2021-01-02");
+        let first = fs::read_to_string(partition.join("part-00000.ndjson")).unwrap();
+        let second = fs::read_to_string(partition.join("part-00001.ndjson")).unwrap();
+        assert_eq!(first.lines().count(), 2);
+        assert_eq!(second.lines().count(), 1);
+
+        let row: serde_json::Value = serde_json::from_str(first.lines().next().unwrap()).unwrap();
+        assert_eq!(row["link_type"], "follow");
+        assert_eq!(row["message_hash"], "ab".repeat(20));
+        assert_eq!(row["shard_id"], 1);
+
+        // The next batch fills the current part first
+        sink.write(1, Cow::Owned(link_batch(1))).await.unwrap();
+        let second = fs::read_to_string(partition.join("part-00001.ndjson")).unwrap();
+        assert_eq!(second.lines().count(), 2);
+
+        // A new sink never appends to existing parts
+        let restarted = NdjsonSink::new(dir.path().to_path_buf(), rotation);
+        restarted.write(1, Cow::Owned(link_batch(1))).await.unwrap();
+        assert!(partition.join("part-00002.ndjson").exists());
+    }
+}
@@ -0,0 +1,212 @@
+//! The Parquet file sink
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use super::files::next_part;
+use super::files::part_path;
+use super::files::partition_dir;
+use super::files::partitions;
+use super::records::table_records;
+use super::records::Record;
+use super::records::Table;
+use super::FileRotation;
+use super::SyncSink;
+use crate::export::format::arrow_schema;
+use crate::export::format::parquet_error;
+use crate::export::format::record_batch;
+use crate::sync::shard_processor::BatchedData;
+use crate::Result;
+
+const EXTENSION: &str = "parquet";
+
+/// Rows handed to the Parquet writer at a time; rotation is checked between them
+const WRITE_CHUNK_ROWS: usize = 8192;
+
+/// Writes each batch's rows of a partition to new, complete Parquet files
+///
+/// A Parquet file is only readable once its footer is written, so files are
+/// finished within the batch: written under a `.tmp` name and renamed into
+/// place. Larger sync batches give fewer, larger files.
+#[derive(Debug)]
+pub struct ParquetSink {
+    root: PathBuf,
+    rotation: FileRotation,
+    next_parts: Mutex<HashMap<PathBuf, u32>>,
+}
+
+impl ParquetSink {
+    #[must_use]
+    pub fn new(root: PathBuf, rotation: FileRotation) -> Self {
+        Self {
+            root,
+            rotation,
+            next_parts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Write `records` to one or more new parts, returning the next free part
+    fn write_parts(
+        &self,
+        dir: &Path,
+        mut part: u32,
+        table: &Table,
+        mut records: &[&Record],
+    ) -> Result<u32> {
+        fs::create_dir_all(dir)?;
+        let schema = arrow_schema(table.columns);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        while !records.is_empty() {
+            let path = part_path(dir, part, EXTENSION);
+            let tmp = path.with_extension("parquet.tmp");
+            let mut writer = ArrowWriter::try_new(
+                File::create(&tmp)?,
+                schema.clone(),
+                Some(properties.clone()),
+            )
+            .map_err(parquet_error)?;
+
+            let mut rows = 0;
+            while !records.is_empty()
+                && rows < self.rotation.max_rows
+                && ((writer.bytes_written() + writer.in_progress_size()) as u64)
+                    < self.rotation.max_bytes
+            {
+                let count = records
+                    .len()
+                    .min(WRITE_CHUNK_ROWS)
+                    .min(self.rotation.max_rows - rows);
+                let cells: Vec<_> = records[..count]
+                    .iter()
+                    .map(|record| record.cells.as_slice())
+                    .collect();
+                writer
+                    .write(&record_batch(&schema, table.columns, &cells)?)
+                    .map_err(parquet_error)?;
+                records = &records[count..];
+                rows += count;
+            }
+
+            writer.close().map_err(parquet_error)?;
+            fs::rename(&tmp, &path)?;
+            part += 1;
+        }
+
+        Ok(part)
+    }
+}
+
+#[async_trait]
+impl SyncSink for ParquetSink {
+    fn name(&self) -> &'static str {
+        "parquet"
+    }
+
+    #[allow(clippy::significant_drop_tightening)] // Partitions must not race for part numbers
+    async fn write(&self, shard_id: u32, batched: Cow<'_, BatchedData>) -> Result<()> {
+        let tables = table_records(shard_id, &batched);
+        let mut next_parts = self
+            .next_parts
+            .lock()
+            .map_err(|_| crate::SnapRagError::Custom("Parquet sink lock poisoned".to_string()))?;
+
+        for table in &tables {
+            for ((shard_id, day), records) in partitions(&table.records) {
+                let dir = partition_dir(&self.root, table.table.name, shard_id, day);
+                let part = match next_parts.get(&dir) {
+                    Some(part) => *part,
+                    None => next_part(&dir, EXTENSION)?,
+                };
+                let next = self.write_parts(&dir, part, table.table, &records)?;
+                next_parts.insert(dir, next);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array;
+    use arrow_array::StringArray;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::models::ShardBlockInfo;
+
+    #[tokio::test]
+    async fn test_writes_readable_rotated_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = ParquetSink::new(
+            dir.path().to_path_buf(),
+            FileRotation {
+                max_rows: 2,
+                max_bytes: u64::MAX,
+            },
+        );
+
+        let mut batched = BatchedData::new();
+        for fid in 1..=3 {
+            batched.casts.push((
+                fid,
+                (fid != 2).then(|| format!("cast {fid}")),
+                0,
+                vec![u8::try_from(fid).unwrap(); 20],
+                None,
+                None,
+                Some(serde_json::json!([])),
+                None,
+                None,
+                None,
+                ShardBlockInfo::new(1, 10, 0, 0),
+            ));
+        }
+        sink.write(1, Cow::Borrowed(&batched)).await.unwrap();
+        sink.write(1, Cow::Owned(batched)).await.unwrap();
+
+        let partition = dir.path().join("casts/shard=1/date=2021-01-01");
+        let mut parts: Vec<_> = fs::read_dir(&partition)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        parts.sort();
+        assert_eq!(
+            parts,
+            vec![
+                "part-00000.parquet",
+                "part-00001.parquet",
+                "part-00002.parquet",
+                "part-00003.parquet"
+            ]
+        );
+
+        let file = File::open(partition.join("part-00000.parquet")).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batch = reader.into_iter().next().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let text = batch
+            .column_by_name("text")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(text.value(0), "cast 1");
+        assert!(text.is_null(1));
+    }
+}
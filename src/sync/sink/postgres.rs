@@ -0,0 +1,35 @@
+//! The `PostgreSQL` sink
+
+use std::borrow::Cow;
+
+use async_trait::async_trait;
+
+use super::SyncSink;
+use crate::database::Database;
+use crate::sync::shard_processor::flush_batched_data;
+use crate::sync::shard_processor::BatchedData;
+use crate::Result;
+
+/// Writes batches to the `PostgreSQL` tables with [`flush_batched_data`]
+#[derive(Debug, Clone)]
+pub struct PostgresSink {
+    database: Database,
+}
+
+impl PostgresSink {
+    #[must_use]
+    pub const fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl SyncSink for PostgresSink {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn write(&self, _shard_id: u32, batched: Cow<'_, BatchedData>) -> Result<()> {
+        flush_batched_data(&self.database, batched.into_owned()).await
+    }
+}
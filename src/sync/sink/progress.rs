@@ -0,0 +1,91 @@
+//! Sync progress of an export-only sync
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::Result;
+
+/// Last processed height per shard, kept in a JSON file next to the exports
+///
+/// Takes the place of the `sync_progress` table when sync runs without
+/// `PostgreSQL`. The file is replaced atomically on every update.
+#[derive(Debug)]
+pub struct ExportProgress {
+    path: PathBuf,
+    heights: Mutex<BTreeMap<u32, u64>>,
+}
+
+impl ExportProgress {
+    /// Load the progress file at `path`; a missing file means no progress
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let heights = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            heights: Mutex::new(heights),
+        })
+    }
+
+    /// Last processed height of a shard, 0 if it was never synced
+    ///
+    /// # Errors
+    /// Returns an error if the progress lock is poisoned
+    pub fn last_processed_height(&self, shard_id: u32) -> Result<u64> {
+        Ok(self
+            .heights
+            .lock()
+            .map_err(|_| crate::SnapRagError::Custom("Export progress lock poisoned".to_string()))?
+            .get(&shard_id)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    /// Record the last processed height of a shard
+    ///
+    /// # Errors
+    /// Returns an error if the progress file cannot be written
+    #[allow(clippy::significant_drop_tightening)] // The lock also orders the file writes
+    pub fn update(&self, shard_id: u32, height: u64) -> Result<()> {
+        let mut heights = self.heights.lock().map_err(|_| {
+            crate::SnapRagError::Custom("Export progress lock poisoned".to_string())
+        })?;
+        heights.insert(shard_id, height);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&*heights)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exports").join("sync_progress.json");
+
+        let progress = ExportProgress::open(path.clone()).unwrap();
+        assert_eq!(progress.last_processed_height(1).unwrap(), 0);
+        progress.update(1, 500).unwrap();
+        progress.update(2, 7).unwrap();
+
+        let reopened = ExportProgress::open(path).unwrap();
+        assert_eq!(reopened.last_processed_height(1).unwrap(), 500);
+        assert_eq!(reopened.last_processed_height(2).unwrap(), 7);
+    }
+}
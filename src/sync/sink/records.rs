@@ -0,0 +1,733 @@
+//! Batched rows as flat per-table records for the file sinks
+//!
+//! Every table of the raw stream has a fixed column list. Message tables end
+//! with the `shard_id` and `block_height` the message was synced from.
+//! Records are partitioned by shard and by the UTC day of their timestamp:
+//! the Farcaster timestamp for messages, the block timestamp for onchain
+//! events.
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+
+use crate::export::columns::bytes;
+use crate::export::columns::int;
+use crate::export::columns::json;
+use crate::export::columns::text;
+use crate::export::columns::Cell;
+use crate::export::columns::Column;
+use crate::models::ShardBlockInfo;
+use crate::sync::shard_processor::BatchedData;
+
+/// An exported table
+#[derive(Debug, PartialEq, Eq)]
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+}
+
+pub static CASTS: Table = Table {
+    name: "casts",
+    columns: &[
+        int("fid"),
+        text("text"),
+        int("timestamp"),
+        bytes("message_hash"),
+        bytes("parent_hash"),
+        bytes("root_hash"),
+        json("embeds"),
+        json("mentions"),
+        int("parent_fid"),
+        text("parent_url"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static CAST_REMOVES: Table = Table {
+    name: "cast_removes",
+    columns: &[
+        int("fid"),
+        bytes("target_hash"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static LINKS: Table = Table {
+    name: "links",
+    columns: &[
+        int("fid"),
+        int("target_fid"),
+        text("link_type"),
+        text("event_type"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static LINK_COMPACT_STATES: Table = Table {
+    name: "link_compact_states",
+    columns: &[
+        int("fid"),
+        text("link_type"),
+        json("target_fids"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static REACTIONS: Table = Table {
+    name: "reactions",
+    columns: &[
+        int("fid"),
+        bytes("target_cast_hash"),
+        int("target_fid"),
+        int("reaction_type"),
+        text("event_type"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static VERIFICATIONS: Table = Table {
+    name: "verifications",
+    columns: &[
+        int("fid"),
+        bytes("address"),
+        bytes("claim_signature"),
+        bytes("block_hash"),
+        int("verification_type"),
+        int("chain_id"),
+        text("event_type"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static USER_PROFILE_CHANGES: Table = Table {
+    name: "user_profile_changes",
+    columns: &[
+        int("fid"),
+        text("field_name"),
+        text("field_value"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static USERNAME_PROOFS: Table = Table {
+    name: "username_proofs",
+    columns: &[
+        int("fid"),
+        text("username"),
+        bytes("owner"),
+        bytes("signature"),
+        int("username_type"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static FRAME_ACTIONS: Table = Table {
+    name: "frame_actions",
+    columns: &[
+        int("fid"),
+        text("url"),
+        int("button_index"),
+        bytes("cast_hash"),
+        int("cast_fid"),
+        text("input_text"),
+        bytes("state"),
+        bytes("transaction_id"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static STORAGE_LENDS: Table = Table {
+    name: "storage_lends",
+    columns: &[
+        int("fid"),
+        int("to_fid"),
+        int("num_units"),
+        int("unit_type"),
+        int("timestamp"),
+        bytes("message_hash"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static REJECTED_MESSAGES: Table = Table {
+    name: "rejected_messages",
+    columns: &[
+        bytes("message_hash"),
+        int("fid"),
+        int("message_type"),
+        bytes("signer"),
+        text("reason"),
+        json("message"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+pub static ONCHAIN_EVENTS: Table = Table {
+    name: "onchain_events",
+    columns: &[
+        int("fid"),
+        int("event_type"),
+        int("chain_id"),
+        int("block_number"),
+        bytes("block_hash"),
+        int("block_timestamp"),
+        bytes("transaction_hash"),
+        int("log_index"),
+        json("event_data"),
+    ],
+};
+
+pub static SIGNER_EVENTS: Table = Table {
+    name: "signer_events",
+    columns: &[
+        int("fid"),
+        bytes("key"),
+        int("key_type"),
+        int("event_type"),
+        bytes("metadata"),
+        int("block_number"),
+        int("log_index"),
+        int("block_timestamp"),
+    ],
+};
+
+pub static ID_REGISTER_EVENTS: Table = Table {
+    name: "id_register_events",
+    columns: &[
+        int("fid"),
+        int("event_type"),
+        bytes("to"),
+        bytes("from"),
+        bytes("recovery_address"),
+        int("block_number"),
+        int("log_index"),
+        int("block_timestamp"),
+    ],
+};
+
+pub static STORAGE_RENTS: Table = Table {
+    name: "storage_rents",
+    columns: &[
+        int("fid"),
+        bytes("payer"),
+        int("units"),
+        int("expiry"),
+        int("block_number"),
+        int("log_index"),
+        int("block_timestamp"),
+        bytes("transaction_hash"),
+    ],
+};
+
+pub static FNAME_TRANSFERS: Table = Table {
+    name: "fname_transfers",
+    columns: &[
+        int("transfer_id"),
+        int("from_fid"),
+        int("to_fid"),
+        text("username"),
+        bytes("owner"),
+        bytes("signature"),
+        int("timestamp"),
+        int("shard_id"),
+        int("block_height"),
+    ],
+};
+
+/// One exported row with the partition it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub shard_id: u32,
+    pub day: NaiveDate,
+    /// One cell per column of the table
+    pub cells: Vec<Cell>,
+}
+
+/// Records of one table
+#[derive(Debug)]
+pub struct TableRecords {
+    pub table: &'static Table,
+    pub records: Vec<Record>,
+}
+
+impl TableRecords {
+    const fn new(table: &'static Table) -> Self {
+        Self {
+            table,
+            records: Vec::new(),
+        }
+    }
+
+    /// Add a row partitioned by a Unix timestamp
+    fn push(&mut self, shard_id: u32, unix_timestamp: i64, cells: Vec<Cell>) {
+        debug_assert_eq!(cells.len(), self.table.columns.len());
+        self.records.push(Record {
+            shard_id,
+            day: utc_day(unix_timestamp),
+            cells,
+        });
+    }
+
+    /// Add a message row, appending its shard and block height
+    fn push_message(&mut self, info: &ShardBlockInfo, timestamp: i64, mut cells: Vec<Cell>) {
+        cells.push(Cell::Int(i64::from(info.shard_id)));
+        cells.push(Cell::Int(
+            i64::try_from(info.block_height).unwrap_or(i64::MAX),
+        ));
+        self.push(info.shard_id, farcaster_to_unix(timestamp), cells);
+    }
+}
+
+fn farcaster_to_unix(timestamp: i64) -> i64 {
+    timestamp.saturating_add(i64::try_from(crate::FARCASTER_EPOCH / 1000).unwrap_or_default())
+}
+
+/// UTC day of a Unix timestamp
+fn utc_day(unix_timestamp: i64) -> NaiveDate {
+    DateTime::from_timestamp(unix_timestamp, 0).map_or(NaiveDate::MIN, |time| time.date_naive())
+}
+
+/// Records of every non-empty table in a batch synced from `shard_id`
+#[must_use]
+pub fn table_records(shard_id: u32, batched: &BatchedData) -> Vec<TableRecords> {
+    let mut tables = message_records(shard_id, batched);
+    tables.extend(onchain_records(shard_id, batched));
+    tables.retain(|table| !table.records.is_empty());
+    tables
+}
+
+fn message_records(shard_id: u32, batched: &BatchedData) -> Vec<TableRecords> {
+    let mut casts = TableRecords::new(&CASTS);
+    for (
+        fid,
+        text,
+        timestamp,
+        hash,
+        parent_hash,
+        root_hash,
+        embeds,
+        mentions,
+        parent_fid,
+        parent_url,
+        info,
+    ) in &batched.casts
+    {
+        casts.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                text.clone().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+                parent_hash.clone().into(),
+                root_hash.clone().into(),
+                embeds.clone().into(),
+                mentions.clone().into(),
+                (*parent_fid).into(),
+                parent_url.clone().into(),
+            ],
+        );
+    }
+
+    let mut cast_removes = TableRecords::new(&CAST_REMOVES);
+    for (fid, target_hash, timestamp, hash, info) in &batched.cast_removes {
+        cast_removes.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                target_hash.clone().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut links = TableRecords::new(&LINKS);
+    for (fid, target_fid, link_type, event_type, timestamp, hash, info) in &batched.links {
+        links.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                (*target_fid).into(),
+                link_type.as_str().into(),
+                event_type.as_str().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut link_compact_states = TableRecords::new(&LINK_COMPACT_STATES);
+    for (fid, link_type, target_fids, timestamp, hash, info) in &batched.link_compact_states {
+        link_compact_states.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                link_type.as_str().into(),
+                serde_json::json!(target_fids).into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut reactions = TableRecords::new(&REACTIONS);
+    for (fid, target_hash, target_fid, reaction_type, event_type, timestamp, hash, info) in
+        &batched.reactions
+    {
+        reactions.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                target_hash.clone().into(),
+                (*target_fid).into(),
+                (*reaction_type).into(),
+                event_type.as_str().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut tables = vec![casts, cast_removes, links, link_compact_states, reactions];
+    tables.extend(account_records(shard_id, batched));
+    tables
+}
+
+/// Verifications, profile changes and username proofs
+fn account_records(shard_id: u32, batched: &BatchedData) -> Vec<TableRecords> {
+    let mut verifications = TableRecords::new(&VERIFICATIONS);
+    for (
+        fid,
+        address,
+        claim_signature,
+        block_hash,
+        verification_type,
+        chain_id,
+        event_type,
+        timestamp,
+        hash,
+        info,
+    ) in &batched.verifications
+    {
+        verifications.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                address.clone().into(),
+                claim_signature.clone().into(),
+                block_hash.clone().into(),
+                (*verification_type).into(),
+                (*chain_id).into(),
+                event_type.as_str().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut profile_changes = TableRecords::new(&USER_PROFILE_CHANGES);
+    for (fid, field, value, timestamp, hash, info) in &batched.profile_updates {
+        profile_changes.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                field.as_str().into(),
+                value.clone().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut username_proofs = TableRecords::new(&USERNAME_PROOFS);
+    for (fid, username, owner, signature, username_type, timestamp, hash, info) in
+        &batched.username_proofs
+    {
+        username_proofs.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                username.as_str().into(),
+                owner.clone().into(),
+                signature.clone().into(),
+                (*username_type).into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut tables = vec![verifications, profile_changes, username_proofs];
+    tables.extend(rare_message_records(batched));
+    tables
+}
+
+/// Frame actions, storage lends and rejected messages
+fn rare_message_records(batched: &BatchedData) -> Vec<TableRecords> {
+    let mut frame_actions = TableRecords::new(&FRAME_ACTIONS);
+    for (
+        fid,
+        url,
+        button_index,
+        cast_hash,
+        cast_fid,
+        input_text,
+        state,
+        transaction_id,
+        timestamp,
+        hash,
+        info,
+    ) in &batched.frame_actions
+    {
+        frame_actions.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                url.as_str().into(),
+                (*button_index).into(),
+                cast_hash.clone().into(),
+                (*cast_fid).into(),
+                input_text.clone().into(),
+                state.clone().into(),
+                transaction_id.clone().into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut storage_lends = TableRecords::new(&STORAGE_LENDS);
+    for (fid, to_fid, num_units, unit_type, timestamp, hash, info) in &batched.storage_lends {
+        storage_lends.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*fid).into(),
+                (*to_fid).into(),
+                (*num_units).into(),
+                (*unit_type).into(),
+                (*timestamp).into(),
+                hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut rejected = TableRecords::new(&REJECTED_MESSAGES);
+    for (hash, fid, message_type, signer, reason, message, info) in &batched.rejected_messages {
+        let timestamp = i64::try_from(info.timestamp).unwrap_or(i64::MAX);
+        rejected.push_message(
+            info,
+            timestamp,
+            vec![
+                hash.clone().into(),
+                (*fid).into(),
+                (*message_type).into(),
+                signer.clone().into(),
+                (*reason).into(),
+                message.clone().into(),
+            ],
+        );
+    }
+
+    vec![frame_actions, storage_lends, rejected]
+}
+
+/// Onchain events and signer events
+fn onchain_records(shard_id: u32, batched: &BatchedData) -> Vec<TableRecords> {
+    let mut onchain_events = TableRecords::new(&ONCHAIN_EVENTS);
+    for (
+        fid,
+        event_type,
+        chain_id,
+        block_number,
+        block_hash,
+        block_timestamp,
+        tx_hash,
+        log_index,
+        event_data,
+        _shard_block_info,
+    ) in &batched.onchain_events
+    {
+        onchain_events.push(
+            shard_id,
+            *block_timestamp,
+            vec![
+                (*fid).into(),
+                (*event_type).into(),
+                (*chain_id).into(),
+                (*block_number).into(),
+                block_hash.clone().into(),
+                (*block_timestamp).into(),
+                tx_hash.clone().into(),
+                (*log_index).into(),
+                event_data.clone().into(),
+            ],
+        );
+    }
+
+    let mut signer_events = TableRecords::new(&SIGNER_EVENTS);
+    for (fid, key, key_type, event_type, metadata, block_number, log_index, block_timestamp, _) in
+        &batched.signer_events
+    {
+        signer_events.push(
+            shard_id,
+            *block_timestamp,
+            vec![
+                (*fid).into(),
+                key.clone().into(),
+                (*key_type).into(),
+                (*event_type).into(),
+                metadata.clone().into(),
+                (*block_number).into(),
+                (*log_index).into(),
+                (*block_timestamp).into(),
+            ],
+        );
+    }
+
+    let mut tables = vec![onchain_events, signer_events];
+    tables.extend(registry_records(shard_id, batched));
+    tables
+}
+
+/// FID registrations, storage rents and fname transfers
+fn registry_records(shard_id: u32, batched: &BatchedData) -> Vec<TableRecords> {
+    let mut id_register_events = TableRecords::new(&ID_REGISTER_EVENTS);
+    for (fid, event_type, to, from, recovery, block_number, log_index, block_timestamp, _) in
+        &batched.id_register_events
+    {
+        id_register_events.push(
+            shard_id,
+            *block_timestamp,
+            vec![
+                (*fid).into(),
+                (*event_type).into(),
+                to.clone().into(),
+                from.clone().into(),
+                recovery.clone().into(),
+                (*block_number).into(),
+                (*log_index).into(),
+                (*block_timestamp).into(),
+            ],
+        );
+    }
+
+    let mut storage_rents = TableRecords::new(&STORAGE_RENTS);
+    for (fid, payer, units, expiry, block_number, log_index, block_timestamp, tx_hash, _) in
+        &batched.storage_rents
+    {
+        storage_rents.push(
+            shard_id,
+            *block_timestamp,
+            vec![
+                (*fid).into(),
+                payer.clone().into(),
+                (*units).into(),
+                (*expiry).into(),
+                (*block_number).into(),
+                (*log_index).into(),
+                (*block_timestamp).into(),
+                tx_hash.clone().into(),
+            ],
+        );
+    }
+
+    let mut fname_transfers = TableRecords::new(&FNAME_TRANSFERS);
+    for (transfer_id, from_fid, to_fid, username, owner, signature, timestamp, info) in
+        &batched.fname_transfers
+    {
+        fname_transfers.push_message(
+            info,
+            *timestamp,
+            vec![
+                (*transfer_id).into(),
+                (*from_fid).into(),
+                (*to_fid).into(),
+                username.as_str().into(),
+                owner.clone().into(),
+                signature.clone().into(),
+                (*timestamp).into(),
+            ],
+        );
+    }
+
+    vec![id_register_events, storage_rents, fname_transfers]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_records_partition_and_columns() {
+        let info = ShardBlockInfo::new(2, 40, 7, 0);
+        let mut batched = BatchedData::new();
+        batched.casts.push((
+            7,
+            Some("gm".to_string()),
+            0, // Farcaster epoch: 2021-01-01
+            vec![1; 20],
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some("https://warpcast.com/~/channel/base".to_string()),
+            info.clone(),
+        ));
+        batched
+            .storage_rents
+            .push((7, None, 1, 0, 100, 0, 1_700_000_000, None, info));
+
+        let tables = table_records(2, &batched);
+        let names: Vec<_> = tables.iter().map(|table| table.table.name).collect();
+        assert_eq!(names, vec!["casts", "storage_rents"]);
+
+        let cast = &tables[0].records[0];
+        assert_eq!(cast.shard_id, 2);
+        assert_eq!(cast.day, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        assert_eq!(cast.cells.len(), CASTS.columns.len());
+        assert_eq!(cast.cells[1], Cell::Text("gm".to_string()));
+        assert_eq!(cast.cells[4], Cell::Null);
+        assert_eq!(cast.cells[11], Cell::Int(40));
+
+        let rent = &tables[1].records[0];
+        assert_eq!(rent.day, NaiveDate::from_ymd_opt(2023, 11, 14).unwrap());
+    }
+}
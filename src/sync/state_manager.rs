@@ -20,6 +20,9 @@ pub struct PersistentSyncState {
     pub status: String,
     /// Last processed heights for each shard
     pub last_processed_heights: HashMap<u32, u64>,
+    /// Last hub event id applied per shard (event-stream mode)
+    #[serde(default)]
+    pub last_event_ids: HashMap<u32, u64>,
     /// Total messages processed per shard
     pub total_messages_processed: HashMap<u32, u64>,
     /// Total blocks processed per shard
@@ -39,6 +42,7 @@ impl Default for PersistentSyncState {
                 .as_secs(),
             status: "NotStarted".to_string(),
             last_processed_heights: HashMap::new(),
+            last_event_ids: HashMap::new(),
             total_messages_processed: HashMap::new(),
             total_blocks_processed: HashMap::new(),
             last_sync_timestamp: None,
@@ -129,6 +133,18 @@ impl SyncStateManager {
             .unwrap_or(0)
     }
 
+    /// Update last applied hub event id for a shard
+    pub fn update_last_event_id(&mut self, shard_id: u32, event_id: u64) -> Result<()> {
+        self.state.last_event_ids.insert(shard_id, event_id);
+        self.save()
+    }
+
+    /// Get last applied hub event id for a shard
+    #[must_use]
+    pub fn get_last_event_id(&self, shard_id: u32) -> Option<u64> {
+        self.state.last_event_ids.get(&shard_id).copied()
+    }
+
     /// Increment processed messages for a shard
     pub fn increment_messages_processed(&mut self, shard_id: u32, count: u64) -> Result<()> {
         let current = self
@@ -247,6 +263,11 @@ mod tests {
         manager.update_last_processed_height(0, 100).unwrap();
         assert_eq!(manager.get_last_processed_height(0), 100);
 
+        // Test event id tracking
+        assert_eq!(manager.get_last_event_id(1), None);
+        manager.update_last_event_id(1, 4242).unwrap();
+        assert_eq!(manager.get_last_event_id(1), Some(4242));
+
         // Test incrementing counters
         manager.increment_messages_processed(0, 50).unwrap();
         manager.increment_blocks_processed(0, 10).unwrap();
@@ -5,6 +5,8 @@ use std::collections::HashMap;
 use serde::Deserialize;
 use serde::Serialize;
 
+pub use crate::config::RealtimeSyncMode;
+
 /// Sync configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
@@ -28,6 +30,10 @@ pub struct SyncConfig {
     pub enable_continuous_sync: bool,
     /// Continuous sync interval in seconds (how often to poll for new blocks)
     pub continuous_sync_interval_secs: u64,
+    /// How real-time sync receives new data
+    pub realtime_sync_mode: RealtimeSyncMode,
+    /// Event id to start streaming from when no event id has been persisted (0 = live tail)
+    pub from_event_id: u64,
 }
 
 impl Default for SyncConfig {
@@ -43,6 +49,8 @@ impl Default for SyncConfig {
             sync_interval_ms: 1000,
             enable_continuous_sync: true,
             continuous_sync_interval_secs: 5,
+            realtime_sync_mode: RealtimeSyncMode::Poll,
+            from_event_id: 0,
         }
     }
 }
@@ -62,6 +70,8 @@ impl SyncConfig {
             sync_interval_ms: app_config.sync_interval_ms(),
             enable_continuous_sync: app_config.continuous_sync_enabled(),
             continuous_sync_interval_secs: app_config.continuous_sync_interval_secs(),
+            realtime_sync_mode: app_config.realtime_sync_mode(),
+            from_event_id: app_config.historical_sync_from_event_id(),
         }
     }
 }
@@ -57,6 +57,8 @@ mod tests {
             sync_interval_ms: 1000,
             enable_continuous_sync: true,
             continuous_sync_interval_secs: 5,
+            realtime_sync_mode: RealtimeSyncMode::Poll,
+            from_event_id: 0,
         };
 
         assert_eq!(config.shard_ids.len(), 3);
@@ -309,3 +309,231 @@ async fn test_large_batch_insert() {
 
     println!("✅ Large batch insert test passed");
 }
+
+// FIDs of the flush strategy fixtures, one per strategy so runs don't collide
+const INSERT_FIXTURE_FID: i64 = 9_900_001;
+const COPY_FIXTURE_FID: i64 = 9_900_002;
+
+/// `rows` of each high-volume message type for `fid`, with a duplicate of every
+/// link to exercise `ON CONFLICT DO NOTHING`
+fn flush_fixture(fid: i64, rows: u32) -> BatchedData {
+    let shard_info = test_shard_info();
+    let seed = u32::try_from(fid % 100).unwrap_or(0) * 10_000_000;
+    let mut batched = BatchedData::new();
+
+    for i in 0..rows {
+        let timestamp = 1_698_765_432 + i64::from(i);
+        let cast_hash = test_message_hash(seed + i);
+        batched.casts.push((
+            fid,
+            Some(format!("copy fixture cast {i}")),
+            timestamp,
+            cast_hash.clone(),
+            None,
+            None,
+            Some(serde_json::json!([{ "url": "https://example.com" }])),
+            None,
+            None,
+            Some("https://warpcast.com/~/channel/test".to_string()),
+            shard_info.clone(),
+        ));
+        let link = (
+            fid,
+            i64::from(i),
+            "follow".to_string(),
+            "add".to_string(),
+            timestamp,
+            test_message_hash(seed + 1_000_000 + i),
+            shard_info.clone(),
+        );
+        batched.links.push(link.clone());
+        batched.links.push(link);
+        batched.reactions.push((
+            fid,
+            cast_hash.clone(),
+            Some(fid),
+            1,
+            "add".to_string(),
+            timestamp,
+            test_message_hash(seed + 2_000_000 + i),
+            shard_info.clone(),
+        ));
+        batched.verifications.push((
+            fid,
+            vec![0x11; 20],
+            Some(vec![0x22; 65]),
+            None,
+            Some(0),
+            Some(1),
+            "add".to_string(),
+            timestamp,
+            test_message_hash(seed + 3_000_000 + i),
+            shard_info.clone(),
+        ));
+        batched.profile_updates.push((
+            fid,
+            "bio".to_string(),
+            (i % 2 == 0).then(|| format!("bio {i}")),
+            timestamp,
+            test_message_hash(seed + 4_000_000 + i),
+            shard_info.clone(),
+        ));
+        if i % 10 == 0 {
+            batched.cast_removes.push((
+                fid,
+                cast_hash,
+                timestamp + 1,
+                test_message_hash(seed + 5_000_000 + i),
+                shard_info.clone(),
+            ));
+        }
+    }
+
+    batched
+}
+
+const FIXTURE_TABLES: [&str; 6] = [
+    "casts",
+    "cast_removes",
+    "links",
+    "reactions",
+    "verifications",
+    "user_profile_changes",
+];
+
+async fn fixture_row_counts(db: &Database, fid: i64) -> Vec<i64> {
+    let mut counts = Vec::new();
+    for table in FIXTURE_TABLES {
+        let count: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE fid = $1"))
+                .bind(fid)
+                .fetch_one(db.pool())
+                .await
+                .expect("Failed to count fixture rows");
+        counts.push(count);
+    }
+    counts
+}
+
+async fn cleanup_fixture(db: &Database, fid: i64) {
+    for table in FIXTURE_TABLES {
+        sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+            .bind(fid)
+            .execute(db.pool())
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+#[ignore = "Requires database access - production database should not be modified"]
+async fn test_copy_flush_matches_insert_flush() {
+    use crate::config::FlushStrategy;
+
+    let db = setup_test_db().await;
+    cleanup_fixture(&db, INSERT_FIXTURE_FID).await;
+    cleanup_fixture(&db, COPY_FIXTURE_FID).await;
+
+    let insert_db = db.clone().with_flush_strategy(FlushStrategy::Insert);
+    let copy_db = db.clone().with_flush_strategy(FlushStrategy::Copy);
+    flush_batched_data(&insert_db, flush_fixture(INSERT_FIXTURE_FID, 50))
+        .await
+        .expect("INSERT flush should succeed");
+    flush_batched_data(&copy_db, flush_fixture(COPY_FIXTURE_FID, 50))
+        .await
+        .expect("COPY flush should succeed");
+
+    let expected = vec![50, 5, 50, 50, 50, 50];
+    assert_eq!(fixture_row_counts(&db, INSERT_FIXTURE_FID).await, expected);
+    assert_eq!(fixture_row_counts(&db, COPY_FIXTURE_FID).await, expected);
+
+    // Re-flushing is a no-op on both paths, and COPY reuses its staging tables
+    flush_batched_data(&copy_db, flush_fixture(COPY_FIXTURE_FID, 50))
+        .await
+        .expect("COPY re-flush should succeed");
+    assert_eq!(fixture_row_counts(&db, COPY_FIXTURE_FID).await, expected);
+
+    // Values survive the binary encoding, including NULLs and JSONB
+    let cast: (Option<String>, Option<serde_json::Value>, Option<i64>, Option<i32>) =
+        sqlx::query_as(
+            "SELECT text, embeds, parent_fid, shard_id FROM casts WHERE fid = $1 ORDER BY timestamp LIMIT 1",
+        )
+        .bind(COPY_FIXTURE_FID)
+        .fetch_one(db.pool())
+        .await
+        .expect("Failed to query cast");
+    assert_eq!(cast.0.as_deref(), Some("copy fixture cast 0"));
+    assert_eq!(
+        cast.1,
+        Some(serde_json::json!([{ "url": "https://example.com" }]))
+    );
+    assert_eq!(cast.2, None);
+    assert_eq!(cast.3, Some(1));
+
+    let bios: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM user_profile_changes WHERE fid = $1 AND field_value IS NULL",
+    )
+    .bind(COPY_FIXTURE_FID)
+    .fetch_one(db.pool())
+    .await
+    .expect("Failed to count profile changes");
+    assert_eq!(bios, 25);
+
+    // Both paths write the same columns, down to the transaction FID
+    for fid in [INSERT_FIXTURE_FID, COPY_FIXTURE_FID] {
+        let transaction_fids: Vec<Option<i64>> = sqlx::query_scalar(
+            "SELECT DISTINCT transaction_fid FROM casts WHERE fid = $1 \
+             UNION SELECT DISTINCT transaction_fid FROM user_profile_changes WHERE fid = $1 AND field_name = 'bio'",
+        )
+        .bind(fid)
+        .fetch_all(db.pool())
+        .await
+        .expect("Failed to query transaction FIDs");
+        assert_eq!(transaction_fids, vec![Some(99)]);
+    }
+
+    cleanup_fixture(&db, INSERT_FIXTURE_FID).await;
+    cleanup_fixture(&db, COPY_FIXTURE_FID).await;
+}
+
+/// Benchmark of the two flush strategies on the same fixture
+///
+/// Run with `cargo test --lib bench_flush_strategies -- --ignored --nocapture`;
+/// `SNAPRAG_BENCH_ROWS` sets the rows per message type (default 5000).
+#[tokio::test]
+#[ignore = "Requires database access - production database should not be modified"]
+async fn bench_flush_strategies() {
+    use std::time::Instant;
+
+    use crate::config::FlushStrategy;
+
+    let db = setup_test_db().await;
+    let rows: u32 = std::env::var("SNAPRAG_BENCH_ROWS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(5000);
+
+    println!("📊 Flushing {rows} rows per message type with each strategy...");
+
+    for (strategy, fid) in [
+        (FlushStrategy::Insert, INSERT_FIXTURE_FID),
+        (FlushStrategy::Copy, COPY_FIXTURE_FID),
+    ] {
+        cleanup_fixture(&db, fid).await;
+        let strategy_db = db.clone().with_flush_strategy(strategy);
+        let batched = flush_fixture(fid, rows);
+
+        let start = Instant::now();
+        flush_batched_data(&strategy_db, batched)
+            .await
+            .expect("Flush should succeed");
+        let elapsed = start.elapsed();
+
+        let written: i64 = fixture_row_counts(&db, fid).await.iter().sum();
+        #[allow(clippy::cast_precision_loss)] // Row counts stay far below 2^52
+        let rows_per_sec = written as f64 / elapsed.as_secs_f64();
+        println!("   {strategy:?}: {written} rows in {elapsed:?} ({rows_per_sec:.0} rows/s)");
+
+        cleanup_fixture(&db, fid).await;
+    }
+}
@@ -0,0 +1,67 @@
+/// Tests for versioned embedding models (shadow columns and cutover)
+///
+/// Registers a throwaway shadow model next to the active one, checks the
+/// coverage gate of cutover and prunes the shadow again.
+
+#[cfg(test)]
+mod embedding_versions_tests {
+    use crate::database::CAST_EMBEDDING_COLUMN;
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_shadow_version_lifecycle() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let active = db
+            .register_base_embedding_version(config.embedding_model(), config.embedding_dimension())
+            .await
+            .unwrap();
+        assert!(active.is_active());
+        if db.shadow_embedding_version().await.unwrap().is_some() {
+            // A real model migration is in progress, leave it alone
+            return;
+        }
+
+        let shadow = db
+            .add_shadow_embedding_version("snaprag-test-model", 8)
+            .await
+            .unwrap();
+        assert!(shadow.is_shadow());
+        assert_eq!(
+            shadow.column(CAST_EMBEDDING_COLUMN),
+            format!("embedding_v{}", shadow.version)
+        );
+        assert!(
+            db.add_shadow_embedding_version("another-model", 8)
+                .await
+                .is_err(),
+            "only one shadow version at a time"
+        );
+
+        // Wrong-sized vectors are rejected for the shadow columns
+        assert!(db
+            .store_versioned_cast_embedding(&shadow, &[0xFE, 0xED], &[0.0; 4])
+            .await
+            .is_err());
+
+        let coverage = db.embedding_coverage(&shadow).await.unwrap();
+        assert!(coverage.casts_covered <= coverage.casts_total);
+        assert!(coverage.ratio() <= 1.0);
+
+        // Coverage can never exceed 100%, so cutover must refuse and change nothing
+        assert!(db.cutover_embedding_version(1.01).await.is_err());
+        let still_active = db.active_embedding_version().await.unwrap().unwrap();
+        assert_eq!(still_active.version, active.version);
+
+        assert!(db.prune_embedding_version(active.version).await.is_err());
+        let pruned = db.prune_embedding_version(shadow.version).await.unwrap();
+        assert!(pruned.pruned_at.is_some());
+        assert!(db.shadow_embedding_version().await.unwrap().is_none());
+    }
+}
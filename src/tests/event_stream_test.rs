@@ -0,0 +1,388 @@
+/// Tests for the event-stream (`Subscribe`) real-time sync
+///
+/// A local mock `HubService` that only implements `Subscribe` replays a fixed
+/// list of hub events, so the streaming path can be exercised without a node.
+
+#[cfg(test)]
+#[allow(clippy::unreadable_literal)] // Test data with large numbers is acceptable
+mod event_stream_tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use futures::StreamExt;
+    use tonic::codegen::http;
+    use tonic::codegen::BoxFuture;
+    use tonic::codegen::Context;
+    use tonic::codegen::Poll;
+    use tonic::codegen::Service;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::Status;
+
+    use crate::database::HeightRange;
+    use crate::generated::grpc_client::hub_event;
+    use crate::generated::grpc_client::message_data;
+    use crate::generated::grpc_client::BlockConfirmedBody;
+    use crate::generated::grpc_client::CastAddBody;
+    use crate::generated::grpc_client::HubEvent;
+    use crate::generated::grpc_client::HubEventType;
+    use crate::generated::grpc_client::MergeMessageBody;
+    use crate::generated::grpc_client::MergeOnChainEventBody;
+    use crate::generated::grpc_client::Message;
+    use crate::generated::grpc_client::MessageData;
+    use crate::generated::grpc_client::MessageType;
+    use crate::generated::grpc_client::OnChainEvent;
+    use crate::generated::grpc_client::SubscribeRequest;
+    use crate::sync::client::SnapchainClient;
+    use crate::sync::service::HubEventBatch;
+    use crate::sync::service::RealtimeMonitor;
+    use crate::sync::state_manager::SyncStateManager;
+    use crate::sync::types::RealtimeSyncMode;
+    use crate::sync::types::SyncConfig;
+
+    const TEST_FID: u64 = 99_000_002;
+
+    /// Mock hub that serves `Subscribe` from a fixed event list and records requests
+    #[derive(Clone, Default)]
+    struct MockHub {
+        events: Arc<Vec<HubEvent>>,
+        requests: Arc<Mutex<Vec<SubscribeRequest>>>,
+    }
+
+    impl tonic::server::ServerStreamingService<SubscribeRequest> for MockHub {
+        type Response = HubEvent;
+        type ResponseStream = futures::stream::Iter<std::vec::IntoIter<Result<HubEvent, Status>>>;
+        type Future = BoxFuture<tonic::Response<Self::ResponseStream>, Status>;
+
+        fn call(&mut self, request: tonic::Request<SubscribeRequest>) -> Self::Future {
+            let request = request.into_inner();
+            let from_id = request.from_id.unwrap_or(0);
+            let shard_index = request.shard_index;
+            self.requests.lock().unwrap().push(request);
+
+            let events: Vec<_> = self
+                .events
+                .iter()
+                .filter(|event| event.id >= from_id)
+                .filter(|event| shard_index.map_or(true, |shard| event.shard_index == shard))
+                .cloned()
+                .map(Ok)
+                .collect();
+
+            Box::pin(async move { Ok(tonic::Response::new(futures::stream::iter(events))) })
+        }
+    }
+
+    impl Service<http::Request<tonic::transport::Body>> for MockHub {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<tonic::transport::Body>) -> Self::Future {
+            let hub = self.clone();
+            Box::pin(async move {
+                if req.uri().path() == "/HubService/Subscribe" {
+                    let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                    Ok(grpc.server_streaming(hub, req).await)
+                } else {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12") // UNIMPLEMENTED
+                        .header("content-type", "application/grpc")
+                        .body(tonic::codegen::empty_body())
+                        .unwrap())
+                }
+            })
+        }
+    }
+
+    impl tonic::server::NamedService for MockHub {
+        const NAME: &'static str = "HubService";
+    }
+
+    /// Start the mock hub on an ephemeral port and return its address
+    async fn start_mock_hub(hub: MockHub) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(hub)
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        });
+
+        addr
+    }
+
+    async fn connect(addr: SocketAddr) -> SnapchainClient {
+        let endpoint = format!("http://{addr}");
+        SnapchainClient::new(&endpoint, &endpoint).await.unwrap()
+    }
+
+    fn cast_message(hash: Vec<u8>, text: &str) -> Message {
+        Message {
+            data: Some(MessageData {
+                r#type: MessageType::CastAdd as i32,
+                fid: TEST_FID,
+                timestamp: 120_000_000,
+                body: Some(message_data::Body::CastAddBody(CastAddBody {
+                    text: text.to_string(),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }),
+            hash,
+            ..Default::default()
+        }
+    }
+
+    fn merge_message_event(id: u64, block_number: u64, message: Message) -> HubEvent {
+        HubEvent {
+            r#type: HubEventType::MergeMessage as i32,
+            id,
+            block_number,
+            shard_index: 1,
+            timestamp: 120_000_000,
+            body: Some(hub_event::Body::MergeMessageBody(MergeMessageBody {
+                message: Some(message),
+                deleted_messages: vec![],
+            })),
+        }
+    }
+
+    fn on_chain_event(id: u64, block_number: u64) -> HubEvent {
+        HubEvent {
+            r#type: HubEventType::MergeOnChainEvent as i32,
+            id,
+            block_number,
+            shard_index: 1,
+            timestamp: 120_000_000,
+            body: Some(hub_event::Body::MergeOnChainEventBody(
+                MergeOnChainEventBody {
+                    on_chain_event: Some(OnChainEvent {
+                        fid: TEST_FID,
+                        block_number: 130_000_000,
+                        ..Default::default()
+                    }),
+                },
+            )),
+        }
+    }
+
+    fn block_confirmed_event(id: u64, block_number: u64, block_hash: Vec<u8>) -> HubEvent {
+        HubEvent {
+            r#type: HubEventType::BlockConfirmed as i32,
+            id,
+            block_number,
+            shard_index: 1,
+            timestamp: 120_000_000,
+            body: Some(hub_event::Body::BlockConfirmedBody(BlockConfirmedBody {
+                block_number,
+                shard_index: 1,
+                block_hash,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Two blocks: block 500 has a cast, an on-chain event and its confirmation,
+    /// block 501 has a second cast
+    fn sample_events(hash_prefix: u8) -> Vec<HubEvent> {
+        vec![
+            merge_message_event(
+                10,
+                500,
+                cast_message(vec![0xFE, 0xFE, hash_prefix, 1], "one"),
+            ),
+            on_chain_event(11, 500),
+            block_confirmed_event(12, 500, vec![0xAB; 4]),
+            merge_message_event(
+                13,
+                501,
+                cast_message(vec![0xFE, 0xFE, hash_prefix, 2], "two"),
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_converts_hub_events() {
+        let hub = MockHub {
+            events: Arc::new(sample_events(0x20)),
+            ..Default::default()
+        };
+        let requests = hub.requests.clone();
+        let client = connect(start_mock_hub(hub).await).await;
+
+        let events: Vec<_> = client
+            .subscribe(1, Some(11))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+
+        // Resume point is honoured by the server
+        assert_eq!(
+            events.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![11, 12, 13]
+        );
+        assert!(events[0].on_chain_event.is_some());
+        assert_eq!(events[1].block_hash, vec![0xAB; 4]);
+        assert_eq!(events[2].messages.len(), 1);
+        let body = events[2].messages[0].data.as_ref().unwrap().body.as_ref();
+        assert_eq!(body.unwrap()["cast_add_body"]["text"], "two");
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert_eq!(request.from_id, Some(11));
+        assert_eq!(request.shard_index, Some(1));
+        assert!(request
+            .event_types
+            .contains(&(HubEventType::MergeMessage as i32)));
+    }
+
+    #[test]
+    fn test_hub_event_batch_groups_events_by_block() {
+        let mut batch = HubEventBatch::new();
+        for event in sample_events(0x21) {
+            batch.push(crate::sync::client::convert_hub_event(event));
+        }
+
+        assert_eq!(batch.event_count(), 4);
+        assert_eq!(batch.block_count(), 2);
+        assert_eq!(batch.last_event_id(), Some(13));
+
+        let (chunks, last_event_id) = batch.take();
+        assert!(batch.is_empty());
+        assert_eq!(last_event_id, Some(13));
+
+        let block_500 = &chunks[0];
+        assert_eq!(block_500.hash, vec![0xAB; 4]);
+        assert_eq!(
+            block_500
+                .header
+                .as_ref()
+                .unwrap()
+                .height
+                .as_ref()
+                .unwrap()
+                .block_number,
+            500
+        );
+        // Without a parent hash the chunk is left out of reorg detection
+        assert_eq!(crate::sync::shard_processor::chunk_link(block_500), None);
+        // User message under the author's fid, on-chain event in a system transaction
+        let user_tx = block_500.transactions.iter().find(|tx| tx.fid == TEST_FID);
+        assert_eq!(user_tx.unwrap().user_messages.len(), 1);
+        let system_tx = block_500.transactions.iter().find(|tx| tx.fid == 0);
+        assert_eq!(system_tx.unwrap().system_messages.len(), 1);
+
+        assert_eq!(chunks[1].transactions[0].user_messages.len(), 1);
+    }
+
+    #[test]
+    fn test_hub_event_batch_covers_blocks_without_events() {
+        // Blocks 500 and 501
+        let mut batch = HubEventBatch::new();
+        for event in sample_events(0x23) {
+            batch.push(crate::sync::client::convert_hub_event(event));
+        }
+        assert_eq!(batch.covered_range(), Some(HeightRange::new(500, 501)));
+        batch.take();
+        assert_eq!(batch.covered_range(), None);
+
+        // The next flush starts after the previous one, across blocks without events
+        let event = block_confirmed_event(14, 505, vec![0xCD; 4]);
+        batch.push(crate::sync::client::convert_hub_event(event));
+        assert_eq!(batch.covered_range(), Some(HeightRange::new(502, 505)));
+
+        // A resumed stream continues from the height flushed before
+        let mut batch = HubEventBatch::resuming_after(497);
+        for event in sample_events(0x23) {
+            batch.push(crate::sync::client::convert_hub_event(event));
+        }
+        assert_eq!(batch.covered_range(), Some(HeightRange::new(498, 501)));
+
+        // Late events for an already flushed block cover nothing new
+        let mut batch = HubEventBatch::resuming_after(501);
+        for event in sample_events(0x23) {
+            batch.push(crate::sync::client::convert_hub_event(event));
+        }
+        assert_eq!(batch.covered_range(), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_event_stream_sync_persists_and_resumes() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let hashes = [vec![0xFE, 0xFE, 0x22, 1], vec![0xFE, 0xFE, 0x22, 2]];
+        for hash in &hashes {
+            sqlx::query("DELETE FROM casts WHERE message_hash = $1")
+                .bind(hash)
+                .execute(db.pool())
+                .await
+                .ok();
+        }
+
+        let hub = MockHub {
+            events: Arc::new(sample_events(0x22)),
+            ..Default::default()
+        };
+        let requests = hub.requests.clone();
+        let client = connect(start_mock_hub(hub).await).await;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("sync_state.json");
+        let state_manager = Arc::new(tokio::sync::RwLock::new(SyncStateManager::new(
+            state_file.to_str().unwrap(),
+        )));
+        let config = SyncConfig {
+            realtime_sync_mode: RealtimeSyncMode::Stream,
+            from_event_id: 10,
+            batch_size: 1, // Flush every block
+            ..SyncConfig::default()
+        };
+        let monitor =
+            RealtimeMonitor::new(client, Arc::new(db.clone()), state_manager.clone(), config);
+
+        let stats = monitor.consume_event_stream(1).await.unwrap();
+        assert_eq!(stats.messages_processed, 2);
+        assert_eq!(stats.last_block_number, Some(501));
+
+        for hash in &hashes {
+            let (count,): (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM casts WHERE message_hash = $1")
+                    .bind(hash)
+                    .fetch_one(db.pool())
+                    .await
+                    .unwrap();
+            assert_eq!(count, 1, "cast should be written exactly once");
+        }
+        assert_eq!(state_manager.read().await.get_last_event_id(1), Some(13));
+
+        // Second session resumes after the last applied event
+        let stats = monitor.consume_event_stream(1).await.unwrap();
+        assert_eq!(stats.messages_processed, 0);
+        assert_eq!(requests.lock().unwrap()[0].from_id, Some(10));
+        assert_eq!(requests.lock().unwrap()[1].from_id, Some(14));
+
+        for hash in &hashes {
+            sqlx::query("DELETE FROM casts WHERE message_hash = $1")
+                .bind(hash)
+                .execute(db.pool())
+                .await
+                .ok();
+        }
+    }
+}
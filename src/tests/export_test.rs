@@ -0,0 +1,86 @@
+/// Tests for bulk dataset exports through server-side cursors
+
+#[cfg(test)]
+mod export_tests {
+    use crate::export::export_chunks;
+    use crate::export::ExportFormat;
+    use crate::export::ExportQuery;
+    use crate::export::ExportTable;
+    use crate::export::FidRange;
+    use crate::models::ShardBlockInfo;
+    use crate::sync::shard_processor::flush_batched_data;
+    use crate::sync::shard_processor::BatchedData;
+
+    const TEST_FID: i64 = 9201;
+
+    fn hash(byte: u8) -> Vec<u8> {
+        let mut hash = vec![byte; 20];
+        hash[0] = 0xfe; // keep clear of real message hashes
+        hash
+    }
+
+    async fn cleanup(db: &crate::Database) {
+        sqlx::query("DELETE FROM links WHERE fid = $1")
+            .bind(TEST_FID)
+            .execute(db.pool())
+            .await
+            .ok();
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_export_streams_filtered_rows_in_batches() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        cleanup(&db).await;
+
+        let mut batched = BatchedData::new();
+        for (index, timestamp) in [100_i64, 86_400, 86_500, 200_000].into_iter().enumerate() {
+            batched.links.push((
+                TEST_FID,
+                TEST_FID + 1 + index as i64,
+                "follow".to_string(),
+                "add".to_string(),
+                timestamp,
+                hash(index as u8 + 1),
+                ShardBlockInfo::new(1, 1, TEST_FID as u64, 0),
+            ));
+        }
+        flush_batched_data(&db, batched).await.unwrap();
+
+        let mut query = ExportQuery::new(ExportTable::Links);
+        query.fids = FidRange {
+            min: Some(TEST_FID),
+            max: Some(TEST_FID),
+        };
+        query.since = Some(crate::export::parse_time("2021-01-02").unwrap());
+        query.until = Some(crate::export::parse_time("2021-01-03").unwrap());
+
+        let mut chunks = Vec::new();
+        let rows = export_chunks(&db, &query, ExportFormat::Ndjson, 1, |chunk| {
+            chunks.push(chunk);
+            std::future::ready(Ok(()))
+        })
+        .await
+        .unwrap();
+        assert_eq!(rows, 2);
+        // One chunk per fetched row plus the final one
+        assert!(chunks.len() >= 3);
+
+        let output = String::from_utf8(chunks.concat()).unwrap();
+        let timestamps: Vec<i64> = output
+            .lines()
+            .map(|line| {
+                let row: serde_json::Value = serde_json::from_str(line).unwrap();
+                assert_eq!(row["fid"], TEST_FID);
+                row["timestamp"].as_i64().unwrap()
+            })
+            .collect();
+        assert_eq!(timestamps.len(), 2);
+        assert!(timestamps.contains(&86_400));
+        assert!(timestamps.contains(&86_500));
+
+        cleanup(&db).await;
+    }
+}
@@ -0,0 +1,157 @@
+/// Tests for full-text keyword search over casts and profiles
+
+#[cfg(test)]
+mod full_text_search_tests {
+    use crate::database::Database;
+
+    const TEST_FID: i64 = 9104;
+
+    async fn insert_cast(db: &Database, marker: u8, text: &str) {
+        let mut message_hash = vec![0xfd; 20];
+        message_hash[1] = marker;
+        sqlx::query(
+            "INSERT INTO casts (fid, text, timestamp, message_hash) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (message_hash) DO NOTHING",
+        )
+        .bind(TEST_FID)
+        .bind(text)
+        .bind(1000 + i64::from(marker))
+        .bind(message_hash)
+        .execute(db.pool())
+        .await
+        .expect("insert cast");
+    }
+
+    async fn insert_profile_field(db: &Database, marker: u8, field: &str, value: &str, ts: i64) {
+        let mut message_hash = vec![0xfc; 20];
+        message_hash[1] = marker;
+        sqlx::query(
+            "INSERT INTO user_profile_changes (fid, field_name, field_value, timestamp, message_hash) \
+             VALUES ($1, $2, $3, $4, $5) ON CONFLICT (message_hash) DO NOTHING",
+        )
+        .bind(TEST_FID)
+        .bind(field)
+        .bind(value)
+        .bind(ts)
+        .bind(message_hash)
+        .execute(db.pool())
+        .await
+        .expect("insert profile change");
+    }
+
+    async fn setup() -> Database {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+        cleanup(&db).await;
+        db
+    }
+
+    async fn cleanup(db: &Database) {
+        for table in ["casts", "user_profile_changes"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                .bind(TEST_FID)
+                .execute(db.pool())
+                .await
+                .ok();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_cast_search_ranks_and_parses_web_syntax() {
+        let db = setup().await;
+        insert_cast(
+            &db,
+            1,
+            "Zyxquorp engines are fast. Zyxquorp everywhere, zyxquorp!",
+        )
+        .await;
+        insert_cast(&db, 2, "Shipping a zyxquorp plugin for frames today").await;
+        insert_cast(&db, 3, "Frames need better tooling").await;
+
+        let results = db.full_text_search_casts("zyxquorp", 10).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].similarity >= results[1].similarity);
+        assert!(results
+            .iter()
+            .all(|r| r.similarity > 0.0 && r.similarity < 1.0));
+
+        // Stemming: "plugins" matches "plugin"; exclusion drops the plugin cast
+        let plugins = db
+            .full_text_search_casts("zyxquorp plugins", 10)
+            .await
+            .unwrap();
+        assert_eq!(plugins.len(), 1);
+        let excluded = db
+            .full_text_search_casts("zyxquorp -frames", 10)
+            .await
+            .unwrap();
+        assert_eq!(excluded.len(), 1);
+        assert!(excluded[0].text.starts_with("Zyxquorp engines"));
+
+        // Phrases keep word order; OR widens the match
+        let phrase = db
+            .full_text_search_casts("\"zyxquorp plugin\"", 10)
+            .await
+            .unwrap();
+        assert_eq!(phrase.len(), 1);
+        let either = db
+            .full_text_search_casts("zyxquorp OR tooling", 100)
+            .await
+            .unwrap();
+        assert_eq!(either.iter().filter(|r| r.fid == TEST_FID).count(), 3);
+
+        assert!(db
+            .full_text_search_casts("  ", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        cleanup(&db).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_profile_search_uses_current_fields() {
+        let db = setup().await;
+        insert_profile_field(&db, 1, "username", "qwzlbuilder", 10).await;
+        insert_profile_field(&db, 2, "bio", "Former plumbing enthusiast", 10).await;
+        insert_profile_field(&db, 3, "bio", "Building onchain games", 20).await;
+
+        let rank = |results: Vec<crate::database::RankedProfile>| {
+            results
+                .into_iter()
+                .find(|ranked| ranked.profile.fid == TEST_FID)
+                .map(|ranked| ranked.rank)
+        };
+
+        let by_name = rank(
+            db.full_text_search_profiles("qwzlbuilder", 10)
+                .await
+                .unwrap(),
+        )
+        .expect("username should match");
+
+        // Bios are stemmed, and matching name and bio ranks above the name alone
+        let by_both = rank(
+            db.full_text_search_profiles("qwzlbuilder OR \"build onchain game\"", 10)
+                .await
+                .unwrap(),
+        )
+        .expect("name and bio should match");
+        assert!(by_both > by_name);
+
+        // The replaced bio is no longer searchable
+        let stale = db
+            .full_text_search_profiles("former plumbing enthusiast", 10)
+            .await
+            .unwrap();
+        assert!(!stale.iter().any(|ranked| ranked.profile.fid == TEST_FID));
+
+        cleanup(&db).await;
+    }
+}
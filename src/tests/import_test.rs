@@ -0,0 +1,156 @@
+/// Tests for resumable archive imports
+
+#[cfg(test)]
+mod import_tests {
+    use std::path::Path;
+
+    use crate::export::archive::import_archive;
+    use crate::export::archive::ArchiveManifest;
+    use crate::export::archive::ArchiveTable;
+    use crate::export::archive::ARCHIVE_FORMAT_VERSION;
+    use crate::export::archive::ARCHIVE_TABLES;
+    use crate::export::archive::MANIFEST_FILE;
+    use crate::export::columns::Cell;
+    use crate::export::format::json_row;
+    use crate::export::ExportTable;
+
+    const TEST_FID: i64 = 9301;
+
+    fn hash(byte: u8) -> Vec<u8> {
+        let mut hash = vec![byte; 20];
+        hash[0] = 0xfd; // keep clear of real message hashes
+        hash
+    }
+
+    async fn cleanup(db: &crate::Database) {
+        sqlx::query("DELETE FROM links WHERE fid = $1")
+            .bind(TEST_FID)
+            .execute(db.pool())
+            .await
+            .ok();
+    }
+
+    async fn embedding_dimension(db: &crate::Database) -> usize {
+        if let Some(active) = db.active_embedding_version().await.unwrap() {
+            return active.dimension();
+        }
+        db.embedding_column_dimensions()
+            .await
+            .unwrap()
+            .iter()
+            .find(|column| column.table == "cast_embeddings")
+            .map(|column| usize::try_from(column.dimension).unwrap())
+            .unwrap()
+    }
+
+    /// An archive with three links and every other table empty
+    async fn write_archive(db: &crate::Database, dir: &Path) -> ArchiveManifest {
+        let links: String = (1..=3u8)
+            .map(|index| {
+                let cells = vec![
+                    Cell::Int(TEST_FID),
+                    Cell::Int(TEST_FID + i64::from(index)),
+                    Cell::Text("follow".to_string()),
+                    Cell::Text("add".to_string()),
+                    Cell::Int(i64::from(index) * 100),
+                    Cell::Bytes(hash(index)),
+                    Cell::Int(1),
+                    Cell::Int(i64::from(index)),
+                ];
+                format!("{}\n", json_row(ExportTable::Links.columns(), &cells))
+            })
+            .collect();
+
+        let mut tables = Vec::new();
+        for table in ARCHIVE_TABLES {
+            let file = format!("{}.ndjson", table.name());
+            let rows = if table == ExportTable::Links { 3 } else { 0 };
+            let content = if rows == 0 { "" } else { links.as_str() };
+            std::fs::write(dir.join(&file), content).unwrap();
+            tables.push(ArchiveTable { table, file, rows });
+        }
+
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            archive_id: uuid::Uuid::new_v4(),
+            schema_version: db.schema_version().await.unwrap().unwrap(),
+            embedding_dimension: embedding_dimension(db).await,
+            embedding_model: "test-model".to_string(),
+            created_at: chrono::Utc::now(),
+            tables,
+        };
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest).unwrap(),
+        )
+        .unwrap();
+        manifest
+    }
+
+    async fn imported_links(db: &crate::Database) -> Vec<i64> {
+        sqlx::query_scalar("SELECT target_fid FROM links WHERE fid = $1 ORDER BY target_fid")
+            .bind(TEST_FID)
+            .fetch_all(db.pool())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_interrupted_import_resumes_after_last_batch() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        cleanup(&db).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = write_archive(&db, dir.path()).await;
+        let id = manifest.archive_id;
+
+        // Simulate an import interrupted after the first link
+        for table in ARCHIVE_TABLES {
+            if table != ExportTable::Links {
+                db.complete_import_table(id, table, 0).await.unwrap();
+            }
+        }
+        db.import_batch(id, ExportTable::Links, &[], 1)
+            .await
+            .unwrap();
+
+        import_archive(&db, dir.path(), 1).await.unwrap();
+        assert_eq!(imported_links(&db).await, vec![TEST_FID + 2, TEST_FID + 3]);
+        let progress = db.import_progress(id).await.unwrap();
+        assert!(progress.iter().all(|table| table.completed));
+
+        // Completed tables are skipped on a rerun
+        import_archive(&db, dir.path(), 1).await.unwrap();
+        assert_eq!(imported_links(&db).await.len(), 2);
+
+        sqlx::query("DELETE FROM import_progress WHERE archive_id = $1")
+            .bind(id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        cleanup(&db).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_import_rejects_mismatched_schema_version() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = write_archive(&db, dir.path()).await;
+        manifest.schema_version += 1;
+        std::fs::write(
+            dir.path().join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest).unwrap(),
+        )
+        .unwrap();
+
+        let error = import_archive(&db, dir.path(), 100).await.unwrap_err();
+        assert!(error.to_string().contains("schema version"));
+    }
+}
@@ -0,0 +1,139 @@
+/// Tests for the live feed: sync publishes committed events, the API listens
+
+#[cfg(test)]
+mod live_feed_tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::api::live_feed::LiveFeed;
+    use crate::api::live_feed::LiveFeedFilter;
+    use crate::api::live_feed::LiveFeedMessage;
+    use crate::models::ShardBlockInfo;
+    use crate::sync::shard_processor::flush_batched_data;
+    use crate::sync::shard_processor::write_batched_data;
+    use crate::sync::shard_processor::BatchedData;
+    use crate::webhooks::ChangeEventType;
+
+    const TEST_FID: i64 = 9105;
+
+    fn hash(byte: u8) -> Vec<u8> {
+        let mut hash = vec![byte; 20];
+        hash[0] = 0xfc; // keep clear of real message hashes
+        hash
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_flush_publishes_to_listening_feed() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database")
+            .with_live_feed(true);
+        let cleanup = || async {
+            for table in ["casts", "links"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                    .bind(TEST_FID)
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        let feed = Arc::new(LiveFeed::new(16));
+        let mut subscriber = feed.subscribe();
+        tokio::spawn(feed.clone().listen(db.pool().clone()));
+        // Give the listener time to issue LISTEN
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let info = ShardBlockInfo::new(1, 1, TEST_FID as u64, 0);
+        let mut batched = BatchedData::new();
+        batched.links.push((
+            TEST_FID,
+            3,
+            "follow".to_string(),
+            "add".to_string(),
+            100,
+            hash(1),
+            info.clone(),
+        ));
+        batched.casts.push((
+            TEST_FID,
+            Some("Shipping the live feed".to_string()),
+            101,
+            hash(2),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            info,
+        ));
+        flush_batched_data(&db, batched).await.unwrap();
+
+        let filter = LiveFeedFilter {
+            fids: HashSet::from([TEST_FID]),
+            keyword: Some("live feed".to_string()),
+            ..Default::default()
+        };
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next(&filter))
+            .await
+            .expect("live feed event should arrive");
+        let Some(LiveFeedMessage::Event(event)) = message else {
+            panic!("expected an event, got {message:?}");
+        };
+        assert_eq!(event.event_type, ChangeEventType::CastAdd);
+        assert_eq!(event.message_hash, hex::encode(hash(2)));
+        assert_eq!(event.text(), Some("Shipping the live feed"));
+
+        cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_replayed_messages_are_not_republished() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database")
+            .with_live_feed(true);
+        let cleanup = || async {
+            for table in ["reactions", "webhook_events"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                    .bind(TEST_FID)
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        let reaction_batch = || {
+            let mut batched = BatchedData::new();
+            batched.reactions.push((
+                TEST_FID,
+                hash(3),
+                Some(3),
+                1,
+                "add".to_string(),
+                102,
+                hash(4),
+                ShardBlockInfo::new(1, 2, TEST_FID as u64, 0),
+            ));
+            batched
+        };
+
+        // A re-sync or reorg replay stores the same message again
+        for expected in [1, 0] {
+            let mut tx = db.pool().begin().await.unwrap();
+            let events = write_batched_data(&mut tx, &db, reaction_batch())
+                .await
+                .unwrap();
+            tx.commit().await.unwrap();
+            assert_eq!(events.len(), expected);
+        }
+
+        cleanup().await;
+    }
+}
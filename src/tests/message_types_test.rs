@@ -45,12 +45,15 @@ mod message_types_tests {
     async fn cleanup_by_message_hash(db: &Database, message_hash: &[u8]) {
         let cleanup_queries = vec![
             "DELETE FROM casts WHERE message_hash = $1",
+            "DELETE FROM cast_removes WHERE message_hash = $1",
             "DELETE FROM links WHERE message_hash = $1",
             "DELETE FROM reactions WHERE message_hash = $1",
             "DELETE FROM verifications WHERE message_hash = $1",
             "DELETE FROM user_profile_changes WHERE message_hash = $1",
             // username_proofs: Skip cleanup (no message_hash column, has UNIQUE constraint on fid+username_type)
             "DELETE FROM frame_actions WHERE message_hash = $1",
+            "DELETE FROM link_compact_states WHERE message_hash = $1",
+            "DELETE FROM storage_lends WHERE message_hash = $1",
         ];
 
         for query in cleanup_queries {
@@ -88,7 +91,7 @@ mod message_types_tests {
         // Test matrix: [message_type, table_name, is_implemented]
         let message_types = vec![
             (1, "casts", true, "CastAdd"),
-            (2, "casts", true, "CastRemove (soft delete)"),
+            (2, "cast_removes", true, "CastRemove (tombstone)"),
             (3, "reactions", true, "ReactionAdd"),
             (4, "reactions", true, "ReactionRemove (soft delete)"),
             (5, "links", true, "LinkAdd"),
@@ -98,8 +101,13 @@ mod message_types_tests {
             (11, "user_profile_changes", true, "UserDataAdd"),
             (12, "username_proofs", true, "UsernameProof"),
             (13, "frame_actions", true, "FrameAction"),
-            (14, "N/A", true, "LinkCompactState (log only)"),
-            (15, "N/A", true, "LendStorage (log only)"),
+            (
+                14,
+                "link_compact_states",
+                true,
+                "LinkCompactState (applied to links)",
+            ),
+            (15, "storage_lends", true, "LendStorage"),
         ];
 
         println!("\n📋 Message Type Coverage Report:\n");
@@ -142,6 +150,8 @@ mod message_types_tests {
             None,                                  // root_hash
             None,                                  // embeds
             None,                                  // mentions
+            None,                                  // parent_fid
+            None,                                  // parent_url
             shard_info.clone(),
         ));
 
@@ -177,7 +187,11 @@ mod message_types_tests {
         assert_eq!(result.2, 1698765432, "Timestamp should match");
         assert_eq!(result.3, test_hash.clone(), "Message hash should match");
         assert_eq!(result.4, None, "Parent hash should be None");
-        assert_eq!(result.5, None, "Root hash should be None");
+        assert_eq!(
+            result.5,
+            Some(test_hash.clone()),
+            "Top-level cast should be its own root"
+        );
 
         // Cleanup after test
         cleanup_by_message_hash(&db, &test_hash).await;
@@ -185,6 +199,90 @@ mod message_types_tests {
         println!("✅ Type 1 (CastAdd) test passed");
     }
 
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_cast_remove_type_2() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let add_hash = test_message_hash(2001);
+        let remove_hash = test_message_hash(2002);
+
+        cleanup_by_message_hash(&db, &add_hash).await;
+        cleanup_by_message_hash(&db, &remove_hash).await;
+
+        // Add a cast with an embedding
+        let mut batched = BatchedData::new();
+        batched.casts.push((
+            99,
+            Some("Cast that will be deleted".to_string()),
+            1698765432,
+            add_hash.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            shard_info.clone(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        db.store_cast_embedding(&add_hash, 99, "Cast that will be deleted", &[0.1; 384])
+            .await
+            .expect("Failed to store embedding");
+
+        // Remove the cast - INSERT tombstone into cast_removes
+        let mut batched = BatchedData::new();
+        batched.cast_removes.push((
+            99,
+            add_hash.clone(), // target_hash
+            1698765500,
+            remove_hash.clone(),
+            shard_info.clone(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush remove");
+
+        // 🎯 STRICT VALIDATION: Verify tombstone
+        let result: (i64, Vec<u8>, i64) = sqlx::query_as(
+            "SELECT fid, target_hash, timestamp FROM cast_removes WHERE message_hash = $1",
+        )
+        .bind(&remove_hash)
+        .fetch_one(db.pool())
+        .await
+        .expect("Failed to query cast remove");
+
+        assert_eq!(result.0, 99, "FID should match");
+        assert_eq!(result.1, add_hash.clone(), "Target hash should match");
+        assert_eq!(result.2, 1698765500, "Timestamp should match");
+
+        // Embedding must be gone and the cast must be hidden from readers
+        let embedding_count: (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM cast_embeddings WHERE message_hash = $1")
+                .bind(&add_hash)
+                .fetch_one(db.pool())
+                .await
+                .expect("Failed to count embeddings");
+        assert_eq!(embedding_count.0, 0, "Embedding should be removed");
+
+        let casts = db
+            .get_casts_by_fid(99, Some(1000), Some(0))
+            .await
+            .expect("Failed to get casts");
+        assert!(
+            casts.iter().all(|c| c.message_hash != add_hash),
+            "Removed cast should not be returned"
+        );
+
+        cleanup_by_message_hash(&db, &add_hash).await;
+        cleanup_by_message_hash(&db, &remove_hash).await;
+
+        println!("✅ Type 2 (CastRemove) test passed");
+    }
+
     #[tokio::test]
     #[ignore = "Requires database access - production database should not be modified"]
     async fn test_reaction_add_type_3() {
@@ -372,6 +470,7 @@ mod message_types_tests {
             Some("testuser".to_string()), // value
             1698765432,                   // timestamp
             test_hash.clone(),            // message_hash
+            test_shard_info(),
         ));
 
         flush_batched_data(&db, batched)
@@ -891,6 +990,8 @@ mod message_types_tests {
             None, // No root_hash
             None, // No embeds
             None, // No mentions
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched)
@@ -925,6 +1026,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched)
@@ -962,6 +1065,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched)
@@ -998,6 +1103,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched)
@@ -1030,6 +1137,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched)
@@ -1072,6 +1181,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
         flush_batched_data(&db, batched1)
@@ -1089,6 +1200,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
 
@@ -1229,6 +1342,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
 
@@ -1247,6 +1362,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
 
@@ -1423,6 +1540,7 @@ mod message_types_tests {
             Some("testuser".to_string()),
             1698765432,
             hash_username.clone(),
+            test_shard_info(),
         ));
         batched.profile_updates.push((
             test_fid,
@@ -1430,6 +1548,7 @@ mod message_types_tests {
             Some("Test User".to_string()),
             1698765433,
             hash_display.clone(),
+            test_shard_info(),
         ));
         batched.profile_updates.push((
             test_fid,
@@ -1437,6 +1556,7 @@ mod message_types_tests {
             Some("Test bio".to_string()),
             1698765434,
             hash_bio.clone(),
+            test_shard_info(),
         ));
         batched.profile_updates.push((
             test_fid,
@@ -1444,6 +1564,7 @@ mod message_types_tests {
             Some("https://example.com/pfp.png".to_string()),
             1698765435,
             hash_pfp.clone(),
+            test_shard_info(),
         ));
 
         // FNAME proof
@@ -1516,6 +1637,7 @@ mod message_types_tests {
             Some("Updated Display Name".to_string()),
             1698765999, // Later timestamp
             hash_display2.clone(),
+            test_shard_info(),
         ));
         flush_batched_data(&db, batched)
             .await
@@ -1590,6 +1712,8 @@ mod message_types_tests {
                     None,
                     None,
                     None,
+                    None,
+                    None,
                     shard_clone,
                 ));
 
@@ -1653,6 +1777,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
 
@@ -1682,6 +1808,8 @@ mod message_types_tests {
             None,
             None,
             None,
+            None,
+            None,
             shard_info.clone(),
         ));
 
@@ -1758,6 +1886,7 @@ mod message_types_tests {
             Some(vec![0x22; 32]), // transaction_hash
             Some(0),              // log_index
             event1_data,
+            shard_info.clone(),
         ));
 
         // Test 2: ID_REGISTER event (type 3)
@@ -1785,6 +1914,7 @@ mod message_types_tests {
             Some(vec![0x44; 32]),
             Some(1),
             event2_data,
+            shard_info.clone(),
         ));
 
         // Test 3: STORAGE_RENT event (type 4)
@@ -1812,6 +1942,7 @@ mod message_types_tests {
             Some(vec![0x66; 32]),
             Some(2),
             event3_data,
+            shard_info.clone(),
         ));
 
         flush_batched_data(&db, batched)
@@ -1885,4 +2016,585 @@ mod message_types_tests {
         println!("✅ All OnChainEvent types tested (3/5 core types)");
         println!("   Note: SIGNER_MIGRATED (2) and TIER_PURCHASE (5) are less common");
     }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_cast_channel_parent_fid_and_root_hash() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let root = test_message_hash(13001);
+        let reply = test_message_hash(13002);
+        let nested = test_message_hash(13003);
+        let late = test_message_hash(13004);
+        for hash in [&root, &reply, &nested, &late] {
+            cleanup_by_message_hash(&db, hash).await;
+        }
+        let channel = "https://warpcast.com/~/channel/snaprag-test".to_string();
+
+        // Root in a channel, reply and nested reply in the same batch
+        let mut batched = BatchedData::new();
+        batched.casts.push((
+            99,
+            Some("Channel root".to_string()),
+            1698765432,
+            root.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(channel.clone()),
+            shard_info.clone(),
+        ));
+        for (hash, parent) in [(&reply, &root), (&nested, &reply)] {
+            batched.casts.push((
+                100,
+                Some("Reply".to_string()),
+                1698765433,
+                hash.clone(),
+                Some(parent.clone()),
+                None,
+                None,
+                None,
+                Some(99),
+                None,
+                shard_info.clone(),
+            ));
+        }
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        // Reply to a cast that was stored by an earlier flush
+        let mut batched = BatchedData::new();
+        batched.casts.push((
+            101,
+            Some("Late reply".to_string()),
+            1698765434,
+            late.clone(),
+            Some(nested.clone()),
+            None,
+            None,
+            None,
+            Some(100),
+            None,
+            shard_info.clone(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        for hash in [&root, &reply, &nested, &late] {
+            let (root_hash,): (Option<Vec<u8>>,) =
+                sqlx::query_as("SELECT root_hash FROM casts WHERE message_hash = $1")
+                    .bind(hash)
+                    .fetch_one(db.pool())
+                    .await
+                    .expect("Failed to query cast");
+            assert_eq!(
+                root_hash,
+                Some(root.clone()),
+                "Root hash should be resolved"
+            );
+        }
+
+        let (parent_fid, parent_url): (Option<i64>, Option<String>) =
+            sqlx::query_as("SELECT parent_fid, parent_url FROM casts WHERE message_hash = $1")
+                .bind(&reply)
+                .fetch_one(db.pool())
+                .await
+                .expect("Failed to query cast");
+        assert_eq!(parent_fid, Some(99));
+        assert_eq!(parent_url, None);
+
+        let in_channel = db
+            .list_casts(crate::models::CastQuery {
+                fid: None,
+                text_search: None,
+                parent_hash: None,
+                root_hash: None,
+                channel: Some("snaprag-test".to_string()),
+                has_mentions: None,
+                has_embeds: None,
+                start_timestamp: None,
+                end_timestamp: None,
+                limit: Some(10),
+                offset: None,
+                sort_by: None,
+                sort_order: None,
+            })
+            .await
+            .expect("Failed to list casts");
+        assert!(in_channel.iter().any(|cast| cast.message_hash == root));
+
+        let thread = db
+            .get_casts_by_root(&root, None)
+            .await
+            .expect("Failed to load thread");
+        assert_eq!(thread.len(), 4);
+
+        for hash in [&root, &reply, &nested, &late] {
+            cleanup_by_message_hash(&db, hash).await;
+        }
+
+        println!("✅ Cast channel, parent_fid and root_hash test passed");
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_cast_root_hash_reaches_stored_descendants() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let root = test_message_hash(13101);
+        let reply = test_message_hash(13102);
+        let nested = test_message_hash(13103);
+        let deepest = test_message_hash(13104);
+        for hash in [&root, &reply, &nested, &deepest] {
+            cleanup_by_message_hash(&db, hash).await;
+        }
+
+        let cast = |hash: &Vec<u8>, parent: Option<&Vec<u8>>| {
+            (
+                100,
+                Some("Out of order".to_string()),
+                1698765440,
+                hash.clone(),
+                parent.cloned(),
+                None,
+                None,
+                None,
+                parent.map(|_| 100),
+                None,
+                shard_info.clone(),
+            )
+        };
+
+        // A reply chain arrives before the casts it replies to
+        let mut batched = BatchedData::new();
+        batched.casts.push(cast(&nested, Some(&reply)));
+        batched.casts.push(cast(&deepest, Some(&nested)));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        let mut batched = BatchedData::new();
+        batched.casts.push(cast(&root, None));
+        batched.casts.push(cast(&reply, Some(&root)));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        for hash in [&root, &reply, &nested, &deepest] {
+            let (root_hash,): (Option<Vec<u8>>,) =
+                sqlx::query_as("SELECT root_hash FROM casts WHERE message_hash = $1")
+                    .bind(hash)
+                    .fetch_one(db.pool())
+                    .await
+                    .expect("Failed to query cast");
+            assert_eq!(
+                root_hash,
+                Some(root.clone()),
+                "Root hash should reach every stored descendant"
+            );
+        }
+
+        for hash in [&root, &reply, &nested, &deepest] {
+            cleanup_by_message_hash(&db, hash).await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_link_compact_state_type_14() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let fid = 99_000_014_i64;
+        let compact_hash = test_message_hash(14001);
+
+        let cleanup = || async {
+            sqlx::query("DELETE FROM links WHERE fid = $1")
+                .bind(fid)
+                .execute(db.pool())
+                .await
+                .ok();
+            sqlx::query("DELETE FROM link_compact_states WHERE fid = $1")
+                .bind(fid)
+                .execute(db.pool())
+                .await
+                .ok();
+        };
+        cleanup().await;
+
+        // Before the compact state: follows 100 and 101
+        let mut batched = BatchedData::new();
+        for (target_fid, test_id) in [(100, 14002), (101, 14003)] {
+            batched.links.push((
+                fid,
+                target_fid,
+                "follow".to_string(),
+                "add".to_string(),
+                1000,
+                test_message_hash(test_id),
+                shard_info.clone(),
+            ));
+        }
+        // After the compact state: follows 103
+        batched.links.push((
+            fid,
+            103,
+            "follow".to_string(),
+            "add".to_string(),
+            3000,
+            test_message_hash(14004),
+            shard_info.clone(),
+        ));
+        // Compact state at 2000: the follow set is exactly {101, 102}
+        batched.link_compact_states.push((
+            fid,
+            "follow".to_string(),
+            vec![101, 102],
+            2000,
+            compact_hash.clone(),
+            shard_info.clone(),
+        ));
+
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        let following = |db: Database| async move {
+            let mut targets: Vec<i64> = db
+                .list_links(crate::models::LinkQuery {
+                    fid: Some(fid),
+                    target_fid: None,
+                    link_type: None,
+                    start_timestamp: None,
+                    end_timestamp: None,
+                    limit: Some(100),
+                    offset: None,
+                    sort_by: None,
+                    sort_order: None,
+                })
+                .await
+                .expect("Failed to list links")
+                .into_iter()
+                .map(|link| link.target_fid)
+                .collect();
+            targets.sort_unstable();
+            targets
+        };
+
+        assert_eq!(
+            following(db.clone()).await,
+            vec![101, 102, 103],
+            "100 should be removed, 102 added, 103 (newer) kept"
+        );
+
+        let (stored_targets,): (Vec<i64>,) =
+            sqlx::query_as("SELECT target_fids FROM link_compact_states WHERE message_hash = $1")
+                .bind(&compact_hash)
+                .fetch_one(db.pool())
+                .await
+                .expect("Failed to query compact state");
+        assert_eq!(stored_targets, vec![101, 102]);
+
+        // Re-applying the same compact state is a no-op
+        let (links_before,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM links WHERE fid = $1")
+            .bind(fid)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        let mut batched = BatchedData::new();
+        batched.link_compact_states.push((
+            fid,
+            "follow".to_string(),
+            vec![101, 102],
+            2000,
+            compact_hash.clone(),
+            shard_info.clone(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+        let (links_after,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM links WHERE fid = $1")
+            .bind(fid)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(links_before, links_after);
+
+        cleanup().await;
+
+        println!("✅ Type 14 (LinkCompactState) test passed");
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_lend_storage_type_15() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let test_hash = test_message_hash(15001);
+
+        cleanup_by_message_hash(&db, &test_hash).await;
+
+        let mut batched = BatchedData::new();
+        batched.storage_lends.push((
+            99,         // fid (lender)
+            100,        // to_fid
+            5,          // num_units
+            2,          // unit_type (2025)
+            1698765432, // timestamp
+            test_hash.clone(),
+            shard_info.clone(),
+        ));
+
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        let result: (i64, i64, i64, i16, i64, Option<i32>, Option<i64>) = sqlx::query_as(
+            "SELECT fid, to_fid, num_units, unit_type, timestamp, shard_id, block_height
+             FROM storage_lends
+             WHERE message_hash = $1",
+        )
+        .bind(&test_hash)
+        .fetch_one(db.pool())
+        .await
+        .expect("Failed to query storage lend");
+
+        assert_eq!(result.0, 99, "Lender FID should match");
+        assert_eq!(result.1, 100, "Borrower FID should match");
+        assert_eq!(result.2, 5, "Units should match");
+        assert_eq!(result.3, 2, "Unit type should match");
+        assert_eq!(result.4, 1698765432, "Timestamp should match");
+        assert_eq!(result.5, Some(1), "Shard ID should match");
+        assert_eq!(result.6, Some(1000), "Block height should match");
+
+        cleanup_by_message_hash(&db, &test_hash).await;
+
+        println!("✅ Type 15 (LendStorage) test passed");
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_fname_transfer_history() {
+        let db = setup_test_db().await;
+        let shard_info = test_shard_info();
+        let username = "snaprag-test-fname";
+        let transfer_ids = [99_000_001_i64, 99_000_002_i64];
+
+        let cleanup = || async {
+            sqlx::query("DELETE FROM fname_transfers WHERE transfer_id = ANY($1)")
+                .bind(&transfer_ids[..])
+                .execute(db.pool())
+                .await
+                .ok();
+        };
+        cleanup().await;
+
+        // Registered to FID 99, then transferred to FID 100
+        let mut batched = BatchedData::new();
+        for (transfer_id, from_fid, to_fid, timestamp) in [
+            (transfer_ids[0], 0, 99, 1698765432),
+            (transfer_ids[1], 99, 100, 1698765532),
+        ] {
+            batched.fname_transfers.push((
+                transfer_id,
+                from_fid,
+                to_fid,
+                username.to_string(),
+                vec![0xAA; 20],
+                vec![0xBB; 65],
+                timestamp,
+                shard_info.clone(),
+            ));
+        }
+
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        let history = db
+            .get_fname_transfers(username)
+            .await
+            .expect("Failed to query fname transfers");
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].from_fid, history[0].to_fid), (0, 99));
+        assert_eq!((history[1].from_fid, history[1].to_fid), (99, 100));
+        assert_eq!(history[1].owner, Some(vec![0xAA; 20]));
+
+        let for_fid = db
+            .get_fid_fname_transfers(99)
+            .await
+            .expect("Failed to query fname transfers");
+        assert_eq!(
+            for_fid
+                .iter()
+                .filter(|transfer| transfer.username == username)
+                .count(),
+            2
+        );
+
+        cleanup().await;
+
+        println!("✅ FnameTransfer history test passed");
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_onchain_state_tables() {
+        let db = setup_test_db().await;
+        let fid = 99_000_005_i64;
+        let key = vec![0x51; 32];
+        let removed_key = vec![0x52; 32];
+        let custody = vec![0xC1; 20];
+        let new_custody = vec![0xC2; 20];
+        let recovery = vec![0xD1; 20];
+
+        let cleanup = || async {
+            for table in ["fid_signers", "fid_registrations", "storage_rents"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                    .bind(fid)
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        let now = chrono::Utc::now().timestamp();
+        let mut batched = BatchedData::new();
+        batched.id_register_events.push((
+            fid,
+            1, // REGISTER
+            Some(custody.clone()),
+            None,
+            Some(recovery.clone()),
+            9_000_000,
+            0,
+            1_700_000_000,
+            test_shard_info(),
+        ));
+        batched.signer_events.push((
+            fid,
+            key.clone(),
+            1,
+            1,
+            None,
+            9_000_001,
+            0,
+            1_700_000_100,
+            test_shard_info(),
+        ));
+        batched.signer_events.push((
+            fid,
+            removed_key.clone(),
+            1,
+            1,
+            None,
+            9_000_001,
+            1,
+            1_700_000_100,
+            test_shard_info(),
+        ));
+        batched.signer_events.push((
+            fid,
+            removed_key.clone(),
+            1,
+            2, // REMOVE
+            None,
+            9_000_002,
+            0,
+            1_700_000_200,
+            test_shard_info(),
+        ));
+        batched.storage_rents.push((
+            fid,
+            None,
+            2,
+            now + 86_400,
+            9_000_003,
+            0,
+            now,
+            None,
+            test_shard_info(),
+        ));
+        batched.storage_rents.push((
+            fid,
+            None,
+            5,
+            now - 86_400,
+            9_000_003,
+            1,
+            now,
+            None,
+            test_shard_info(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        // Custody transfer in a later batch; an older replayed event is ignored
+        let mut batched = BatchedData::new();
+        batched.id_register_events.push((
+            fid,
+            2, // TRANSFER
+            Some(new_custody.clone()),
+            Some(custody.clone()),
+            None,
+            9_000_010,
+            0,
+            1_700_001_000,
+            test_shard_info(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+        let mut batched = BatchedData::new();
+        batched.signer_events.push((
+            fid,
+            key.clone(),
+            1,
+            2, // REMOVE, but older than the stored state
+            None,
+            9_000_000,
+            5,
+            1_699_999_999,
+            test_shard_info(),
+        ));
+        flush_batched_data(&db, batched)
+            .await
+            .expect("Failed to flush");
+
+        let state = db
+            .get_onchain_state(fid)
+            .await
+            .expect("Failed to query on-chain state");
+
+        let registration = state.registration.expect("Registration should exist");
+        assert_eq!(registration.custody_address, Some(new_custody.clone()));
+        assert_eq!(registration.recovery_address, Some(recovery));
+        assert_eq!(registration.registered_block, Some(9_000_000));
+        assert_eq!(
+            db.get_fid_by_custody_address(&new_custody).await.unwrap(),
+            Some(fid)
+        );
+
+        assert_eq!(state.active_signers.len(), 1);
+        assert_eq!(state.active_signers[0].key, key);
+        let all_signers = db.get_fid_signers(fid).await.unwrap();
+        let removed = all_signers
+            .iter()
+            .find(|signer| signer.key == removed_key)
+            .expect("Removed signer should be kept");
+        assert!(!removed.active);
+        assert_eq!(removed.removal_type, Some(2));
+
+        assert_eq!(state.storage_units, 2, "Only unexpired units count");
+        assert_eq!(state.storage_rents.len(), 2);
+
+        cleanup().await;
+
+        println!("✅ Signer / custody / storage state tables test passed");
+    }
 }
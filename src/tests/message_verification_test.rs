@@ -0,0 +1,201 @@
+/// Tests for sync-time message hash and signature verification
+///
+/// `tests/fixtures/signed_messages.json` is one block of FID 9101 with a
+/// SIGNER add event and four casts: one valid, one with tampered data, one
+/// signed by a different key than it claims and one signed by a key that was
+/// never added.
+
+#[cfg(test)]
+mod message_verification_tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::config::MessageVerification;
+    use crate::database::VerificationPolicy;
+    use crate::sync::client::proto::ShardChunk;
+    use crate::sync::shard_processor::message_verification::message_fids;
+    use crate::sync::shard_processor::message_verification::verify_chunks;
+    use crate::sync::shard_processor::message_verification::verify_message;
+    use crate::sync::shard_processor::RejectionReason;
+    use crate::sync::shard_processor::SignerSet;
+    use crate::sync::ShardProcessor;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/signed_messages.json");
+    const TEST_FID: i64 = 9101;
+
+    #[derive(Deserialize)]
+    struct SignedFixture {
+        /// Expected rejection reason of each user message, `null` when valid
+        expected: Vec<Option<String>>,
+        chunk: ShardChunk,
+    }
+
+    fn fixture() -> SignedFixture {
+        serde_json::from_str(FIXTURE).expect("fixture should parse")
+    }
+
+    #[test]
+    fn test_verify_message_checks_hash_and_signature() {
+        let fixture = fixture();
+        let messages = &fixture.chunk.transactions[0].user_messages;
+
+        assert_eq!(verify_message(&messages[0]), Ok(()));
+        assert_eq!(
+            verify_message(&messages[1]),
+            Err(RejectionReason::HashMismatch)
+        );
+        assert_eq!(
+            verify_message(&messages[2]),
+            Err(RejectionReason::InvalidSignature)
+        );
+        // Correctly signed, only the signer check catches it
+        assert_eq!(verify_message(&messages[3]), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_message_requires_signed_bytes() {
+        let fixture = fixture();
+        let valid = &fixture.chunk.transactions[0].user_messages[0];
+
+        let mut missing = valid.clone();
+        missing.data_bytes = None;
+        assert_eq!(verify_message(&missing), Err(RejectionReason::MissingData));
+
+        let mut other_fid = valid.clone();
+        other_fid.data.as_mut().unwrap().fid = 1;
+        assert_eq!(
+            verify_message(&other_fid),
+            Err(RejectionReason::DataMismatch)
+        );
+
+        // A body swapped in after signing no longer matches the signed bytes
+        let mut other_body = valid.clone();
+        other_body.data.as_mut().unwrap().body =
+            Some(serde_json::json!({"cast_add_body": {"text": "not what was signed", "type": 0}}));
+        assert_eq!(
+            verify_message(&other_body),
+            Err(RejectionReason::DataMismatch)
+        );
+
+        let mut eip712 = valid.clone();
+        eip712.signature_scheme = 2;
+        assert_eq!(
+            verify_message(&eip712),
+            Err(RejectionReason::UnsupportedSignatureScheme)
+        );
+    }
+
+    #[test]
+    fn test_verify_chunks_applies_signer_events_in_order() {
+        let fixture = fixture();
+        let chunks = std::slice::from_ref(&fixture.chunk);
+        assert_eq!(message_fids(chunks), vec![TEST_FID]);
+
+        // No stored signers: the key is added by the block's own SIGNER event
+        let mut signers = SignerSet::default();
+        let rejections = verify_chunks(chunks, 1, Some(&mut signers));
+
+        let mut reasons = vec![None; fixture.expected.len()];
+        for rejection in &rejections {
+            assert_eq!(rejection.chunk_index, 0);
+            assert_eq!(rejection.shard_block_info.block_height, 1_500_000);
+            reasons[rejection.message_index] = Some(rejection.reason.to_string());
+        }
+        assert_eq!(reasons, fixture.expected);
+
+        // Without the signer check only hash and signature failures remain
+        assert_eq!(verify_chunks(chunks, 1, None).len(), 2);
+    }
+
+    #[test]
+    fn test_signer_set_follows_remove_events() {
+        let fixture = fixture();
+        let transaction = &fixture.chunk.transactions[0];
+        let add = transaction.system_messages[0]
+            .on_chain_event
+            .clone()
+            .unwrap();
+        let key = transaction.user_messages[0].signer.clone();
+
+        let mut signers = SignerSet::new(HashMap::new());
+        assert!(!signers.is_active(TEST_FID, &key));
+        signers.apply_event(&add);
+        assert!(signers.is_active(TEST_FID, &key));
+        assert!(!signers.is_active(TEST_FID + 1, &key));
+
+        let mut remove = add;
+        remove.body.as_mut().unwrap()["signer_event_body"]["event_type"] = 2.into();
+        signers.apply_event(&remove);
+        assert!(!signers.is_active(TEST_FID, &key));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_quarantine_skips_invalid_messages() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+        let db = db.with_message_verification(VerificationPolicy {
+            mode: MessageVerification::Quarantine,
+            verify_signers: true,
+        });
+
+        let cleanup = || async {
+            for table in [
+                "casts",
+                "rejected_messages",
+                "fid_signers",
+                "onchain_events",
+            ] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                    .bind(TEST_FID)
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        let fixture = fixture();
+        ShardProcessor::new(db.clone())
+            .apply_snapshot_chunk(&fixture.chunk, 1)
+            .await
+            .expect("verified chunk should be applied");
+
+        let messages = &fixture.chunk.transactions[0].user_messages;
+        let valid = db.get_cast_by_hash(messages[0].hash.clone()).await.unwrap();
+        assert_eq!(
+            valid.and_then(|cast| cast.text).as_deref(),
+            Some("signed and valid")
+        );
+        for rejected in &messages[1..] {
+            assert!(db
+                .get_cast_by_hash(rejected.hash.clone())
+                .await
+                .unwrap()
+                .is_none());
+        }
+
+        let rejected = db.list_rejected_messages(Some(TEST_FID), 10).await.unwrap();
+        assert_eq!(rejected.len(), 3);
+        for row in &rejected {
+            let index = messages
+                .iter()
+                .position(|message| message.hash == row.message_hash)
+                .expect("rejected message comes from the fixture");
+            assert_eq!(
+                Some(row.reason.as_str()),
+                fixture.expected[index].as_deref()
+            );
+            assert_eq!(row.block_height, 1_500_000);
+            assert!(row.message.is_some(), "quarantine keeps the message");
+        }
+
+        cleanup().await;
+    }
+}
@@ -0,0 +1,53 @@
+/// Tests for the versioned schema migrations
+///
+/// Runs against an initialized test database: the baseline is either already
+/// recorded or gets bootstrapped, and re-running `migrate up` must be a no-op.
+
+#[cfg(test)]
+mod migrations_tests {
+    use crate::database::MigrationState;
+    use crate::database::MIGRATIONS;
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_migrate_up_is_idempotent_and_verified() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let dimension = crate::tests::load_test_config()
+            .expect("Failed to load test config")
+            .embedding_dimension();
+
+        db.migrate_up(None, dimension)
+            .await
+            .expect("migrate up should succeed");
+        assert!(
+            db.migrate_up(None, dimension).await.unwrap().is_empty(),
+            "second run should have nothing to apply"
+        );
+        assert_eq!(db.pending_migration_count().await.unwrap(), 0);
+        db.verify_schema_or_error()
+            .await
+            .expect("a migrated schema should verify");
+
+        let statuses = db.migration_status().await.unwrap();
+        for migration in MIGRATIONS {
+            let status = statuses
+                .iter()
+                .find(|s| s.version == migration.version)
+                .expect("every known migration should be listed");
+            assert_eq!(status.state, MigrationState::Applied);
+            assert!(status.applied_at.is_some());
+        }
+        assert!(db.verify_migrations().await.unwrap().is_empty());
+
+        // Recording the baseline again is a no-op
+        assert!(!db.record_baseline().await.unwrap());
+
+        // The baseline has no down script, so rolling it back must fail untouched
+        if MIGRATIONS.len() == 1 {
+            assert!(db.migrate_down(1, dimension).await.is_err());
+            assert_eq!(db.pending_migration_count().await.unwrap(), 0);
+        }
+    }
+}
@@ -2,16 +2,29 @@ pub mod batch_insert_test;
 pub mod cross_batch_duplicates_test;
 pub mod database_tests;
 pub mod deterministic_blocks_test;
+pub mod embedding_versions_test;
 pub mod event_sourcing_test;
+pub mod event_stream_test;
+pub mod export_test;
+pub mod full_text_search_test;
 pub mod grpc_shard_chunks_test;
+pub mod import_test;
 pub mod integration_sync_test;
+pub mod live_feed_test;
 pub mod message_types_test;
+pub mod message_verification_test;
+pub mod migrations_test;
 pub mod rag_integration_test;
 pub mod real_data_test;
+pub mod reconcile_test;
+pub mod reorg_test;
+pub mod snapshot_bootstrap_test;
 pub mod strict_test_config;
 pub mod strict_test_runner;
 pub mod strict_test_validation;
+pub mod sync_gaps_test;
 pub mod unit_tests;
+pub mod webhooks_test;
 
 use crate::config::AppConfig;
 use crate::database::Database;
@@ -155,3 +168,54 @@ pub async fn get_user_profile_data(
         Ok(None)
     }
 }
+
+/// A request captured by [`spawn_webhook_receiver`]
+#[derive(Debug, Clone)]
+pub struct ReceivedWebhook {
+    pub headers: axum::http::HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedWebhook {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+/// Test helper: local HTTP endpoint answering every POST with `status`
+///
+/// Responses carry a `Location` header pointing back at the endpoint, so a
+/// redirect status loops for clients that follow redirects.
+///
+/// Returns its URL and the requests received so far.
+pub async fn spawn_webhook_receiver(
+    status: axum::http::StatusCode,
+) -> (
+    String,
+    std::sync::Arc<std::sync::Mutex<Vec<ReceivedWebhook>>>,
+) {
+    let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let app = axum::Router::new().route(
+        "/hook",
+        axum::routing::post(
+            move |headers: axum::http::HeaderMap, body: axum::body::Bytes| async move {
+                sink.lock().unwrap().push(ReceivedWebhook {
+                    headers,
+                    body: body.to_vec(),
+                });
+                (status, [(axum::http::header::LOCATION, "/hook")])
+            },
+        ),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind webhook receiver");
+    let addr = listener.local_addr().expect("receiver address");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.ok();
+    });
+
+    (format!("http://{addr}/hook"), received)
+}
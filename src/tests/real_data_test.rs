@@ -158,6 +158,7 @@ mod real_data_tests {
                 text_search: None,
                 parent_hash: None,
                 root_hash: None,
+                channel: None,
                 has_mentions: None,
                 has_embeds: None,
                 start_timestamp: None,
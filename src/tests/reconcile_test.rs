@@ -0,0 +1,115 @@
+/// Tests for the per-FID message state that reconcile compares with the hub
+
+#[cfg(test)]
+mod reconcile_tests {
+    use crate::models::ShardBlockInfo;
+    use crate::sync::shard_processor::flush_batched_data;
+    use crate::sync::shard_processor::BatchedData;
+
+    const TEST_FID: i64 = 9102;
+
+    fn hash(byte: u8) -> Vec<u8> {
+        let mut hash = vec![byte; 20];
+        hash[0] = 0xfe; // keep clear of real message hashes
+        hash
+    }
+
+    fn info() -> ShardBlockInfo {
+        ShardBlockInfo::new(1, 1, TEST_FID as u64, 0)
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_fid_message_state_keeps_latest_message_per_key() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let cleanup = || async {
+            for table in ["casts", "cast_removes", "links", "reactions"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                    .bind(TEST_FID)
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        let mut batched = BatchedData::new();
+        for (byte, text) in [(1, "kept"), (2, "removed")] {
+            batched.casts.push((
+                TEST_FID,
+                Some(text.to_string()),
+                100,
+                hash(byte),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                info(),
+            ));
+        }
+        batched
+            .cast_removes
+            .push((TEST_FID, hash(2), 150, hash(3), info()));
+        // Followed then unfollowed 1, still following 2
+        for (target, event_type, timestamp, byte) in [
+            (1, "add", 100, 4),
+            (1, "remove", 200, 5),
+            (2, "add", 100, 6),
+        ] {
+            batched.links.push((
+                TEST_FID,
+                target,
+                "follow".to_string(),
+                event_type.to_string(),
+                timestamp,
+                hash(byte),
+                info(),
+            ));
+        }
+        batched.reactions.push((
+            TEST_FID,
+            hash(1),
+            Some(TEST_FID),
+            1,
+            "add".to_string(),
+            120,
+            hash(7),
+            info(),
+        ));
+        flush_batched_data(&db, batched).await.unwrap();
+
+        let state = db.fid_message_state(TEST_FID).await.unwrap();
+
+        assert_eq!(state.casts.len(), 1);
+        assert_eq!(state.casts[0].message_hash, hash(1));
+        assert_eq!(state.cast_removes, vec![hash(3)]);
+
+        let mut links: Vec<_> = state
+            .links
+            .iter()
+            .map(|link| {
+                (
+                    link.target_fid,
+                    link.event_type.as_str(),
+                    link.message_hash.clone(),
+                )
+            })
+            .collect();
+        links.sort();
+        assert_eq!(links, vec![(1, "remove", hash(5)), (2, "add", hash(6))]);
+
+        assert_eq!(state.reactions.len(), 1);
+        assert_eq!(state.reactions[0].message_hash, hash(7));
+
+        cleanup().await;
+    }
+}
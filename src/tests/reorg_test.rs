@@ -0,0 +1,353 @@
+/// Tests for rolling back blocks that the node replaced with another chain
+
+#[cfg(test)]
+mod reorg_tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+
+    use crate::database::rollback_blocks;
+    use crate::sync::client::proto;
+    use crate::sync::client::proto::ShardChunk;
+    use crate::sync::shard_processor::chunk_link;
+    use crate::sync::shard_processor::ChunkSource;
+    use crate::sync::ShardProcessor;
+    use crate::Result;
+
+    const TEST_FID: u64 = 9103;
+    const TEST_SHARD: u32 = 97;
+
+    /// Canonical chain served to the processor during a rollback
+    struct Chain(Vec<ShardChunk>);
+
+    #[async_trait]
+    impl ChunkSource for Chain {
+        async fn shard_chunks(
+            &self,
+            _shard_id: u32,
+            from: u64,
+            to: u64,
+        ) -> Result<Vec<ShardChunk>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|chunk| chunk_link(chunk).is_some_and(|l| (from..=to).contains(&l.height)))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn block_hash(fork: u8, height: u64) -> Vec<u8> {
+        vec![0xfe, fork, u8::try_from(height).unwrap()]
+    }
+
+    /// Block `height` of chain `fork` holding one cast named after it
+    fn chunk(fork: u8, parent_fork: u8, height: u64) -> ShardChunk {
+        let text = format!("{}{height}", char::from(fork));
+        let mut message_hash = vec![0xfe; 20];
+        message_hash[1] = fork;
+        message_hash[2] = u8::try_from(height).unwrap();
+
+        ShardChunk {
+            header: Some(proto::ShardHeader {
+                height: Some(proto::Height {
+                    shard_index: TEST_SHARD,
+                    block_number: height,
+                }),
+                timestamp: 100 + height,
+                parent_hash: block_hash(parent_fork, height - 1),
+                shard_root: vec![],
+            }),
+            hash: block_hash(fork, height),
+            transactions: vec![proto::Transaction {
+                fid: TEST_FID,
+                user_messages: vec![proto::Message {
+                    data: Some(proto::MessageData {
+                        r#type: 1, // CastAdd
+                        fid: TEST_FID,
+                        timestamp: u32::try_from(100 + height).unwrap(),
+                        network: 1,
+                        body: Some(serde_json::json!({ "cast_add_body": { "text": text } })),
+                    }),
+                    hash: message_hash,
+                    hash_scheme: 1,
+                    signature: vec![],
+                    signature_scheme: 1,
+                    signer: vec![],
+                    data_bytes: None,
+                }],
+                system_messages: vec![],
+            }],
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_replaced_block_is_rolled_back_and_reapplied() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let cleanup = || async {
+            for table in [
+                "casts",
+                "processed_messages",
+                "block_hashes",
+                "processed_blocks",
+                "sync_progress",
+            ] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE shard_id = $1"))
+                    .bind(i32::try_from(TEST_SHARD).unwrap())
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+        };
+        cleanup().await;
+
+        // Chain `a` is synced first
+        let stale: Vec<_> = (10..=12).map(|height| chunk(b'a', b'a', height)).collect();
+        ShardProcessor::new(db.clone())
+            .process_chunks_batch(&stale, TEST_SHARD)
+            .await
+            .expect("chain a should be applied");
+        assert_eq!(
+            db.block_hash(TEST_SHARD, 12)
+                .await
+                .unwrap()
+                .unwrap()
+                .block_hash,
+            block_hash(b'a', 12)
+        );
+
+        // The node now serves chain `b`, forking after block 10
+        let canonical = vec![chunk(b'b', b'a', 11), chunk(b'b', b'b', 12)];
+        let processor =
+            ShardProcessor::new(db.clone()).with_chunk_source(Arc::new(Chain(canonical.clone())));
+        processor
+            .process_chunks_batch(&canonical[..1], TEST_SHARD)
+            .await
+            .expect("chain b should replace chain a");
+
+        let texts: Vec<String> = sqlx::query_scalar(
+            "SELECT text FROM casts WHERE shard_id = $1 AND fid = $2 ORDER BY block_height",
+        )
+        .bind(i32::try_from(TEST_SHARD).unwrap())
+        .bind(i64::try_from(TEST_FID).unwrap())
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(texts, ["a10", "b11", "b12"]);
+
+        let hashes = db.block_hashes(TEST_SHARD, 10, 12).await.unwrap();
+        let hashes: Vec<_> = hashes.into_iter().map(|block| block.block_hash).collect();
+        assert_eq!(
+            hashes,
+            [
+                block_hash(b'a', 10),
+                block_hash(b'b', 11),
+                block_hash(b'b', 12)
+            ]
+        );
+        assert_eq!(db.get_last_processed_height(TEST_SHARD).await.unwrap(), 12);
+
+        // Without a node to fetch block 12 from, nothing is rolled back
+        let stale_again = [chunk(b'a', b'a', 11)];
+        assert!(ShardProcessor::new(db.clone())
+            .process_chunks_batch(&stale_again, TEST_SHARD)
+            .await
+            .is_err());
+        assert_eq!(
+            db.block_hash(TEST_SHARD, 11)
+                .await
+                .unwrap()
+                .unwrap()
+                .block_hash,
+            block_hash(b'b', 11)
+        );
+
+        // A re-apply that fails after the rollback leaves the stored chain intact
+        let mut broken = chunk(b'c', b'b', 12);
+        broken.transactions[0].user_messages[0]
+            .data
+            .as_mut()
+            .unwrap()
+            .body = Some(serde_json::json!({ "cast_add_body": { "text": "c12\0" } }));
+        assert!(ShardProcessor::new(db.clone())
+            .process_chunks_batch(&[broken], TEST_SHARD)
+            .await
+            .is_err());
+        let texts: Vec<String> = sqlx::query_scalar(
+            "SELECT text FROM casts WHERE shard_id = $1 AND fid = $2 ORDER BY block_height",
+        )
+        .bind(i32::try_from(TEST_SHARD).unwrap())
+        .bind(i64::try_from(TEST_FID).unwrap())
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(texts, ["a10", "b11", "b12"]);
+        assert_eq!(
+            db.block_hash(TEST_SHARD, 12)
+                .await
+                .unwrap()
+                .unwrap()
+                .block_hash,
+            block_hash(b'b', 12)
+        );
+        assert_eq!(db.get_last_processed_height(TEST_SHARD).await.unwrap(), 12);
+
+        cleanup().await;
+    }
+
+    /// Block `height` carrying a bio change and an on-chain signer event
+    fn onchain_chunk(height: u64, bio: &str, signer_event_type: i64) -> ShardChunk {
+        let mut message_hash = vec![0xfd; 20];
+        message_hash[2] = u8::try_from(height).unwrap();
+        let signer_key = "11".repeat(32);
+
+        ShardChunk {
+            header: Some(proto::ShardHeader {
+                height: Some(proto::Height {
+                    shard_index: TEST_SHARD,
+                    block_number: height,
+                }),
+                timestamp: 100 + height,
+                parent_hash: block_hash(b'a', height - 1),
+                shard_root: vec![],
+            }),
+            hash: block_hash(b'a', height),
+            transactions: vec![
+                proto::Transaction {
+                    fid: TEST_FID,
+                    user_messages: vec![proto::Message {
+                        data: Some(proto::MessageData {
+                            r#type: 11, // UserDataAdd
+                            fid: TEST_FID,
+                            timestamp: u32::try_from(100 + height).unwrap(),
+                            network: 1,
+                            body: Some(serde_json::json!({
+                                "user_data_body": { "type": 3, "value": bio }
+                            })),
+                        }),
+                        hash: message_hash,
+                        hash_scheme: 1,
+                        signature: vec![],
+                        signature_scheme: 1,
+                        signer: vec![],
+                        data_bytes: None,
+                    }],
+                    system_messages: vec![],
+                },
+                proto::Transaction {
+                    fid: 0,
+                    user_messages: vec![],
+                    system_messages: vec![proto::ValidatorMessage {
+                        on_chain_event: Some(proto::OnChainEvent {
+                            r#type: 1, // Signer
+                            chain_id: 10,
+                            block_number: 9_000 + height,
+                            block_hash: vec![0xfd; 32],
+                            block_timestamp: 1_700_000_000 + height,
+                            transaction_hash: vec![0xfd, u8::try_from(height).unwrap()],
+                            log_index: 1,
+                            fid: TEST_FID,
+                            body: Some(serde_json::json!({
+                                "signer_event_body": {
+                                    "key": signer_key,
+                                    "key_type": 1,
+                                    "event_type": signer_event_type,
+                                }
+                            })),
+                        }),
+                        fname_transfer: None,
+                    }],
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_rollback_removes_profile_changes_and_onchain_state() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let fid = i64::try_from(TEST_FID).unwrap();
+        let cleanup = || async {
+            for table in [
+                "user_profile_changes",
+                "onchain_events",
+                "fid_signers",
+                "processed_messages",
+                "block_hashes",
+                "processed_blocks",
+                "sync_progress",
+            ] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE shard_id = $1"))
+                    .bind(i32::try_from(TEST_SHARD).unwrap())
+                    .execute(db.pool())
+                    .await
+                    .ok();
+            }
+            sqlx::query("DELETE FROM fid_signers WHERE fid = $1")
+                .bind(fid)
+                .execute(db.pool())
+                .await
+                .ok();
+        };
+        cleanup().await;
+
+        // The signer is added at block 10 and removed at block 11
+        ShardProcessor::new(db.clone())
+            .process_chunks_batch(
+                &[onchain_chunk(10, "a10", 1), onchain_chunk(11, "a11", 2)],
+                TEST_SHARD,
+            )
+            .await
+            .expect("blocks should be applied");
+        let signers = db.get_fid_signers(fid).await.unwrap();
+        assert_eq!(signers.len(), 1);
+        assert!(!signers[0].active);
+
+        let mut tx = db.pool().begin().await.unwrap();
+        rollback_blocks(&mut tx, TEST_SHARD, 11)
+            .await
+            .expect("rollback should succeed");
+        tx.commit().await.unwrap();
+
+        let bios: Vec<String> = sqlx::query_scalar(
+            "SELECT field_value FROM user_profile_changes \
+             WHERE fid = $1 AND field_name = 'bio' ORDER BY timestamp",
+        )
+        .bind(fid)
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(bios, ["a10"]);
+
+        let events: Vec<i64> =
+            sqlx::query_scalar("SELECT shard_block_height FROM onchain_events WHERE shard_id = $1")
+                .bind(i32::try_from(TEST_SHARD).unwrap())
+                .fetch_all(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(events, [10]);
+
+        // Rebuilt from the add at block 10
+        let signers = db.get_fid_signers(fid).await.unwrap();
+        assert_eq!(signers.len(), 1);
+        assert!(signers[0].active);
+        assert_eq!(signers[0].last_block_number, 9_010);
+
+        cleanup().await;
+    }
+}
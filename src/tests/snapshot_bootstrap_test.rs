@@ -0,0 +1,187 @@
+/// Tests for snapshot-based bootstrap sync
+///
+/// Replays `tests/fixtures/snapshot_shard1.json`, recorded replication
+/// responses for a small shard snapshot, instead of talking to a node.
+
+#[cfg(test)]
+mod snapshot_bootstrap_tests {
+    use crate::sync::bootstrap::select_snapshot;
+    use crate::sync::bootstrap::snapshot_chunk;
+    use crate::sync::bootstrap::SnapshotSource;
+    use crate::sync::bootstrap::TRIE_VIRTUAL_SHARDS;
+    use crate::sync::client::proto::ShardTransactionsRequest;
+    use crate::sync::SnapshotFixture;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/snapshot_shard1.json");
+    const SNAPSHOT_HEIGHT: u64 = 1_480_000;
+
+    fn fixture() -> SnapshotFixture {
+        serde_json::from_str(FIXTURE).expect("fixture should parse")
+    }
+
+    fn first_page(virtual_shard: u32) -> ShardTransactionsRequest {
+        ShardTransactionsRequest {
+            shard_id: 1,
+            height: SNAPSHOT_HEIGHT,
+            trie_virtual_shard: virtual_shard,
+            page_token: None,
+        }
+    }
+
+    #[test]
+    fn test_select_snapshot() {
+        let fixture = fixture();
+
+        let latest = select_snapshot(&fixture.snapshots, None).unwrap();
+        assert_eq!(latest.height, SNAPSHOT_HEIGHT);
+
+        let older = select_snapshot(&fixture.snapshots, Some(1_380_000)).unwrap();
+        assert_eq!(older.height, 1_380_000);
+
+        assert!(select_snapshot(&fixture.snapshots, Some(42)).is_err());
+        assert!(select_snapshot(&[], None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_replays_pages() {
+        let fixture = fixture();
+
+        assert_eq!(fixture.snapshot_metadata(1).await.unwrap().len(), 2);
+        assert!(fixture.snapshot_metadata(2).await.unwrap().is_empty());
+
+        let page = fixture.shard_transactions(first_page(0)).await.unwrap();
+        assert_eq!(page.entries.len(), 3);
+        let token = page.next_page_token.expect("virtual shard 0 has two pages");
+
+        let mut next = first_page(0);
+        next.page_token = Some(token);
+        let page = fixture.shard_transactions(next).await.unwrap();
+        assert_eq!(page.entries.len(), 3);
+        assert!(page.next_page_token.is_none());
+
+        // Virtual shards that were empty when recorded replay as empty
+        let empty = fixture.shard_transactions(first_page(1)).await.unwrap();
+        assert!(empty.entries.is_empty());
+        assert!(empty.next_page_token.is_none());
+
+        // An unknown continuation token is an error, not an empty page
+        let mut unknown = first_page(1);
+        unknown.page_token = Some("missing".to_string());
+        assert!(fixture.shard_transactions(unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_chunk_groups_entries_by_fid() {
+        let fixture = fixture();
+        let snapshot = select_snapshot(&fixture.snapshots, None).unwrap();
+        let page = fixture.shard_transactions(first_page(3)).await.unwrap();
+
+        let chunk = snapshot_chunk(&snapshot, &page.entries);
+        let header = chunk.header.expect("chunk has a header");
+        let height = header.height.expect("header has a height");
+        assert_eq!(height.shard_index, 1);
+        assert_eq!(height.block_number, SNAPSHOT_HEIGHT);
+        assert_eq!(header.timestamp, snapshot.timestamp);
+
+        // id_register, link, cast and fname transfer of FID 9002
+        assert_eq!(chunk.transactions.len(), 1);
+        let transaction = &chunk.transactions[0];
+        assert_eq!(transaction.fid, 9002);
+        assert_eq!(transaction.user_messages.len(), 2);
+        assert_eq!(transaction.system_messages.len(), 2);
+        assert!(transaction.system_messages[0].on_chain_event.is_some());
+        assert!(transaction.system_messages[1].fname_transfer.is_some());
+    }
+
+    #[test]
+    fn test_fixture_covers_recorded_virtual_shards() {
+        let fixture = fixture();
+        assert_eq!(fixture.entry_count(), 10);
+        assert!(fixture
+            .pages
+            .iter()
+            .all(|page| page.request.trie_virtual_shard < TRIE_VIRTUAL_SHARDS));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_bootstrap_from_fixture_hands_off_to_block_sync() {
+        // Replay the fixture as a shard no real sync uses
+        const TEST_SHARD: u32 = 9_999;
+
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let mut fixture = fixture();
+        for snapshot in &mut fixture.snapshots {
+            snapshot.shard_id = TEST_SHARD;
+        }
+        for page in &mut fixture.pages {
+            page.request.shard_id = TEST_SHARD;
+        }
+
+        for table in ["snapshot_bootstraps", "sync_progress", "processed_blocks"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE shard_id = $1"))
+                .bind(TEST_SHARD as i32)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+
+        let stats = crate::sync::bootstrap_shard(&fixture, &db, TEST_SHARD, None)
+            .await
+            .expect("bootstrap should succeed");
+        assert_eq!(stats.height, SNAPSHOT_HEIGHT);
+        assert_eq!(stats.entries, 10);
+        assert_eq!(stats.pages, u64::from(TRIE_VIRTUAL_SHARDS) + 1);
+        assert!(!stats.resumed);
+
+        let progress = db
+            .get_snapshot_bootstrap(TEST_SHARD)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(progress.is_completed());
+        assert_eq!(progress.entries_applied, 10);
+
+        // Block sync picks up right after the snapshot
+        assert_eq!(
+            db.get_last_processed_height(TEST_SHARD).await.unwrap(),
+            SNAPSHOT_HEIGHT + 1
+        );
+        assert!(db
+            .find_sync_gaps(TEST_SHARD, Some(0), Some(SNAPSHOT_HEIGHT))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The snapshot's messages went through the normal write path
+        let first_entry = &fixture.pages[0].response.entries[2];
+        let cast_hash = first_entry.user_message.as_ref().unwrap().hash.clone();
+        let cast = db.get_cast_by_hash(cast_hash).await.unwrap();
+        assert_eq!(
+            cast.and_then(|cast| cast.text).as_deref(),
+            Some("gm from the snapshot")
+        );
+
+        // A second bootstrap would go backwards and is refused
+        assert!(
+            crate::sync::bootstrap_shard(&fixture, &db, TEST_SHARD, None)
+                .await
+                .is_err()
+        );
+
+        for table in ["snapshot_bootstraps", "sync_progress", "processed_blocks"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE shard_id = $1"))
+                .bind(TEST_SHARD as i32)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+    }
+}
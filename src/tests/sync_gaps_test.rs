@@ -0,0 +1,87 @@
+/// Tests for the processed_blocks ledger and sync gap detection
+
+#[cfg(test)]
+mod sync_gaps_tests {
+    use crate::database::HeightRange;
+    use crate::sync::shard_processor::flush_batched_data;
+    use crate::sync::shard_processor::BatchedData;
+
+    // A shard no real sync uses
+    const TEST_SHARD: u32 = 9_998;
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_ledger_merges_ranges_and_reports_gaps() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database");
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+
+        let cleanup = || async {
+            sqlx::query("DELETE FROM processed_blocks WHERE shard_id = $1")
+                .bind(TEST_SHARD as i32)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        };
+        cleanup().await;
+
+        // Two parallel batches with a failed one (20-29) and a lost block (15) in between
+        let mut batched = BatchedData::new();
+        for height in (10..15).chain(16..20).chain(30..40) {
+            batched.processed_blocks.push((TEST_SHARD, height));
+        }
+        flush_batched_data(&db, batched).await.unwrap();
+
+        assert_eq!(
+            db.processed_block_ranges(TEST_SHARD).await.unwrap(),
+            vec![
+                HeightRange::new(10, 14),
+                HeightRange::new(16, 19),
+                HeightRange::new(30, 39)
+            ]
+        );
+        assert_eq!(
+            db.find_sync_gaps(TEST_SHARD, None, None).await.unwrap(),
+            vec![HeightRange::new(15, 15), HeightRange::new(20, 29)]
+        );
+        assert_eq!(
+            db.find_sync_gaps(TEST_SHARD, Some(0), Some(45))
+                .await
+                .unwrap(),
+            vec![
+                HeightRange::new(0, 9),
+                HeightRange::new(15, 15),
+                HeightRange::new(20, 29),
+                HeightRange::new(40, 45)
+            ]
+        );
+
+        // Repairing the holes merges everything into one range
+        db.record_processed_blocks(TEST_SHARD, HeightRange::new(15, 15))
+            .await
+            .unwrap();
+        db.record_processed_blocks(TEST_SHARD, HeightRange::new(20, 29))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.processed_block_ranges(TEST_SHARD).await.unwrap(),
+            vec![HeightRange::new(10, 39)]
+        );
+        assert!(db
+            .find_sync_gaps(TEST_SHARD, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .processed_block_shards()
+            .await
+            .unwrap()
+            .contains(&TEST_SHARD));
+
+        cleanup().await;
+    }
+}
@@ -0,0 +1,198 @@
+/// Tests for change-data-capture webhooks: recording, delivery, dead letters and replay
+
+#[cfg(test)]
+mod webhooks_tests {
+    use std::sync::Arc;
+
+    use axum::http::StatusCode;
+
+    use crate::config::WebhooksConfig;
+    use crate::models::ShardBlockInfo;
+    use crate::sync::shard_processor::flush_batched_data;
+    use crate::sync::shard_processor::BatchedData;
+    use crate::tests::spawn_webhook_receiver;
+    use crate::webhooks::verify_signature;
+    use crate::webhooks::ChangeEventType;
+    use crate::webhooks::SubscriptionFilter;
+    use crate::webhooks::WebhookDispatcher;
+    use crate::webhooks::SIGNATURE_HEADER;
+
+    const TEST_FID: i64 = 9103;
+    const FOLLOWED_FID: i64 = 9104;
+
+    fn hash(byte: u8) -> Vec<u8> {
+        let mut hash = vec![byte; 20];
+        hash[0] = 0xfd; // keep clear of real message hashes
+        hash
+    }
+
+    fn follow_batch(byte: u8) -> BatchedData {
+        let mut batched = BatchedData::new();
+        batched.links.push((
+            TEST_FID,
+            FOLLOWED_FID,
+            "follow".to_string(),
+            "add".to_string(),
+            100,
+            hash(byte),
+            ShardBlockInfo::new(1, 1, TEST_FID as u64, 0),
+        ));
+        batched.profile_updates.push((
+            TEST_FID,
+            "bio".to_string(),
+            Some("gm".to_string()),
+            100,
+            hash(byte + 1),
+            ShardBlockInfo::new(1, 1, TEST_FID as u64, 0),
+        ));
+        batched
+    }
+
+    async fn cleanup(db: &crate::Database) {
+        sqlx::query("DELETE FROM webhook_subscriptions WHERE url LIKE 'http://127.0.0.1:%'")
+            .execute(db.pool())
+            .await
+            .ok();
+        sqlx::query("DELETE FROM webhook_events WHERE fid = $1")
+            .bind(TEST_FID)
+            .execute(db.pool())
+            .await
+            .ok();
+        for table in ["links", "user_profile_changes"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE fid = $1"))
+                .bind(TEST_FID)
+                .execute(db.pool())
+                .await
+                .ok();
+        }
+    }
+
+    fn follows_filter() -> SubscriptionFilter {
+        SubscriptionFilter {
+            event_types: vec![ChangeEventType::LinkAdd],
+            target_fid: Some(FOLLOWED_FID),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_flush_delivers_signed_events_and_replays() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database")
+            .with_change_capture(true);
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+        cleanup(&db).await;
+
+        let (url, received) = spawn_webhook_receiver(StatusCode::OK).await;
+        let subscription = db
+            .create_webhook_subscription(&url, "test-secret", &follows_filter())
+            .await
+            .unwrap();
+
+        flush_batched_data(&db, follow_batch(1)).await.unwrap();
+        // Re-syncing the same block must not queue the event again
+        flush_batched_data(&db, follow_batch(1)).await.unwrap();
+
+        let db = Arc::new(db);
+        let dispatcher = WebhookDispatcher::new(db.clone(), WebhooksConfig::default())
+            .unwrap()
+            .allowing_internal_destinations();
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 1);
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 0);
+
+        let body: serde_json::Value = {
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 1);
+            let request = &received[0];
+            let signature = request.header(SIGNATURE_HEADER).unwrap();
+            assert!(verify_signature("test-secret", signature, &request.body).is_some());
+            serde_json::from_slice(&request.body).unwrap()
+        };
+        assert_eq!(body["event_type"], "link_add");
+        assert_eq!(body["fid"], TEST_FID);
+        assert_eq!(body["target_fid"], FOLLOWED_FID);
+        assert_eq!(body["data"]["link_type"], "follow");
+
+        let offset = body["offset"].as_i64().unwrap();
+        let subscription = db
+            .get_webhook_subscription(subscription.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(subscription.last_delivered_offset, offset);
+
+        let queued = db
+            .replay_webhook_subscription(&subscription, offset)
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 1);
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        cleanup(&db).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database access - production database should not be modified"]
+    async fn test_failed_deliveries_are_dead_lettered() {
+        let db = crate::tests::create_test_database()
+            .await
+            .expect("Failed to create database")
+            .with_change_capture(true);
+        let config = crate::tests::load_test_config().expect("Failed to load test config");
+        db.migrate_up(None, config.embedding_dimension())
+            .await
+            .expect("migrate up should succeed");
+        cleanup(&db).await;
+
+        let (url, received) = spawn_webhook_receiver(StatusCode::SERVICE_UNAVAILABLE).await;
+        let subscription = db
+            .create_webhook_subscription(&url, "test-secret", &follows_filter())
+            .await
+            .unwrap();
+        flush_batched_data(&db, follow_batch(3)).await.unwrap();
+
+        let db = Arc::new(db);
+        let dispatcher = WebhookDispatcher::new(
+            db.clone(),
+            WebhooksConfig {
+                max_attempts: 2,
+                retry_base_secs: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .allowing_internal_destinations();
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 1);
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 1);
+        assert_eq!(dispatcher.dispatch_due().await.unwrap(), 0);
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        let dead_letters = db
+            .list_webhook_dead_letters(subscription.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].attempts, 2);
+        assert!(dead_letters[0].last_error.contains("503"));
+
+        // Replaying from the dead-lettered offset queues it again
+        let queued = db
+            .replay_webhook_subscription(&subscription, dead_letters[0].event_id)
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+        assert!(db
+            .list_webhook_dead_letters(subscription.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        cleanup(&db).await;
+    }
+}
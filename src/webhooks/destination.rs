@@ -0,0 +1,165 @@
+//! Checks that keep webhook requests away from internal networks
+
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+
+use url::Host;
+
+use crate::Result;
+
+/// NAT64 well-known prefix 64:ff9b::/96, which translates to IPv4 addresses
+const NAT64_PREFIX: [u16; 6] = [0x64, 0xff9b, 0, 0, 0, 0];
+
+/// A webhook url whose addresses were checked
+#[derive(Debug, Clone)]
+pub struct Destination {
+    /// Host name the addresses were resolved from, `None` for IP literals
+    pub domain: Option<String>,
+    pub addresses: Vec<SocketAddr>,
+}
+
+const fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        // 0.0.0.0/8 ("this network")
+        || first == 0
+        // 100.64.0.0/10 (carrier-grade NAT)
+        || (first == 100 && second & 0xc0 == 64)
+}
+
+fn is_internal_ipv6(ip: Ipv6Addr) -> bool {
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        || ip.segments()[..6] == NAT64_PREFIX
+}
+
+/// Loopback, private, link-local, carrier-grade NAT and NAT64 addresses,
+/// which webhooks must not reach
+#[must_use]
+pub fn is_internal_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_ipv4(ip),
+        IpAddr::V6(ip) => ip
+            .to_ipv4_mapped()
+            .map_or_else(|| is_internal_ipv6(ip), is_internal_ipv4),
+    }
+}
+
+/// Resolve a webhook url, rejecting it if its host is, or resolves to, an
+/// internal address
+///
+/// # Errors
+/// Returns an error if the url is invalid, cannot be resolved or points to
+/// an internal address
+pub async fn resolve_destination(url: &str) -> Result<Destination> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| crate::SnapRagError::Custom(format!("Invalid url '{url}': {e}")))?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let destination = match parsed.host() {
+        Some(Host::Ipv4(ip)) => Destination {
+            domain: None,
+            addresses: vec![SocketAddr::new(IpAddr::V4(ip), port)],
+        },
+        Some(Host::Ipv6(ip)) => Destination {
+            domain: None,
+            addresses: vec![SocketAddr::new(IpAddr::V6(ip), port)],
+        },
+        Some(Host::Domain(domain)) => Destination {
+            domain: Some(domain.to_string()),
+            addresses: tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| {
+                    crate::SnapRagError::Custom(format!("Cannot resolve '{domain}': {e}"))
+                })?
+                .collect(),
+        },
+        None => Destination {
+            domain: None,
+            addresses: Vec::new(),
+        },
+    };
+
+    if destination.addresses.is_empty()
+        || destination
+            .addresses
+            .iter()
+            .any(|address| is_internal_address(address.ip()))
+    {
+        return Err(crate::SnapRagError::Custom(
+            "Webhook url must not point to a loopback, private or link-local address".to_string(),
+        ));
+    }
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_addresses() {
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "100.64.0.1",
+            "100.127.255.254",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:100.64.0.1",
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(is_internal_address(internal.parse().unwrap()), "{internal}");
+        }
+        for public in [
+            "93.184.216.34",
+            "100.63.255.255",
+            "100.128.0.1",
+            "2606:2800:220:1::1",
+            "64:ff9b:1::1",
+        ] {
+            assert!(!is_internal_address(public.parse().unwrap()), "{public}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_destination() {
+        let destination = resolve_destination("https://93.184.216.34/hook")
+            .await
+            .unwrap();
+        assert!(destination.domain.is_none());
+        assert_eq!(
+            destination.addresses,
+            vec!["93.184.216.34:443".parse().unwrap()]
+        );
+
+        assert!(resolve_destination("http://127.0.0.1:8080/hook")
+            .await
+            .is_err());
+        assert!(resolve_destination("http://[::1]/hook").await.is_err());
+        assert!(resolve_destination("http://100.64.0.1/hook").await.is_err());
+        assert!(resolve_destination("http://[64:ff9b::7f00:1]/hook")
+            .await
+            .is_err());
+        assert!(
+            resolve_destination("http://169.254.169.254/latest/meta-data")
+                .await
+                .is_err()
+        );
+        assert!(resolve_destination("http://localhost/hook").await.is_err());
+    }
+}
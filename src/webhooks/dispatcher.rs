@@ -0,0 +1,338 @@
+//! Delivery of queued webhook events
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::debug;
+use tracing::warn;
+
+use super::destination::resolve_destination;
+use super::destination::Destination;
+use super::signature::sign_payload;
+use super::signature::SIGNATURE_HEADER;
+use super::ChangeEventType;
+use crate::config::WebhooksConfig;
+use crate::database::Database;
+use crate::database::PendingDelivery;
+use crate::Result;
+
+/// Header naming the event type of a delivery
+pub const EVENT_HEADER: &str = "x-snaprag-event";
+
+/// Header with a delivery id that stays the same across retries and replays
+pub const DELIVERY_HEADER: &str = "x-snaprag-delivery";
+
+/// JSON body POSTed to subscribers
+#[derive(Debug, Serialize)]
+pub struct DeliveryBody<'a> {
+    /// Event offset, usable with the replay endpoint
+    pub offset: i64,
+    pub subscription_id: i64,
+    pub event_type: ChangeEventType,
+    pub fid: i64,
+    pub target_fid: Option<i64>,
+    pub message_hash: String,
+    pub parent_hash: Option<String>,
+    pub channel: Option<&'a str>,
+    /// Farcaster timestamp of the message
+    pub timestamp: i64,
+    pub data: &'a serde_json::Value,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl<'a> DeliveryBody<'a> {
+    #[must_use]
+    pub fn new(delivery: &'a PendingDelivery) -> Self {
+        let event = &delivery.event.event;
+        Self {
+            offset: delivery.event.id,
+            subscription_id: delivery.subscription_id,
+            event_type: event.event_type,
+            fid: event.fid,
+            target_fid: event.target_fid,
+            message_hash: hex::encode(&event.message_hash),
+            parent_hash: event.parent_hash.as_deref().map(hex::encode),
+            channel: event.channel.as_deref(),
+            timestamp: event.timestamp,
+            data: &event.payload,
+            recorded_at: delivery.event.created_at,
+        }
+    }
+}
+
+/// POST a signed JSON body; any non-2xx response is an error
+pub async fn post_signed(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    event_type: ChangeEventType,
+    delivery_id: &str,
+    body: Vec<u8>,
+) -> Result<()> {
+    let signature = sign_payload(secret, chrono::Utc::now().timestamp(), &body);
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(EVENT_HEADER, event_type.as_str())
+        .header(DELIVERY_HEADER, delivery_id)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(crate::SnapRagError::Custom(format!(
+            "Webhook endpoint responded with HTTP {status}"
+        )))
+    }
+}
+
+/// Delay before retrying after `attempts` failed attempts
+#[must_use]
+pub fn retry_delay(config: &WebhooksConfig, attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(32);
+    Duration::from_secs(
+        config
+            .retry_base_secs
+            .saturating_mul(1 << exponent)
+            .min(config.retry_max_secs),
+    )
+}
+
+/// Client settings shared by every delivery; redirects are not followed, as
+/// they could lead to an address the destination check never saw
+fn client_builder(config: &WebhooksConfig) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .redirect(reqwest::redirect::Policy::none())
+}
+
+/// Sends due deliveries, retrying failures with exponential backoff and
+/// dead-lettering them once `max_attempts` is reached
+///
+/// Deliveries are claimed with `SKIP LOCKED`, so several dispatchers can run
+/// against the same database.
+pub struct WebhookDispatcher {
+    database: Arc<Database>,
+    config: WebhooksConfig,
+    client: reqwest::Client,
+    allow_internal_destinations: bool,
+}
+
+impl WebhookDispatcher {
+    pub fn new(database: Arc<Database>, config: WebhooksConfig) -> Result<Self> {
+        let client = client_builder(&config).build()?;
+        Ok(Self {
+            database,
+            config,
+            client,
+            allow_internal_destinations: false,
+        })
+    }
+
+    /// Skip the destination check, so tests can deliver to a local receiver
+    #[cfg(test)]
+    pub(crate) const fn allowing_internal_destinations(mut self) -> Self {
+        self.allow_internal_destinations = true;
+        self
+    }
+
+    /// Client for one delivery, with the destination resolved and checked
+    ///
+    /// The host is resolved again for every delivery and the request is pinned
+    /// to the checked addresses, so a DNS change after the subscription was
+    /// created cannot redirect deliveries to an internal address.
+    async fn destination_client(&self, url: &str) -> Result<reqwest::Client> {
+        if self.allow_internal_destinations {
+            return Ok(self.client.clone());
+        }
+        match resolve_destination(url).await? {
+            Destination {
+                domain: Some(domain),
+                addresses,
+            } => Ok(client_builder(&self.config)
+                .resolve_to_addrs(&domain, &addresses)
+                .build()?),
+            // IP literals are never resolved, so the checked address is the one used
+            _ => Ok(self.client.clone()),
+        }
+    }
+
+    /// Deliver queued events until the process exits
+    pub async fn run(self) {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        loop {
+            match self.dispatch_due().await {
+                // A full batch means more may be due right away
+                Ok(sent) if sent >= self.config.batch_size => continue,
+                Ok(_) => {}
+                Err(e) => warn!("Webhook dispatch failed: {}", e),
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Send one batch of due deliveries concurrently
+    ///
+    /// Returns the number of deliveries attempted.
+    pub async fn dispatch_due(&self) -> Result<usize> {
+        // Long enough that a claimed delivery is not picked up again mid-request
+        let lease = Duration::from_secs(self.config.request_timeout_secs.saturating_mul(2).max(1));
+        let limit = i64::try_from(self.config.batch_size).unwrap_or(i64::MAX);
+        let deliveries = self.database.claim_webhook_deliveries(limit, lease).await?;
+
+        let results =
+            futures::future::join_all(deliveries.iter().map(|delivery| self.deliver(delivery)))
+                .await;
+        for result in results {
+            if let Err(e) = result {
+                warn!("Failed to update webhook delivery state: {}", e);
+            }
+        }
+
+        Ok(deliveries.len())
+    }
+
+    async fn deliver(&self, delivery: &PendingDelivery) -> Result<()> {
+        let subscription_id = delivery.subscription_id;
+        let event_id = delivery.event.id;
+        let body = serde_json::to_vec(&DeliveryBody::new(delivery))?;
+        let delivery_id = format!("{subscription_id}-{event_id}");
+
+        let result = match self.destination_client(&delivery.url).await {
+            Ok(client) => {
+                post_signed(
+                    &client,
+                    &delivery.url,
+                    &delivery.secret,
+                    delivery.event.event.event_type,
+                    &delivery_id,
+                    body,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        let Err(e) = result else {
+            debug!(
+                "🪝 Delivered event {} to subscription {}",
+                event_id, subscription_id
+            );
+            return self
+                .database
+                .complete_webhook_delivery(subscription_id, event_id)
+                .await;
+        };
+
+        let error = e.to_string();
+        let attempts = u32::try_from(delivery.attempts).unwrap_or(0) + 1;
+        if attempts >= self.config.max_attempts {
+            warn!(
+                "☠️  Dead-lettering event {} for subscription {} after {} attempts: {}",
+                event_id, subscription_id, attempts, error
+            );
+            self.database
+                .dead_letter_webhook_delivery(subscription_id, event_id, &error)
+                .await
+        } else {
+            let delay = retry_delay(&self.config, attempts);
+            debug!(
+                "Webhook delivery {} failed (attempt {}), retrying in {:?}: {}",
+                delivery_id, attempts, delay, error
+            );
+            self.database
+                .retry_webhook_delivery(subscription_id, event_id, &error, delay)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+    use crate::tests::spawn_webhook_receiver;
+    use crate::webhooks::verify_signature;
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let config = WebhooksConfig {
+            retry_base_secs: 5,
+            retry_max_secs: 60,
+            ..Default::default()
+        };
+        let delays: Vec<u64> = (1..=6)
+            .map(|attempts| retry_delay(&config, attempts).as_secs())
+            .collect();
+        assert_eq!(delays, vec![5, 10, 20, 40, 60, 60]);
+        assert_eq!(retry_delay(&config, 200).as_secs(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_post_signed_to_local_receiver() {
+        let (url, received) = spawn_webhook_receiver(StatusCode::OK).await;
+        let client = reqwest::Client::new();
+
+        post_signed(
+            &client,
+            &url,
+            "secret",
+            ChangeEventType::LinkAdd,
+            "1-42",
+            br#"{"offset":42}"#.to_vec(),
+        )
+        .await
+        .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let request = &received[0];
+        assert_eq!(request.body, br#"{"offset":42}"#);
+        assert_eq!(request.header(EVENT_HEADER), Some("link_add"));
+        assert_eq!(request.header(DELIVERY_HEADER), Some("1-42"));
+        let signature = request.header(SIGNATURE_HEADER).unwrap();
+        assert!(verify_signature("secret", signature, &request.body).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_post_signed_rejects_error_status() {
+        let (url, received) = spawn_webhook_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+
+        let result = post_signed(
+            &reqwest::Client::new(),
+            &url,
+            "secret",
+            ChangeEventType::CastAdd,
+            "1-1",
+            b"{}".to_vec(),
+        )
+        .await;
+
+        assert!(result.unwrap_err().to_string().contains("500"));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_not_followed() {
+        let (url, received) = spawn_webhook_receiver(StatusCode::TEMPORARY_REDIRECT).await;
+        let client = client_builder(&WebhooksConfig::default()).build().unwrap();
+
+        let result = post_signed(
+            &client,
+            &url,
+            "secret",
+            ChangeEventType::CastAdd,
+            "1-1",
+            b"{}".to_vec(),
+        )
+        .await;
+
+        assert!(result.unwrap_err().to_string().contains("307"));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+}
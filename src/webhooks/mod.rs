@@ -0,0 +1,391 @@
+//! Change-data-capture webhooks
+//!
+//! The messages a sync batch writes are turned into [`ChangeEvent`]s and
+//! matched against the active subscriptions in the batch's transaction.
+//! Matching events are stored in `webhook_events`, whose id is the offset
+//! subscribers replay from, and a delivery is queued per matching
+//! subscription, so every committed message is delivered at least once. The
+//! [`WebhookDispatcher`] sends due deliveries as HMAC-signed POST requests,
+//! retrying with exponential backoff before dead-lettering them. Destinations
+//! are resolved and checked against internal addresses on every delivery, and
+//! redirects are not followed.
+
+pub mod destination;
+pub mod dispatcher;
+pub mod signature;
+
+use std::fmt;
+use std::str::FromStr;
+
+pub use destination::is_internal_address;
+pub use destination::resolve_destination;
+pub use dispatcher::WebhookDispatcher;
+use serde::Deserialize;
+use serde::Serialize;
+pub use signature::sign_payload;
+pub use signature::verify_signature;
+pub use signature::SIGNATURE_HEADER;
+
+use crate::sync::shard_processor::BatchedData;
+
+/// Kind of a synced Farcaster message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ChangeEventType {
+    CastAdd,
+    CastRemove,
+    LinkAdd,
+    LinkRemove,
+    ReactionAdd,
+    ReactionRemove,
+    VerificationAdd,
+    VerificationRemove,
+    UserDataUpdate,
+}
+
+impl ChangeEventType {
+    pub const ALL: [Self; 9] = [
+        Self::CastAdd,
+        Self::CastRemove,
+        Self::LinkAdd,
+        Self::LinkRemove,
+        Self::ReactionAdd,
+        Self::ReactionRemove,
+        Self::VerificationAdd,
+        Self::VerificationRemove,
+        Self::UserDataUpdate,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::CastAdd => "cast_add",
+            Self::CastRemove => "cast_remove",
+            Self::LinkAdd => "link_add",
+            Self::LinkRemove => "link_remove",
+            Self::ReactionAdd => "reaction_add",
+            Self::ReactionRemove => "reaction_remove",
+            Self::VerificationAdd => "verification_add",
+            Self::VerificationRemove => "verification_remove",
+            Self::UserDataUpdate => "user_data_update",
+        }
+    }
+
+    /// Add or remove variant for a stored `event_type` column
+    fn add_or_remove(event_type: &str, add: Self, remove: Self) -> Self {
+        if event_type == "remove" {
+            remove
+        } else {
+            add
+        }
+    }
+}
+
+impl fmt::Display for ChangeEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChangeEventType {
+    type Err = crate::SnapRagError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == s)
+            .ok_or_else(|| {
+                crate::SnapRagError::Custom(format!(
+                    "Unknown event type '{s}' (expected one of: {})",
+                    Self::ALL.map(|event_type| event_type.as_str()).join(", ")
+                ))
+            })
+    }
+}
+
+/// A committed message, as published to webhook subscribers
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ChangeEvent {
+    pub event_type: ChangeEventType,
+    pub fid: i64,
+    /// Followed FID, author of the reacted-to cast or parent cast author
+    pub target_fid: Option<i64>,
+    pub message_hash: Vec<u8>,
+    /// Cast replied to or reacted to
+    pub parent_hash: Option<Vec<u8>>,
+    /// Channel (`parent_url`) of a cast
+    pub channel: Option<String>,
+    pub timestamp: i64,
+    /// Type-specific fields such as the cast text or the changed profile field
+    pub payload: serde_json::Value,
+}
+
+/// Which events a subscription receives; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionFilter {
+    pub event_types: Vec<ChangeEventType>,
+    pub fid: Option<i64>,
+    pub target_fid: Option<i64>,
+    pub parent_hash: Option<Vec<u8>>,
+    pub channel: Option<String>,
+}
+
+impl SubscriptionFilter {
+    #[must_use]
+    pub fn matches(&self, event: &ChangeEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self.fid.is_none_or(|fid| fid == event.fid)
+            && self
+                .target_fid
+                .is_none_or(|fid| Some(fid) == event.target_fid)
+            && self
+                .parent_hash
+                .as_ref()
+                .is_none_or(|hash| Some(hash) == event.parent_hash.as_ref())
+            && self
+                .channel
+                .as_ref()
+                .is_none_or(|channel| Some(channel) == event.channel.as_ref())
+    }
+}
+
+impl ChangeEvent {
+    /// Event without target, parent or channel
+    fn message(
+        event_type: ChangeEventType,
+        fid: i64,
+        message_hash: &[u8],
+        timestamp: i64,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            event_type,
+            fid,
+            target_fid: None,
+            message_hash: message_hash.to_vec(),
+            parent_hash: None,
+            channel: None,
+            timestamp,
+            payload,
+        }
+    }
+}
+
+/// Change events of the messages in a sync batch
+#[must_use]
+pub fn change_events(batched: &BatchedData) -> Vec<ChangeEvent> {
+    let mut events = Vec::new();
+
+    for (
+        fid,
+        text,
+        timestamp,
+        hash,
+        parent_hash,
+        root_hash,
+        embeds,
+        mentions,
+        parent_fid,
+        parent_url,
+        info,
+    ) in &batched.casts
+    {
+        let payload = serde_json::json!({
+            "text": text,
+            "embeds": embeds,
+            "mentions": mentions,
+            "root_hash": root_hash.as_deref().map(hex::encode),
+            "shard_id": info.shard_id,
+            "block_height": info.block_height,
+        });
+        events.push(ChangeEvent {
+            target_fid: *parent_fid,
+            parent_hash: parent_hash.clone(),
+            channel: parent_url.clone(),
+            ..ChangeEvent::message(ChangeEventType::CastAdd, *fid, hash, *timestamp, payload)
+        });
+    }
+
+    for (fid, target_hash, timestamp, hash, _) in &batched.cast_removes {
+        let payload = serde_json::json!({ "target_hash": hex::encode(target_hash) });
+        events.push(ChangeEvent::message(
+            ChangeEventType::CastRemove,
+            *fid,
+            hash,
+            *timestamp,
+            payload,
+        ));
+    }
+
+    for (fid, target_fid, link_type, event_type, timestamp, hash, _) in &batched.links {
+        let event_type = ChangeEventType::add_or_remove(
+            event_type,
+            ChangeEventType::LinkAdd,
+            ChangeEventType::LinkRemove,
+        );
+        let payload = serde_json::json!({ "link_type": link_type });
+        events.push(ChangeEvent {
+            target_fid: Some(*target_fid),
+            ..ChangeEvent::message(event_type, *fid, hash, *timestamp, payload)
+        });
+    }
+
+    for (fid, target_hash, target_fid, reaction_type, event_type, timestamp, hash, _) in
+        &batched.reactions
+    {
+        let event_type = ChangeEventType::add_or_remove(
+            event_type,
+            ChangeEventType::ReactionAdd,
+            ChangeEventType::ReactionRemove,
+        );
+        let payload = serde_json::json!({ "reaction_type": reaction_type });
+        events.push(ChangeEvent {
+            target_fid: *target_fid,
+            parent_hash: Some(target_hash.clone()),
+            ..ChangeEvent::message(event_type, *fid, hash, *timestamp, payload)
+        });
+    }
+
+    for (fid, address, _, _, verification_type, chain_id, event_type, timestamp, hash, _) in
+        &batched.verifications
+    {
+        let event_type = ChangeEventType::add_or_remove(
+            event_type,
+            ChangeEventType::VerificationAdd,
+            ChangeEventType::VerificationRemove,
+        );
+        let payload = serde_json::json!({
+            "address": hex::encode(address),
+            "verification_type": verification_type,
+            "chain_id": chain_id,
+        });
+        events.push(ChangeEvent::message(
+            event_type, *fid, hash, *timestamp, payload,
+        ));
+    }
+
+    for (fid, field, value, timestamp, hash, _) in &batched.profile_updates {
+        let payload = serde_json::json!({ "field": field, "value": value });
+        events.push(ChangeEvent::message(
+            ChangeEventType::UserDataUpdate,
+            *fid,
+            hash,
+            *timestamp,
+            payload,
+        ));
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ShardBlockInfo;
+
+    fn event(event_type: ChangeEventType, fid: i64) -> ChangeEvent {
+        ChangeEvent {
+            event_type,
+            fid,
+            target_fid: Some(2),
+            message_hash: vec![1; 20],
+            parent_hash: Some(vec![9; 20]),
+            channel: Some("https://warpcast.com/~/channel/rust".to_string()),
+            timestamp: 100,
+            payload: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_event_type_round_trip() {
+        for event_type in ChangeEventType::ALL {
+            assert_eq!(
+                event_type.as_str().parse::<ChangeEventType>().unwrap(),
+                event_type
+            );
+        }
+        assert!("cast".parse::<ChangeEventType>().is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let reply = event(ChangeEventType::CastAdd, 1);
+        assert!(SubscriptionFilter::default().matches(&reply));
+
+        let filter = SubscriptionFilter {
+            event_types: vec![ChangeEventType::CastAdd, ChangeEventType::ReactionAdd],
+            parent_hash: Some(vec![9; 20]),
+            ..Default::default()
+        };
+        assert!(filter.matches(&reply));
+        assert!(!filter.matches(&event(ChangeEventType::LinkAdd, 1)));
+        assert!(!filter.matches(&ChangeEvent {
+            parent_hash: None,
+            ..reply.clone()
+        }));
+
+        let followed_us = SubscriptionFilter {
+            event_types: vec![ChangeEventType::LinkAdd],
+            fid: Some(7),
+            target_fid: Some(2),
+            ..Default::default()
+        };
+        assert!(followed_us.matches(&event(ChangeEventType::LinkAdd, 7)));
+        assert!(!followed_us.matches(&event(ChangeEventType::LinkAdd, 8)));
+
+        let channel = SubscriptionFilter {
+            channel: Some("https://warpcast.com/~/channel/base".to_string()),
+            ..Default::default()
+        };
+        assert!(!channel.matches(&reply));
+    }
+
+    #[test]
+    fn test_change_events_from_batch() {
+        let info = ShardBlockInfo::new(1, 10, 7, 0);
+        let mut batched = BatchedData::new();
+        batched.links.push((
+            7,
+            2,
+            "follow".to_string(),
+            "remove".to_string(),
+            100,
+            vec![1; 20],
+            info.clone(),
+        ));
+        batched.reactions.push((
+            7,
+            vec![9; 20],
+            Some(2),
+            1,
+            "add".to_string(),
+            101,
+            vec![2; 20],
+            info.clone(),
+        ));
+        batched.profile_updates.push((
+            7,
+            "bio".to_string(),
+            Some("gm".to_string()),
+            102,
+            vec![3; 20],
+            info,
+        ));
+
+        let events = change_events(&batched);
+        let types: Vec<_> = events.iter().map(|event| event.event_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                ChangeEventType::LinkRemove,
+                ChangeEventType::ReactionAdd,
+                ChangeEventType::UserDataUpdate
+            ]
+        );
+        assert_eq!(events[0].target_fid, Some(2));
+        assert_eq!(events[1].parent_hash, Some(vec![9; 20]));
+        assert_eq!(events[2].payload["field"], "bio");
+        assert_eq!(events[2].payload["value"], "gm");
+    }
+}
@@ -73,6 +73,8 @@ async fn test_message_types_quick() {
         None,
         None,
         None,
+        None,
+        None,
         shard_info.clone(),
     ));
     flush_batched_data(&db, batched)